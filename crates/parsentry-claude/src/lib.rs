@@ -33,6 +33,16 @@ pub enum SessionEvent {
     Complete,
 }
 
+/// Token usage and timing of one transcript.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TranscriptStats {
+    /// Prompt tokens, including cache reads and writes.
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub started_at: Option<String>,
+    pub ended_at: Option<String>,
+}
+
 /// Subagent metadata
 #[derive(Debug, Clone)]
 pub struct SubagentMeta {
@@ -65,6 +75,19 @@ struct JournalEntry {
 #[derive(Deserialize)]
 struct MessageBody {
     content: Option<serde_json::Value>,
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize)]
+struct Usage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    cache_creation_input_tokens: u64,
+    #[serde(default)]
+    cache_read_input_tokens: u64,
 }
 
 #[derive(Deserialize)]
@@ -279,6 +302,86 @@ pub fn extract_surface_id(path: &Path) -> Option<String> {
     None
 }
 
+/// Sum token usage and record the first and last timestamps of a JSONL
+/// transcript.
+pub fn transcript_stats(path: &Path) -> Result<TranscriptStats> {
+    let file = fs::File::open(path).with_context(|| format!("opening {}", path.display()))?;
+    let mut stats = TranscriptStats::default();
+    for line in BufReader::new(file).lines() {
+        let Ok(entry) = serde_json::from_str::<JournalEntry>(&line?) else {
+            continue;
+        };
+        if let Some(ts) = entry.timestamp {
+            stats.started_at.get_or_insert_with(|| ts.clone());
+            stats.ended_at = Some(ts);
+        }
+        if let Some(usage) = entry.message.and_then(|m| m.usage) {
+            stats.input_tokens += usage.input_tokens
+                + usage.cache_creation_input_tokens
+                + usage.cache_read_input_tokens;
+            stats.output_tokens += usage.output_tokens;
+        }
+    }
+    Ok(stats)
+}
+
+/// Find the newest subagent transcript under `project_dir` whose first user
+/// message contains `needle` (e.g. the path of the prompt it was given).
+pub fn find_subagent_transcript(project_dir: &Path, needle: &str) -> Result<Option<PathBuf>> {
+    if !project_dir.exists() {
+        return Ok(None);
+    }
+    let mut best: Option<(std::time::SystemTime, PathBuf)> = None;
+    for session in fs::read_dir(project_dir)? {
+        let Ok(agents) = fs::read_dir(session?.path().join("subagents")) else {
+            continue;
+        };
+        for agent in agents {
+            let path = agent?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl")
+                || !first_user_text(&path).is_some_and(|t| t.contains(needle))
+            {
+                continue;
+            }
+            let modified = fs::metadata(&path)?.modified()?;
+            if best.as_ref().is_none_or(|(newest, _)| modified > *newest) {
+                best = Some((modified, path));
+            }
+        }
+    }
+    Ok(best.map(|(_, path)| path))
+}
+
+fn first_user_text(path: &Path) -> Option<String> {
+    let file = fs::File::open(path).ok()?;
+    for line in BufReader::new(file).lines() {
+        let entry: JournalEntry = match serde_json::from_str(&line.ok()?) {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+        if entry.entry_type != "queue-operation" && entry.entry_type != "user" {
+            continue;
+        }
+        if let Some(content) = entry.content {
+            return Some(content);
+        }
+        match entry.message.and_then(|m| m.content) {
+            Some(serde_json::Value::String(text)) => return Some(text),
+            Some(serde_json::Value::Array(blocks)) => {
+                return Some(
+                    blocks
+                        .iter()
+                        .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                );
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
 // --- Internal helpers ---
 
 fn claude_home() -> Result<PathBuf> {
@@ -465,6 +568,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_transcript_stats_and_lookup() {
+        let project = tempfile::tempdir().unwrap();
+        let agents = project.path().join("session-1").join("subagents");
+        fs::create_dir_all(&agents).unwrap();
+        let path = agents.join("agent-a.jsonl");
+        fs::write(
+            &path,
+            [
+                r#"{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{"content":"Read `/r/S-1/prompt.md` and execute it."}}"#,
+                r#"{"type":"assistant","timestamp":"2026-01-01T00:00:05Z","message":{"content":[],"usage":{"input_tokens":10,"cache_read_input_tokens":90,"output_tokens":7}}}"#,
+                r#"{"type":"assistant","timestamp":"2026-01-01T00:01:00Z","message":{"content":[],"usage":{"input_tokens":5,"output_tokens":3}}}"#,
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+
+        let stats = transcript_stats(&path).unwrap();
+        assert_eq!(stats.input_tokens, 105);
+        assert_eq!(stats.output_tokens, 10);
+        assert_eq!(stats.started_at.as_deref(), Some("2026-01-01T00:00:00Z"));
+        assert_eq!(stats.ended_at.as_deref(), Some("2026-01-01T00:01:00Z"));
+
+        assert_eq!(
+            find_subagent_transcript(project.path(), "/r/S-1/prompt.md").unwrap(),
+            Some(path)
+        );
+        assert_eq!(
+            find_subagent_transcript(project.path(), "/r/S-2/prompt.md").unwrap(),
+            None
+        );
+    }
+
     #[test]
     fn test_short_path() {
         assert_eq!(short_path("/a/b/c/d.rs"), "c/d.rs");
@@ -1,6 +1,8 @@
+use crate::dependencies::{Dependency, parse_dependencies, render_dependencies};
 use crate::file_classifier::FileClassifier;
 use crate::file_discovery::FileDiscovery;
 use crate::language::Language;
+use crate::path_filter::PathFilter;
 use anyhow::Result;
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
@@ -16,8 +18,13 @@ pub struct RepoMetadata {
     pub languages: HashMap<Language, usize>,
     /// Contents of dependency manifests found
     pub dependency_manifests: Vec<ManifestInfo>,
+    /// Dependencies parsed from the manifests
+    pub dependencies: Vec<Dependency>,
     /// Entry point files detected
     pub entry_points: Vec<String>,
+    /// Route handlers, CLI commands and message consumers found by
+    /// framework, one Markdown line each
+    pub framework_entry_points: Vec<String>,
     /// Total number of source files
     pub total_files: usize,
     /// Source URL when collected from a web page (None for local repos)
@@ -44,6 +51,9 @@ const MANIFEST_FILES: &[&str] = &[
     "composer.json",
 ];
 
+/// Most dependencies listed in the threat model prompt.
+const MAX_LISTED_DEPENDENCIES: usize = 100;
+
 const ENTRY_POINT_PATTERNS: &[&str] = &[
     "main.py",
     "app.py",
@@ -66,7 +76,12 @@ const ENTRY_POINT_PATTERNS: &[&str] = &[
 impl RepoMetadata {
     /// Collect metadata from the given repository root.
     pub fn collect(root_dir: &Path) -> Result<Self> {
-        let discovery = FileDiscovery::new(root_dir.to_path_buf());
+        Self::collect_filtered(root_dir, &PathFilter::default())
+    }
+
+    /// Collect metadata, counting only files allowed by `filter`.
+    pub fn collect_filtered(root_dir: &Path, filter: &PathFilter) -> Result<Self> {
+        let discovery = FileDiscovery::new(root_dir.to_path_buf()).with_filter(filter.clone());
         let files = discovery.get_files()?;
 
         let mut languages: HashMap<Language, usize> = HashMap::new();
@@ -82,6 +97,10 @@ impl RepoMetadata {
         let directory_tree = build_directory_tree(root_dir, 3)?;
 
         let dependency_manifests = collect_manifests(root_dir)?;
+        let dependencies = dependency_manifests
+            .iter()
+            .flat_map(parse_dependencies)
+            .collect();
 
         let entry_points = detect_entry_points(root_dir, &files);
 
@@ -90,7 +109,9 @@ impl RepoMetadata {
             directory_tree,
             languages,
             dependency_manifests,
+            dependencies,
             entry_points,
+            framework_entry_points: Vec::new(),
             total_files: files.len(),
             source_url: None,
         })
@@ -112,6 +133,15 @@ impl RepoMetadata {
         self.total_files = files.len();
     }
 
+    /// Record a framework entry point in `file`, which becomes an entry
+    /// point file.
+    pub fn add_framework_entry_point(&mut self, file: &str, description: String) {
+        if !self.entry_points.iter().any(|f| f == file) {
+            self.entry_points.push(file.to_string());
+        }
+        self.framework_entry_points.push(description);
+    }
+
     /// Render metadata as a compact string for LLM consumption.
     pub fn to_prompt_context(&self) -> String {
         let mut ctx = String::new();
@@ -133,6 +163,13 @@ impl RepoMetadata {
         }
         ctx.push('\n');
 
+        if !self.dependencies.is_empty() {
+            ctx.push_str("## Known Dependencies and Versions\n");
+            let deps: Vec<&Dependency> = self.dependencies.iter().collect();
+            ctx.push_str(&render_dependencies(&deps, MAX_LISTED_DEPENDENCIES));
+            ctx.push('\n');
+        }
+
         if !self.dependency_manifests.is_empty() {
             ctx.push_str("## Dependencies\n");
             for manifest in &self.dependency_manifests {
@@ -156,6 +193,14 @@ impl RepoMetadata {
             ctx.push('\n');
         }
 
+        if !self.framework_entry_points.is_empty() {
+            ctx.push_str("## Externally Reachable Handlers\n");
+            for handler in &self.framework_entry_points {
+                ctx.push_str(&format!("- {}\n", handler));
+            }
+            ctx.push('\n');
+        }
+
         ctx.push_str(&format!("Total source files: {}\n", self.total_files));
 
         ctx
@@ -279,6 +324,11 @@ mod tests {
         assert!(meta.languages.contains_key(&Language::Python));
         assert_eq!(meta.dependency_manifests.len(), 1);
         assert!(meta.dependency_manifests[0].content.contains("flask"));
+        assert_eq!(meta.dependencies.len(), 2);
+        assert!(
+            meta.to_prompt_context()
+                .contains("## Known Dependencies and Versions\n- requirements.txt: flask ==3.0, sqlalchemy ==2.0\n")
+        );
     }
 
     #[test]
@@ -398,7 +448,9 @@ mod tests {
                 path: "package.json".to_string(),
                 content: long_content.clone(),
             }],
+            dependencies: vec![],
             entry_points: vec![],
+            framework_entry_points: vec![],
             total_files: 1,
             source_url: None,
         };
@@ -415,7 +467,9 @@ mod tests {
             directory_tree: String::new(),
             languages: HashMap::new(),
             dependency_manifests: vec![],
+            dependencies: vec![],
             entry_points: vec!["src/main.py".to_string()],
+            framework_entry_points: vec![],
             total_files: 1,
             source_url: None,
         };
@@ -424,6 +478,26 @@ mod tests {
         assert!(ctx.contains("- src/main.py"));
     }
 
+    #[test]
+    fn test_framework_entry_points_mark_their_files() {
+        let mut meta = RepoMetadata {
+            root_dir: PathBuf::from("/tmp/test"),
+            directory_tree: String::new(),
+            languages: HashMap::new(),
+            dependency_manifests: vec![],
+            dependencies: vec![],
+            entry_points: vec!["src/main.py".to_string()],
+            framework_entry_points: vec![],
+            total_files: 2,
+            source_url: None,
+        };
+        meta.add_framework_entry_point("src/main.py", "`main` at `src/main.py:1`".into());
+        meta.add_framework_entry_point("src/views.py", "`list_users` at `src/views.py:4`".into());
+        assert_eq!(meta.entry_points, vec!["src/main.py", "src/views.py"]);
+        let ctx = meta.to_prompt_context();
+        assert!(ctx.contains("## Externally Reachable Handlers\n- `main` at `src/main.py:1`\n"));
+    }
+
     #[test]
     fn test_prompt_context_no_entry_points_when_empty() {
         let meta = RepoMetadata {
@@ -431,7 +505,9 @@ mod tests {
             directory_tree: String::new(),
             languages: HashMap::new(),
             dependency_manifests: vec![],
+            dependencies: vec![],
             entry_points: vec![],
+            framework_entry_points: vec![],
             total_files: 0,
             source_url: None,
         };
@@ -446,7 +522,9 @@ mod tests {
             directory_tree: String::new(),
             languages: HashMap::new(),
             dependency_manifests: vec![],
+            dependencies: vec![],
             entry_points: vec![],
+            framework_entry_points: vec![],
             total_files: 0,
             source_url: None,
         };
@@ -461,7 +539,9 @@ mod tests {
             directory_tree: String::new(),
             languages: HashMap::new(),
             dependency_manifests: vec![],
+            dependencies: vec![],
             entry_points: vec![],
+            framework_entry_points: vec![],
             total_files: 42,
             source_url: None,
         };
@@ -497,7 +577,9 @@ mod tests {
                 path: "req.txt".to_string(),
                 content: manifest_2000.clone(),
             }],
+            dependencies: vec![],
             entry_points: vec![],
+            framework_entry_points: vec![],
             total_files: 0,
             source_url: None,
         };
@@ -517,7 +599,9 @@ mod tests {
                 path: "req.txt".to_string(),
                 content: manifest_2001.clone(),
             }],
+            dependencies: vec![],
             entry_points: vec![],
+            framework_entry_points: vec![],
             total_files: 0,
             source_url: None,
         };
@@ -0,0 +1,319 @@
+//! Content sniffing of discovered files.
+//!
+//! An extension does not say what a file holds: a `.js` file may be a
+//! bundle on a single line, a `.h` file a compiled artifact. Discovery
+//! reads the first bytes of each file and leaves out binaries and minified
+//! code, which a model cannot usefully read, counting them instead.
+//!
+//! Generated code (build output with a `sourceMappingURL`, files marked
+//! `@generated` or `DO NOT EDIT`) is left out too unless
+//! `--include-generated` is set; a bundle whose source map points at files
+//! in the repository is read as those files instead.
+
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use regex::Regex;
+
+/// Bytes read from the start of a file to sniff it, as many as git reads
+/// to tell binary files apart.
+pub const SNIFF_BYTES: usize = 8000;
+
+/// A line at least this long is only written by a minifier or generator.
+const MINIFIED_LINE: usize = 1000;
+/// Average line length above which a file counts as minified.
+const MINIFIED_AVERAGE_LINE: usize = 300;
+/// Share of whitespace below which code counts as minified; hand-written
+/// code has several times more.
+const MINIFIED_WHITESPACE: f64 = 0.04;
+/// Bytes read from the end of a file for its `sourceMappingURL` comment.
+const TAIL_BYTES: u64 = 512;
+/// Lines at the start of a file searched for a generated-code marker.
+const MARKER_LINES: usize = 5;
+
+static INCLUDE_GENERATED: AtomicBool = AtomicBool::new(false);
+
+static GENERATED_MARKER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)@generated|do not edit|auto-?generated|automatically generated|code generated by",
+    )
+    .expect("valid regex")
+});
+static SOURCE_MAPPING_URL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[#@]\s*sourceMappingURL\s*=\s*([^\s*]+)").expect("valid regex"));
+
+/// Keep minified and generated files for the rest of the process
+/// (`--include-generated`).
+pub fn set_include_generated(include: bool) {
+    INCLUDE_GENERATED.store(include, Ordering::Relaxed);
+}
+
+pub fn includes_generated() -> bool {
+    INCLUDE_GENERATED.load(Ordering::Relaxed)
+}
+
+/// Whether files of `content` are left out of analysis.
+pub fn is_skipped(content: ContentType) -> bool {
+    match content {
+        ContentType::Text => false,
+        ContentType::Binary => true,
+        ContentType::Minified | ContentType::Generated => !includes_generated(),
+    }
+}
+
+/// What a file holds, judged by its first [`SNIFF_BYTES`] bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ContentType {
+    Text,
+    Binary,
+    /// Text on a few very long lines or without whitespace: minified
+    /// code.
+    Minified,
+    /// Readable text produced by a compiler or code generator.
+    Generated,
+}
+
+impl ContentType {
+    pub fn label(self) -> &'static str {
+        match self {
+            ContentType::Text => "text",
+            ContentType::Binary => "binary",
+            ContentType::Minified => "minified",
+            ContentType::Generated => "generated",
+        }
+    }
+}
+
+/// Sniff the file at `path`. Unreadable files count as text, to be
+/// reported by whoever reads them.
+pub fn sniff_file(path: &Path) -> ContentType {
+    let head = read_head(path);
+    match sniff(&head) {
+        // The source map comment closes the file
+        ContentType::Text
+            if head.len() == SNIFF_BYTES && source_mapping_url(&read_tail(path)).is_some() =>
+        {
+            ContentType::Generated
+        }
+        content => content,
+    }
+}
+
+/// Sniff the first bytes of a file.
+pub fn sniff(head: &[u8]) -> ContentType {
+    if head.is_empty() {
+        return ContentType::Text;
+    }
+    if head.contains(&0) || executable_format(head).is_some() {
+        return ContentType::Binary;
+    }
+    let text = match std::str::from_utf8(head) {
+        Ok(text) => text,
+        // A multi-byte character cut at the end of the head is still text
+        Err(e) if e.error_len().is_none() => {
+            std::str::from_utf8(&head[..e.valid_up_to()]).unwrap_or_default()
+        }
+        Err(_) => return ContentType::Binary,
+    };
+    let control = text
+        .bytes()
+        .filter(|b| b.is_ascii_control() && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b))
+        .count();
+    if control * 10 > text.len() {
+        return ContentType::Binary;
+    }
+    let lines = text.lines().count().max(1);
+    let longest = text.lines().map(str::len).max().unwrap_or(0);
+    let whitespace = text.bytes().filter(u8::is_ascii_whitespace).count();
+    if longest >= MINIFIED_LINE
+        || (lines > 1 && text.len() / lines > MINIFIED_AVERAGE_LINE)
+        || (text.len() >= 2 * MINIFIED_LINE
+            && (whitespace as f64) < MINIFIED_WHITESPACE * text.len() as f64)
+    {
+        return ContentType::Minified;
+    }
+    let marked = text
+        .lines()
+        .take(MARKER_LINES)
+        .any(|line| GENERATED_MARKER.is_match(line));
+    if marked || source_mapping_url(head).is_some() {
+        return ContentType::Generated;
+    }
+    ContentType::Text
+}
+
+/// Target of the last `sourceMappingURL` comment in `bytes`.
+fn source_mapping_url(bytes: &[u8]) -> Option<String> {
+    let text = String::from_utf8_lossy(bytes);
+    SOURCE_MAPPING_URL
+        .captures_iter(&text)
+        .last()
+        .map(|c| c[1].to_string())
+}
+
+/// Original files in the repository that the source map of the generated
+/// file at `path` was built from; none without a map file or when its
+/// sources are not under `root`.
+pub fn source_map_sources(root: &Path, path: &Path) -> Vec<PathBuf> {
+    let (Ok(canonical_root), Some(dir)) = (root.canonicalize(), path.parent()) else {
+        return Vec::new();
+    };
+    let url = source_mapping_url(&read_tail(path)).or_else(|| source_mapping_url(&read_head(path)));
+    let map_path = match url {
+        // Inline maps carry no file names worth resolving
+        Some(url) if url.starts_with("data:") => return Vec::new(),
+        Some(url) => dir.join(url.split(['?', '#']).next().unwrap_or_default()),
+        None => PathBuf::from(format!("{}.map", path.display())),
+    };
+    let Some(map) = std::fs::read_to_string(&map_path)
+        .ok()
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+    else {
+        return Vec::new();
+    };
+    let map_dir = map_path.parent().unwrap_or(dir);
+    let source_root = map["sourceRoot"].as_str().unwrap_or_default();
+    let mut sources = Vec::new();
+    for source in map["sources"].as_array().into_iter().flatten() {
+        let Some(source) = source.as_str() else {
+            continue;
+        };
+        // `webpack://app/./src/index.ts` names `./src/index.ts`
+        let source = match source.split_once("://") {
+            Some((_, rest)) => rest.split_once('/').map_or(rest, |(_, path)| path),
+            None => source,
+        };
+        let source = source.trim_start_matches('/');
+        let candidates = [map_dir.join(source_root).join(source), root.join(source)];
+        let Some(original) = candidates.iter().find_map(|candidate| {
+            candidate
+                .canonicalize()
+                .ok()
+                .filter(|c| c.is_file() && c.starts_with(&canonical_root))
+        }) else {
+            continue;
+        };
+        if original
+            .components()
+            .any(|c| c.as_os_str() == "node_modules")
+        {
+            continue;
+        }
+        let original = root.join(original.strip_prefix(&canonical_root).unwrap_or(&original));
+        if !sources.contains(&original) {
+            sources.push(original);
+        }
+    }
+    sources
+}
+
+/// Name of the executable or compiled-code format `head` starts with.
+pub fn executable_format(head: &[u8]) -> Option<&'static str> {
+    const FORMATS: &[(&[u8], &str)] = &[
+        (b"\x7fELF", "ELF executable or shared library"),
+        (b"MZ", "Windows PE executable or DLL"),
+        (b"\xcf\xfa\xed\xfe", "Mach-O executable or dylib"),
+        (b"\xce\xfa\xed\xfe", "Mach-O executable or dylib"),
+        (b"\xca\xfe\xba\xbe", "Java class or Mach-O universal binary"),
+        (b"\0asm", "WebAssembly module"),
+        (b"!<arch>\n", "static library"),
+    ];
+    FORMATS
+        .iter()
+        .find(|(magic, _)| head.starts_with(magic))
+        .map(|(_, format)| *format)
+}
+
+/// Up to [`SNIFF_BYTES`] bytes from the start of `path`.
+pub fn read_head(path: &Path) -> Vec<u8> {
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    if let Ok(file) = std::fs::File::open(path) {
+        let _ = file.take(SNIFF_BYTES as u64).read_to_end(&mut head);
+    }
+    head
+}
+
+/// Up to [`TAIL_BYTES`] bytes from the end of `path`.
+fn read_tail(path: &Path) -> Vec<u8> {
+    let mut tail = Vec::new();
+    if let Ok(mut file) = std::fs::File::open(path) {
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        if file
+            .seek(SeekFrom::Start(len.saturating_sub(TAIL_BYTES)))
+            .is_ok()
+        {
+            let _ = file.read_to_end(&mut tail);
+        }
+    }
+    tail
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tells_text_binary_and_minified_apart() {
+        assert_eq!(sniff(b""), ContentType::Text);
+        assert_eq!(sniff(b"def main():\n    pass\n"), ContentType::Text);
+        assert_eq!(sniff("// コメント\n".as_bytes()), ContentType::Text);
+        // A character cut by the sniffing limit
+        assert_eq!(sniff(&"é".as_bytes()[..1]), ContentType::Text);
+
+        assert_eq!(sniff(b"\x7fELF\x02\x01\x01"), ContentType::Binary);
+        assert_eq!(sniff(b"PK\x03\x04\x14\0\0"), ContentType::Binary);
+        assert_eq!(sniff(b"\xff\xd8\xff\xe0 JFIF"), ContentType::Binary);
+        assert_eq!(
+            executable_format(b"MZ\x90\0"),
+            Some("Windows PE executable or DLL")
+        );
+        assert_eq!(executable_format(b"#!/bin/sh"), None);
+
+        let bundle = format!("!function(e){{{}}}(window);\n", "var a=1;".repeat(200));
+        assert_eq!(sniff(bundle.as_bytes()), ContentType::Minified);
+        let wide = format!("{}\n", "x".repeat(400)).repeat(5);
+        assert_eq!(sniff(wide.as_bytes()), ContentType::Minified);
+        let dense = "a=b+c;".repeat(50) + "\n";
+        assert_eq!(sniff(dense.repeat(10).as_bytes()), ContentType::Minified);
+
+        assert_eq!(
+            sniff(b"// Code generated by protoc-gen-go. DO NOT EDIT.\npackage pb\n"),
+            ContentType::Generated
+        );
+        assert_eq!(
+            sniff(b"\"use strict\";\nexports.a = 1;\n//# sourceMappingURL=index.js.map\n"),
+            ContentType::Generated
+        );
+    }
+
+    #[test]
+    fn resolves_source_maps_to_files_in_the_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for (name, content) in [
+            ("src/index.ts", "export const a = 1;\n"),
+            ("src/util.ts", "export const b = 2;\n"),
+            (
+                "dist/index.js",
+                "exports.a=1;\n//# sourceMappingURL=index.js.map\n",
+            ),
+            (
+                "dist/index.js.map",
+                r#"{"version":3,"sources":["../src/index.ts","webpack://app/./src/util.ts","../node_modules/x/y.js","../src/gone.ts"]}"#,
+            ),
+            ("dist/other.js", "exports.b=2;\n"),
+        ] {
+            let path = root.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+
+        assert_eq!(
+            source_map_sources(root, &root.join("dist/index.js")),
+            vec![root.join("src/index.ts"), root.join("src/util.ts")]
+        );
+        assert!(source_map_sources(root, &root.join("dist/other.js")).is_empty());
+    }
+}
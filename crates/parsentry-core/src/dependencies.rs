@@ -0,0 +1,227 @@
+//! Dependencies and versions declared in manifests.
+//!
+//! A condensed list lets the model reason about framework versions, e.g.
+//! whether a template engine escapes by default, without reading whole
+//! manifests. Only `package.json`, `Cargo.toml` and `requirements.txt` are
+//! parsed; other manifests stay in the prompt as raw text.
+
+use serde::{Deserialize, Serialize};
+
+use crate::collector::ManifestInfo;
+use crate::language::Language;
+
+/// Reports directory file recording the scanned repository's dependencies.
+pub const DEPENDENCIES_FILE: &str = "dependencies.json";
+
+/// A dependency declared in a manifest.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Dependency {
+    pub name: String,
+    /// Version requirement as written, e.g. `^4.18` or `==3.0.2`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Manifest path relative to the repository root.
+    pub manifest: String,
+    /// Only needed to develop or test the repository.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dev: bool,
+}
+
+impl Dependency {
+    /// Languages of the code that uses the dependency.
+    pub fn languages(&self) -> &'static [Language] {
+        match self.manifest.rsplit('/').next().unwrap_or_default() {
+            "package.json" => &[Language::JavaScript, Language::TypeScript],
+            "Cargo.toml" => &[Language::Rust],
+            "requirements.txt" => &[Language::Python],
+            _ => &[],
+        }
+    }
+}
+
+/// Dependencies declared in `manifest`, in manifest order; none when its
+/// format is not supported or it does not parse.
+pub fn parse_dependencies(manifest: &ManifestInfo) -> Vec<Dependency> {
+    let dependency = |name: &str, version: Option<&str>, dev: bool| Dependency {
+        name: name.to_string(),
+        version: version
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty()),
+        manifest: manifest.path.clone(),
+        dev,
+    };
+    match manifest.path.rsplit('/').next().unwrap_or_default() {
+        "package.json" => {
+            let Ok(json) = serde_json::from_str::<serde_json::Value>(&manifest.content) else {
+                return Vec::new();
+            };
+            [
+                ("dependencies", false),
+                ("peerDependencies", false),
+                ("devDependencies", true),
+            ]
+            .into_iter()
+            .flat_map(|(key, dev)| {
+                json[key]
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .map(move |(name, version)| dependency(name, version.as_str(), dev))
+            })
+            .collect()
+        }
+        "requirements.txt" => manifest
+            .content
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default().trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('-'))
+            .map(|line| {
+                let line = line.split(';').next().unwrap_or_default();
+                let split = line
+                    .find(|c: char| "=<>!~ [".contains(c))
+                    .unwrap_or(line.len());
+                let version = line[split..].trim_start_matches('[');
+                let version = version.split(']').next_back().unwrap_or_default();
+                dependency(line[..split].trim(), Some(version), false)
+            })
+            .collect(),
+        "Cargo.toml" => {
+            let mut deps = Vec::new();
+            let mut section: Option<bool> = None;
+            for line in manifest.content.lines() {
+                let line = line.split('#').next().unwrap_or_default().trim();
+                if line.starts_with('[') {
+                    let table = line.trim_matches(|c| c == '[' || c == ']');
+                    let table = table.rsplit('.').next().unwrap_or_default();
+                    section = match table {
+                        "dependencies" | "build-dependencies" => Some(false),
+                        "dev-dependencies" => Some(true),
+                        _ => None,
+                    };
+                    continue;
+                }
+                let (Some(dev), Some((name, value))) = (section, line.split_once('=')) else {
+                    continue;
+                };
+                let value = value.trim();
+                let version = if value.starts_with('"') {
+                    Some(value.trim_matches('"'))
+                } else {
+                    value
+                        .split_once("version")
+                        .and_then(|(_, rest)| rest.split('"').nth(1))
+                };
+                deps.push(dependency(name.trim(), version, dev));
+            }
+            deps
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// One line per manifest, `name version` pairs separated by commas, at
+/// most `limit` dependencies with runtime ones first.
+pub fn render_dependencies(deps: &[&Dependency], limit: usize) -> String {
+    let mut sorted: Vec<&&Dependency> = deps.iter().collect();
+    sorted.sort_by_key(|d| d.dev);
+    let mut manifests: Vec<(&str, Vec<String>)> = Vec::new();
+    for dep in sorted.iter().take(limit) {
+        let entry = match &dep.version {
+            Some(version) => format!("{} {}", dep.name, version),
+            None => dep.name.clone(),
+        };
+        let entry = if dep.dev {
+            format!("{entry} (dev)")
+        } else {
+            entry
+        };
+        match manifests.iter_mut().find(|(m, _)| *m == dep.manifest) {
+            Some((_, entries)) => entries.push(entry),
+            None => manifests.push((&dep.manifest, vec![entry])),
+        }
+    }
+    let mut out: String = manifests
+        .iter()
+        .map(|(manifest, entries)| format!("- {manifest}: {}\n", entries.join(", ")))
+        .collect();
+    if deps.len() > limit {
+        out.push_str(&format!("- … and {} more\n", deps.len() - limit));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(path: &str, content: &str) -> ManifestInfo {
+        ManifestInfo {
+            path: path.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    fn pairs(manifest: &ManifestInfo) -> Vec<(String, Option<String>, bool)> {
+        parse_dependencies(manifest)
+            .into_iter()
+            .map(|d| (d.name, d.version, d.dev))
+            .collect()
+    }
+
+    #[test]
+    fn parses_supported_manifests() {
+        let package = manifest(
+            "package.json",
+            r#"{"dependencies": {"express": "^4.18.2"}, "devDependencies": {"jest": "29"}}"#,
+        );
+        assert_eq!(
+            pairs(&package),
+            vec![
+                ("express".into(), Some("^4.18.2".into()), false),
+                ("jest".into(), Some("29".into()), true),
+            ]
+        );
+
+        let requirements = manifest(
+            "requirements.txt",
+            "# web\nJinja2==2.10  # pinned\nuvicorn[standard]>=0.20\nrequests\n-r dev.txt\n",
+        );
+        assert_eq!(
+            pairs(&requirements),
+            vec![
+                ("Jinja2".into(), Some("==2.10".into()), false),
+                ("uvicorn".into(), Some(">=0.20".into()), false),
+                ("requests".into(), None, false),
+            ]
+        );
+
+        let cargo = manifest(
+            "Cargo.toml",
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\naxum = \"0.7\"\n\
+             serde = { version = \"1\", features = [\"derive\"] }\nlocal = { path = \"../local\" }\n\n\
+             [dev-dependencies]\ntempfile = \"3\"\n",
+        );
+        assert_eq!(
+            pairs(&cargo),
+            vec![
+                ("axum".into(), Some("0.7".into()), false),
+                ("serde".into(), Some("1".into()), false),
+                ("local".into(), None, false),
+                ("tempfile".into(), Some("3".into()), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_runtime_dependencies_first() {
+        let deps = parse_dependencies(&manifest(
+            "package.json",
+            r#"{"devDependencies": {"jest": "29"}, "dependencies": {"ejs": "3.1.6", "react": "18"}}"#,
+        ));
+        let deps: Vec<&Dependency> = deps.iter().collect();
+        assert_eq!(
+            render_dependencies(&deps, 2),
+            "- package.json: ejs 3.1.6, react 18\n- … and 1 more\n"
+        );
+    }
+}
@@ -1,10 +1,155 @@
+use anyhow::{Context, Result, bail};
+use globset::{GlobBuilder, GlobMatcher};
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
 use crate::language::Language;
 
+/// Comma-separated `glob=language` pairs that override detection, e.g.
+/// `bin/*=bash,*.cgi=python`.
+pub const LANGUAGE_OVERRIDES_ENV: &str = "PARSENTRY_LANGUAGE_OVERRIDES";
+
+/// Extensions of templates whose language is that of the name without it,
+/// e.g. `deploy.sh.j2`.
+const TEMPLATE_EXTENSIONS: &[&str] = &[
+    "j2", "jinja", "jinja2", "tmpl", "tpl", "template", "in", "erb", "dist", "example",
+];
+
+/// Bytes read from a file to detect its language from content.
+const HEAD_BYTES: u64 = 1024;
+
+/// Languages forced on paths matching user globs.
+#[derive(Debug, Clone, Default)]
+pub struct LanguageOverrides {
+    rules: Vec<(GlobMatcher, Language)>,
+}
+
+impl LanguageOverrides {
+    /// Parse comma-separated `glob=language` pairs.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut rules = Vec::new();
+        for pair in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let Some((pattern, language)) = pair.rsplit_once('=') else {
+                bail!("invalid language override {pair:?}: expected GLOB=LANGUAGE");
+            };
+            let glob = GlobBuilder::new(pattern.trim().trim_start_matches("./"))
+                .literal_separator(true)
+                .build()
+                .with_context(|| format!("invalid glob: {pattern}"))?;
+            let language = Language::from_str(language.trim()).map_err(anyhow::Error::msg)?;
+            rules.push((glob.compile_matcher(), language));
+        }
+        Ok(Self { rules })
+    }
+
+    /// Overrides from [`LANGUAGE_OVERRIDES_ENV`]; none when it is unset or
+    /// invalid.
+    pub fn from_env() -> Self {
+        std::env::var(LANGUAGE_OVERRIDES_ENV)
+            .ok()
+            .and_then(|spec| Self::parse(&spec).ok())
+            .unwrap_or_default()
+    }
+
+    /// Language of the first rule matching `path` or one of its suffixes,
+    /// so rules relative to the repository root also match absolute paths.
+    pub fn get(&self, path: &str) -> Option<Language> {
+        let path = path.trim_start_matches("./");
+        let suffixes =
+            std::iter::once(path).chain(path.match_indices('/').map(|(i, _)| &path[i + 1..]));
+        suffixes.into_iter().find_map(|suffix| {
+            self.rules
+                .iter()
+                .find(|(glob, _)| glob.is_match(suffix))
+                .map(|(_, language)| *language)
+        })
+    }
+}
+
+/// Pipeline and deployment configuration analyzed with dedicated patterns
+/// and prompt guidance rather than as plain YAML.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ConfigKind {
+    GitHubActions,
+    GitLabCi,
+    Helm,
+}
+
+impl ConfigKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            ConfigKind::GitHubActions => "GitHub Actions workflow",
+            ConfigKind::GitLabCi => "GitLab CI pipeline",
+            ConfigKind::Helm => "Helm chart",
+        }
+    }
+}
+
+static OVERRIDES: LazyLock<LanguageOverrides> = LazyLock::new(LanguageOverrides::from_env);
+
 pub struct FileClassifier;
 
 impl FileClassifier {
+    /// Classify the file at `path`, reading its first bytes when the name
+    /// alone does not tell.
+    pub fn classify_file(path: &Path) -> Language {
+        let filename = path.to_string_lossy();
+        match Self::classify(&filename, "") {
+            Language::Other => Self::classify(&filename, &read_head(path)),
+            language => language,
+        }
+    }
+
+    /// Language of a file that extension-based discovery misses: one with
+    /// a language override, a script without an extension, or a template.
+    /// `None` for files with other extensions or no detectable language.
+    pub fn script_language(path: &Path) -> Option<Language> {
+        let filename = path.to_string_lossy();
+        if let Some(language) = OVERRIDES.get(&filename) {
+            return Some(language);
+        }
+        if path.extension().is_some_and(|ext| {
+            !TEMPLATE_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str())
+        }) {
+            return None;
+        }
+        match Self::classify_untyped(&filename, &read_head(path)) {
+            Language::Other => None,
+            language => Some(language),
+        }
+    }
+
+    /// Kind of pipeline or deployment configuration at `path`, from its
+    /// location: workflows under `.github/workflows`, `.gitlab-ci.yml` and
+    /// files it includes from `.gitlab/ci`, and YAML files of a directory
+    /// with a `Chart.yaml`.
+    pub fn config_kind(path: &Path) -> Option<ConfigKind> {
+        let filename = path.to_string_lossy().replace('\\', "/");
+        let is_yaml = filename.ends_with(".yml") || filename.ends_with(".yaml");
+        if filename.contains(".github/workflows/") && is_yaml {
+            return Some(ConfigKind::GitHubActions);
+        }
+        if filename.ends_with(".gitlab-ci.yml") || (filename.contains(".gitlab/ci/") && is_yaml) {
+            return Some(ConfigKind::GitLabCi);
+        }
+        let in_chart = path
+            .ancestors()
+            .skip(1)
+            .any(|dir| dir.join("Chart.yaml").is_file());
+        if (is_yaml || filename.ends_with(".tpl")) && in_chart {
+            return Some(ConfigKind::Helm);
+        }
+        None
+    }
+
     /// Classifies a file based on filename and content
     pub fn classify(filename: &str, content: &str) -> Language {
+        if let Some(language) = OVERRIDES.get(filename) {
+            return language;
+        }
+
         // CI/CD platform detection
         if Self::is_gitlab_ci(filename, content) {
             return Language::Yaml;
@@ -40,7 +185,65 @@ impl FileClassifier {
         }
 
         // Fall back to extension-based detection
-        Language::from_filename(filename)
+        match Language::from_filename(filename) {
+            Language::Other => Self::classify_untyped(filename, content),
+            language => language,
+        }
+    }
+
+    /// Language of a file without a known extension: that of a template's
+    /// name without its template extension, else from the content.
+    fn classify_untyped(filename: &str, content: &str) -> Language {
+        let path = Path::new(filename);
+        if let Some(ext) = path.extension().and_then(|e| e.to_str())
+            && TEMPLATE_EXTENSIONS.contains(&ext.to_lowercase().as_str())
+        {
+            let inner = Language::from_filename(&path.with_extension("").to_string_lossy());
+            if inner != Language::Other {
+                return inner;
+            }
+        }
+        Self::detect_from_content(content)
+    }
+
+    /// Language named by a shebang, a Vim or Emacs modeline, or telltale
+    /// content; `Other` when none does.
+    pub fn detect_from_content(content: &str) -> Language {
+        let first = content.lines().next().unwrap_or_default();
+        if let Some(interpreter) = first.strip_prefix("#!") {
+            let mut words = interpreter.split_whitespace();
+            let mut program = words.next().unwrap_or_default().rsplit('/').next();
+            if program == Some("env") {
+                program = words.find(|w| !w.starts_with('-'));
+            }
+            let language = program.map_or(Language::Other, interpreter_language);
+            if language != Language::Other {
+                return language;
+            }
+        }
+
+        let lines: Vec<&str> = content.lines().collect();
+        let edges = lines
+            .iter()
+            .take(5)
+            .chain(lines.iter().skip(lines.len().saturating_sub(5)));
+        for line in edges {
+            if let Some(language) = modeline_language(line) {
+                return language;
+            }
+        }
+
+        if content.trim_start().starts_with("<?php") {
+            return Language::Php;
+        }
+        let first_code = lines
+            .iter()
+            .map(|l| l.trim())
+            .find(|l| !l.is_empty() && !l.starts_with('#'));
+        if first_code.is_some_and(|l| l.starts_with("set -e") || l.starts_with("set -o errexit")) {
+            return Language::Bash;
+        }
+        Language::Other
     }
 
     fn is_github_actions_workflow(filename: &str, content: &str) -> bool {
@@ -175,10 +378,158 @@ impl FileClassifier {
     }
 }
 
+/// Language run by a shebang's interpreter, e.g. `python3` or `bash`.
+fn interpreter_language(program: &str) -> Language {
+    let name = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    match name {
+        "python" | "pypy" => Language::Python,
+        "node" | "nodejs" | "deno" | "bun" => Language::JavaScript,
+        "ts-node" | "tsx" => Language::TypeScript,
+        "ruby" => Language::Ruby,
+        "php" => Language::Php,
+        // Like `.sh` files, parsed with the Bash grammar
+        "bash" | "sh" | "dash" | "ksh" | "zsh" | "ash" => Language::Bash,
+        _ => Language::Other,
+    }
+}
+
+/// Language set by a Vim (`vim: set ft=sh:`) or Emacs (`-*- mode: ruby -*-`)
+/// modeline.
+fn modeline_language(line: &str) -> Option<Language> {
+    let name = if let Some((_, rest)) = line.split_once("-*-") {
+        let inner = rest.split("-*-").next()?.trim();
+        let mode = inner
+            .split(';')
+            .find_map(|part| part.trim().strip_prefix("mode:"))
+            .unwrap_or(if inner.contains(':') { "" } else { inner });
+        mode.trim()
+    } else {
+        let (_, rest) = line.split_once("vim:").or_else(|| line.split_once("vi:"))?;
+        rest.split(|c: char| c.is_whitespace() || c == ':')
+            .find_map(|opt| {
+                opt.strip_prefix("ft=")
+                    .or_else(|| opt.strip_prefix("filetype="))
+            })?
+    };
+    match interpreter_language(&name.to_lowercase()) {
+        Language::Other => Language::from_str(name)
+            .ok()
+            .filter(|l| *l != Language::Other),
+        language => Some(language),
+    }
+}
+
+/// Up to [`HEAD_BYTES`] of the file at `path`, lossily decoded.
+fn read_head(path: &Path) -> String {
+    let mut head = Vec::new();
+    if let Ok(file) = std::fs::File::open(path) {
+        let _ = file.take(HEAD_BYTES).read_to_end(&mut head);
+    }
+    String::from_utf8_lossy(&head).into_owned()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn detects_scripts_from_content() {
+        let detect = FileClassifier::detect_from_content;
+        assert_eq!(detect("#!/usr/bin/env python3\nprint(1)"), Language::Python);
+        assert_eq!(detect("#!/bin/bash -eu\necho hi"), Language::Bash);
+        assert_eq!(
+            detect("#!/usr/bin/env -S node --no-warnings\n"),
+            Language::JavaScript
+        );
+        assert_eq!(detect("#!/bin/sh\n"), Language::Bash);
+        assert_eq!(detect("echo hi\n# vim: set ft=bash :\n"), Language::Bash);
+        assert_eq!(detect("# -*- mode: ruby -*-\nputs 1"), Language::Ruby);
+        assert_eq!(detect("# -*- python -*-\n"), Language::Python);
+        assert_eq!(detect("<?php echo $_GET['x'];"), Language::Php);
+        assert_eq!(detect("# deploy\nset -euo pipefail\n"), Language::Bash);
+        assert_eq!(detect("MIT License\n"), Language::Other);
+    }
+
+    #[test]
+    fn classifies_templates_by_inner_extension() {
+        assert_eq!(
+            FileClassifier::classify("deploy/run.sh.j2", ""),
+            Language::Bash
+        );
+        assert_eq!(
+            FileClassifier::classify("config/settings.py.tmpl", ""),
+            Language::Python
+        );
+        assert_eq!(
+            FileClassifier::classify("bin/tool", "#!/usr/bin/env ruby\n"),
+            Language::Ruby
+        );
+    }
+
+    #[test]
+    fn finds_scripts_without_extensions() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            path
+        };
+        let script = write("deploy", "#!/usr/bin/env python3\nimport os\n");
+        let license = write("LICENSE", "MIT License\n");
+        let notes = write("notes.txt", "#!/bin/sh\n");
+        let template = write("entrypoint.sh.j2", "echo {{ name }}\n");
+        assert_eq!(
+            FileClassifier::script_language(&script),
+            Some(Language::Python)
+        );
+        assert_eq!(FileClassifier::script_language(&license), None);
+        assert_eq!(FileClassifier::script_language(&notes), None);
+        assert_eq!(
+            FileClassifier::script_language(&template),
+            Some(Language::Bash)
+        );
+    }
+
+    #[test]
+    fn classifies_pipeline_and_chart_configs() {
+        let dir = tempfile::tempdir().unwrap();
+        let chart = dir.path().join("charts/web");
+        std::fs::create_dir_all(chart.join("templates")).unwrap();
+        std::fs::write(chart.join("Chart.yaml"), "apiVersion: v2\nname: web\n").unwrap();
+
+        let kind = |path: &Path| FileClassifier::config_kind(path);
+        assert_eq!(
+            kind(Path::new(".github/workflows/ci.yml")),
+            Some(ConfigKind::GitHubActions)
+        );
+        assert_eq!(
+            kind(Path::new(".gitlab-ci.yml")),
+            Some(ConfigKind::GitLabCi)
+        );
+        assert_eq!(
+            kind(Path::new(".gitlab/ci/deploy.yml")),
+            Some(ConfigKind::GitLabCi)
+        );
+        assert_eq!(
+            kind(&chart.join("templates/deployment.yaml")),
+            Some(ConfigKind::Helm)
+        );
+        assert_eq!(kind(&chart.join("values.yaml")), Some(ConfigKind::Helm));
+        assert_eq!(kind(&dir.path().join("config.yaml")), None);
+        assert_eq!(kind(Path::new(".github/dependabot.yml")), None);
+    }
+
+    #[test]
+    fn language_overrides_match_relative_globs() {
+        let overrides = LanguageOverrides::parse("bin/*=bash, *.cgi=python").unwrap();
+        assert_eq!(overrides.get("bin/deploy"), Some(Language::Bash));
+        assert_eq!(overrides.get("/repo/bin/deploy"), Some(Language::Bash));
+        assert_eq!(overrides.get("web/app.cgi"), Some(Language::Python));
+        assert_eq!(overrides.get("bin/sub/deploy"), None);
+        assert!(LanguageOverrides::parse("bin/*").is_err());
+        assert!(LanguageOverrides::parse("bin/*=cobol").is_err());
+    }
+
     #[test]
     fn test_github_actions_detection() {
         let content = r#"
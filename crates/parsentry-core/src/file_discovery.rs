@@ -1,11 +1,42 @@
 use anyhow::Result;
 use std::path::{Path, PathBuf};
 
+use crate::content_type::{ContentType, is_skipped, sniff_file};
+use crate::file_classifier::FileClassifier;
+use crate::language::Language;
+use crate::path_filter::{IGNORE_FILE, PathFilter};
+
+/// Files found by a discovery walk, and those left out by their content.
+#[derive(Debug, Default)]
+pub struct Discovered {
+    pub files: Vec<PathBuf>,
+    /// Files with a supported extension that are binary, minified or
+    /// generated.
+    pub skipped: Vec<(PathBuf, ContentType)>,
+}
+
+impl Discovered {
+    /// Number of skipped files of `content`.
+    pub fn skipped_count(&self, content: ContentType) -> usize {
+        self.skipped.iter().filter(|(_, c)| *c == content).count()
+    }
+
+    fn push(&mut self, path: &Path) {
+        match sniff_file(path) {
+            content if is_skipped(content) => self.skipped.push((path.to_path_buf(), content)),
+            _ => self.files.push(path.to_path_buf()),
+        }
+    }
+}
+
 /// Common file discovery functionality for traversing directories
 /// and finding files with specific extensions.
 pub struct FileDiscovery {
     root_path: PathBuf,
     supported_extensions: Vec<String>,
+    /// Also discover scripts and templates by their content.
+    detect_scripts: bool,
+    filter: PathFilter,
 }
 
 impl FileDiscovery {
@@ -13,7 +44,7 @@ impl FileDiscovery {
     const DEFAULT_EXTENSIONS: &'static [&'static str] = &[
         "py", "js", "jsx", "ts", "tsx", "rs", "go", "java", "rb", "c", "h", "cpp", "cxx", "cc",
         "hpp", "hxx", "tf", "hcl", "yml", "yaml", "sh", "bash", "php", "php3", "php4", "php5",
-        "phtml", "html", "htm", "css",
+        "phtml", "html", "htm", "css", "graphql", "graphqls", "gql",
     ];
 
     /// Create a new FileDiscovery with default extensions
@@ -24,6 +55,8 @@ impl FileDiscovery {
                 .iter()
                 .map(|s| s.to_string())
                 .collect(),
+            detect_scripts: true,
+            filter: PathFilter::default(),
         }
     }
 
@@ -32,9 +65,17 @@ impl FileDiscovery {
         Self {
             root_path,
             supported_extensions: extensions,
+            detect_scripts: false,
+            filter: PathFilter::default(),
         }
     }
 
+    /// Restrict discovered files to those allowed by `filter`.
+    pub fn with_filter(mut self, filter: PathFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
     /// Get the root path
     pub fn root_path(&self) -> &Path {
         &self.root_path
@@ -50,38 +91,104 @@ impl FileDiscovery {
         self.supported_extensions.contains(&ext.to_lowercase())
     }
 
-    /// Get all files matching supported extensions in the root directory
+    /// Check a file path against the include/exclude filter
+    pub fn is_in_scope(&self, path: &Path) -> bool {
+        let rel = path.strip_prefix(&self.root_path).unwrap_or(path);
+        self.filter.allows(rel)
+    }
+
+    /// Check whether a file is discovered: it has a supported extension or,
+    /// with default extensions, is a script in a supported language.
+    fn is_discovered(&self, path: &Path) -> bool {
+        let supported = path
+            .extension()
+            .is_some_and(|ext| self.is_supported_extension(&ext.to_string_lossy()));
+        (supported
+            || self.detect_scripts
+                && FileClassifier::script_language(path).is_some_and(|language| {
+                    self.supported_extensions
+                        .iter()
+                        .any(|ext| Language::from_extension(ext) == language)
+                }))
+            && self.is_in_scope(path)
+    }
+
+    /// Get all text files matching supported extensions in the root
+    /// directory
     pub fn get_files(&self) -> Result<Vec<PathBuf>> {
         self.get_files_in_path(&self.root_path)
     }
 
-    /// Get all files matching supported extensions in a specific path
+    /// Get all text files matching supported extensions in a specific path
     pub fn get_files_in_path(&self, path: &Path) -> Result<Vec<PathBuf>> {
+        Ok(self.discover_in_path(path)?.files)
+    }
+
+    /// Like [`Self::get_files`], also listing the binary, minified and
+    /// generated files left out.
+    pub fn discover(&self) -> Result<Discovered> {
+        self.discover_in_path(&self.root_path)
+    }
+
+    /// Like [`Self::get_files_in_path`], also listing the binary,
+    /// minified and generated files left out.
+    pub fn discover_in_path(&self, path: &Path) -> Result<Discovered> {
+        let mut discovered = Discovered::default();
         if path.is_file() {
-            if let Some(ext) = path.extension() {
-                let ext_str = ext.to_string_lossy().to_lowercase();
-                if self.supported_extensions.contains(&ext_str) {
-                    return Ok(vec![path.to_path_buf()]);
-                }
+            if self.is_discovered(path) {
+                discovered.push(path);
             }
-            return Ok(vec![]);
+            return Ok(discovered);
         }
 
         if !path.is_dir() {
             anyhow::bail!("Path does not exist: {}", path.display());
         }
 
-        let mut files = Vec::new();
-        self.visit_dirs(path, &mut |p: &Path| {
-            if let Some(ext) = p.extension() {
-                let ext_str = ext.to_string_lossy().to_lowercase();
-                if self.supported_extensions.contains(&ext_str) {
-                    files.push(p.to_path_buf());
+        if let Some(listed) = self.filter.listed_files() {
+            for p in listed.iter().map(|rel| self.root_path.join(rel)) {
+                if p.starts_with(path) && p.is_file() && self.is_discovered(&p) {
+                    discovered.push(&p);
                 }
             }
-        })?;
+            return Ok(discovered);
+        }
+
+        let mut cb = |p: &Path| {
+            if self.is_discovered(p) {
+                discovered.push(p);
+            }
+        };
+        if self.filter.respects_ignore_files() {
+            self.visit_unignored(path, &mut cb)?;
+        } else {
+            self.visit_dirs(path, &mut cb)?;
+        }
 
-        Ok(files)
+        Ok(discovered)
+    }
+
+    /// Like [`Self::visit_dirs`], but skips paths excluded by `.gitignore`
+    /// (also outside a git checkout) and `.parsentryignore`.
+    fn visit_unignored<F>(&self, dir: &Path, cb: &mut F) -> Result<()>
+    where
+        F: FnMut(&Path),
+    {
+        let walker = ignore::WalkBuilder::new(dir)
+            .hidden(false)
+            .git_global(false)
+            .require_git(false)
+            .follow_links(false)
+            .add_custom_ignore_filename(IGNORE_FILE)
+            .filter_entry(|e| e.file_name() != ".git")
+            .build();
+        for entry in walker {
+            let entry = entry?;
+            if entry.file_type().is_some_and(|t| t.is_file()) {
+                cb(entry.path());
+            }
+        }
+        Ok(())
     }
 
     /// Recursively visit directories and call callback for each file
@@ -177,6 +284,103 @@ mod tests {
         assert_eq!(files[0], file_path);
     }
 
+    #[test]
+    fn test_get_files_applies_filter() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        fs::create_dir_all(temp_path.join("vendor/lib")).unwrap();
+        File::create(temp_path.join("app.py")).unwrap();
+        File::create(temp_path.join("vendor/lib/dep.py")).unwrap();
+
+        let filter = PathFilter::new(&[], &["vendor/**".to_string()]).unwrap();
+        let discovery = FileDiscovery::new(temp_path.to_path_buf()).with_filter(filter);
+        let files = discovery.get_files().unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("app.py"));
+    }
+
+    #[test]
+    fn test_get_files_respects_ignore_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        fs::create_dir_all(temp_path.join("node_modules/pkg")).unwrap();
+        fs::create_dir_all(temp_path.join("gen")).unwrap();
+        fs::write(temp_path.join(".gitignore"), "node_modules/\n").unwrap();
+        fs::write(temp_path.join(IGNORE_FILE), "gen/\n").unwrap();
+        File::create(temp_path.join("app.js")).unwrap();
+        File::create(temp_path.join("node_modules/pkg/index.js")).unwrap();
+        File::create(temp_path.join("gen/api.js")).unwrap();
+
+        let files = FileDiscovery::new(temp_path.to_path_buf())
+            .get_files()
+            .unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("app.js"));
+
+        let files = FileDiscovery::new(temp_path.to_path_buf())
+            .with_filter(PathFilter::default().with_ignore_files(false))
+            .get_files()
+            .unwrap();
+        assert_eq!(files.len(), 3);
+    }
+
+    #[test]
+    fn test_get_files_includes_scripts_without_extensions() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        fs::create_dir(temp_path.join("bin")).unwrap();
+        fs::write(temp_path.join("bin/deploy"), "#!/bin/bash\nrm -rf \"$1\"\n").unwrap();
+        fs::write(temp_path.join("LICENSE"), "MIT License\n").unwrap();
+
+        let files = FileDiscovery::new(temp_path.to_path_buf())
+            .get_files()
+            .unwrap();
+        assert_eq!(files, vec![temp_path.join("bin/deploy")]);
+
+        let custom = FileDiscovery::with_extensions(temp_path.to_path_buf(), vec!["py".into()]);
+        assert!(custom.get_files().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_discover_skips_binary_and_minified_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        fs::write(temp_path.join("app.js"), "module.exports = {};\n").unwrap();
+        fs::write(temp_path.join("vendor.min.js"), "var a=1;".repeat(200)).unwrap();
+        fs::write(temp_path.join("blob.h"), b"\x7fELF\x02\x01\x01\0").unwrap();
+
+        let discovered = FileDiscovery::new(temp_path.to_path_buf())
+            .discover()
+            .unwrap();
+        assert_eq!(discovered.files, vec![temp_path.join("app.js")]);
+        assert_eq!(discovered.skipped_count(ContentType::Binary), 1);
+        assert_eq!(discovered.skipped_count(ContentType::Minified), 1);
+    }
+
+    #[test]
+    fn test_get_files_uses_listed_files_without_walking() {
+        let temp_dir = TempDir::new().unwrap();
+        let temp_path = temp_dir.path();
+
+        fs::create_dir(temp_path.join("src")).unwrap();
+        File::create(temp_path.join("src/a.py")).unwrap();
+        File::create(temp_path.join("src/b.py")).unwrap();
+        File::create(temp_path.join("README.md")).unwrap();
+
+        let filter = PathFilter::default().with_files(["src/a.py", "README.md", "gone.py"]);
+        let discovery = FileDiscovery::new(temp_path.to_path_buf()).with_filter(filter);
+
+        let files = discovery.get_files().unwrap();
+        assert_eq!(files, vec![temp_path.join("src/a.py")]);
+        let in_src = discovery.get_files_in_path(&temp_path.join("src")).unwrap();
+        assert_eq!(in_src.len(), 1);
+    }
+
     #[test]
     fn test_supported_extensions_returns_actual_list() {
         let discovery = FileDiscovery::new(PathBuf::from("/tmp"));
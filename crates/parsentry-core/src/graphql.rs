@@ -0,0 +1,259 @@
+//! GraphQL schema (SDL) reading.
+//!
+//! The root fields of a schema are its entry points: every `Query`,
+//! `Mutation` and `Subscription` field is callable by any client the
+//! endpoint accepts. A light reading of the SDL, without a full parser, is
+//! enough to list them and to point workers at the fields most often left
+//! open: mutations without an auth directive and list queries without
+//! pagination arguments.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// Extensions of GraphQL schema files.
+pub const GRAPHQL_EXTENSIONS: &[&str] = &["graphql", "graphqls", "gql"];
+
+/// Directive names that mark a field or type as access-controlled, e.g.
+/// `@auth`, `@hasRole`, `@aws_cognito_user_pools`.
+static AUTH_DIRECTIVE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)@\w*(auth|role|permission|scope|guard|login|iam|cognito|owner|private)")
+        .expect("valid regex")
+});
+
+/// Argument names that bound the length of a returned list.
+static PAGINATION_ARGUMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(first|last|limit|take|top|page|page_?size|per_?page|pagination)\s*:")
+        .expect("valid regex")
+});
+
+/// A field definition starting a line or a type body:
+/// `name(args): Type @directives`.
+static FIELD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?m)(?:^|\{)[ \t]*(\w+)\s*(\([^)]*\))?\s*:\s*([\[\]\w!]+)((?:\s*@\w+(?:\s*\([^)]*\))?)*)",
+    )
+    .expect("valid regex")
+});
+
+/// A type definition or extension header: `extend type Mutation @auth {`.
+static TYPE_HEADER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:extend\s+)?(type|schema)\b\s*(\w*)[^{]*\{").expect("valid regex")
+});
+
+/// The root type a field belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Query,
+    Mutation,
+    Subscription,
+}
+
+/// A field of a root type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootField {
+    pub operation: Operation,
+    /// Schema file path relative to the repository root.
+    pub file: String,
+    /// `Type.field`, e.g. `Mutation.deleteUser`.
+    pub name: String,
+    /// 1-based line of the field definition.
+    pub line: usize,
+    /// Whether the field or its type carries an auth directive.
+    pub authenticated: bool,
+    /// Whether the field returns a list without a pagination argument.
+    pub unbounded_list: bool,
+}
+
+impl RootField {
+    /// One-line Markdown description for prompts.
+    pub fn describe(&self) -> String {
+        format!("`{}` at `{}:{}`", self.name, self.file, self.line)
+    }
+
+    /// Why the field deserves a look, if it does.
+    pub fn issue(&self) -> Option<&'static str> {
+        if self.operation == Operation::Mutation && !self.authenticated {
+            Some("mutation without an auth directive")
+        } else if self.operation == Operation::Query && self.unbounded_list {
+            Some("list query without pagination arguments")
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether `path` names a GraphQL schema file.
+pub fn is_graphql_file(path: &str) -> bool {
+    std::path::Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| GRAPHQL_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Root fields defined in `sdl`, the schema at `file`, in source order.
+/// Root types are named by a `schema { ... }` block, else `Query`,
+/// `Mutation` and `Subscription`.
+pub fn root_fields(file: &str, sdl: &str) -> Vec<RootField> {
+    let code = blank_comments_and_strings(sdl);
+    let mut roots = vec![
+        ("Query".to_string(), Operation::Query),
+        ("Mutation".to_string(), Operation::Mutation),
+        ("Subscription".to_string(), Operation::Subscription),
+    ];
+    // (keyword, type name, header, offset of the body)
+    let blocks: Vec<(&str, &str, &str, usize)> = TYPE_HEADER
+        .captures_iter(&code)
+        .filter_map(|c| {
+            let header = c.get(0)?;
+            Some((
+                c.get(1)?.as_str(),
+                c.get(2)?.as_str(),
+                header.as_str(),
+                header.end(),
+            ))
+        })
+        .collect();
+
+    if let Some((_, _, _, start)) = blocks.iter().find(|(kind, ..)| *kind == "schema") {
+        let body = &code[*start..*start + code[*start..].find('}').unwrap_or(0)];
+        for (root, operation) in [
+            ("query", Operation::Query),
+            ("mutation", Operation::Mutation),
+            ("subscription", Operation::Subscription),
+        ] {
+            let named = Regex::new(&format!(r"\b{root}\s*:\s*(\w+)")).expect("valid regex");
+            if let Some(c) = named.captures(body) {
+                roots.retain(|(_, op)| *op != operation);
+                roots.push((c[1].to_string(), operation));
+            }
+        }
+    }
+
+    let mut fields = Vec::new();
+    for (kind, name, header, start) in blocks {
+        let Some(operation) = roots
+            .iter()
+            .find(|(root, _)| kind == "type" && root == name)
+            .map(|(_, op)| *op)
+        else {
+            continue;
+        };
+        let type_authenticated = AUTH_DIRECTIVE.is_match(header);
+        let end = start + code[start..].find('}').unwrap_or(code.len() - start);
+        for c in FIELD.captures_iter(&code[start..end]) {
+            let args = c.get(2).map_or("", |m| m.as_str());
+            let directives = c.get(4).map_or("", |m| m.as_str());
+            let offset = start + c.get(1).map_or(0, |m| m.start());
+            fields.push(RootField {
+                operation,
+                file: file.to_string(),
+                name: format!("{name}.{}", &c[1]),
+                line: code[..offset].matches('\n').count() + 1,
+                authenticated: type_authenticated || AUTH_DIRECTIVE.is_match(directives),
+                unbounded_list: c[3].starts_with('[') && !PAGINATION_ARGUMENT.is_match(args),
+            });
+        }
+    }
+    fields.sort_by_key(|f| f.line);
+    fields
+}
+
+/// `sdl` with comments and descriptions replaced by spaces, keeping byte
+/// offsets and line breaks.
+fn blank_comments_and_strings(sdl: &str) -> String {
+    let mut out = String::with_capacity(sdl.len());
+    let mut rest = sdl;
+    while let Some(c) = rest.chars().next() {
+        let skipped = if c == '#' {
+            rest.find('\n').unwrap_or(rest.len())
+        } else if let Some(block) = rest.strip_prefix("\"\"\"") {
+            block.find("\"\"\"").map_or(rest.len(), |i| i + 6)
+        } else if c == '"' {
+            rest[1..]
+                .find(['"', '\n'])
+                .map_or(rest.len(), |i| i + 2)
+                .min(rest.len())
+        } else {
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+            continue;
+        };
+        out.extend(
+            rest[..skipped]
+                .chars()
+                .map(|c| if c == '\n' { '\n' } else { ' ' }),
+        );
+        rest = &rest[skipped..];
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+"""The root query"""
+type Query {
+  # Every user, unpaginated
+  users: [User!]!
+  posts(first: Int, after: String): [Post!]!
+  me: User @auth
+}
+
+type Mutation {
+  login(email: String!, password: String!): Token
+  deleteUser(id: ID!): Boolean @hasRole(role: ADMIN)
+}
+
+extend type Mutation @authenticated {
+  updateProfile(input: ProfileInput!): User
+}
+
+type User {
+  friends: [User!]!
+}
+"#;
+
+    #[test]
+    fn reads_root_fields_and_their_issues() {
+        let fields = root_fields("schema.graphql", SCHEMA);
+        let summary: Vec<(&str, usize, Option<&str>)> = fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.line, f.issue()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "Query.users",
+                    5,
+                    Some("list query without pagination arguments")
+                ),
+                ("Query.posts", 6, None),
+                ("Query.me", 7, None),
+                (
+                    "Mutation.login",
+                    11,
+                    Some("mutation without an auth directive")
+                ),
+                ("Mutation.deleteUser", 12, None),
+                ("Mutation.updateProfile", 16, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn follows_renamed_root_types() {
+        let sdl =
+            "schema { query: RootQuery }\ntype RootQuery { items: [Item] }\ntype Query { x: Int }";
+        let names: Vec<String> = root_fields("schema.graphql", sdl)
+            .into_iter()
+            .map(|f| f.name)
+            .collect();
+        assert_eq!(names, vec!["RootQuery.items"]);
+        assert!(is_graphql_file("api/schema.graphql"));
+        assert!(!is_graphql_file("api/schema.json"));
+    }
+}
@@ -0,0 +1,99 @@
+//! Outbound HTTP configuration shared by every client and `git` call.
+//!
+//! HTTP clients honour `HTTPS_PROXY`, `HTTP_PROXY`, `ALL_PROXY` and
+//! `NO_PROXY`, as does `git`. Networks that intercept TLS need their CA
+//! trusted on top of the system roots: `PARSENTRY_CA_BUNDLE` names a PEM
+//! bundle that every client built by [`client_builder`] and every `git`
+//! command given [`git_env`] trusts.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+
+/// PEM bundle of extra trusted CA certificates.
+pub const CA_BUNDLE_ENV: &str = "PARSENTRY_CA_BUNDLE";
+
+/// The CA bundle configured in the environment, if any.
+pub fn ca_bundle() -> Option<PathBuf> {
+    std::env::var_os(CA_BUNDLE_ENV)
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Certificates of the configured CA bundle; empty without one.
+pub fn ca_certificates() -> Result<Vec<reqwest::Certificate>> {
+    let Some(path) = ca_bundle() else {
+        return Ok(Vec::new());
+    };
+    let pem = std::fs::read(&path)
+        .with_context(|| format!("cannot read {CA_BUNDLE_ENV} {}", path.display()))?;
+    let certs = reqwest::Certificate::from_pem_bundle(&pem)
+        .with_context(|| format!("invalid PEM in {CA_BUNDLE_ENV} {}", path.display()))?;
+    if certs.is_empty() {
+        bail!("{CA_BUNDLE_ENV} {} holds no certificates", path.display());
+    }
+    Ok(certs)
+}
+
+/// A client builder trusting the system roots and the configured CA
+/// bundle, with proxies taken from the environment.
+pub fn client_builder() -> Result<reqwest::ClientBuilder> {
+    Ok(reqwest::Client::builder().tls_certs_merge(ca_certificates()?))
+}
+
+/// [`client_builder`] with defaults.
+pub fn client() -> Result<reqwest::Client> {
+    Ok(client_builder()?.build()?)
+}
+
+/// Environment for `git` commands that reach a remote, trusting the
+/// configured CA bundle. For `git` the bundle replaces the default CA
+/// file, so it must also hold the public roots of hosts in `NO_PROXY`.
+pub fn git_env() -> Vec<(&'static str, PathBuf)> {
+    ca_bundle()
+        .map(|path| vec![("GIT_SSL_CAINFO", path)])
+        .unwrap_or_default()
+}
+
+/// `err` explained as a proxy or certificate problem when a connection or
+/// TLS handshake failed, which is otherwise reported as a bare transport
+/// error.
+pub fn explain_network_error(err: anyhow::Error) -> anyhow::Error {
+    let failed_connection = err.chain().any(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_connect())
+            || [
+                "SSL certificate problem",
+                "unable to get local issuer certificate",
+            ]
+            .iter()
+            .any(|needle| cause.to_string().contains(needle))
+    });
+    if !failed_connection {
+        return err;
+    }
+    err.context(format!(
+        "connection failed: behind a proxy, set HTTPS_PROXY (and NO_PROXY for hosts reached \
+         directly); behind TLS interception, set {CA_BUNDLE_ENV} to a PEM bundle of its CA"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explains_certificate_failures() {
+        let err = anyhow::anyhow!(
+            "git fetch failed: fatal: unable to access 'https://github.com/o/r/': \
+             SSL certificate problem: self-signed certificate in certificate chain"
+        );
+        let explained = format!("{:#}", explain_network_error(err));
+        assert!(explained.starts_with("connection failed"));
+        assert!(explained.contains(CA_BUNDLE_ENV));
+
+        let other = explain_network_error(anyhow::anyhow!("no sarif files found"));
+        assert_eq!(other.to_string(), "no sarif files found");
+    }
+}
@@ -25,6 +25,7 @@ pub enum Language {
     Php,
     Html,
     Css,
+    GraphQL,
     Other,
 }
 
@@ -48,6 +49,7 @@ impl Language {
             "php" | "php3" | "php4" | "php5" | "phtml" => Language::Php,
             "html" | "htm" => Language::Html,
             "css" => Language::Css,
+            "graphql" | "graphqls" | "gql" => Language::GraphQL,
             _ => Language::Other,
         }
     }
@@ -96,6 +98,7 @@ impl Language {
             Language::Php => "PHP",
             Language::Html => "HTML",
             Language::Css => "CSS",
+            Language::GraphQL => "GraphQL",
             Language::Other => "Other",
         }
     }
@@ -131,9 +134,10 @@ impl FromStr for Language {
             "php" => Ok(Language::Php),
             "html" | "htm" => Ok(Language::Html),
             "css" => Ok(Language::Css),
+            "graphql" | "gql" => Ok(Language::GraphQL),
             "other" => Ok(Language::Other),
             _ => Err(format!(
-                "Unknown language: '{}'. Supported languages: python, javascript, rust, typescript, java, go, ruby, c, cpp, terraform, cloudformation, kubernetes, yaml, bash, shell, php, html, css",
+                "Unknown language: '{}'. Supported languages: python, javascript, rust, typescript, java, go, ruby, c, cpp, terraform, cloudformation, kubernetes, yaml, bash, shell, php, html, css, graphql",
                 s
             )),
         }
@@ -234,6 +238,7 @@ mod tests {
         assert_eq!(Language::from_str("html").unwrap(), Language::Html);
         assert_eq!(Language::from_str("htm").unwrap(), Language::Html);
         assert_eq!(Language::from_str("css").unwrap(), Language::Css);
+        assert_eq!(Language::from_str("GraphQL").unwrap(), Language::GraphQL);
         assert_eq!(Language::from_str("other").unwrap(), Language::Other);
         assert_eq!(Language::from_str("tsx").unwrap(), Language::TypeScript);
         assert_eq!(
@@ -273,6 +278,7 @@ mod tests {
         assert_eq!(Language::from_extension("html"), Language::Html);
         assert_eq!(Language::from_extension("htm"), Language::Html);
         assert_eq!(Language::from_extension("css"), Language::Css);
+        assert_eq!(Language::from_extension("gql"), Language::GraphQL);
     }
 
     // --- Mutant-killing: is_iac all variants ---
@@ -1,21 +1,34 @@
 //! Core types and traits for Parsentry.
 
 mod collector;
+pub mod content_type;
+mod dependencies;
 mod file_classifier;
 mod file_discovery;
+pub mod graphql;
+pub mod http;
 mod language;
+pub mod offline;
+mod path_filter;
 mod response;
+mod severity;
+pub mod template;
 mod threat_model;
+mod threat_model_graph;
 mod threat_model_prompt;
 mod threat_model_report;
 mod vuln_type;
 
 pub use collector::{ManifestInfo, RepoMetadata};
-pub use file_classifier::FileClassifier;
-pub use file_discovery::FileDiscovery;
+pub use dependencies::{DEPENDENCIES_FILE, Dependency, parse_dependencies, render_dependencies};
+pub use file_classifier::{ConfigKind, FileClassifier, LANGUAGE_OVERRIDES_ENV, LanguageOverrides};
+pub use file_discovery::{Discovered, FileDiscovery};
 pub use language::Language;
+pub use path_filter::{IGNORE_FILE, PathFilter};
 pub use response::{Response, response_json_schema};
+pub use severity::Severity;
 pub use threat_model::{AttackSurface, ThreatModel};
+pub use threat_model_graph::{GraphEdge, GraphNode, GraphNodeKind, ThreatModelGraph};
 pub use threat_model_prompt::{
     THREAT_MODEL_SYSTEM_PROMPT, build_threat_model_prompt, parse_threat_model_response,
     threat_model_schema,
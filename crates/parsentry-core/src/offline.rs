@@ -0,0 +1,54 @@
+//! Process-wide offline switch (`--offline`).
+//!
+//! Every code path that would reach the network calls [`ensure_online`]
+//! first, so offline runs fail with the list of operations that needed
+//! network access instead of timing out or silently degrading.
+
+use anyhow::{Result, bail};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Forbid network access for the rest of the process.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Fail in offline mode if `action` would need network access.
+pub fn ensure_online(action: &str) -> Result<()> {
+    require_network(&[action.to_string()])
+}
+
+/// Fail in offline mode, listing every action that would need network access.
+pub fn require_network(actions: &[String]) -> Result<()> {
+    check_network(is_offline(), actions)
+}
+
+fn check_network(offline: bool, actions: &[String]) -> Result<()> {
+    if !offline || actions.is_empty() {
+        return Ok(());
+    }
+    let list: String = actions.iter().map(|a| format!("\n  - {a}")).collect();
+    bail!("--offline: network access required for:{list}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offline_lists_every_action() {
+        let actions = ["clone a/b".to_string(), "Jira export".to_string()];
+        assert!(check_network(false, &actions).is_ok());
+        let err = check_network(true, &actions).unwrap_err().to_string();
+        assert!(
+            err.contains("- clone a/b") && err.contains("- Jira export"),
+            "{err}"
+        );
+        assert!(check_network(true, &[]).is_ok());
+    }
+}
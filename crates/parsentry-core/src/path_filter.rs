@@ -0,0 +1,182 @@
+use anyhow::{Context, Result};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// Name of the Parsentry-specific ignore file (gitignore syntax).
+pub const IGNORE_FILE: &str = ".parsentryignore";
+
+/// User-supplied include/exclude globs, matched against paths relative to
+/// the repository root.
+///
+/// A path is kept when it matches at least one include glob (or no include
+/// globs were given) and matches no exclude glob. Directory walks also honor
+/// `.gitignore` and [`IGNORE_FILE`] unless ignore files are disabled.
+#[derive(Debug, Clone)]
+pub struct PathFilter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    include_patterns: Vec<String>,
+    exclude_patterns: Vec<String>,
+    ignore_files: bool,
+    files: Option<BTreeSet<PathBuf>>,
+}
+
+impl Default for PathFilter {
+    fn default() -> Self {
+        Self {
+            include: None,
+            exclude: None,
+            include_patterns: Vec::new(),
+            exclude_patterns: Vec::new(),
+            ignore_files: true,
+            files: None,
+        }
+    }
+}
+
+fn glob(pattern: &str) -> Result<Glob> {
+    GlobBuilder::new(pattern.trim_start_matches("./"))
+        .literal_separator(true)
+        .build()
+        .with_context(|| format!("invalid glob: {pattern}"))
+}
+
+fn build_set(patterns: &[String]) -> Result<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(glob(pattern)?);
+    }
+    Ok(Some(builder.build()?))
+}
+
+impl PathFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+        Ok(Self {
+            include: build_set(include)?,
+            exclude: build_set(exclude)?,
+            include_patterns: include.to_vec(),
+            exclude_patterns: exclude.to_vec(),
+            ignore_files: true,
+            files: None,
+        })
+    }
+
+    /// Enable or disable `.gitignore` / `.parsentryignore` handling.
+    pub fn with_ignore_files(mut self, enabled: bool) -> Self {
+        self.ignore_files = enabled;
+        self
+    }
+
+    pub fn respects_ignore_files(&self) -> bool {
+        self.ignore_files
+    }
+
+    /// Restrict the scope to an explicit list of files (relative to the
+    /// root), replacing directory discovery. Globs still apply.
+    pub fn with_files<I, P>(mut self, files: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        self.files = Some(
+            files
+                .into_iter()
+                .map(|p| {
+                    p.as_ref()
+                        .strip_prefix("./")
+                        .unwrap_or(p.as_ref())
+                        .to_path_buf()
+                })
+                .collect(),
+        );
+        self
+    }
+
+    /// The explicit file list, if one was given.
+    pub fn listed_files(&self) -> Option<&BTreeSet<PathBuf>> {
+        self.files.as_ref()
+    }
+
+    /// True when neither globs nor a file list were given.
+    pub fn is_empty(&self) -> bool {
+        self.include.is_none() && self.exclude.is_none() && self.files.is_none()
+    }
+
+    pub fn include_patterns(&self) -> &[String] {
+        &self.include_patterns
+    }
+
+    pub fn exclude_patterns(&self) -> &[String] {
+        &self.exclude_patterns
+    }
+
+    /// Whether a file at `rel_path` (relative to the root) is in scope.
+    pub fn allows(&self, rel_path: &Path) -> bool {
+        if let Some(files) = &self.files
+            && !files.contains(rel_path)
+        {
+            return false;
+        }
+        if let Some(exclude) = &self.exclude
+            && exclude.is_match(rel_path)
+        {
+            return false;
+        }
+        match &self.include {
+            Some(include) => include.is_match(rel_path),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(include: &[&str], exclude: &[&str]) -> PathFilter {
+        let owned = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        PathFilter::new(&owned(include), &owned(exclude)).unwrap()
+    }
+
+    #[test]
+    fn test_empty_filter_allows_everything() {
+        let f = PathFilter::default();
+        assert!(f.is_empty());
+        assert!(f.allows(Path::new("vendor/lib.js")));
+    }
+
+    #[test]
+    fn test_exclude_wins_over_include() {
+        let f = filter(&["src/**"], &["**/generated/**", "vendor/**"]);
+        assert!(f.allows(Path::new("src/app.py")));
+        assert!(f.allows(Path::new("src/api/handlers.py")));
+        assert!(!f.allows(Path::new("src/generated/pb.py")));
+        assert!(!f.allows(Path::new("vendor/lib.js")));
+        assert!(!f.allows(Path::new("tests/test_app.py")));
+    }
+
+    #[test]
+    fn test_single_star_does_not_cross_directories() {
+        let f = filter(&["src/*.py"], &[]);
+        assert!(f.allows(Path::new("src/app.py")));
+        assert!(!f.allows(Path::new("src/api/app.py")));
+    }
+
+    #[test]
+    fn test_file_list_restricts_scope() {
+        let f = filter(&[], &["vendor/**"]).with_files(["./src/app.py", "vendor/x.py"]);
+        assert!(!f.is_empty());
+        assert!(f.allows(Path::new("src/app.py")));
+        assert!(!f.allows(Path::new("src/other.py")));
+        assert!(!f.allows(Path::new("vendor/x.py")));
+    }
+
+    #[test]
+    fn test_invalid_glob_is_rejected() {
+        assert!(PathFilter::new(&["src/[".to_string()], &[]).is_err());
+    }
+}
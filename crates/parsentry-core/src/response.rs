@@ -3,6 +3,7 @@
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
+use crate::severity::Severity;
 use crate::vuln_type::VulnType;
 
 /// The main response structure for security analysis.
@@ -22,6 +23,9 @@ pub struct Response {
     pub file_path: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pattern_description: Option<String>,
+    /// Severity declared by the pattern that led to this finding.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern_severity: Option<Severity>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub matched_source_code: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -56,16 +60,19 @@ impl Response {
         !self.vulnerability_types.is_empty() && self.confidence_score > 0
     }
 
-    /// Get severity level based on confidence score.
+    /// Severity from the confidence score, raised to the matched pattern's
+    /// severity when that is higher.
+    #[must_use]
+    pub fn severity(&self) -> Severity {
+        let from_confidence = Severity::from_confidence(self.confidence_score);
+        self.pattern_severity
+            .map_or(from_confidence, |s| s.max(from_confidence))
+    }
+
+    /// Get severity level based on confidence score and pattern severity.
     #[must_use]
     pub fn severity_level(&self) -> &'static str {
-        match self.confidence_score {
-            90..=100 => "critical",
-            70..=89 => "high",
-            50..=69 => "medium",
-            30..=49 => "low",
-            _ => "info",
-        }
+        self.severity().as_str()
     }
 }
 
@@ -123,6 +130,19 @@ mod tests {
         assert_eq!(response.severity_level(), "info");
     }
 
+    #[test]
+    fn test_pattern_severity_raises_level() {
+        let mut response = Response {
+            confidence_score: 55,
+            pattern_severity: Some(Severity::High),
+            ..Default::default()
+        };
+        assert_eq!(response.severity_level(), "high");
+
+        response.confidence_score = 95;
+        assert_eq!(response.severity_level(), "critical");
+    }
+
     #[test]
     fn test_sanitize() {
         let mut response = Response {
@@ -0,0 +1,70 @@
+//! Finding severity levels.
+
+use serde::{Deserialize, Serialize};
+
+/// Severity of a finding or a pattern, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// Severity implied by a 0-100 confidence score.
+    #[must_use]
+    pub fn from_confidence(score: i32) -> Self {
+        match score {
+            90..=100 => Severity::Critical,
+            70..=89 => Severity::High,
+            50..=69 => Severity::Medium,
+            30..=49 => Severity::Low,
+            _ => Severity::Info,
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+
+    /// SARIF `level` for this severity.
+    #[must_use]
+    pub fn sarif_level(self) -> &'static str {
+        match self {
+            Severity::Critical => "error",
+            Severity::High => "warning",
+            Severity::Medium => "note",
+            Severity::Low | Severity::Info => "info",
+        }
+    }
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ordering_and_confidence() {
+        assert!(Severity::Critical > Severity::High);
+        assert_eq!(Severity::from_confidence(95), Severity::Critical);
+        assert_eq!(Severity::from_confidence(50), Severity::Medium);
+        assert_eq!(Severity::from_confidence(10), Severity::Info);
+        assert_eq!(Severity::High.sarif_level(), "warning");
+    }
+}
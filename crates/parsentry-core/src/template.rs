@@ -0,0 +1,67 @@
+//! `{{placeholder}}` substitution for user-provided templates.
+//!
+//! Prompt and report templates share this syntax: a placeholder is a name
+//! between double braces, optionally padded with spaces (`{{ name }}`).
+//! Templates are checked once when loaded so that a typo fails the command
+//! instead of leaving a placeholder in the output.
+
+use anyhow::{Result, bail};
+
+/// Placeholder names in `text`, in order, failing on a name outside
+/// `allowed` or an unterminated placeholder.
+pub fn placeholders<'a>(text: &'a str, allowed: &[&str]) -> Result<Vec<&'a str>> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            bail!("unterminated placeholder in template");
+        };
+        let name = rest[start + 2..start + end].trim();
+        if !allowed.contains(&name) {
+            bail!(
+                "unknown placeholder {{{{{name}}}}} in template; expected one of: {}",
+                allowed.join(", ")
+            );
+        }
+        names.push(name);
+        rest = &rest[start + end + 2..];
+    }
+    Ok(names)
+}
+
+/// `text` with every placeholder replaced by `value(name)`. Unterminated
+/// placeholders are left as they are.
+pub fn fill_placeholders(text: &str, mut value: impl FnMut(&str) -> String) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        out.push_str(&value(rest[start + 2..start + end].trim()));
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_and_fills_placeholders() {
+        let text = "Hello {{ name }}, see {{url}}.";
+        assert_eq!(
+            placeholders(text, &["name", "url"]).unwrap(),
+            vec!["name", "url"]
+        );
+        assert!(placeholders(text, &["name"]).is_err());
+        assert!(placeholders("{{name", &["name"]).is_err());
+        assert_eq!(
+            fill_placeholders(text, |n| n.to_uppercase()),
+            "Hello NAME, see URL."
+        );
+    }
+}
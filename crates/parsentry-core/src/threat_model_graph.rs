@@ -0,0 +1,235 @@
+use serde::{Deserialize, Serialize};
+
+use crate::threat_model::AttackSurface;
+
+/// Fill and stroke of security-relevant nodes: files with PAR pattern hits
+/// and the surfaces that reach them.
+const HIT_FILL: &str = "#f8d7da";
+const HIT_STROKE: &str = "#c62828";
+const REACHES_FILL: &str = "#ffe0b2";
+const REACHES_STROKE: &str = "#ef6c00";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphNodeKind {
+    Surface,
+    File,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    /// Identifier usable in DOT and Mermaid (`s0`, `f3`, ...).
+    pub id: String,
+    pub kind: GraphNodeKind,
+    pub label: String,
+    /// PAR pattern matches in the file, or in all files of the surface.
+    pub pattern_hits: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+}
+
+/// Threat model as a graph: each surface points at the source files it
+/// hands to its worker, so surfaces sharing code share file nodes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThreatModelGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl ThreatModelGraph {
+    /// Add `surface` with its files and their pattern hit counts.
+    pub fn add_surface(&mut self, surface: &AttackSurface, files: &[(String, usize)]) {
+        let surface_id = format!("s{}", self.count(GraphNodeKind::Surface));
+        self.nodes.push(GraphNode {
+            id: surface_id.clone(),
+            kind: GraphNodeKind::Surface,
+            label: format!("{}\n{}", surface.id, surface.identifier),
+            pattern_hits: files.iter().map(|(_, hits)| hits).sum(),
+        });
+        for (path, hits) in files {
+            let file_id = match self
+                .nodes
+                .iter()
+                .find(|n| n.kind == GraphNodeKind::File && n.label == *path)
+            {
+                Some(node) => node.id.clone(),
+                None => {
+                    let id = format!("f{}", self.count(GraphNodeKind::File));
+                    self.nodes.push(GraphNode {
+                        id: id.clone(),
+                        kind: GraphNodeKind::File,
+                        label: path.clone(),
+                        pattern_hits: *hits,
+                    });
+                    id
+                }
+            };
+            self.edges.push(GraphEdge {
+                from: surface_id.clone(),
+                to: file_id,
+            });
+        }
+    }
+
+    fn count(&self, kind: GraphNodeKind) -> usize {
+        self.nodes.iter().filter(|n| n.kind == kind).count()
+    }
+
+    /// Render as a Graphviz digraph.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph threat_model {\n");
+        dot.push_str("  rankdir=LR;\n");
+        dot.push_str("  node [fontname=\"Helvetica\", fontsize=10];\n");
+        for node in &self.nodes {
+            let shape = match node.kind {
+                GraphNodeKind::Surface => "box",
+                GraphNodeKind::File => "note",
+            };
+            let mut attrs = format!("label=\"{}\", shape={shape}", dot_escape(&node_label(node)));
+            if let Some((fill, stroke)) = node_colors(node) {
+                attrs.push_str(&format!(
+                    ", style=filled, fillcolor=\"{fill}\", color=\"{stroke}\""
+                ));
+            }
+            dot.push_str(&format!("  {} [{attrs}];\n", node.id));
+        }
+        for edge in &self.edges {
+            dot.push_str(&format!("  {} -> {};\n", edge.from, edge.to));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Render as a Mermaid flowchart, e.g. for a fenced block in Markdown.
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = String::from("flowchart LR\n");
+        for node in &self.nodes {
+            let label = mermaid_escape(&node_label(node));
+            let shape = match node.kind {
+                GraphNodeKind::Surface => format!("[\"{label}\"]"),
+                GraphNodeKind::File => format!("([\"{label}\"])"),
+            };
+            mermaid.push_str(&format!("  {}{shape}\n", node.id));
+        }
+        for edge in &self.edges {
+            mermaid.push_str(&format!("  {} --> {}\n", edge.from, edge.to));
+        }
+        for (class, kind, fill, stroke) in [
+            ("hit", GraphNodeKind::File, HIT_FILL, HIT_STROKE),
+            (
+                "reaches",
+                GraphNodeKind::Surface,
+                REACHES_FILL,
+                REACHES_STROKE,
+            ),
+        ] {
+            let ids: Vec<&str> = self
+                .nodes
+                .iter()
+                .filter(|n| n.kind == kind && n.pattern_hits > 0)
+                .map(|n| n.id.as_str())
+                .collect();
+            if !ids.is_empty() {
+                mermaid.push_str(&format!(
+                    "  classDef {class} fill:{fill},stroke:{stroke}\n  class {} {class}\n",
+                    ids.join(",")
+                ));
+            }
+        }
+        mermaid
+    }
+}
+
+fn node_label(node: &GraphNode) -> String {
+    match (node.kind, node.pattern_hits) {
+        (GraphNodeKind::File, 1) => format!("{}\n1 pattern hit", node.label),
+        (GraphNodeKind::File, n) if n > 1 => format!("{}\n{n} pattern hits", node.label),
+        _ => node.label.clone(),
+    }
+}
+
+fn node_colors(node: &GraphNode) -> Option<(&'static str, &'static str)> {
+    if node.pattern_hits == 0 {
+        return None;
+    }
+    Some(match node.kind {
+        GraphNodeKind::File => (HIT_FILL, HIT_STROKE),
+        GraphNodeKind::Surface => (REACHES_FILL, REACHES_STROKE),
+    })
+}
+
+fn dot_escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn mermaid_escape(label: &str) -> String {
+    label.replace('"', "#quot;").replace('\n', "<br/>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn surface(id: &str, identifier: &str) -> AttackSurface {
+        AttackSurface {
+            id: id.to_string(),
+            kind: "endpoint".to_string(),
+            identifier: identifier.to_string(),
+            locations: Vec::new(),
+            description: String::new(),
+        }
+    }
+
+    fn graph() -> ThreatModelGraph {
+        let mut graph = ThreatModelGraph::default();
+        graph.add_surface(
+            &surface("SURFACE-001", "POST \"/login\""),
+            &[("app.py".to_string(), 2), ("db.py".to_string(), 0)],
+        );
+        graph.add_surface(
+            &surface("SURFACE-002", "GET /health"),
+            &[("db.py".to_string(), 0)],
+        );
+        graph
+    }
+
+    #[test]
+    fn test_shared_files_are_one_node() {
+        let graph = graph();
+        assert_eq!(graph.nodes.len(), 4);
+        assert_eq!(graph.edges.len(), 3);
+        assert_eq!(graph.edges[2].from, "s1");
+        assert_eq!(graph.edges[2].to, "f1");
+        assert_eq!(graph.nodes[0].pattern_hits, 2);
+    }
+
+    #[test]
+    fn test_render_dot_colors_pattern_hits() {
+        let dot = graph().to_dot();
+        assert!(dot.starts_with("digraph threat_model {"));
+        assert!(dot.contains(
+            "f0 [label=\"app.py\\n2 pattern hits\", shape=note, style=filled, fillcolor=\"#f8d7da\""
+        ));
+        assert!(dot.contains("s0 [label=\"SURFACE-001\\nPOST \\\"/login\\\"\""));
+        assert!(dot.contains("f1 [label=\"db.py\", shape=note];"));
+        assert!(dot.contains("s1 -> f1;"));
+    }
+
+    #[test]
+    fn test_render_mermaid_colors_pattern_hits() {
+        let mermaid = graph().to_mermaid();
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("s0[\"SURFACE-001<br/>POST #quot;/login#quot;\"]"));
+        assert!(mermaid.contains("f0([\"app.py<br/>2 pattern hits\"])"));
+        assert!(mermaid.contains("class f0 hit\n"));
+        assert!(mermaid.contains("class s0 reaches\n"));
+        assert!(mermaid.contains("s1 --> f1"));
+    }
+}
@@ -0,0 +1,823 @@
+//! Repository-wide call graph resolved by name.
+//!
+//! Built from the definitions and call sites [`CodeParser`] finds in each
+//! file. Calls are resolved to every definition of the same name, without
+//! type or import information, so the graph over-approximates; names defined
+//! in many files are treated as ambiguous and not followed. Calls across a
+//! language boundary, by HTTP route or by running a script, are linked by
+//! the heuristics in [`crate::cross_language`].
+
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::cross_language::{
+    Literal, identifiers, is_annotation, route_key, routes_match, script_target, string_literals,
+};
+use crate::parser::{CodeParser, Context};
+
+/// Names defined in more files than this are too generic to follow.
+const MAX_DEFINITION_SITES: usize = 3;
+
+/// Lines of a related function's source shown in a prompt.
+const SNIPPET_LINES: usize = 15;
+
+/// Longest call chain followed from a source.
+const MAX_PATH_LENGTH: usize = 8;
+
+/// Version of the index entries written by [`CallGraph::build_indexed`];
+/// bump when a file's entry would change for the same content.
+const INDEX_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Function {
+    name: String,
+    /// Filled in on load: index entries are keyed by content, not path.
+    #[serde(skip)]
+    file: String,
+    line: usize,
+    start_byte: usize,
+    end_byte: usize,
+    source: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CallSite {
+    name: String,
+    #[serde(skip)]
+    file: String,
+    start_byte: usize,
+}
+
+/// Definitions and calls of one file, as stored in the index.
+#[derive(Serialize, Deserialize)]
+struct FileEntry {
+    functions: Vec<Function>,
+    calls: Vec<CallSite>,
+}
+
+impl From<Context> for FileEntry {
+    fn from(context: Context) -> Self {
+        Self {
+            functions: context
+                .definitions
+                .into_iter()
+                .map(|d| Function {
+                    name: d.name,
+                    file: String::new(),
+                    line: d.line_number.unwrap_or(1),
+                    start_byte: d.start_byte,
+                    end_byte: d.end_byte,
+                    source: d.source,
+                })
+                .collect(),
+            calls: context
+                .references
+                .into_iter()
+                .map(|r| CallSite {
+                    name: r.name,
+                    file: String::new(),
+                    start_byte: r.start_byte,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Index key of a file: its content, and its extension, which selects the
+/// grammar and queries it is parsed with.
+fn index_key(path: &Path, text: &str) -> String {
+    let extension = path.extension().unwrap_or_default().to_string_lossy();
+    let digest = Sha256::digest(format!(
+        "{INDEX_VERSION}\0{}\0{extension}\0{text}",
+        env!("CARGO_PKG_VERSION")
+    ));
+    format!("{digest:x}")
+}
+
+/// Remove index entries not in `keys`, left by changed or deleted files.
+fn prune_index(dir: &Path, keys: &HashSet<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let stale = path.extension().is_some_and(|e| e == "json")
+            && path
+                .file_stem()
+                .is_some_and(|stem| !keys.contains(stem.to_string_lossy().as_ref()));
+        if stale {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// A call the names do not show: a request to a route, linked to the
+/// route's handler, or a script path, linked to the script's top level.
+#[derive(Debug, Clone)]
+struct Link {
+    file: String,
+    start_byte: usize,
+    target: Node,
+}
+
+/// How a [`RelatedFunction`] relates to the analyzed code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Relation {
+    /// Defined elsewhere and called from the analyzed files.
+    Callee,
+    /// Defined elsewhere and calls into the analyzed files.
+    Caller,
+}
+
+impl Relation {
+    pub fn label(self) -> &'static str {
+        match self {
+            Relation::Callee => "callee",
+            Relation::Caller => "caller",
+        }
+    }
+}
+
+/// A function outside the analyzed files, with the start of its source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelatedFunction {
+    pub name: String,
+    /// Path relative to the repository root.
+    pub file: String,
+    pub line: usize,
+    pub relation: Relation,
+    pub snippet: String,
+}
+
+/// One function on a call path; `<module>` for top-level code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PathStep {
+    pub name: String,
+    pub file: String,
+    pub line: usize,
+}
+
+/// A function, or the top-level code of a file.
+type Node = (String, Option<usize>);
+
+#[derive(Debug, Clone, Default)]
+pub struct CallGraph {
+    functions: Vec<Function>,
+    calls: Vec<CallSite>,
+    links: Vec<Link>,
+    /// Indices into `functions` of each file's definitions.
+    file_functions: HashMap<String, Range<usize>>,
+}
+
+/// Code reached from a set of sources by following calls, see
+/// [`CallGraph::reach_from`].
+pub struct Reach<'a> {
+    graph: &'a CallGraph,
+    /// Source each node was first reached from and its predecessor.
+    visited: HashMap<Node, (usize, Option<Node>)>,
+}
+
+impl Reach<'_> {
+    /// Index of the source that reaches `byte` of `file`, and the call path
+    /// from that source's function to the one containing `byte`.
+    pub fn path_to(&self, file: &str, byte: usize) -> Option<(usize, Vec<PathStep>)> {
+        let mut node = self.graph.node_at(file, byte);
+        let (source, _) = self.visited.get(&node)?;
+        let mut path = Vec::new();
+        loop {
+            path.push(self.graph.step(&node));
+            match &self.visited[&node].1 {
+                Some(previous) => node = previous.clone(),
+                None => break,
+            }
+        }
+        path.reverse();
+        Some((*source, path))
+    }
+}
+
+impl CallGraph {
+    /// Parse `files` (absolute paths under `root`); files in unsupported
+    /// languages or that fail to parse are left out.
+    pub fn build(root: &Path, files: &[PathBuf]) -> Self {
+        Self::build_with_index(root, files, None)
+    }
+
+    /// Like [`CallGraph::build`], but only parse files whose content is not
+    /// in the index under `index_dir`. The index is updated to hold exactly
+    /// the entries of `files`; failing to read or write it only costs
+    /// parsing time.
+    pub fn build_indexed(root: &Path, files: &[PathBuf], index_dir: &Path) -> Self {
+        Self::build_with_index(root, files, Some(index_dir))
+    }
+
+    fn build_with_index(root: &Path, files: &[PathBuf], index_dir: Option<&Path>) -> Self {
+        let mut graph = Self::default();
+        let mut parser = CodeParser::default();
+        let mut texts = Vec::new();
+        let mut keys = HashSet::new();
+        for path in files {
+            if parser.get_language(path).is_none() {
+                continue;
+            }
+            let Ok(text) = std::fs::read_to_string(path) else {
+                continue;
+            };
+            let file = relative_path(root, path);
+            let key = index_key(path, &text);
+            let stored = index_dir.and_then(|dir| {
+                let json = std::fs::read_to_string(dir.join(format!("{key}.json"))).ok()?;
+                serde_json::from_str::<FileEntry>(&json).ok()
+            });
+            let entry = match stored {
+                Some(entry) => entry,
+                None => {
+                    // Each file is only needed while it is parsed.
+                    parser.files.insert(path.clone(), text.clone());
+                    let context = parser.file_context(path);
+                    parser.files.remove(path);
+                    let Ok(entry) = context.map(FileEntry::from) else {
+                        continue;
+                    };
+                    if let Some(dir) = index_dir
+                        && std::fs::create_dir_all(dir).is_ok()
+                        && let Ok(json) = serde_json::to_string(&entry)
+                    {
+                        let _ = std::fs::write(dir.join(format!("{key}.json")), json);
+                    }
+                    entry
+                }
+            };
+            keys.insert(key);
+
+            let first = graph.functions.len();
+            graph
+                .functions
+                .extend(entry.functions.into_iter().map(|f| Function {
+                    file: file.clone(),
+                    ..f
+                }));
+            graph
+                .calls
+                .extend(entry.calls.into_iter().map(|c| CallSite {
+                    file: file.clone(),
+                    ..c
+                }));
+            graph
+                .file_functions
+                .insert(file.clone(), first..graph.functions.len());
+            texts.push((file, text));
+        }
+        if let Some(dir) = index_dir {
+            prune_index(dir, &keys);
+        }
+        let files: Vec<String> = files.iter().map(|p| relative_path(root, p)).collect();
+        graph.link_across_languages(&texts, &files);
+        graph
+    }
+
+    /// Link route requests to handlers and script paths to scripts, from
+    /// the string literals of each parsed file's `(file, text)`.
+    fn link_across_languages(&mut self, texts: &[(String, String)], files: &[String]) {
+        let mut handlers: Vec<(Vec<String>, usize)> = Vec::new();
+        let mut requests: Vec<(&str, usize, Vec<String>)> = Vec::new();
+        for (file, text) in texts {
+            for literal in string_literals(text) {
+                if let Some(script) = script_target(literal.value, file, files) {
+                    self.links.push(Link {
+                        file: file.clone(),
+                        start_byte: literal.start_byte,
+                        target: (script.to_string(), None),
+                    });
+                    continue;
+                }
+                let Some(route) = route_key(literal.value) else {
+                    continue;
+                };
+                let registered = self.route_handlers(file, text, literal);
+                if registered.is_empty() {
+                    requests.push((file, literal.start_byte, route));
+                } else {
+                    handlers.extend(registered.into_iter().map(|i| (route.clone(), i)));
+                }
+            }
+        }
+        for (file, start_byte, route) in requests {
+            let caller = self.enclosing_index(file, start_byte);
+            for (handled, i) in &handlers {
+                if routes_match(&route, handled) && caller != Some(*i) {
+                    self.links.push(Link {
+                        file: file.to_string(),
+                        start_byte,
+                        target: (self.functions[*i].file.clone(), Some(*i)),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Functions a route literal registers as handlers: the function under
+    /// the decorator or annotation holding it (`@app.route("/users")`), or
+    /// the last function named among the call's remaining arguments
+    /// (`http.HandleFunc("/users", listUsers)`).
+    fn route_handlers(&self, file: &str, text: &str, literal: Literal<'_>) -> Vec<usize> {
+        let line_start = text[..literal.start_byte].rfind('\n').map_or(0, |i| i + 1);
+        let end = literal.start_byte + literal.value.len() + 2;
+        let line_end = text[end..].find('\n').map_or(text.len(), |i| end + i);
+        if is_annotation(&text[line_start..line_end]) {
+            let line = text[..line_start].matches('\n').count() + 1;
+            return self
+                .file_functions
+                .get(file)
+                .cloned()
+                .into_iter()
+                .flatten()
+                .filter(|&i| (line..=line + 2).contains(&self.functions[i].line))
+                .min_by_key(|&i| self.functions[i].line)
+                .into_iter()
+                .collect();
+        }
+        let Some(arguments) = text[end..line_end].trim_start().strip_prefix(',') else {
+            return Vec::new();
+        };
+        let arguments = arguments.split([')', '{']).next().unwrap_or_default();
+        identifiers(arguments)
+            .map(|name| self.definition_indices(name))
+            .filter(|defs| !defs.is_empty())
+            .last()
+            .unwrap_or_default()
+    }
+
+    /// Indices of the definitions of `name`, or none when the name is
+    /// ambiguous.
+    fn definition_indices(&self, name: &str) -> Vec<usize> {
+        let defs: Vec<usize> = (0..self.functions.len())
+            .filter(|&i| self.functions[i].name == name)
+            .collect();
+        let files: HashSet<&str> = defs
+            .iter()
+            .map(|&i| self.functions[i].file.as_str())
+            .collect();
+        if files.len() > MAX_DEFINITION_SITES {
+            return Vec::new();
+        }
+        defs
+    }
+
+    fn definitions(&self, name: &str) -> Vec<&Function> {
+        self.definition_indices(name)
+            .into_iter()
+            .map(|i| &self.functions[i])
+            .collect()
+    }
+
+    /// Index of the innermost function of `file` containing `byte`.
+    fn enclosing_index(&self, file: &str, byte: usize) -> Option<usize> {
+        self.file_functions
+            .get(file)?
+            .clone()
+            .filter(|&i| {
+                let f = &self.functions[i];
+                f.start_byte <= byte && byte < f.end_byte
+            })
+            .min_by_key(|&i| self.functions[i].end_byte - self.functions[i].start_byte)
+    }
+
+    fn enclosing(&self, file: &str, byte: usize) -> Option<&Function> {
+        self.enclosing_index(file, byte).map(|i| &self.functions[i])
+    }
+
+    fn node_at(&self, file: &str, byte: usize) -> Node {
+        (file.to_string(), self.enclosing_index(file, byte))
+    }
+
+    fn step(&self, (file, function): &Node) -> PathStep {
+        match function {
+            Some(i) => PathStep {
+                name: self.functions[*i].name.clone(),
+                file: file.clone(),
+                line: self.functions[*i].line,
+            },
+            None => PathStep {
+                name: "<module>".to_string(),
+                file: file.clone(),
+                line: 1,
+            },
+        }
+    }
+
+    /// What each function or top level calls, by name or by [`Link`].
+    fn callees(&self) -> HashMap<Node, Vec<Node>> {
+        let mut callees: HashMap<Node, Vec<Node>> = HashMap::new();
+        for call in &self.calls {
+            callees
+                .entry(self.node_at(&call.file, call.start_byte))
+                .or_default()
+                .extend(
+                    self.definition_indices(&call.name)
+                        .into_iter()
+                        .map(|i| (self.functions[i].file.clone(), Some(i))),
+                );
+        }
+        for link in &self.links {
+            callees
+                .entry(self.node_at(&link.file, link.start_byte))
+                .or_default()
+                .push(link.target.clone());
+        }
+        for targets in callees.values_mut() {
+            targets.sort();
+            targets.dedup();
+        }
+        callees
+    }
+
+    /// Call paths from a definition of `from` to a definition of `to`, of
+    /// at most `max_depth` calls and without revisiting a function; the
+    /// first `limit` found, shortest first.
+    pub fn paths(
+        &self,
+        from: &str,
+        to: &str,
+        max_depth: usize,
+        limit: usize,
+    ) -> Vec<Vec<PathStep>> {
+        let callees = self.callees();
+        let is_target = |node: &Node| node.1.is_some_and(|i| self.functions[i].name == to);
+        let mut paths: Vec<Vec<Node>> = Vec::new();
+        // Breadth-first over partial paths, so shorter paths come first.
+        let mut queue: VecDeque<Vec<Node>> = self
+            .functions
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.name == from)
+            .map(|(i, f)| vec![(f.file.clone(), Some(i))])
+            .collect();
+        while let Some(path) = queue.pop_front() {
+            if paths.len() >= limit {
+                break;
+            }
+            let last = path.last().expect("paths are never empty");
+            if path.len() > 1 && is_target(last) {
+                paths.push(path);
+                continue;
+            }
+            if path.len() > max_depth {
+                continue;
+            }
+            for next in callees.get(last).into_iter().flatten() {
+                if !path.contains(next) {
+                    let mut longer = path.clone();
+                    longer.push(next.clone());
+                    queue.push_back(longer);
+                }
+            }
+        }
+        paths
+            .iter()
+            .map(|path| path.iter().map(|node| self.step(node)).collect())
+            .collect()
+    }
+
+    /// Follow calls from the functions containing each source location
+    /// (`file`, byte offset), up to [`MAX_PATH_LENGTH`] calls deep.
+    pub fn reach_from(&self, sources: &[(String, usize)]) -> Reach<'_> {
+        let callees = self.callees();
+
+        let mut visited: HashMap<Node, (usize, Option<Node>)> = HashMap::new();
+        let mut queue: VecDeque<(Node, usize)> = VecDeque::new();
+        for (index, (file, byte)) in sources.iter().enumerate() {
+            let node = self.node_at(file, *byte);
+            if !visited.contains_key(&node) {
+                visited.insert(node.clone(), (index, None));
+                queue.push_back((node, 0));
+            }
+        }
+        while let Some((node, depth)) = queue.pop_front() {
+            if depth >= MAX_PATH_LENGTH {
+                continue;
+            }
+            let source = visited[&node].0;
+            for next in callees.get(&node).into_iter().flatten() {
+                if !visited.contains_key(next) {
+                    visited.insert(next.clone(), (source, Some(node.clone())));
+                    queue.push_back((next.clone(), depth + 1));
+                }
+            }
+        }
+        Reach {
+            graph: self,
+            visited,
+        }
+    }
+
+    /// Files reachable by following calls from `entry_files`, including
+    /// the entry files themselves.
+    pub fn reachable_from(&self, entry_files: &[String]) -> HashSet<String> {
+        let mut reached: HashSet<String> = entry_files.iter().cloned().collect();
+        let mut queue: VecDeque<String> = entry_files.iter().cloned().collect();
+        while let Some(file) = queue.pop_front() {
+            let called: BTreeSet<&str> = self
+                .calls
+                .iter()
+                .filter(|c| c.file == file)
+                .map(|c| c.name.as_str())
+                .collect();
+            let targets = called
+                .into_iter()
+                .flat_map(|name| self.definitions(name))
+                .map(|def| &def.file)
+                .chain(
+                    self.links
+                        .iter()
+                        .filter(|l| l.file == file)
+                        .map(|l| &l.target.0),
+                );
+            for target in targets {
+                if reached.insert(target.clone()) {
+                    queue.push_back(target.clone());
+                }
+            }
+        }
+        reached
+    }
+
+    /// Functions outside `files` that code in `files` calls, then functions
+    /// outside `files` that call into them, at most `limit` in total.
+    pub fn related(&self, files: &[String], limit: usize) -> Vec<RelatedFunction> {
+        let inside = |file: &str| files.iter().any(|f| f == file);
+        let mut related: Vec<RelatedFunction> = Vec::new();
+        let mut push = |function: &Function, relation| {
+            if !related
+                .iter()
+                .any(|r| r.file == function.file && r.name == function.name)
+            {
+                related.push(RelatedFunction {
+                    name: function.name.clone(),
+                    file: function.file.clone(),
+                    line: function.line,
+                    relation,
+                    snippet: snippet(&function.source),
+                });
+            }
+        };
+
+        let called: BTreeSet<&str> = self
+            .calls
+            .iter()
+            .filter(|c| inside(&c.file))
+            .map(|c| c.name.as_str())
+            .collect();
+        for name in called {
+            let defs = self.definitions(name);
+            // Calls the analyzed files can resolve themselves stay local.
+            if defs.iter().any(|d| inside(&d.file)) {
+                continue;
+            }
+            for def in defs {
+                push(def, Relation::Callee);
+            }
+        }
+
+        let defined: HashSet<&str> = self
+            .functions
+            .iter()
+            .filter(|f| inside(&f.file))
+            .map(|f| f.name.as_str())
+            .collect();
+        for call in &self.calls {
+            if inside(&call.file) || !defined.contains(call.name.as_str()) {
+                continue;
+            }
+            if self.definitions(&call.name).is_empty() {
+                continue;
+            }
+            if let Some(caller) = self.enclosing(&call.file, call.start_byte) {
+                push(caller, Relation::Caller);
+            }
+        }
+
+        for link in &self.links {
+            let (file, Some(i)) = &link.target else {
+                continue;
+            };
+            if inside(&link.file) && !inside(file) {
+                push(&self.functions[*i], Relation::Callee);
+            } else if !inside(&link.file)
+                && inside(file)
+                && let Some(caller) = self.enclosing(&link.file, link.start_byte)
+            {
+                push(caller, Relation::Caller);
+            }
+        }
+
+        related.sort_by(|a, b| (a.relation, &a.file, a.line).cmp(&(b.relation, &b.file, b.line)));
+        related.truncate(limit);
+        related
+    }
+}
+
+/// `path` relative to `root`, with forward slashes.
+pub(crate) fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn snippet(source: &str) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    if lines.len() <= SNIPPET_LINES {
+        return source.trim_end().to_string();
+    }
+    format!("{}\n...", lines[..SNIPPET_LINES].join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(files: &[(&str, &str)]) -> (tempfile::TempDir, CallGraph) {
+        let root = tempfile::tempdir().unwrap();
+        let paths: Vec<PathBuf> = files
+            .iter()
+            .map(|(name, source)| {
+                let path = root.path().join(name);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(&path, source).unwrap();
+                path
+            })
+            .collect();
+        let graph = CallGraph::build(root.path(), &paths);
+        (root, graph)
+    }
+
+    #[test]
+    fn related_lists_callees_and_callers_outside_the_files() {
+        let (_root, graph) = graph(&[
+            (
+                "views.py",
+                "def login(request):\n    return find_user(request.args['name'])\n",
+            ),
+            (
+                "db.py",
+                "def find_user(name):\n    return cursor.execute('SELECT ' + name)\n",
+            ),
+            ("app.py", "def main():\n    login(None)\n"),
+        ]);
+
+        let related = graph.related(&["views.py".to_string()], 10);
+        assert_eq!(related.len(), 2);
+        assert_eq!(related[0].name, "find_user");
+        assert_eq!(related[0].relation, Relation::Callee);
+        assert_eq!(related[0].file, "db.py");
+        assert!(related[0].snippet.contains("cursor.execute"));
+        assert_eq!(related[1].name, "main");
+        assert_eq!(related[1].relation, Relation::Caller);
+
+        assert_eq!(graph.related(&["views.py".to_string()], 1).len(), 1);
+    }
+
+    #[test]
+    fn reach_from_reconstructs_call_paths() {
+        let (_root, graph) = graph(&[
+            (
+                "views.py",
+                "def login(request):\n    name = request.args['name']\n    return find_user(name)\n",
+            ),
+            (
+                "db.py",
+                "def find_user(name):\n    return run(name)\n\ndef run(q):\n    cursor.execute(q)\n",
+            ),
+            ("admin.py", "def purge():\n    cursor.execute('DELETE')\n"),
+        ]);
+        let sink = "def find_user(name):\n    return run(name)\n\ndef run(q):\n    cursor".len();
+
+        let reach = graph.reach_from(&[("views.py".to_string(), 30)]);
+        let (source, path) = reach.path_to("db.py", sink).unwrap();
+        assert_eq!(source, 0);
+        let names: Vec<&str> = path.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["login", "find_user", "run"]);
+        assert_eq!(path[2].line, 4);
+        assert!(reach.path_to("admin.py", 20).is_none());
+    }
+
+    #[test]
+    fn links_routes_and_scripts_across_languages() {
+        let (_root, graph) = graph(&[
+            (
+                "web/app.js",
+                "function load(id) {\n  return fetch(`/api/users/${id}`);\n}\n",
+            ),
+            (
+                "server/main.go",
+                "package main\n\nfunc main() {\n\thttp.HandleFunc(\"GET /api/users/{id}\", getUser)\n}\n\nfunc getUser(w http.ResponseWriter, r *http.Request) {\n\trunReport()\n}\n",
+            ),
+            (
+                "server/report.py",
+                "import subprocess\n\n@app.route('/reports')\ndef runReport():\n    subprocess.run(['./export.sh'])\n",
+            ),
+            ("server/export.sh", "pg_dump > out.sql\n"),
+        ]);
+
+        let reach = graph.reach_from(&[("web/app.js".to_string(), 20)]);
+        let (_, path) = reach.path_to("server/report.py", 80).unwrap();
+        let names: Vec<&str> = path.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["load", "getUser", "runReport"]);
+        let (_, path) = reach.path_to("server/export.sh", 0).unwrap();
+        assert_eq!(path.last().unwrap().name, "<module>");
+
+        let related = graph.related(&["web/app.js".to_string()], 10);
+        assert_eq!(related[0].name, "getUser");
+        assert!(
+            graph
+                .reachable_from(&["web/app.js".to_string()])
+                .contains("server/export.sh")
+        );
+    }
+
+    #[test]
+    fn indexed_builds_reuse_unchanged_files() {
+        let root = tempfile::tempdir().unwrap();
+        let index = root.path().join("index");
+        let files = [root.path().join("app.py"), root.path().join("db.py")];
+        std::fs::write(&files[0], "def main():\n    query()\n").unwrap();
+        std::fs::write(&files[1], "def query():\n    pass\n").unwrap();
+
+        let graph = CallGraph::build_indexed(root.path(), &files, &index);
+        assert_eq!(graph.functions.len(), 2);
+        let entries = || std::fs::read_dir(&index).unwrap().count();
+        assert_eq!(entries(), 2);
+
+        // An unchanged file is read from its entry, not parsed again.
+        let key = index_key(&files[1], "def query():\n    pass\n");
+        let entry = index.join(format!("{key}.json"));
+        let json = std::fs::read_to_string(&entry).unwrap();
+        std::fs::write(&entry, json.replace("\"query\"", "\"cached\"")).unwrap();
+        std::fs::write(&files[0], "def main():\n    cached()\n").unwrap();
+
+        let graph = CallGraph::build_indexed(root.path(), &files, &index);
+        assert!(
+            graph
+                .definitions("cached")
+                .iter()
+                .any(|f| f.file == "db.py")
+        );
+        assert!(
+            graph
+                .reachable_from(&["app.py".to_string()])
+                .contains("db.py")
+        );
+        assert_eq!(entries(), 2, "the old entry of app.py is pruned");
+    }
+
+    #[test]
+    fn paths_lists_bounded_call_chains_between_symbols() {
+        let (_root, graph) = graph(&[
+            (
+                "views.py",
+                "def handler_x(request):\n    find_user(request)\n    exec_sql(request)\n",
+            ),
+            (
+                "db.py",
+                "def find_user(name):\n    return exec_sql(name)\n\ndef exec_sql(q):\n    cursor.execute(q)\n",
+            ),
+        ]);
+
+        let paths = graph.paths("handler_x", "exec_sql", 8, 10);
+        let names: Vec<Vec<&str>> = paths
+            .iter()
+            .map(|p| p.iter().map(|s| s.name.as_str()).collect())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                vec!["handler_x", "exec_sql"],
+                vec!["handler_x", "find_user", "exec_sql"]
+            ]
+        );
+        assert_eq!((paths[0][1].file.as_str(), paths[0][1].line), ("db.py", 4));
+        assert_eq!(graph.paths("handler_x", "exec_sql", 1, 10).len(), 1);
+        assert_eq!(graph.paths("handler_x", "exec_sql", 8, 1).len(), 1);
+        assert!(graph.paths("exec_sql", "handler_x", 8, 10).is_empty());
+    }
+
+    #[test]
+    fn reachable_from_follows_calls_across_files() {
+        let (_root, graph) = graph(&[
+            ("app.py", "def main():\n    handle()\n"),
+            ("views.py", "def handle():\n    query()\n"),
+            ("db.py", "def query():\n    pass\n"),
+            ("admin.py", "def unused():\n    pass\n"),
+        ]);
+
+        let reached = graph.reachable_from(&["app.py".to_string()]);
+        assert!(reached.contains("views.py"));
+        assert!(reached.contains("db.py"));
+        assert!(!reached.contains("admin.py"));
+    }
+}
@@ -0,0 +1,169 @@
+//! Heuristics for calls that cross a language boundary.
+//!
+//! Name resolution cannot see that `fetch("/api/users")` in a frontend is
+//! served by the Go handler registered for `/api/users`, or that a Python
+//! view runs `scripts/deploy.sh`. Both show up as string literals: HTTP
+//! routes, matched against the routes handlers are registered for, and
+//! paths of scripts in the repository.
+
+use std::path::Path;
+
+/// Extensions of files another program may run as a script.
+const SCRIPT_EXTENSIONS: &[&str] = &[
+    "sh", "bash", "zsh", "py", "rb", "js", "mjs", "cjs", "ts", "pl", "php",
+];
+
+/// Methods that may prefix a route pattern, as in Go's `"GET /users"`.
+const HTTP_METHODS: &[&str] = &[
+    "GET", "HEAD", "POST", "PUT", "PATCH", "DELETE", "OPTIONS", "ANY",
+];
+
+/// A quoted string in source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Literal<'a> {
+    /// Offset of the opening quote.
+    pub start_byte: usize,
+    pub value: &'a str,
+}
+
+/// Quoted strings of `text`, in any of the common quoting styles. Quotes
+/// opened by `'` or `"` end at the line; backticks may span lines.
+pub(crate) fn string_literals(text: &str) -> Vec<Literal<'_>> {
+    let bytes = text.as_bytes();
+    let mut literals = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let quote = bytes[i];
+        if !matches!(quote, b'"' | b'\'' | b'`') {
+            i += 1;
+            continue;
+        }
+        let mut end = i + 1;
+        while end < bytes.len() && bytes[end] != quote {
+            if bytes[end] == b'\n' && quote != b'`' {
+                break;
+            }
+            end += if bytes[end] == b'\\' { 2 } else { 1 };
+        }
+        if end < bytes.len() && bytes[end] == quote {
+            literals.push(Literal {
+                start_byte: i,
+                value: &text[i + 1..end],
+            });
+            i = end + 1;
+        } else {
+            i += 1;
+        }
+    }
+    literals
+}
+
+/// Path segments of a route or URL literal, with parameters (`:id`,
+/// `{id}`, `<int:id>`, `${id}`, `*`) as `*`; `None` when it is not one.
+pub(crate) fn route_key(literal: &str) -> Option<Vec<String>> {
+    let mut route = literal.trim();
+    if let Some((method, rest)) = route.split_once(' ')
+        && HTTP_METHODS.contains(&method)
+    {
+        route = rest.trim_start();
+    }
+    if let Some(rest) = route
+        .strip_prefix("https://")
+        .or_else(|| route.strip_prefix("http://"))
+    {
+        route = rest.find('/').map_or("/", |i| &rest[i..]);
+    }
+    if !route.starts_with('/') || route.starts_with("//") || route.contains(char::is_whitespace) {
+        return None;
+    }
+    let route = route.split(['?', '#']).next().unwrap_or_default();
+    let segments: Vec<String> = route
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            let parameter = s.starts_with([':', '{', '<', '*']) || s.contains("${");
+            if parameter { "*" } else { s }.to_string()
+        })
+        .collect();
+    (!segments.is_empty()).then_some(segments)
+}
+
+/// Whether a request to route `a` could be served by route `b`.
+pub(crate) fn routes_match(a: &[String], b: &[String]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x == y || x == "*" || y == "*")
+}
+
+/// Whether `line` is a decorator or annotation, which registers the
+/// function defined right below it.
+pub(crate) fn is_annotation(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with('@') || line.starts_with("#[")
+}
+
+/// Identifiers of `text`, in order.
+pub(crate) fn identifiers(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|t| t.starts_with(|c: char| c.is_alphabetic() || c == '_'))
+}
+
+/// The script in `files` (repository-relative) that a literal in `file`
+/// names, relative to the repository root or to `file`'s directory.
+pub(crate) fn script_target<'a>(literal: &str, file: &str, files: &'a [String]) -> Option<&'a str> {
+    let literal = literal.trim().trim_start_matches("./");
+    let extension = Path::new(literal).extension()?.to_str()?;
+    if !SCRIPT_EXTENSIONS.contains(&extension) || literal.contains(char::is_whitespace) {
+        return None;
+    }
+    let sibling = match file.rsplit_once('/') {
+        Some((dir, _)) => format!("{dir}/{literal}"),
+        None => literal.to_string(),
+    };
+    files
+        .iter()
+        .find(|f| f.as_str() != file && (*f == literal || **f == sibling))
+        .map(String::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn route_keys_normalize_parameters_and_hosts() {
+        let key = |s| route_key(s).map(|k| k.join("/"));
+        assert_eq!(key("/api/users/<int:id>").as_deref(), Some("api/users/*"));
+        assert_eq!(key("GET /api/users/{id}").as_deref(), Some("api/users/*"));
+        assert_eq!(
+            key("https://example.com/api/users/${id}?full=1").as_deref(),
+            Some("api/users/*")
+        );
+        assert_eq!(key("/"), None);
+        assert_eq!(key("hello world"), None);
+        assert!(routes_match(
+            &route_key("/api/users/:id").unwrap(),
+            &route_key("/api/users/42").unwrap()
+        ));
+        assert!(!routes_match(
+            &route_key("/api/users").unwrap(),
+            &route_key("/api/users/42").unwrap()
+        ));
+    }
+
+    #[test]
+    fn literals_and_script_targets() {
+        let text = "run(\"./deploy.sh\", 'x\\'y')\nfetch(`/api/${id}`)\n";
+        let values: Vec<&str> = string_literals(text).iter().map(|l| l.value).collect();
+        assert_eq!(values, ["./deploy.sh", "x\\'y", "/api/${id}"]);
+
+        let files = [
+            "scripts/deploy.sh".to_string(),
+            "scripts/app.py".to_string(),
+        ];
+        assert_eq!(
+            script_target("./deploy.sh", "scripts/app.py", &files),
+            Some("scripts/deploy.sh")
+        );
+        assert_eq!(script_target("deploy.sh", "app.py", &files), None);
+        assert_eq!(script_target("config.json", "scripts/app.py", &files), None);
+    }
+}
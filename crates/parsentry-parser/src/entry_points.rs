@@ -0,0 +1,374 @@
+//! Framework entry points: HTTP route handlers, CLI commands and message
+//! consumers.
+//!
+//! Each language's `entry_points.scm` query captures the handler under a
+//! `<kind>.<framework>` name (`@route.flask`, `@consumer.spring`) and its
+//! name as `@name`; captures starting with `_` only serve predicates.
+//! GraphQL schemas have no grammar here; their root fields are read from
+//! the SDL instead.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use parsentry_core::graphql;
+use serde::Serialize;
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{Parser, Query, QueryCursor};
+
+use crate::call_graph::relative_path;
+use crate::parser::CodeParser;
+
+/// How code outside the repository reaches an [`EntryPoint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    /// Handles HTTP requests.
+    Route,
+    /// Runs from the command line.
+    Cli,
+    /// Handles messages from a queue or topic.
+    Consumer,
+}
+
+impl EntryKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            EntryKind::Route => "HTTP route",
+            EntryKind::Cli => "CLI entry point",
+            EntryKind::Consumer => "message consumer",
+        }
+    }
+
+    fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "route" => Some(EntryKind::Route),
+            "cli" => Some(EntryKind::Cli),
+            "consumer" => Some(EntryKind::Consumer),
+            _ => None,
+        }
+    }
+}
+
+/// A function that code outside the repository calls into.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EntryPoint {
+    pub kind: EntryKind,
+    pub framework: &'static str,
+    /// Handler name, or the route when the handler is inline.
+    pub name: String,
+    /// Path relative to the repository root.
+    pub file: String,
+    pub line: usize,
+}
+
+impl EntryPoint {
+    /// One-line Markdown description for prompts.
+    pub fn describe(&self) -> String {
+        format!(
+            "`{}` ({} {}) at `{}:{}`",
+            self.name,
+            self.framework,
+            self.kind.label(),
+            self.file,
+            self.line
+        )
+    }
+}
+
+fn framework_name(capture: &str) -> &'static str {
+    match capture {
+        "flask" => "Flask/FastAPI",
+        "django" => "Django",
+        "click" => "Click/Typer",
+        "celery" => "Celery",
+        "express" => "Express/Koa",
+        "commander" => "Commander",
+        "amqp" => "AMQP",
+        "kafkajs" => "KafkaJS",
+        "nestjs" => "NestJS",
+        "spring" => "Spring/JAX-RS",
+        "axum" => "axum",
+        "actix" => "actix-web/Rocket",
+        "http" => "net/http",
+        "script" => "script",
+        _ => "main",
+    }
+}
+
+/// Entry points in `files` (absolute paths under `root`), in file order.
+/// Files in languages without an entry-point query are skipped.
+pub fn detect_entry_points(root: &Path, files: &[PathBuf]) -> Vec<EntryPoint> {
+    let code_parser = CodeParser::default();
+    let mut parser = Parser::new();
+    let mut queries: Vec<(tree_sitter::Language, Option<Query>)> = Vec::new();
+    let mut entry_points = Vec::new();
+    for path in files {
+        let file = relative_path(root, path);
+        if graphql::is_graphql_file(&file) {
+            // Root fields of a schema are its entry points
+            let Ok(sdl) = std::fs::read_to_string(path) else {
+                continue;
+            };
+            entry_points.extend(graphql::root_fields(&file, &sdl).into_iter().map(|field| {
+                EntryPoint {
+                    kind: EntryKind::Route,
+                    framework: "GraphQL",
+                    name: field.name,
+                    file: field.file,
+                    line: field.line,
+                }
+            }));
+            continue;
+        }
+        let Some(language) = code_parser.get_language(path) else {
+            continue;
+        };
+        // Keyed by grammar: scripts without an extension share one
+        let index = match queries.iter().position(|(l, _)| *l == language) {
+            Some(index) => index,
+            None => {
+                let query = code_parser
+                    .get_query_content(&language, "entry_points")
+                    .ok()
+                    .and_then(|source| Query::new(&language, source).ok());
+                queries.push((language.clone(), query));
+                queries.len() - 1
+            }
+        };
+        let Some(query) = queries[index].1.as_ref() else {
+            continue;
+        };
+        let Ok(text) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        if parser.set_language(&language).is_err() {
+            continue;
+        }
+        let Some(tree) = parser.parse(&text, None) else {
+            continue;
+        };
+        let mut seen = HashSet::new();
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(query, tree.root_node(), text.as_bytes());
+        while let Some(m) = matches.next() {
+            let mut entry = None;
+            let mut name = None;
+            let mut route = None;
+            for capture in m.captures {
+                let capture_name = query.capture_names()[capture.index as usize];
+                let node_text = capture.node.utf8_text(text.as_bytes()).unwrap_or_default();
+                match capture_name {
+                    "name" => name = Some(node_text),
+                    "path" => route = Some(node_text),
+                    _ => {
+                        if let Some((kind, framework)) = capture_name.split_once('.')
+                            && let Some(kind) = EntryKind::parse(kind)
+                        {
+                            entry = Some((kind, framework_name(framework), capture.node));
+                        }
+                    }
+                }
+            }
+            let Some((kind, framework, node)) = entry else {
+                continue;
+            };
+            let is_identifier = |s: &&str| {
+                s.chars()
+                    .all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | ':' | '$'))
+            };
+            let name = name
+                .filter(is_identifier)
+                .or(route)
+                .or(name)
+                .map(|s| s.trim_matches(|c| matches!(c, '"' | '\'' | '`')))
+                .unwrap_or("main");
+            let name = name.lines().next().unwrap_or_default().to_string();
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+            entry_points.push(EntryPoint {
+                kind,
+                framework,
+                name,
+                file: file.clone(),
+                line: node.start_position().row + 1,
+            });
+        }
+    }
+    entry_points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(files: &[(&str, &str)]) -> Vec<(EntryKind, &'static str, String)> {
+        let dir = tempfile::tempdir().unwrap();
+        let paths: Vec<PathBuf> = files
+            .iter()
+            .map(|(name, source)| {
+                let path = dir.path().join(name);
+                std::fs::write(&path, source).unwrap();
+                path
+            })
+            .collect();
+        detect_entry_points(dir.path(), &paths)
+            .into_iter()
+            .map(|e| (e.kind, e.framework, e.name))
+            .collect()
+    }
+
+    #[test]
+    fn detects_python_entry_points() {
+        let found = detect(&[(
+            "app.py",
+            r#"
+@app.route("/users")
+def list_users():
+    pass
+
+@cli.command()
+def migrate():
+    pass
+
+@shared_task
+def send_mail(to):
+    pass
+
+def helper(x):
+    pass
+
+if __name__ == "__main__":
+    app.run()
+"#,
+        )]);
+        assert_eq!(
+            found,
+            vec![
+                (EntryKind::Route, "Flask/FastAPI", "list_users".to_string()),
+                (EntryKind::Cli, "Click/Typer", "migrate".to_string()),
+                (EntryKind::Consumer, "Celery", "send_mail".to_string()),
+                (EntryKind::Cli, "script", "__main__".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_express_routes_and_consumers() {
+        let found = detect(&[(
+            "server.js",
+            r#"
+app.get("/users", listUsers);
+app.post('/login', (req, res) => res.send("ok"));
+cache.get("key", fallback);
+channel.consume("orders", onOrder);
+"#,
+        )]);
+        assert_eq!(
+            found,
+            vec![
+                (EntryKind::Route, "Express/Koa", "listUsers".to_string()),
+                (EntryKind::Route, "Express/Koa", "/login".to_string()),
+                (EntryKind::Consumer, "AMQP", "onOrder".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_nestjs_handlers() {
+        let found = detect(&[(
+            "users.controller.ts",
+            r#"
+@Controller("users")
+export class UsersController {
+  @Get(":id")
+  findOne(@Param("id") id: string) {}
+
+  @EventPattern("user.created")
+  onCreated(data: unknown) {}
+
+  private helper() {}
+}
+"#,
+        )]);
+        assert_eq!(
+            found,
+            vec![
+                (EntryKind::Route, "NestJS", "findOne".to_string()),
+                (EntryKind::Consumer, "NestJS", "onCreated".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_graphql_root_fields() {
+        let found = detect(&[(
+            "schema.graphql",
+            "type Query {\n  users: [User!]!\n}\n\ntype Mutation {\n  deleteUser(id: ID!): Boolean\n}\n",
+        )]);
+        assert_eq!(
+            found,
+            vec![
+                (EntryKind::Route, "GraphQL", "Query.users".to_string()),
+                (
+                    EntryKind::Route,
+                    "GraphQL",
+                    "Mutation.deleteUser".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_spring_axum_and_go_handlers() {
+        let found = detect(&[
+            (
+                "UserController.java",
+                r#"
+class UserController {
+    @GetMapping("/users")
+    public List<User> list() { return repo.findAll(); }
+
+    @KafkaListener(topics = "orders")
+    void onOrder(String message) {}
+
+    private void helper() {}
+}
+"#,
+            ),
+            (
+                "main.rs",
+                r#"
+fn main() {
+    let app = Router::new().route("/users", get(list_users));
+}
+
+#[post("/login")]
+async fn login() {}
+"#,
+            ),
+            (
+                "server.go",
+                r#"
+package main
+
+func main() {
+    http.HandleFunc("/users", listUsers)
+}
+"#,
+            ),
+        ]);
+        assert_eq!(
+            found,
+            vec![
+                (EntryKind::Route, "Spring/JAX-RS", "list".to_string()),
+                (EntryKind::Consumer, "Spring/JAX-RS", "onOrder".to_string()),
+                (EntryKind::Cli, "main", "main".to_string()),
+                (EntryKind::Route, "axum", "list_users".to_string()),
+                (EntryKind::Route, "actix-web/Rocket", "login".to_string()),
+                (EntryKind::Cli, "main", "main".to_string()),
+                (EntryKind::Route, "net/http", "listUsers".to_string()),
+            ]
+        );
+    }
+}
@@ -2,15 +2,36 @@
 //!
 //! This crate provides:
 //! - Code parsing using tree-sitter grammars
+//! - A name-resolved call graph for prompt context
 //! - Security pattern matching for vulnerability detection
+//! - Principal-to-resource reachability over the call graph
+//! - Framework entry points (routes, CLI commands, message consumers)
+//! - Structural similarity of code snippets
 
+mod call_graph;
+mod cross_language;
+mod entry_points;
 mod parser;
+mod pattern_file;
+mod pattern_pack;
 mod patterns;
+mod reachability;
+mod similarity;
 
-pub use parser::{CodeParser, Context, Definition};
+pub use call_graph::{CallGraph, PathStep, Reach, RelatedFunction, Relation};
+pub use entry_points::{EntryKind, EntryPoint, detect_entry_points};
+pub use parser::{CodeParser, Context, Definition, GRAMMAR_EXTENSIONS};
+pub use pattern_file::{MergeStats, PatternFile};
+pub use pattern_pack::{
+    InstalledPack, PatternPack, Provenance, default_packs_dir, install_pack, installed_packs,
+};
 pub use patterns::{
-    LanguagePatterns, PatternConfig, PatternMatch, PatternQuery, SecurityRiskPatterns,
+    CUSTOM_PATTERNS_FILE, DISABLED_PATTERNS_ENV, LanguagePatterns, PatternConfig, PatternEntry,
+    PatternLintIssue, PatternMatch, PatternOrigin, PatternQuery, SanitizeReport,
+    SecurityRiskPatterns, disabled_patterns,
 };
+pub use reachability::{ReachableSink, reachable_sinks};
+pub use similarity::{Cluster, Shingles, cluster_snippets};
 
 // Re-export tree-sitter types for downstream crates
 pub use streaming_iterator::StreamingIterator;
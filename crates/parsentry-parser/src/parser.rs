@@ -1,6 +1,7 @@
 //! Code parser using tree-sitter.
 
 use anyhow::{Result, anyhow};
+use parsentry_core::{FileClassifier, Language as CoreLanguage};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -25,6 +26,11 @@ pub struct Context {
     pub references: Vec<Definition>,
 }
 
+/// One file extension per bundled tree-sitter grammar.
+pub const GRAMMAR_EXTENSIONS: &[&str] = &[
+    "c", "cpp", "py", "js", "ts", "tsx", "java", "rs", "go", "rb", "tf", "php",
+];
+
 /// Tree-sitter based code parser.
 pub struct CodeParser {
     pub files: HashMap<PathBuf, String>,
@@ -69,6 +75,19 @@ impl CodeParser {
             Some("php") | Some("php3") | Some("php4") | Some("php5") | Some("phtml") => {
                 Some(tree_sitter_php::LANGUAGE_PHP.into())
             }
+            _ => Self::language_from_content(path),
+        }
+    }
+
+    /// Grammar for a file without a known extension, from its shebang,
+    /// modeline or a language override.
+    fn language_from_content(path: &Path) -> Option<Language> {
+        match FileClassifier::classify_file(path) {
+            CoreLanguage::Python => Some(tree_sitter_python::LANGUAGE.into()),
+            CoreLanguage::JavaScript => Some(tree_sitter_javascript::LANGUAGE.into()),
+            CoreLanguage::TypeScript => Some(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
+            CoreLanguage::Ruby => Some(tree_sitter_ruby::LANGUAGE.into()),
+            CoreLanguage::Php => Some(tree_sitter_php::LANGUAGE_PHP.into()),
             _ => None,
         }
     }
@@ -131,16 +150,22 @@ impl CodeParser {
             ("cpp", "calls") => include_str!("queries/cpp/calls.scm"),
             ("python", "definitions") => include_str!("queries/python/definitions.scm"),
             ("python", "calls") => include_str!("queries/python/calls.scm"),
+            ("python", "entry_points") => include_str!("queries/python/entry_points.scm"),
             ("javascript", "definitions") => include_str!("queries/javascript/definitions.scm"),
             ("javascript", "calls") => include_str!("queries/javascript/calls.scm"),
+            ("javascript", "entry_points") => include_str!("queries/javascript/entry_points.scm"),
             ("typescript", "definitions") => include_str!("queries/typescript/definitions.scm"),
             ("typescript", "calls") => include_str!("queries/typescript/calls.scm"),
+            ("typescript", "entry_points") => include_str!("queries/typescript/entry_points.scm"),
             ("java", "definitions") => include_str!("queries/java/definitions.scm"),
             ("java", "calls") => include_str!("queries/java/calls.scm"),
+            ("java", "entry_points") => include_str!("queries/java/entry_points.scm"),
             ("go", "definitions") => include_str!("queries/go/definitions.scm"),
             ("go", "calls") => include_str!("queries/go/calls.scm"),
+            ("go", "entry_points") => include_str!("queries/go/entry_points.scm"),
             ("rust", "definitions") => include_str!("queries/rust/definitions.scm"),
             ("rust", "calls") => include_str!("queries/rust/calls.scm"),
+            ("rust", "entry_points") => include_str!("queries/rust/entry_points.scm"),
             ("ruby", "definitions") => include_str!("queries/ruby/definitions.scm"),
             ("ruby", "calls") => include_str!("queries/ruby/calls.scm"),
             ("terraform", "definitions") => include_str!("queries/terraform/definitions.scm"),
@@ -153,6 +178,35 @@ impl CodeParser {
         Ok(query_content)
     }
 
+    /// Load every bundled grammar and compile its queries, one result per
+    /// file extension in [`GRAMMAR_EXTENSIONS`].
+    pub fn check_grammars(&mut self) -> Vec<(&'static str, Result<()>)> {
+        GRAMMAR_EXTENSIONS
+            .iter()
+            .map(|ext| {
+                let result = self
+                    .get_language(Path::new(&format!("a.{ext}")))
+                    .ok_or_else(|| anyhow!("no grammar for .{ext}"))
+                    .and_then(|language| {
+                        self.parser
+                            .set_language(&language)
+                            .map_err(|e| anyhow!("Failed to set language: {}", e))?;
+                        for query in ["definitions", "calls"] {
+                            Query::new(&language, self.get_query_content(&language, query)?)
+                                .map_err(|e| anyhow!("{query} query does not compile: {e}"))?;
+                        }
+                        // Not every language has entry-point queries.
+                        if let Ok(source) = self.get_query_content(&language, "entry_points") {
+                            Query::new(&language, source)
+                                .map_err(|e| anyhow!("entry_points query does not compile: {e}"))?;
+                        }
+                        Ok(())
+                    });
+                (*ext, result)
+            })
+            .collect()
+    }
+
     /// Find a definition by name in a specific file.
     pub fn find_definition(
         &mut self,
@@ -330,8 +384,9 @@ impl CodeParser {
         Ok(results)
     }
 
-    /// Build context (definitions and references) from a file.
-    pub fn build_context_from_file(&mut self, start_path: &Path) -> Result<Context> {
+    /// Definitions and references of a single file, without following them
+    /// into other files.
+    pub fn file_context(&mut self, path: &Path) -> Result<Context> {
         use std::collections::HashSet;
 
         let mut collected: HashSet<String> = HashSet::new();
@@ -340,10 +395,10 @@ impl CodeParser {
 
         let file_content = self
             .files
-            .get(start_path)
-            .ok_or_else(|| anyhow!("File not found: {}", start_path.display()))?;
+            .get(path)
+            .ok_or_else(|| anyhow!("File not found: {}", path.display()))?;
 
-        let language = match self.get_language(start_path) {
+        let language = match self.get_language(path) {
             Some(lang) => lang,
             None => {
                 return Ok(Context {
@@ -360,7 +415,7 @@ impl CodeParser {
         let tree = self
             .parser
             .parse(file_content, None)
-            .ok_or_else(|| anyhow!("Failed to parse: {}", start_path.display()))?;
+            .ok_or_else(|| anyhow!("Failed to parse: {}", path.display()))?;
 
         let definitions_query_str = self.get_query_content(&language, "definitions")?;
         let definitions_query = Query::new(&language, definitions_query_str)?;
@@ -372,8 +427,6 @@ impl CodeParser {
             file_content.as_bytes(),
         );
 
-        let mut to_visit: Vec<(PathBuf, String)> = Vec::new();
-
         while let Some(mat) = matches.next() {
             let mut def_node: Option<Node> = None;
             let mut name_node: Option<Node> = None;
@@ -397,11 +450,10 @@ impl CodeParser {
                         start_byte,
                         end_byte,
                         source,
-                        file_path: Some(start_path.to_path_buf()),
+                        file_path: Some(path.to_path_buf()),
                         line_number: Some(line_number),
                     });
-                    collected.insert(name.clone());
-                    to_visit.push((start_path.to_path_buf(), name));
+                    collected.insert(name);
                 }
             }
         }
@@ -456,13 +508,33 @@ impl CodeParser {
                         start_byte,
                         end_byte,
                         source,
-                        file_path: Some(start_path.to_path_buf()),
+                        file_path: Some(path.to_path_buf()),
                         line_number: Some(line_number),
                     });
                 }
             }
         }
 
+        Ok(Context {
+            definitions,
+            references,
+        })
+    }
+
+    /// Build context (definitions and references) from a file.
+    pub fn build_context_from_file(&mut self, start_path: &Path) -> Result<Context> {
+        use std::collections::HashSet;
+
+        let Context {
+            mut definitions,
+            references,
+        } = self.file_context(start_path)?;
+        let mut collected: HashSet<String> = definitions.iter().map(|d| d.name.clone()).collect();
+        let mut to_visit: Vec<(PathBuf, String)> = definitions
+            .iter()
+            .map(|d| (start_path.to_path_buf(), d.name.clone()))
+            .collect();
+
         while let Some((file_path, func_name)) = to_visit.pop() {
             if let Some((_, def)) = self.find_definition(&func_name, &file_path)? {
                 let refs = self.find_calls(&def.name)?;
@@ -491,3 +563,16 @@ impl Default for CodeParser {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_queries_compile() {
+        let mut parser = CodeParser::default();
+        for (ext, result) in parser.check_grammars() {
+            assert!(result.is_ok(), ".{ext}: {result:?}");
+        }
+    }
+}
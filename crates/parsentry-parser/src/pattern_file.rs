@@ -0,0 +1,203 @@
+//! On-disk pattern file (`vuln-patterns.yml`) model.
+//!
+//! The file maps language names (`Python`, `GitLabCI`, ...) to
+//! [`LanguagePatterns`]. Keys are kept as written so that a load/save round
+//! trip does not rename sections.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::patterns::{LanguagePatterns, PatternConfig};
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct PatternFile {
+    pub languages: BTreeMap<String, LanguagePatterns>,
+}
+
+/// Counts returned by [`PatternFile::merge`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MergeStats {
+    pub added: usize,
+    pub updated: usize,
+}
+
+impl PatternFile {
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(content)?)
+    }
+
+    pub fn to_yaml(&self) -> Result<String> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::from_yaml(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Load `path`, or an empty file if it does not exist yet.
+    pub fn load_or_default(path: &Path) -> Result<Self> {
+        if path.exists() {
+            Self::load(path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_yaml()?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Insert a pattern, replacing an existing one with the same query.
+    /// Returns `true` when the pattern was new.
+    pub fn upsert(&mut self, language: &str, category: &str, config: PatternConfig) -> bool {
+        let patterns = self
+            .languages
+            .entry(language.to_string())
+            .or_insert_with(|| LanguagePatterns {
+                principals: None,
+                actions: None,
+                resources: None,
+            });
+        let list = patterns.category_mut(category);
+
+        let key = query_key(&config);
+        match list.iter_mut().find(|c| query_key(c) == key) {
+            Some(existing) => {
+                *existing = config;
+                false
+            }
+            None => {
+                list.push(config);
+                true
+            }
+        }
+    }
+
+    /// Merge `other` into `self`, deduplicating by query text.
+    pub fn merge(&mut self, other: PatternFile) -> MergeStats {
+        let mut stats = MergeStats::default();
+        for (language, patterns) in other.languages {
+            for (category, config) in patterns.iter() {
+                if self.upsert(&language, category, config.clone()) {
+                    stats.added += 1;
+                } else {
+                    stats.updated += 1;
+                }
+            }
+        }
+        stats
+    }
+
+    /// Total number of patterns across all languages.
+    pub fn len(&self) -> usize {
+        self.languages.values().map(|p| p.iter().count()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Queries that differ only in whitespace are the same pattern.
+fn query_key(config: &PatternConfig) -> (&'static str, String) {
+    (
+        config.pattern_type.kind(),
+        config
+            .pattern_type
+            .query()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns::PatternQuery;
+
+    fn config(query: &str, description: &str) -> PatternConfig {
+        PatternConfig {
+            pattern_type: PatternQuery::Reference {
+                reference: query.to_string(),
+            },
+            description: description.to_string(),
+            attack_vector: vec!["T1059".to_string()],
+            severity: None,
+            enabled: true,
+            tags: Vec::new(),
+            languages: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_round_trip_preserves_quotes_and_colons() {
+        let mut file = PatternFile::default();
+        file.upsert(
+            "Python",
+            "resources",
+            config(
+                "(call\n  function: (identifier) @f (#eq? @f \"eval\")) @call",
+                "eval(): \"dynamic\" code: 'danger'",
+            ),
+        );
+        file.upsert("GitLabCI", "principals", config("(block_mapping) @m", "x"));
+
+        let yaml = file.to_yaml().unwrap();
+        let parsed = PatternFile::from_yaml(&yaml).unwrap();
+        assert_eq!(parsed, file);
+        assert!(parsed.languages.contains_key("GitLabCI"));
+    }
+
+    #[test]
+    fn test_merge_dedups_by_query() {
+        let mut base = PatternFile::default();
+        base.upsert("Python", "resources", config("(call) @c", "old"));
+
+        let mut incoming = PatternFile::default();
+        incoming.upsert("Python", "resources", config("(call)   @c", "new"));
+        incoming.upsert("Python", "actions", config("(string) @s", "strings"));
+
+        let stats = base.merge(incoming);
+        assert_eq!(
+            stats,
+            MergeStats {
+                added: 1,
+                updated: 1
+            }
+        );
+        assert_eq!(base.len(), 2);
+        let resources = base.languages["Python"].resources.as_ref().unwrap();
+        assert_eq!(resources[0].description, "new");
+    }
+
+    #[test]
+    fn test_builtin_pattern_yaml_round_trips() {
+        let builtin: LanguagePatterns =
+            serde_yaml::from_str(include_str!("patterns/python.yml")).unwrap();
+        let mut file = PatternFile::default();
+        file.languages.insert("Python".to_string(), builtin);
+        assert_eq!(
+            PatternFile::from_yaml(&file.to_yaml().unwrap()).unwrap(),
+            file
+        );
+    }
+
+    #[test]
+    fn test_load_save() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("vuln-patterns.yml");
+        assert!(PatternFile::load_or_default(&path).unwrap().is_empty());
+
+        let mut file = PatternFile::default();
+        file.upsert("Go", "actions", config("(call_expression) @c", "calls"));
+        file.save(&path).unwrap();
+        assert_eq!(PatternFile::load(&path).unwrap(), file);
+    }
+}
@@ -0,0 +1,144 @@
+//! Installable pattern packs.
+//!
+//! A pack is a versioned YAML bundle:
+//!
+//! ```yaml
+//! name: django
+//! version: 1.2.0
+//! description: Django views and ORM sinks
+//! patterns:
+//!   Python:
+//!     principals: [...]
+//! ```
+//!
+//! Installed packs live in `<packs dir>/<name>/pack.yml` next to a
+//! `provenance.yml` recording where the pack came from, and are merged with
+//! the built-in patterns by [`crate::SecurityRiskPatterns`].
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::pattern_file::PatternFile;
+
+const PACK_FILE: &str = "pack.yml";
+const PROVENANCE_FILE: &str = "provenance.yml";
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct PatternPack {
+    pub name: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    pub patterns: PatternFile,
+}
+
+/// Where an installed pack came from.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Provenance {
+    /// URL, git repository or path the pack was installed from.
+    pub source: String,
+    pub version: String,
+    /// SHA-256 of the fetched bundle.
+    pub sha256: String,
+    pub installed_at: String,
+}
+
+/// A pack on disk together with its provenance.
+#[derive(Debug, Clone)]
+pub struct InstalledPack {
+    pub pack: PatternPack,
+    pub provenance: Provenance,
+}
+
+impl PatternPack {
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        let pack: Self = serde_yaml::from_str(content).context("invalid pattern pack")?;
+        if pack.name.is_empty()
+            || !pack
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            bail!(
+                "invalid pack name `{}` (use letters, digits, - and _)",
+                pack.name
+            );
+        }
+        Ok(pack)
+    }
+}
+
+/// User-level pack directory: `PARSENTRY_PATTERNS_DIR`, else
+/// `<data dir>/parsentry/patterns`.
+#[must_use]
+pub fn default_packs_dir() -> Option<PathBuf> {
+    match std::env::var_os("PARSENTRY_PATTERNS_DIR") {
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => dirs::data_dir().map(|d| d.join("parsentry").join("patterns")),
+    }
+}
+
+/// Write a pack and its provenance, replacing any previous version.
+pub fn install_pack(dir: &Path, pack: &PatternPack, provenance: &Provenance) -> Result<PathBuf> {
+    let pack_dir = dir.join(&pack.name);
+    std::fs::create_dir_all(&pack_dir)
+        .with_context(|| format!("cannot create {}", pack_dir.display()))?;
+    std::fs::write(pack_dir.join(PACK_FILE), serde_yaml::to_string(pack)?)?;
+    std::fs::write(
+        pack_dir.join(PROVENANCE_FILE),
+        serde_yaml::to_string(provenance)?,
+    )?;
+    Ok(pack_dir)
+}
+
+/// All packs installed in `dir`, sorted by name. Unreadable packs are skipped.
+pub fn installed_packs(dir: &Path) -> Vec<InstalledPack> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut packs: Vec<InstalledPack> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let pack_dir = e.path();
+            let pack = std::fs::read_to_string(pack_dir.join(PACK_FILE)).ok()?;
+            let provenance = std::fs::read_to_string(pack_dir.join(PROVENANCE_FILE)).ok()?;
+            Some(InstalledPack {
+                pack: PatternPack::from_yaml(&pack).ok()?,
+                provenance: serde_yaml::from_str(&provenance).ok()?,
+            })
+        })
+        .collect();
+    packs.sort_by(|a, b| a.pack.name.cmp(&b.pack.name));
+    packs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACK: &str = "name: demo\nversion: 1.0.0\npatterns:\n  Python:\n    resources:\n      - reference: \"(call) @c\"\n        description: calls\n        attack_vector: []\n";
+
+    #[test]
+    fn test_install_and_list() {
+        let tmp = tempfile::tempdir().unwrap();
+        let pack = PatternPack::from_yaml(PACK).unwrap();
+        let provenance = Provenance {
+            source: "https://example.com/demo.yml".to_string(),
+            version: pack.version.clone(),
+            sha256: "00".to_string(),
+            installed_at: "2026-01-01T00:00:00Z".to_string(),
+        };
+        install_pack(tmp.path(), &pack, &provenance).unwrap();
+
+        let installed = installed_packs(tmp.path());
+        assert_eq!(installed.len(), 1);
+        assert_eq!(installed[0].pack, pack);
+        assert_eq!(installed[0].provenance, provenance);
+    }
+
+    #[test]
+    fn test_rejects_path_like_names() {
+        assert!(PatternPack::from_yaml(&PACK.replace("demo", "../evil")).is_err());
+    }
+}
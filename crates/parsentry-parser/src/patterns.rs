@@ -1,49 +1,215 @@
 //! Security pattern matching for vulnerability detection.
 
-use parsentry_core::Language;
-use serde::Deserialize;
+use parsentry_core::{Language, Severity};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use streaming_iterator::StreamingIterator;
 use tree_sitter::{Language as TreeSitterLanguage, Parser, Query, QueryCursor};
 
+use crate::pattern_file::PatternFile;
+use crate::pattern_pack::{default_packs_dir, installed_packs};
+
+/// Environment variable listing patterns to skip, comma-separated.
+/// Entries match a pattern's description (case-insensitive) or one of its tags.
+pub const DISABLED_PATTERNS_ENV: &str = "PARSENTRY_DISABLED_PATTERNS";
+
 /// Configuration for a security pattern.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct PatternConfig {
     #[serde(flatten)]
     pub pattern_type: PatternQuery,
     pub description: String,
     pub attack_vector: Vec<String>,
+    /// Minimum severity of findings led by this pattern.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    #[serde(default = "enabled_by_default", skip_serializing_if = "is_enabled")]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Other languages the pattern also applies to (pattern file names,
+    /// e.g. `TypeScript` on a `JavaScript` pattern).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+fn is_enabled(enabled: &bool) -> bool {
+    *enabled
+}
+
+impl PatternConfig {
+    /// Whether `name` refers to this pattern by description or tag.
+    #[must_use]
+    pub fn is_named(&self, name: &str) -> bool {
+        self.description.eq_ignore_ascii_case(name)
+            || self.tags.iter().any(|t| t.eq_ignore_ascii_case(name))
+    }
+
+    /// Whether the pattern should be matched, given the disabled names.
+    #[must_use]
+    pub fn is_active(&self, disabled: &[String]) -> bool {
+        self.enabled && !disabled.iter().any(|d| self.is_named(d))
+    }
+}
+
+/// Names listed in [`DISABLED_PATTERNS_ENV`].
+#[must_use]
+pub fn disabled_patterns() -> Vec<String> {
+    std::env::var(DISABLED_PATTERNS_ENV)
+        .map(|v| {
+            v.split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
 /// Query type for pattern matching.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum PatternQuery {
     Definition { definition: String },
     Reference { reference: String },
 }
 
+impl PatternQuery {
+    /// The tree-sitter query source.
+    #[must_use]
+    pub fn query(&self) -> &str {
+        match self {
+            PatternQuery::Definition { definition } => definition,
+            PatternQuery::Reference { reference } => reference,
+        }
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PatternQuery::Definition { .. } => "definition",
+            PatternQuery::Reference { .. } => "reference",
+        }
+    }
+}
+
 /// Language-specific patterns configuration.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct LanguagePatterns {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub principals: Option<Vec<PatternConfig>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub actions: Option<Vec<PatternConfig>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub resources: Option<Vec<PatternConfig>>,
 }
 
+impl LanguagePatterns {
+    /// Patterns of all categories with their category name.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &PatternConfig)> {
+        [
+            ("principals", &self.principals),
+            ("actions", &self.actions),
+            ("resources", &self.resources),
+        ]
+        .into_iter()
+        .flat_map(|(category, configs)| configs.iter().flatten().map(move |c| (category, c)))
+    }
+
+    /// Patterns of one category, created empty if missing. Unknown
+    /// categories map to `resources`.
+    pub(crate) fn category_mut(&mut self, category: &str) -> &mut Vec<PatternConfig> {
+        match category {
+            "principals" => &mut self.principals,
+            "actions" => &mut self.actions,
+            _ => &mut self.resources,
+        }
+        .get_or_insert_with(Vec::new)
+    }
+
+    fn extend(&mut self, other: LanguagePatterns) {
+        for (mine, theirs) in [
+            (&mut self.principals, other.principals),
+            (&mut self.actions, other.actions),
+            (&mut self.resources, other.resources),
+        ] {
+            if let Some(theirs) = theirs {
+                mine.get_or_insert_with(Vec::new).extend(theirs);
+            }
+        }
+    }
+}
+
+/// File name of the per-repository custom pattern file.
+pub const CUSTOM_PATTERNS_FILE: &str = "vuln-patterns.yml";
+
+/// Where a pattern was loaded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternOrigin {
+    BuiltIn,
+    /// Installed with `parsentry patterns install`.
+    Pack,
+    Custom,
+}
+
+impl std::fmt::Display for PatternOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatternOrigin::BuiltIn => write!(f, "built-in"),
+            PatternOrigin::Pack => write!(f, "pack"),
+            PatternOrigin::Custom => write!(f, "custom"),
+        }
+    }
+}
+
+/// A pattern together with its language, category and origin.
+#[derive(Debug, Clone)]
+pub struct PatternEntry {
+    pub language: Language,
+    pub category: &'static str,
+    pub origin: PatternOrigin,
+    pub config: PatternConfig,
+}
+
+/// A pattern whose query does not compile for its language.
+#[derive(Debug, Clone)]
+pub struct PatternLintIssue {
+    pub entry: PatternEntry,
+    pub error: String,
+}
+
+/// Result of [`SecurityRiskPatterns::sanitize_pattern_file`].
+#[derive(Debug, Default)]
+pub struct SanitizeReport {
+    /// Patterns left unchanged.
+    pub kept: usize,
+    /// Descriptions of patterns whose query was repaired.
+    pub repaired: Vec<String>,
+    /// Patterns dropped because their query could not be repaired.
+    pub rejected: Vec<PatternLintIssue>,
+}
+
 /// Security risk pattern matcher.
 pub struct SecurityRiskPatterns {
     definition_queries: Vec<Query>,
     reference_queries: Vec<Query>,
     language: TreeSitterLanguage,
     pattern_configs: Vec<PatternConfig>,
+    /// PAR category of each entry of `pattern_configs`.
+    pattern_categories: Vec<&'static str>,
 }
 
 /// A matched security pattern.
 #[derive(Debug, Clone)]
 pub struct PatternMatch {
     pub pattern_config: PatternConfig,
+    /// PAR category of the pattern: `principals`, `actions` or `resources`.
+    pub category: &'static str,
     pub start_byte: usize,
     pub end_byte: usize,
     pub matched_text: String,
@@ -74,29 +240,25 @@ impl SecurityRiskPatterns {
         let mut definition_queries = Vec::new();
         let mut reference_queries = Vec::new();
         let mut pattern_configs = Vec::new();
+        let mut pattern_categories = Vec::new();
 
-        // Collect all patterns from principals, actions, and resources into a flat list
-        let all_configs: Vec<&PatternConfig> = lang_patterns
-            .principals
-            .iter()
-            .chain(lang_patterns.actions.iter())
-            .chain(lang_patterns.resources.iter())
-            .flat_map(|v| v.iter())
-            .collect();
-
-        for config in all_configs {
+        // Disabled configs and configs whose query does not compile are
+        // skipped entirely so that query indices stay aligned with
+        // `pattern_configs`; `lint_patterns` reports the invalid ones.
+        let disabled = disabled_patterns();
+        // Principals, actions and resources, flattened in that order.
+        for (category, config) in lang_patterns.iter() {
+            if !config.is_active(&disabled) {
+                continue;
+            }
+            let Ok(query) = Query::new(&ts_language, config.pattern_type.query()) else {
+                continue;
+            };
             pattern_configs.push(config.clone());
+            pattern_categories.push(category);
             match &config.pattern_type {
-                PatternQuery::Definition { definition } => {
-                    if let Ok(query) = Query::new(&ts_language, definition) {
-                        definition_queries.push(query);
-                    }
-                }
-                PatternQuery::Reference { reference } => {
-                    if let Ok(query) = Query::new(&ts_language, reference) {
-                        reference_queries.push(query);
-                    }
-                }
+                PatternQuery::Definition { .. } => definition_queries.push(query),
+                PatternQuery::Reference { .. } => reference_queries.push(query),
             }
         }
 
@@ -105,6 +267,7 @@ impl SecurityRiskPatterns {
             reference_queries,
             language: ts_language,
             pattern_configs,
+            pattern_categories,
         }
     }
 
@@ -299,7 +462,9 @@ impl SecurityRiskPatterns {
 
                         // Find the matching config by counting definition/reference queries
                         let mut config_idx = 0;
-                        for config in &self.pattern_configs {
+                        for (config, category) in
+                            self.pattern_configs.iter().zip(&self.pattern_categories)
+                        {
                             let matches_type = matches!(
                                 (&config.pattern_type, is_definition),
                                 (PatternQuery::Definition { .. }, true)
@@ -310,6 +475,7 @@ impl SecurityRiskPatterns {
                                 if config_idx == query_idx {
                                     pattern_matches.push(PatternMatch {
                                         pattern_config: config.clone(),
+                                        category,
                                         start_byte,
                                         end_byte,
                                         matched_text: best_text.clone(),
@@ -331,6 +497,31 @@ impl SecurityRiskPatterns {
     }
 
     fn load_patterns(root_dir: Option<&Path>) -> HashMap<Language, LanguagePatterns> {
+        let mut map = Self::builtin_patterns();
+        merge_by_language(&mut map, Self::pack_patterns());
+        match Self::custom_patterns(root_dir) {
+            Ok(custom) => merge_by_language(&mut map, custom),
+            Err(e) => eprintln!("{e}"),
+        }
+        share_across_languages(&mut map);
+        map
+    }
+
+    /// Patterns from packs installed in the user-level pack directory.
+    #[must_use]
+    pub fn pack_patterns() -> HashMap<Language, LanguagePatterns> {
+        let mut map = HashMap::new();
+        if let Some(dir) = default_packs_dir() {
+            for installed in installed_packs(&dir) {
+                merge_by_language(&mut map, by_language(installed.pack.patterns));
+            }
+        }
+        map
+    }
+
+    /// Patterns shipped with Parsentry, keyed by language.
+    #[must_use]
+    pub fn builtin_patterns() -> HashMap<Language, LanguagePatterns> {
         use Language::*;
 
         let mut map = HashMap::new();
@@ -367,6 +558,7 @@ impl SecurityRiskPatterns {
             include_str!("patterns/circleci.yml"),
             include_str!("patterns/travis.yml"),
             include_str!("patterns/jenkins.yml"),
+            include_str!("patterns/helm.yml"),
         ];
 
         let mut merged_yaml_patterns = LanguagePatterns {
@@ -377,37 +569,179 @@ impl SecurityRiskPatterns {
 
         for content in cicd_patterns {
             if let Ok(patterns) = serde_yaml::from_str::<LanguagePatterns>(content) {
-                if let Some(principals) = patterns.principals {
-                    merged_yaml_patterns
-                        .principals
-                        .as_mut()
-                        .unwrap()
-                        .extend(principals);
-                }
-                if let Some(actions) = patterns.actions {
-                    merged_yaml_patterns
-                        .actions
-                        .as_mut()
-                        .unwrap()
-                        .extend(actions);
-                }
-                if let Some(resources) = patterns.resources {
-                    merged_yaml_patterns
-                        .resources
-                        .as_mut()
-                        .unwrap()
-                        .extend(resources);
-                }
+                merged_yaml_patterns.extend(patterns);
             }
         }
 
         map.insert(Yaml, merged_yaml_patterns);
 
-        Self::load_custom_patterns(&mut map, root_dir);
-
         map
     }
 
+    /// Path of the custom pattern file for `root_dir` (or the current directory).
+    #[must_use]
+    pub fn custom_patterns_path(root_dir: Option<&Path>) -> PathBuf {
+        match root_dir {
+            Some(root) => root.join(CUSTOM_PATTERNS_FILE),
+            None => PathBuf::from(CUSTOM_PATTERNS_FILE),
+        }
+    }
+
+    /// Custom patterns from `vuln-patterns.yml`; empty when the file is absent.
+    pub fn custom_patterns(
+        root_dir: Option<&Path>,
+    ) -> anyhow::Result<HashMap<Language, LanguagePatterns>> {
+        let path = Self::custom_patterns_path(root_dir);
+        if !path.exists() {
+            return Ok(HashMap::new());
+        }
+        Self::read_pattern_file(&path)
+    }
+
+    /// Parse a pattern file keyed by language name (e.g. `Python:`).
+    pub fn read_pattern_file(path: &Path) -> anyhow::Result<HashMap<Language, LanguagePatterns>> {
+        Ok(by_language(PatternFile::load(path)?))
+    }
+
+    /// Flatten a pattern file into entries (origin [`PatternOrigin::Pack`]).
+    #[must_use]
+    pub fn entries_of(file: &PatternFile) -> Vec<PatternEntry> {
+        by_language(file.clone())
+            .into_iter()
+            .flat_map(|(language, patterns)| {
+                patterns
+                    .iter()
+                    .map(|(category, config)| PatternEntry {
+                        language,
+                        category,
+                        origin: PatternOrigin::Pack,
+                        config: config.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Built-in, pack and custom patterns as a flat list, sorted by language.
+    pub fn list_patterns(root_dir: Option<&Path>) -> anyhow::Result<Vec<PatternEntry>> {
+        let mut entries = Vec::new();
+        for (origin, map) in [
+            (PatternOrigin::BuiltIn, Self::builtin_patterns()),
+            (PatternOrigin::Pack, Self::pack_patterns()),
+            (PatternOrigin::Custom, Self::custom_patterns(root_dir)?),
+        ] {
+            for (language, patterns) in &map {
+                entries.extend(patterns.iter().map(|(category, config)| PatternEntry {
+                    language: *language,
+                    category,
+                    origin,
+                    config: config.clone(),
+                }));
+            }
+        }
+        entries.sort_by_key(|e| (e.language.display_name(), e.origin as u8));
+        Ok(entries)
+    }
+
+    /// Compile a query for `language`, returning the tree-sitter error text.
+    pub fn validate_query(language: Language, query: &str) -> Result<(), String> {
+        Query::new(&Self::get_tree_sitter_language(language), query)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Return `query` if it compiles, otherwise a mechanically repaired
+    /// version (code fences stripped, parentheses balanced) if that compiles.
+    #[must_use]
+    pub fn repair_query(language: Language, query: &str) -> Option<String> {
+        if Self::validate_query(language, query).is_ok() {
+            return Some(query.to_string());
+        }
+        let repaired = balance_parens(&strip_code_fences(query));
+        Self::validate_query(language, &repaired)
+            .is_ok()
+            .then_some(repaired)
+    }
+
+    /// Validate every query in a pattern file, repairing what can be
+    /// repaired and dropping the rest. The file is rewritten only when
+    /// something changed.
+    pub fn sanitize_pattern_file(path: &Path) -> anyhow::Result<SanitizeReport> {
+        let mut file = PatternFile::load(path)?;
+        let mut report = SanitizeReport::default();
+
+        for (lang_name, patterns) in file.languages.iter_mut() {
+            let Some(language) = custom_language(lang_name) else {
+                report.kept += patterns.iter().count();
+                continue;
+            };
+            for (category, configs) in [
+                ("principals", &mut patterns.principals),
+                ("actions", &mut patterns.actions),
+                ("resources", &mut patterns.resources),
+            ] {
+                let Some(configs) = configs else { continue };
+                configs.retain_mut(|config| {
+                    let query = config.pattern_type.query().to_string();
+                    match Self::repair_query(language, &query) {
+                        Some(fixed) if fixed == query => {
+                            report.kept += 1;
+                            true
+                        }
+                        Some(fixed) => {
+                            config.pattern_type = match config.pattern_type {
+                                PatternQuery::Definition { .. } => {
+                                    PatternQuery::Definition { definition: fixed }
+                                }
+                                PatternQuery::Reference { .. } => {
+                                    PatternQuery::Reference { reference: fixed }
+                                }
+                            };
+                            report.repaired.push(config.description.clone());
+                            true
+                        }
+                        None => {
+                            let error = Self::validate_query(language, &query)
+                                .err()
+                                .unwrap_or_default();
+                            report.rejected.push(PatternLintIssue {
+                                entry: PatternEntry {
+                                    language,
+                                    category,
+                                    origin: PatternOrigin::Custom,
+                                    config: config.clone(),
+                                },
+                                error,
+                            });
+                            false
+                        }
+                    }
+                });
+            }
+        }
+
+        if !report.repaired.is_empty() || !report.rejected.is_empty() {
+            file.save(path)?;
+        }
+        Ok(report)
+    }
+
+    /// Patterns whose query fails to compile.
+    #[must_use]
+    pub fn lint_patterns(entries: &[PatternEntry]) -> Vec<PatternLintIssue> {
+        entries
+            .iter()
+            .filter_map(|entry| {
+                Self::validate_query(entry.language, entry.config.pattern_type.query())
+                    .err()
+                    .map(|error| PatternLintIssue {
+                        entry: entry.clone(),
+                        error,
+                    })
+            })
+            .collect()
+    }
+
     /// Add dynamic queries (e.g. from threat model) at runtime.
     /// `query_type` is "definition" or "reference".
     pub fn add_query(
@@ -437,6 +771,10 @@ impl SecurityRiskPatterns {
             pattern_type: pattern_query,
             description: description.to_string(),
             attack_vector,
+            severity: None,
+            enabled: true,
+            tags: Vec::new(),
+            languages: Vec::new(),
         };
 
         self.pattern_configs.push(config);
@@ -449,89 +787,393 @@ impl SecurityRiskPatterns {
 
         true
     }
+}
 
-    fn load_custom_patterns(
-        map: &mut HashMap<Language, LanguagePatterns>,
-        root_dir: Option<&Path>,
-    ) {
-        let vuln_patterns_path = if let Some(root) = root_dir {
-            root.join("vuln-patterns.yml")
-        } else {
-            Path::new("vuln-patterns.yml").to_path_buf()
-        };
+/// Key a pattern file by [`Language`], merging sections that map to the
+/// same language (e.g. `GitLabCI` and `CircleCI` both become YAML).
+fn by_language(file: PatternFile) -> HashMap<Language, LanguagePatterns> {
+    let mut map = HashMap::new();
+    for (lang_name, patterns) in file.languages {
+        if let Some(language) = custom_language(&lang_name) {
+            merge_by_language(&mut map, HashMap::from([(language, patterns)]));
+        }
+    }
+    map
+}
 
-        if vuln_patterns_path.exists() {
-            match std::fs::read_to_string(&vuln_patterns_path) {
-                Ok(content) => {
-                    match serde_yaml::from_str::<HashMap<String, LanguagePatterns>>(&content) {
-                        Ok(custom_patterns) => {
-                            for (lang_name, patterns) in custom_patterns {
-                                let language = match lang_name.as_str() {
-                                    "Python" => Language::Python,
-                                    "JavaScript" => Language::JavaScript,
-                                    "TypeScript" => Language::TypeScript,
-                                    "Rust" => Language::Rust,
-                                    "Java" => Language::Java,
-                                    "Go" => Language::Go,
-                                    "Ruby" => Language::Ruby,
-                                    "C" => Language::C,
-                                    "Cpp" => Language::Cpp,
-                                    "Terraform" => Language::Terraform,
-                                    "CloudFormation" => Language::CloudFormation,
-                                    "Kubernetes" => Language::Kubernetes,
-                                    "YAML" => Language::Yaml,
-                                    "GitLabCI" => Language::Yaml,
-                                    "CircleCI" => Language::Yaml,
-                                    "TravisCI" => Language::Yaml,
-                                    "Jenkins" => Language::Yaml,
-                                    "Bash" => Language::Bash,
-                                    "Shell" => Language::Shell,
-                                    "Php" | "PHP" => Language::Php,
-                                    _ => continue,
-                                };
-
-                                match map.get_mut(&language) {
-                                    Some(existing) => {
-                                        if let Some(custom_principals) = patterns.principals {
-                                            match &mut existing.principals {
-                                                Some(principals) => {
-                                                    principals.extend(custom_principals)
-                                                }
-                                                None => {
-                                                    existing.principals = Some(custom_principals)
-                                                }
-                                            }
-                                        }
-                                        if let Some(custom_actions) = patterns.actions {
-                                            match &mut existing.actions {
-                                                Some(actions) => actions.extend(custom_actions),
-                                                None => existing.actions = Some(custom_actions),
-                                            }
-                                        }
-                                        if let Some(custom_resources) = patterns.resources {
-                                            match &mut existing.resources {
-                                                Some(resources) => {
-                                                    resources.extend(custom_resources)
-                                                }
-                                                None => existing.resources = Some(custom_resources),
-                                            }
-                                        }
-                                    }
-                                    None => {
-                                        map.insert(language, patterns);
-                                    }
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            eprintln!("Failed to parse vuln-patterns.yml: {}", e);
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Failed to read vuln-patterns.yml: {}", e);
+fn merge_by_language(
+    map: &mut HashMap<Language, LanguagePatterns>,
+    other: HashMap<Language, LanguagePatterns>,
+) {
+    for (language, patterns) in other {
+        match map.get_mut(&language) {
+            Some(existing) => existing.extend(patterns),
+            None => {
+                map.insert(language, patterns);
+            }
+        }
+    }
+}
+
+/// Copy patterns that list extra `languages` into those languages.
+fn share_across_languages(map: &mut HashMap<Language, LanguagePatterns>) {
+    let mut shared = Vec::new();
+    for (language, patterns) in map.iter() {
+        for (category, config) in patterns.iter() {
+            for target in config.languages.iter().filter_map(|l| custom_language(l)) {
+                if target != *language {
+                    shared.push((target, category, config.clone()));
                 }
             }
         }
     }
+    for (language, category, config) in shared {
+        map.entry(language)
+            .or_insert_with(|| LanguagePatterns {
+                principals: None,
+                actions: None,
+                resources: None,
+            })
+            .category_mut(category)
+            .push(config);
+    }
+}
+
+/// Drop Markdown code fence lines that LLMs like to wrap queries in.
+fn strip_code_fences(query: &str) -> String {
+    query
+        .lines()
+        .filter(|l| !l.trim_start().starts_with("```"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Append missing `)` or trim surplus trailing `)`, ignoring string literals.
+fn balance_parens(query: &str) -> String {
+    let mut depth: i64 = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in query.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => depth -= 1,
+            _ => {}
+        }
+    }
+    let mut out = query.trim_end().to_string();
+    while depth < 0 && out.ends_with(')') {
+        out.pop();
+        out = out.trim_end().to_string();
+        depth += 1;
+    }
+    for _ in 0..depth.max(0) {
+        out.push(')');
+    }
+    out
+}
+
+/// Map a language key in `vuln-patterns.yml` to a [`Language`].
+fn custom_language(name: &str) -> Option<Language> {
+    Some(match name {
+        "Python" => Language::Python,
+        "JavaScript" => Language::JavaScript,
+        "TypeScript" => Language::TypeScript,
+        "Rust" => Language::Rust,
+        "Java" => Language::Java,
+        "Go" => Language::Go,
+        "Ruby" => Language::Ruby,
+        "C" => Language::C,
+        "Cpp" => Language::Cpp,
+        "Terraform" => Language::Terraform,
+        "CloudFormation" => Language::CloudFormation,
+        "Kubernetes" => Language::Kubernetes,
+        "YAML" => Language::Yaml,
+        "GitLabCI" => Language::Yaml,
+        "CircleCI" => Language::Yaml,
+        "TravisCI" => Language::Yaml,
+        "Jenkins" => Language::Yaml,
+        "Bash" => Language::Bash,
+        "Shell" => Language::Shell,
+        "Php" | "PHP" => Language::Php,
+        "GraphQL" => Language::GraphQL,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_query_reports_errors() {
+        assert!(SecurityRiskPatterns::validate_query(Language::Python, "(call) @call").is_ok());
+        assert!(SecurityRiskPatterns::validate_query(Language::Python, "(no_such_node)").is_err());
+    }
+
+    #[test]
+    fn test_repair_query() {
+        let repaired = SecurityRiskPatterns::repair_query(
+            Language::Python,
+            "```scheme\n(call (identifier) @f\n```",
+        );
+        assert_eq!(repaired.as_deref(), Some("(call (identifier) @f)"));
+        assert_eq!(
+            SecurityRiskPatterns::repair_query(Language::Python, "(call) @c))").as_deref(),
+            Some("(call) @c")
+        );
+        assert!(SecurityRiskPatterns::repair_query(Language::Python, "(no_such_node)").is_none());
+    }
+
+    #[test]
+    fn test_sanitize_pattern_file_repairs_and_drops() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(CUSTOM_PATTERNS_FILE);
+        std::fs::write(
+            &path,
+            "Python:\n  resources:\n    - reference: \"(call) @call\"\n      description: ok\n      attack_vector: []\n    - reference: \"(call (identifier) @f\"\n      description: fixable\n      attack_vector: []\n    - reference: \"(nope)\"\n      description: broken\n      attack_vector: []\n",
+        )
+        .unwrap();
+
+        let report = SecurityRiskPatterns::sanitize_pattern_file(&path).unwrap();
+        assert_eq!(report.kept, 1);
+        assert_eq!(report.repaired, vec!["fixable".to_string()]);
+        assert_eq!(report.rejected.len(), 1);
+
+        let entries = SecurityRiskPatterns::list_patterns(Some(tmp.path())).unwrap();
+        let custom: Vec<_> = entries
+            .into_iter()
+            .filter(|e| e.origin == PatternOrigin::Custom)
+            .collect();
+        assert_eq!(custom.len(), 2);
+        assert!(SecurityRiskPatterns::lint_patterns(&custom).is_empty());
+    }
+
+    #[test]
+    fn test_builtin_patterns_compile() {
+        let entries: Vec<_> = SecurityRiskPatterns::builtin_patterns()
+            .into_iter()
+            .flat_map(|(language, patterns)| {
+                patterns
+                    .iter()
+                    .map(|(category, config)| PatternEntry {
+                        language,
+                        category,
+                        origin: PatternOrigin::BuiltIn,
+                        config: config.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        let issues: Vec<_> = SecurityRiskPatterns::lint_patterns(&entries)
+            .into_iter()
+            .map(|issue| {
+                format!(
+                    "{:?} {}: {}",
+                    issue.entry.language, issue.entry.config.description, issue.error
+                )
+            })
+            .collect();
+        assert!(issues.is_empty(), "{issues:#?}");
+
+        let matcher = SecurityRiskPatterns::new(Language::Php);
+        let matches = matcher.get_pattern_matches("<?php\necho $_GET['q'];\n");
+        assert!(matches.iter().any(|m| {
+            m.pattern_config
+                .description
+                .starts_with("HTTP GET parameters")
+        }));
+    }
+
+    #[test]
+    fn test_custom_patterns_are_listed_and_linted() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        std::fs::write(
+            dir.join(CUSTOM_PATTERNS_FILE),
+            "Python:\n  resources:\n    - reference: \"(call) @call\"\n      description: ok\n      attack_vector: []\n    - reference: \"(call\"\n      description: broken\n      attack_vector: []\n",
+        )
+        .unwrap();
+
+        let entries = SecurityRiskPatterns::list_patterns(Some(dir)).unwrap();
+        let custom: Vec<_> = entries
+            .iter()
+            .filter(|e| e.origin == PatternOrigin::Custom)
+            .cloned()
+            .collect();
+        assert_eq!(custom.len(), 2);
+        assert!(custom.iter().all(|e| e.language == Language::Python));
+
+        let issues = SecurityRiskPatterns::lint_patterns(&custom);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].entry.config.description, "broken");
+
+        // The broken query is skipped without shifting the others.
+        let matcher = SecurityRiskPatterns::new_with_root(Language::Python, Some(dir));
+        let matches = matcher.get_pattern_matches("print('hello')\n");
+        assert!(
+            matches
+                .iter()
+                .all(|m| m.pattern_config.description != "broken")
+        );
+    }
+
+    #[test]
+    fn test_ci_patterns_flag_script_injection() {
+        let matcher = SecurityRiskPatterns::new(Language::Yaml);
+        let descriptions = |content: &str| -> Vec<String> {
+            matcher
+                .get_pattern_matches(content)
+                .into_iter()
+                .map(|m| m.pattern_config.description)
+                .collect()
+        };
+
+        let workflow = descriptions(
+            r#"on: pull_request_target
+jobs:
+  greet:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@8f4b7f84864484a7bf31766abe9204da3cbe65b3
+        with:
+          ref: ${{ github.event.pull_request.head.sha }}
+      - run: |
+          echo "${{ github.event.pull_request.title }}"
+      - run: echo "$TITLE"
+"#,
+        );
+        let injections: Vec<_> = workflow
+            .iter()
+            .filter(|d| d.starts_with("Untrusted context interpolated"))
+            .collect();
+        assert_eq!(injections.len(), 1);
+        assert!(
+            workflow
+                .iter()
+                .any(|d| d.starts_with("Checkout of pull request head"))
+        );
+
+        let gitlab = descriptions(
+            r#"build:
+  image: node
+  rules:
+    - if: $CI_PIPELINE_SOURCE == "merge_request_event"
+  script:
+    - echo "Building $CI_MERGE_REQUEST_TITLE"
+"#,
+        );
+        assert!(
+            gitlab
+                .iter()
+                .any(|d| d.starts_with("Merge request pipeline"))
+        );
+        assert!(
+            gitlab
+                .iter()
+                .any(|d| d.starts_with("Contributor-controlled CI variable"))
+        );
+        assert!(
+            gitlab
+                .iter()
+                .any(|d| d.starts_with("Container image without"))
+        );
+
+        let values = descriptions(
+            r#"hostNetwork: true
+automountServiceAccountToken: true
+securityContext:
+  privileged: true
+  runAsUser: 0
+  capabilities:
+    add: ["NET_ADMIN"]
+volumes:
+  - hostPath:
+      path: /var/run/docker.sock
+auth:
+  password: hunter2
+  token: ""
+"#,
+        );
+        for expected in [
+            "Pod shares the host's network, PID or IPC namespace",
+            "Service account token mounted into the pod",
+            "Privileged container or privilege escalation allowed",
+            "Container runs as root",
+            "Dangerous Linux capability added",
+            "Host filesystem mounted into the pod",
+        ] {
+            assert!(values.iter().any(|d| d == expected), "{expected}");
+        }
+        let credentials = values
+            .iter()
+            .filter(|d| d.starts_with("Hard-coded credential"))
+            .count();
+        assert_eq!(credentials, 1);
+    }
+
+    #[test]
+    fn test_graphql_resolvers_are_principals() {
+        let principals = |language: Language, content: &str| -> Vec<String> {
+            SecurityRiskPatterns::new(language)
+                .get_pattern_matches(content)
+                .into_iter()
+                .filter(|m| m.category == "principals")
+                .map(|m| m.pattern_config.description)
+                .collect()
+        };
+        let resolvers = "const resolvers = { Query: { user: (_, { id }) => db.find(id) } };\n";
+        assert!(
+            principals(Language::TypeScript, resolvers).contains(&"GraphQL resolver map".into())
+        );
+
+        let graphene = "class Query(graphene.ObjectType):\n    def resolve_user(self, info, id):\n        return find(id)\n";
+        assert!(principals(Language::Python, graphene).contains(&"GraphQL resolvers".into()));
+        let strawberry =
+            "@strawberry.mutation\ndef delete_user(id: str) -> bool:\n    return remove(id)\n";
+        assert!(
+            principals(Language::Python, strawberry)
+                .contains(&"Strawberry GraphQL resolvers".into())
+        );
+    }
+
+    #[test]
+    fn test_pattern_metadata_is_honored_when_matching() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        std::fs::write(
+            dir.join(CUSTOM_PATTERNS_FILE),
+            r#"JavaScript:
+  resources:
+    - reference: "(call_expression function: (identifier) @f (#eq? @f \"evalish\")) @call"
+      description: evalish call
+      attack_vector: []
+      severity: critical
+      tags: [exec]
+      languages: [TypeScript]
+    - reference: "(call_expression) @call"
+      description: any call
+      attack_vector: []
+      enabled: false
+"#,
+        )
+        .unwrap();
+
+        let matcher = SecurityRiskPatterns::new_with_root(Language::TypeScript, Some(dir));
+        let matches = matcher.get_pattern_matches("evalish(input);\n");
+        let custom: Vec<_> = matches
+            .iter()
+            .filter(|m| m.pattern_config.description.ends_with("call"))
+            .collect();
+        assert_eq!(custom.len(), 1);
+        assert_eq!(custom[0].pattern_config.description, "evalish call");
+        assert_eq!(custom[0].pattern_config.severity, Some(Severity::Critical));
+
+        let config = &custom[0].pattern_config;
+        assert!(config.is_active(&[]));
+        assert!(!config.is_active(&["EXEC".to_string()]));
+        assert!(!config.is_active(&["evalish call".to_string()]));
+    }
 }
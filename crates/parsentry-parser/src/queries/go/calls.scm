@@ -10,7 +10,8 @@
 ; Function references
 (var_spec
   name: (identifier)
-  value: (identifier) @reference)
+  value: (expression_list
+    (identifier) @reference))
 
 ; Function as argument (callbacks)
 (argument_list
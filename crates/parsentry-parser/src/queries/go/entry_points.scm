@@ -0,0 +1,15 @@
+; net/http, gin, echo and chi routes: http.HandleFunc("/users", listUsers)
+(call_expression
+  function: (selector_expression
+    field: (field_identifier) @_verb)
+  arguments: (argument_list
+    .
+    (interpreted_string_literal) @path
+    [(identifier) (selector_expression) (func_literal)] @name .)
+  (#match? @_verb "^(HandleFunc|Handle|GET|POST|PUT|PATCH|DELETE|Any|Get|Post|Put|Patch|Delete)$")
+  (#match? @path "^\"/")) @route.http
+
+; func main()
+(function_declaration
+  name: (identifier) @name
+  (#eq? @name "main")) @cli.main
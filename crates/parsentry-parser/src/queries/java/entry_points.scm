@@ -0,0 +1,26 @@
+; Spring MVC / JAX-RS handlers: @GetMapping("/users"), @Path("/users")
+(method_declaration
+  (modifiers
+    [
+      (annotation name: (identifier) @_mapping)
+      (marker_annotation name: (identifier) @_mapping)
+    ])
+  name: (identifier) @name
+  (#match? @_mapping "^(RequestMapping|GetMapping|PostMapping|PutMapping|PatchMapping|DeleteMapping|Path|GET|POST|PUT|PATCH|DELETE)$")) @route.spring
+
+; Message listeners: @KafkaListener(topics = "orders")
+(method_declaration
+  (modifiers
+    [
+      (annotation name: (identifier) @_listener)
+      (marker_annotation name: (identifier) @_listener)
+    ])
+  name: (identifier) @name
+  (#match? @_listener "^(KafkaListener|RabbitListener|JmsListener|SqsListener|StreamListener)$")) @consumer.spring
+
+; public static void main(String[] args)
+(method_declaration
+  (modifiers) @_modifiers
+  name: (identifier) @name
+  (#eq? @name "main")
+  (#match? @_modifiers "static")) @cli.main
@@ -0,0 +1,40 @@
+; Express / Koa / Fastify routes: app.get("/users", handler)
+(call_expression
+  function: (member_expression
+    property: (property_identifier) @_verb)
+  arguments: (arguments
+    .
+    (string) @path
+    [(identifier) (member_expression) (arrow_function) (function_expression)] @name .)
+  (#match? @_verb "^(get|post|put|patch|delete|all|use|route)$")
+  (#match? @path "^['\"`]/")) @route.express
+
+; Commander / yargs commands: program.command("run").action(handler)
+(call_expression
+  function: (member_expression
+    property: (property_identifier) @_action)
+  arguments: (arguments
+    [(identifier) (member_expression) (arrow_function) (function_expression)] @name .)
+  (#match? @_action "^(action|handler)$")) @cli.commander
+
+; Queue consumers: channel.consume(queue, handler), consumer.run({ eachMessage })
+(call_expression
+  function: (member_expression
+    property: (property_identifier) @_consume)
+  arguments: (arguments
+    [(identifier) (member_expression) (arrow_function) (function_expression)] @name .)
+  (#match? @_consume "^(consume|subscribe)$")) @consumer.amqp
+
+(pair
+  key: (property_identifier) @_each
+  value: [(identifier) (member_expression) (arrow_function) (function_expression)] @name
+  (#match? @_each "^each(Message|Batch)$")) @consumer.kafkajs
+
+; if (require.main === module)
+(if_statement
+  condition: (parenthesized_expression
+    (binary_expression
+      left: (member_expression) @name
+      right: (identifier) @_module))
+  (#eq? @name "require.main")
+  (#eq? @_module "module")) @cli.script
@@ -12,5 +12,6 @@
   right: (name) @reference)
 
 ; Function as argument (callbacks)
-(argument_list
-  (name) @callback)
\ No newline at end of file
+(arguments
+  (argument
+    (name) @callback))
\ No newline at end of file
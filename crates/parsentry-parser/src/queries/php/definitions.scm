@@ -38,7 +38,7 @@
 ; Const declarations
 (const_declaration
   (const_element
-    name: (name) @name)) @definition
+    (name) @name)) @definition
 
 ; Function-like method names (constructors, destructors)
 (method_declaration
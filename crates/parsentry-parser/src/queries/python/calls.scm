@@ -8,7 +8,7 @@
 
 ; Function references
 (assignment
-  value: (identifier) @reference)
+  right: (identifier) @reference)
 
 ; Function as argument (callbacks)
 (argument_list
@@ -0,0 +1,45 @@
+; Flask / FastAPI routes: @app.route("/users"), @router.get("/users")
+(decorated_definition
+  (decorator
+    (call
+      function: (attribute
+        attribute: (identifier) @_verb)))
+  definition: (function_definition
+    name: (identifier) @name)
+  (#match? @_verb "^(route|get|post|put|patch|delete|websocket|api_route)$")) @route.flask
+
+; Click / Typer commands: @cli.command(), @app.command
+(decorated_definition
+  (decorator
+    [
+      (attribute attribute: (identifier) @_command)
+      (call function: (attribute attribute: (identifier) @_command))
+    ])
+  definition: (function_definition
+    name: (identifier) @name)
+  (#match? @_command "^(command|group|callback)$")) @cli.click
+
+; Celery tasks and queue consumers: @app.task, @shared_task
+(decorated_definition
+  (decorator
+    [
+      (identifier) @_task
+      (attribute attribute: (identifier) @_task)
+      (call function: [(identifier) (attribute attribute: (identifier))] @_task)
+    ])
+  definition: (function_definition
+    name: (identifier) @name)
+  (#match? @_task "(^|\\.)(task|shared_task|actor|subscriber|consumer)$")) @consumer.celery
+
+; Django views: def view(request, ...)
+(function_definition
+  name: (identifier) @name
+  parameters: (parameters . (identifier) @_request)
+  (#eq? @_request "request")) @route.django
+
+; if __name__ == "__main__":
+(if_statement
+  condition: (comparison_operator
+    (identifier) @_module
+    (string) @name)
+  (#eq? @_module "__name__")) @cli.script
@@ -0,0 +1,25 @@
+; axum routes: Router::new().route("/users", get(list_users))
+(call_expression
+  function: (field_expression
+    field: (field_identifier) @_route)
+  arguments: (arguments
+    (string_literal) @path
+    (call_expression
+      function: (identifier) @_verb
+      arguments: (arguments . (_) @name)))
+  (#eq? @_route "route")
+  (#match? @_verb "^(get|post|put|patch|delete|any)$")) @route.axum
+
+; actix-web / Rocket handlers: #[get("/users")] async fn list_users()
+((attribute_item
+  (attribute
+    (identifier) @_verb))
+  .
+  (function_item
+    name: (identifier) @name) @route.actix
+  (#match? @_verb "^(get|post|put|patch|delete|route)$"))
+
+; fn main()
+(function_item
+  name: (identifier) @name
+  (#eq? @name "main")) @cli.main
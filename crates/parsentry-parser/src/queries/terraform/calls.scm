@@ -1,6 +1,6 @@
 ; Function calls
 (function_call
-  function_name: (identifier) @direct_call)
+  (identifier) @direct_call)
 
 ; Variable references
 (variable_expr
@@ -10,10 +10,6 @@
 (variable_expr
   (identifier) @reference)
 
-; Module calls
-(module_call
-  (identifier) @method_call)
-
 ; Data source references
 (variable_expr
   (identifier) @reference)
@@ -7,11 +7,6 @@
   function: (member_expression
     property: (property_identifier) @method_call))
 
-; Computed method calls
-(call_expression
-  function: (member_expression
-    property: (computed_property_name) @method_call))
-
 ; Function references (assignment)
 (variable_declarator
   value: (identifier) @reference)
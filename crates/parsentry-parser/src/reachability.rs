@@ -0,0 +1,146 @@
+//! Resource accesses reachable from principals.
+//!
+//! Matches the PAR patterns of every file, then follows the [`CallGraph`]
+//! from each principal match (where untrusted input enters) to find the
+//! resource matches (sensitive operations) it can reach. The graph resolves
+//! calls by name only, so a reachable sink is a lead, not a proven flow.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use parsentry_core::{FileClassifier, Language};
+
+use crate::call_graph::{CallGraph, PathStep, relative_path};
+use crate::patterns::SecurityRiskPatterns;
+
+/// A resource match reached from a principal match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReachableSink {
+    /// Description of the resource pattern.
+    pub resource: String,
+    /// Path relative to the repository root.
+    pub file: String,
+    pub line: usize,
+    pub text: String,
+    /// Description of the principal pattern the sink is reached from.
+    pub principal: String,
+    pub principal_file: String,
+    pub principal_line: usize,
+    /// Functions called from the principal's function to the sink's.
+    pub path: Vec<PathStep>,
+}
+
+struct Located {
+    description: String,
+    file: String,
+    line: usize,
+    start_byte: usize,
+    text: String,
+}
+
+/// Resource matches in `files` (absolute paths under `root`) with a call
+/// path from a principal match, in file order.
+pub fn reachable_sinks(root: &Path, files: &[PathBuf], graph: &CallGraph) -> Vec<ReachableSink> {
+    let mut matchers: HashMap<Language, SecurityRiskPatterns> = HashMap::new();
+    let mut principals = Vec::new();
+    let mut resources = Vec::new();
+    for path in files {
+        let language = match Language::from_filename(&path.to_string_lossy()) {
+            Language::Other => FileClassifier::classify_file(path),
+            language => language,
+        };
+        if language == Language::Other {
+            continue;
+        }
+        let Ok(text) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        let file = relative_path(root, path);
+        let matcher = matchers
+            .entry(language)
+            .or_insert_with(|| SecurityRiskPatterns::new_with_root(language, Some(root)));
+        for m in matcher.get_pattern_matches(&text) {
+            let located = Located {
+                description: m.pattern_config.description,
+                file: file.clone(),
+                line: text[..m.start_byte.min(text.len())].matches('\n').count() + 1,
+                start_byte: m.start_byte,
+                text: m
+                    .matched_text
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+            };
+            match m.category {
+                "principals" => principals.push(located),
+                "resources" => resources.push(located),
+                _ => {}
+            }
+        }
+    }
+
+    let sources: Vec<(String, usize)> = principals
+        .iter()
+        .map(|p| (p.file.clone(), p.start_byte))
+        .collect();
+    let reach = graph.reach_from(&sources);
+    resources
+        .into_iter()
+        .filter_map(|resource| {
+            let (source, path) = reach.path_to(&resource.file, resource.start_byte)?;
+            let principal = &principals[source];
+            Some(ReachableSink {
+                resource: resource.description,
+                file: resource.file,
+                line: resource.line,
+                text: resource.text,
+                principal: principal.description.clone(),
+                principal_file: principal.file.clone(),
+                principal_line: principal.line,
+                path,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_only_sinks_with_a_path_from_a_principal() {
+        let root = tempfile::tempdir().unwrap();
+        let files: Vec<PathBuf> = [
+            (
+                "views.py",
+                "def login(request):\n    return find_user(request.args['name'])\n",
+            ),
+            (
+                "db.py",
+                "def find_user(name):\n    return cursor.execute('SELECT ' + name)\n",
+            ),
+            (
+                "admin.py",
+                "def purge():\n    cursor.execute('DELETE FROM t')\n",
+            ),
+        ]
+        .iter()
+        .map(|(name, source)| {
+            let path = root.path().join(name);
+            std::fs::write(&path, source).unwrap();
+            path
+        })
+        .collect();
+        let graph = CallGraph::build(root.path(), &files);
+
+        let sinks = reachable_sinks(root.path(), &files, &graph);
+        assert_eq!(sinks.len(), 1, "{sinks:?}");
+        let sink = &sinks[0];
+        assert_eq!((sink.file.as_str(), sink.line), ("db.py", 2));
+        assert_eq!(sink.resource, "SQL execution resource");
+        assert_eq!(sink.principal_file, "views.py");
+        let names: Vec<&str> = sink.path.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["login", "find_user"]);
+    }
+}
@@ -0,0 +1,196 @@
+//! Structural similarity of code snippets.
+//!
+//! Snippets are compared by shingles of normalized tokens: local names,
+//! string and number literals become placeholders, so a copy of the same
+//! code with renamed variables looks the same, while keywords and the
+//! names of called functions and members, which say what the code does,
+//! are kept.
+
+use std::collections::HashSet;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Tokens per shingle.
+const SHINGLE_SIZE: usize = 3;
+
+/// Keywords kept as written, common to the supported languages.
+const KEYWORDS: &[&str] = &[
+    "if", "else", "elif", "for", "while", "return", "def", "function", "fn", "func", "class",
+    "new", "await", "async", "import", "from", "let", "const", "var", "try", "catch", "except",
+    "raise", "throw", "with", "in", "not", "and", "or", "null", "nil", "None", "true", "false",
+    "True", "False",
+];
+
+/// Normalized token shingles of a snippet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Shingles(HashSet<u64>);
+
+impl Shingles {
+    pub fn of(code: &str) -> Self {
+        let tokens = normalized_tokens(code);
+        let windows: Vec<&[String]> = if tokens.len() < SHINGLE_SIZE {
+            vec![&tokens[..]]
+        } else {
+            tokens.windows(SHINGLE_SIZE).collect()
+        };
+        Self(
+            windows
+                .into_iter()
+                .filter(|w| !w.is_empty())
+                .map(|w| {
+                    let mut hasher = DefaultHasher::new();
+                    w.hash(&mut hasher);
+                    hasher.finish()
+                })
+                .collect(),
+        )
+    }
+
+    /// Jaccard similarity of the shingle sets, from 0.0 to 1.0.
+    pub fn similarity(&self, other: &Self) -> f64 {
+        let union = self.0.union(&other.0).count();
+        if union == 0 {
+            return 1.0;
+        }
+        self.0.intersection(&other.0).count() as f64 / union as f64
+    }
+}
+
+/// Snippets grouped by similarity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cluster {
+    /// Indices of the member snippets, in input order.
+    pub members: Vec<usize>,
+    /// The member most similar to the others on average.
+    pub exemplar: usize,
+}
+
+/// Group snippets that are at least `threshold` similar to the first
+/// member of their cluster. Largest clusters come first.
+pub fn cluster_snippets(snippets: &[Shingles], threshold: f64) -> Vec<Cluster> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for (i, snippet) in snippets.iter().enumerate() {
+        let closest = groups
+            .iter_mut()
+            .map(|g| {
+                let similarity = snippets[g[0]].similarity(snippet);
+                (similarity, g)
+            })
+            .filter(|(similarity, _)| *similarity >= threshold)
+            .max_by(|a, b| a.0.total_cmp(&b.0));
+        match closest {
+            Some((_, group)) => group.push(i),
+            None => groups.push(vec![i]),
+        }
+    }
+
+    let mut clusters: Vec<Cluster> = groups
+        .into_iter()
+        .map(|members| {
+            let total_similarity = |i: usize| -> f64 {
+                members
+                    .iter()
+                    .filter(|&&j| j != i)
+                    .map(|&j| snippets[i].similarity(&snippets[j]))
+                    .sum()
+            };
+            let exemplar = members
+                .iter()
+                .copied()
+                .fold(None, |best: Option<(usize, f64)>, i| {
+                    let score = total_similarity(i);
+                    match best {
+                        Some((_, s)) if s >= score => best,
+                        _ => Some((i, score)),
+                    }
+                })
+                .map_or(members[0], |(i, _)| i);
+            Cluster { members, exemplar }
+        })
+        .collect();
+    clusters.sort_by_key(|c| std::cmp::Reverse(c.members.len()));
+    clusters
+}
+
+/// Tokens of `code` with local names as `$`, strings as `"` and numbers as
+/// `0`. Names followed by `(` or preceded by `.`, `->` or `::` are kept.
+fn normalized_tokens(code: &str) -> Vec<String> {
+    let chars: Vec<char> = code.chars().collect();
+    let mut tokens: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let name: String = chars[start..i].iter().collect();
+            let member = matches!(tokens.last().map(String::as_str), Some("." | ">" | ":"));
+            let called = chars[i..].iter().find(|c| !c.is_whitespace()) == Some(&'(');
+            if member || called || KEYWORDS.contains(&name.as_str()) {
+                tokens.push(name);
+            } else {
+                tokens.push("$".to_string());
+            }
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push("0".to_string());
+        } else if matches!(c, '"' | '\'' | '`') {
+            i += 1;
+            while i < chars.len() && chars[i] != c && (c == '`' || chars[i] != '\n') {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i += 1;
+            tokens.push("\"".to_string());
+        } else {
+            tokens.push(c.to_string());
+            i += 1;
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renamed_copies_are_similar_and_other_calls_are_not() {
+        let seed = Shingles::of("cursor.execute(\"SELECT * FROM users WHERE id=\" + user_id)");
+        let renamed = Shingles::of("cur.execute('SELECT name FROM t WHERE k=' + key)");
+        let other = Shingles::of("subprocess.run(cmd, shell=True)");
+        assert_eq!(seed.similarity(&renamed), 1.0);
+        assert!(seed.similarity(&other) < 0.2);
+    }
+
+    #[test]
+    fn clusters_pick_the_most_central_exemplar() {
+        let snippets: Vec<Shingles> = [
+            "db.execute(\"SELECT \" + a)",
+            "os.system(cmd)",
+            "db.execute(\"SELECT \" + b)",
+            "db.execute(\"SELECT \" + c, params)",
+        ]
+        .iter()
+        .map(|s| Shingles::of(s))
+        .collect();
+        let clusters = cluster_snippets(&snippets, 0.5);
+        assert_eq!(
+            clusters,
+            [
+                Cluster {
+                    members: vec![0, 2, 3],
+                    exemplar: 0,
+                },
+                Cluster {
+                    members: vec![1],
+                    exemplar: 1,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Relative artifact URIs for code scanning uploads.
+//!
+//! Agents write whatever path they read a file from: absolute paths into the
+//! checkout, paths through the cached `repo/` clone, `file://` URIs or
+//! `./`-prefixed relative ones. GitHub only maps a result onto the
+//! repository when its URI is relative to the repository root, so
+//! [`UriBase::apply`] rewrites every artifact URI relative to the scanned
+//! checkout, resolved through the `%SRCROOT%` base id (SARIF §3.14.14).
+//! When only a subdirectory of a monorepo was scanned, a prefix makes the
+//! URIs relative to the repository root instead.
+
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use crate::sarif::{SarifArtifactLocation, SarifReport};
+
+/// Base id of the repository root that relative URIs resolve against.
+pub const SRCROOT: &str = "%SRCROOT%";
+
+/// The checkout artifact URIs are made relative to.
+#[derive(Debug, Clone)]
+pub struct UriBase {
+    root: PathBuf,
+    prefix: Vec<String>,
+}
+
+impl UriBase {
+    /// URIs relative to the checkout at `root`.
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf()),
+            prefix: Vec::new(),
+        }
+    }
+
+    /// Prepend `prefix`, the checkout's path within its repository, e.g.
+    /// `services/api` when only that directory of a monorepo was scanned.
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = normalize(&prefix.replace('\\', "/"));
+        self
+    }
+
+    /// `uri` relative to the repository root, or `None` when it points
+    /// outside the checkout.
+    pub fn relativize(&self, uri: &str) -> Option<String> {
+        let path = uri
+            .strip_prefix("file://")
+            .map(|p| urlencoding::decode(p).map_or(p.to_string(), |p| p.into_owned()))
+            .unwrap_or_else(|| uri.to_string())
+            .replace('\\', "/");
+        let relative = if Path::new(&path).is_absolute() {
+            let absolute = std::fs::canonicalize(&path).unwrap_or_else(|_| PathBuf::from(&path));
+            let relative = absolute.strip_prefix(&self.root).ok()?;
+            normalize(&relative.to_string_lossy())
+        } else {
+            self.without_checkout_name(normalize(&path))
+        };
+        if relative.first().is_some_and(|c| c == "..") {
+            return None;
+        }
+        Some([self.prefix.as_slice(), &relative].concat().join("/"))
+    }
+
+    /// Drop a leading `repo/` (the checkout's own directory name) that
+    /// agents copy from the clone path, when only the rest exists.
+    fn without_checkout_name(&self, relative: Vec<String>) -> Vec<String> {
+        let name = self.root.file_name().map(|n| n.to_string_lossy());
+        match relative.split_first() {
+            Some((first, rest))
+                if Some(first.as_str()) == name.as_deref()
+                    && !rest.is_empty()
+                    && !self.root.join(relative.join("/")).exists()
+                    && self.root.join(rest.join("/")).exists() =>
+            {
+                rest.to_vec()
+            }
+            _ => relative,
+        }
+    }
+
+    /// `file://` URI of the repository root: the checkout, or the ancestor
+    /// the prefix leads down from. `None` when the prefix is not a suffix of
+    /// the checkout path.
+    fn root_uri(&self) -> Option<String> {
+        let mut root = self.root.as_path();
+        for component in self.prefix.iter().rev() {
+            if root.file_name()? != component.as_str() {
+                return None;
+            }
+            root = root.parent()?;
+        }
+        let path = root.to_string_lossy().replace('\\', "/");
+        let path = path.trim_end_matches('/');
+        let encoded: Vec<String> = path
+            .split('/')
+            .map(|segment| urlencoding::encode(segment).into_owned())
+            .collect();
+        let separator = if path.starts_with('/') { "" } else { "/" };
+        Some(format!("file://{separator}{}/", encoded.join("/")))
+    }
+
+    /// Make every artifact URI in `report` relative and declare `%SRCROOT%`
+    /// on each run. Returns the number of URIs rewritten; URIs outside the
+    /// checkout are left as they are.
+    pub fn apply(&self, report: &mut SarifReport) -> usize {
+        let root_uri = self.root_uri();
+        let mut rewritten = 0;
+        for run in &mut report.runs {
+            let locations = run
+                .results
+                .iter_mut()
+                .flat_map(|r| &mut r.locations)
+                .map(|l| &mut l.physical_location.artifact_location)
+                .chain(run.artifacts.iter_mut().flatten().map(|a| &mut a.location));
+            for location in locations {
+                if location.uri_base_id.is_some() {
+                    continue;
+                }
+                let Some(relative) = self.relativize(&location.uri) else {
+                    continue;
+                };
+                if relative != location.uri {
+                    location.uri = relative;
+                    rewritten += 1;
+                }
+                location.uri_base_id = Some(SRCROOT.to_string());
+            }
+            if let Some(uri) = &root_uri {
+                run.original_uri_base_ids
+                    .get_or_insert_with(BTreeMap::new)
+                    .insert(
+                        SRCROOT.to_string(),
+                        SarifArtifactLocation {
+                            uri: uri.clone(),
+                            uri_base_id: None,
+                            index: None,
+                        },
+                    );
+            }
+        }
+        rewritten
+    }
+}
+
+/// Path segments of `path` with `.`, empty and resolvable `..` segments
+/// removed.
+fn normalize(path: &str) -> Vec<String> {
+    let mut segments: Vec<String> = Vec::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(segment) => segments.push(segment.to_string_lossy().into_owned()),
+            Component::ParentDir if segments.last().is_some_and(|s| s != "..") => {
+                segments.pop();
+            }
+            Component::ParentDir => segments.push("..".to_string()),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+        }
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::result;
+
+    fn report(uris: &[&str]) -> SarifReport {
+        crate::test_support::report(uris.iter().map(|uri| result("SQLI", uri)))
+    }
+
+    fn uris(report: &SarifReport) -> Vec<(&str, Option<&str>)> {
+        report.runs[0]
+            .results
+            .iter()
+            .map(|r| {
+                let location = &r.locations[0].physical_location.artifact_location;
+                (location.uri.as_str(), location.uri_base_id.as_deref())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn makes_uris_relative_to_the_checkout() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+        std::fs::write(repo.join("src/db.py"), "").unwrap();
+        let root = std::fs::canonicalize(&repo).unwrap();
+        let absolute = root.join("src/db.py").display().to_string();
+
+        let mut sarif = report(&[
+            &absolute,
+            &format!("file://{absolute}"),
+            "./src/../src/db.py",
+            "repo/src/db.py",
+            "/elsewhere/app.py",
+        ]);
+        let base = UriBase::new(&repo);
+        assert_eq!(base.apply(&mut sarif), 4);
+        let relative = Some(SRCROOT);
+        assert_eq!(
+            uris(&sarif),
+            [
+                ("src/db.py", relative),
+                ("src/db.py", relative),
+                ("src/db.py", relative),
+                ("src/db.py", relative),
+                ("/elsewhere/app.py", None),
+            ]
+        );
+        let base_ids = sarif.runs[0].original_uri_base_ids.as_ref().unwrap();
+        assert_eq!(base_ids[SRCROOT].uri, format!("file://{}/", root.display()));
+        assert_eq!(base.apply(&mut sarif), 0);
+    }
+
+    #[test]
+    fn prefixes_monorepo_subpaths() {
+        let dir = tempfile::tempdir().unwrap();
+        let api = dir.path().join("services/api");
+        std::fs::create_dir_all(&api).unwrap();
+        let base = UriBase::new(&api).with_prefix("./services/api/");
+        let mut sarif = report(&["main.go"]);
+        base.apply(&mut sarif);
+        assert_eq!(uris(&sarif), [("services/api/main.go", Some(SRCROOT))]);
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        assert_eq!(
+            sarif.runs[0].original_uri_base_ids.as_ref().unwrap()[SRCROOT].uri,
+            format!("file://{}/", root.display())
+        );
+
+        let mut sarif = report(&["main.go"]);
+        UriBase::new(&api).with_prefix("other").apply(&mut sarif);
+        assert_eq!(uris(&sarif), [("other/main.go", Some(SRCROOT))]);
+        assert!(sarif.runs[0].original_uri_base_ids.is_none());
+    }
+}
@@ -0,0 +1,349 @@
+//! AWS Security Finding Format (ASFF) output for AWS Security Hub.
+//!
+//! The report is a `BatchImportFindings` request, so it can be imported with
+//! `aws securityhub batch-import-findings --cli-input-json file://asff.json`
+//! (the API takes at most 100 findings per call). Findings are imported as
+//! the account's own `default` product, which needs no integration setup.
+//! Schema: https://docs.aws.amazon.com/securityhub/latest/userguide/securityhub-findings-format.html
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::gitlab_sast::vulnerability_id;
+use crate::output::write_atomic;
+use crate::report_common::{Severity, code_location, security_severity_scores};
+use crate::sarif::{SarifReport, SarifResult};
+use crate::summary::AnalysisSummary;
+
+pub const ASFF_SCHEMA_VERSION: &str = "2018-10-08";
+
+/// Security Hub limits on `Title` and `Description`.
+const MAX_TITLE_CHARS: usize = 256;
+const MAX_DESCRIPTION_CHARS: usize = 1024;
+
+/// AWS account and region the findings are imported into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsffAccount {
+    pub account_id: String,
+    pub region: String,
+}
+
+impl AsffAccount {
+    /// From `PARSENTRY_ASFF_ACCOUNT_ID` and `PARSENTRY_ASFF_REGION`, the
+    /// region falling back to `AWS_REGION` and `AWS_DEFAULT_REGION`.
+    pub fn from_env() -> Result<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        let Some(account_id) = var("PARSENTRY_ASFF_ACCOUNT_ID") else {
+            bail!("--format asff needs the AWS account id in PARSENTRY_ASFF_ACCOUNT_ID");
+        };
+        let Some(region) = var("PARSENTRY_ASFF_REGION")
+            .or_else(|| var("AWS_REGION"))
+            .or_else(|| var("AWS_DEFAULT_REGION"))
+        else {
+            bail!("--format asff needs the AWS region in PARSENTRY_ASFF_REGION or AWS_REGION");
+        };
+        if account_id.len() != 12 || !account_id.bytes().all(|b| b.is_ascii_digit()) {
+            bail!("PARSENTRY_ASFF_ACCOUNT_ID must be a 12-digit AWS account id, got {account_id}");
+        }
+        Ok(Self { account_id, region })
+    }
+
+    /// AWS partition of the region, e.g. `aws-cn` for `cn-north-1`.
+    fn partition(&self) -> &'static str {
+        if self.region.starts_with("cn-") {
+            "aws-cn"
+        } else if self.region.starts_with("us-gov-") {
+            "aws-us-gov"
+        } else {
+            "aws"
+        }
+    }
+
+    /// ARN of the account's `default` product, used for custom findings.
+    pub fn product_arn(&self) -> String {
+        format!(
+            "arn:{}:securityhub:{}:{}:product/{}/default",
+            self.partition(),
+            self.region,
+            self.account_id,
+            self.account_id
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AsffReport {
+    pub findings: Vec<AsffFinding>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AsffFinding {
+    pub schema_version: String,
+    pub id: String,
+    pub product_arn: String,
+    pub generator_id: String,
+    pub aws_account_id: String,
+    pub types: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub severity: AsffSeverity,
+    /// 0-100, from the finding's confidence.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<u8>,
+    pub title: String,
+    pub description: String,
+    pub resources: Vec<AsffResource>,
+    pub record_state: String,
+    pub product_fields: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AsffSeverity {
+    /// INFORMATIONAL | LOW | MEDIUM | HIGH | CRITICAL
+    pub label: String,
+    /// The SARIF level the label was derived from.
+    pub original: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AsffResource {
+    #[serde(rename = "Type")]
+    pub kind: String,
+    pub id: String,
+    pub partition: String,
+    pub region: String,
+    pub details: AsffResourceDetails,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AsffResourceDetails {
+    pub other: BTreeMap<String, String>,
+}
+
+impl AsffReport {
+    /// Convert an analysis summary into Security Hub findings.
+    pub fn from_analysis_summary(
+        summary: &AnalysisSummary,
+        version: &str,
+        account: &AsffAccount,
+    ) -> Self {
+        Self::from_sarif(
+            &SarifReport::from_analysis_summary(summary, version),
+            account,
+        )
+    }
+
+    /// Convert a (merged) SARIF report into Security Hub findings.
+    pub fn from_sarif(sarif: &SarifReport, account: &AsffAccount) -> Self {
+        let scores = security_severity_scores(sarif);
+        let mut rule_names = HashMap::new();
+        for run in &sarif.runs {
+            for rule in run.tool.driver.rules.iter().flatten() {
+                if let Some(desc) = &rule.short_description {
+                    rule_names.insert(rule.id.clone(), desc.text.clone());
+                }
+            }
+        }
+        let now = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+        let findings = sarif
+            .runs
+            .iter()
+            .flat_map(|run| run.results.iter())
+            .filter(|r| r.suppressions.as_ref().is_none_or(|s| s.is_empty()))
+            .map(|r| to_finding(r, &rule_names, &scores, account, &now))
+            .collect();
+        Self { findings }
+    }
+
+    /// Export report to JSON string
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Save report to file
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        write_atomic(path, self.to_json()?)?;
+        Ok(())
+    }
+}
+
+fn to_finding(
+    result: &SarifResult,
+    rule_names: &HashMap<String, String>,
+    scores: &HashMap<String, f64>,
+    account: &AsffAccount,
+    now: &str,
+) -> AsffFinding {
+    let (file, start_line, end_line) = code_location(result);
+    let mut details = BTreeMap::new();
+    if !file.is_empty() {
+        details.insert("FilePath".to_string(), file.clone());
+    }
+    if let Some(line) = start_line {
+        details.insert("StartLine".to_string(), line.to_string());
+    }
+    if let Some(line) = end_line {
+        details.insert("EndLine".to_string(), line.to_string());
+    }
+
+    let props = result.properties.as_ref();
+    let mut types: Vec<String> = props
+        .and_then(|p| p.cwe.as_ref())
+        .into_iter()
+        .flatten()
+        .map(|cwe| format!("Software and Configuration Checks/Vulnerabilities/{cwe}"))
+        .collect();
+    if types.is_empty() {
+        types.push("Software and Configuration Checks/Vulnerabilities/Code".to_string());
+    }
+
+    let mut product_fields =
+        BTreeMap::from([("parsentry/RuleId".to_string(), result.rule_id.clone())]);
+    if let Some(fp) = result
+        .fingerprints
+        .as_ref()
+        .and_then(|fp| fp.get("parsentry/v1"))
+    {
+        product_fields.insert("parsentry/Fingerprint".to_string(), fp.clone());
+    }
+
+    let name = rule_names
+        .get(&result.rule_id)
+        .cloned()
+        .unwrap_or_else(|| result.rule_id.clone());
+    let title = match start_line {
+        Some(line) => format!("{name} in {file}:{line}"),
+        None if !file.is_empty() => format!("{name} in {file}"),
+        None => name,
+    };
+
+    AsffFinding {
+        schema_version: ASFF_SCHEMA_VERSION.to_string(),
+        id: vulnerability_id(result, &file, start_line),
+        product_arn: account.product_arn(),
+        generator_id: format!("parsentry/{}", result.rule_id),
+        aws_account_id: account.account_id.clone(),
+        types,
+        created_at: now.to_string(),
+        updated_at: now.to_string(),
+        severity: AsffSeverity {
+            label: severity_label(Severity::of(result, scores)).to_string(),
+            original: result.level.clone(),
+        },
+        confidence: props
+            .and_then(|p| p.confidence)
+            .map(|c| (c.clamp(0.0, 1.0) * 100.0).round() as u8),
+        title: truncate(&title, MAX_TITLE_CHARS),
+        description: truncate(&result.message.text, MAX_DESCRIPTION_CHARS),
+        resources: vec![AsffResource {
+            kind: "Other".to_string(),
+            id: if file.is_empty() {
+                result.rule_id.clone()
+            } else {
+                file
+            },
+            partition: account.partition().to_string(),
+            region: account.region.clone(),
+            details: AsffResourceDetails { other: details },
+        }],
+        record_state: "ACTIVE".to_string(),
+        product_fields,
+    }
+}
+
+fn severity_label(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Informational => "INFORMATIONAL",
+        Severity::Low => "LOW",
+        Severity::Medium => "MEDIUM",
+        Severity::High => "HIGH",
+        Severity::Critical => "CRITICAL",
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parsentry_core::{Response, VulnType};
+    use std::path::PathBuf;
+
+    fn account() -> AsffAccount {
+        AsffAccount {
+            account_id: "123456789012".to_string(),
+            region: "us-east-1".to_string(),
+        }
+    }
+
+    #[test]
+    fn converts_summary_to_security_hub_findings() {
+        let mut summary = AnalysisSummary::new();
+        summary.add_result(
+            PathBuf::from("app/db.py"),
+            Response {
+                analysis: "User input reaches raw SQL".to_string(),
+                confidence_score: 95,
+                vulnerability_types: vec![VulnType::SQLI],
+                ..Default::default()
+            },
+            "db.py.md".to_string(),
+        );
+        let report = AsffReport::from_analysis_summary(&summary, "1.0.0", &account());
+        assert_eq!(report.findings.len(), 1);
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        let finding = &json["Findings"][0];
+        assert_eq!(finding["SchemaVersion"], ASFF_SCHEMA_VERSION);
+        assert_eq!(
+            finding["ProductArn"],
+            "arn:aws:securityhub:us-east-1:123456789012:product/123456789012/default"
+        );
+        assert_eq!(finding["AwsAccountId"], "123456789012");
+        assert_eq!(finding["GeneratorId"], "parsentry/SQLI");
+        assert_eq!(
+            finding["Types"][0],
+            "Software and Configuration Checks/Vulnerabilities/CWE-89"
+        );
+        // The SQLI rule's security-severity of 8.5 outranks its level
+        assert_eq!(finding["Severity"]["Label"], "HIGH");
+        assert_eq!(finding["Resources"][0]["Type"], "Other");
+        assert_eq!(finding["Resources"][0]["Id"], "app/db.py");
+        assert_eq!(
+            finding["Resources"][0]["Details"]["Other"]["FilePath"],
+            "app/db.py"
+        );
+        assert_eq!(finding["Id"].as_str().unwrap().len(), 36);
+    }
+
+    #[test]
+    fn severity_and_partition() {
+        assert_eq!(severity_label(Severity::from_score(9.8)), "CRITICAL");
+        assert_eq!(severity_label(Severity::from_score(0.0)), "INFORMATIONAL");
+        assert_eq!(severity_label(Severity::from_level("note")), "LOW");
+        let china = AsffAccount {
+            region: "cn-north-1".to_string(),
+            ..account()
+        };
+        assert!(
+            china
+                .product_arn()
+                .starts_with("arn:aws-cn:securityhub:cn-north-1:")
+        );
+        assert_eq!(truncate("abcdef", 4), "abc…");
+    }
+}
@@ -0,0 +1,179 @@
+//! Executables and libraries committed to the repository.
+//!
+//! Compiled code in source directories cannot be reviewed, and is a common
+//! hiding place for backdoored dependencies. `scan --report-binaries`
+//! lists such files as informational findings in [`BINARIES_SARIF_FILE`],
+//! which `merge` picks up alongside the workers' results.
+
+use std::path::{Path, PathBuf};
+
+use parsentry_core::content_type::{executable_format, read_head};
+
+use crate::sarif::{
+    SARIF_SCHEMA, SARIF_VERSION, SarifArtifactLocation, SarifConfiguration, SarifDriver,
+    SarifLocation, SarifMessage, SarifPhysicalLocation, SarifReport, SarifResult,
+    SarifResultProperties, SarifRule, SarifRuleProperties, SarifRun, SarifTool,
+};
+
+/// Reports directory file holding the committed binaries.
+pub const BINARIES_SARIF_FILE: &str = "binaries.sarif.json";
+
+/// Extensions of compiled code worth reporting.
+pub const BINARY_EXTENSIONS: &[&str] = &[
+    "so", "dll", "dylib", "exe", "bin", "o", "a", "lib", "class", "jar", "war", "wasm", "node",
+    "pyc",
+];
+
+const RULE_ID: &str = "BINARY-COMMITTED-EXECUTABLE";
+const CWE: &str = "CWE-506";
+
+/// Format of a committed binary at `path`, if it holds compiled code.
+fn format_of(path: &Path) -> Option<&'static str> {
+    let head = read_head(path);
+    executable_format(&head).or_else(|| {
+        let ext = path.extension()?.to_string_lossy().to_lowercase();
+        match ext.as_str() {
+            "jar" | "war" if head.starts_with(b"PK\x03\x04") => Some("Java archive"),
+            "pyc" => Some("compiled Python bytecode"),
+            _ => None,
+        }
+    })
+}
+
+/// Report the binaries among `files` (absolute paths under `root`) that
+/// hold compiled code. The report has one run and no results when none do.
+pub fn scan_binaries(root: &Path, files: &[PathBuf]) -> SarifReport {
+    let results = files
+        .iter()
+        .filter_map(|path| {
+            let format = format_of(path)?;
+            let file = path
+                .strip_prefix(root)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            Some(SarifResult {
+                rule_id: RULE_ID.to_string(),
+                rule_index: Some(0),
+                level: "note".to_string(),
+                message: SarifMessage {
+                    text: format!(
+                        "Committed {format} `{file}` cannot be reviewed as source; check where it \
+                         comes from and whether it can be built from source instead"
+                    ),
+                    markdown: None,
+                },
+                locations: vec![SarifLocation {
+                    physical_location: SarifPhysicalLocation {
+                        artifact_location: SarifArtifactLocation {
+                            uri: file,
+                            uri_base_id: None,
+                            index: None,
+                        },
+                        region: None,
+                    },
+                }],
+                fingerprints: None,
+                partial_fingerprints: None,
+                baseline_state: None,
+                suppressions: None,
+                properties: Some(SarifResultProperties {
+                    confidence: Some(1.0),
+                    cwe: Some(vec![CWE.to_string()]),
+                    ..Default::default()
+                }),
+            })
+        })
+        .collect();
+    let help = "Binaries in the repository bypass code review. Build them from source in CI, \
+                or verify their origin and checksum.";
+    SarifReport {
+        schema: SARIF_SCHEMA.to_string(),
+        version: SARIF_VERSION.to_string(),
+        runs: vec![SarifRun {
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: "parsentry".to_string(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    information_uri: Some(
+                        "https://github.com/HikaruEgashira/parsentry".to_string(),
+                    ),
+                    rules: Some(vec![SarifRule {
+                        id: RULE_ID.to_string(),
+                        name: Some("Committed Binary".to_string()),
+                        short_description: Some(SarifMessage {
+                            text: "Committed Binary".to_string(),
+                            markdown: None,
+                        }),
+                        full_description: None,
+                        help: Some(SarifMessage {
+                            text: help.to_string(),
+                            markdown: Some(help.to_string()),
+                        }),
+                        properties: Some(SarifRuleProperties {
+                            tags: Some(vec![
+                                "security".to_string(),
+                                "supply-chain".to_string(),
+                                CWE.to_string(),
+                            ]),
+                            precision: Some("high".to_string()),
+                            problem_severity: Some("0.0".to_string()),
+                            // Informational: a binary is not a vulnerability
+                            security_severity: Some("0.0".to_string()),
+                        }),
+                        default_configuration: Some(SarifConfiguration {
+                            level: "note".to_string(),
+                        }),
+                    }]),
+                },
+            },
+            results,
+            artifacts: None,
+            invocation: None,
+            automation_details: None,
+            original_uri_base_ids: None,
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_compiled_code_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let files: Vec<PathBuf> = [
+            ("lib/libcrypto.so", &b"\x7fELF\x02\x01\x01\0"[..]),
+            ("tools/helper.exe", b"MZ\x90\0\x03\0"),
+            ("data/model.bin", b"\x01\x02\x03\x04"),
+            ("libs/app.jar", b"PK\x03\x04\x14\0"),
+        ]
+        .iter()
+        .map(|(name, content)| {
+            let path = dir.path().join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, content).unwrap();
+            path
+        })
+        .collect();
+
+        let report = scan_binaries(dir.path(), &files);
+        let uris: Vec<&str> = report.runs[0]
+            .results
+            .iter()
+            .map(|r| {
+                r.locations[0]
+                    .physical_location
+                    .artifact_location
+                    .uri
+                    .as_str()
+            })
+            .collect();
+        assert_eq!(
+            uris,
+            vec!["lib/libcrypto.so", "tools/helper.exe", "libs/app.jar"]
+        );
+        assert!(report.runs[0].results[0].message.text.contains("ELF"));
+    }
+}
@@ -0,0 +1,176 @@
+//! The last commit to change each finding's line.
+//!
+//! `generate` runs `git blame` on the scanned checkout and records the
+//! commit, author and date of every located finding in `properties.blame`,
+//! so findings can be handed to whoever wrote the code and long-standing
+//! issues told apart from fresh ones.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::sarif::SarifReport;
+
+/// The commit that last changed a line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Blame {
+    pub commit: String,
+    /// `Name <email>` of the commit author.
+    pub author: String,
+    /// First line of the commit message.
+    pub summary: String,
+    /// Author date, `YYYY-MM-DD`.
+    pub date: String,
+    /// Days between the author date and the report.
+    pub age_days: i64,
+}
+
+impl Blame {
+    /// One-line Markdown description for reports.
+    pub fn describe(&self) -> String {
+        format!(
+            "`{}` by {} on {} ({} days ago): {}",
+            &self.commit[..self.commit.len().min(12)],
+            self.author,
+            self.date,
+            self.age_days,
+            self.summary
+        )
+    }
+}
+
+/// Blame of each line in `git blame --porcelain --root` output, aged
+/// relative to `now`. Uncommitted lines and lines of boundary commits,
+/// whose history a shallow clone lacks, are left out.
+pub fn parse_porcelain(porcelain: &str, now: DateTime<Utc>) -> HashMap<usize, Blame> {
+    #[derive(Default)]
+    struct Commit {
+        author: String,
+        mail: String,
+        time: i64,
+        summary: String,
+        boundary: bool,
+    }
+    let mut commits: HashMap<String, Commit> = HashMap::new();
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    let mut current = String::new();
+    for line in porcelain.lines() {
+        if line.starts_with('\t') {
+            continue;
+        }
+        let mut parts = line.split(' ');
+        let first = parts.next().unwrap_or_default();
+        if first.len() >= 40 && first.chars().all(|c| c.is_ascii_hexdigit()) {
+            if let Some(final_line) = parts.nth(1).and_then(|n| n.parse().ok()) {
+                current = first.to_string();
+                commits.entry(current.clone()).or_default();
+                lines.push((final_line, current.clone()));
+            }
+            continue;
+        }
+        let Some(commit) = commits.get_mut(&current) else {
+            continue;
+        };
+        let value = line.split_once(' ').map_or("", |(_, v)| v).to_string();
+        match first {
+            "author" => commit.author = value,
+            "author-mail" => commit.mail = value,
+            "author-time" => commit.time = value.parse().unwrap_or_default(),
+            "summary" => commit.summary = value,
+            "boundary" => commit.boundary = true,
+            _ => {}
+        }
+    }
+
+    lines
+        .into_iter()
+        .filter_map(|(line, sha)| {
+            let commit = &commits[&sha];
+            if commit.boundary || sha.chars().all(|c| c == '0') {
+                return None;
+            }
+            let authored = DateTime::from_timestamp(commit.time, 0)?;
+            Some((
+                line,
+                Blame {
+                    commit: sha,
+                    author: format!("{} {}", commit.author, commit.mail),
+                    summary: commit.summary.clone(),
+                    date: authored.format("%Y-%m-%d").to_string(),
+                    age_days: (now - authored).num_days(),
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Record the blame of each result's first located line in
+/// `properties.blame`. `blame_file` returns the blame of the requested
+/// lines of a repository-relative file, and is called once per file.
+pub fn assign<F>(report: &mut SarifReport, mut blame_file: F)
+where
+    F: FnMut(&str, &[usize]) -> HashMap<usize, Blame>,
+{
+    let location = |result: &crate::sarif::SarifResult| {
+        let location = &result.locations.first()?.physical_location;
+        let line = usize::try_from(location.region.as_ref()?.start_line).ok()?;
+        let uri = location.artifact_location.uri.trim_start_matches("./");
+        Some((uri.to_string(), line))
+    };
+    let mut lines: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for result in report.runs.iter().flat_map(|run| &run.results) {
+        if let Some((uri, line)) = location(result) {
+            lines.entry(uri).or_default().push(line);
+        }
+    }
+    let blames: HashMap<String, HashMap<usize, Blame>> = lines
+        .into_iter()
+        .map(|(uri, mut lines)| {
+            lines.sort_unstable();
+            lines.dedup();
+            let blame = blame_file(&uri, &lines);
+            (uri, blame)
+        })
+        .collect();
+    for result in report.runs.iter_mut().flat_map(|run| &mut run.results) {
+        let Some(blame) =
+            location(result).and_then(|(uri, line)| blames.get(&uri)?.get(&line).cloned())
+        else {
+            continue;
+        };
+        result.properties.get_or_insert_with(Default::default).blame = Some(blame);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_porcelain_with_age() {
+        let a = "a".repeat(40);
+        let b = "b".repeat(40);
+        let zero = "0".repeat(40);
+        let porcelain = format!(
+            "{a} 3 3 1\nauthor Alice\nauthor-mail <alice@example.com>\nauthor-time 1704067200\n\
+             summary Add upload\nfilename app.py\n\tline\n\
+             {b} 1 7 1\nauthor Bob\nauthor-mail <bob@example.com>\nauthor-time 1704067200\n\
+             summary Grafted\nboundary\nfilename app.py\n\tline\n\
+             {zero} 9 9 1\nauthor Not Committed Yet\nauthor-time 1735689600\n\
+             summary Version of app.py from app.py\nfilename app.py\n\tline\n"
+        );
+        let now = DateTime::from_timestamp(1704067200 + 10 * 86400, 0).unwrap();
+        let blame = parse_porcelain(&porcelain, now);
+
+        assert_eq!(blame.len(), 1);
+        let line = &blame[&3];
+        assert_eq!(line.author, "Alice <alice@example.com>");
+        assert_eq!(line.date, "2024-01-01");
+        assert_eq!(line.age_days, 10);
+        assert_eq!(
+            line.describe(),
+            "`aaaaaaaaaaaa` by Alice <alice@example.com> on 2024-01-01 (10 days ago): Add upload"
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Confidence calibration per model and vulnerability type.
+//!
+//! Models report confidence on their own scales: one model's 0.9 may be
+//! right half the time, another's 0.6 almost always. `parsentry eval
+//! --calibration FILE` fits a curve from each labeled detection's reported
+//! confidence to whether it was a true positive (isotonic regression, so a
+//! higher raw confidence never calibrates lower), grouped by model and
+//! vulnerability type. `generate` and `merge` apply the file named by
+//! `PARSENTRY_CALIBRATION` before policies threshold on confidence, keeping
+//! the model's own value in `properties.raw_confidence`.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::eval::{Dataset, file_matches, result_key, result_uri, vuln_key};
+use crate::output::write_atomic;
+use crate::sarif::SarifReport;
+use crate::summary::AnalysisSummary;
+
+/// Key of the curve used for any model or vulnerability type.
+pub const ANY: &str = "*";
+
+/// Fewest labeled detections a curve is fitted from; smaller groups fall
+/// back to the model's or all models' curve.
+pub const MIN_SAMPLES: usize = 10;
+
+/// One labeled detection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub model: String,
+    pub vuln_type: String,
+    pub confidence: f64,
+    pub true_positive: bool,
+}
+
+/// Monotone map from raw to calibrated confidence, interpolated linearly
+/// between points and constant beyond the first and last.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Curve {
+    /// `(raw confidence, observed precision)`, ascending in both.
+    pub points: Vec<(f64, f64)>,
+    pub samples: usize,
+}
+
+impl Curve {
+    /// Isotonic fit by pooling adjacent violators.
+    pub fn fit(samples: &[(f64, bool)]) -> Self {
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+        // (sum of confidences, true positives, count) per pooled block
+        let mut blocks: Vec<(f64, f64, f64)> = Vec::new();
+        for (confidence, true_positive) in sorted {
+            blocks.push((confidence, f64::from(u8::from(true_positive)), 1.0));
+            while let [.., (c1, t1, n1), (c2, t2, n2)] = blocks[..]
+                && t1 / n1 >= t2 / n2
+            {
+                blocks.truncate(blocks.len() - 2);
+                blocks.push((c1 + c2, t1 + t2, n1 + n2));
+            }
+        }
+        Self {
+            points: blocks.iter().map(|(c, t, n)| (c / n, t / n)).collect(),
+            samples: samples.len(),
+        }
+    }
+
+    pub fn apply(&self, confidence: f64) -> f64 {
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return confidence;
+        };
+        if confidence <= first.0 {
+            return first.1;
+        }
+        if confidence >= last.0 {
+            return last.1;
+        }
+        let i = self.points.partition_point(|(raw, _)| *raw <= confidence);
+        let ((x0, y0), (x1, y1)) = (self.points[i - 1], self.points[i]);
+        y0 + (y1 - y0) * (confidence - x0) / (x1 - x0)
+    }
+}
+
+/// Curves by model, then by vulnerability type; [`ANY`] at either level
+/// covers the rest.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Calibration {
+    pub models: BTreeMap<String, BTreeMap<String, Curve>>,
+}
+
+impl Calibration {
+    /// Fit a curve for every model and type, every model, every type and
+    /// overall that has at least [`MIN_SAMPLES`] detections.
+    pub fn fit(samples: &[Sample]) -> Self {
+        let mut groups: BTreeMap<(&str, &str), Vec<(f64, bool)>> = BTreeMap::new();
+        for s in samples {
+            let point = (s.confidence, s.true_positive);
+            for key in [
+                (s.model.as_str(), s.vuln_type.as_str()),
+                (s.model.as_str(), ANY),
+                (ANY, s.vuln_type.as_str()),
+                (ANY, ANY),
+            ] {
+                groups.entry(key).or_default().push(point);
+            }
+        }
+        let mut calibration = Self::default();
+        for ((model, vuln_type), points) in groups {
+            if points.len() >= MIN_SAMPLES {
+                calibration
+                    .models
+                    .entry(model.to_string())
+                    .or_default()
+                    .insert(vuln_type.to_string(), Curve::fit(&points));
+            }
+        }
+        calibration
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("cannot read calibration {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("invalid calibration {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        write_atomic(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The most specific curve for `model` and `vuln_type`.
+    pub fn curve(&self, model: Option<&str>, vuln_type: &str) -> Option<&Curve> {
+        let model = model.and_then(|m| self.models.get(m));
+        let any = self.models.get(ANY);
+        [model, any]
+            .into_iter()
+            .flatten()
+            .find_map(|types| types.get(vuln_type).or_else(|| types.get(ANY)))
+    }
+
+    /// Replace each result's confidence with its calibrated value, keeping
+    /// the reported one in `raw_confidence`. Returns the number calibrated.
+    pub fn apply(&self, report: &mut SarifReport) -> usize {
+        let mut calibrated = 0;
+        for result in report.runs.iter_mut().flat_map(|run| &mut run.results) {
+            let key = result_key(result);
+            let Some(props) = result.properties.as_mut() else {
+                continue;
+            };
+            let Some(raw) = props.raw_confidence.or(props.confidence) else {
+                continue;
+            };
+            if let Some(curve) = self.curve(props.model.as_deref(), &key) {
+                props.raw_confidence = Some(raw);
+                props.confidence = Some(curve.apply(raw));
+                calibrated += 1;
+            }
+        }
+        calibrated
+    }
+
+    /// Calibrate the confidence scores of `summary`, whose responses came
+    /// from `model`, before they are mapped to SARIF levels.
+    pub fn apply_to_summary(&self, summary: &mut AnalysisSummary, model: Option<&str>) {
+        for result in &mut summary.results {
+            let response = &mut result.response;
+            let key = response
+                .vulnerability_types
+                .first()
+                .map_or(ANY.to_string(), |v| vuln_key(&v.to_string(), &v.cwe_ids()));
+            if let Some(curve) = self.curve(model, &key) {
+                let raw = f64::from(response.confidence_score) / 100.0;
+                response.confidence_score = (curve.apply(raw) * 100.0).round() as i32;
+            }
+        }
+    }
+}
+
+/// Labeled detections of `report`: findings in labeled files, true
+/// positives when a vulnerable label of their type matches. Findings
+/// without a model are attributed to `run`.
+pub fn samples(dataset: &Dataset, run: &str, report: &SarifReport) -> Vec<Sample> {
+    let mut samples = Vec::new();
+    for result in report.runs.iter().flat_map(|r| &r.results) {
+        let props = result.properties.as_ref();
+        let (Some(uri), Some(confidence)) = (
+            result_uri(result),
+            props.and_then(|p| p.raw_confidence.or(p.confidence)),
+        ) else {
+            continue;
+        };
+        let labels: Vec<_> = dataset
+            .labels
+            .iter()
+            .filter(|l| file_matches(&l.file, &uri))
+            .collect();
+        if labels.is_empty() {
+            continue;
+        }
+        let vuln_type = result_key(result);
+        samples.push(Sample {
+            model: props
+                .and_then(|p| p.model.clone())
+                .unwrap_or_else(|| run.to_string()),
+            true_positive: labels
+                .iter()
+                .any(|l| l.vulnerable && l.vuln_type == vuln_type),
+            vuln_type,
+            confidence,
+        });
+    }
+    samples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(model: &str, confidence: f64, true_positive: bool) -> Sample {
+        Sample {
+            model: model.to_string(),
+            vuln_type: "SQLI".to_string(),
+            confidence,
+            true_positive,
+        }
+    }
+
+    #[test]
+    fn fits_a_monotone_curve() {
+        let curve = Curve::fit(&[
+            (0.9, true),
+            (0.9, false),
+            (0.8, true),
+            (0.5, false),
+            (0.6, true),
+            (0.4, false),
+        ]);
+        assert!(curve.points.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert_eq!(curve.apply(0.1), 0.0);
+        assert_eq!(curve.apply(0.45), 0.0);
+        assert_eq!(curve.apply(0.95), 0.75);
+    }
+
+    #[test]
+    fn calibrates_per_model_with_fallback() {
+        let mut samples: Vec<Sample> = (0..10)
+            .map(|i| sample("overconfident", 0.9, i < 3))
+            .collect();
+        samples.extend((0..10).map(|i| sample("modest", 0.5, i < 9)));
+        let calibration = Calibration::fit(&samples);
+
+        let overconfident = calibration.curve(Some("overconfident"), "XSS").unwrap();
+        assert!((overconfident.apply(0.9) - 0.3).abs() < 1e-9);
+        let modest = calibration.curve(Some("modest"), "SQLI").unwrap();
+        assert!((modest.apply(0.5) - 0.9).abs() < 1e-9);
+        assert_eq!(
+            calibration.curve(Some("unknown"), "SQLI").unwrap().samples,
+            20
+        );
+
+        let mut report = SarifReport::from_json(
+            r#"{"$schema":"","version":"2.1.0","runs":[{"tool":{"driver":{"name":"parsentry","version":"1"}},"results":[
+                {"ruleId":"SQLI","level":"error","message":{"text":"a"},"properties":{"confidence":0.9,"model":"overconfident"}}
+            ]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(calibration.apply(&mut report), 1);
+        assert_eq!(calibration.apply(&mut report), 1);
+        let props = report.runs[0].results[0].properties.as_ref().unwrap();
+        assert_eq!(props.raw_confidence, Some(0.9));
+        assert!((props.confidence.unwrap() - 0.3).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,387 @@
+//! Side-by-side comparison of per-model results from `scan --compare-models`.
+//!
+//! Each surface directory holds one result per model:
+//!
+//! ```text
+//! reports/<surface>/models/<model>/result.sarif.json
+//! ```
+//!
+//! Findings are matched across models by file and normalised vulnerability
+//! type (see [`crate::eval::vuln_key`]), since agent-provided fingerprints
+//! differ between models.
+
+use anyhow::{Context, Result, bail};
+use chrono::DateTime;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use crate::eval::{result_key, result_uri};
+use crate::sarif::SarifReport;
+
+/// Subdirectory of a surface directory holding per-model results.
+pub const MODELS_DIR: &str = "models";
+
+/// Directory name for a model's results: anything outside `[A-Za-z0-9._-]`
+/// becomes `-`.
+pub fn model_dir_name(model: &str) -> String {
+    model
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// A finding identity shared across models: (file, vulnerability key).
+type FindingKey = (String, String);
+
+/// All results of one model across surfaces.
+#[derive(Debug, Clone, Default)]
+pub struct ModelRun {
+    pub model: String,
+    pub surfaces: usize,
+    /// Highest confidence per finding; `None` when the worker gave none.
+    pub findings: BTreeMap<FindingKey, Option<f64>>,
+    pub cost_usd: Option<f64>,
+    pub latency_ms: Option<i64>,
+}
+
+/// Statistics of one model relative to the others.
+#[derive(Debug, Clone)]
+pub struct ModelStats {
+    pub model: String,
+    pub surfaces: usize,
+    pub findings: usize,
+    /// Findings no other model reported.
+    pub unique: usize,
+    /// Share of this model's findings reported by at least one other model.
+    pub agreement: Option<f64>,
+    /// Mean confidence difference to the other models on shared findings.
+    pub confidence_delta: Option<f64>,
+    pub cost_usd: Option<f64>,
+    pub latency_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModelComparison {
+    pub models: Vec<ModelStats>,
+    /// Distinct findings across all models.
+    pub distinct: usize,
+    /// Findings reported by every model.
+    pub consensus: usize,
+    /// Distinct findings with the confidence each model gave (`None` = not found).
+    pub matrix: Vec<(FindingKey, Vec<Option<Option<f64>>>)>,
+}
+
+/// Load every model's results from a reports directory.
+pub fn load_model_runs(reports_dir: &Path) -> Result<Vec<ModelRun>> {
+    let mut runs: BTreeMap<String, ModelRun> = BTreeMap::new();
+    let surfaces = std::fs::read_dir(reports_dir)
+        .with_context(|| format!("cannot read directory: {}", reports_dir.display()))?;
+    for surface in surfaces.filter_map(|e| e.ok()) {
+        let Ok(models) = std::fs::read_dir(surface.path().join(MODELS_DIR)) else {
+            continue;
+        };
+        for model_dir in models.filter_map(|e| e.ok()) {
+            let path = model_dir.path().join("result.sarif.json");
+            if !path.exists() {
+                continue;
+            }
+            let report = SarifReport::from_file(&path)
+                .with_context(|| format!("invalid SARIF JSON in {}", path.display()))?;
+            let name = model_dir.file_name().to_string_lossy().to_string();
+            let run = runs.entry(name.clone()).or_insert_with(|| ModelRun {
+                model: name,
+                ..Default::default()
+            });
+            run.add(&report);
+        }
+    }
+    if runs.is_empty() {
+        bail!(
+            "no per-model results in {}; run `parsentry scan --compare-models` first",
+            reports_dir.display()
+        );
+    }
+    Ok(runs.into_values().collect())
+}
+
+impl ModelRun {
+    /// Add one surface's report to the run.
+    pub fn add(&mut self, report: &SarifReport) {
+        self.surfaces += 1;
+        for run in &report.runs {
+            for result in &run.results {
+                let Some(uri) = result_uri(result) else {
+                    continue;
+                };
+                let confidence = result.properties.as_ref().and_then(|p| p.confidence);
+                let slot = self.findings.entry((uri, result_key(result))).or_default();
+                *slot = match (*slot, confidence) {
+                    (Some(a), Some(b)) => Some(a.max(b)),
+                    (a, b) => a.or(b),
+                };
+            }
+            let Some(invocation) = &run.invocation else {
+                continue;
+            };
+            if let Some(props) = &invocation.properties {
+                if let Some(model) = &props.model {
+                    self.model.clone_from(model);
+                }
+                if let Some(cost) = props.cost_usd {
+                    *self.cost_usd.get_or_insert(0.0) += cost;
+                }
+            }
+            if let (Some(start), Some(end)) = (&invocation.start_time_utc, &invocation.end_time_utc)
+                && let (Ok(start), Ok(end)) = (
+                    DateTime::parse_from_rfc3339(start),
+                    DateTime::parse_from_rfc3339(end),
+                )
+            {
+                *self.latency_ms.get_or_insert(0) += (end - start).num_milliseconds();
+            }
+        }
+    }
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+impl ModelComparison {
+    pub fn compute(runs: &[ModelRun]) -> Self {
+        let all: BTreeSet<&FindingKey> = runs.iter().flat_map(|r| r.findings.keys()).collect();
+
+        let models = runs
+            .iter()
+            .enumerate()
+            .map(|(i, run)| {
+                let others: Vec<&ModelRun> = runs
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, r)| r)
+                    .collect();
+                let mut shared = 0;
+                let mut deltas = Vec::new();
+                for (key, confidence) in &run.findings {
+                    let other_conf: Vec<Option<f64>> = others
+                        .iter()
+                        .filter_map(|o| o.findings.get(key).copied())
+                        .collect();
+                    if other_conf.is_empty() {
+                        continue;
+                    }
+                    shared += 1;
+                    let known: Vec<f64> = other_conf.into_iter().flatten().collect();
+                    if let (Some(mine), Some(theirs)) = (confidence, mean(&known)) {
+                        deltas.push(mine - theirs);
+                    }
+                }
+                let findings = run.findings.len();
+                ModelStats {
+                    model: run.model.clone(),
+                    surfaces: run.surfaces,
+                    findings,
+                    unique: findings - shared,
+                    agreement: (findings > 0 && !others.is_empty())
+                        .then(|| shared as f64 / findings as f64),
+                    confidence_delta: mean(&deltas),
+                    cost_usd: run.cost_usd,
+                    latency_ms: run.latency_ms,
+                }
+            })
+            .collect();
+
+        let matrix: Vec<_> = all
+            .iter()
+            .map(|key| {
+                (
+                    (*key).clone(),
+                    runs.iter()
+                        .map(|r| r.findings.get(*key).copied())
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        let consensus = matrix
+            .iter()
+            .filter(|(_, cells)| cells.iter().all(Option::is_some))
+            .count();
+
+        Self {
+            models,
+            distinct: all.len(),
+            consensus,
+            matrix,
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::from("# Parsentry Model Comparison\n\n");
+        md.push_str(&format!(
+            "{} distinct finding(s), {} reported by every model\n\n",
+            self.distinct, self.consensus
+        ));
+        md.push_str(
+            "| Model | Surfaces | Findings | Unique | Agreement | Δ Confidence | Cost | Latency |\n\
+             |---|---|---|---|---|---|---|---|\n",
+        );
+        for m in &self.models {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} | {} | {} |\n",
+                m.model,
+                m.surfaces,
+                m.findings,
+                m.unique,
+                m.agreement
+                    .map_or("-".to_string(), |a| format!("{:.0}%", a * 100.0)),
+                m.confidence_delta
+                    .map_or("-".to_string(), |d| format!("{d:+.2}")),
+                m.cost_usd.map_or("-".to_string(), |c| format!("${c:.2}")),
+                m.latency_ms
+                    .map_or("-".to_string(), |l| format!("{:.1}s", l as f64 / 1000.0)),
+            ));
+        }
+
+        if !self.matrix.is_empty() {
+            md.push_str("\n## Findings\n\n| Finding |");
+            for m in &self.models {
+                md.push_str(&format!(" {} |", m.model));
+            }
+            md.push_str(&format!("\n|---|{}\n", "---|".repeat(self.models.len())));
+            for ((uri, key), cells) in &self.matrix {
+                md.push_str(&format!("| {key} in {uri} |"));
+                for cell in cells {
+                    let text = match cell {
+                        None => "—".to_string(),
+                        Some(None) => "✓".to_string(),
+                        Some(Some(c)) => format!("✓ {c:.2}"),
+                    };
+                    md.push_str(&format!(" {text} |"));
+                }
+                md.push('\n');
+            }
+        }
+        md
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        let models: Vec<_> = self
+            .models
+            .iter()
+            .map(|m| {
+                json!({
+                    "model": m.model,
+                    "surfaces": m.surfaces,
+                    "findings": m.findings,
+                    "unique": m.unique,
+                    "agreement": m.agreement,
+                    "confidence_delta": m.confidence_delta,
+                    "cost_usd": m.cost_usd,
+                    "latency_ms": m.latency_ms,
+                })
+            })
+            .collect();
+        let findings: Vec<_> = self
+            .matrix
+            .iter()
+            .map(|((uri, key), cells)| {
+                let by_model: serde_json::Map<_, _> = self
+                    .models
+                    .iter()
+                    .zip(cells)
+                    .filter_map(|(m, cell)| cell.map(|c| (m.model.clone(), json!(c))))
+                    .collect();
+                json!({ "uri": uri, "type": key, "confidence": by_model })
+            })
+            .collect();
+        Ok(serde_json::to_string_pretty(&json!({
+            "distinct": self.distinct,
+            "consensus": self.consensus,
+            "models": models,
+            "findings": findings,
+        }))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sarif::{SarifInvocation, SarifInvocationProperties, SarifResult};
+    use crate::test_support::{ResultFixture, report, result};
+
+    fn write_result(
+        dir: &Path,
+        surface: &str,
+        model: &str,
+        results: impl IntoIterator<Item = SarifResult>,
+        invocation: Option<SarifInvocation>,
+    ) {
+        let path = dir.join(surface).join(MODELS_DIR).join(model);
+        std::fs::create_dir_all(&path).unwrap();
+        let mut report = report(results);
+        report.runs[0].invocation = invocation;
+        report.save_to_file(path.join("result.sarif.json")).unwrap();
+    }
+
+    #[test]
+    fn test_compare_models() {
+        let dir = tempfile::tempdir().unwrap();
+        let reports = dir.path();
+        write_result(
+            reports,
+            "S1",
+            "model-a",
+            [
+                result("SQLI", "a.py").confidence(0.9),
+                result("XSS", "b.js").confidence(0.5),
+            ],
+            Some(SarifInvocation {
+                execution_successful: true,
+                start_time_utc: Some("2026-01-01T00:00:00Z".to_string()),
+                end_time_utc: Some("2026-01-01T00:00:30Z".to_string()),
+                arguments: None,
+                properties: Some(SarifInvocationProperties {
+                    cost_usd: Some(0.25),
+                    ..Default::default()
+                }),
+            }),
+        );
+        write_result(
+            reports,
+            "S1",
+            "model-b",
+            [result("sql-injection", "a.py").confidence(0.7)],
+            None,
+        );
+
+        assert_eq!(model_dir_name("openai/gpt 5"), "openai-gpt-5");
+        let runs = load_model_runs(reports).unwrap();
+        assert_eq!(runs.len(), 2);
+        let cmp = ModelComparison::compute(&runs);
+        assert_eq!(cmp.distinct, 2);
+        assert_eq!(cmp.consensus, 1);
+
+        let a = &cmp.models[0];
+        assert_eq!((a.findings, a.unique), (2, 1));
+        assert_eq!(a.agreement, Some(0.5));
+        assert!((a.confidence_delta.unwrap() - 0.2).abs() < 1e-9);
+        assert_eq!(a.cost_usd, Some(0.25));
+        assert_eq!(a.latency_ms, Some(30_000));
+        let b = &cmp.models[1];
+        assert_eq!((b.findings, b.unique), (1, 0));
+
+        let md = cmp.to_markdown();
+        assert!(md.contains("| model-a | 1 | 2 | 1 | 50% | +0.20 | $0.25 | 30.0s |"));
+        assert!(md.contains("| XSS in b.js | ✓ 0.50 | — |"));
+        let json: serde_json::Value = serde_json::from_str(&cmp.to_json().unwrap()).unwrap();
+        assert_eq!(json["findings"][0]["confidence"]["model-b"], 0.7);
+    }
+}
@@ -0,0 +1,265 @@
+//! Consensus over several analyses of the same surface.
+//!
+//! With `scan --samples` or `--compare-models`, every surface has one result
+//! per member under `<surface>/models/<member>/result.sarif.json`. A finding
+//! is kept when at least `quorum` members report it (matched by file and
+//! normalised vulnerability type); its confidence is the members' average
+//! and PAR fields are taken from the most confident member, filling gaps
+//! from the others. The consensus is written to `<surface>/result.sarif.json`
+//! so the regular merge picks it up, with a `.cache_key` derived from the
+//! members' keys.
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::compare::MODELS_DIR;
+use crate::eval::{result_key, result_uri};
+use crate::output::write_atomic;
+use crate::sarif::{
+    SARIF_VERSION, SarifDriver, SarifReport, SarifResult, SarifRule, SarifRun, SarifTool,
+};
+
+/// Counts returned by [`apply_consensus`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ConsensusStats {
+    pub surfaces: usize,
+    pub kept: usize,
+    pub dropped: usize,
+}
+
+/// Default quorum for `members` analyses: a strict majority.
+pub fn majority(members: usize) -> usize {
+    members / 2 + 1
+}
+
+/// Findings reported by at least `quorum` of `members`.
+pub fn consensus_report(members: &[SarifReport], quorum: usize) -> (SarifReport, usize) {
+    let mut votes: BTreeMap<(String, String), Vec<&SarifResult>> = BTreeMap::new();
+    let mut rules: Vec<SarifRule> = Vec::new();
+    for member in members {
+        // One vote per member and finding, however often it repeats it.
+        let mut seen: HashMap<(String, String), &SarifResult> = HashMap::new();
+        for run in &member.runs {
+            for rule in run.tool.driver.rules.iter().flatten() {
+                if !rules.iter().any(|r| r.id == rule.id) {
+                    rules.push(rule.clone());
+                }
+            }
+            for result in &run.results {
+                if let Some(uri) = result_uri(result) {
+                    seen.entry((uri, result_key(result))).or_insert(result);
+                }
+            }
+        }
+        for (key, result) in seen {
+            votes.entry(key).or_default().push(result);
+        }
+    }
+
+    let total = votes.len();
+    let results: Vec<SarifResult> = votes
+        .into_values()
+        .filter(|voters| voters.len() >= quorum)
+        .map(|voters| combine(&voters, members.len()))
+        .collect();
+    let dropped = total - results.len();
+
+    let driver = members
+        .iter()
+        .find_map(|m| m.runs.first())
+        .map(|r| &r.tool.driver);
+    let report = SarifReport {
+        schema: members
+            .first()
+            .map_or_else(String::new, |m| m.schema.clone()),
+        version: SARIF_VERSION.to_string(),
+        runs: vec![SarifRun {
+            original_uri_base_ids: None,
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: driver.map_or_else(|| "Parsentry".to_string(), |d| d.name.clone()),
+                    version: driver.map_or_else(String::new, |d| d.version.clone()),
+                    information_uri: driver.and_then(|d| d.information_uri.clone()),
+                    rules: (!rules.is_empty()).then_some(rules),
+                },
+            },
+            results,
+            artifacts: None,
+            invocation: None,
+            automation_details: None,
+        }],
+    };
+    (report, dropped)
+}
+
+fn confidence(result: &SarifResult) -> Option<f64> {
+    result.properties.as_ref().and_then(|p| p.confidence)
+}
+
+/// Merge the votes for one finding into a single result.
+fn combine(voters: &[&SarifResult], members: usize) -> SarifResult {
+    let best = voters
+        .iter()
+        .max_by(|a, b| {
+            confidence(a)
+                .unwrap_or(0.0)
+                .total_cmp(&confidence(b).unwrap_or(0.0))
+        })
+        .expect("at least one voter");
+    let mut result = (*best).clone();
+    result.rule_index = None;
+
+    let confidences: Vec<f64> = voters.iter().filter_map(|r| confidence(r)).collect();
+    let mut props = result.properties.take().unwrap_or_default();
+    if !confidences.is_empty() {
+        props.confidence = Some(confidences.iter().sum::<f64>() / confidences.len() as f64);
+    }
+    for voter in voters {
+        let Some(other) = &voter.properties else {
+            continue;
+        };
+        for (mine, theirs) in [
+            (&mut props.principal, &other.principal),
+            (&mut props.action, &other.action),
+            (&mut props.resource, &other.resource),
+            (&mut props.data_flow, &other.data_flow),
+        ] {
+            if mine.is_none() {
+                mine.clone_from(theirs);
+            }
+        }
+        for (mine, theirs) in [
+            (&mut props.mitre_attack, &other.mitre_attack),
+            (&mut props.cwe, &other.cwe),
+            (&mut props.owasp, &other.owasp),
+        ] {
+            for id in theirs.iter().flatten() {
+                let list = mine.get_or_insert_with(Vec::new);
+                if !list.contains(id) {
+                    list.push(id.clone());
+                }
+            }
+        }
+    }
+    props.consensus = Some(format!("{}/{}", voters.len(), members));
+    result.properties = Some(props);
+    result
+}
+
+/// Replace every surface's result with the consensus of its members.
+///
+/// Surfaces without per-member results are left untouched. A `quorum` of
+/// `None` uses a strict majority of each surface's members.
+pub fn apply_consensus(reports_dir: &Path, quorum: Option<usize>) -> Result<ConsensusStats> {
+    let mut stats = ConsensusStats::default();
+    let surfaces = std::fs::read_dir(reports_dir)
+        .with_context(|| format!("cannot read directory: {}", reports_dir.display()))?;
+    for surface in surfaces.filter_map(|e| e.ok()) {
+        let Ok(entries) = std::fs::read_dir(surface.path().join(MODELS_DIR)) else {
+            continue;
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path().join("result.sarif.json"))
+            .filter(|p| p.exists())
+            .collect();
+        if paths.is_empty() {
+            continue;
+        }
+        paths.sort();
+        let members = paths
+            .iter()
+            .map(|p| {
+                SarifReport::from_file(p)
+                    .with_context(|| format!("invalid SARIF JSON in {}", p.display()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let quorum = quorum.unwrap_or_else(|| majority(members.len()));
+        let (report, dropped) = consensus_report(&members, quorum);
+        stats.surfaces += 1;
+        stats.kept += report.runs.iter().map(|r| r.results.len()).sum::<usize>();
+        stats.dropped += dropped;
+        report.save_to_file(surface.path().join("result.sarif.json"))?;
+        // Key the consensus by its members so verdicts from `parsentry
+        // verify` are discarded once any member is re-scanned.
+        let mut hasher = Sha256::new();
+        for path in &paths {
+            let key = path.with_file_name(".cache_key");
+            hasher.update(std::fs::read(&key).unwrap_or_default());
+            hasher.update(b"\0");
+        }
+        let key: String = hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        write_atomic(surface.path().join(".cache_key"), key)?;
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{ResultFixture, report, result};
+
+    fn member(results: &[(&str, &str, f64, Option<&str>)]) -> SarifReport {
+        report(results.iter().map(|&(rule, uri, confidence, principal)| {
+            result(rule, uri).confidence(confidence).properties(|p| {
+                p.cwe = Some(vec![format!("CWE-{rule}")]);
+                p.principal = principal.map(str::to_string);
+            })
+        }))
+    }
+
+    #[test]
+    fn test_consensus_keeps_quorum_and_averages() {
+        let members = [
+            member(&[("89", "a.py", 0.9, None), ("79", "b.js", 0.6, None)]),
+            member(&[("89", "a.py", 0.7, Some("user input"))]),
+            member(&[("78", "c.sh", 0.95, None)]),
+        ];
+        assert_eq!(majority(members.len()), 2);
+
+        let (report, dropped) = consensus_report(&members, 2);
+        assert_eq!(dropped, 2);
+        let results = &report.runs[0].results;
+        assert_eq!(results.len(), 1);
+        let props = results[0].properties.as_ref().unwrap();
+        assert!((props.confidence.unwrap() - 0.8).abs() < 1e-9);
+        assert_eq!(props.principal.as_deref(), Some("user input"));
+        assert_eq!(props.consensus.as_deref(), Some("2/3"));
+
+        let (report, _) = consensus_report(&members, 1);
+        assert_eq!(report.runs[0].results.len(), 3);
+    }
+
+    #[test]
+    fn test_apply_consensus_writes_surface_result() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, m) in [
+            ("sample-1", member(&[("89", "a.py", 0.9, None)])),
+            ("sample-2", member(&[("89", "a.py", 0.5, None)])),
+            ("sample-3", member(&[("79", "b.js", 0.9, None)])),
+        ] {
+            let path = dir.path().join("S-1").join(MODELS_DIR).join(name);
+            std::fs::create_dir_all(&path).unwrap();
+            m.save_to_file(path.join("result.sarif.json")).unwrap();
+        }
+
+        let stats = apply_consensus(dir.path(), None).unwrap();
+        assert_eq!(
+            stats,
+            ConsensusStats {
+                surfaces: 1,
+                kept: 1,
+                dropped: 1
+            }
+        );
+        let merged = crate::merge_sarif_dir(dir.path(), None).unwrap();
+        assert_eq!(merged.runs[0].results.len(), 1);
+    }
+}
@@ -0,0 +1,182 @@
+//! What a scan looked at: files discovered, matched by a surface, analyzed,
+//! and skipped with the reason.
+//!
+//! `scan` records discovery and the files of every surface in
+//! [`COVERAGE_FILE`]; `merge` resolves it against the surfaces that produced
+//! a usable result and reports a [`Coverage`] in the SARIF invocation.
+
+use crate::output::write_atomic;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Coverage recorded by `scan` in the reports directory.
+pub const COVERAGE_FILE: &str = "coverage.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SkipReason {
+    /// Larger than the per-file prompt limit.
+    TooLarge,
+    /// Not UTF-8 text or not readable.
+    Unreadable,
+    /// Binary content behind a source extension.
+    Binary,
+    /// Minified code on a few very long lines.
+    Minified,
+    /// Output of a compiler or code generator, without a source map to
+    /// files in the repository.
+    Generated,
+    /// Absolute, traversing or symlinked outside the repository.
+    OutsideRepository,
+    /// The surface's worker was given up on after its deadline.
+    TimedOut,
+    /// The surface's worker wrote no result.
+    NoResult,
+    /// The surface's worker wrote output that could not be repaired.
+    InvalidOutput,
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SkipReason::TooLarge => "too large",
+            SkipReason::Unreadable => "not readable as text",
+            SkipReason::Binary => "binary",
+            SkipReason::Minified => "minified",
+            SkipReason::Generated => "generated",
+            SkipReason::OutsideRepository => "outside the repository",
+            SkipReason::TimedOut => "worker timed out",
+            SkipReason::NoResult => "no worker result",
+            SkipReason::InvalidOutput => "invalid worker output",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: SkipReason,
+    /// Surface whose location or worker the file was skipped for.
+    pub surface: String,
+}
+
+/// Discovery and per-surface files of one scan.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScanCoverage {
+    /// Source files found in scope.
+    pub discovered: usize,
+    /// Files each surface's worker is given, by surface id.
+    pub surfaces: BTreeMap<String, Vec<String>>,
+    /// Files left out of the prompts.
+    pub skipped: Vec<SkippedFile>,
+}
+
+/// Coverage of a merged report.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Coverage {
+    pub discovered: usize,
+    /// Distinct files referenced by the threat model's surfaces.
+    pub matched: usize,
+    /// Distinct files of surfaces with a usable result.
+    pub analyzed: usize,
+    pub skipped: Vec<SkippedFile>,
+}
+
+impl ScanCoverage {
+    pub fn load(reports_dir: &Path) -> Option<Self> {
+        let json = std::fs::read_to_string(reports_dir.join(COVERAGE_FILE)).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    pub fn save(&self, reports_dir: &Path) -> Result<()> {
+        write_atomic(
+            reports_dir.join(COVERAGE_FILE),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+
+    /// Coverage once `unusable` surfaces are known. Their files count as
+    /// skipped unless another surface analyzed them.
+    pub fn resolve(&self, unusable: &[(String, SkipReason)]) -> Coverage {
+        let unusable: BTreeMap<&str, SkipReason> =
+            unusable.iter().map(|(s, r)| (s.as_str(), *r)).collect();
+        let mut analyzed = BTreeSet::new();
+        let mut lost = Vec::new();
+        for (surface, files) in &self.surfaces {
+            for file in files {
+                match unusable.get(surface.as_str()) {
+                    Some(&reason) => lost.push(SkippedFile {
+                        path: file.clone(),
+                        reason,
+                        surface: surface.clone(),
+                    }),
+                    None => {
+                        analyzed.insert(file.as_str());
+                    }
+                }
+            }
+        }
+        let matched: BTreeSet<&str> = self
+            .surfaces
+            .values()
+            .flatten()
+            .map(String::as_str)
+            .chain(self.skipped.iter().map(|s| s.path.as_str()))
+            .collect();
+        let mut skipped = self.skipped.clone();
+        skipped.extend(
+            lost.into_iter()
+                .filter(|s| !analyzed.contains(s.path.as_str())),
+        );
+        Coverage {
+            discovered: self.discovered,
+            matched: matched.len(),
+            analyzed: analyzed.len(),
+            skipped,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unusable_surfaces_skip_files_not_analyzed_elsewhere() {
+        let skipped = |path: &str, reason, surface: &str| SkippedFile {
+            path: path.to_string(),
+            reason,
+            surface: surface.to_string(),
+        };
+        let scan = ScanCoverage {
+            discovered: 10,
+            surfaces: BTreeMap::from([
+                (
+                    "S-1".to_string(),
+                    vec!["a.py".to_string(), "b.py".to_string()],
+                ),
+                (
+                    "S-2".to_string(),
+                    vec!["b.py".to_string(), "c.py".to_string()],
+                ),
+            ]),
+            skipped: vec![skipped("big.js", SkipReason::TooLarge, "S-1")],
+        };
+
+        let all = scan.resolve(&[]);
+        assert_eq!((all.matched, all.analyzed, all.skipped.len()), (4, 3, 1));
+
+        let partial = scan.resolve(&[("S-2".to_string(), SkipReason::TimedOut)]);
+        assert_eq!(partial.analyzed, 2);
+        assert_eq!(
+            partial.skipped,
+            vec![
+                skipped("big.js", SkipReason::TooLarge, "S-1"),
+                skipped("c.py", SkipReason::TimedOut, "S-2"),
+            ]
+        );
+    }
+}
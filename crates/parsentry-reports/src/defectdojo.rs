@@ -0,0 +1,124 @@
+/// DefectDojo integration for parsentry findings.
+///
+/// Required env vars:
+///   DEFECTDOJO_URL     - e.g. https://defectdojo.example.com
+///   DEFECTDOJO_API_KEY - API v2 key from the user's profile page
+///
+/// Findings at or above `min_level` are uploaded through the import-scan API as
+/// a SARIF scan into the given engagement. Deduplication is left to DefectDojo:
+/// findings are deduplicated within the engagement (`parsentry/v1` fingerprints
+/// are preserved in the uploaded SARIF) and findings missing from the new
+/// import are closed.
+use anyhow::{Result, anyhow};
+use reqwest::multipart::{Form, Part};
+use serde_json::Value;
+use std::env;
+use std::path::Path;
+
+use crate::merge::merge_sarif_dir;
+use crate::report_common::level_passes;
+use crate::sarif::SarifReport;
+use parsentry_core::offline::ensure_online;
+
+const SCAN_TYPE: &str = "SARIF";
+
+pub async fn run_defectdojo_command(
+    reports_dir: &Path,
+    engagement_id: &str,
+    dry_run: bool,
+    min_level: &str,
+) -> Result<()> {
+    if engagement_id.is_empty() || !engagement_id.chars().all(|c| c.is_ascii_digit()) {
+        anyhow::bail!("Invalid DefectDojo engagement ID: {engagement_id}");
+    }
+
+    let mut report = merge_sarif_dir(reports_dir, None)?;
+    retain_reportable(&mut report, min_level);
+    let count: usize = report.runs.iter().map(|r| r.results.len()).sum();
+
+    if dry_run {
+        eprintln!(
+            "[dry-run] Would import {count} finding(s) into DefectDojo engagement {engagement_id}"
+        );
+        return Ok(());
+    }
+
+    let base_url = env::var("DEFECTDOJO_URL")
+        .map_err(|_| anyhow!("DEFECTDOJO_URL not set (e.g. https://defectdojo.example.com)"))?
+        .trim_end_matches('/')
+        .to_string();
+    let api_key =
+        env::var("DEFECTDOJO_API_KEY").map_err(|_| anyhow!("DEFECTDOJO_API_KEY not set"))?;
+
+    let file = Part::bytes(serde_json::to_vec_pretty(&report)?)
+        .file_name("parsentry.sarif.json")
+        .mime_str("application/json")?;
+    let form = Form::new()
+        .text("scan_type", SCAN_TYPE)
+        .text("engagement", engagement_id.to_string())
+        .text("active", "true")
+        .text("verified", "false")
+        .text("close_old_findings", "true")
+        .text("deduplication_on_engagement", "true")
+        .text("minimum_severity", "Info")
+        .part("file", file);
+
+    ensure_online("DefectDojo import")?;
+    let resp = parsentry_core::http::client()?
+        .post(format!("{base_url}/api/v2/import-scan/"))
+        .header("Authorization", format!("Token {api_key}"))
+        .multipart(form)
+        .send()
+        .await
+        .map_err(|e| anyhow!("DefectDojo import failed: {e}"))?;
+
+    let status = resp.status();
+    let body: Value = resp.json().await.unwrap_or(Value::Null);
+    if !status.is_success() {
+        anyhow::bail!("DefectDojo import failed ({}): {}", status, body);
+    }
+
+    let test_id = body["test"]
+        .as_u64()
+        .or_else(|| body["test_id"].as_u64())
+        .map(|id| id.to_string())
+        .unwrap_or_else(|| "?".to_string());
+    eprintln!(
+        "Imported {count} finding(s) into DefectDojo engagement {engagement_id}: {base_url}/test/{test_id}"
+    );
+    Ok(())
+}
+
+/// Drop results below `min_level` and results already suppressed by triage.
+fn retain_reportable(report: &mut SarifReport, min_level: &str) {
+    for run in &mut report.runs {
+        run.results.retain(|r| {
+            level_passes(&r.level, min_level)
+                && r.baseline_state.as_deref() != Some("absent")
+                && r.suppressions.as_ref().is_none_or(|s| s.is_empty())
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{ResultFixture, report, result};
+
+    #[test]
+    fn test_retain_reportable_filters_level_absent_and_suppressed() {
+        let mut r = report([
+            result("SQLI", "a.py"),
+            result("XSS", "b.js").level("note"),
+            result("RCE", "c.sh").baseline_state("absent"),
+            result("SSRF", "d.py").level("warning").suppressed(None),
+        ]);
+        retain_reportable(&mut r, "warning");
+        let ids: Vec<_> = r.runs[0]
+            .results
+            .iter()
+            .map(|r| r.rule_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["SQLI"]);
+    }
+}
@@ -0,0 +1,128 @@
+//! Compare two reports and classify findings as new, fixed or unchanged.
+//!
+//! Findings are matched by fingerprint (see [`crate::merge`]), so a finding
+//! that moved lines but kept its fingerprint is reported as unchanged.
+//! Inputs may be SARIF or a serialized [`AnalysisSummary`].
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::merge::fingerprint;
+use crate::report_common::build_title;
+use crate::sarif::{SarifReport, SarifResult};
+use crate::summary::AnalysisSummary;
+
+#[derive(Debug, Default, Serialize)]
+pub struct ReportDiff {
+    pub new: Vec<SarifResult>,
+    pub fixed: Vec<SarifResult>,
+    pub unchanged: Vec<SarifResult>,
+}
+
+/// Load a SARIF report, falling back to an `AnalysisSummary` JSON file.
+pub fn load_report(path: &Path) -> Result<SarifReport> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+    if let Ok(report) = SarifReport::from_json(&content) {
+        return Ok(report);
+    }
+    let summary: AnalysisSummary = serde_json::from_str(&content)
+        .with_context(|| format!("{} is neither SARIF nor a summary JSON", path.display()))?;
+    Ok(SarifReport::from_analysis_summary(
+        &summary,
+        env!("CARGO_PKG_VERSION"),
+    ))
+}
+
+fn current_results(report: &SarifReport) -> impl Iterator<Item = &SarifResult> {
+    report
+        .runs
+        .iter()
+        .flat_map(|r| r.results.iter())
+        .filter(|r| r.baseline_state.as_deref() != Some("absent"))
+}
+
+impl ReportDiff {
+    pub fn compute(old: &SarifReport, new: &SarifReport) -> Self {
+        let old_fps: HashSet<String> = current_results(old).map(fingerprint).collect();
+        let new_fps: HashSet<String> = current_results(new).map(fingerprint).collect();
+
+        let mut diff = Self::default();
+        let mut seen = HashSet::new();
+        for result in current_results(new) {
+            let fp = fingerprint(result);
+            if !seen.insert(fp.clone()) {
+                continue;
+            }
+            if old_fps.contains(&fp) {
+                diff.unchanged.push(result.clone());
+            } else {
+                diff.new.push(result.clone());
+            }
+        }
+        let mut seen = HashSet::new();
+        for result in current_results(old) {
+            let fp = fingerprint(result);
+            if !new_fps.contains(&fp) && seen.insert(fp) {
+                diff.fixed.push(result.clone());
+            }
+        }
+        diff
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::from("# Parsentry Report Diff\n\n");
+        md.push_str("| Status | Count |\n|--------|-------|\n");
+        md.push_str(&format!("| 🆕 New | {} |\n", self.new.len()));
+        md.push_str(&format!("| ✅ Fixed | {} |\n", self.fixed.len()));
+        md.push_str(&format!("| ➖ Unchanged | {} |\n", self.unchanged.len()));
+
+        for (heading, results) in [("New", &self.new), ("Fixed", &self.fixed)] {
+            if results.is_empty() {
+                continue;
+            }
+            md.push_str(&format!("\n## {heading}\n\n"));
+            for r in results {
+                md.push_str(&format!("- {}\n", build_title(r)));
+            }
+        }
+        md
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{report, result};
+
+    #[test]
+    fn test_compute_classifies_by_fingerprint() {
+        let old = report([result("SQLI", "a.py"), result("XSS", "b.js")]);
+        let new = report([result("SQLI", "a.py"), result("RCE", "c.sh")]);
+        let diff = ReportDiff::compute(&old, &new);
+        assert_eq!(diff.new.len(), 1);
+        assert_eq!(diff.new[0].rule_id, "RCE");
+        assert_eq!(diff.fixed.len(), 1);
+        assert_eq!(diff.fixed[0].rule_id, "XSS");
+        assert_eq!(diff.unchanged.len(), 1);
+
+        let md = diff.to_markdown();
+        assert!(md.contains("| 🆕 New | 1 |"));
+        assert!(md.contains("RCE in c.sh"));
+    }
+
+    #[test]
+    fn test_load_report_accepts_summary_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("summary.json");
+        std::fs::write(&path, r#"{"results":[]}"#).unwrap();
+        let report = load_report(&path).unwrap();
+        assert!(report.runs[0].results.is_empty());
+    }
+}
@@ -0,0 +1,470 @@
+//! Score reports against labeled datasets.
+//!
+//! A dataset is a checkout with ground-truth labels, either OWASP
+//! Benchmark's `expectedresults-*.csv` or a `parsentry-labels.yml`:
+//!
+//! ```yaml
+//! - file: routes/search.ts
+//!   type: SQLI
+//! - file: routes/login.ts
+//!   type: XSS
+//!   vulnerable: false
+//! ```
+//!
+//! Findings and labels are compared by file and vulnerability type. Rule ids
+//! and CWEs are normalised to a common key (`SQLI`, `XSS`, `RCE`, ...), so
+//! `sql-injection` and `CWE-89` both count as `SQLI`. Findings in files
+//! without any label are counted as unlabeled and do not affect precision.
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use crate::sarif::{SarifReport, SarifResult};
+
+/// Generic label file at the dataset root.
+pub const LABELS_FILE: &str = "parsentry-labels.yml";
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Label {
+    /// Path relative to the dataset root, or a bare test name matched
+    /// against file stems (OWASP Benchmark).
+    pub file: String,
+    #[serde(rename = "type")]
+    pub vuln_type: String,
+    #[serde(default = "vulnerable_by_default")]
+    pub vulnerable: bool,
+}
+
+fn vulnerable_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Dataset {
+    pub name: String,
+    pub labels: Vec<Label>,
+}
+
+impl Dataset {
+    /// Load labels from a dataset checkout.
+    pub fn load(name: &str, root: &Path) -> Result<Self> {
+        let labels_path = root.join(LABELS_FILE);
+        if labels_path.exists() {
+            let content = std::fs::read_to_string(&labels_path)?;
+            return Self::from_labels_yaml(name, &content)
+                .with_context(|| format!("invalid {}", labels_path.display()));
+        }
+
+        let csv = std::fs::read_dir(root)
+            .with_context(|| format!("cannot read dataset {}", root.display()))?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .find(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with("expectedresults") && n.ends_with(".csv"))
+            });
+        match csv {
+            Some(path) => Ok(Self::from_owasp_csv(name, &std::fs::read_to_string(&path)?)),
+            None => bail!(
+                "{} has no {LABELS_FILE} or expectedresults-*.csv",
+                root.display()
+            ),
+        }
+    }
+
+    pub fn from_labels_yaml(name: &str, content: &str) -> Result<Self> {
+        let labels: Vec<Label> = serde_yaml::from_str(content)?;
+        Ok(Self {
+            name: name.to_string(),
+            labels: labels
+                .into_iter()
+                .map(|l| Label {
+                    vuln_type: vuln_key(&l.vuln_type, &[]),
+                    ..l
+                })
+                .collect(),
+        })
+    }
+
+    /// Parse OWASP Benchmark's `# test name, category, real vulnerability, cwe` CSV.
+    pub fn from_owasp_csv(name: &str, content: &str) -> Self {
+        let labels = content
+            .lines()
+            .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+            .filter_map(|line| {
+                let cols: Vec<&str> = line.split(',').map(str::trim).collect();
+                let [test, category, real, cwe, ..] = cols.as_slice() else {
+                    return None;
+                };
+                Some(Label {
+                    file: (*test).to_string(),
+                    vuln_type: vuln_key(category, &[format!("CWE-{cwe}")]),
+                    vulnerable: real.eq_ignore_ascii_case("true"),
+                })
+            })
+            .collect();
+        Self {
+            name: name.to_string(),
+            labels,
+        }
+    }
+
+    pub fn positives(&self) -> usize {
+        self.labels.iter().filter(|l| l.vulnerable).count()
+    }
+}
+
+/// Confusion counts for one vulnerability type (or overall).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Counts {
+    pub tp: usize,
+    pub fp: usize,
+    pub fn_: usize,
+    pub tn: usize,
+}
+
+impl Counts {
+    fn add(&mut self, other: Counts) {
+        self.tp += other.tp;
+        self.fp += other.fp;
+        self.fn_ += other.fn_;
+        self.tn += other.tn;
+    }
+
+    pub fn precision(&self) -> Option<f64> {
+        ratio(self.tp, self.tp + self.fp)
+    }
+
+    pub fn recall(&self) -> Option<f64> {
+        ratio(self.tp, self.tp + self.fn_)
+    }
+
+    pub fn f1(&self) -> Option<f64> {
+        let (p, r) = (self.precision()?, self.recall()?);
+        (p + r > 0.0).then(|| 2.0 * p * r / (p + r))
+    }
+
+    fn to_json(self) -> serde_json::Value {
+        json!({
+            "tp": self.tp,
+            "fp": self.fp,
+            "fn": self.fn_,
+            "tn": self.tn,
+            "precision": self.precision(),
+            "recall": self.recall(),
+            "f1": self.f1(),
+        })
+    }
+}
+
+fn ratio(num: usize, den: usize) -> Option<f64> {
+    (den > 0).then(|| num as f64 / den as f64)
+}
+
+/// Scores of one run (e.g. one model's report) against a dataset.
+#[derive(Debug, Clone)]
+pub struct RunScore {
+    pub name: String,
+    pub overall: Counts,
+    pub by_type: BTreeMap<String, Counts>,
+    /// Findings in files that have no label.
+    pub unlabeled: usize,
+}
+
+/// Results of `parsentry eval`: one or more runs against one dataset.
+#[derive(Debug, Clone)]
+pub struct EvalReport {
+    pub dataset: String,
+    pub labels: usize,
+    pub positives: usize,
+    pub runs: Vec<RunScore>,
+}
+
+/// Normalise a rule id (and optional CWEs) to a vulnerability key.
+pub fn vuln_key(rule_id: &str, cwes: &[String]) -> String {
+    const CWES: &[(&[&str], &str)] = &[
+        (&["89"], "SQLI"),
+        (&["79", "80"], "XSS"),
+        (&["77", "78", "94"], "RCE"),
+        (&["22", "98"], "LFI"),
+        (&["918"], "SSRF"),
+        (&["639"], "IDOR"),
+        (&["90"], "LDAPI"),
+        (&["643"], "XPATHI"),
+        (&["327"], "CRYPTO"),
+        (&["328"], "HASH"),
+        (&["330"], "WEAKRAND"),
+        (&["614"], "SECURECOOKIE"),
+        (&["501"], "TRUSTBOUND"),
+    ];
+    const ALIASES: &[(&str, &str)] = &[
+        ("sql", "SQLI"),
+        ("xss", "XSS"),
+        ("cross-site", "XSS"),
+        ("ssrf", "SSRF"),
+        ("idor", "IDOR"),
+        ("cmdi", "RCE"),
+        ("command", "RCE"),
+        ("rce", "RCE"),
+        ("code-exec", "RCE"),
+        ("lfi", "LFI"),
+        ("pathtraver", "LFI"),
+        ("path-traversal", "LFI"),
+        ("ldap", "LDAPI"),
+        ("xpath", "XPATHI"),
+        ("crypto", "CRYPTO"),
+        ("hash", "HASH"),
+        ("weakrand", "WEAKRAND"),
+        ("securecookie", "SECURECOOKIE"),
+        ("trustbound", "TRUSTBOUND"),
+    ];
+
+    for cwe in cwes {
+        let id = cwe.trim().trim_start_matches("CWE-");
+        if let Some((_, key)) = CWES.iter().find(|(ids, _)| ids.contains(&id)) {
+            return (*key).to_string();
+        }
+    }
+    let lower = rule_id.to_lowercase().replace('_', "-");
+    ALIASES
+        .iter()
+        .find(|(alias, _)| lower.contains(alias))
+        .map_or_else(|| rule_id.to_uppercase(), |(_, key)| (*key).to_string())
+}
+
+pub(crate) fn result_key(result: &SarifResult) -> String {
+    let cwes = result
+        .properties
+        .as_ref()
+        .and_then(|p| p.cwe.clone())
+        .unwrap_or_default();
+    vuln_key(&result.rule_id, &cwes)
+}
+
+pub(crate) fn result_uri(result: &SarifResult) -> Option<String> {
+    let uri = &result
+        .locations
+        .first()?
+        .physical_location
+        .artifact_location
+        .uri;
+    Some(
+        uri.trim_start_matches("file://")
+            .trim_start_matches("./")
+            .to_string(),
+    )
+}
+
+/// Whether a label's file refers to `uri`: bare names match the file stem,
+/// paths match on a component boundary from the end.
+pub(crate) fn file_matches(label: &str, uri: &str) -> bool {
+    let label = label.trim_start_matches("./");
+    if !label.contains('/') && !label.contains('.') {
+        return Path::new(uri).file_stem().and_then(|s| s.to_str()) == Some(label);
+    }
+    uri == label || uri.ends_with(&format!("/{label}")) || label.ends_with(&format!("/{uri}"))
+}
+
+/// Score one report against the dataset's labels.
+pub fn score(dataset: &Dataset, name: &str, report: &SarifReport) -> RunScore {
+    let detections: HashSet<(String, String)> = report
+        .runs
+        .iter()
+        .flat_map(|r| r.results.iter())
+        .filter(|r| r.baseline_state.as_deref() != Some("absent"))
+        .filter_map(|r| Some((result_uri(r)?, result_key(r))))
+        .collect();
+
+    let mut by_type: BTreeMap<String, Counts> = BTreeMap::new();
+    let mut matched = HashSet::new();
+    for label in &dataset.labels {
+        let hits: Vec<_> = detections
+            .iter()
+            .filter(|(uri, key)| *key == label.vuln_type && file_matches(&label.file, uri))
+            .collect();
+        let counts = by_type.entry(label.vuln_type.clone()).or_default();
+        match (label.vulnerable, hits.is_empty()) {
+            (true, false) => counts.tp += 1,
+            (true, true) => counts.fn_ += 1,
+            (false, false) => counts.fp += 1,
+            (false, true) => counts.tn += 1,
+        }
+        matched.extend(hits);
+    }
+
+    let mut unlabeled = 0;
+    for detection in detections.iter().filter(|d| !matched.contains(d)) {
+        let (uri, key) = detection;
+        if dataset.labels.iter().any(|l| file_matches(&l.file, uri)) {
+            by_type.entry(key.clone()).or_default().fp += 1;
+        } else {
+            unlabeled += 1;
+        }
+    }
+
+    let mut overall = Counts::default();
+    for counts in by_type.values() {
+        overall.add(*counts);
+    }
+    RunScore {
+        name: name.to_string(),
+        overall,
+        by_type,
+        unlabeled,
+    }
+}
+
+fn pct(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{:.1}%", v * 100.0))
+}
+
+fn counts_row(label: &str, c: &Counts) -> String {
+    format!(
+        "| {label} | {} | {} | {} | {} | {} | {} |\n",
+        c.tp,
+        c.fp,
+        c.fn_,
+        pct(c.precision()),
+        pct(c.recall()),
+        pct(c.f1())
+    )
+}
+
+const TABLE_HEADER: &str =
+    "| TP | FP | FN | Precision | Recall | F1 |\n|---|---|---|---|---|---|---|\n";
+
+impl EvalReport {
+    pub fn new(dataset: &Dataset, runs: Vec<RunScore>) -> Self {
+        Self {
+            dataset: dataset.name.clone(),
+            labels: dataset.labels.len(),
+            positives: dataset.positives(),
+            runs,
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("# Parsentry Evaluation: {}\n\n", self.dataset);
+        md.push_str(&format!(
+            "{} labels ({} vulnerable)\n\n## Summary\n\n| Run {TABLE_HEADER}",
+            self.labels, self.positives
+        ));
+        for run in &self.runs {
+            md.push_str(&counts_row(&run.name, &run.overall));
+        }
+
+        for run in &self.runs {
+            md.push_str(&format!("\n## {}\n\n| Type {TABLE_HEADER}", run.name));
+            for (key, counts) in &run.by_type {
+                md.push_str(&counts_row(key, counts));
+            }
+            if run.unlabeled > 0 {
+                md.push_str(&format!(
+                    "\n{} finding(s) in unlabeled files\n",
+                    run.unlabeled
+                ));
+            }
+        }
+        md
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        let runs: Vec<_> = self
+            .runs
+            .iter()
+            .map(|run| {
+                json!({
+                    "name": run.name,
+                    "overall": run.overall.to_json(),
+                    "by_type": run
+                        .by_type
+                        .iter()
+                        .map(|(k, c)| (k.clone(), c.to_json()))
+                        .collect::<serde_json::Map<_, _>>(),
+                    "unlabeled": run.unlabeled,
+                })
+            })
+            .collect();
+        Ok(serde_json::to_string_pretty(&json!({
+            "dataset": self.dataset,
+            "labels": self.labels,
+            "positives": self.positives,
+            "runs": runs,
+        }))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{report, result};
+
+    #[test]
+    fn test_vuln_key_normalises_aliases_and_cwes() {
+        assert_eq!(vuln_key("sql-injection", &[]), "SQLI");
+        assert_eq!(vuln_key("pathtraver", &[]), "LFI");
+        assert_eq!(vuln_key("anything", &["CWE-78".to_string()]), "RCE");
+        assert_eq!(vuln_key("deserialization", &[]), "DESERIALIZATION");
+    }
+
+    #[test]
+    fn test_score_owasp_benchmark() {
+        let dataset = Dataset::from_owasp_csv(
+            "owasp",
+            "# test name, category, real vulnerability, cwe, Benchmark version: 1.2\n\
+             BenchmarkTest00001,sqli,true,89\n\
+             BenchmarkTest00002,sqli,false,89\n\
+             BenchmarkTest00003,cmdi,true,78\n\
+             BenchmarkTest00004,xss,false,79\n",
+        );
+        assert_eq!(dataset.positives(), 2);
+
+        let report = report([
+            result("SQLI", "src/main/java/BenchmarkTest00001.java"),
+            result("SQLI", "src/main/java/BenchmarkTest00002.java"),
+            result("XSS", "src/main/java/BenchmarkTest00003.java"),
+            result("RCE", "src/main/java/Helper.java"),
+        ]);
+        let run = score(&dataset, "model-a", &report);
+
+        let sqli = run.by_type["SQLI"];
+        assert_eq!((sqli.tp, sqli.fp, sqli.fn_), (1, 1, 0));
+        let rce = run.by_type["RCE"];
+        assert_eq!((rce.tp, rce.fn_), (0, 1));
+        assert_eq!(run.by_type["XSS"].fp, 1);
+        assert_eq!(run.by_type["XSS"].tn, 1);
+        assert_eq!(run.unlabeled, 1);
+        assert_eq!(run.overall.precision(), Some(1.0 / 3.0));
+        assert_eq!(run.overall.recall(), Some(0.5));
+
+        let eval = EvalReport::new(&dataset, vec![run]);
+        let md = eval.to_markdown();
+        assert!(md.contains("| model-a | 1 | 2 | 1 | 33.3% | 50.0% | 40.0% |"));
+        let json: serde_json::Value = serde_json::from_str(&eval.to_json().unwrap()).unwrap();
+        assert_eq!(json["runs"][0]["by_type"]["SQLI"]["fp"], 1);
+    }
+
+    #[test]
+    fn test_load_labels_yaml() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(LABELS_FILE),
+            "- file: routes/search.ts\n  type: sql-injection\n- file: routes/login.ts\n  type: XSS\n  vulnerable: false\n",
+        )
+        .unwrap();
+        let dataset = Dataset::load("juice-shop", dir.path()).unwrap();
+        assert_eq!(dataset.labels[0].vuln_type, "SQLI");
+        assert!(!dataset.labels[1].vulnerable);
+
+        let run = score(
+            &dataset,
+            "r",
+            &report([result("SQLI", "./routes/search.ts")]),
+        );
+        assert_eq!(run.overall.tp, 1);
+        assert_eq!(run.overall.tn, 1);
+    }
+}
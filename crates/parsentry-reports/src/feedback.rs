@@ -0,0 +1,199 @@
+//! Known false positives fed back into later prompts.
+//!
+//! When `generate` merges a target, findings triaged as false positives
+//! (see [`crate::triage`]) are kept as exemplars in [`FALSE_POSITIVES_FILE`]
+//! in the project cache: the rule, the language and the flagged code with
+//! string literals, URLs and long numbers masked, but no file path. A team
+//! can also check exemplars into [`FEEDBACK_FILE`] at the repository root.
+//! The next `scan` of the repository shows workers the exemplars of their
+//! surface's languages, so they stop reporting the same pattern again.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::LazyLock;
+
+use crate::merge::fingerprint;
+use crate::output::write_atomic;
+use crate::sarif::{SarifReport, SarifResult};
+use crate::triage::{Decision, Triage};
+
+/// Exemplars collected from triage, in the project cache directory.
+pub const FALSE_POSITIVES_FILE: &str = "false-positives.json";
+/// Exemplars maintained by hand, at the repository root (YAML or JSON).
+pub const FEEDBACK_FILE: &str = "parsentry-feedback.yml";
+
+/// Most lines of code kept per exemplar.
+const MAX_CODE_LINES: usize = 12;
+
+static STRING_LITERAL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#""(?:[^"\\\n]|\\.)*"|'(?:[^'\\\n]|\\.){2,}'|`[^`]*`"#).expect("valid regex")
+});
+static URL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b[a-z][a-z0-9+.-]*://[^\s'\x22`)]+").expect("valid regex"));
+static LONG_NUMBER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b\d{4,}\b").expect("valid regex"));
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FalsePositive {
+    pub rule_id: String,
+    /// File extension of the flagged code, e.g. `py`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// The flagged code, anonymized.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Why the finding was wrong, from the triage note.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Fingerprint of the finding, so it is recorded once.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+}
+
+/// False-positive exemplars of a repository.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FalsePositives {
+    pub exemplars: Vec<FalsePositive>,
+}
+
+impl FalsePositives {
+    /// Load `path`, or no exemplars when it does not exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("cannot read {}", path.display()))?;
+        serde_yaml::from_str(&content).with_context(|| format!("invalid {}", path.display()))
+    }
+
+    /// Exemplars of the project cache and the repository's feedback file.
+    pub fn for_repository(project_cache: &Path, repo_root: &Path) -> Result<Self> {
+        let mut all = Self::load(&project_cache.join(FALSE_POSITIVES_FILE))?;
+        all.exemplars
+            .extend(Self::load(&repo_root.join(FEEDBACK_FILE))?.exemplars);
+        Ok(all)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        write_atomic(path, serde_json::to_string_pretty(self)? + "\n")
+    }
+
+    /// Record `result` as a false positive; `false` when it already is.
+    pub fn record(&mut self, result: &SarifResult, reason: Option<&str>) -> bool {
+        let fp = fingerprint(result);
+        if self
+            .exemplars
+            .iter()
+            .any(|e| e.fingerprint.as_deref() == Some(fp.as_str()))
+        {
+            return false;
+        }
+        let location = result.locations.first().map(|l| &l.physical_location);
+        let language = location
+            .and_then(|l| Path::new(&l.artifact_location.uri).extension())
+            .map(|ext| ext.to_string_lossy().to_lowercase());
+        let code = location
+            .and_then(|l| l.region.as_ref()?.snippet.as_ref())
+            .map(|s| anonymize(&s.text))
+            .filter(|code| !code.is_empty());
+        self.exemplars.push(FalsePositive {
+            rule_id: result.rule_id.clone(),
+            language,
+            code,
+            reason: reason.map(anonymize).filter(|r| !r.is_empty()),
+            fingerprint: Some(fp),
+        });
+        true
+    }
+
+    /// Record the findings of `report` triaged as false positives. Returns
+    /// the number of new exemplars.
+    pub fn collect(&mut self, report: &SarifReport, triage: &Triage) -> usize {
+        report
+            .runs
+            .iter()
+            .flat_map(|run| &run.results)
+            .filter_map(|result| {
+                let entry = triage.get(result)?;
+                (entry.decision == Decision::FalsePositive).then_some((result, entry))
+            })
+            .filter(|(result, entry)| self.record(result, entry.note.as_deref()))
+            .count()
+    }
+
+    /// Exemplars in one of `languages` (file extensions) or of unknown
+    /// language, most recent first.
+    pub fn for_languages(&self, languages: &[String]) -> Vec<&FalsePositive> {
+        self.exemplars
+            .iter()
+            .rev()
+            .filter(|e| {
+                e.language
+                    .as_ref()
+                    .is_none_or(|l| languages.iter().any(|x| x.eq_ignore_ascii_case(l)))
+            })
+            .collect()
+    }
+}
+
+/// `text` with string literals, URLs and long numbers masked and at most
+/// [`MAX_CODE_LINES`] lines.
+pub fn anonymize(text: &str) -> String {
+    let text = URL.replace_all(text, "<url>");
+    let text = STRING_LITERAL.replace_all(&text, |c: &regex::Captures| {
+        let quote = &c[0][..1];
+        format!("{quote}…{quote}")
+    });
+    let text = LONG_NUMBER.replace_all(&text, "<n>");
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(MAX_CODE_LINES)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_anonymized_false_positives_from_triage() {
+        let report = SarifReport::from_json(
+            r#"{"$schema":"","version":"2.1.0","runs":[{"tool":{"driver":{"name":"p","version":"0"}},"results":[
+                {"ruleId":"SQLI","level":"error","message":{"text":"m"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"app/billing/db.py"},"region":{"startLine":3,"snippet":{"text":"cursor.execute(\"SELECT * FROM acme_invoices WHERE id = %s\", (1234567,))"}}}}]},
+                {"ruleId":"XSS","level":"warning","message":{"text":"m"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"web/app.js"}}}]}
+            ]}]}"#,
+        )
+        .unwrap();
+        let results = &report.runs[0].results;
+        let mut triage = Triage::default();
+        triage.decide(
+            &results[0],
+            Decision::FalsePositive,
+            Some("parameterized query, see https://internal.example/wiki".into()),
+        );
+        triage.decide(&results[1], Decision::TruePositive, None);
+
+        let mut store = FalsePositives::default();
+        assert_eq!(store.collect(&report, &triage), 1);
+        assert_eq!(store.collect(&report, &triage), 0);
+        let exemplar = &store.exemplars[0];
+        assert_eq!(exemplar.language.as_deref(), Some("py"));
+        assert_eq!(
+            exemplar.code.as_deref(),
+            Some("cursor.execute(\"…\", (<n>,))")
+        );
+        assert_eq!(
+            exemplar.reason.as_deref(),
+            Some("parameterized query, see <url>")
+        );
+        let json = serde_json::to_string(&store).unwrap();
+        assert!(!json.contains("billing") && !json.contains("acme"));
+
+        assert_eq!(store.for_languages(&["py".to_string()]).len(), 1);
+        assert!(store.for_languages(&["js".to_string()]).is_empty());
+    }
+}
@@ -1,3 +1,10 @@
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Result, bail};
+
+use crate::sarif::SarifReport;
+
 /// Generate a unique output filename based on the relative path from root directory
 ///
 /// This function creates unique filenames by:
@@ -41,7 +48,17 @@ pub fn generate_pattern_specific_filename(
     let base_filename = generate_output_filename(file_path, root_dir);
     let base_without_md = base_filename.trim_end_matches(".md");
 
-    // Create a safe pattern identifier from the description
+    // Combine base filename with pattern identifier
+    format!(
+        "{}-{}.md",
+        base_without_md,
+        pattern_slug(pattern_description)
+    )
+}
+
+/// Lowercase, dash-separated identifier of a pattern description:
+/// `SQL Injection (raw)` is `sql-injection-raw`.
+pub fn pattern_slug(pattern_description: &str) -> String {
     // First replace various characters with dashes, then filter and clean up
     let pattern_id = pattern_description
         .to_lowercase()
@@ -66,14 +83,213 @@ pub fn generate_pattern_specific_filename(
         .join("-");
 
     // Ensure pattern_id is not empty
-    let pattern_id = if pattern_id.is_empty() {
+    if pattern_id.is_empty() {
         "pattern".to_string()
     } else {
         pattern_id
-    };
+    }
+}
 
-    // Combine base filename with pattern identifier
-    format!("{}-{}.md", base_without_md, pattern_id)
+/// Path component with path traversal and characters invalid in file
+/// names replaced.
+fn clean_component(component: &str) -> String {
+    component
+        .replace("..", "dotdot")
+        .replace([':', '*', '?', '<', '>', '|', '"', '\\'], "_")
+}
+
+/// Where `{relpath}` puts per-finding reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Layout {
+    /// All in one directory, the source path joined with dashes
+    /// (`src-api-db.py`).
+    #[default]
+    Flat,
+    /// In directories mirroring the source tree (`src/api/db.py`).
+    Mirrored,
+}
+
+impl std::str::FromStr for Layout {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "flat" => Ok(Self::Flat),
+            "mirrored" => Ok(Self::Mirrored),
+            other => bail!("unknown layout `{other}` (expected flat or mirrored)"),
+        }
+    }
+}
+
+/// Template of per-finding report names when `--filename-template` is
+/// not given.
+pub const TEMPLATE_ENV: &str = "PARSENTRY_FILENAME_TEMPLATE";
+/// Layout of per-finding reports when `--layout` is not given.
+pub const LAYOUT_ENV: &str = "PARSENTRY_REPORT_LAYOUT";
+
+/// Placeholders of a [`FilenameTemplate`].
+pub const TEMPLATE_PLACEHOLDERS: &[&str] = &["relpath", "filename", "pattern_slug", "rule_id"];
+
+/// Names of per-finding reports, e.g. `{relpath}/{pattern_slug}.md`.
+///
+/// - `{relpath}`: the source file's path from the root, flattened or
+///   mirrored by the [`Layout`]
+/// - `{filename}`: the source file's name
+/// - `{pattern_slug}`: [`pattern_slug`] of the pattern description
+/// - `{rule_id}`: the SARIF rule id, e.g. `SQLI`
+///
+/// A `/` in the template starts a directory. Findings whose names
+/// coincide share a report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilenameTemplate {
+    template: String,
+    layout: Layout,
+}
+
+impl Default for FilenameTemplate {
+    fn default() -> Self {
+        Self {
+            template: Self::FLAT.to_string(),
+            layout: Layout::Flat,
+        }
+    }
+}
+
+impl FilenameTemplate {
+    /// Default of the flat layout, the names of
+    /// [`generate_pattern_specific_filename`].
+    pub const FLAT: &str = "{relpath}-{pattern_slug}.md";
+    /// Default of the mirrored layout.
+    pub const MIRRORED: &str = "{relpath}/{pattern_slug}.md";
+
+    /// `template`, or the default of `layout`. Fails on unknown
+    /// placeholders and on names that would leave the output directory.
+    pub fn new(template: Option<&str>, layout: Layout) -> Result<Self> {
+        let template = template.unwrap_or(match layout {
+            Layout::Flat => Self::FLAT,
+            Layout::Mirrored => Self::MIRRORED,
+        });
+        if template.starts_with('/') || template.split('/').any(|part| part == "..") {
+            bail!("filename template `{template}` must stay inside the output directory");
+        }
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let Some(len) = rest[start..].find('}') else {
+                bail!("unclosed `{{` in filename template `{template}`");
+            };
+            let name = &rest[start + 1..start + len];
+            if !TEMPLATE_PLACEHOLDERS.contains(&name) {
+                bail!(
+                    "unknown placeholder `{{{name}}}` in filename template (expected {})",
+                    TEMPLATE_PLACEHOLDERS
+                        .iter()
+                        .map(|p| format!("{{{p}}}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+            rest = &rest[start + len + 1..];
+        }
+        Ok(Self {
+            template: template.to_string(),
+            layout,
+        })
+    }
+
+    /// [`FilenameTemplate::new`] with `template` and `layout` falling back
+    /// to [`TEMPLATE_ENV`] and [`LAYOUT_ENV`].
+    pub fn configured(template: Option<&str>, layout: Option<Layout>) -> Result<Self> {
+        let env = |name| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let layout = match layout {
+            Some(layout) => layout,
+            None => env(LAYOUT_ENV)
+                .map(|v| v.parse())
+                .transpose()?
+                .unwrap_or_default(),
+        };
+        Self::new(
+            template
+                .map(str::to_string)
+                .or_else(|| env(TEMPLATE_ENV))
+                .as_deref(),
+            layout,
+        )
+    }
+
+    /// Relative path of the report on `pattern_description` (rule
+    /// `rule_id`) in `file_path`.
+    pub fn render(
+        &self,
+        file_path: &Path,
+        root_dir: &Path,
+        rule_id: &str,
+        pattern_description: &str,
+    ) -> PathBuf {
+        let relative_path = file_path.strip_prefix(root_dir).unwrap_or(file_path);
+        let relpath = match self.layout {
+            Layout::Flat => generate_output_filename(file_path, root_dir)
+                .trim_end_matches(".md")
+                .to_string(),
+            Layout::Mirrored => relative_path
+                .components()
+                .filter_map(|c| match c {
+                    Component::Normal(part) => Some(clean_component(&part.to_string_lossy())),
+                    Component::ParentDir => Some("dotdot".to_string()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("/"),
+        };
+        let filename = relative_path
+            .file_name()
+            .map(|n| clean_component(&n.to_string_lossy()))
+            .unwrap_or_default();
+        let rendered = self
+            .template
+            .replace("{relpath}", &relpath)
+            .replace("{filename}", &filename)
+            .replace("{pattern_slug}", &pattern_slug(pattern_description))
+            .replace("{rule_id}", &clean_component(&rule_id.replace('/', "-")));
+        rendered
+            .split('/')
+            .filter(|part| !part.is_empty() && *part != ".")
+            .map(clean_component)
+            .collect()
+    }
+
+    /// `report` split into a report per rendered name. Each keeps the
+    /// runs and rules of `report` with only its own results; results
+    /// without a file location are filed under `no-location`.
+    pub fn split(&self, report: &SarifReport) -> BTreeMap<PathBuf, SarifReport> {
+        let mut reports = BTreeMap::new();
+        for (index, run) in report.runs.iter().enumerate() {
+            for result in &run.results {
+                let file = result.locations.first().map_or("no-location", |l| {
+                    l.physical_location.artifact_location.uri.as_str()
+                });
+                let description = run
+                    .tool
+                    .driver
+                    .rules
+                    .iter()
+                    .flatten()
+                    .find(|rule| rule.id == result.rule_id)
+                    .and_then(|rule| rule.name.as_deref())
+                    .unwrap_or(&result.rule_id);
+                let name =
+                    self.render(Path::new(file), Path::new(""), &result.rule_id, description);
+                let split: &mut SarifReport = reports.entry(name).or_insert_with(|| {
+                    let mut empty = report.clone();
+                    for run in &mut empty.runs {
+                        run.results.clear();
+                    }
+                    empty
+                });
+                split.runs[index].results.push(result.clone());
+            }
+        }
+        reports
+    }
 }
 
 #[cfg(test)]
@@ -115,4 +331,65 @@ mod tests {
         assert_eq!(filename2, "routes.py-xss-vulnerability.md");
         assert_ne!(filename1, filename2);
     }
+
+    #[test]
+    fn renders_templates_in_both_layouts() {
+        let root = Path::new("/project");
+        let file = Path::new("/project/src/api/db.py");
+
+        let flat = FilenameTemplate::default();
+        assert_eq!(
+            flat.render(file, root, "SQLI", "SQL Injection"),
+            PathBuf::from(generate_pattern_specific_filename(
+                file,
+                root,
+                "SQL Injection"
+            ))
+        );
+        let mirrored = FilenameTemplate::new(None, Layout::Mirrored).unwrap();
+        assert_eq!(
+            mirrored.render(file, root, "SQLI", "SQL Injection"),
+            PathBuf::from("src/api/db.py/sql-injection.md")
+        );
+        let custom = FilenameTemplate::new(Some("{rule_id}/{filename}.md"), Layout::Flat).unwrap();
+        assert_eq!(
+            custom.render(Path::new("/project/../etc/passwd"), root, "LFI", ""),
+            PathBuf::from("LFI/passwd.md")
+        );
+        assert_eq!(
+            mirrored.render(Path::new("../etc/passwd"), Path::new(""), "LFI", "LFI"),
+            PathBuf::from("dotdot/etc/passwd/lfi.md")
+        );
+
+        assert!(FilenameTemplate::new(Some("{path}.md"), Layout::Flat).is_err());
+        assert!(FilenameTemplate::new(Some("../{relpath}.md"), Layout::Flat).is_err());
+        assert!(FilenameTemplate::new(Some("/tmp/{relpath}.md"), Layout::Flat).is_err());
+        assert_eq!("Mirrored".parse::<Layout>().unwrap(), Layout::Mirrored);
+    }
+
+    #[test]
+    fn splits_reports_by_rendered_name() {
+        let report = SarifReport::from_json(
+            r#"{"$schema":"","version":"2.1.0","runs":[{"tool":{"driver":{"name":"parsentry","version":"0","rules":[{"id":"SQLI","name":"SQL Injection"}]}},"results":[
+                {"ruleId":"SQLI","level":"error","message":{"text":"a"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"app/db.py"}}}]},
+                {"ruleId":"SQLI","level":"error","message":{"text":"b"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"app/db.py"}}}]},
+                {"ruleId":"XSS","level":"warning","message":{"text":"c"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"app/views.py"}}}]}
+            ]}]}"#,
+        )
+        .unwrap();
+        let split = FilenameTemplate::new(None, Layout::Mirrored)
+            .unwrap()
+            .split(&report);
+        let names: Vec<_> = split
+            .keys()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["app/db.py/sql-injection.md", "app/views.py/xss.md"]);
+        assert_eq!(
+            split[Path::new("app/db.py/sql-injection.md")].runs[0]
+                .results
+                .len(),
+            2
+        );
+    }
 }
@@ -0,0 +1,101 @@
+//! Content-based fingerprints that survive line shifts.
+//!
+//! A finding keeps its identity across re-runs and unrelated edits when it
+//! is keyed on what it is about rather than where it is: the vulnerability
+//! type, the file's path in the repository and the matched code with
+//! whitespace normalized. Line numbers and the analysis text (which differs
+//! on every run) are left out, and URIs are made relative to the checkout
+//! (see [`crate::UriBase`]) before they are hashed, so the directory a
+//! checkout lives in is too. The hash is stored as a SARIF
+//! `partialFingerprints` entry and doubles as the `parsentry/v1`
+//! fingerprint that baselines, diffs and triage key on.
+
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path};
+
+/// `partialFingerprints` key of the content hash.
+pub const CONTENT_FINGERPRINT: &str = "parsentry/content/v2";
+
+/// Hash of `rule_id`, the [`repository_path`] of `uri` and the normalized
+/// `code`.
+pub fn content_fingerprint(rule_id: &str, uri: &str, code: &str) -> String {
+    let mut hasher = Sha256::new();
+    for part in [rule_id, &repository_path(uri), &normalize_code(code)] {
+        hasher.update(part.as_bytes());
+        hasher.update(b"\0");
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// `uri` as a `/`-separated path without a `file://` scheme, `.` segments
+/// or resolvable `..` segments, so `./app/db.py` and `app\\db.py` are the
+/// same file. Directories are kept: `routes/index.js` and `lib/index.js`
+/// are different files.
+pub fn repository_path(uri: &str) -> String {
+    let path = uri
+        .strip_prefix("file://")
+        .unwrap_or(uri)
+        .replace('\\', "/");
+    let mut segments: Vec<String> = Vec::new();
+    for component in Path::new(&path).components() {
+        match component {
+            Component::Normal(segment) => segments.push(segment.to_string_lossy().into_owned()),
+            Component::ParentDir if segments.last().is_some_and(|s| s != "..") => {
+                segments.pop();
+            }
+            Component::ParentDir => segments.push("..".to_string()),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+        }
+    }
+    segments.join("/")
+}
+
+/// `code` with blank lines dropped and each line's whitespace collapsed,
+/// so re-indentation and reformatting do not change the fingerprint.
+pub fn normalize_code(code: &str) -> String {
+    code.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignores_whitespace_and_line_shifts() {
+        let code = "    cursor.execute(\n        query)\n";
+        let shifted = "\ncursor.execute(  \n  query)";
+        assert_eq!(
+            content_fingerprint("SQLI", "./app/db.py", code),
+            content_fingerprint("SQLI", "app\\db.py", shifted)
+        );
+        assert_ne!(
+            content_fingerprint("SQLI", "app/db.py", code),
+            content_fingerprint("XSS", "app/db.py", code)
+        );
+    }
+
+    #[test]
+    fn keeps_same_named_files_apart() {
+        let code = "res.send(req.query.q)";
+        assert_ne!(
+            content_fingerprint("XSS", "routes/index.js", code),
+            content_fingerprint("XSS", "lib/index.js", code)
+        );
+        assert_ne!(
+            content_fingerprint("XSS", "routes/index.js", ""),
+            content_fingerprint("XSS", "lib/index.js", "")
+        );
+        assert_eq!(
+            repository_path("file://src/../lib/index.js"),
+            "lib/index.js"
+        );
+    }
+}
@@ -0,0 +1,309 @@
+//! GitLab SAST report (`gl-sast-report.json`) output.
+//!
+//! Produces a report conforming to GitLab's Security Report schema v15 so
+//! findings show up in the merge request widget and the Security tab when the
+//! file is uploaded as an `artifacts:reports:sast` artifact.
+//! Schema: https://gitlab.com/gitlab-org/security-products/security-report-schemas
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::output::write_atomic;
+use crate::report_common::code_location;
+use crate::sarif::{SarifReport, SarifResult};
+use crate::summary::AnalysisSummary;
+
+pub const GITLAB_SAST_SCHEMA_VERSION: &str = "15.0.7";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GitLabSastReport {
+    pub version: String,
+    pub vulnerabilities: Vec<GitLabVulnerability>,
+    pub scan: GitLabScan,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GitLabVulnerability {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// Critical | High | Medium | Low | Info | Unknown
+    pub severity: String,
+    pub identifiers: Vec<GitLabIdentifier>,
+    pub location: GitLabLocation,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GitLabIdentifier {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub name: String,
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GitLabLocation {
+    pub file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GitLabScan {
+    pub analyzer: GitLabScanner,
+    pub scanner: GitLabScanner,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub status: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GitLabScanner {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub vendor: GitLabVendor,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GitLabVendor {
+    pub name: String,
+}
+
+impl GitLabSastReport {
+    /// Convert an analysis summary into a GitLab SAST report.
+    pub fn from_analysis_summary(summary: &AnalysisSummary, version: &str) -> Self {
+        Self::from_sarif(&SarifReport::from_analysis_summary(summary, version))
+    }
+
+    /// Convert a (merged) SARIF report into a GitLab SAST report.
+    pub fn from_sarif(sarif: &SarifReport) -> Self {
+        let version = sarif
+            .runs
+            .first()
+            .map(|r| r.tool.driver.version.clone())
+            .unwrap_or_else(|| env!("CARGO_PKG_VERSION").to_string());
+
+        let mut rule_names = HashMap::new();
+        for run in &sarif.runs {
+            for rule in run.tool.driver.rules.iter().flatten() {
+                if let Some(desc) = &rule.short_description {
+                    rule_names.insert(rule.id.clone(), desc.text.clone());
+                }
+            }
+        }
+
+        let vulnerabilities = sarif
+            .runs
+            .iter()
+            .flat_map(|run| run.results.iter())
+            .filter(|r| r.suppressions.as_ref().is_none_or(|s| s.is_empty()))
+            .map(|r| to_vulnerability(r, &rule_names))
+            .collect();
+
+        let now = gitlab_timestamp();
+        let scanner = GitLabScanner {
+            id: "parsentry".to_string(),
+            name: "Parsentry".to_string(),
+            version,
+            vendor: GitLabVendor {
+                name: "Parsentry".to_string(),
+            },
+        };
+
+        Self {
+            version: GITLAB_SAST_SCHEMA_VERSION.to_string(),
+            vulnerabilities,
+            scan: GitLabScan {
+                analyzer: GitLabScanner {
+                    id: scanner.id.clone(),
+                    name: scanner.name.clone(),
+                    version: scanner.version.clone(),
+                    vendor: GitLabVendor {
+                        name: scanner.vendor.name.clone(),
+                    },
+                },
+                scanner,
+                kind: "sast".to_string(),
+                start_time: now.clone(),
+                end_time: now,
+                status: "success".to_string(),
+            },
+        }
+    }
+
+    /// Export report to JSON string
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Save report to file
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        write_atomic(path, self.to_json()?)?;
+        Ok(())
+    }
+}
+
+fn to_vulnerability(
+    result: &SarifResult,
+    rule_names: &HashMap<String, String>,
+) -> GitLabVulnerability {
+    let (file, start_line, end_line) = code_location(result);
+
+    let mut identifiers = vec![GitLabIdentifier {
+        kind: "parsentry_rule_id".to_string(),
+        name: format!("Parsentry - {}", result.rule_id),
+        value: result.rule_id.clone(),
+        url: None,
+    }];
+    if let Some(props) = &result.properties {
+        for cwe in props.cwe.iter().flatten() {
+            let number = cwe.trim_start_matches("CWE-");
+            identifiers.push(GitLabIdentifier {
+                kind: "cwe".to_string(),
+                name: cwe.clone(),
+                value: number.to_string(),
+                url: Some(format!(
+                    "https://cwe.mitre.org/data/definitions/{}.html",
+                    number
+                )),
+            });
+        }
+    }
+
+    let name = rule_names
+        .get(&result.rule_id)
+        .cloned()
+        .unwrap_or_else(|| result.rule_id.clone());
+
+    GitLabVulnerability {
+        id: vulnerability_id(result, &file, start_line),
+        name,
+        description: result
+            .message
+            .markdown
+            .clone()
+            .unwrap_or_else(|| result.message.text.clone()),
+        severity: level_to_severity(&result.level).to_string(),
+        identifiers,
+        location: GitLabLocation {
+            file,
+            start_line,
+            end_line,
+        },
+    }
+}
+
+/// Map a SARIF level onto GitLab's severity scale.
+fn level_to_severity(level: &str) -> &'static str {
+    match level {
+        "error" => "High",
+        "warning" => "Medium",
+        "note" => "Low",
+        "none" | "info" => "Info",
+        _ => "Unknown",
+    }
+}
+
+/// Deterministic UUID-shaped id so GitLab can track a finding across pipelines.
+pub(crate) fn vulnerability_id(result: &SarifResult, file: &str, line: Option<u32>) -> String {
+    let seed = match result
+        .fingerprints
+        .as_ref()
+        .and_then(|fp| fp.get("parsentry/v1"))
+    {
+        Some(fp) => fp.clone(),
+        None => format!("{}:{}:{}", result.rule_id, file, line.unwrap_or(0)),
+    };
+    let hex: String = Sha256::digest(seed.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+fn gitlab_timestamp() -> String {
+    chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parsentry_core::{Response, VulnType};
+    use std::path::PathBuf;
+
+    fn sample_summary() -> AnalysisSummary {
+        let mut summary = AnalysisSummary::new();
+        summary.add_result(
+            PathBuf::from("app/db.py"),
+            Response {
+                analysis: "User input reaches raw SQL".to_string(),
+                confidence_score: 95,
+                vulnerability_types: vec![VulnType::SQLI],
+                ..Default::default()
+            },
+            "db.py.md".to_string(),
+        );
+        summary
+    }
+
+    #[test]
+    fn converts_summary_to_gitlab_schema() {
+        let report = GitLabSastReport::from_analysis_summary(&sample_summary(), "1.0.0");
+        assert_eq!(report.version, GITLAB_SAST_SCHEMA_VERSION);
+        assert_eq!(report.scan.kind, "sast");
+        assert_eq!(report.vulnerabilities.len(), 1);
+
+        let vuln = &report.vulnerabilities[0];
+        assert_eq!(vuln.severity, "High");
+        assert_eq!(vuln.location.file, "app/db.py");
+        assert!(
+            vuln.identifiers
+                .iter()
+                .any(|i| i.kind == "cwe" && i.value == "89")
+        );
+        assert_eq!(vuln.id.len(), 36);
+    }
+
+    #[test]
+    fn ids_are_stable_across_conversions() {
+        let a = GitLabSastReport::from_analysis_summary(&sample_summary(), "1.0.0");
+        let b = GitLabSastReport::from_analysis_summary(&sample_summary(), "1.0.0");
+        assert_eq!(a.vulnerabilities[0].id, b.vulnerabilities[0].id);
+    }
+
+    #[test]
+    fn json_uses_gitlab_field_names() {
+        let report = GitLabSastReport::from_analysis_summary(&sample_summary(), "1.0.0");
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["scan"]["type"], "sast");
+        assert_eq!(
+            json["vulnerabilities"][0]["identifiers"][0]["type"],
+            "parsentry_rule_id"
+        );
+    }
+
+    #[test]
+    fn level_mapping() {
+        assert_eq!(level_to_severity("error"), "High");
+        assert_eq!(level_to_severity("warning"), "Medium");
+        assert_eq!(level_to_severity("note"), "Low");
+        assert_eq!(level_to_severity("bogus"), "Unknown");
+    }
+}
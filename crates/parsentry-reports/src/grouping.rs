@@ -0,0 +1,355 @@
+//! Findings grouped by vulnerability type, top-level directory and owner.
+//!
+//! Owners come from the scanned repository's CODEOWNERS file: `generate`
+//! records the owners of each finding's file in `properties.owners`, so
+//! large reports can be split up and handed to the teams responsible.
+
+use globset::{GlobBuilder, GlobMatcher};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::sarif::{SarifReport, SarifResult};
+
+/// Where GitHub looks for a CODEOWNERS file, in order.
+pub const CODEOWNERS_PATHS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// Group label of findings without a file location.
+const NO_LOCATION: &str = "(no location)";
+/// Group label of findings whose file has no owner.
+const UNOWNED: &str = "(unowned)";
+
+/// Owners of repository paths, as listed in a CODEOWNERS file.
+#[derive(Debug, Clone, Default)]
+pub struct CodeOwners {
+    rules: Vec<(Vec<GlobMatcher>, Vec<String>)>,
+}
+
+impl CodeOwners {
+    /// Parse CODEOWNERS `text`; lines with an invalid pattern are skipped.
+    pub fn parse(text: &str) -> Self {
+        let mut rules = Vec::new();
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else {
+                continue;
+            };
+            let owners: Vec<String> = parts.map(str::to_string).collect();
+            if let Some(matchers) = matchers(pattern) {
+                rules.push((matchers, owners));
+            }
+        }
+        Self { rules }
+    }
+
+    /// The first CODEOWNERS file of [`CODEOWNERS_PATHS`] in `repo_root`.
+    pub fn load(repo_root: &Path) -> Option<Self> {
+        CODEOWNERS_PATHS
+            .iter()
+            .find_map(|path| std::fs::read_to_string(repo_root.join(path)).ok())
+            .map(|text| Self::parse(&text))
+    }
+
+    /// Owners of the repository-relative `path`; the last matching rule
+    /// wins, and a rule without owners leaves the path unowned.
+    pub fn owners(&self, path: &str) -> &[String] {
+        let path = path.trim_start_matches("./").trim_start_matches('/');
+        self.rules
+            .iter()
+            .rev()
+            .find(|(matchers, _)| matchers.iter().any(|m| m.is_match(path)))
+            .map_or(&[], |(_, owners)| owners.as_slice())
+    }
+
+    /// Record the owners of each result's file in `properties.owners`.
+    pub fn assign(&self, report: &mut SarifReport) {
+        for result in report.runs.iter_mut().flat_map(|run| &mut run.results) {
+            let Some(uri) = file(result) else {
+                continue;
+            };
+            let owners = self.owners(uri);
+            if !owners.is_empty() {
+                result
+                    .properties
+                    .get_or_insert_with(Default::default)
+                    .owners = Some(owners.to_vec());
+            }
+        }
+    }
+}
+
+/// Glob matchers for a CODEOWNERS pattern, following gitignore rules: a
+/// pattern without an inner `/` matches at any depth, and a pattern naming
+/// a directory also matches everything below it.
+fn matchers(pattern: &str) -> Option<Vec<GlobMatcher>> {
+    let directory = pattern.ends_with('/');
+    let trimmed = pattern.trim_end_matches('/');
+    let anchored = trimmed.contains('/');
+    let trimmed = trimmed.trim_start_matches('/');
+    if trimmed.is_empty() {
+        return None;
+    }
+    let base = if anchored || trimmed.starts_with("**") {
+        trimmed.to_string()
+    } else {
+        format!("**/{trimmed}")
+    };
+    let mut globs = Vec::new();
+    if !directory {
+        globs.push(base.clone());
+    }
+    // `docs/*` owns the files directly in docs/, not those in subdirectories
+    let last = trimmed.rsplit('/').next().unwrap_or(trimmed);
+    if directory || !last.contains('*') {
+        globs.push(format!("{base}/**"));
+    }
+    globs
+        .iter()
+        .map(|glob| {
+            GlobBuilder::new(glob)
+                .literal_separator(true)
+                .build()
+                .ok()
+                .map(|g| g.compile_matcher())
+        })
+        .collect()
+}
+
+/// File of a result's first location.
+fn file(result: &SarifResult) -> Option<&str> {
+    result
+        .locations
+        .first()
+        .map(|l| l.physical_location.artifact_location.uri.as_str())
+}
+
+/// How findings are grouped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grouping {
+    /// By rule id, e.g. `SQLI`.
+    VulnType,
+    /// By the first directory of the file's path.
+    Directory,
+    /// By CODEOWNERS owner; a finding with several owners is in each group.
+    Owner,
+}
+
+impl Grouping {
+    pub const ALL: [Grouping; 3] = [Grouping::VulnType, Grouping::Directory, Grouping::Owner];
+
+    /// Section title, e.g. "By Vulnerability Type".
+    pub fn title(self) -> &'static str {
+        match self {
+            Grouping::VulnType => "By Vulnerability Type",
+            Grouping::Directory => "By Directory",
+            Grouping::Owner => "By Owner",
+        }
+    }
+
+    /// Column header of the group names.
+    pub fn label(self) -> &'static str {
+        match self {
+            Grouping::VulnType => "Vulnerability",
+            Grouping::Directory => "Directory",
+            Grouping::Owner => "Owner",
+        }
+    }
+
+    fn keys(self, result: &SarifResult) -> Vec<String> {
+        match self {
+            Grouping::VulnType => vec![result.rule_id.clone()],
+            Grouping::Directory => {
+                let Some(uri) = file(result) else {
+                    return vec![NO_LOCATION.to_string()];
+                };
+                let path = uri.trim_start_matches("./").trim_start_matches('/');
+                match path.split_once('/') {
+                    Some((dir, _)) => vec![format!("{dir}/")],
+                    None => vec!["/".to_string()],
+                }
+            }
+            Grouping::Owner => match result.properties.as_ref().and_then(|p| p.owners.clone()) {
+                Some(owners) if !owners.is_empty() => owners,
+                _ => vec![UNOWNED.to_string()],
+            },
+        }
+    }
+}
+
+/// Findings grouped by `grouping`, largest group first.
+pub fn group<'a>(
+    results: impl IntoIterator<Item = &'a SarifResult>,
+    grouping: Grouping,
+) -> Vec<(String, Vec<&'a SarifResult>)> {
+    let mut groups: BTreeMap<String, Vec<&SarifResult>> = BTreeMap::new();
+    for result in results {
+        for key in grouping.keys(result) {
+            groups.entry(key).or_default().push(result);
+        }
+    }
+    let mut groups: Vec<_> = groups.into_iter().collect();
+    groups.sort_by_key(|(_, members)| std::cmp::Reverse(members.len()));
+    groups
+}
+
+/// Whether grouping `results` by `grouping` tells the reader anything:
+/// there is more than one group, or for owners, some finding has one.
+pub fn is_informative(results: &[&SarifResult], grouping: Grouping) -> bool {
+    let groups = group(results.iter().copied(), grouping);
+    match grouping {
+        Grouping::Owner => groups.iter().any(|(owner, _)| owner != UNOWNED),
+        _ => groups.len() > 1,
+    }
+}
+
+/// `report` split into one report per CODEOWNERS owner, plus one for
+/// unowned findings, each with only that owner's findings. A finding with
+/// several owners is in each of their reports. Owners are in name order.
+pub fn split_by_owner(report: &SarifReport) -> Vec<(String, SarifReport)> {
+    let mut owners: BTreeMap<String, SarifReport> = BTreeMap::new();
+    for (index, run) in report.runs.iter().enumerate() {
+        for result in &run.results {
+            for owner in Grouping::Owner.keys(result) {
+                let split = owners.entry(owner).or_insert_with(|| {
+                    let mut empty = report.clone();
+                    for run in &mut empty.runs {
+                        run.results.clear();
+                    }
+                    empty
+                });
+                split.runs[index].results.push(result.clone());
+            }
+        }
+    }
+    owners.into_iter().collect()
+}
+
+/// File name stem for an owner's report: `@org/api` is `org-api`.
+pub fn owner_file_stem(owner: &str) -> String {
+    let stem: String = owner
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    stem.trim_matches(['-', '.']).to_string()
+}
+
+/// Markdown tables of `results` per vulnerability type, directory and
+/// owner, leaving out groupings that tell the reader nothing.
+pub fn grouped_markdown(results: &[&SarifResult]) -> String {
+    let mut md = String::new();
+    for grouping in Grouping::ALL {
+        if !is_informative(results, grouping) {
+            continue;
+        }
+        md.push_str(&format!(
+            "## {}\n\n| {} | Error | Warning | Note | Total |\n|---|---|---|---|---|\n",
+            grouping.title(),
+            grouping.label()
+        ));
+        for (key, members) in group(results.iter().copied(), grouping) {
+            let count = |level: &str| members.iter().filter(|r| r.level == level).count();
+            md.push_str(&format!(
+                "| `{key}` | {} | {} | {} | {} |\n",
+                count("error"),
+                count("warning"),
+                members.len() - count("error") - count("warning"),
+                members.len()
+            ));
+        }
+        md.push('\n');
+    }
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{ResultFixture, report, result};
+
+    #[test]
+    fn matches_codeowners_rules() {
+        let owners = CodeOwners::parse(
+            "# comment\n\
+             *          @org/security\n\
+             /api/      @org/api\n\
+             docs/*     @org/docs\n\
+             *.tf       @org/infra\n\
+             /api/vendor/\n",
+        );
+        assert_eq!(owners.owners("api/users.py"), ["@org/api"]);
+        assert_eq!(owners.owners("./api/v1/users.py"), ["@org/api"]);
+        assert_eq!(owners.owners("docs/index.md"), ["@org/docs"]);
+        assert_eq!(owners.owners("docs/a/b.md"), ["@org/security"]);
+        assert_eq!(owners.owners("deploy/main.tf"), ["@org/infra"]);
+        assert!(owners.owners("api/vendor/lib.py").is_empty());
+        assert_eq!(owners.owners("web/app.js"), ["@org/security"]);
+    }
+
+    #[test]
+    fn groups_by_type_directory_and_owner() {
+        let owners = CodeOwners::parse("/api/ @org/api\n/web/ @org/web @alice\n");
+        let mut report = report([
+            result("SQLI", "api/db.py"),
+            result("SQLI", "api/search.py").level("warning"),
+            result("XSS", "web/view.js"),
+            result("RCE", "setup.py"),
+        ]);
+        owners.assign(&mut report);
+        let results: Vec<&SarifResult> = report.runs[0].results.iter().collect();
+
+        let by_type = group(results.iter().copied(), Grouping::VulnType);
+        assert_eq!(by_type[0].0, "SQLI");
+        assert_eq!(by_type[0].1.len(), 2);
+        let dirs: Vec<String> = group(results.iter().copied(), Grouping::Directory)
+            .into_iter()
+            .map(|(dir, _)| dir)
+            .collect();
+        assert_eq!(dirs, vec!["api/", "/", "web/"]);
+        let by_owner = group(results.iter().copied(), Grouping::Owner);
+        let owner_keys: Vec<&str> = by_owner.iter().map(|(o, _)| o.as_str()).collect();
+        assert_eq!(
+            owner_keys,
+            vec!["@org/api", "(unowned)", "@alice", "@org/web"]
+        );
+
+        let md = grouped_markdown(&results);
+        assert!(md.contains("## By Vulnerability Type"));
+        assert!(md.contains("| `SQLI` | 1 | 1 | 0 | 2 |"));
+        assert!(md.contains("## By Owner"));
+    }
+
+    #[test]
+    fn splits_report_per_owner() {
+        let owners = CodeOwners::parse("/api/ @org/api\n/web/ @org/web @alice\n");
+        let mut report = report([
+            result("SQLI", "api/db.py"),
+            result("XSS", "web/view.js"),
+            result("RCE", "setup.py"),
+        ]);
+        owners.assign(&mut report);
+
+        let split = split_by_owner(&report);
+        let rules = |owner: &str| -> Vec<String> {
+            split.iter().find(|(o, _)| o == owner).unwrap().1.runs[0]
+                .results
+                .iter()
+                .map(|r| r.rule_id.clone())
+                .collect()
+        };
+        assert_eq!(split.len(), 4);
+        assert_eq!(rules("@org/api"), ["SQLI"]);
+        assert_eq!(rules("@org/web"), ["XSS"]);
+        assert_eq!(rules("@alice"), ["XSS"]);
+        assert_eq!(rules("(unowned)"), ["RCE"]);
+
+        assert_eq!(owner_file_stem("@org/api"), "org-api");
+        assert_eq!(owner_file_stem("(unowned)"), "unowned");
+        assert_eq!(owner_file_stem("dev@example.com"), "dev-example.com");
+    }
+}
@@ -0,0 +1,376 @@
+//! Self-contained HTML dashboard for merged SARIF.
+//!
+//! Each run is one repository (see [`crate::merge::combine_reports`]): the
+//! dashboard opens with a per-repository summary table and drills down
+//! into each repository's findings with `<details>` elements, so the file
+//! works offline without scripts. A repository with recorded scan history
+//! also gets an inline SVG chart of its finding count over time.
+
+use crate::grouping::{Grouping, group, is_informative};
+use crate::risk::by_risk;
+use crate::sarif::{SarifReport, SarifResult, SarifRun};
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;margin:2rem auto;max-width:72rem;padding:0 1rem;color:#1f2328}\
+table{border-collapse:collapse;width:100%}th,td{border-bottom:1px solid #d0d7de;padding:.4rem .6rem;text-align:left}\
+td.n{text-align:right}summary{cursor:pointer}section>details>summary{font-size:1.25rem;font-weight:600;margin:1.5rem 0 .5rem}\
+details.finding{border-left:4px solid #d0d7de;margin:.4rem 0;padding:.3rem .8rem}details.error{border-color:#cf222e}\
+details.warning{border-color:#bf8700}details.note{border-color:#0969da}.level{font-weight:600;text-transform:uppercase}\
+.partial{color:#9a6700}dt{font-weight:600}pre{background:#f6f8fa;overflow-x:auto;padding:.6rem}\
+svg.trend{display:block;max-width:40rem}svg.trend rect{fill:#0969da}svg.trend rect:last-of-type{fill:#1f2328}";
+
+/// Chart size in SVG user units.
+const TREND_WIDTH: usize = 600;
+const TREND_HEIGHT: usize = 100;
+
+/// One recorded scan of a repository, for the trend chart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrendPoint {
+    /// When the scan was recorded, e.g. `2026-01-01 12:00`.
+    pub date: String,
+    pub commit: Option<String>,
+    pub total: usize,
+    pub duration_secs: Option<f64>,
+    pub cost_usd: Option<f64>,
+}
+
+/// Render `report` as a standalone HTML page. `trends[i]` is the scan
+/// history of run `i`, oldest first; runs without one get no chart.
+pub fn to_html_dashboard(report: &SarifReport, trends: &[Vec<TrendPoint>]) -> String {
+    let runs: Vec<(String, Vec<&SarifResult>)> = report
+        .runs
+        .iter()
+        .enumerate()
+        .map(|(i, run)| (run_label(run, i), active_results(run)))
+        .collect();
+    let total: usize = runs.iter().map(|(_, results)| results.len()).sum();
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>Parsentry Security Dashboard</title>\n");
+    html.push_str(&format!("<style>{STYLE}</style>\n</head>\n<body>\n"));
+    html.push_str("<h1>Security Dashboard</h1>\n");
+    html.push_str(&format!(
+        "<p>{total} findings across {} repositories</p>\n",
+        runs.len()
+    ));
+
+    html.push_str("<table>\n<thead><tr><th>Repository</th><th>Error</th><th>Warning</th><th>Note</th><th>Total</th></tr></thead>\n<tbody>\n");
+    for (i, (label, results)) in runs.iter().enumerate() {
+        let count = |level: &str| results.iter().filter(|r| r.level == level).count();
+        html.push_str(&format!(
+            "<tr><td><a href=\"#run-{}\">{}</a></td><td class=\"n\">{}</td><td class=\"n\">{}</td><td class=\"n\">{}</td><td class=\"n\">{}</td></tr>\n",
+            i + 1,
+            escape(label),
+            count("error"),
+            count("warning"),
+            results.len() - count("error") - count("warning"),
+            results.len()
+        ));
+    }
+    html.push_str("</tbody>\n</table>\n");
+
+    for (i, ((label, results), run)) in runs.iter().zip(&report.runs).enumerate() {
+        html.push_str(&format!(
+            "<section id=\"run-{}\">\n<details open>\n<summary>{} — {} findings</summary>\n",
+            i + 1,
+            escape(label),
+            results.len()
+        ));
+        if let Some(props) = run.invocation.as_ref().and_then(|i| i.properties.as_ref()) {
+            let not_analyzed: Vec<&String> = props
+                .timed_out
+                .iter()
+                .chain(&props.missing_results)
+                .collect();
+            if !not_analyzed.is_empty() {
+                html.push_str(&format!(
+                    "<p class=\"partial\">Partial results; not analyzed: {}</p>\n",
+                    escape(
+                        &not_analyzed
+                            .iter()
+                            .map(|s| s.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                ));
+            }
+        }
+        if let Some(trend) = trends.get(i) {
+            html.push_str(&render_trend(trend));
+        }
+        html.push_str(&render_groups(results));
+        if results.is_empty() {
+            html.push_str("<p>No findings.</p>\n");
+        }
+        for result in results {
+            html.push_str(&render_finding(result));
+        }
+        html.push_str("</details>\n</section>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Repository name of `run`, from the `automationDetails.id` that
+/// `combine_reports` assigns.
+fn run_label(run: &SarifRun, index: usize) -> String {
+    run.automation_details
+        .as_ref()
+        .map(|d| {
+            let id = d.id.strip_prefix("parsentry/").unwrap_or(&d.id);
+            id.strip_suffix('/').unwrap_or(id).to_string()
+        })
+        .unwrap_or_else(|| format!("run {}", index + 1))
+}
+
+/// Unsuppressed findings still present, most severe first.
+fn active_results(run: &SarifRun) -> Vec<&SarifResult> {
+    let rank = |level: &str| match level {
+        "error" => 0,
+        "warning" => 1,
+        _ => 2,
+    };
+    let mut results: Vec<&SarifResult> = run
+        .results
+        .iter()
+        .filter(|r| r.baseline_state.as_deref() != Some("absent"))
+        .filter(|r| r.suppressions.as_ref().is_none_or(|s| s.is_empty()))
+        .collect();
+    results.sort_by(|a, b| {
+        rank(&a.level)
+            .cmp(&rank(&b.level))
+            .then_with(|| by_risk(a, b))
+            .then_with(|| location(a).cmp(&location(b)))
+    });
+    results
+}
+
+fn location(result: &SarifResult) -> String {
+    let Some(loc) = result.locations.first() else {
+        return "unknown".to_string();
+    };
+    let uri = &loc.physical_location.artifact_location.uri;
+    match &loc.physical_location.region {
+        Some(region) => format!("{uri}:{}", region.start_line),
+        None => uri.clone(),
+    }
+}
+
+/// Tables of `results` per vulnerability type, directory and owner.
+fn render_groups(results: &[&SarifResult]) -> String {
+    let mut html = String::new();
+    for grouping in Grouping::ALL {
+        if !is_informative(results, grouping) {
+            continue;
+        }
+        html.push_str(&format!(
+            "<details class=\"groups\">\n<summary>{}</summary>\n<table>\n<thead><tr><th>{}</th><th>Error</th><th>Warning</th><th>Note</th><th>Total</th></tr></thead>\n<tbody>\n",
+            grouping.title(),
+            grouping.label()
+        ));
+        for (key, members) in group(results.iter().copied(), grouping) {
+            let count = |level: &str| members.iter().filter(|r| r.level == level).count();
+            html.push_str(&format!(
+                "<tr><td>{}</td><td class=\"n\">{}</td><td class=\"n\">{}</td><td class=\"n\">{}</td><td class=\"n\">{}</td></tr>\n",
+                escape(&key),
+                count("error"),
+                count("warning"),
+                members.len() - count("error") - count("warning"),
+                members.len()
+            ));
+        }
+        html.push_str("</tbody>\n</table>\n</details>\n");
+    }
+    html
+}
+
+/// Bar chart of the finding count per scan, with the scan's commit,
+/// duration and cost as a tooltip. Needs at least two scans.
+fn render_trend(points: &[TrendPoint]) -> String {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return String::new();
+    };
+    if points.len() < 2 {
+        return String::new();
+    }
+    let max = points.iter().map(|p| p.total).max().unwrap_or(0).max(1);
+    let slot = TREND_WIDTH / points.len();
+    let delta = match last.total as i64 - first.total as i64 {
+        0 => "±0".to_string(),
+        delta => format!("{delta:+}"),
+    };
+    let mut html = format!(
+        "<p>{} scans: {} → {} findings ({delta})</p>\n<svg class=\"trend\" viewBox=\"0 0 {TREND_WIDTH} {TREND_HEIGHT}\" role=\"img\" aria-label=\"Findings per scan\">\n",
+        points.len(),
+        first.total,
+        last.total
+    );
+    for (i, point) in points.iter().enumerate() {
+        let height = (point.total * TREND_HEIGHT).div_ceil(max).max(1);
+        let mut tooltip = format!("{}: {} findings", point.date, point.total);
+        if let Some(commit) = &point.commit {
+            tooltip.push_str(&format!(", {}", &commit[..commit.len().min(8)]));
+        }
+        if let Some(secs) = point.duration_secs {
+            tooltip.push_str(&format!(", {secs:.0}s"));
+        }
+        if let Some(cost) = point.cost_usd {
+            tooltip.push_str(&format!(", ${cost:.2}"));
+        }
+        html.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{height}\"><title>{}</title></rect>\n",
+            i * slot,
+            TREND_HEIGHT - height,
+            (slot * 4 / 5).max(1),
+            escape(&tooltip)
+        ));
+    }
+    html.push_str("</svg>\n");
+    html
+}
+
+fn render_finding(result: &SarifResult) -> String {
+    let mut html = format!(
+        "<details class=\"finding {}\">\n<summary><span class=\"level\">{}</span> {} — {}</summary>\n",
+        escape(&result.level),
+        escape(&result.level),
+        escape(&result.rule_id),
+        escape(&location(result))
+    );
+    html.push_str(&format!("<p>{}</p>\n", escape(&result.message.text)));
+
+    let mut facts: Vec<(&str, String)> = Vec::new();
+    if let Some(props) = &result.properties {
+        if let Some(confidence) = props.confidence {
+            facts.push(("Confidence", format!("{confidence:.2}")));
+        }
+        if let Some(cwe) = props.cwe.as_ref().filter(|c| !c.is_empty()) {
+            facts.push(("CWE", cwe.join(", ")));
+        }
+        for (name, value) in [
+            ("Principal", &props.principal),
+            ("Action", &props.action),
+            ("Resource", &props.resource),
+            ("Data flow", &props.data_flow),
+        ] {
+            if let Some(value) = value {
+                facts.push((name, value.clone()));
+            }
+        }
+        if let Some(blame) = &props.blame {
+            facts.push((
+                "Last changed",
+                format!(
+                    "{} by {} on {} ({} days ago)",
+                    &blame.commit[..blame.commit.len().min(12)],
+                    blame.author,
+                    blame.date,
+                    blame.age_days
+                ),
+            ));
+        }
+    }
+    if let Some(state) = &result.baseline_state {
+        facts.push(("Baseline", state.clone()));
+    }
+    if !facts.is_empty() {
+        html.push_str("<dl>\n");
+        for (name, value) in facts {
+            html.push_str(&format!("<dt>{name}</dt><dd>{}</dd>\n", escape(&value)));
+        }
+        html.push_str("</dl>\n");
+    }
+
+    if let Some(snippet) = result
+        .locations
+        .first()
+        .and_then(|l| l.physical_location.region.as_ref())
+        .and_then(|r| r.snippet.as_ref())
+    {
+        html.push_str(&format!(
+            "<pre><code>{}</code></pre>\n",
+            escape(&snippet.text)
+        ));
+    }
+    html.push_str("</details>\n");
+    html
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merge::combine_reports;
+    use crate::test_support::{ResultFixture, report, result};
+
+    #[test]
+    fn dashboard_summarizes_and_drills_into_each_repository() {
+        let combined = combine_reports(vec![
+            (
+                "acme/api".to_string(),
+                report([
+                    result("XSS", "b.py")
+                        .level("note")
+                        .message("reflected")
+                        .line(7),
+                    result("SQLI", "a.py")
+                        .message("query built from <input>")
+                        .line(7),
+                ]),
+            ),
+            ("acme/web".to_string(), report([])),
+        ]);
+        let html = to_html_dashboard(&combined, &[]);
+
+        assert!(html.contains("<p>2 findings across 2 repositories</p>"));
+        assert!(html.contains(
+            "<tr><td><a href=\"#run-1\">acme/api</a></td><td class=\"n\">1</td><td class=\"n\">0</td><td class=\"n\">1</td><td class=\"n\">2</td></tr>"
+        ));
+        assert!(html.contains("<summary>acme/web — 0 findings</summary>\n<p>No findings.</p>"));
+        assert!(html.contains("<p>query built from &lt;input&gt;</p>"));
+        let sqli = html.find("SQLI — a.py:7").unwrap();
+        let xss = html.find("XSS — b.py:7").unwrap();
+        assert!(sqli < xss, "errors come before notes");
+        assert!(!html.contains("<svg"));
+    }
+
+    #[test]
+    fn dashboard_charts_scan_history() {
+        let point = |date: &str, total| TrendPoint {
+            date: date.to_string(),
+            commit: Some("0123456789abcdef".to_string()),
+            total,
+            duration_secs: Some(90.0),
+            cost_usd: Some(1.5),
+        };
+        let combined = combine_reports(vec![
+            ("acme/api".to_string(), report([result("SQLI", "a.py")])),
+            ("acme/web".to_string(), report([])),
+        ]);
+        let trends = vec![
+            vec![point("2026-01-01", 4), point("2026-01-02", 1)],
+            vec![point("2026-01-02", 0)],
+        ];
+        let html = to_html_dashboard(&combined, &trends);
+
+        assert_eq!(html.matches("<svg").count(), 1, "one scan is no trend");
+        assert!(html.contains("<p>2 scans: 4 → 1 findings (-3)</p>"));
+        assert!(html.contains("<title>2026-01-01: 4 findings, 01234567, 90s, $1.50</title>"));
+        assert!(html.contains("<rect x=\"0\" y=\"0\" width=\"240\" height=\"100\">"));
+        assert!(html.contains("<rect x=\"300\" y=\"75\" width=\"240\" height=\"25\">"));
+    }
+}
@@ -0,0 +1,176 @@
+//! Lines a commit-range scan looked at, and the commit that introduced each.
+//!
+//! `scan --commits A..B` records the lines added between `A` and `B` with
+//! their introducing commit in [`INTRODUCED_FILE`]; `merge` keeps the
+//! findings in changed files and attributes each one located on an
+//! introduced line to its commit and author.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::output::write_atomic;
+use crate::sarif::SarifResult;
+
+/// Introduced lines recorded by `scan --commits` in the reports directory.
+pub const INTRODUCED_FILE: &str = "introduced.json";
+
+/// The commit that introduced a line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Introduction {
+    pub commit: String,
+    /// `Name <email>` of the commit author.
+    pub author: String,
+    /// First line of the commit message.
+    pub summary: String,
+}
+
+/// Consecutive lines of a file introduced by one commit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntroducedHunk {
+    /// Path relative to the repository root.
+    pub path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    #[serde(flatten)]
+    pub introduction: Introduction,
+}
+
+/// Lines introduced by a commit range.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntroducedChanges {
+    /// The range as given, e.g. `v1.2.0..v1.3.0`.
+    pub range: String,
+    pub hunks: Vec<IntroducedHunk>,
+}
+
+/// Whether a SARIF location `uri` names the repository-relative `path`.
+fn uri_matches(uri: &str, path: &str) -> bool {
+    let uri = uri.strip_prefix("./").unwrap_or(uri);
+    uri == path || uri.ends_with(&format!("/{path}"))
+}
+
+impl IntroducedChanges {
+    pub fn load(reports_dir: &Path) -> Option<Self> {
+        let json = std::fs::read_to_string(reports_dir.join(INTRODUCED_FILE)).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    pub fn save(&self, reports_dir: &Path) -> Result<()> {
+        write_atomic(
+            reports_dir.join(INTRODUCED_FILE),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+
+    /// Files with introduced lines.
+    pub fn files(&self) -> BTreeSet<&str> {
+        self.hunks.iter().map(|h| h.path.as_str()).collect()
+    }
+
+    /// Hunks in any of `files`.
+    pub fn hunks_in<'a>(&'a self, files: &[String]) -> Vec<&'a IntroducedHunk> {
+        self.hunks
+            .iter()
+            .filter(|h| files.iter().any(|f| f == &h.path))
+            .collect()
+    }
+
+    /// The commit that introduced `line` of the file at `uri`.
+    pub fn introduction(&self, uri: &str, line: usize) -> Option<&Introduction> {
+        self.hunks
+            .iter()
+            .find(|h| uri_matches(uri, &h.path) && (h.start_line..=h.end_line).contains(&line))
+            .map(|h| &h.introduction)
+    }
+
+    /// Drop results outside the changed files and record the introducing
+    /// commit on those located on an introduced line.
+    pub fn attribute(&self, results: &mut Vec<SarifResult>) {
+        let files = self.files();
+        results.retain(|r| {
+            r.locations.iter().any(|loc| {
+                let uri = &loc.physical_location.artifact_location.uri;
+                files.iter().any(|path| uri_matches(uri, path))
+            })
+        });
+        for result in results {
+            let introduction = result.locations.iter().find_map(|loc| {
+                let region = loc.physical_location.region.as_ref()?;
+                self.introduction(
+                    &loc.physical_location.artifact_location.uri,
+                    usize::try_from(region.start_line).ok()?,
+                )
+            });
+            if let Some(introduction) = introduction {
+                result
+                    .properties
+                    .get_or_insert_with(Default::default)
+                    .introduced_by = Some(introduction.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{ResultFixture, result};
+
+    fn hunk(path: &str, start_line: usize, end_line: usize, commit: &str) -> IntroducedHunk {
+        IntroducedHunk {
+            path: path.to_string(),
+            start_line,
+            end_line,
+            introduction: Introduction {
+                commit: commit.to_string(),
+                author: "Alice <alice@example.com>".to_string(),
+                summary: "Add upload endpoint".to_string(),
+            },
+        }
+    }
+
+    fn result_at(uri: &str, line: i32) -> SarifResult {
+        result("SQLI", uri).line(line)
+    }
+
+    #[test]
+    fn attributes_results_on_introduced_lines() {
+        let changes = IntroducedChanges {
+            range: "v1..v2".to_string(),
+            hunks: vec![
+                hunk("src/app.py", 10, 20, "abc123"),
+                hunk("src/db.py", 3, 3, "def456"),
+            ],
+        };
+        let mut results = vec![
+            result_at("src/app.py", 12),
+            result_at("./src/db.py", 40),
+            result_at("src/other.py", 12),
+        ];
+        changes.attribute(&mut results);
+
+        assert_eq!(results.len(), 2);
+        let introduced = results[0]
+            .properties
+            .as_ref()
+            .unwrap()
+            .introduced_by
+            .as_ref();
+        assert_eq!(introduced.map(|i| i.commit.as_str()), Some("abc123"));
+        assert!(results[1].properties.is_none());
+    }
+
+    #[test]
+    fn round_trips_through_reports_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let changes = IntroducedChanges {
+            range: "main..HEAD".to_string(),
+            hunks: vec![hunk("app.py", 1, 2, "abc123")],
+        };
+        changes.save(dir.path()).unwrap();
+        assert_eq!(IntroducedChanges::load(dir.path()), Some(changes));
+    }
+}
@@ -21,6 +21,7 @@ use crate::report_common::{
     SURFACE_MARKER, build_markdown_body, build_title, extract_fingerprint, load_surface_reports,
     parse_fingerprint_from_body, parse_surface_from_body,
 };
+use parsentry_core::offline::ensure_online;
 
 const JIRA_LABEL: &str = "parsentry";
 
@@ -37,7 +38,8 @@ pub async fn run_jira_command(
     let email = env::var("JIRA_EMAIL").map_err(|_| anyhow!("JIRA_EMAIL not set"))?;
     let token = env::var("JIRA_API_TOKEN").map_err(|_| anyhow!("JIRA_API_TOKEN not set"))?;
 
-    let client = Client::new();
+    ensure_online("Jira export")?;
+    let client = parsentry_core::http::client()?;
     let auth = JiraAuth {
         base_url,
         email,
@@ -0,0 +1,158 @@
+//! JUnit XML output for CI test report views.
+//!
+//! Jenkins, GitLab and Azure DevOps show JUnit reports without plugins, so
+//! each finding at or above the minimum level becomes a failing test case
+//! in a test suite per run (one per target in a combined report).
+//! Suppressed findings are skipped test cases, and a suite without
+//! failures gets a single passing test case so the run still shows up.
+
+use crate::report_common::{build_markdown_body, code_location, level_passes};
+use crate::sarif::{SarifReport, SarifResult, SarifRun};
+
+/// JUnit XML for `report`, failing one test case per unsuppressed finding
+/// at or above `min_level`.
+pub fn to_junit_xml(report: &SarifReport, min_level: &str) -> String {
+    let (mut suites, mut tests, mut failures) = (String::new(), 0, 0);
+    for run in &report.runs {
+        let (suite, suite_tests, suite_failures) = suite_xml(run, min_level);
+        suites.push_str(&suite);
+        tests += suite_tests;
+        failures += suite_failures;
+    }
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <testsuites name=\"parsentry\" tests=\"{tests}\" failures=\"{failures}\">\n{suites}</testsuites>\n"
+    )
+}
+
+/// A run's test suite with its number of tests and failures.
+fn suite_xml(run: &SarifRun, min_level: &str) -> (String, usize, usize) {
+    let name = run
+        .automation_details
+        .as_ref()
+        .map(|d| d.id.trim_end_matches('/').to_string())
+        .unwrap_or_else(|| run.tool.driver.name.clone());
+    let mut cases = String::new();
+    let (mut tests, mut failures, mut skipped) = (0, 0, 0);
+    for result in &run.results {
+        if !level_passes(&result.level, min_level) {
+            continue;
+        }
+        tests += 1;
+        let suppressed = result.suppressions.as_ref().is_some_and(|s| !s.is_empty());
+        if suppressed {
+            skipped += 1;
+            cases.push_str(&case_xml(&name, result, "    <skipped/>\n"));
+        } else {
+            failures += 1;
+            let failure = format!(
+                "    <failure message=\"{}\" type=\"{}\">{}</failure>\n",
+                escape(&result.message.text),
+                escape(&result.rule_id),
+                escape(&build_markdown_body(result, None)),
+            );
+            cases.push_str(&case_xml(&name, result, &failure));
+        }
+    }
+    if failures == 0 {
+        tests += 1;
+        cases.push_str(&format!(
+            "  <testcase classname=\"{}\" name=\"no findings at or above {}\"/>\n",
+            escape(&name),
+            escape(min_level)
+        ));
+    }
+    let suite = format!(
+        "<testsuite name=\"{}\" tests=\"{tests}\" failures=\"{failures}\" skipped=\"{skipped}\">\n{cases}</testsuite>\n",
+        escape(&name)
+    );
+    (suite, tests, failures)
+}
+
+/// A test case named after the finding's rule and location, grouped by file.
+fn case_xml(suite: &str, result: &SarifResult, body: &str) -> String {
+    let (file, start_line, _) = code_location(result);
+    let classname = if file.is_empty() { suite } else { &file };
+    let name = match start_line {
+        Some(line) => format!("{} at {file}:{line}", result.rule_id),
+        None => result.rule_id.clone(),
+    };
+    format!(
+        "  <testcase classname=\"{}\" name=\"{}\">\n{body}  </testcase>\n",
+        escape(classname),
+        escape(&name)
+    )
+}
+
+/// Escape text for XML attributes and content, dropping control characters
+/// XML 1.0 does not allow.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sarif::SarifAutomationDetails;
+    use crate::test_support::{ResultFixture, result};
+
+    fn report(results: impl IntoIterator<Item = SarifResult>) -> SarifReport {
+        let mut report = crate::test_support::report(results);
+        report.runs[0].automation_details = Some(SarifAutomationDetails {
+            id: "parsentry/acme/app/".to_string(),
+        });
+        report
+    }
+
+    #[test]
+    fn fails_one_test_per_finding_above_threshold() {
+        let xml = to_junit_xml(
+            &report([
+                result("SQLI", "app/db.py")
+                    .message("query <built> from \"input\"")
+                    .line(12),
+                result("XSS", "")
+                    .level("warning")
+                    .message("reflected")
+                    .unlocated()
+                    .suppressed(Some("accepted")),
+                result("INFO", "")
+                    .level("note")
+                    .message("minor")
+                    .unlocated(),
+            ]),
+            "warning",
+        );
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+        assert!(xml.contains("<testsuites name=\"parsentry\" tests=\"2\" failures=\"1\">"));
+        assert!(xml.contains(
+            "<testsuite name=\"parsentry/acme/app\" tests=\"2\" failures=\"1\" skipped=\"1\">"
+        ));
+        assert!(xml.contains("<testcase classname=\"app/db.py\" name=\"SQLI at app/db.py:12\">"));
+        assert!(xml.contains(
+            "<failure message=\"query &lt;built&gt; from &quot;input&quot;\" type=\"SQLI\">"
+        ));
+        assert!(xml.contains("<skipped/>"));
+        assert!(!xml.contains("INFO"));
+    }
+
+    #[test]
+    fn passes_a_suite_without_findings() {
+        let xml = to_junit_xml(&report([]), "error");
+        assert!(xml.contains("tests=\"1\" failures=\"0\" skipped=\"0\""));
+        assert!(xml.contains("name=\"no findings at or above error\"/>"));
+    }
+}
@@ -3,26 +3,74 @@
 //! This crate provides various report formats:
 //! - Markdown reports
 //! - SARIF (Static Analysis Results Interchange Format)
+//! - GitLab SAST (gl-sast-report.json)
+//! - AWS Security Finding Format and OCSF
+//! - HTML dashboards
+//! - JUnit XML for CI test reports
 //! - Summary reports
 //! - Filename generation utilities
 
+pub mod artifact_uri;
+pub mod asff;
+pub mod binaries;
+pub mod blame;
+pub mod calibration;
+pub mod compare;
+pub mod consensus;
+pub mod coverage;
+pub mod defectdojo;
+pub mod diff;
+pub mod eval;
+pub mod feedback;
 pub mod filename;
+pub mod fingerprint;
+pub mod gitlab_sast;
+pub mod grouping;
+pub mod html;
+pub mod introduced;
 pub mod jira;
+pub mod junit;
 pub mod linear;
+pub mod manifest;
 pub mod markdown;
 pub mod merge;
+pub mod mobile;
+pub mod notify;
 pub mod notion;
+pub mod ocsf;
+pub mod output;
+pub mod policy;
+pub mod redact;
+pub mod repair;
+pub mod replay;
 pub mod report_common;
+pub mod report_template;
+pub mod risk;
 pub mod sarif;
+pub mod schema;
+pub mod signing;
 pub mod summary;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+pub mod transcript;
+pub mod triage;
 pub mod validation;
+pub mod verify;
 
+pub use artifact_uri::UriBase;
+pub use asff::{AsffAccount, AsffReport};
+pub use defectdojo::run_defectdojo_command;
 pub use filename::{generate_output_filename, generate_pattern_specific_filename};
+pub use gitlab_sast::GitLabSastReport;
+pub use html::{TrendPoint, to_html_dashboard};
 pub use jira::run_jira_command;
+pub use junit::to_junit_xml;
 pub use linear::run_linear_command;
 pub use markdown::to_markdown;
-pub use merge::merge_sarif_dir;
+pub use merge::{combine_reports, merge_sarif_dir};
+pub use notify::{ScanNotification, send_notifications};
 pub use notion::run_notion_command;
+pub use ocsf::OcsfReport;
 pub use report_common::{SurfaceReport, load_surface_reports};
 pub use sarif::{SarifReport, SarifResult, SarifResultProperties};
 pub use summary::AnalysisSummary;
@@ -21,6 +21,7 @@ use crate::report_common::{
     SURFACE_MARKER, build_markdown_body, build_title, extract_fingerprint, load_surface_reports,
     parse_fingerprint_from_body, parse_surface_from_body,
 };
+use parsentry_core::offline::ensure_online;
 
 const LINEAR_API: &str = "https://api.linear.app/graphql";
 const LINEAR_LABEL: &str = "parsentry";
@@ -33,7 +34,8 @@ pub async fn run_linear_command(
 ) -> Result<()> {
     let api_key = env::var("LINEAR_API_KEY").map_err(|_| anyhow!("LINEAR_API_KEY not set"))?;
 
-    let client = Client::new();
+    ensure_online("Linear export")?;
+    let client = parsentry_core::http::client()?;
 
     let surfaces = load_surface_reports(reports_dir, min_level)?;
     if surfaces.is_empty() {
@@ -0,0 +1,230 @@
+//! What a scan was produced with, for audit trails and replays.
+//!
+//! `scan` records the tool version, configuration, model, scan options,
+//! pattern files, commit and environment of a target in [`MANIFEST_FILE`],
+//! with the time each preparation phase took. `merge` embeds it in the
+//! SARIF invocation, and `scan --replay` compares it with the manifest of
+//! the results it restores.
+
+use crate::output::write_atomic;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Instant;
+
+use anyhow::Result;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Manifest recorded by `scan` in the reports directory.
+pub const MANIFEST_FILE: &str = "scan-manifest.json";
+
+/// Parameter recording how much source code prompts carried
+/// (`scan --no-source-upload`).
+pub const SOURCE_UPLOAD_PARAMETER: &str = "source_upload";
+/// `source_upload` of scans whose workers never read the code.
+pub const HASHED_SOURCE_UPLOAD: &str = "hashed";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScanManifest {
+    pub tool_version: String,
+    /// HEAD of the scanned checkout.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// The checkout had uncommitted changes `commit` does not describe.
+    #[serde(default)]
+    pub dirty: bool,
+    /// SHA-256 of the non-secret `PARSENTRY_*` settings in effect.
+    pub config_hash: String,
+    /// SHA-256 of the threat model the surfaces came from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threat_model_hash: Option<String>,
+    /// Worker model; `None` leaves the choice to the agent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Scan options and profile settings, by name.
+    #[serde(default)]
+    pub parameters: BTreeMap<String, String>,
+    /// SHA-256 of each pattern file and pack beyond the built-in patterns.
+    #[serde(default)]
+    pub patterns: BTreeMap<String, String>,
+    /// Operating system, architecture and CI provider.
+    #[serde(default)]
+    pub environment: BTreeMap<String, String>,
+    /// RFC 3339 start and end of the scan.
+    pub started_at: String,
+    pub finished_at: String,
+    #[serde(default)]
+    pub phases: Vec<PhaseTiming>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhaseTiming {
+    pub name: String,
+    pub millis: u64,
+}
+
+impl ScanManifest {
+    pub fn load(reports_dir: &Path) -> Option<Self> {
+        let json = std::fs::read_to_string(reports_dir.join(MANIFEST_FILE)).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    pub fn save(&self, reports_dir: &Path) -> Result<()> {
+        write_atomic(
+            reports_dir.join(MANIFEST_FILE),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+
+    /// Whether the findings were made without reading the code, from
+    /// names and structure alone.
+    pub fn reduced_precision(&self) -> bool {
+        self.parameters
+            .get(SOURCE_UPLOAD_PARAMETER)
+            .is_some_and(|mode| mode == HASHED_SOURCE_UPLOAD)
+    }
+
+    /// What differs between `self` and the manifest of an `other` scan
+    /// that would change its results, e.g. `model: a → b`. Timing and
+    /// environment are left out.
+    pub fn differences(&self, other: &ScanManifest) -> Vec<String> {
+        let show = |value: Option<&String>| value.map_or("-".to_string(), |v| short(v));
+        let mut differences = Vec::new();
+        let mut compare = |name: &str, ours: Option<&String>, theirs: Option<&String>| {
+            if ours != theirs {
+                differences.push(format!("{name}: {} → {}", show(theirs), show(ours)));
+            }
+        };
+        compare(
+            "tool_version",
+            Some(&self.tool_version),
+            Some(&other.tool_version),
+        );
+        compare("commit", self.commit.as_ref(), other.commit.as_ref());
+        compare(
+            "config_hash",
+            Some(&self.config_hash),
+            Some(&other.config_hash),
+        );
+        compare(
+            "threat_model_hash",
+            self.threat_model_hash.as_ref(),
+            other.threat_model_hash.as_ref(),
+        );
+        compare("model", self.model.as_ref(), other.model.as_ref());
+        for (kind, ours, theirs) in [
+            ("parameters", &self.parameters, &other.parameters),
+            ("patterns", &self.patterns, &other.patterns),
+        ] {
+            let names: std::collections::BTreeSet<&String> =
+                ours.keys().chain(theirs.keys()).collect();
+            for name in names {
+                compare(&format!("{kind}.{name}"), ours.get(name), theirs.get(name));
+            }
+        }
+        differences
+    }
+}
+
+/// Hashes and commit ids are shown by their first 12 characters.
+fn short(value: &str) -> String {
+    if value.len() == 64 || value.len() == 40 {
+        value[..12].to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Wall-clock time of consecutive scan phases.
+#[derive(Debug)]
+pub struct PhaseTimer {
+    started_at: DateTime<Utc>,
+    lap: Instant,
+    phases: Vec<PhaseTiming>,
+}
+
+impl PhaseTimer {
+    pub fn start() -> Self {
+        Self {
+            started_at: Utc::now(),
+            lap: Instant::now(),
+            phases: Vec::new(),
+        }
+    }
+
+    /// End the phase `name`, which started when the previous one ended.
+    pub fn lap(&mut self, name: &str) {
+        self.phases.push(PhaseTiming {
+            name: name.to_string(),
+            millis: self.lap.elapsed().as_millis() as u64,
+        });
+        self.lap = Instant::now();
+    }
+
+    /// Record the start, end and phases in `manifest`.
+    pub fn finish(self, manifest: &mut ScanManifest) {
+        manifest.started_at = self.started_at.to_rfc3339_opts(SecondsFormat::Secs, true);
+        manifest.finished_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        manifest.phases = self.phases;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_differences_that_change_results() {
+        let old = ScanManifest {
+            tool_version: "1.0.0".to_string(),
+            commit: Some("a".repeat(40)),
+            config_hash: "c".repeat(64),
+            model: Some("opus".to_string()),
+            parameters: BTreeMap::from([("samples".to_string(), "1".to_string())]),
+            environment: BTreeMap::from([("os".to_string(), "linux".to_string())]),
+            ..Default::default()
+        };
+        let mut new = old.clone();
+        new.environment
+            .insert("os".to_string(), "macos".to_string());
+        new.finished_at = "2026-01-01T00:00:00Z".to_string();
+        assert!(new.differences(&old).is_empty());
+        assert!(!new.reduced_precision());
+
+        new.model = Some("sonnet".to_string());
+        new.commit = Some("b".repeat(40));
+        new.parameters
+            .insert("samples".to_string(), "3".to_string());
+        new.parameters.insert(
+            SOURCE_UPLOAD_PARAMETER.to_string(),
+            HASHED_SOURCE_UPLOAD.to_string(),
+        );
+        assert!(new.reduced_precision());
+        new.patterns
+            .insert("vuln-patterns.yml".to_string(), "d".repeat(64));
+        assert_eq!(
+            new.differences(&old),
+            vec![
+                "commit: aaaaaaaaaaaa → bbbbbbbbbbbb",
+                "model: opus → sonnet",
+                "parameters.samples: 1 → 3",
+                "parameters.source_upload: - → hashed",
+                "patterns.vuln-patterns.yml: - → dddddddddddd",
+            ]
+        );
+    }
+
+    #[test]
+    fn times_phases() {
+        let mut timer = PhaseTimer::start();
+        timer.lap("discovery");
+        timer.lap("prompts");
+        let mut manifest = ScanManifest::default();
+        timer.finish(&mut manifest);
+        let names: Vec<&str> = manifest.phases.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["discovery", "prompts"]);
+        assert!(manifest.started_at <= manifest.finished_at);
+        assert!(manifest.started_at.ends_with('Z'));
+    }
+}
@@ -5,23 +5,48 @@
 //! each result with `baselineState` (new/unchanged/updated/absent).
 //!
 //! Results are matched across runs by fingerprint. If the agent didn't
-//! generate fingerprints, they are computed from `ruleId + file URI`.
+//! generate fingerprints, they are computed from the snippet's content (see
+//! [`crate::fingerprint`]), or from `ruleId + file URI` without one.
+//!
+//! Malformed worker output is repaired where possible (see
+//! [`crate::repair`]) and skipped with a warning otherwise.
+//!
+//! Findings triaged as false positive or accepted risk (see
+//! [`crate::triage`]) are suppressed.
+//!
+//! With `PARSENTRY_REDACT` set, secrets in the merged results are redacted
+//! (see [`crate::redact`]).
 
 use anyhow::{Context, Result};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::path::Path;
 
+use crate::artifact_uri::UriBase;
+use crate::coverage::{ScanCoverage, SkipReason};
+use crate::fingerprint::{CONTENT_FINGERPRINT, content_fingerprint};
+use crate::introduced::IntroducedChanges;
+use crate::manifest::ScanManifest;
+use crate::redact::Redactor;
+use crate::repair::{Validation, check_sarif};
 use crate::sarif::*;
+use crate::triage::{TRIAGE_FILE, Triage};
+use parsentry_core::{DEPENDENCIES_FILE, Dependency};
 
 /// Maximum SARIF file size (10 MiB) to prevent OOM from malicious agents.
 const MAX_SARIF_FILE_SIZE: u64 = 10 * 1024 * 1024;
 
+/// Marker the orchestrator leaves next to a worker's prompt when it gave up
+/// on the worker after its deadline.
+pub const TIMED_OUT_FILE: &str = "timed-out";
+
 /// Compute a stable fingerprint for a result.
 ///
-/// Uses agent-provided `fingerprints["parsentry/v1"]` if available.
-/// Otherwise falls back to `SHA256(ruleId + first location URI)`.
-fn fingerprint(result: &SarifResult) -> String {
+/// Uses agent-provided `fingerprints["parsentry/v1"]` if available, then
+/// the content fingerprint of the result's snippet (see
+/// [`content_fingerprint`]). Otherwise falls back to
+/// `SHA256(ruleId + first location URI)`.
+pub(crate) fn fingerprint(result: &SarifResult) -> String {
     // Use agent-provided fingerprint if available
     if let Some(ref fps) = result.fingerprints {
         if let Some(fp) = fps.get("parsentry/v1") {
@@ -32,6 +57,9 @@ fn fingerprint(result: &SarifResult) -> String {
             return fp.clone();
         }
     }
+    if let Some(fp) = snippet_fingerprint(result) {
+        return fp;
+    }
 
     // Compute from ruleId + first location URI
     let uri = result
@@ -51,11 +79,35 @@ fn fingerprint(result: &SarifResult) -> String {
         .collect::<String>()
 }
 
-/// Ensure every result has a `fingerprints` map with `parsentry/v1`.
+/// The result's content fingerprint: an agent-provided
+/// `partialFingerprints` entry, or the hash of its first location's snippet.
+fn snippet_fingerprint(result: &SarifResult) -> Option<String> {
+    if let Some(fp) = result
+        .partial_fingerprints
+        .as_ref()
+        .and_then(|fps| fps.get(CONTENT_FINGERPRINT))
+    {
+        return Some(fp.clone());
+    }
+    let location = &result.locations.first()?.physical_location;
+    let snippet = &location.region.as_ref()?.snippet.as_ref()?.text;
+    (!snippet.trim().is_empty())
+        .then(|| content_fingerprint(&result.rule_id, &location.artifact_location.uri, snippet))
+}
+
+/// Ensure every result has a `fingerprints` map with `parsentry/v1`, and a
+/// content fingerprint in `partialFingerprints` when it has a snippet.
 fn ensure_fingerprint(result: &mut SarifResult) {
     let fp = fingerprint(result);
     let map = result.fingerprints.get_or_insert_with(HashMap::new);
     map.entry("parsentry/v1".to_string()).or_insert(fp);
+    if let Some(content) = snippet_fingerprint(result) {
+        result
+            .partial_fingerprints
+            .get_or_insert_with(HashMap::new)
+            .entry(CONTENT_FINGERPRINT.to_string())
+            .or_insert(content);
+    }
 }
 
 /// Merge all `*.sarif.json` files in `dir` into a single [`SarifReport`].
@@ -67,6 +119,17 @@ fn ensure_fingerprint(result: &mut SarifResult) {
 ///   - `"unchanged"`: same ruleId + fingerprint exists in baseline
 ///   - `"absent"`: in baseline but not in current scan (appended with absent state)
 pub fn merge_sarif_dir(dir: &Path, baseline: Option<&Path>) -> Result<SarifReport> {
+    merge_sarif_dir_with(dir, baseline, None)
+}
+
+/// [`merge_sarif_dir`], making artifact URIs relative to `base` before
+/// deduplicating, so findings are told apart by their path in the
+/// repository rather than by where it was checked out.
+pub fn merge_sarif_dir_with(
+    dir: &Path,
+    baseline: Option<&Path>,
+    base: Option<&UriBase>,
+) -> Result<SarifReport> {
     // Collect result.sarif.json from surface subdirectories, falling back to
     // flat *.sarif.json files in dir for backward compatibility.
     let mut sarif_files: Vec<std::path::PathBuf> = std::fs::read_dir(dir)
@@ -95,8 +158,9 @@ pub fn merge_sarif_dir(dir: &Path, baseline: Option<&Path>) -> Result<SarifRepor
         .collect();
 
     sarif_files.sort();
+    let (timed_out, missing) = missing_results(dir);
 
-    if sarif_files.is_empty() {
+    if sarif_files.is_empty() && missing.is_empty() && timed_out.is_empty() {
         anyhow::bail!("no sarif files found in {}", dir.display());
     }
 
@@ -107,10 +171,12 @@ pub fn merge_sarif_dir(dir: &Path, baseline: Option<&Path>) -> Result<SarifRepor
         HashMap::new()
     };
 
-    let mut all_rules: Vec<SarifRule> = Vec::new();
-    let mut rule_index_map: HashMap<String, usize> = HashMap::new();
+    let mut rules = RuleTable::default();
     let mut all_results: Vec<SarifResult> = Vec::new();
     let mut seen_fingerprints: HashMap<String, usize> = HashMap::new();
+    let mut invalid = Vec::new();
+    // Sum of what the workers reported spending.
+    let mut cost_usd: Option<f64> = None;
 
     for path in &sarif_files {
         let meta =
@@ -126,31 +192,53 @@ pub fn merge_sarif_dir(dir: &Path, baseline: Option<&Path>) -> Result<SarifRepor
         let content = std::fs::read_to_string(path)
             .with_context(|| format!("cannot read {}", path.display()))?;
 
-        let report: SarifReport = serde_json::from_str(&content)
-            .with_context(|| format!("invalid SARIF JSON in {}", path.display()))?;
+        // Per-surface results may carry second-pass verification verdicts.
+        let surface_dir = path
+            .ends_with("result.sarif.json")
+            .then(|| path.parent())
+            .flatten();
 
-        for run in report.runs {
-            let local_rules = run.tool.driver.rules.unwrap_or_default();
-            let mut local_to_merged: HashMap<usize, usize> = HashMap::new();
+        let mut report = match check_sarif(&content) {
+            Validation::Valid(report) | Validation::Repaired { report, .. } => report,
+            Validation::Invalid(errors) => {
+                // One malformed worker output must not sink the whole report.
+                tracing::warn!(
+                    path = %path.display(),
+                    "skipping invalid SARIF: {}",
+                    errors.join("; ")
+                );
+                if let Some(name) = surface_dir.and_then(|d| d.file_name()) {
+                    invalid.push((
+                        name.to_string_lossy().to_string(),
+                        SkipReason::InvalidOutput,
+                    ));
+                }
+                continue;
+            }
+        };
+        if let Some(base) = base {
+            base.apply(&mut report);
+        }
 
-            for (local_idx, rule) in local_rules.into_iter().enumerate() {
-                let merged_idx = if let Some(&existing) = rule_index_map.get(&rule.id) {
-                    existing
-                } else {
-                    let idx = all_rules.len();
-                    rule_index_map.insert(rule.id.clone(), idx);
-                    all_rules.push(rule);
-                    idx
-                };
-                local_to_merged.insert(local_idx, merged_idx);
+        for mut run in report.runs {
+            if let Some(surface_dir) = surface_dir {
+                crate::verify::apply_verdicts(surface_dir, &mut run.results);
             }
+            // Findings keep the model their worker ran on (`scan --model`
+            // fallbacks, `--compare-models`).
+            let worker = run.invocation.as_ref().and_then(|i| i.properties.as_ref());
+            let model = worker.and_then(|p| p.model.clone());
+            if let Some(cost) = worker.and_then(|p| p.cost_usd) {
+                *cost_usd.get_or_insert(0.0) += cost;
+            }
+            let local_to_merged = rules.add(run.tool.driver.rules.unwrap_or_default());
 
             for mut result in run.results {
-                // Rewrite ruleIndex
-                if let Some(local_idx) = result.rule_index {
-                    result.rule_index = local_to_merged.get(&local_idx).copied();
-                } else if let Some(&idx) = rule_index_map.get(&result.rule_id) {
-                    result.rule_index = Some(idx);
+                rules.reindex(&mut result, &local_to_merged);
+
+                if let Some(model) = &model {
+                    let properties = result.properties.get_or_insert_with(Default::default);
+                    properties.model.get_or_insert_with(|| model.clone());
                 }
 
                 // Ensure fingerprint exists
@@ -187,47 +275,326 @@ pub fn merge_sarif_dir(dir: &Path, baseline: Option<&Path>) -> Result<SarifRepor
                 absent.baseline_state = Some("absent".to_string());
 
                 // Ensure rule exists in merged rules
-                if let Some(&idx) = rule_index_map.get(&absent.rule_id) {
-                    absent.rule_index = Some(idx);
-                } else {
-                    let idx = all_rules.len();
-                    rule_index_map.insert(absent.rule_id.clone(), idx);
-                    all_rules.push(SarifRule {
-                        id: absent.rule_id.clone(),
-                        name: None,
-                        short_description: None,
-                        full_description: None,
-                        help: None,
-                        properties: None,
-                        default_configuration: None,
-                    });
-                    absent.rule_index = Some(idx);
-                }
+                absent.rule_index = Some(rules.index_of(&absent.rule_id));
 
                 all_results.push(absent);
             }
         }
     }
 
-    Ok(SarifReport {
-        schema: "https://raw.githubusercontent.com/oasis-tcs/sarif-spec/main/sarif-2.1/schema/sarif-schema-2.1.0.json".to_string(),
-        version: "2.1.0".to_string(),
+    // Decisions from `parsentry review`, kept in the project cache.
+    if let Some(cache_dir) = dir.parent() {
+        Triage::load(&cache_dir.join(TRIAGE_FILE))?.apply(&mut all_results);
+    }
+    if let Some(changes) = IntroducedChanges::load(dir) {
+        changes.attribute(&mut all_results);
+    }
+
+    let coverage = ScanCoverage::load(dir).map(|scan| {
+        let unusable: Vec<_> = timed_out
+            .iter()
+            .map(|s| (s.clone(), SkipReason::TimedOut))
+            .chain(missing.iter().map(|s| (s.clone(), SkipReason::NoResult)))
+            .chain(invalid)
+            .collect();
+        scan.resolve(&unusable)
+    });
+    let partial = !missing.is_empty() || !timed_out.is_empty();
+    let manifest = ScanManifest::load(dir);
+    let dependencies: Vec<Dependency> = std::fs::read_to_string(dir.join(DEPENDENCIES_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
+    let mut report = SarifReport {
+        schema: SARIF_SCHEMA.to_string(),
+        version: SARIF_VERSION.to_string(),
         runs: vec![SarifRun {
+            original_uri_base_ids: None,
             tool: SarifTool {
                 driver: SarifDriver {
                     name: "parsentry".to_string(),
                     version: env!("CARGO_PKG_VERSION").to_string(),
-                    information_uri: Some("https://github.com/HikaruEgashira/parsentry".to_string()),
-                    rules: Some(all_rules),
+                    information_uri: Some(
+                        "https://github.com/HikaruEgashira/parsentry".to_string(),
+                    ),
+                    rules: Some(rules.rules),
                 },
             },
             results: all_results,
             artifacts: None,
-            invocation: None,
+            // A partial merge (e.g. after Ctrl+C in the orchestrator) is
+            // marked so that reports never pass for a complete scan.
+            invocation: (partial
+                || coverage.is_some()
+                || !dependencies.is_empty()
+                || manifest.is_some()
+                || cost_usd.is_some())
+            .then(|| SarifInvocation {
+                execution_successful: !partial,
+                start_time_utc: manifest.as_ref().map(|m| m.started_at.clone()),
+                end_time_utc: manifest.as_ref().map(|m| m.finished_at.clone()),
+                arguments: None,
+                properties: Some(SarifInvocationProperties {
+                    missing_results: missing,
+                    timed_out,
+                    coverage,
+                    dependencies,
+                    manifest,
+                    cost_usd,
+                    ..Default::default()
+                }),
+            }),
+            automation_details: None,
         }],
+    };
+    redact(&mut report)?;
+    Ok(report)
+}
+
+/// Apply `PARSENTRY_REDACT` to a merged report.
+fn redact(report: &mut SarifReport) -> Result<()> {
+    if let Some(redactor) = Redactor::from_env()? {
+        redactor.redact_report(report)?;
+    }
+    Ok(())
+}
+
+/// Rules of several runs, deduplicated by id.
+#[derive(Default)]
+struct RuleTable {
+    rules: Vec<SarifRule>,
+    by_id: HashMap<String, usize>,
+}
+
+impl RuleTable {
+    /// Add a run's rules, keeping the first rule of each id, and map the
+    /// run's rule indices to merged ones.
+    fn add(&mut self, run_rules: Vec<SarifRule>) -> HashMap<usize, usize> {
+        let mut local_to_merged = HashMap::new();
+        for (local_idx, rule) in run_rules.into_iter().enumerate() {
+            let merged_idx = match self.by_id.get(&rule.id) {
+                Some(&existing) => existing,
+                None => {
+                    self.by_id.insert(rule.id.clone(), self.rules.len());
+                    self.rules.push(rule);
+                    self.rules.len() - 1
+                }
+            };
+            local_to_merged.insert(local_idx, merged_idx);
+        }
+        local_to_merged
+    }
+
+    /// Index of the rule `id`, adding a bare rule if there is none.
+    fn index_of(&mut self, id: &str) -> usize {
+        if let Some(&idx) = self.by_id.get(id) {
+            return idx;
+        }
+        self.add(vec![SarifRule {
+            id: id.to_string(),
+            name: None,
+            short_description: None,
+            full_description: None,
+            help: None,
+            properties: None,
+            default_configuration: None,
+        }]);
+        self.rules.len() - 1
+    }
+
+    /// Point `result.ruleIndex` into the merged rules.
+    fn reindex(&self, result: &mut SarifResult, local_to_merged: &HashMap<usize, usize>) {
+        if let Some(local_idx) = result.rule_index {
+            result.rule_index = local_to_merged.get(&local_idx).copied();
+        } else if let Some(&idx) = self.by_id.get(&result.rule_id) {
+            result.rule_index = Some(idx);
+        }
+    }
+}
+
+impl SarifReport {
+    /// Merge SARIF logs of one scan split into shards, e.g. per-language or
+    /// CI matrix jobs, into one log.
+    ///
+    /// Runs with the same `automationDetails.id` (or none) become a single
+    /// run: rules are deduplicated by id, `ruleIndex` points into the merged
+    /// rules, and results with the same fingerprint are kept once. Runs of
+    /// different targets stay separate. Coverage is kept only when a single
+    /// shard reports it, since shards may discover the same files.
+    pub fn merge(reports: Vec<SarifReport>) -> SarifReport {
+        let mut groups: Vec<(Option<String>, Vec<SarifRun>)> = Vec::new();
+        for run in reports.into_iter().flat_map(|r| r.runs) {
+            let id = run.automation_details.as_ref().map(|d| d.id.clone());
+            match groups.iter_mut().find(|(group_id, _)| *group_id == id) {
+                Some((_, runs)) => runs.push(run),
+                None => groups.push((id, vec![run])),
+            }
+        }
+        SarifReport {
+            schema: SARIF_SCHEMA.to_string(),
+            version: SARIF_VERSION.to_string(),
+            runs: groups
+                .into_iter()
+                .map(|(_, runs)| merge_runs(runs))
+                .collect(),
+        }
+    }
+}
+
+/// Load SARIF files written by separate scans (`merge --sarif`) and
+/// [`SarifReport::merge`] them, repairing common defects on the way.
+pub fn merge_sarif_files(paths: &[std::path::PathBuf]) -> Result<SarifReport> {
+    let mut reports = Vec::new();
+    for path in paths {
+        let meta =
+            std::fs::metadata(path).with_context(|| format!("cannot stat {}", path.display()))?;
+        if meta.len() > MAX_SARIF_FILE_SIZE {
+            anyhow::bail!(
+                "SARIF file exceeds {}MiB limit: {}",
+                MAX_SARIF_FILE_SIZE / (1024 * 1024),
+                path.display()
+            );
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("cannot read {}", path.display()))?;
+        match check_sarif(&content) {
+            Validation::Valid(report) | Validation::Repaired { report, .. } => reports.push(report),
+            Validation::Invalid(errors) => {
+                anyhow::bail!("invalid SARIF in {}: {}", path.display(), errors.join("; "))
+            }
+        }
+    }
+    let mut report = SarifReport::merge(reports);
+    redact(&mut report)?;
+    Ok(report)
+}
+
+/// One run holding the rules, results, artifacts and invocations of `runs`.
+fn merge_runs(runs: Vec<SarifRun>) -> SarifRun {
+    let mut runs = runs.into_iter();
+    let first = runs.next().expect("a run group is never empty");
+    let mut merged = SarifRun {
+        original_uri_base_ids: first.original_uri_base_ids.clone(),
+        tool: SarifTool {
+            driver: SarifDriver {
+                rules: None,
+                ..first.tool.driver.clone()
+            },
+        },
+        results: Vec::new(),
+        artifacts: None,
+        invocation: None,
+        automation_details: first.automation_details.clone(),
+    };
+    let mut rules = RuleTable::default();
+    let mut seen = std::collections::HashSet::new();
+    let mut artifacts: Vec<SarifArtifact> = Vec::new();
+    let mut invocations = Vec::new();
+    for run in std::iter::once(first).chain(runs) {
+        let local_to_merged = rules.add(run.tool.driver.rules.unwrap_or_default());
+        for mut result in run.results {
+            rules.reindex(&mut result, &local_to_merged);
+            ensure_fingerprint(&mut result);
+            if seen.insert(fingerprint(&result)) {
+                merged.results.push(result);
+            }
+        }
+        for artifact in run.artifacts.unwrap_or_default() {
+            if !artifacts
+                .iter()
+                .any(|a| a.location.uri == artifact.location.uri)
+            {
+                artifacts.push(artifact);
+            }
+        }
+        invocations.extend(run.invocation);
+    }
+    merged.tool.driver.rules = (!rules.rules.is_empty()).then_some(rules.rules);
+    merged.artifacts = (!artifacts.is_empty()).then_some(artifacts);
+    merged.invocation = merge_invocations(invocations);
+    merged
+}
+
+/// An invocation that succeeded only if every shard did, listing the
+/// surfaces any shard missed.
+fn merge_invocations(invocations: Vec<SarifInvocation>) -> Option<SarifInvocation> {
+    if invocations.len() <= 1 {
+        return invocations.into_iter().next();
+    }
+    let mut properties = SarifInvocationProperties::default();
+    let mut coverages = Vec::new();
+    let mut manifests = Vec::new();
+    for props in invocations.iter().filter_map(|i| i.properties.as_ref()) {
+        properties
+            .missing_results
+            .extend(props.missing_results.iter().cloned());
+        properties.timed_out.extend(props.timed_out.iter().cloned());
+        coverages.extend(props.coverage.clone());
+        manifests.extend(props.manifest.clone());
+        properties.redacted += props.redacted;
+        properties
+            .dependencies
+            .extend(props.dependencies.iter().cloned());
+    }
+    properties.dependencies.sort();
+    properties.dependencies.dedup();
+    if coverages.len() == 1 {
+        properties.coverage = coverages.pop();
+    }
+    if manifests.len() == 1 {
+        properties.manifest = manifests.pop();
+    }
+    Some(SarifInvocation {
+        execution_successful: invocations.iter().all(|i| i.execution_successful),
+        start_time_utc: None,
+        end_time_utc: None,
+        arguments: None,
+        properties: Some(properties),
     })
 }
 
+/// Combine the merged reports of several targets into one log with a run
+/// per target, identified by `automationDetails.id` (`parsentry/<target>/`).
+pub fn combine_reports(reports: Vec<(String, SarifReport)>) -> SarifReport {
+    let mut runs = Vec::new();
+    for (target, report) in reports {
+        for mut run in report.runs {
+            run.automation_details
+                .get_or_insert_with(|| SarifAutomationDetails {
+                    id: format!("parsentry/{target}/"),
+                });
+            runs.push(run);
+        }
+    }
+    SarifReport {
+        schema: SARIF_SCHEMA.to_string(),
+        version: SARIF_VERSION.to_string(),
+        runs,
+    }
+}
+
+/// Surfaces in `dir` that were assigned a worker but have no result,
+/// split into those that timed out and the rest.
+fn missing_results(dir: &Path) -> (Vec<String>, Vec<String>) {
+    let mut missing: Vec<_> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.join("prompt.md").exists() && !p.join("result.sarif.json").exists())
+        .collect();
+    missing.sort();
+    let name = |p: &std::path::PathBuf| p.file_name().map(|n| n.to_string_lossy().to_string());
+    let (timed_out, missing): (Vec<_>, Vec<_>) = missing
+        .into_iter()
+        .partition(|p| p.join(TIMED_OUT_FILE).exists());
+    (
+        timed_out.iter().filter_map(name).collect(),
+        missing.iter().filter_map(name).collect(),
+    )
+}
+
 /// Load baseline SARIF and index results by fingerprint.
 fn load_baseline(path: &Path) -> Result<HashMap<String, SarifResult>> {
     let content = std::fs::read_to_string(path)
@@ -296,6 +663,37 @@ mod tests {
         assert!(run.results[0].baseline_state.is_none());
     }
 
+    #[test]
+    fn keeps_same_snippet_in_same_named_files() {
+        let checkout = TempDir::new().unwrap();
+        let tmp = TempDir::new().unwrap();
+        for (name, dir) in [("S1.sarif.json", "routes"), ("S2.sarif.json", "lib")] {
+            let uri = checkout.path().join(dir).join("index.js");
+            let sarif = minimal_sarif("XSS", &uri.to_string_lossy(), "xss").replacen(
+                r#"}}}]"#,
+                r#"}, "region": {"startLine": 3, "snippet": {"text": "res.send(req.query.q)"}}}}]"#,
+                1,
+            );
+            write_sarif(tmp.path(), name, &sarif);
+        }
+
+        let base = UriBase::new(checkout.path());
+        let merged = merge_sarif_dir_with(tmp.path(), None, Some(&base)).unwrap();
+        let mut uris: Vec<_> = merged.runs[0]
+            .results
+            .iter()
+            .map(|r| {
+                r.locations[0]
+                    .physical_location
+                    .artifact_location
+                    .uri
+                    .as_str()
+            })
+            .collect();
+        uris.sort();
+        assert_eq!(uris, ["lib/index.js", "routes/index.js"]);
+    }
+
     #[test]
     fn baseline_marks_new_and_unchanged() {
         let tmp = TempDir::new().unwrap();
@@ -333,6 +731,49 @@ mod tests {
         assert_eq!(xss.baseline_state.as_deref(), Some("new"));
     }
 
+    #[test]
+    fn tags_results_with_worker_model() {
+        let tmp = TempDir::new().unwrap();
+        let sarif = minimal_sarif("SQLI", "app.py", "sqli").replacen(
+            r#""results""#,
+            r#""invocation": {"executionSuccessful": true, "properties": {"model": "o4-mini"}},
+                "results""#,
+            1,
+        );
+        write_sarif(tmp.path(), "S1.sarif.json", &sarif);
+
+        let merged = merge_sarif_dir(tmp.path(), None).unwrap();
+        let properties = merged.runs[0].results[0].properties.as_ref().unwrap();
+        assert_eq!(properties.model.as_deref(), Some("o4-mini"));
+    }
+
+    #[test]
+    fn sums_worker_costs() {
+        let tmp = TempDir::new().unwrap();
+        for (name, cost) in [("S1.sarif.json", "0.25"), ("S2.sarif.json", "0.5")] {
+            let sarif = minimal_sarif("SQLI", name, "sqli").replacen(
+                r#""results""#,
+                &format!(
+                    r#""invocation": {{"executionSuccessful": true, "properties": {{"cost_usd": {cost}}}}},
+                "results""#
+                ),
+                1,
+            );
+            write_sarif(tmp.path(), name, &sarif);
+        }
+        write_sarif(
+            tmp.path(),
+            "S3.sarif.json",
+            &minimal_sarif("XSS", "web.py", "xss"),
+        );
+
+        let merged = merge_sarif_dir(tmp.path(), None).unwrap();
+        let invocation = merged.runs[0].invocation.as_ref().unwrap();
+        assert!(invocation.execution_successful);
+        let properties = invocation.properties.as_ref().unwrap();
+        assert_eq!(properties.cost_usd, Some(0.75));
+    }
+
     #[test]
     fn baseline_marks_absent() {
         let scan_dir = TempDir::new().unwrap();
@@ -429,12 +870,137 @@ mod tests {
         assert_eq!(merged.runs[0].results.len(), 1);
     }
 
+    #[test]
+    fn records_scanned_dependencies() {
+        let tmp = TempDir::new().unwrap();
+        write_sarif(
+            tmp.path(),
+            "S1.sarif.json",
+            &minimal_sarif("XSS", "views.py", "xss"),
+        );
+        write_sarif(
+            tmp.path(),
+            DEPENDENCIES_FILE,
+            r#"[{"name": "Jinja2", "version": "==2.10", "manifest": "requirements.txt"}]"#,
+        );
+
+        let merged = merge_sarif_dir(tmp.path(), None).unwrap();
+        let invocation = merged.runs[0].invocation.as_ref().unwrap();
+        assert!(invocation.execution_successful);
+        let deps = &invocation.properties.as_ref().unwrap().dependencies;
+        assert_eq!(deps[0].name, "Jinja2");
+        assert_eq!(deps[0].version.as_deref(), Some("==2.10"));
+    }
+
+    #[test]
+    fn embeds_scan_manifest_in_invocation() {
+        let tmp = TempDir::new().unwrap();
+        write_sarif(
+            tmp.path(),
+            "S1.sarif.json",
+            &minimal_sarif("XSS", "views.py", "xss"),
+        );
+        ScanManifest {
+            tool_version: "1.2.3".to_string(),
+            commit: Some("abc".to_string()),
+            started_at: "2026-01-01T00:00:00Z".to_string(),
+            finished_at: "2026-01-01T00:01:00Z".to_string(),
+            ..Default::default()
+        }
+        .save(tmp.path())
+        .unwrap();
+
+        let merged = merge_sarif_dir(tmp.path(), None).unwrap();
+        let invocation = merged.runs[0].invocation.as_ref().unwrap();
+        assert_eq!(
+            invocation.start_time_utc.as_deref(),
+            Some("2026-01-01T00:00:00Z")
+        );
+        let manifest = invocation.properties.as_ref().unwrap().manifest.as_ref();
+        assert_eq!(manifest.unwrap().commit.as_deref(), Some("abc"));
+    }
+
     #[test]
     fn errors_on_empty_dir() {
         let tmp = TempDir::new().unwrap();
         assert!(merge_sarif_dir(tmp.path(), None).is_err());
     }
 
+    #[test]
+    fn marks_surfaces_without_result_as_missing() {
+        let tmp = TempDir::new().unwrap();
+        for surface in ["S-1", "S-2"] {
+            std::fs::create_dir(tmp.path().join(surface)).unwrap();
+            write_sarif(&tmp.path().join(surface), "prompt.md", "analyze");
+        }
+        write_sarif(
+            &tmp.path().join("S-1"),
+            "result.sarif.json",
+            &minimal_sarif("SQLI", "app.py", "sqli"),
+        );
+
+        let merged = merge_sarif_dir(tmp.path(), None).unwrap();
+        let invocation = merged.runs[0].invocation.as_ref().unwrap();
+        assert!(!invocation.execution_successful);
+        assert_eq!(
+            invocation.properties.as_ref().unwrap().missing_results,
+            vec!["S-2"]
+        );
+        assert!(merged.to_markdown().contains("partial report"));
+
+        write_sarif(&tmp.path().join("S-2"), TIMED_OUT_FILE, "");
+        let merged = merge_sarif_dir(tmp.path(), None).unwrap();
+        let props = merged.runs[0]
+            .invocation
+            .as_ref()
+            .unwrap()
+            .properties
+            .as_ref();
+        assert_eq!(props.unwrap().timed_out, vec!["S-2"]);
+        assert!(props.unwrap().missing_results.is_empty());
+        assert!(merged.to_markdown().contains("- `S-2`: timed out"));
+
+        write_sarif(
+            &tmp.path().join("S-2"),
+            "result.sarif.json",
+            &minimal_sarif("XSS", "web.py", "xss"),
+        );
+        let merged = merge_sarif_dir(tmp.path(), None).unwrap();
+        assert!(merged.runs[0].invocation.is_none());
+    }
+
+    #[test]
+    fn suppresses_findings_triaged_in_project_cache() {
+        use crate::triage::{Decision, TRIAGE_FILE, Triage};
+
+        let tmp = TempDir::new().unwrap();
+        let reports = tmp.path().join("reports");
+        std::fs::create_dir(&reports).unwrap();
+        write_sarif(
+            &reports,
+            "a.sarif.json",
+            &minimal_sarif("SQLI", "app.py", "sqli"),
+        );
+        write_sarif(
+            &reports,
+            "b.sarif.json",
+            &minimal_sarif("XSS", "web.py", "xss"),
+        );
+
+        let merged = merge_sarif_dir(&reports, None).unwrap();
+        let mut triage = Triage::default();
+        triage.decide(&merged.runs[0].results[0], Decision::FalsePositive, None);
+        triage.save(&tmp.path().join(TRIAGE_FILE)).unwrap();
+
+        let merged = merge_sarif_dir(&reports, None).unwrap();
+        let suppressed: Vec<_> = merged.runs[0]
+            .results
+            .iter()
+            .map(|r| r.suppressions.is_some())
+            .collect();
+        assert_eq!(suppressed, vec![true, false]);
+    }
+
     #[test]
     fn rejects_oversized_sarif_file() {
         // Kills > → == and > → >= : file larger than 10MiB must be rejected
@@ -480,4 +1046,84 @@ mod tests {
             "ensure_fingerprint must add parsentry/v1"
         );
     }
+
+    #[test]
+    fn combine_reports_keeps_a_run_per_target() {
+        let report = |rule_id: &str| {
+            let tmp = TempDir::new().unwrap();
+            write_sarif(
+                tmp.path(),
+                "S1.sarif.json",
+                &minimal_sarif(rule_id, "app.py", "test"),
+            );
+            merge_sarif_dir(tmp.path(), None).unwrap()
+        };
+        let combined = combine_reports(vec![
+            ("acme/api".to_string(), report("SQLI")),
+            ("acme/web".to_string(), report("XSS")),
+        ]);
+        let runs: Vec<(&str, &str)> = combined
+            .runs
+            .iter()
+            .map(|r| {
+                (
+                    r.automation_details.as_ref().unwrap().id.as_str(),
+                    r.results[0].rule_id.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            runs,
+            [
+                ("parsentry/acme/api/", "SQLI"),
+                ("parsentry/acme/web/", "XSS")
+            ]
+        );
+        let json = serde_json::to_string(&combined).unwrap();
+        assert!(json.contains(r#""automationDetails":{"id":"parsentry/acme/api/"}"#));
+    }
+
+    #[test]
+    fn merges_shards_into_one_run() {
+        let shard = |sarif: &str| SarifReport::from_json(sarif).unwrap();
+        let python = shard(&minimal_sarif("SQLI", "app.py", "sqli"));
+        // A second shard lists its rules in another order and repeats a finding.
+        let mut go = shard(&minimal_sarif("XSS", "web.go", "xss"));
+        let sqli = python.runs[0].clone();
+        go.runs[0]
+            .tool
+            .driver
+            .rules
+            .as_mut()
+            .unwrap()
+            .extend(sqli.tool.driver.rules.unwrap());
+        let mut repeated = sqli.results[0].clone();
+        repeated.rule_index = Some(1);
+        go.runs[0].results.push(repeated);
+
+        let merged = SarifReport::merge(vec![python, go]);
+        assert_eq!(merged.runs.len(), 1);
+        let run = &merged.runs[0];
+        let rule_ids: Vec<&str> = run
+            .tool
+            .driver
+            .rules
+            .iter()
+            .flatten()
+            .map(|r| r.id.as_str())
+            .collect();
+        assert_eq!(rule_ids, ["SQLI", "XSS"]);
+        let results: Vec<(&str, Option<usize>)> = run
+            .results
+            .iter()
+            .map(|r| (r.rule_id.as_str(), r.rule_index))
+            .collect();
+        assert_eq!(results, [("SQLI", Some(0)), ("XSS", Some(1))]);
+
+        let combined = combine_reports(vec![
+            ("acme/api".to_string(), merged.clone()),
+            ("acme/web".to_string(), merged),
+        ]);
+        assert_eq!(SarifReport::merge(vec![combined]).runs.len(), 2);
+    }
 }
@@ -0,0 +1,689 @@
+//! Deterministic checks of Android and iOS app configuration.
+//!
+//! Exported components, cleartext traffic and debuggable builds are
+//! declared in manifests rather than code, so `scan` reads them directly:
+//! `AndroidManifest.xml`, network security configs and Gradle build files
+//! on Android, `Info.plist` and `.entitlements` on iOS. Findings are
+//! written to [`MOBILE_SARIF_FILE`] in the reports directory, where
+//! `merge` picks them up alongside the workers' results.
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::sarif::{
+    SARIF_SCHEMA, SARIF_VERSION, SarifArtifactContent, SarifArtifactLocation, SarifConfiguration,
+    SarifDriver, SarifLocation, SarifMessage, SarifPhysicalLocation, SarifRegion, SarifReport,
+    SarifResult, SarifResultProperties, SarifRule, SarifRuleProperties, SarifRun, SarifTool,
+};
+
+/// Reports directory file holding the mobile configuration findings.
+pub const MOBILE_SARIF_FILE: &str = "mobile.sarif.json";
+
+/// Extensions of the files [`is_mobile_config`] may accept.
+pub const MOBILE_CONFIG_EXTENSIONS: &[&str] = &["xml", "gradle", "kts", "plist", "entitlements"];
+
+/// A configuration weakness with a fixed severity.
+struct MobileRule {
+    id: &'static str,
+    name: &'static str,
+    help: &'static str,
+    cwe: &'static str,
+    security_severity: &'static str,
+}
+
+const EXPORTED_COMPONENT: MobileRule = MobileRule {
+    id: "MOBILE-EXPORTED-COMPONENT",
+    name: "Exported Android Component",
+    help: "Any app on the device can start, bind to or query an exported component without a \
+           permission. Set android:exported=\"false\" or require a signature permission.",
+    cwe: "CWE-926",
+    security_severity: "6.5",
+};
+
+const CLEARTEXT_TRAFFIC: MobileRule = MobileRule {
+    id: "MOBILE-CLEARTEXT-TRAFFIC",
+    name: "Cleartext Traffic Allowed",
+    help: "Plain HTTP lets anyone on the network read and modify the app's traffic. Use HTTPS \
+           and limit exceptions to specific domains.",
+    cwe: "CWE-319",
+    security_severity: "6.0",
+};
+
+const DEBUGGABLE: MobileRule = MobileRule {
+    id: "MOBILE-DEBUGGABLE",
+    name: "Debuggable Release Build",
+    help: "A debuggable app lets anyone with device access attach a debugger, read its memory \
+           and run code as the app. Disable debugging in release builds.",
+    cwe: "CWE-489",
+    security_severity: "7.0",
+};
+
+const BACKUP_ALLOWED: MobileRule = MobileRule {
+    id: "MOBILE-BACKUP-ALLOWED",
+    name: "Application Data Backup Allowed",
+    help: "Backups copy the app's private data, including tokens, off the device. Disable \
+           backup or exclude sensitive files.",
+    cwe: "CWE-530",
+    security_severity: "4.0",
+};
+
+const DANGEROUS_PERMISSION: MobileRule = MobileRule {
+    id: "MOBILE-DANGEROUS-PERMISSION",
+    name: "Dangerous Permission Requested",
+    help: "The permission grants access to sensitive user data or device features. Check that \
+           the app needs it and guards the data it exposes.",
+    cwe: "CWE-250",
+    security_severity: "3.0",
+};
+
+const USER_CA_TRUSTED: MobileRule = MobileRule {
+    id: "MOBILE-USER-CA-TRUSTED",
+    name: "User-Installed CAs Trusted",
+    help: "Trusting user-installed certificate authorities lets anyone who can install one \
+           intercept TLS traffic. Limit user CAs to debug-overrides.",
+    cwe: "CWE-295",
+    security_severity: "5.0",
+};
+
+const URL_SCHEME: MobileRule = MobileRule {
+    id: "MOBILE-URL-SCHEME",
+    name: "Custom URL Scheme",
+    help: "Any app or web page can open a custom URL scheme, and another app can claim the \
+           same scheme. Validate every parameter of the URLs it handles, and prefer universal \
+           links for sensitive flows.",
+    cwe: "CWE-939",
+    security_severity: "4.0",
+};
+
+const WEAK_ENTITLEMENT: MobileRule = MobileRule {
+    id: "MOBILE-WEAK-ENTITLEMENT",
+    name: "Weakened Code Signing Entitlement",
+    help: "The entitlement disables a hardened runtime protection, letting other code be \
+           injected into or run by the app.",
+    cwe: "CWE-693",
+    security_severity: "6.0",
+};
+
+const RULES: &[&MobileRule] = &[
+    &EXPORTED_COMPONENT,
+    &CLEARTEXT_TRAFFIC,
+    &DEBUGGABLE,
+    &BACKUP_ALLOWED,
+    &DANGEROUS_PERMISSION,
+    &USER_CA_TRUSTED,
+    &URL_SCHEME,
+    &WEAK_ENTITLEMENT,
+];
+
+/// Android runtime permissions with the `dangerous` protection level, and
+/// special permissions as sensitive.
+const DANGEROUS_PERMISSIONS: &[&str] = &[
+    "READ_SMS",
+    "SEND_SMS",
+    "RECEIVE_SMS",
+    "READ_CONTACTS",
+    "WRITE_CONTACTS",
+    "READ_CALL_LOG",
+    "WRITE_CALL_LOG",
+    "CALL_PHONE",
+    "READ_PHONE_STATE",
+    "ACCESS_FINE_LOCATION",
+    "ACCESS_BACKGROUND_LOCATION",
+    "RECORD_AUDIO",
+    "CAMERA",
+    "BODY_SENSORS",
+    "READ_EXTERNAL_STORAGE",
+    "WRITE_EXTERNAL_STORAGE",
+    "MANAGE_EXTERNAL_STORAGE",
+    "SYSTEM_ALERT_WINDOW",
+    "REQUEST_INSTALL_PACKAGES",
+    "BIND_ACCESSIBILITY_SERVICE",
+    "QUERY_ALL_PACKAGES",
+];
+
+static COMPONENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"<(activity-alias|activity|service|receiver|provider)\b([^>]*?)(/?)>")
+        .expect("valid regex")
+});
+static APPLICATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<application\b([^>]*)>").expect("valid regex"));
+static PERMISSION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"<uses-permission(?:-sdk-23)?\b[^>]*android:name\s*=\s*"android\.permission\.(\w+)""#,
+    )
+    .expect("valid regex")
+});
+static PLIST_TRUE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<key>\s*([\w.-]+)\s*</key>\s*<true\s*/>").expect("valid regex"));
+static URL_SCHEMES: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<key>\s*CFBundleURLSchemes\s*</key>\s*<array>(.*?)</array>")
+        .expect("valid regex")
+});
+static PLIST_STRING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<string>\s*([^<]+?)\s*</string>").expect("valid regex"));
+static GRADLE_DEBUGGABLE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(isDebuggable|debuggable)\b\s*=?\s*true\b").expect("valid regex")
+});
+static GRADLE_CLEARTEXT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"usesCleartextTraffic"?\s*[:=]\s*"?true"#).expect("valid regex"));
+
+/// Whether `path` (relative to the repository root) is a file the mobile
+/// checks read.
+pub fn is_mobile_config(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or_default();
+    name == "AndroidManifest.xml"
+        || name == "build.gradle"
+        || name == "build.gradle.kts"
+        || name == "Info.plist"
+        || name.ends_with(".entitlements")
+        || (path.contains("res/xml/") && name.ends_with(".xml"))
+}
+
+/// A finding before it becomes a SARIF result.
+struct Finding {
+    rule: &'static MobileRule,
+    /// Byte offset of the finding in its file.
+    offset: usize,
+    message: String,
+}
+
+/// Check the mobile configuration among `files` (absolute paths under
+/// `root`). The report has one run and no results when nothing is found.
+pub fn scan_mobile_configs(root: &Path, files: &[PathBuf]) -> SarifReport {
+    let mut results = Vec::new();
+    for path in files {
+        let relative = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        if !is_mobile_config(&relative) {
+            continue;
+        }
+        let Ok(text) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        for finding in check_file(&relative, &text) {
+            results.push(to_result(&relative, &text, finding));
+        }
+    }
+    SarifReport {
+        schema: SARIF_SCHEMA.to_string(),
+        version: SARIF_VERSION.to_string(),
+        runs: vec![SarifRun {
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: "parsentry".to_string(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    information_uri: Some(
+                        "https://github.com/HikaruEgashira/parsentry".to_string(),
+                    ),
+                    rules: Some(RULES.iter().map(|rule| to_rule(rule)).collect()),
+                },
+            },
+            results,
+            artifacts: None,
+            invocation: None,
+            automation_details: None,
+            original_uri_base_ids: None,
+        }],
+    }
+}
+
+fn check_file(path: &str, text: &str) -> Vec<Finding> {
+    let name = path.rsplit('/').next().unwrap_or_default();
+    match name {
+        "AndroidManifest.xml" => android_manifest(text),
+        "build.gradle" | "build.gradle.kts" => gradle(text),
+        "Info.plist" => info_plist(text),
+        _ if name.ends_with(".entitlements") => entitlements(text),
+        _ if text.contains("<network-security-config") => network_security_config(text),
+        _ => Vec::new(),
+    }
+}
+
+/// Value of the `android:<name>` attribute in `attributes`.
+fn android_attribute<'a>(attributes: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = Regex::new(&format!(r#"android:{name}\s*=\s*"([^"]*)""#)).ok()?;
+    pattern
+        .captures(attributes)
+        .and_then(|c| c.get(1))
+        .map(|m| m.as_str())
+}
+
+fn android_manifest(text: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    if let Some(application) = APPLICATION.captures(text) {
+        let attributes = &application[1];
+        let offset = application.get(0).map_or(0, |m| m.start());
+        for (attribute, rule, message) in [
+            (
+                "usesCleartextTraffic",
+                &CLEARTEXT_TRAFFIC,
+                "The application allows cleartext HTTP traffic (android:usesCleartextTraffic=\"true\")",
+            ),
+            (
+                "debuggable",
+                &DEBUGGABLE,
+                "The application is debuggable (android:debuggable=\"true\")",
+            ),
+            (
+                "allowBackup",
+                &BACKUP_ALLOWED,
+                "The application allows backup of its data (android:allowBackup=\"true\")",
+            ),
+        ] {
+            if android_attribute(attributes, attribute) == Some("true") {
+                findings.push(Finding {
+                    rule,
+                    offset,
+                    message: message.to_string(),
+                });
+            }
+        }
+    }
+
+    for component in COMPONENT.captures_iter(text) {
+        let (Some(whole), tag, attributes) = (component.get(0), &component[1], &component[2])
+        else {
+            continue;
+        };
+        let body = if &component[3] == "/" {
+            ""
+        } else {
+            let rest = &text[whole.end()..];
+            &rest[..rest.find(&format!("</{tag}>")).unwrap_or(0)]
+        };
+        // The launcher activity has to be exported.
+        if body.contains("android.intent.action.MAIN") {
+            continue;
+        }
+        let implicit = match android_attribute(attributes, "exported") {
+            Some("true") => false,
+            None if body.contains("<intent-filter") => true,
+            _ => continue,
+        };
+        let protected = ["permission", "readPermission", "writePermission"]
+            .iter()
+            .any(|name| android_attribute(attributes, name).is_some());
+        if protected {
+            continue;
+        }
+        let name = android_attribute(attributes, "name").unwrap_or("(unnamed)");
+        let how = if implicit {
+            "is exported by its intent filter on Android 11 and earlier"
+        } else {
+            "is exported"
+        };
+        findings.push(Finding {
+            rule: &EXPORTED_COMPONENT,
+            offset: whole.start(),
+            message: format!(
+                "The {tag} `{name}` {how} without a permission; any app can invoke it"
+            ),
+        });
+    }
+
+    for permission in PERMISSION.captures_iter(text) {
+        if DANGEROUS_PERMISSIONS.contains(&&permission[1]) {
+            findings.push(Finding {
+                rule: &DANGEROUS_PERMISSION,
+                offset: permission.get(0).map_or(0, |m| m.start()),
+                message: format!("The app requests android.permission.{}", &permission[1]),
+            });
+        }
+    }
+    findings
+}
+
+fn network_security_config(text: &str) -> Vec<Finding> {
+    // Overrides that only apply to debuggable builds are fine.
+    let debug = text.find("<debug-overrides").map(|start| {
+        let end = text[start..]
+            .find("</debug-overrides>")
+            .map_or(text.len(), |i| start + i);
+        start..end
+    });
+    let in_debug = |offset: usize| debug.as_ref().is_some_and(|range| range.contains(&offset));
+    let mut findings = Vec::new();
+    for (offset, _) in text.match_indices("cleartextTrafficPermitted=\"true\"") {
+        if !in_debug(offset) {
+            findings.push(Finding {
+                rule: &CLEARTEXT_TRAFFIC,
+                offset,
+                message: "The network security config permits cleartext traffic".to_string(),
+            });
+        }
+    }
+    for (offset, _) in text.match_indices("src=\"user\"") {
+        if !in_debug(offset) {
+            findings.push(Finding {
+                rule: &USER_CA_TRUSTED,
+                offset,
+                message: "The network security config trusts user-installed CAs".to_string(),
+            });
+        }
+    }
+    findings
+}
+
+fn gradle(text: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    // Headers of the blocks enclosing the current position, e.g. `release`.
+    let mut blocks: Vec<String> = Vec::new();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let code = line.split("//").next().unwrap_or_default();
+        if GRADLE_DEBUGGABLE.is_match(code) && blocks.iter().any(|b| b.contains("release")) {
+            findings.push(Finding {
+                rule: &DEBUGGABLE,
+                offset,
+                message: "The release build type is debuggable".to_string(),
+            });
+        }
+        if GRADLE_CLEARTEXT.is_match(code) {
+            findings.push(Finding {
+                rule: &CLEARTEXT_TRAFFIC,
+                offset,
+                message: "The build enables cleartext traffic through a manifest placeholder"
+                    .to_string(),
+            });
+        }
+        let mut start = 0;
+        for (i, c) in code.char_indices() {
+            match c {
+                '{' => {
+                    blocks.push(code[start..i].trim().to_string());
+                    start = i + 1;
+                }
+                '}' => {
+                    blocks.pop();
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        offset += line.len();
+    }
+    findings
+}
+
+fn info_plist(text: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for key in PLIST_TRUE.captures_iter(text) {
+        if matches!(
+            &key[1],
+            "NSAllowsArbitraryLoads"
+                | "NSAllowsArbitraryLoadsInWebContent"
+                | "NSAllowsArbitraryLoadsForMedia"
+                | "NSExceptionAllowsInsecureHTTPLoads"
+        ) {
+            findings.push(Finding {
+                rule: &CLEARTEXT_TRAFFIC,
+                offset: key.get(0).map_or(0, |m| m.start()),
+                message: format!("App Transport Security is relaxed by {}", &key[1]),
+            });
+        }
+    }
+    for schemes in URL_SCHEMES.captures_iter(text) {
+        let Some(array) = schemes.get(1) else {
+            continue;
+        };
+        for scheme in PLIST_STRING.captures_iter(array.as_str()) {
+            findings.push(Finding {
+                rule: &URL_SCHEME,
+                offset: array.start() + scheme.get(0).map_or(0, |m| m.start()),
+                message: format!(
+                    "The custom URL scheme `{}://` can be opened by any app or web page",
+                    &scheme[1]
+                ),
+            });
+        }
+    }
+    findings
+}
+
+fn entitlements(text: &str) -> Vec<Finding> {
+    PLIST_TRUE
+        .captures_iter(text)
+        .filter_map(|key| {
+            let offset = key.get(0).map_or(0, |m| m.start());
+            let (rule, message) = match &key[1] {
+                "get-task-allow" | "com.apple.security.get-task-allow" => (
+                    &DEBUGGABLE,
+                    "The app can be attached to by a debugger (get-task-allow)",
+                ),
+                "com.apple.security.cs.disable-library-validation"
+                | "com.apple.security.cs.allow-unsigned-executable-memory"
+                | "com.apple.security.cs.allow-dyld-environment-variables"
+                | "com.apple.security.cs.disable-executable-page-protection" => {
+                    (&WEAK_ENTITLEMENT, "")
+                }
+                _ => return None,
+            };
+            let message = if message.is_empty() {
+                format!("The entitlement {} weakens the hardened runtime", &key[1])
+            } else {
+                message.to_string()
+            };
+            Some(Finding {
+                rule,
+                offset,
+                message,
+            })
+        })
+        .collect()
+}
+
+fn level(rule: &MobileRule) -> &'static str {
+    match rule.security_severity.parse::<f64>().unwrap_or(0.0) {
+        s if s >= 8.0 => "error",
+        s if s >= 6.0 => "warning",
+        _ => "note",
+    }
+}
+
+fn to_rule(rule: &MobileRule) -> SarifRule {
+    SarifRule {
+        id: rule.id.to_string(),
+        name: Some(rule.name.to_string()),
+        short_description: Some(SarifMessage {
+            text: rule.name.to_string(),
+            markdown: None,
+        }),
+        full_description: None,
+        help: Some(SarifMessage {
+            text: rule.help.to_string(),
+            markdown: Some(rule.help.to_string()),
+        }),
+        properties: Some(SarifRuleProperties {
+            tags: Some(vec![
+                "security".to_string(),
+                "mobile".to_string(),
+                rule.cwe.to_string(),
+            ]),
+            precision: Some("high".to_string()),
+            problem_severity: Some(rule.security_severity.to_string()),
+            security_severity: Some(rule.security_severity.to_string()),
+        }),
+        default_configuration: Some(SarifConfiguration {
+            level: level(rule).to_string(),
+        }),
+    }
+}
+
+fn to_result(file: &str, text: &str, finding: Finding) -> SarifResult {
+    let line = text[..finding.offset.min(text.len())].matches('\n').count() + 1;
+    let snippet = text.lines().nth(line - 1).unwrap_or_default().trim();
+    SarifResult {
+        rule_id: finding.rule.id.to_string(),
+        rule_index: RULES.iter().position(|r| r.id == finding.rule.id),
+        level: level(finding.rule).to_string(),
+        message: SarifMessage {
+            text: finding.message,
+            markdown: None,
+        },
+        locations: vec![SarifLocation {
+            physical_location: SarifPhysicalLocation {
+                artifact_location: SarifArtifactLocation {
+                    uri: file.to_string(),
+                    uri_base_id: None,
+                    index: None,
+                },
+                region: Some(SarifRegion {
+                    start_line: line as i32,
+                    start_column: None,
+                    end_line: None,
+                    end_column: None,
+                    snippet: Some(SarifArtifactContent {
+                        text: snippet.to_string(),
+                    }),
+                }),
+            },
+        }],
+        fingerprints: None,
+        partial_fingerprints: None,
+        baseline_state: None,
+        suppressions: None,
+        properties: Some(SarifResultProperties {
+            // Read from the configuration, not inferred
+            confidence: Some(1.0),
+            cwe: Some(vec![finding.rule.cwe.to_string()]),
+            ..Default::default()
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(files: &[(&str, &str)]) -> Vec<(String, String, i32)> {
+        let dir = tempfile::tempdir().unwrap();
+        let paths: Vec<PathBuf> = files
+            .iter()
+            .map(|(name, content)| {
+                let path = dir.path().join(name);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(&path, content).unwrap();
+                path
+            })
+            .collect();
+        let report = scan_mobile_configs(dir.path(), &paths);
+        report.runs[0]
+            .results
+            .iter()
+            .map(|r| {
+                let location = &r.locations[0].physical_location;
+                (
+                    r.rule_id.clone(),
+                    location.artifact_location.uri.clone(),
+                    location.region.as_ref().unwrap().start_line,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn checks_android_manifest_and_build() {
+        let found = scan(&[
+            (
+                "app/src/main/AndroidManifest.xml",
+                r#"<manifest xmlns:android="http://schemas.android.com/apk/res/android">
+    <uses-permission android:name="android.permission.READ_SMS" />
+    <uses-permission android:name="android.permission.INTERNET" />
+    <application android:usesCleartextTraffic="true" android:allowBackup="false">
+        <activity android:name=".MainActivity" android:exported="true">
+            <intent-filter>
+                <action android:name="android.intent.action.MAIN" />
+            </intent-filter>
+        </activity>
+        <activity android:name=".DeepLinkActivity">
+            <intent-filter>
+                <action android:name="android.intent.action.VIEW" />
+            </intent-filter>
+        </activity>
+        <service android:name=".SyncService" android:exported="true"
+            android:permission="com.example.SYNC" />
+        <provider android:name=".FilesProvider" android:exported="true" />
+    </application>
+</manifest>
+"#,
+            ),
+            (
+                "app/build.gradle",
+                "android {\n    buildTypes {\n        debug {\n            debuggable true\n        }\n        release {\n            debuggable true\n        }\n    }\n}\n",
+            ),
+            (
+                "app/src/main/res/xml/network_security_config.xml",
+                r#"<network-security-config>
+    <base-config cleartextTrafficPermitted="false" />
+    <debug-overrides>
+        <trust-anchors><certificates src="user" /></trust-anchors>
+    </debug-overrides>
+</network-security-config>
+"#,
+            ),
+        ]);
+        let manifest = "app/src/main/AndroidManifest.xml".to_string();
+        assert_eq!(
+            found,
+            vec![
+                ("MOBILE-CLEARTEXT-TRAFFIC".into(), manifest.clone(), 4),
+                ("MOBILE-EXPORTED-COMPONENT".into(), manifest.clone(), 10),
+                ("MOBILE-EXPORTED-COMPONENT".into(), manifest.clone(), 17),
+                ("MOBILE-DANGEROUS-PERMISSION".into(), manifest, 2),
+                ("MOBILE-DEBUGGABLE".into(), "app/build.gradle".into(), 7),
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_ios_plist_and_entitlements() {
+        let found = scan(&[
+            (
+                "ios/App/Info.plist",
+                r#"<plist><dict>
+    <key>NSAppTransportSecurity</key>
+    <dict>
+        <key>NSAllowsArbitraryLoads</key>
+        <true/>
+    </dict>
+    <key>CFBundleURLTypes</key>
+    <array><dict>
+        <key>CFBundleURLSchemes</key>
+        <array><string>myapp</string></array>
+    </dict></array>
+</dict></plist>
+"#,
+            ),
+            (
+                "ios/App/App.entitlements",
+                "<plist><dict>\n<key>get-task-allow</key>\n<true/>\n<key>aps-environment</key>\n<string>production</string>\n</dict></plist>\n",
+            ),
+            (
+                "ios/App/Other.plist",
+                "<key>NSAllowsArbitraryLoads</key><true/>",
+            ),
+        ]);
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "MOBILE-CLEARTEXT-TRAFFIC".into(),
+                    "ios/App/Info.plist".into(),
+                    4
+                ),
+                ("MOBILE-URL-SCHEME".into(), "ios/App/Info.plist".into(), 10),
+                (
+                    "MOBILE-DEBUGGABLE".into(),
+                    "ios/App/App.entitlements".into(),
+                    2
+                ),
+            ]
+        );
+    }
+}
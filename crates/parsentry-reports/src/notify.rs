@@ -0,0 +1,270 @@
+/// Scan completion notifications for Slack, Microsoft Teams and generic webhooks.
+///
+/// The payload shape is chosen from the webhook URL:
+///   hooks.slack.com                      - Slack incoming webhook (`text`)
+///   *.webhook.office.com / logic.azure.com - Teams MessageCard
+///   anything else                        - generic JSON event
+///
+/// Message text can be customised with `PARSENTRY_NOTIFY_TEMPLATE`; the
+/// placeholders `{{target}}`, `{{status}}`, `{{total}}`, `{{error}}`,
+/// `{{warning}}`, `{{note}}`, `{{top_findings}}` and `{{links}}` are replaced.
+use anyhow::{Result, anyhow};
+use serde_json::{Value, json};
+use std::env;
+
+use crate::report_common::{build_title, level_passes};
+use crate::risk::by_risk;
+use crate::sarif::{SarifReport, SarifResult};
+use parsentry_core::offline::ensure_online;
+
+const TOP_FINDINGS: usize = 5;
+
+const DEFAULT_TEMPLATE: &str = "Parsentry scan {{status}}: {{target}}\n\
+Findings: {{total}} (error: {{error}}, warning: {{warning}}, note: {{note}})\n\
+{{top_findings}}{{links}}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookKind {
+    Slack,
+    Teams,
+    Generic,
+}
+
+impl WebhookKind {
+    pub fn detect(url: &str) -> Self {
+        let host = url
+            .split("://")
+            .nth(1)
+            .unwrap_or(url)
+            .split(['/', '?'])
+            .next()
+            .unwrap_or("");
+        if host == "hooks.slack.com" {
+            WebhookKind::Slack
+        } else if host.ends_with(".webhook.office.com") || host.ends_with(".logic.azure.com") {
+            WebhookKind::Teams
+        } else {
+            WebhookKind::Generic
+        }
+    }
+}
+
+/// Summary of a finished (or failed) scan, as sent to webhooks.
+#[derive(Debug, Clone, Default)]
+pub struct ScanNotification {
+    pub target: String,
+    /// `None` on success, the error message on failure.
+    pub failure: Option<String>,
+    pub error: usize,
+    pub warning: usize,
+    pub note: usize,
+    pub top_findings: Vec<String>,
+    pub links: Vec<String>,
+}
+
+impl ScanNotification {
+    pub fn completed(target: &str, report: &SarifReport, links: Vec<String>) -> Self {
+        let mut n = Self {
+            target: target.to_string(),
+            links,
+            ..Default::default()
+        };
+
+        let mut results: Vec<_> = report
+            .runs
+            .iter()
+            .flat_map(|r| r.results.iter())
+            .filter(|r| r.baseline_state.as_deref() != Some("absent"))
+            .collect();
+        for r in &results {
+            match r.level.as_str() {
+                "error" => n.error += 1,
+                "warning" => n.warning += 1,
+                _ => n.note += 1,
+            }
+        }
+
+        // error first, then warning, then the rest; riskiest first within each
+        let rank = |r: &SarifResult| {
+            if level_passes(&r.level, "error") {
+                0
+            } else if level_passes(&r.level, "warning") {
+                1
+            } else {
+                2
+            }
+        };
+        results.sort_by(|a, b| rank(a).cmp(&rank(b)).then_with(|| by_risk(a, b)));
+        n.top_findings = results
+            .iter()
+            .take(TOP_FINDINGS)
+            .map(|r| build_title(r))
+            .collect();
+        n
+    }
+
+    pub fn failed(target: &str, error: &str) -> Self {
+        Self {
+            target: target.to_string(),
+            failure: Some(error.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.error + self.warning + self.note
+    }
+
+    fn status(&self) -> &'static str {
+        if self.failure.is_some() {
+            "failed"
+        } else {
+            "completed"
+        }
+    }
+
+    /// Render the message text from `template` (or the built-in default).
+    pub fn render(&self, template: Option<&str>) -> String {
+        let top = if self.top_findings.is_empty() {
+            String::new()
+        } else {
+            let mut s = String::from("Top findings:\n");
+            for t in &self.top_findings {
+                s.push_str(&format!("• {t}\n"));
+            }
+            s
+        };
+        let links = self
+            .links
+            .iter()
+            .map(|l| format!("{l}\n"))
+            .collect::<String>();
+
+        let mut text = template
+            .unwrap_or(DEFAULT_TEMPLATE)
+            .replace("{{target}}", &self.target)
+            .replace("{{status}}", self.status())
+            .replace("{{total}}", &self.total().to_string())
+            .replace("{{error}}", &self.error.to_string())
+            .replace("{{warning}}", &self.warning.to_string())
+            .replace("{{note}}", &self.note.to_string())
+            .replace("{{top_findings}}", &top)
+            .replace("{{links}}", &links);
+        if let Some(err) = &self.failure {
+            text.push_str(&format!("\nError: {err}"));
+        }
+        text.trim_end().to_string()
+    }
+
+    fn payload(&self, kind: WebhookKind, text: &str) -> Value {
+        match kind {
+            WebhookKind::Slack => json!({ "text": text }),
+            WebhookKind::Teams => json!({
+                "@type": "MessageCard",
+                "@context": "https://schema.org/extensions",
+                "summary": format!("Parsentry scan {}: {}", self.status(), self.target),
+                "themeColor": if self.failure.is_some() || self.error > 0 { "D73A49" } else { "2EA44F" },
+                "text": text.replace('\n', "<br>"),
+            }),
+            WebhookKind::Generic => json!({
+                "event": format!("scan.{}", self.status()),
+                "target": self.target,
+                "error": self.failure,
+                "counts": {
+                    "total": self.total(),
+                    "error": self.error,
+                    "warning": self.warning,
+                    "note": self.note,
+                },
+                "top_findings": self.top_findings,
+                "links": self.links,
+                "text": text,
+            }),
+        }
+    }
+}
+
+/// Post `notification` to every webhook URL. All URLs are attempted; the
+/// first failure is returned.
+pub async fn send_notifications(urls: &[String], notification: &ScanNotification) -> Result<()> {
+    let template = env::var("PARSENTRY_NOTIFY_TEMPLATE").ok();
+    let text = notification.render(template.as_deref());
+    ensure_online("webhook notification")?;
+    let client = parsentry_core::http::client()?;
+    let mut first_err = None;
+
+    for url in urls {
+        let payload = notification.payload(WebhookKind::detect(url), &text);
+        let outcome = match client.post(url).json(&payload).send().await {
+            Ok(resp) if resp.status().is_success() => Ok(()),
+            Ok(resp) => Err(anyhow!("webhook returned {}", resp.status())),
+            Err(e) => Err(anyhow!("webhook request failed: {e}")),
+        };
+        if let Err(e) = outcome
+            && first_err.is_none()
+        {
+            first_err = Some(e);
+        }
+    }
+
+    match first_err {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{ResultFixture, result};
+
+    fn report() -> SarifReport {
+        crate::test_support::report([
+            result("XSS", "b.js").level("note").message("b"),
+            result("SQLI", "a.py").message("a"),
+            result("RCE", "c.sh").message("c").baseline_state("absent"),
+        ])
+    }
+
+    #[test]
+    fn test_detect_webhook_kind() {
+        assert_eq!(
+            WebhookKind::detect("https://hooks.slack.com/services/T/B/X"),
+            WebhookKind::Slack
+        );
+        assert_eq!(
+            WebhookKind::detect("https://acme.webhook.office.com/webhookb2/abc"),
+            WebhookKind::Teams
+        );
+        assert_eq!(
+            WebhookKind::detect("https://example.com/hooks.slack.com"),
+            WebhookKind::Generic
+        );
+    }
+
+    #[test]
+    fn test_completed_counts_and_orders_findings() {
+        let n = ScanNotification::completed("owner/repo", &report(), vec![]);
+        assert_eq!((n.error, n.warning, n.note), (1, 0, 1));
+        assert!(n.top_findings[0].contains("SQLI"));
+    }
+
+    #[test]
+    fn test_render_custom_template() {
+        let n = ScanNotification::completed("owner/repo", &report(), vec![]);
+        assert_eq!(
+            n.render(Some("{{target}} {{status}} {{total}}/{{error}}")),
+            "owner/repo completed 2/1"
+        );
+        let failed = ScanNotification::failed("owner/repo", "boom");
+        assert!(failed.render(None).contains("Error: boom"));
+    }
+
+    #[test]
+    fn test_generic_payload_shape() {
+        let n = ScanNotification::failed("t", "boom");
+        let p = n.payload(WebhookKind::Generic, "x");
+        assert_eq!(p["event"], "scan.failed");
+        assert_eq!(p["counts"]["total"], 0);
+    }
+}
@@ -28,6 +28,7 @@ use crate::report_common::{
     build_markdown_body, build_title, extract_fingerprint, load_surface_reports,
 };
 use crate::sarif::SarifResult;
+use parsentry_core::offline::ensure_online;
 
 const NOTION_API: &str = "https://api.notion.com/v1";
 const NOTION_VERSION: &str = "2022-06-28";
@@ -41,7 +42,8 @@ pub async fn run_notion_command(
     let token = env::var("NOTION_TOKEN").map_err(|_| anyhow!("NOTION_TOKEN not set"))?;
     let db_id = env::var("NOTION_DATABASE_ID").unwrap_or_else(|_| database_id.to_string());
 
-    let client = Client::new();
+    ensure_online("Notion export")?;
+    let client = parsentry_core::http::client()?;
 
     let surfaces = load_surface_reports(reports_dir, min_level)?;
     if surfaces.is_empty() {
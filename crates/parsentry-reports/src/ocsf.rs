@@ -0,0 +1,299 @@
+//! Open Cybersecurity Schema Framework (OCSF) output for security data lakes.
+//!
+//! Each finding is a Vulnerability Finding event (class 2002) whose
+//! vulnerability points at the affected code, so Amazon Security Lake,
+//! Splunk and other OCSF consumers can ingest the report as a JSON array.
+//! Schema: https://schema.ocsf.io/1.1.0/classes/vulnerability_finding
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::gitlab_sast::vulnerability_id;
+use crate::output::write_atomic;
+use crate::report_common::{Severity, code_location, security_severity_scores};
+use crate::sarif::{SarifReport, SarifResult};
+use crate::summary::AnalysisSummary;
+
+pub const OCSF_VERSION: &str = "1.1.0";
+
+/// Vulnerability Finding, in the Findings category.
+const CLASS_UID: u32 = 2002;
+const CATEGORY_UID: u32 = 2;
+/// `class_uid * 100 + activity_id` for the Create activity.
+const TYPE_UID: u32 = CLASS_UID * 100 + 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct OcsfReport {
+    pub findings: Vec<OcsfFinding>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OcsfFinding {
+    pub activity_id: u32,
+    pub activity_name: String,
+    pub category_uid: u32,
+    pub category_name: String,
+    pub class_uid: u32,
+    pub class_name: String,
+    pub type_uid: u32,
+    pub type_name: String,
+    /// 1 Informational, 2 Low, 3 Medium, 4 High, 5 Critical
+    pub severity_id: u8,
+    pub severity: String,
+    pub status_id: u8,
+    pub status: String,
+    /// Milliseconds since the Unix epoch.
+    pub time: i64,
+    /// 0-100, from the finding's confidence.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence_score: Option<u8>,
+    pub message: String,
+    pub metadata: OcsfMetadata,
+    pub finding_info: OcsfFindingInfo,
+    pub vulnerabilities: Vec<OcsfVulnerability>,
+    pub resources: Vec<OcsfResource>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OcsfMetadata {
+    pub version: String,
+    pub product: OcsfProduct,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OcsfProduct {
+    pub name: String,
+    pub vendor_name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OcsfFindingInfo {
+    pub uid: String,
+    pub title: String,
+    pub desc: String,
+    pub types: Vec<String>,
+    pub created_time: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OcsfVulnerability {
+    pub title: String,
+    pub desc: String,
+    pub severity: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwe: Option<OcsfCwe>,
+    pub affected_code: Vec<OcsfAffectedCode>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OcsfCwe {
+    pub uid: String,
+    pub src_url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OcsfAffectedCode {
+    pub file: OcsfFile,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OcsfFile {
+    pub name: String,
+    pub path: String,
+    /// 1 Regular File
+    pub type_id: u8,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OcsfResource {
+    pub uid: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+impl OcsfReport {
+    /// Convert an analysis summary into OCSF Vulnerability Finding events.
+    pub fn from_analysis_summary(summary: &AnalysisSummary, version: &str) -> Self {
+        Self::from_sarif(&SarifReport::from_analysis_summary(summary, version))
+    }
+
+    /// Convert a (merged) SARIF report into OCSF Vulnerability Finding events.
+    pub fn from_sarif(sarif: &SarifReport) -> Self {
+        let version = sarif
+            .runs
+            .first()
+            .map(|r| r.tool.driver.version.clone())
+            .unwrap_or_else(|| env!("CARGO_PKG_VERSION").to_string());
+        let scores = security_severity_scores(sarif);
+        let now = chrono::Utc::now().timestamp_millis();
+        let findings = sarif
+            .runs
+            .iter()
+            .flat_map(|run| run.results.iter())
+            .filter(|r| r.suppressions.as_ref().is_none_or(|s| s.is_empty()))
+            .map(|r| to_finding(r, &scores, &version, now))
+            .collect();
+        Self { findings }
+    }
+
+    /// Export report to JSON string
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Save report to file
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        write_atomic(path, self.to_json()?)?;
+        Ok(())
+    }
+}
+
+fn to_finding(
+    result: &SarifResult,
+    scores: &HashMap<String, f64>,
+    version: &str,
+    now: i64,
+) -> OcsfFinding {
+    let (file, start_line, end_line) = code_location(result);
+    let (severity_id, severity) = severity(Severity::of(result, scores));
+    let props = result.properties.as_ref();
+    let cwe = props
+        .and_then(|p| p.cwe.as_ref())
+        .and_then(|cwes| cwes.first())
+        .map(|cwe| {
+            let number = cwe.trim_start_matches("CWE-");
+            OcsfCwe {
+                uid: cwe.clone(),
+                src_url: format!("https://cwe.mitre.org/data/definitions/{number}.html"),
+            }
+        });
+    let name = file.rsplit('/').next().unwrap_or(&file).to_string();
+    let affected_code = if file.is_empty() {
+        Vec::new()
+    } else {
+        vec![OcsfAffectedCode {
+            file: OcsfFile {
+                name: name.clone(),
+                path: file.clone(),
+                type_id: 1,
+            },
+            start_line,
+            end_line,
+        }]
+    };
+    let resources = if file.is_empty() {
+        Vec::new()
+    } else {
+        vec![OcsfResource {
+            uid: file.clone(),
+            name,
+            kind: "File".to_string(),
+        }]
+    };
+    let title = format!("{} in {}", result.rule_id, file);
+
+    OcsfFinding {
+        activity_id: 1,
+        activity_name: "Create".to_string(),
+        category_uid: CATEGORY_UID,
+        category_name: "Findings".to_string(),
+        class_uid: CLASS_UID,
+        class_name: "Vulnerability Finding".to_string(),
+        type_uid: TYPE_UID,
+        type_name: "Vulnerability Finding: Create".to_string(),
+        severity_id,
+        severity: severity.to_string(),
+        status_id: 1,
+        status: "New".to_string(),
+        time: now,
+        confidence_score: props
+            .and_then(|p| p.confidence)
+            .map(|c| (c.clamp(0.0, 1.0) * 100.0).round() as u8),
+        message: result.message.text.clone(),
+        metadata: OcsfMetadata {
+            version: OCSF_VERSION.to_string(),
+            product: OcsfProduct {
+                name: "Parsentry".to_string(),
+                vendor_name: "Parsentry".to_string(),
+                version: version.to_string(),
+            },
+        },
+        finding_info: OcsfFindingInfo {
+            uid: vulnerability_id(result, &file, start_line),
+            title: title.clone(),
+            desc: result.message.text.clone(),
+            types: vec![result.rule_id.clone()],
+            created_time: now,
+        },
+        vulnerabilities: vec![OcsfVulnerability {
+            title,
+            desc: result
+                .message
+                .markdown
+                .clone()
+                .unwrap_or_else(|| result.message.text.clone()),
+            severity: severity.to_string(),
+            cwe,
+            affected_code,
+        }],
+        resources,
+    }
+}
+
+/// OCSF `severity_id` and its caption.
+fn severity(severity: Severity) -> (u8, &'static str) {
+    match severity {
+        Severity::Informational => (1, "Informational"),
+        Severity::Low => (2, "Low"),
+        Severity::Medium => (3, "Medium"),
+        Severity::High => (4, "High"),
+        Severity::Critical => (5, "Critical"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parsentry_core::{Response, VulnType};
+    use std::path::PathBuf;
+
+    #[test]
+    fn converts_summary_to_vulnerability_findings() {
+        let mut summary = AnalysisSummary::new();
+        summary.add_result(
+            PathBuf::from("app/db.py"),
+            Response {
+                analysis: "User input reaches raw SQL".to_string(),
+                confidence_score: 95,
+                vulnerability_types: vec![VulnType::SQLI],
+                ..Default::default()
+            },
+            "db.py.md".to_string(),
+        );
+        let report = OcsfReport::from_analysis_summary(&summary, "1.0.0");
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        let event = &json[0];
+        assert_eq!(event["class_uid"], 2002);
+        assert_eq!(event["type_uid"], 200201);
+        assert_eq!(event["severity_id"], 4);
+        assert_eq!(event["metadata"]["version"], OCSF_VERSION);
+        assert_eq!(event["metadata"]["product"]["version"], "1.0.0");
+        let vulnerability = &event["vulnerabilities"][0];
+        assert_eq!(vulnerability["cwe"]["uid"], "CWE-89");
+        assert_eq!(
+            vulnerability["affected_code"][0]["file"]["path"],
+            "app/db.py"
+        );
+        assert_eq!(vulnerability["affected_code"][0]["file"]["name"], "db.py");
+        assert_eq!(event["resources"][0]["type"], "File");
+    }
+}
@@ -0,0 +1,187 @@
+//! Crash- and concurrency-safe writing of reports.
+//!
+//! Files are written to a temporary file next to their destination and
+//! renamed over it, so readers and later runs see the old file or the new
+//! one, never a partial one. Writing the content a file already has leaves
+//! it untouched, so retried steps are idempotent. Processes writing into
+//! the same directory (a target's cache directory) take turns through an
+//! [`OutputLock`]. Directories of generated reports record what they hold,
+//! so [`prune_stale`] removes reports a later run no longer writes without
+//! touching anything else in them.
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::{Context, Result};
+
+/// Lock file of a directory being written.
+pub const LOCK_FILE: &str = ".parsentry.lock";
+
+/// Files written to a report directory by the last run, one per line.
+pub const WRITTEN_FILE: &str = ".parsentry-files";
+
+static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// Replace `path` with `contents` in one step, creating its directory.
+pub fn write_atomic(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Result<()> {
+    let (path, contents) = (path.as_ref(), contents.as_ref());
+    if std::fs::read(path).is_ok_and(|existing| existing == contents) {
+        return Ok(());
+    }
+    let dir = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let temp = temp_path(dir, path);
+    let written = File::create(&temp)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|()| std::fs::rename(&temp, path));
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&temp);
+        return Err(e).with_context(|| format!("failed to write {}", path.display()));
+    }
+    Ok(())
+}
+
+/// Hidden temporary file in `dir`, unique across processes and threads.
+fn temp_path(dir: &Path, path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map_or_else(|| "output".into(), |n| n.to_string_lossy());
+    dir.join(format!(
+        ".{name}.{}.{}.tmp",
+        std::process::id(),
+        TEMP_FILES.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Record `written`, paths relative to `dir`, as the reports in `dir`, and
+/// delete those the previous run recorded that are not among them.
+pub fn prune_stale(dir: &Path, written: &[PathBuf]) -> Result<()> {
+    let manifest = dir.join(WRITTEN_FILE);
+    let previous = std::fs::read_to_string(&manifest).unwrap_or_default();
+    for name in previous.lines().map(Path::new) {
+        let inside = name
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_)));
+        if !inside || written.iter().any(|w| w == name) {
+            continue;
+        }
+        let path = dir.join(name);
+        match std::fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(e).with_context(|| format!("failed to remove {}", path.display()));
+            }
+        }
+        // Mirrored layouts leave directories that held only stale reports.
+        let mut parent = path.parent();
+        while let Some(p) = parent.filter(|p| *p != dir) {
+            if std::fs::remove_dir(p).is_err() {
+                break;
+            }
+            parent = p.parent();
+        }
+    }
+    let names: String = written
+        .iter()
+        .map(|w| format!("{}\n", w.to_string_lossy()))
+        .collect();
+    write_atomic(&manifest, names)
+}
+
+/// Exclusive lock on a directory, released when dropped or when the
+/// holding process dies.
+#[derive(Debug)]
+pub struct OutputLock {
+    _file: File,
+}
+
+impl OutputLock {
+    /// Lock `dir`, waiting until no one else holds it.
+    pub fn acquire(dir: &Path) -> Result<Self> {
+        let file = Self::open(dir)?;
+        file.lock()
+            .with_context(|| format!("failed to lock {}", dir.display()))?;
+        Ok(Self { _file: file })
+    }
+
+    /// Lock `dir`, or `None` when someone else holds it.
+    pub fn try_acquire(dir: &Path) -> Result<Option<Self>> {
+        let file = Self::open(dir)?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Self { _file: file })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => {
+                Err(e).with_context(|| format!("failed to lock {}", dir.display()))
+            }
+        }
+    }
+
+    fn open(dir: &Path) -> Result<File> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+        let path = dir.join(LOCK_FILE);
+        OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(&path)
+            .with_context(|| format!("failed to open {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_files_whole_and_serializes_writers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reports/report.md");
+        write_atomic(&path, "# Report\n").unwrap();
+        write_atomic(&path, "# Report\n\nupdated\n").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# Report\n\nupdated\n"
+        );
+        let names: Vec<_> = std::fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["report.md"]);
+
+        let lock = OutputLock::try_acquire(dir.path()).unwrap().unwrap();
+        assert!(OutputLock::try_acquire(dir.path()).unwrap().is_none());
+        drop(lock);
+        assert!(OutputLock::try_acquire(dir.path()).unwrap().is_some());
+    }
+
+    #[test]
+    fn prunes_only_reports_it_wrote() {
+        let dir = tempfile::tempdir().unwrap();
+        write_atomic(dir.path().join("notes.md"), "mine").unwrap();
+        write_atomic(dir.path().join("app/db.py.md"), "# db").unwrap();
+        write_atomic(dir.path().join("app/views.py.md"), "# views").unwrap();
+        let first = [
+            PathBuf::from("app/db.py.md"),
+            PathBuf::from("app/views.py.md"),
+        ];
+        prune_stale(dir.path(), &first).unwrap();
+        assert!(dir.path().join("notes.md").exists());
+
+        std::fs::remove_file(dir.path().join("app/views.py.md")).unwrap();
+        write_atomic(dir.path().join("web.py.md"), "# web").unwrap();
+        prune_stale(dir.path(), &[PathBuf::from("web.py.md")]).unwrap();
+        assert!(!dir.path().join("app").exists());
+        assert!(dir.path().join("web.py.md").exists());
+        assert!(dir.path().join("notes.md").exists());
+    }
+}
@@ -0,0 +1,605 @@
+//! Policy-as-code gate evaluated against the merged report.
+//!
+//! A policy file (YAML or JSON) lists rules; each rule selects findings and
+//! caps how many may exist:
+//!
+//! ```yaml
+//! rules:
+//!   - name: no-errors-in-auth
+//!     paths: ["src/auth/**"]
+//!     min_level: error
+//!     max: 0
+//!   - name: limit-sqli
+//!     rule_ids: [SQLI]
+//!     min_level: warning
+//!     max: 5
+//! ```
+//!
+//! Files ending in `.rego` are evaluated with an embedded OPA/Rego engine
+//! instead. The policy must declare `package parsentry` and a `deny` set of
+//! messages; an empty `deny` allows. `input` has the shape
+//! `{"findings": [{rule_id, level, uri, line, confidence, title, cwe}],
+//! "counts": {total, error, warning, note}}`:
+//!
+//! ```rego
+//! package parsentry
+//!
+//! deny contains msg if {
+//!     some f in input.findings
+//!     f.level == "error"
+//!     startswith(f.uri, "src/auth/")
+//!     msg := sprintf("%s in auth code", [f.title])
+//! }
+//! ```
+//!
+//! Suppressed and `absent` results never count against a policy.
+//!
+//! In monorepos, a `parsentry-policy.yml` inside a subdirectory of the target
+//! adds rules scoped to that directory (their `paths` are relative to it) and
+//! may list `exclude_rule_ids` that stop those finding types under the
+//! directory from counting against any rule.
+
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::Path;
+
+use parsentry_core::FileDiscovery;
+
+use crate::report_common::{build_title, level_passes};
+use crate::sarif::{SarifReport, SarifResult};
+
+/// File name of per-directory policy overrides.
+pub const POLICY_OVERRIDE_FILE: &str = "parsentry-policy.yml";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    #[serde(default)]
+    pub rules: Vec<PolicyRule>,
+    /// Rule IDs whose findings never count against this policy.
+    #[serde(default)]
+    pub exclude_rule_ids: Vec<String>,
+    /// Exclusions contributed by nested overrides: (directory, rule IDs).
+    #[serde(skip)]
+    scoped_exclusions: Vec<(String, Vec<String>)>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyRule {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Glob patterns matched against the finding's artifact URI.
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// SARIF rule IDs (e.g. `SQLI`); empty matches every rule.
+    #[serde(default)]
+    pub rule_ids: Vec<String>,
+    #[serde(default = "default_min_level")]
+    pub min_level: String,
+    /// Minimum `properties.confidence` (0.0–1.0).
+    #[serde(default)]
+    pub min_confidence: Option<f64>,
+    /// Maximum number of matching findings allowed.
+    #[serde(default)]
+    pub max: usize,
+}
+
+fn default_min_level() -> String {
+    "note".to_string()
+}
+
+/// A policy loaded from disk, evaluated by the engine matching its format.
+#[derive(Debug, Clone)]
+pub enum PolicyEngine {
+    Rules(Policy),
+    Rego(RegoPolicy),
+}
+
+#[derive(Debug, Clone)]
+pub struct RegoPolicy {
+    path: String,
+    source: String,
+}
+
+const REGO_DENY_RULE: &str = "data.parsentry.deny";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyViolation {
+    pub rule: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub count: usize,
+    pub max: usize,
+    pub findings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyDecision {
+    pub engine: String,
+    pub passed: bool,
+    pub evaluated: usize,
+    pub violations: Vec<PolicyViolation>,
+}
+
+/// Findings that count against a policy.
+fn active_results(report: &SarifReport) -> Vec<&SarifResult> {
+    report
+        .runs
+        .iter()
+        .flat_map(|r| r.results.iter())
+        .filter(|r| r.baseline_state.as_deref() != Some("absent"))
+        .filter(|r| r.suppressions.as_ref().is_none_or(|s| s.is_empty()))
+        .collect()
+}
+
+impl PolicyEngine {
+    /// Load a policy, choosing Rego for `.rego` files and rules otherwise.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        if path.extension().is_some_and(|ext| ext == "rego") {
+            RegoPolicy::from_file(path).map(Self::Rego)
+        } else {
+            Policy::from_file(path).map(Self::Rules)
+        }
+    }
+
+    pub fn evaluate(&self, report: &SarifReport) -> Result<PolicyDecision> {
+        match self {
+            Self::Rules(policy) => Ok(policy.evaluate(report)),
+            Self::Rego(policy) => policy.evaluate(report),
+        }
+    }
+}
+
+impl Policy {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("cannot read policy: {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("invalid policy: {}", path.display()))
+    }
+
+    /// Parse a YAML (or JSON) policy document.
+    pub fn parse(content: &str) -> Result<Self> {
+        let policy: Policy = serde_yaml::from_str(content)?;
+        for rule in &policy.rules {
+            build_globset(&rule.paths)
+                .with_context(|| format!("invalid path glob in rule '{}'", rule.name))?;
+        }
+        Ok(policy)
+    }
+
+    /// Merge `parsentry-policy.yml` files found below `root` into this policy.
+    /// Returns the directories (relative to `root`) that contributed overrides.
+    pub fn with_overrides(mut self, root: &Path) -> Result<(Self, Vec<String>)> {
+        let mut found = Vec::new();
+        FileDiscovery::with_extensions(root.to_path_buf(), Vec::new()).visit_dirs(
+            root,
+            &mut |p: &Path| {
+                if p.file_name().is_some_and(|n| n == POLICY_OVERRIDE_FILE)
+                    && let Some(dir) = p.parent().and_then(|d| d.strip_prefix(root).ok())
+                    && !dir.as_os_str().is_empty()
+                {
+                    found.push((dir.to_string_lossy().replace('\\', "/"), p.to_path_buf()));
+                }
+            },
+        )?;
+        found.sort();
+
+        let mut dirs = Vec::new();
+        for (dir, path) in found {
+            let nested = Self::from_file(&path)?;
+            self.add_override(&dir, nested);
+            dirs.push(dir);
+        }
+        Ok((self, dirs))
+    }
+
+    /// Scope `nested` to `dir` and append it.
+    fn add_override(&mut self, dir: &str, nested: Policy) {
+        for mut rule in nested.rules {
+            rule.name = format!("{dir}: {}", rule.name);
+            rule.paths = if rule.paths.is_empty() {
+                vec![format!("{dir}/**")]
+            } else {
+                rule.paths.iter().map(|p| format!("{dir}/{p}")).collect()
+            };
+            self.rules.push(rule);
+        }
+        if !nested.exclude_rule_ids.is_empty() {
+            self.scoped_exclusions
+                .push((dir.to_string(), nested.exclude_rule_ids));
+        }
+        for (sub, ids) in nested.scoped_exclusions {
+            self.scoped_exclusions.push((format!("{dir}/{sub}"), ids));
+        }
+    }
+
+    fn is_excluded(&self, result: &SarifResult) -> bool {
+        if self.exclude_rule_ids.contains(&result.rule_id) {
+            return true;
+        }
+        let uri = result
+            .locations
+            .first()
+            .map(|l| {
+                l.physical_location
+                    .artifact_location
+                    .uri
+                    .trim_start_matches("./")
+            })
+            .unwrap_or("");
+        self.scoped_exclusions.iter().any(|(dir, ids)| {
+            ids.contains(&result.rule_id)
+                && uri
+                    .strip_prefix(dir.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+
+    pub fn evaluate(&self, report: &SarifReport) -> PolicyDecision {
+        let results: Vec<&SarifResult> = active_results(report)
+            .into_iter()
+            .filter(|r| !self.is_excluded(r))
+            .collect();
+
+        let mut violations = Vec::new();
+        for rule in &self.rules {
+            // Globs were validated in `parse`.
+            let globs = build_globset(&rule.paths).unwrap_or_else(|_| GlobSet::empty());
+            let matched: Vec<&&SarifResult> =
+                results.iter().filter(|r| rule.matches(r, &globs)).collect();
+            if matched.len() > rule.max {
+                violations.push(PolicyViolation {
+                    rule: rule.name.clone(),
+                    message: format!(
+                        "{}: {} finding(s), max {}",
+                        rule.name,
+                        matched.len(),
+                        rule.max
+                    ),
+                    description: rule.description.clone(),
+                    count: matched.len(),
+                    max: rule.max,
+                    findings: matched.iter().map(|r| build_title(r)).collect(),
+                });
+            }
+        }
+
+        PolicyDecision {
+            engine: "rules".to_string(),
+            passed: violations.is_empty(),
+            evaluated: self.rules.len(),
+            violations,
+        }
+    }
+}
+
+impl RegoPolicy {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("cannot read policy: {}", path.display()))?;
+        Self::parse(&path.display().to_string(), &source)
+            .with_context(|| format!("invalid Rego policy: {}", path.display()))
+    }
+
+    pub fn parse(path: &str, source: &str) -> Result<Self> {
+        let policy = Self {
+            path: path.to_string(),
+            source: source.to_string(),
+        };
+        // Compile once up front so syntax errors surface before the scan is merged.
+        policy.engine()?;
+        Ok(policy)
+    }
+
+    fn engine(&self) -> Result<regorus::Engine> {
+        let mut engine = regorus::Engine::new();
+        engine.add_policy(self.path.clone(), self.source.clone())?;
+        Ok(engine)
+    }
+
+    pub fn evaluate(&self, report: &SarifReport) -> Result<PolicyDecision> {
+        let mut engine = self.engine()?;
+        let input = rego_input(report);
+        engine.set_input(regorus::Value::from_json_str(&input.to_string())?);
+
+        // eval_query (unlike eval_rule) yields no results when `deny` is undefined.
+        let results = engine
+            .eval_query(REGO_DENY_RULE.to_string(), false)
+            .context("Rego evaluation failed")?;
+        let mut messages = Vec::new();
+        for denied in results
+            .result
+            .iter()
+            .flat_map(|r| r.expressions.iter())
+            .map(|e| &e.value)
+        {
+            for item in denied.as_set()?.iter() {
+                messages.push(match item.as_string() {
+                    Ok(s) => s.to_string(),
+                    Err(_) => item.to_json_str()?,
+                });
+            }
+        }
+
+        Ok(PolicyDecision {
+            engine: "rego".to_string(),
+            passed: messages.is_empty(),
+            evaluated: 1,
+            violations: messages
+                .into_iter()
+                .map(|message| PolicyViolation {
+                    rule: "deny".to_string(),
+                    message,
+                    description: None,
+                    count: 0,
+                    max: 0,
+                    findings: Vec::new(),
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Summarised findings passed to Rego as `input`.
+fn rego_input(report: &SarifReport) -> serde_json::Value {
+    let results = active_results(report);
+    let findings: Vec<_> = results
+        .iter()
+        .map(|r| {
+            let location = r.locations.first().map(|l| &l.physical_location);
+            json!({
+                "rule_id": r.rule_id,
+                "level": r.level,
+                "uri": location.map(|l| l.artifact_location.uri.as_str()).unwrap_or(""),
+                "line": location.and_then(|l| l.region.as_ref()).map(|r| r.start_line),
+                "confidence": r.properties.as_ref().and_then(|p| p.confidence),
+                "cwe": r.properties.as_ref().and_then(|p| p.cwe.clone()).unwrap_or_default(),
+                "title": build_title(r),
+            })
+        })
+        .collect();
+    let count = |level: &str| results.iter().filter(|r| r.level == level).count();
+    json!({
+        "findings": findings,
+        "counts": {
+            "total": results.len(),
+            "error": count("error"),
+            "warning": count("warning"),
+            "note": count("note"),
+        },
+    })
+}
+
+impl PolicyRule {
+    fn matches(&self, result: &SarifResult, globs: &GlobSet) -> bool {
+        if !level_passes(&result.level, &self.min_level) {
+            return false;
+        }
+        if !self.rule_ids.is_empty() && !self.rule_ids.iter().any(|id| id == &result.rule_id) {
+            return false;
+        }
+        if let Some(min) = self.min_confidence {
+            let confidence = result
+                .properties
+                .as_ref()
+                .and_then(|p| p.confidence)
+                .unwrap_or(0.0);
+            if confidence < min {
+                return false;
+            }
+        }
+        if !self.paths.is_empty() {
+            let uri = result
+                .locations
+                .first()
+                .map(|l| l.physical_location.artifact_location.uri.as_str())
+                .unwrap_or("");
+            if !globs.is_match(uri.trim_start_matches("./")) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn build_globset(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for p in patterns {
+        builder.add(Glob::new(p)?);
+    }
+    Ok(builder.build()?)
+}
+
+impl PolicyDecision {
+    /// Human-readable reasons, one per violated rule.
+    pub fn reasons(&self) -> Vec<String> {
+        self.violations.iter().map(|v| v.message.clone()).collect()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::from("## Policy\n\n");
+        if self.passed {
+            md.push_str(&format!(
+                "✅ **PASS** ({} rule(s) evaluated)\n",
+                self.evaluated
+            ));
+            return md;
+        }
+        md.push_str(&format!(
+            "❌ **FAIL** ({} of {} rule(s) violated)\n\n",
+            self.violations.len(),
+            self.evaluated
+        ));
+        // Rego reports every message of `deny` under the same rule.
+        for section in self.violations.chunk_by(|a, b| a.rule == b.rule) {
+            md.push_str(&format!("### {}\n\n", section[0].rule));
+            if let [v] = section {
+                md.push_str(&format!("{}\n\n", v.message));
+                if let Some(desc) = &v.description {
+                    md.push_str(&format!("{desc}\n\n"));
+                }
+                for f in &v.findings {
+                    md.push_str(&format!("- {f}\n"));
+                }
+            } else {
+                for v in section {
+                    md.push_str(&format!("- {}\n", v.message));
+                    for f in &v.findings {
+                        md.push_str(&format!("  - {f}\n"));
+                    }
+                }
+            }
+            md.push('\n');
+        }
+        md
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{ResultFixture, result};
+
+    fn report() -> SarifReport {
+        crate::test_support::report([
+            result("SQLI", "src/auth/login.py")
+                .message("a")
+                .confidence(0.9),
+            result("SQLI", "src/db.py")
+                .level("warning")
+                .message("b")
+                .confidence(0.6),
+            result("XSS", "src/auth/view.py")
+                .message("c")
+                .suppressed(None),
+        ])
+    }
+
+    #[test]
+    fn test_path_scoped_rule_fails() {
+        let policy = Policy::parse(
+            "rules:\n  - name: no-errors-in-auth\n    paths: [\"src/auth/**\"]\n    min_level: error\n",
+        )
+        .unwrap();
+        let decision = policy.evaluate(&report());
+        assert!(!decision.passed);
+        assert_eq!(decision.violations[0].count, 1);
+        assert_eq!(
+            decision.reasons(),
+            vec!["no-errors-in-auth: 1 finding(s), max 0"]
+        );
+        assert!(decision.to_markdown().contains("FAIL"));
+    }
+
+    #[test]
+    fn test_rule_id_cap_and_confidence() {
+        let policy = Policy::parse(
+            r#"{"rules":[
+                {"name":"max-sqli","rule_ids":["SQLI"],"max":2},
+                {"name":"confident-sqli","rule_ids":["SQLI"],"min_confidence":0.8,"max":1}
+            ]}"#,
+        )
+        .unwrap();
+        let decision = policy.evaluate(&report());
+        assert!(decision.passed);
+        assert_eq!(decision.evaluated, 2);
+    }
+
+    #[test]
+    fn test_nested_overrides_are_scoped() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/auth")).unwrap();
+        std::fs::write(
+            dir.path().join("src/auth").join(POLICY_OVERRIDE_FILE),
+            "rules:\n  - name: strict\n    min_level: warning\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("src").join(POLICY_OVERRIDE_FILE),
+            "exclude_rule_ids: [SQLI]\nrules:\n  - name: no-sqli\n    rule_ids: [SQLI]\n",
+        )
+        .unwrap();
+
+        let (policy, dirs) = Policy::default().with_overrides(dir.path()).unwrap();
+        assert_eq!(dirs, vec!["src", "src/auth"]);
+
+        // SQLI under src/ is excluded, so only the strict auth rule could fire;
+        // the remaining auth finding (XSS) is suppressed.
+        let decision = policy.evaluate(&report());
+        assert!(decision.passed, "{:?}", decision.reasons());
+
+        let mut strict = Policy::default();
+        strict.add_override(
+            "src/auth",
+            Policy::parse("rules:\n  - name: strict\n").unwrap(),
+        );
+        let decision = strict.evaluate(&report());
+        assert_eq!(
+            decision.reasons(),
+            vec!["src/auth: strict: 1 finding(s), max 0"]
+        );
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        let err = Policy::parse("rules:\n  - name: typo\n    rule_id: [SQLI]\n").unwrap_err();
+        assert!(format!("{err:#}").contains("rule_ids"));
+    }
+
+    #[test]
+    fn test_invalid_glob_is_rejected() {
+        assert!(Policy::parse("rules:\n  - name: bad\n    paths: [\"src/[\"]\n").is_err());
+    }
+
+    #[test]
+    fn test_rego_policy_denies() {
+        let policy = RegoPolicy::parse(
+            "p.rego",
+            r#"package parsentry
+
+deny contains msg if {
+    some f in input.findings
+    f.level == "error"
+    startswith(f.uri, "src/auth/")
+    msg := sprintf("%s in auth code", [f.rule_id])
+}
+"#,
+        )
+        .unwrap();
+        let decision = policy.evaluate(&report()).unwrap();
+        assert_eq!(decision.engine, "rego");
+        assert!(!decision.passed);
+        assert_eq!(decision.reasons(), vec!["SQLI in auth code"]);
+    }
+
+    #[test]
+    fn test_rego_messages_share_one_heading() {
+        let policy = RegoPolicy::parse(
+            "p.rego",
+            r#"package parsentry
+
+deny contains msg if {
+    some f in input.findings
+    f.rule_id == "SQLI"
+    msg := sprintf("SQLI in %s", [f.uri])
+}
+"#,
+        )
+        .unwrap();
+        let md = policy.evaluate(&report()).unwrap().to_markdown();
+        assert_eq!(md.matches("### deny").count(), 1);
+        assert!(md.contains("- SQLI in src/auth/login.py\n- SQLI in src/db.py\n"));
+    }
+
+    #[test]
+    fn test_rego_policy_without_deny_allows() {
+        let policy = RegoPolicy::parse("p.rego", "package parsentry\n\nallow := true\n").unwrap();
+        assert!(policy.evaluate(&report()).unwrap().passed);
+        assert!(RegoPolicy::parse("p.rego", "package parsentry\ndeny contains").is_err());
+    }
+}
@@ -0,0 +1,372 @@
+//! Validation and repair of worker-written SARIF.
+//!
+//! Workers often wrap their JSON in Markdown fences, surround it with prose,
+//! leave trailing commas, report confidence as a percentage, omit metadata
+//! that `merge` requires or break SARIF schema constraints (see
+//! [`crate::schema`]). [`check_sarif`] fixes what can be fixed without
+//! guessing at findings and returns per-field diagnostics for the rest, which
+//! `parsentry validate` saves next to the result and hands to a repair worker.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde_json::{Map, Value, json};
+use std::path::Path;
+use std::sync::LazyLock;
+
+use crate::output::write_atomic;
+use crate::sarif::{SARIF_SCHEMA, SARIF_VERSION, SarifReport};
+use crate::schema;
+use parsentry_core::Severity;
+
+/// Diagnostics for a result that could not be repaired automatically.
+pub const DIAGNOSTICS_FILE: &str = "parse-errors.txt";
+/// Original output kept when a result was repaired in place.
+pub const RAW_RESULT_FILE: &str = "result.raw.json";
+
+static TRAILING_COMMA: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r",(\s*[}\]])").expect("valid regex"));
+
+/// Outcome of [`check_sarif`].
+#[derive(Debug)]
+pub enum Validation {
+    Valid(SarifReport),
+    /// Parsed after the listed repairs.
+    Repaired {
+        report: SarifReport,
+        repairs: Vec<String>,
+    },
+    /// Not usable; one diagnostic per problem.
+    Invalid(Vec<String>),
+}
+
+/// Parse worker output as SARIF, repairing common defects.
+pub fn check_sarif(raw: &str) -> Validation {
+    let mut repairs = Vec::new();
+    let mut value = match parse_lenient(raw, &mut repairs) {
+        Ok(value) => value,
+        Err(e) => return Validation::Invalid(vec![e]),
+    };
+    let errors = normalize(&mut value, &mut repairs);
+    if !errors.is_empty() {
+        return Validation::Invalid(errors);
+    }
+    let mut report = match serde_json::from_value::<SarifReport>(value) {
+        Ok(report) => report,
+        Err(e) => return Validation::Invalid(vec![format!("does not match the SARIF model: {e}")]),
+    };
+    repairs.extend(schema::fix(&mut report));
+    let errors = schema::errors(&report);
+    if !errors.is_empty() {
+        Validation::Invalid(errors)
+    } else if repairs.is_empty() {
+        Validation::Valid(report)
+    } else {
+        Validation::Repaired { report, repairs }
+    }
+}
+
+/// Parse `raw` as JSON, stripping fences, prose and trailing commas if needed.
+fn parse_lenient(raw: &str, repairs: &mut Vec<String>) -> Result<Value, String> {
+    let strict_error = match serde_json::from_str::<Value>(raw) {
+        Ok(value) => return Ok(value),
+        Err(e) => e,
+    };
+    let mut text = raw.trim_start_matches('\u{feff}').trim();
+    if text.starts_with("```") {
+        text = text
+            .split_once('\n')
+            .map_or("", |(_, rest)| rest)
+            .trim_end()
+            .trim_end_matches("```");
+        repairs.push("removed Markdown code fence".to_string());
+    }
+    if let (Some(start), Some(end)) = (text.find('{'), text.rfind('}'))
+        && (start > 0 || end + 1 < text.len())
+        && start < end
+    {
+        text = &text[start..=end];
+        repairs.push("removed text around the JSON object".to_string());
+    }
+    if let Ok(value) = serde_json::from_str(text) {
+        return Ok(value);
+    }
+    let without_commas = TRAILING_COMMA.replace_all(text, "$1");
+    if let Ok(value) = serde_json::from_str(&without_commas) {
+        repairs.push("removed trailing commas".to_string());
+        return Ok(value);
+    }
+    Err(format!(
+        "not valid JSON: {strict_error} (line {}, column {})",
+        strict_error.line(),
+        strict_error.column()
+    ))
+}
+
+/// Fill defaults and fix value types in place; returns what cannot be fixed.
+fn normalize(value: &mut Value, repairs: &mut Vec<String>) -> Vec<String> {
+    let mut errors = Vec::new();
+    let Some(root) = value.as_object_mut() else {
+        return vec!["top level must be a JSON object".to_string()];
+    };
+    default_field(root, "$schema", json!(SARIF_SCHEMA), "$schema", repairs);
+    default_field(root, "version", json!(SARIF_VERSION), "version", repairs);
+    let Some(runs) = root.get_mut("runs").and_then(Value::as_array_mut) else {
+        return vec!["`runs` must be an array".to_string()];
+    };
+    for (i, run) in runs.iter_mut().enumerate() {
+        let Some(run) = run.as_object_mut() else {
+            errors.push(format!("runs[{i}] must be an object"));
+            continue;
+        };
+        let driver = run
+            .entry("tool")
+            .or_insert_with(|| json!({}))
+            .as_object_mut()
+            .map(|tool| tool.entry("driver").or_insert_with(|| json!({})));
+        match driver.and_then(Value::as_object_mut) {
+            Some(driver) => {
+                let at = format!("runs[{i}].tool.driver");
+                default_field(driver, "name", json!("parsentry"), &at, repairs);
+                default_field(driver, "version", json!("unknown"), &at, repairs);
+            }
+            None => errors.push(format!("runs[{i}].tool.driver must be an object")),
+        }
+        let Some(results) = run.get_mut("results").and_then(Value::as_array_mut) else {
+            errors.push(format!("runs[{i}].results must be an array"));
+            continue;
+        };
+        for (j, result) in results.iter_mut().enumerate() {
+            let at = format!("runs[{i}].results[{j}]");
+            match result.as_object_mut() {
+                Some(result) => normalize_result(result, &at, repairs, &mut errors),
+                None => errors.push(format!("{at} must be an object")),
+            }
+        }
+    }
+    errors
+}
+
+fn normalize_result(
+    result: &mut Map<String, Value>,
+    at: &str,
+    repairs: &mut Vec<String>,
+    errors: &mut Vec<String>,
+) {
+    if !result.get("ruleId").is_some_and(Value::is_string) {
+        errors.push(format!("{at}.ruleId is required (vulnerability type)"));
+    }
+    if let Some(Value::String(text)) = result.get("message") {
+        let text = text.clone();
+        result.insert("message".to_string(), json!({ "text": text }));
+        repairs.push(format!("{at}.message: wrapped string in {{\"text\": ...}}"));
+    }
+    if !result
+        .get("message")
+        .and_then(|m| m.get("text"))
+        .is_some_and(Value::is_string)
+    {
+        errors.push(format!("{at}.message.text is required"));
+    }
+
+    let confidence = result
+        .get_mut("properties")
+        .and_then(|p| p.get_mut("confidence"));
+    if let Some(confidence) = confidence {
+        let number = match &*confidence {
+            Value::Number(n) => n.as_f64(),
+            Value::String(s) => s.trim().trim_end_matches('%').parse().ok(),
+            _ => None,
+        };
+        match number {
+            Some(c) if (0.0..=1.0).contains(&c) && confidence.is_number() => {}
+            Some(c) if (0.0..=1.0).contains(&c) => {
+                *confidence = json!(c);
+                repairs.push(format!(
+                    "{at}.properties.confidence: parsed {c} from a string"
+                ));
+            }
+            Some(c) if (1.0..=100.0).contains(&c) => {
+                *confidence = json!(c / 100.0);
+                repairs.push(format!(
+                    "{at}.properties.confidence: scaled {c} to {}",
+                    c / 100.0
+                ));
+            }
+            _ => errors.push(format!(
+                "{at}.properties.confidence must be a number between 0.0 and 1.0"
+            )),
+        }
+    }
+    let level_missing = !result.get("level").is_some_and(Value::is_string);
+    if level_missing {
+        let confidence = result
+            .get("properties")
+            .and_then(|p| p.get("confidence"))
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0);
+        let level = Severity::from_confidence((confidence * 100.0).round() as i32).sarif_level();
+        result.insert("level".to_string(), json!(level));
+        repairs.push(format!("{at}.level: set to `{level}` from confidence"));
+    }
+
+    let locations = result.get_mut("locations").and_then(Value::as_array_mut);
+    for (k, location) in locations.into_iter().flatten().enumerate() {
+        let loc = format!("{at}.locations[{k}].physicalLocation");
+        let physical = location.get_mut("physicalLocation");
+        if !physical
+            .as_ref()
+            .and_then(|p| p.get("artifactLocation"))
+            .and_then(|a| a.get("uri"))
+            .is_some_and(Value::is_string)
+        {
+            errors.push(format!("{loc}.artifactLocation.uri is required"));
+            continue;
+        }
+        let line = physical
+            .and_then(|p| p.get_mut("region"))
+            .and_then(|r| r.get_mut("startLine"));
+        if let Some(line) = line
+            && let Some(s) = line.as_str()
+        {
+            match s.trim().parse::<i64>() {
+                Ok(n) => {
+                    *line = json!(n);
+                    repairs.push(format!("{loc}.region.startLine: parsed {n} from a string"));
+                }
+                Err(_) => errors.push(format!("{loc}.region.startLine must be an integer")),
+            }
+        }
+    }
+}
+
+fn default_field(
+    object: &mut Map<String, Value>,
+    key: &str,
+    default: Value,
+    at: &str,
+    repairs: &mut Vec<String>,
+) {
+    if !object.get(key).is_some_and(Value::is_string) {
+        object.insert(key.to_string(), default.clone());
+        let name = if at == key {
+            key.to_string()
+        } else {
+            format!("{at}.{key}")
+        };
+        repairs.push(format!("{name}: set missing value to {default}"));
+    }
+}
+
+/// Validate the result file at `path`, repairing it in place when possible.
+///
+/// A repaired file keeps the original next to it as [`RAW_RESULT_FILE`];
+/// an unusable one gets a [`DIAGNOSTICS_FILE`] listing every problem.
+pub fn validate_result_file(path: &Path) -> Result<Validation> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let raw =
+        std::fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+    let validation = check_sarif(&raw);
+    match &validation {
+        Validation::Valid(_) => {
+            let _ = std::fs::remove_file(dir.join(DIAGNOSTICS_FILE));
+        }
+        Validation::Repaired { report, .. } => {
+            write_atomic(dir.join(RAW_RESULT_FILE), &raw)?;
+            report.save_to_file(path)?;
+            let _ = std::fs::remove_file(dir.join(DIAGNOSTICS_FILE));
+        }
+        Validation::Invalid(errors) => {
+            let mut diagnostics = format!("{}\n", path.display());
+            for error in errors {
+                diagnostics.push_str(&format!("- {error}\n"));
+            }
+            write_atomic(dir.join(DIAGNOSTICS_FILE), diagnostics)?;
+        }
+    }
+    Ok(validation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESULT: &str = r#"{"ruleId":"SQLI","level":"error","message":{"text":"m"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"a.py"},"region":{"startLine":3}}}],"properties":{"confidence":0.9}}"#;
+
+    fn sarif(results: &str) -> String {
+        format!(
+            r#"{{"$schema":"s","version":"2.1.0","runs":[{{"tool":{{"driver":{{"name":"p","version":"1"}}}},"results":[{results}]}}]}}"#
+        )
+    }
+
+    #[test]
+    fn test_valid_sarif_is_untouched() {
+        assert!(matches!(check_sarif(&sarif(RESULT)), Validation::Valid(_)));
+    }
+
+    #[test]
+    fn test_repairs_fences_prose_and_values() {
+        let raw = format!(
+            "Here is the report:\n```json\n{}\n```",
+            sarif(
+                r#"{"ruleId":"XSS","message":"reflected","locations":[{"physicalLocation":{"artifactLocation":{"uri":"b.js"},"region":{"startLine":"7"}}},],"properties":{"confidence":85}}"#
+            )
+            .replace(r#""$schema":"s","#, "")
+        );
+        let Validation::Repaired { report, repairs } = check_sarif(&raw) else {
+            panic!("expected a repaired report");
+        };
+        let result = &report.runs[0].results[0];
+        assert_eq!(result.message.text, "reflected");
+        assert_eq!(result.level, "warning");
+        assert_eq!(result.properties.as_ref().unwrap().confidence, Some(0.85));
+        assert_eq!(
+            result.locations[0]
+                .physical_location
+                .region
+                .as_ref()
+                .unwrap()
+                .start_line,
+            7
+        );
+        assert!(repairs.iter().any(|r| r.contains("trailing commas")));
+        assert!(repairs.iter().any(|r| r.contains("$schema")));
+    }
+
+    #[test]
+    fn test_reports_every_unfixable_field() {
+        let raw = sarif(
+            r#"{"level":"error","message":{"text":"m"},"locations":[{"physicalLocation":{}}],"properties":{"confidence":"high"}}"#,
+        );
+        let Validation::Invalid(errors) = check_sarif(&raw) else {
+            panic!("expected diagnostics");
+        };
+        assert_eq!(errors.len(), 3, "{errors:?}");
+        assert!(errors[0].contains("runs[0].results[0].ruleId"));
+
+        let Validation::Invalid(errors) = check_sarif("I found no issues.") else {
+            panic!("expected diagnostics");
+        };
+        assert!(errors[0].starts_with("not valid JSON"));
+    }
+
+    #[test]
+    fn test_validate_result_file_writes_diagnostics() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("result.sarif.json");
+        std::fs::write(&path, format!("```json\n{}\n```", sarif(RESULT))).unwrap();
+        assert!(matches!(
+            validate_result_file(&path).unwrap(),
+            Validation::Repaired { .. }
+        ));
+        assert!(dir.path().join(RAW_RESULT_FILE).exists());
+        assert!(SarifReport::from_file(&path).is_ok());
+
+        std::fs::write(&path, "{").unwrap();
+        assert!(matches!(
+            validate_result_file(&path).unwrap(),
+            Validation::Invalid(_)
+        ));
+        let diagnostics = std::fs::read_to_string(dir.path().join(DIAGNOSTICS_FILE)).unwrap();
+        assert!(diagnostics.contains("not valid JSON"));
+    }
+}
@@ -0,0 +1,120 @@
+//! Stored worker results for `scan --replay`.
+//!
+//! A replay source is either a `reports` directory from an earlier scan
+//! (or the project cache containing it) or a `transcripts` directory
+//! written by `--save-transcripts`. Results are looked up by the worker's
+//! cache key, so only analyses of identical prompts are replayed.
+
+use anyhow::{Result, bail};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::manifest::ScanManifest;
+use crate::transcript::{TRANSCRIPT_META_FILE, TRANSCRIPTS_DIR, TranscriptMeta};
+
+/// Where replayed results come from.
+#[derive(Debug)]
+pub enum ReplaySource {
+    /// `<reports>/<worker>/result.sarif.json` next to a `.cache_key`.
+    Reports(PathBuf),
+    /// Transcript results indexed by cache key.
+    Transcripts(HashMap<String, PathBuf>),
+}
+
+impl ReplaySource {
+    /// Open `path` as a transcripts, reports or project cache directory.
+    pub fn open(path: &Path) -> Result<Self> {
+        if !path.is_dir() {
+            bail!("replay source is not a directory: {}", path.display());
+        }
+        if path.file_name().is_some_and(|n| n == TRANSCRIPTS_DIR) {
+            return Ok(Self::Transcripts(index_transcripts(path)?));
+        }
+        let reports = path.join("reports");
+        if reports.is_dir() {
+            return Ok(Self::Reports(reports));
+        }
+        Ok(Self::Reports(path.to_path_buf()))
+    }
+
+    /// Manifest of the scan that produced a reports directory; transcripts
+    /// carry none.
+    pub fn manifest(&self) -> Option<ScanManifest> {
+        match self {
+            Self::Reports(dir) => ScanManifest::load(dir),
+            Self::Transcripts(_) => None,
+        }
+    }
+
+    /// Stored result for the worker in `relative_dir` with `cache_key`.
+    pub fn lookup(&self, relative_dir: &Path, cache_key: &str) -> Option<PathBuf> {
+        match self {
+            Self::Reports(dir) => {
+                let dir = dir.join(relative_dir);
+                let stored = std::fs::read_to_string(dir.join(".cache_key")).ok()?;
+                let result = dir.join("result.sarif.json");
+                (stored.trim() == cache_key && result.exists()).then_some(result)
+            }
+            Self::Transcripts(index) => index.get(cache_key).cloned(),
+        }
+    }
+}
+
+fn index_transcripts(dir: &Path) -> Result<HashMap<String, PathBuf>> {
+    let mut index = HashMap::new();
+    for entry in std::fs::read_dir(dir)?.filter_map(|e| e.ok()) {
+        let meta = std::fs::read_to_string(entry.path().join(TRANSCRIPT_META_FILE))
+            .ok()
+            .and_then(|s| serde_json::from_str::<TranscriptMeta>(&s).ok());
+        let result = entry.path().join("result.sarif.json");
+        if let Some(meta) = meta
+            && !meta.cache_key.is_empty()
+            && result.exists()
+        {
+            index.insert(meta.cache_key, result);
+        }
+    }
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_by_cache_key() {
+        let cache = tempfile::tempdir().unwrap();
+        let worker = cache.path().join("reports").join("S-1");
+        std::fs::create_dir_all(&worker).unwrap();
+        std::fs::write(worker.join(".cache_key"), "k1\n").unwrap();
+        std::fs::write(worker.join("result.sarif.json"), "{}").unwrap();
+
+        let source = ReplaySource::open(cache.path()).unwrap();
+        assert_eq!(
+            source.lookup(Path::new("S-1"), "k1"),
+            Some(worker.join("result.sarif.json"))
+        );
+        assert_eq!(source.lookup(Path::new("S-1"), "k2"), None);
+        assert_eq!(source.lookup(Path::new("S-2"), "k1"), None);
+
+        let transcript = cache
+            .path()
+            .join("reports")
+            .join(TRANSCRIPTS_DIR)
+            .join("S-1");
+        std::fs::create_dir_all(&transcript).unwrap();
+        std::fs::write(
+            transcript.join(TRANSCRIPT_META_FILE),
+            r#"{"id":"S-1","worker":"S-1","cache_key":"k1"}"#,
+        )
+        .unwrap();
+        std::fs::write(transcript.join("result.sarif.json"), "{}").unwrap();
+
+        let source =
+            ReplaySource::open(&cache.path().join("reports").join(TRANSCRIPTS_DIR)).unwrap();
+        assert_eq!(
+            source.lookup(Path::new("anything"), "k1"),
+            Some(transcript.join("result.sarif.json"))
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use std::collections::HashMap;
 use std::path::Path;
 
 use crate::sarif::{SarifReport, SarifResult};
@@ -95,6 +96,78 @@ pub fn level_passes(level: &str, min_level: &str) -> bool {
     rank(level) >= rank(min_level)
 }
 
+/// File, start line and end line of a result's first location; lines
+/// that are unknown or not positive are `None`, and the end defaults to the
+/// start.
+pub fn code_location(result: &SarifResult) -> (String, Option<u32>, Option<u32>) {
+    let location = result.locations.first().map(|l| &l.physical_location);
+    let file = location
+        .map(|l| l.artifact_location.uri.clone())
+        .unwrap_or_default();
+    let region = location.and_then(|l| l.region.as_ref());
+    let line = |line: i32| u32::try_from(line).ok().filter(|l| *l > 0);
+    let start_line = region.and_then(|r| line(r.start_line));
+    let end_line = region
+        .and_then(|r| r.end_line)
+        .and_then(line)
+        .or(start_line);
+    (file, start_line, end_line)
+}
+
+/// Five-step severity of security platforms such as Security Hub and OCSF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Informational,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// Severity of `result`: from its rule's `security-severity` score in
+    /// `scores` when known, else from its SARIF level.
+    pub fn of(result: &SarifResult, scores: &HashMap<String, f64>) -> Self {
+        match scores.get(&result.rule_id) {
+            Some(&score) => Self::from_score(score),
+            None => Self::from_level(&result.level),
+        }
+    }
+
+    /// A 0-10 `security-severity` score, banded like GitHub code scanning.
+    pub fn from_score(score: f64) -> Self {
+        match score {
+            s if s >= 9.0 => Self::Critical,
+            s if s >= 7.0 => Self::High,
+            s if s >= 4.0 => Self::Medium,
+            s if s > 0.0 => Self::Low,
+            _ => Self::Informational,
+        }
+    }
+
+    pub fn from_level(level: &str) -> Self {
+        match level {
+            "error" => Self::High,
+            "warning" => Self::Medium,
+            "note" => Self::Low,
+            _ => Self::Informational,
+        }
+    }
+}
+
+/// `security-severity` scores of the rules in `report`, by rule id.
+pub fn security_severity_scores(report: &SarifReport) -> HashMap<String, f64> {
+    report
+        .runs
+        .iter()
+        .flat_map(|run| run.tool.driver.rules.iter().flatten())
+        .filter_map(|rule| {
+            let score = rule.properties.as_ref()?.security_severity.as_ref()?;
+            Some((rule.id.clone(), score.parse().ok()?))
+        })
+        .collect()
+}
+
 pub fn build_title(result: &SarifResult) -> String {
     let location = result
         .locations
@@ -196,6 +269,7 @@ mod tests {
             locations: vec![crate::sarif::SarifLocation {
                 physical_location: crate::sarif::SarifPhysicalLocation {
                     artifact_location: crate::sarif::SarifArtifactLocation {
+                        uri_base_id: None,
                         uri: uri.to_string(),
                         index: None,
                     },
@@ -203,6 +277,7 @@ mod tests {
                 },
             }],
             fingerprints: None,
+            partial_fingerprints: None,
             baseline_state: None,
             suppressions: None,
             properties: None,
@@ -333,6 +408,7 @@ mod tests {
             },
             locations: vec![],
             fingerprints: None,
+            partial_fingerprints: None,
             baseline_state: None,
             suppressions: None,
             properties: None,
@@ -380,6 +456,7 @@ mod tests {
             },
             locations: vec![],
             fingerprints: None,
+            partial_fingerprints: None,
             baseline_state: None,
             suppressions: None,
             properties: None,
@@ -400,6 +477,18 @@ mod tests {
             action: None,
             resource: None,
             data_flow: None,
+            consensus: None,
+            raw_confidence: None,
+            initial_confidence: None,
+            verification: None,
+            transcript: None,
+            fix_patch: None,
+            introduced_by: None,
+            blame: None,
+            poc_run: None,
+            model: None,
+            owners: None,
+            risk_score: None,
         });
         let body = build_markdown_body(&result, None);
         assert!(body.contains("## Classification"));
@@ -442,6 +531,7 @@ mod tests {
             schema: "".to_string(),
             version: "2.1.0".to_string(),
             runs: vec![crate::sarif::SarifRun {
+                original_uri_base_ids: None,
                 tool: crate::sarif::SarifTool {
                     driver: crate::sarif::SarifDriver {
                         name: "test".to_string(),
@@ -457,6 +547,7 @@ mod tests {
                 ],
                 artifacts: None,
                 invocation: None,
+                automation_details: None,
             }],
         };
         let errors = collect_results(&report, "error");
@@ -0,0 +1,124 @@
+//! User templates for report.md (`PARSENTRY_REPORT_TEMPLATE`).
+//!
+//! A template is Markdown with placeholders for the sections of the
+//! built-in report, so a corporate header, required sections or another
+//! section order can be added without giving up the generated content:
+//!
+//! ```markdown
+//! # ACME Security Review ({{date}})
+//!
+//! {{summary}}
+//!
+//! ## Findings
+//!
+//! {{findings}}
+//! ```
+//!
+//! Without a template, report.md is [`SarifReport::to_markdown`].
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+use crate::sarif::{
+    SarifReport, run_coverage_markdown, run_findings_markdown, run_status_markdown,
+    run_tool_markdown,
+};
+use parsentry_core::template::{fill_placeholders, placeholders};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportTemplate {
+    text: String,
+}
+
+impl ReportTemplate {
+    /// Placeholders a template may use.
+    pub const PLACEHOLDERS: &[&str] = &[
+        "report",
+        "summary",
+        "tool",
+        "status",
+        "coverage",
+        "findings",
+        "total_findings",
+        "date",
+    ];
+
+    /// Parse a template, rejecting unknown or unterminated placeholders.
+    pub fn parse(text: &str) -> Result<Self> {
+        placeholders(text, Self::PLACEHOLDERS)?;
+        Ok(Self {
+            text: text.to_string(),
+        })
+    }
+
+    /// The template at `PARSENTRY_REPORT_TEMPLATE`, if set.
+    pub fn load() -> Result<Option<Self>> {
+        let Some(path) = std::env::var_os("PARSENTRY_REPORT_TEMPLATE").filter(|p| !p.is_empty())
+        else {
+            return Ok(None);
+        };
+        Self::load_from(&PathBuf::from(path)).map(Some)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("cannot read report template {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("invalid report template {}", path.display()))
+    }
+
+    /// The template with every placeholder filled in from `report`.
+    pub fn render(&self, report: &SarifReport) -> String {
+        let sections = |section: fn(&crate::sarif::SarifRun) -> String| -> String {
+            report.runs.iter().map(section).collect()
+        };
+        fill_placeholders(&self.text, |name| match name {
+            "report" => report.to_markdown(),
+            "summary" => report.to_summary_markdown(),
+            "tool" => sections(run_tool_markdown),
+            "status" => sections(run_status_markdown),
+            "coverage" => sections(run_coverage_markdown),
+            "findings" => sections(run_findings_markdown),
+            "total_findings" => report
+                .runs
+                .iter()
+                .map(|run| run.results.len())
+                .sum::<usize>()
+                .to_string(),
+            "date" => chrono::Utc::now().format("%Y-%m-%d").to_string(),
+            _ => String::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{ResultFixture, result};
+
+    fn report(rule_ids: &[&str]) -> SarifReport {
+        crate::test_support::report(
+            rule_ids
+                .iter()
+                .map(|id| result(id, "").message(&format!("{id} finding")).unlocated()),
+        )
+    }
+
+    #[test]
+    fn renders_sections_in_template_order() {
+        let template =
+            ReportTemplate::parse("# ACME Review\n\n{{ total_findings }} issues\n\n{{findings}}")
+                .unwrap();
+        let md = template.render(&report(&["SQLI", "XSS"]));
+        assert!(md.starts_with("# ACME Review\n\n2 issues\n\n**Total findings**: 2"));
+        assert!(md.contains("## Finding 2: XSS"));
+        assert!(!md.contains("# Security Analysis Report"));
+
+        let wrapped = ReportTemplate::parse("Confidential\n\n{{report}}").unwrap();
+        let sarif = report(&[]);
+        assert_eq!(
+            wrapped.render(&sarif),
+            format!("Confidential\n\n{}", sarif.to_markdown())
+        );
+        assert!(ReportTemplate::parse("{{owner}}").is_err());
+    }
+}
@@ -0,0 +1,203 @@
+//! Composite risk score used to rank findings.
+//!
+//! A finding's `risk_score` (0-10) scales the base severity of its
+//! vulnerability type by how sure the worker was, how sensitive the PAR
+//! resource it reaches is, and whether its file is reachable from an entry
+//! point of the repository:
+//!
+//! ```text
+//! risk = severity × (0.4 + 0.6 × confidence) × (0.7 + 0.3 × sensitivity) × reachability
+//! ```
+//!
+//! Reachability is 1.0 for files the call graph reaches from an entry
+//! point, 0.6 for files it does not, and 0.8 when `scan` found no entry
+//! points. Reports rank findings by this score.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use crate::report_common::security_severity_scores;
+use crate::sarif::{SarifReport, SarifResult, create_rule_for_vuln_type};
+
+/// Files reachable from the repository's entry points, written by `scan`
+/// to the project cache.
+pub const REACHABLE_FILE: &str = "reachable.json";
+
+/// Resource keywords and their sensitivity, most sensitive first.
+const SENSITIVITY: &[(&[&str], f64)] = &[
+    (
+        &[
+            "secret",
+            "credential",
+            "password",
+            "token",
+            "private key",
+            "kms",
+            "vault",
+        ],
+        1.0,
+    ),
+    (
+        &[
+            "payment", "billing", "card", "admin", "auth", "session", "pii",
+        ],
+        0.9,
+    ),
+    (&["database", "db", "sql", "table", "user", "account"], 0.8),
+    (
+        &[
+            "file",
+            "filesystem",
+            "bucket",
+            "storage",
+            "shell",
+            "command",
+        ],
+        0.7,
+    ),
+    (&["log", "cache", "metric", "static"], 0.3),
+];
+
+/// Sensitivity of resources that match no keyword.
+const DEFAULT_SENSITIVITY: f64 = 0.5;
+
+/// Sensitivity of a PAR resource description, 0-1.
+pub fn resource_sensitivity(resource: &str) -> f64 {
+    let resource = resource.to_lowercase();
+    SENSITIVITY
+        .iter()
+        .find(|(keywords, _)| keywords.iter().any(|k| resource.contains(k)))
+        .map_or(DEFAULT_SENSITIVITY, |(_, sensitivity)| *sensitivity)
+}
+
+/// Base severity of `result`'s vulnerability type: its rule's
+/// `security-severity`, else parsentry's default for the type.
+fn base_severity(result: &SarifResult, scores: &HashMap<String, f64>) -> f64 {
+    scores.get(&result.rule_id).copied().unwrap_or_else(|| {
+        let vuln_type = result.rule_id.parse().unwrap_or_else(|e| match e {});
+        create_rule_for_vuln_type(&vuln_type)
+            .properties
+            .and_then(|p| p.security_severity?.parse().ok())
+            .unwrap_or(5.0)
+    })
+}
+
+/// Risk score of `result`; `reachable` is `None` when reachability is unknown.
+pub fn risk_score(
+    result: &SarifResult,
+    scores: &HashMap<String, f64>,
+    reachable: Option<bool>,
+) -> f64 {
+    let props = result.properties.as_ref();
+    let confidence = props
+        .and_then(|p| p.confidence)
+        .map_or(0.5, |c| c.clamp(0.0, 1.0));
+    let sensitivity = props
+        .and_then(|p| p.resource.as_deref())
+        .map_or(DEFAULT_SENSITIVITY, resource_sensitivity);
+    let reachability = match reachable {
+        Some(true) => 1.0,
+        Some(false) => 0.6,
+        None => 0.8,
+    };
+    let score = base_severity(result, scores)
+        * (0.4 + 0.6 * confidence)
+        * (0.7 + 0.3 * sensitivity)
+        * reachability;
+    (score.clamp(0.0, 10.0) * 10.0).round() / 10.0
+}
+
+/// Record `risk_score` on every result of `report`. `reachable_files` are
+/// paths relative to the repository root, or `None` when unknown.
+pub fn assign(report: &mut SarifReport, reachable_files: Option<&HashSet<String>>) {
+    let scores = security_severity_scores(report);
+    for result in report.runs.iter_mut().flat_map(|run| &mut run.results) {
+        let reachable = reachable_files.map(|files| {
+            result.locations.first().is_some_and(|l| {
+                let uri = &l.physical_location.artifact_location.uri;
+                files
+                    .iter()
+                    .any(|f| uri == f || uri.ends_with(&format!("/{f}")))
+            })
+        });
+        let score = risk_score(result, &scores, reachable);
+        result
+            .properties
+            .get_or_insert_with(Default::default)
+            .risk_score = Some(score);
+    }
+}
+
+/// `result`'s recorded risk score, or one from its level and confidence.
+pub fn score_of(result: &SarifResult) -> f64 {
+    result
+        .properties
+        .as_ref()
+        .and_then(|p| p.risk_score)
+        .unwrap_or_else(|| {
+            let level = match result.level.as_str() {
+                "error" => 8.0,
+                "warning" => 5.0,
+                "note" => 2.0,
+                _ => 0.0,
+            };
+            risk_score(
+                result,
+                &HashMap::from([(result.rule_id.clone(), level)]),
+                None,
+            )
+        })
+}
+
+/// Order findings by descending risk.
+pub fn by_risk(a: &SarifResult, b: &SarifResult) -> Ordering {
+    score_of(b).total_cmp(&score_of(a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sarif::SarifResultProperties;
+    use crate::test_support::{ResultFixture, result};
+
+    fn report() -> SarifReport {
+        let users = |p: &mut SarifResultProperties| p.resource = Some("users database".to_string());
+        crate::test_support::report([
+            result("SQLI", "app/db.py")
+                .message("a")
+                .confidence(0.9)
+                .properties(users),
+            result("SQLI", "scripts/seed.py")
+                .message("b")
+                .confidence(0.9)
+                .properties(users),
+            result("XSS", "app/view.js")
+                .level("warning")
+                .message("c")
+                .confidence(0.3),
+        ])
+    }
+
+    #[test]
+    fn ranks_reachable_sensitive_findings_first() {
+        let mut sarif = report();
+        let reachable = HashSet::from(["app/db.py".to_string(), "app/view.js".to_string()]);
+        assign(&mut sarif, Some(&reachable));
+        let scores: Vec<f64> = sarif.runs[0].results.iter().map(score_of).collect();
+        // SQLI: 8.5 × 0.94 × 0.94, and × 0.6 when unreachable
+        assert_eq!(scores[0], 7.5);
+        assert_eq!(scores[1], 4.5);
+        assert!(scores[2] < scores[1]);
+
+        let mut ranked: Vec<&SarifResult> = sarif.runs[0].results.iter().collect();
+        ranked.sort_by(|a, b| by_risk(a, b));
+        assert_eq!(ranked[0].message.text, "a");
+    }
+
+    #[test]
+    fn resource_sensitivity_keywords() {
+        assert_eq!(resource_sensitivity("AWS Secrets Manager"), 1.0);
+        assert_eq!(resource_sensitivity("orders table"), 0.8);
+        assert_eq!(resource_sensitivity("thumbnail"), DEFAULT_SENSITIVITY);
+    }
+}
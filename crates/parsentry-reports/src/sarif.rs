@@ -1,15 +1,27 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 
+use crate::blame::Blame;
+use crate::coverage::Coverage;
+use crate::fingerprint::{CONTENT_FINGERPRINT, content_fingerprint};
+use crate::grouping::grouped_markdown;
+use crate::introduced::Introduction;
+use crate::manifest::ScanManifest;
+use crate::output::write_atomic;
 use crate::summary::AnalysisSummary;
-use parsentry_core::{Response, VulnType};
+use parsentry_core::{Dependency, Response, VulnType};
+
+/// SARIF version written by parsentry and required of worker output.
+pub const SARIF_VERSION: &str = "2.1.0";
+/// `$schema` of every SARIF document parsentry writes.
+pub const SARIF_SCHEMA: &str = "https://raw.githubusercontent.com/oasis-tcs/sarif-spec/main/sarif-2.1/schema/sarif-schema-2.1.0.json";
 
 /// SARIF (Static Analysis Results Interchange Format) v2.1.0 implementation
 /// Spec: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SarifReport {
     #[serde(rename = "$schema")]
     pub schema: String,
@@ -17,7 +29,7 @@ pub struct SarifReport {
     pub runs: Vec<SarifRun>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SarifRun {
     pub tool: SarifTool,
     pub results: Vec<SarifResult>,
@@ -25,14 +37,35 @@ pub struct SarifRun {
     pub artifacts: Option<Vec<SarifArtifact>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub invocation: Option<SarifInvocation>,
+    /// Identifies the run among others in the same log, e.g. one run per
+    /// repository in a combined report.
+    #[serde(
+        rename = "automationDetails",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub automation_details: Option<SarifAutomationDetails>,
+    /// Absolute locations of the base ids that relative artifact URIs are
+    /// resolved against, e.g. `%SRCROOT%` for the scanned checkout.
+    #[serde(
+        rename = "originalUriBaseIds",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub original_uri_base_ids: Option<BTreeMap<String, SarifArtifactLocation>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SarifAutomationDetails {
+    pub id: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SarifTool {
     pub driver: SarifDriver,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SarifDriver {
     pub name: String,
     pub version: String,
@@ -42,7 +75,7 @@ pub struct SarifDriver {
     pub rules: Option<Vec<SarifRule>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SarifRule {
     pub id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -59,7 +92,7 @@ pub struct SarifRule {
     pub default_configuration: Option<SarifConfiguration>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SarifRuleProperties {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tags: Option<Vec<String>>,
@@ -71,7 +104,7 @@ pub struct SarifRuleProperties {
     pub security_severity: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SarifConfiguration {
     pub level: String,
 }
@@ -95,6 +128,14 @@ pub struct SarifResult {
     pub locations: Vec<SarifLocation>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub fingerprints: Option<HashMap<String, String>>,
+    /// Content hashes that identify the result across runs, keyed by
+    /// [`crate::fingerprint::CONTENT_FINGERPRINT`].
+    #[serde(
+        rename = "partialFingerprints",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub partial_fingerprints: Option<HashMap<String, String>>,
     /// SARIF §3.34.24: new | unchanged | updated | absent
     #[serde(rename = "baselineState", skip_serializing_if = "Option::is_none")]
     pub baseline_state: Option<String>,
@@ -116,7 +157,7 @@ pub struct SarifSuppression {
     pub justification: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SarifResultProperties {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub confidence: Option<f64>,
@@ -135,6 +176,55 @@ pub struct SarifResultProperties {
     pub resource: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data_flow: Option<String>,
+    /// Members that agreed on the finding, e.g. `2/3` (`merge --quorum`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consensus: Option<String>,
+    /// Confidence the model reported, before calibration (`PARSENTRY_CALIBRATION`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw_confidence: Option<f64>,
+    /// Confidence before second-pass verification (`scan --verify`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_confidence: Option<f64>,
+    /// Verdict of the second-pass verification: confirmed, rejected or uncertain.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification: Option<String>,
+    /// Transcript id under `reports/transcripts/` (`scan --save-transcripts`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transcript: Option<String>,
+    /// Patch file the worker proposed under `<surface>/fixes/` (`scan --suggest-fixes`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix_patch: Option<String>,
+    /// Commit that introduced the finding's line (`scan --commits`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub introduced_by: Option<Introduction>,
+    /// Last commit to change the finding's line, recorded by `generate`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blame: Option<Blame>,
+    /// Outcome of running the proof of concept in a sandbox (`parsentry poc`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub poc_run: Option<PocRun>,
+    /// Model whose worker reported the finding, from its run's invocation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// CODEOWNERS owners of the finding's file, recorded by `generate`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owners: Option<Vec<String>>,
+    /// Composite risk (0-10) reports rank findings by, recorded by `generate`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub risk_score: Option<f64>,
+}
+
+/// Result of one sandboxed proof-of-concept run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PocRun {
+    /// Sandbox the PoC ran in: `bwrap`, `podman` or `docker`.
+    pub sandbox: String,
+    /// Exit code; `None` when the PoC was killed by a signal or the timeout.
+    pub exit_code: Option<i32>,
+    #[serde(default)]
+    pub timed_out: bool,
+    pub stdout: String,
+    pub stderr: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -154,6 +244,8 @@ pub struct SarifPhysicalLocation {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SarifArtifactLocation {
     pub uri: String,
+    #[serde(rename = "uriBaseId", default, skip_serializing_if = "Option::is_none")]
+    pub uri_base_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub index: Option<usize>,
 }
@@ -177,7 +269,7 @@ pub struct SarifArtifactContent {
     pub text: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SarifArtifact {
     pub location: SarifArtifactLocation,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -186,7 +278,7 @@ pub struct SarifArtifact {
     pub mime_type: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SarifInvocation {
     #[serde(rename = "executionSuccessful")]
     pub execution_successful: bool,
@@ -196,6 +288,44 @@ pub struct SarifInvocation {
     pub end_time_utc: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub arguments: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub properties: Option<SarifInvocationProperties>,
+}
+
+/// Worker metadata recorded by `scan --compare-models` workers, and the
+/// workers a merge found no result for.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SarifInvocationProperties {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+    /// Surfaces whose worker was interrupted or never wrote a result.
+    #[serde(
+        rename = "missingResults",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub missing_results: Vec<String>,
+    /// Surfaces whose worker was given up on after its deadline.
+    #[serde(rename = "timedOut", default, skip_serializing_if = "Vec::is_empty")]
+    pub timed_out: Vec<String>,
+    /// Files discovered, matched, analyzed and skipped by the scan.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<Coverage>,
+    /// Dependencies declared in the scanned repository's manifests.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<Dependency>,
+    /// Version, configuration, model, patterns and commit the scan ran with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<ScanManifest>,
+    /// Secrets and personal data replaced by `PARSENTRY_REDACT`.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub redacted: usize,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
 }
 
 impl SarifReport {
@@ -226,6 +356,7 @@ impl SarifReport {
             let artifact_index = artifacts.len();
             artifacts.push(SarifArtifact {
                 location: SarifArtifactLocation {
+                    uri_base_id: None,
                     uri: file_path.to_string_lossy().to_string(),
                     index: Some(artifact_index),
                 },
@@ -237,11 +368,12 @@ impl SarifReport {
             for vuln_type in &response.vulnerability_types {
                 let rule_id = vuln_type.to_string();
                 let rule_index = *rule_map.get(&rule_id).unwrap();
+                let fingerprint = generate_fingerprint(file_path, &rule_id, response);
 
                 results.push(SarifResult {
                     rule_id: rule_id.clone(),
                     rule_index: Some(rule_index),
-                    level: confidence_to_level(response.confidence_score),
+                    level: response.severity().sarif_level().to_string(),
                     message: SarifMessage {
                         text: format!("{}: {}", vuln_type, response.analysis),
                         markdown: Some(response.analysis.clone()),
@@ -249,13 +381,21 @@ impl SarifReport {
                     locations: vec![SarifLocation {
                         physical_location: SarifPhysicalLocation {
                             artifact_location: SarifArtifactLocation {
+                                uri_base_id: None,
                                 uri: file_path.to_string_lossy().to_string(),
                                 index: Some(artifact_index),
                             },
                             region: None,
                         },
                     }],
-                    fingerprints: Some(generate_fingerprints(file_path, response)),
+                    fingerprints: Some(HashMap::from([(
+                        "parsentry/v1".to_string(),
+                        fingerprint.clone(),
+                    )])),
+                    partial_fingerprints: Some(HashMap::from([(
+                        CONTENT_FINGERPRINT.to_string(),
+                        fingerprint,
+                    )])),
                     baseline_state: None,
                     suppressions: None,
                     properties: Some(SarifResultProperties {
@@ -267,20 +407,35 @@ impl SarifReport {
                         action: None,
                         resource: None,
                         data_flow: None,
+                        consensus: None,
+                        raw_confidence: None,
+                        initial_confidence: None,
+                        verification: None,
+                        transcript: None,
+                        fix_patch: None,
+                        introduced_by: None,
+                        blame: None,
+                        poc_run: None,
+                        model: None,
+                        owners: None,
+                        risk_score: None,
                     }),
                 });
             }
         }
 
         SarifReport {
-            schema: "https://raw.githubusercontent.com/oasis-tcs/sarif-spec/master/Schemata/sarif-schema-2.1.0.json".to_string(),
-            version: "2.1.0".to_string(),
+            schema: SARIF_SCHEMA.to_string(),
+            version: SARIF_VERSION.to_string(),
             runs: vec![SarifRun {
+                original_uri_base_ids: None,
                 tool: SarifTool {
                     driver: SarifDriver {
                         name: "Parsentry".to_string(),
                         version: version.to_string(),
-                        information_uri: Some("https://github.com/HikaruEgashira/vulnhuntrs".to_string()),
+                        information_uri: Some(
+                            "https://github.com/HikaruEgashira/vulnhuntrs".to_string(),
+                        ),
                         rules: Some(rules),
                     },
                 },
@@ -291,7 +446,9 @@ impl SarifReport {
                     start_time_utc: None,
                     end_time_utc: None,
                     arguments: None,
+                    properties: None,
                 }),
+                automation_details: None,
             }],
         }
     }
@@ -304,7 +461,7 @@ impl SarifReport {
     /// Save SARIF report to file
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         let json = self.to_json()?;
-        std::fs::write(path, json)?;
+        write_atomic(path, json)?;
         Ok(())
     }
 
@@ -328,93 +485,130 @@ impl SarifReport {
         md.push_str("# Security Analysis Report\n\n");
 
         for run in &self.runs {
-            let tool_name = &run.tool.driver.name;
-            let tool_version = &run.tool.driver.version;
-            md.push_str(&format!("**Tool**: {} v{}\n\n", tool_name, tool_version));
-
-            if run.results.is_empty() {
-                md.push_str("No vulnerabilities detected.\n\n");
-                continue;
-            }
-
-            md.push_str(&format!("**Total findings**: {}\n\n", run.results.len()));
-
-            for (i, result) in run.results.iter().enumerate() {
-                md.push_str(&format!("## Finding {}: {}\n\n", i + 1, result.rule_id));
+            md.push_str(&run_tool_markdown(run));
+            md.push_str(&run_status_markdown(run));
+            md.push_str(&run_coverage_markdown(run));
+            md.push_str(&run_findings_markdown(run));
+        }
 
-                let level_emoji = match result.level.as_str() {
-                    "error" => "🔴",
-                    "warning" => "🟠",
-                    "note" => "🟡",
-                    _ => "⚪",
-                };
-                md.push_str(&format!(
-                    "**Severity**: {} {}\n\n",
-                    level_emoji, result.level
-                ));
+        md
+    }
 
-                if let Some(location) = result.locations.first() {
-                    let uri = &location.physical_location.artifact_location.uri;
-                    md.push_str(&format!("**File**: `{}`\n", uri));
-
-                    if let Some(region) = &location.physical_location.region {
-                        md.push_str(&format!("**Line**: {}\n", region.start_line));
-                        if let Some(snippet) = &region.snippet {
-                            md.push_str("\n**Snippet**:\n```\n");
-                            md.push_str(&snippet.text);
-                            md.push_str("\n```\n");
-                        }
-                    }
-                    md.push('\n');
-                }
+    /// One Markdown document with every finding of every run, grouped by
+    /// severity and then by file behind a table of contents, followed by
+    /// each run's status and coverage (`generate --format markdown`).
+    pub fn to_single_markdown(&self) -> String {
+        const SEVERITIES: [(&str, &str); 4] = [
+            ("error", "🔴 Error"),
+            ("warning", "🟠 Warning"),
+            ("note", "🟡 Note"),
+            ("", "⚪ Other"),
+        ];
+        let severity = |level: &str| {
+            SEVERITIES
+                .iter()
+                .position(|(l, _)| *l == level)
+                .unwrap_or(SEVERITIES.len() - 1)
+        };
+        let location = |result: &SarifResult| {
+            let location = result.locations.first().map(|l| &l.physical_location);
+            (
+                location.map_or_else(String::new, |l| l.artifact_location.uri.clone()),
+                location
+                    .and_then(|l| l.region.as_ref())
+                    .map_or(0, |r| r.start_line),
+            )
+        };
 
-                md.push_str("### Analysis\n\n");
-                if let Some(markdown_text) = &result.message.markdown {
-                    md.push_str(markdown_text);
-                } else {
-                    md.push_str(&result.message.text);
-                }
-                md.push_str("\n\n");
+        let mut findings: Vec<(usize, String, i32, &SarifRun, &SarifResult)> = self
+            .runs
+            .iter()
+            .flat_map(|run| {
+                run.results.iter().map(move |result| {
+                    let (file, line) = location(result);
+                    (severity(&result.level), file, line, run, result)
+                })
+            })
+            .collect();
+        // Within a severity, the files with the riskiest finding come first.
+        let mut file_risk: HashMap<(usize, String), f64> = HashMap::new();
+        for (sev, file, _, _, result) in &findings {
+            let risk = file_risk.entry((*sev, file.clone())).or_default();
+            *risk = risk.max(crate::risk::score_of(result));
+        }
+        findings.sort_by(|a, b| {
+            let (risk_a, risk_b) = (
+                file_risk[&(a.0, a.1.clone())],
+                file_risk[&(b.0, b.1.clone())],
+            );
+            a.0.cmp(&b.0)
+                .then_with(|| risk_b.total_cmp(&risk_a))
+                .then_with(|| (&a.1, a.2).cmp(&(&b.1, b.2)))
+        });
+
+        let mut md = String::from("# Security Analysis Report\n\n");
+        md.push_str(&format!("**Total findings**: {}\n\n", findings.len()));
+        if findings.is_empty() {
+            md.push_str("No vulnerabilities detected.\n\n");
+        }
 
-                if let Some(props) = &result.properties {
-                    if let Some(confidence) = props.confidence {
-                        md.push_str(&format!("**Confidence**: {:.0}%\n", confidence * 100.0));
-                    }
-                    if let Some(cwe) = &props.cwe
-                        && !cwe.is_empty()
-                    {
-                        md.push_str(&format!("**CWE**: {}\n", cwe.join(", ")));
-                    }
-                    if let Some(owasp) = &props.owasp
-                        && !owasp.is_empty()
-                    {
-                        md.push_str(&format!("**OWASP**: {}\n", owasp.join(", ")));
-                    }
-                    if let Some(mitre) = &props.mitre_attack
-                        && !mitre.is_empty()
-                    {
-                        md.push_str(&format!("**MITRE ATT&CK**: {}\n", mitre.join(", ")));
-                    }
-                    md.push('\n');
+        // Groups of (severity, file) in document order, with their anchors.
+        let mut groups: Vec<(usize, &str, usize)> = Vec::new();
+        for (sev, file, ..) in &findings {
+            match groups.last_mut() {
+                Some((s, f, count)) if s == sev && f == file => *count += 1,
+                _ => groups.push((*sev, file, 1)),
+            }
+        }
+        md.push_str(&grouped_markdown(
+            &findings.iter().map(|f| f.4).collect::<Vec<_>>(),
+        ));
+        if !groups.is_empty() {
+            md.push_str("## Contents\n\n");
+            for (sev, (_, label)) in SEVERITIES.iter().enumerate() {
+                let total: usize = groups.iter().filter(|g| g.0 == sev).map(|g| g.2).sum();
+                if total == 0 {
+                    continue;
                 }
-
-                if let Some(rules) = &run.tool.driver.rules
-                    && let Some(rule) = rules.iter().find(|r| r.id == result.rule_id)
-                    && let Some(help) = &rule.help
-                {
-                    md.push_str("### Remediation\n\n");
-                    if let Some(markdown_help) = &help.markdown {
-                        md.push_str(markdown_help);
+                md.push_str(&format!("- [{label}](#severity-{sev}) ({total})\n"));
+                for (i, (_, file, count)) in groups.iter().enumerate().filter(|(_, g)| g.0 == sev) {
+                    let name = if file.is_empty() {
+                        "(no location)"
                     } else {
-                        md.push_str(&help.text);
-                    }
-                    md.push_str("\n\n");
+                        file
+                    };
+                    md.push_str(&format!("  - [`{name}`](#group-{i}) ({count})\n"));
                 }
+            }
+            md.push('\n');
+        }
 
+        let mut n = 0;
+        for (i, (sev, file, count)) in groups.iter().enumerate() {
+            if i == 0 || groups[i - 1].0 != *sev {
+                md.push_str(&format!(
+                    "<a id=\"severity-{sev}\"></a>\n\n## {}\n\n",
+                    SEVERITIES[*sev].1
+                ));
+            }
+            let name = if file.is_empty() {
+                "(no location)"
+            } else {
+                file
+            };
+            md.push_str(&format!("<a id=\"group-{i}\"></a>\n\n### `{name}`\n\n"));
+            for (_, _, _, run, result) in &findings[n..n + count] {
+                n += 1;
+                md.push_str(&format!("#### {n}. {}\n\n", result.rule_id));
+                md.push_str(&result_markdown(run, result, "#####"));
                 md.push_str("---\n\n");
             }
         }
 
+        for run in &self.runs {
+            md.push_str(&run_status_markdown(run));
+            md.push_str(&run_coverage_markdown(run));
+        }
         md
     }
 
@@ -455,6 +649,7 @@ impl SarifReport {
                 md.push_str(&format!("| 🟡 Note | {} |\n", note_count));
             }
             md.push_str(&format!("| **Total** | **{}** |\n\n", run.results.len()));
+            md.push_str(&grouped_markdown(&run.results.iter().collect::<Vec<_>>()));
 
             md.push_str("## Findings\n\n");
             md.push_str("| File | Vulnerability | Severity | Confidence |\n");
@@ -487,7 +682,219 @@ impl SarifReport {
     }
 }
 
-fn create_rule_for_vuln_type(vuln_type: &VulnType) -> SarifRule {
+/// `**Tool**` line of a run in [`SarifReport::to_markdown`].
+pub(crate) fn run_tool_markdown(run: &SarifRun) -> String {
+    format!(
+        "**Tool**: {} v{}\n\n",
+        run.tool.driver.name, run.tool.driver.version
+    )
+}
+
+/// Partial-report status and the surfaces a run did not analyze.
+pub(crate) fn run_status_markdown(run: &SarifRun) -> String {
+    let mut md = String::new();
+    if let Some(props) = run.invocation.as_ref().and_then(|i| i.properties.as_ref()) {
+        let not_analyzed: Vec<(&String, &str)> = props
+            .timed_out
+            .iter()
+            .map(|s| (s, "timed out"))
+            .chain(
+                props
+                    .missing_results
+                    .iter()
+                    .map(|s| (s, "no result (interrupted or failed)")),
+            )
+            .collect();
+        if !not_analyzed.is_empty() {
+            md.push_str(&format!(
+                "**Status**: ⚠️ partial report, {} surfaces not analyzed\n\n## Not Analyzed\n\n",
+                not_analyzed.len()
+            ));
+            for (surface, reason) in not_analyzed {
+                md.push_str(&format!("- `{surface}`: {reason}\n"));
+            }
+            md.push('\n');
+        }
+        if props
+            .manifest
+            .as_ref()
+            .is_some_and(ScanManifest::reduced_precision)
+        {
+            md.push_str(
+                "**Precision**: ⚠️ reduced, findings were made from paths, symbols and structure \
+                 without reading the source code (`--no-source-upload=hashed`)\n\n",
+            );
+        }
+        if props.redacted > 0 {
+            md.push_str(&format!(
+                "**Redacted**: {} secrets or personal data replaced with `[REDACTED:<kind>]`\n\n",
+                props.redacted
+            ));
+        }
+    }
+    md
+}
+
+/// Files discovered, matched, analyzed and skipped by a run.
+pub(crate) fn run_coverage_markdown(run: &SarifRun) -> String {
+    let mut md = String::new();
+    let Some(coverage) = run
+        .invocation
+        .as_ref()
+        .and_then(|i| i.properties.as_ref())
+        .and_then(|p| p.coverage.as_ref())
+    else {
+        return md;
+    };
+    md.push_str("## Coverage\n\n| Files | Count |\n|-------|-------|\n");
+    md.push_str(&format!("| Discovered | {} |\n", coverage.discovered));
+    md.push_str(&format!("| Matched by surfaces | {} |\n", coverage.matched));
+    md.push_str(&format!("| Analyzed | {} |\n", coverage.analyzed));
+    md.push_str(&format!("| Skipped | {} |\n\n", coverage.skipped.len()));
+    for skipped in &coverage.skipped {
+        md.push_str(&format!(
+            "- `{}` ({}): {}\n",
+            skipped.path, skipped.surface, skipped.reason
+        ));
+    }
+    if !coverage.skipped.is_empty() {
+        md.push('\n');
+    }
+    md
+}
+
+/// Every finding of a run, with its location, analysis and remediation.
+pub(crate) fn run_findings_markdown(run: &SarifRun) -> String {
+    let mut md = String::new();
+    if run.results.is_empty() {
+        md.push_str("No vulnerabilities detected.\n\n");
+        return md;
+    }
+
+    md.push_str(&format!("**Total findings**: {}\n\n", run.results.len()));
+
+    for (i, result) in run.results.iter().enumerate() {
+        md.push_str(&format!("## Finding {}: {}\n\n", i + 1, result.rule_id));
+        md.push_str(&result_markdown(run, result, "###"));
+        md.push_str("---\n\n");
+    }
+    md
+}
+
+/// Severity, location, analysis, properties and remediation of one finding,
+/// with `heading` (e.g. `###`) for its Analysis and Remediation sections.
+fn result_markdown(run: &SarifRun, result: &SarifResult, heading: &str) -> String {
+    let mut md = String::new();
+    let level_emoji = match result.level.as_str() {
+        "error" => "🔴",
+        "warning" => "🟠",
+        "note" => "🟡",
+        _ => "⚪",
+    };
+    md.push_str(&format!(
+        "**Severity**: {} {}\n\n",
+        level_emoji, result.level
+    ));
+
+    if let Some(location) = result.locations.first() {
+        let uri = &location.physical_location.artifact_location.uri;
+        md.push_str(&format!("**File**: `{}`\n", uri));
+
+        if let Some(region) = &location.physical_location.region {
+            md.push_str(&format!("**Line**: {}\n", region.start_line));
+            if let Some(snippet) = &region.snippet {
+                md.push_str("\n**Snippet**:\n```\n");
+                md.push_str(&snippet.text);
+                md.push_str("\n```\n");
+            }
+        }
+        md.push('\n');
+    }
+
+    md.push_str(&format!("{heading} Analysis\n\n"));
+    if let Some(markdown_text) = &result.message.markdown {
+        md.push_str(markdown_text);
+    } else {
+        md.push_str(&result.message.text);
+    }
+    md.push_str("\n\n");
+
+    if let Some(props) = &result.properties {
+        if let Some(confidence) = props.confidence {
+            md.push_str(&format!("**Confidence**: {:.0}%\n", confidence * 100.0));
+        }
+        if let (Some(verdict), Some(initial)) = (&props.verification, props.initial_confidence) {
+            md.push_str(&format!(
+                "**Verification**: {verdict} (initial confidence {:.0}%)\n",
+                initial * 100.0
+            ));
+        }
+        if let Some(model) = &props.model {
+            md.push_str(&format!("**Model**: `{model}`\n"));
+        }
+        if let Some(transcript) = &props.transcript {
+            md.push_str(&format!("**Transcript**: `transcripts/{transcript}`\n"));
+        }
+        if let Some(introduced) = &props.introduced_by {
+            md.push_str(&format!(
+                "**Introduced by**: `{}` ({}): {}\n",
+                &introduced.commit[..introduced.commit.len().min(12)],
+                introduced.author,
+                introduced.summary
+            ));
+        }
+        if let Some(blame) = &props.blame {
+            md.push_str(&format!(
+                "**Last changed**: {}
+",
+                blame.describe()
+            ));
+        }
+        if let Some(run) = &props.poc_run {
+            let outcome = match (run.timed_out, run.exit_code) {
+                (true, _) => "timed out".to_string(),
+                (false, Some(code)) => format!("exit {code}"),
+                (false, None) => "killed".to_string(),
+            };
+            md.push_str(&format!("**PoC run**: {outcome} in {}\n", run.sandbox));
+            if !run.stdout.is_empty() {
+                md.push_str(&format!("\n```\n{}\n```\n\n", run.stdout.trim_end()));
+            }
+        }
+        if let Some(cwe) = &props.cwe
+            && !cwe.is_empty()
+        {
+            md.push_str(&format!("**CWE**: {}\n", cwe.join(", ")));
+        }
+        if let Some(owasp) = &props.owasp
+            && !owasp.is_empty()
+        {
+            md.push_str(&format!("**OWASP**: {}\n", owasp.join(", ")));
+        }
+        if let Some(mitre) = &props.mitre_attack
+            && !mitre.is_empty()
+        {
+            md.push_str(&format!("**MITRE ATT&CK**: {}\n", mitre.join(", ")));
+        }
+        md.push('\n');
+    }
+
+    if let Some(rules) = &run.tool.driver.rules
+        && let Some(rule) = rules.iter().find(|r| r.id == result.rule_id)
+        && let Some(help) = &rule.help
+    {
+        md.push_str(&format!("{heading} Remediation\n\n"));
+        if let Some(markdown_help) = &help.markdown {
+            md.push_str(markdown_help);
+        } else {
+            md.push_str(&help.text);
+        }
+        md.push_str("\n\n");
+    }
+    md
+}
+
+pub(crate) fn create_rule_for_vuln_type(vuln_type: &VulnType) -> SarifRule {
     let (name, description, help_text, security_severity, tags) = match vuln_type {
         VulnType::SQLI => (
             "SQL Injection".to_string(),
@@ -580,13 +987,11 @@ fn create_rule_for_vuln_type(vuln_type: &VulnType) -> SarifRule {
     }
 }
 
+#[cfg(test)]
 fn confidence_to_level(confidence: i32) -> String {
-    match confidence {
-        90..=100 => "error".to_string(),
-        70..=89 => "warning".to_string(),
-        50..=69 => "note".to_string(),
-        _ => "info".to_string(),
-    }
+    parsentry_core::Severity::from_confidence(confidence)
+        .sarif_level()
+        .to_string()
 }
 
 #[cfg(test)]
@@ -626,20 +1031,12 @@ fn parse_line_number_from_text(text: &str) -> Option<SarifRegion> {
     None
 }
 
-fn generate_fingerprints(file_path: &Path, response: &Response) -> HashMap<String, String> {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-
-    let mut fingerprints = HashMap::new();
-
-    // Generate a simple fingerprint based on file path and analysis
-    let mut hasher = DefaultHasher::new();
-    format!("{}:{}", file_path.display(), response.analysis).hash(&mut hasher);
-    let fingerprint = format!("{:x}", hasher.finish());
-
-    fingerprints.insert("parsentry/v1".to_string(), fingerprint);
-
-    fingerprints
+/// Content fingerprint of the finding from its matched code, as `merge`
+/// computes it from the snippet. A response without matched code is one
+/// finding per rule and file, so those identify it.
+fn generate_fingerprint(file_path: &Path, rule_id: &str, response: &Response) -> String {
+    let code = response.matched_source_code.as_deref().unwrap_or("");
+    content_fingerprint(rule_id, &file_path.to_string_lossy(), code)
 }
 
 fn guess_mime_type(file_path: &Path) -> Option<String> {
@@ -687,6 +1084,21 @@ mod tests {
         assert_eq!(sarif.runs[0].results.len(), 2); // Two vulnerabilities
     }
 
+    #[test]
+    fn test_sarif_level_uses_pattern_severity() {
+        let mut summary = AnalysisSummary::new();
+        let response = Response {
+            confidence_score: 55,
+            vulnerability_types: vec![VulnType::RCE],
+            pattern_severity: Some(parsentry_core::Severity::Critical),
+            ..Default::default()
+        };
+        summary.add_result(PathBuf::from("run.py"), response, "run.py.md".to_string());
+
+        let sarif = SarifReport::from_analysis_summary(&summary, "0.9.2");
+        assert_eq!(sarif.runs[0].results[0].level, "error");
+    }
+
     #[test]
     fn test_sarif_serialization() {
         let summary = AnalysisSummary::new();
@@ -838,6 +1250,7 @@ mod tests {
             locations: vec![SarifLocation {
                 physical_location: SarifPhysicalLocation {
                     artifact_location: SarifArtifactLocation {
+                        uri_base_id: None,
                         uri: "test.py".to_string(),
                         index: None,
                     },
@@ -845,6 +1258,7 @@ mod tests {
                 },
             }],
             fingerprints: None,
+            partial_fingerprints: None,
             baseline_state: None,
             suppressions: None,
             properties: Some(SarifResultProperties {
@@ -856,16 +1270,74 @@ mod tests {
                 action: None,
                 resource: None,
                 data_flow: None,
+                consensus: None,
+                raw_confidence: None,
+                initial_confidence: None,
+                verification: None,
+                transcript: None,
+                fix_patch: None,
+                introduced_by: None,
+                blame: None,
+                poc_run: None,
+                model: None,
+                owners: None,
+                risk_score: None,
             }),
         }
     }
 
+    #[test]
+    fn test_single_markdown_groups_by_severity_then_file() {
+        let at = |level: &str, rule_id: &str, uri: &str| {
+            let mut result = make_sarif_result(level, rule_id);
+            result.locations[0].physical_location.artifact_location.uri = uri.to_string();
+            result
+        };
+        let run = |results: Vec<SarifResult>| SarifRun {
+            original_uri_base_ids: None,
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: "Parsentry".to_string(),
+                    version: "1.0".to_string(),
+                    information_uri: None,
+                    rules: None,
+                },
+            },
+            results,
+            artifacts: None,
+            invocation: None,
+            automation_details: None,
+        };
+        let report = SarifReport {
+            schema: "".to_string(),
+            version: "2.1.0".to_string(),
+            runs: vec![
+                run(vec![
+                    at("warning", "XSS", "b.py"),
+                    at("error", "SQLI", "b.py"),
+                ]),
+                run(vec![at("error", "RCE", "a.py")]),
+            ],
+        };
+        let md = report.to_single_markdown();
+        assert!(md.contains("**Total findings**: 3"));
+        assert!(md.contains("- [🔴 Error](#severity-0) (2)\n  - [`a.py`](#group-0) (1)\n  - [`b.py`](#group-1) (1)\n- [🟠 Warning](#severity-1) (1)"));
+        let order: Vec<usize> = ["#### 1. RCE", "#### 2. SQLI", "#### 3. XSS"]
+            .iter()
+            .map(|h| md.find(h).unwrap())
+            .collect();
+        assert!(order.windows(2).all(|w| w[0] < w[1]));
+        assert!(md.contains("##### Analysis"));
+        assert_eq!(md.matches("# Security Analysis Report").count(), 1);
+    }
+
     #[test]
     fn test_to_markdown_error_emoji() {
         let report = SarifReport {
             schema: "".to_string(),
             version: "2.1.0".to_string(),
             runs: vec![SarifRun {
+                original_uri_base_ids: None,
                 tool: SarifTool {
                     driver: SarifDriver {
                         name: "Parsentry".to_string(),
@@ -877,6 +1349,7 @@ mod tests {
                 results: vec![make_sarif_result("error", "SQLI")],
                 artifacts: None,
                 invocation: None,
+                automation_details: None,
             }],
         };
         let md = report.to_markdown();
@@ -890,6 +1363,7 @@ mod tests {
             schema: "".to_string(),
             version: "2.1.0".to_string(),
             runs: vec![SarifRun {
+                original_uri_base_ids: None,
                 tool: SarifTool {
                     driver: SarifDriver {
                         name: "Parsentry".to_string(),
@@ -901,6 +1375,7 @@ mod tests {
                 results: vec![make_sarif_result("warning", "XSS")],
                 artifacts: None,
                 invocation: None,
+                automation_details: None,
             }],
         };
         let md = report.to_markdown();
@@ -913,6 +1388,7 @@ mod tests {
             schema: "".to_string(),
             version: "2.1.0".to_string(),
             runs: vec![SarifRun {
+                original_uri_base_ids: None,
                 tool: SarifTool {
                     driver: SarifDriver {
                         name: "Parsentry".to_string(),
@@ -924,6 +1400,7 @@ mod tests {
                 results: vec![make_sarif_result("note", "LFI")],
                 artifacts: None,
                 invocation: None,
+                automation_details: None,
             }],
         };
         let md = report.to_markdown();
@@ -936,6 +1413,7 @@ mod tests {
             schema: "".to_string(),
             version: "2.1.0".to_string(),
             runs: vec![SarifRun {
+                original_uri_base_ids: None,
                 tool: SarifTool {
                     driver: SarifDriver {
                         name: "Parsentry".to_string(),
@@ -947,6 +1425,7 @@ mod tests {
                 results: vec![make_sarif_result("info", "OTHER")],
                 artifacts: None,
                 invocation: None,
+                automation_details: None,
             }],
         };
         let md = report.to_markdown();
@@ -959,6 +1438,7 @@ mod tests {
             schema: "".to_string(),
             version: "2.1.0".to_string(),
             runs: vec![SarifRun {
+                original_uri_base_ids: None,
                 tool: SarifTool {
                     driver: SarifDriver {
                         name: "Parsentry".to_string(),
@@ -970,6 +1450,7 @@ mod tests {
                 results: vec![],
                 artifacts: None,
                 invocation: None,
+                automation_details: None,
             }],
         };
         let md = report.to_markdown();
@@ -982,6 +1463,7 @@ mod tests {
             schema: "".to_string(),
             version: "2.1.0".to_string(),
             runs: vec![SarifRun {
+                original_uri_base_ids: None,
                 tool: SarifTool {
                     driver: SarifDriver {
                         name: "Parsentry".to_string(),
@@ -993,6 +1475,7 @@ mod tests {
                 results: vec![make_sarif_result("error", "SQLI")],
                 artifacts: None,
                 invocation: None,
+                automation_details: None,
             }],
         };
         let md = report.to_markdown();
@@ -1006,6 +1489,7 @@ mod tests {
             schema: "".to_string(),
             version: "2.1.0".to_string(),
             runs: vec![SarifRun {
+                original_uri_base_ids: None,
                 tool: SarifTool {
                     driver: SarifDriver {
                         name: "Parsentry".to_string(),
@@ -1017,6 +1501,7 @@ mod tests {
                 results: vec![make_sarif_result("error", "SQLI")],
                 artifacts: None,
                 invocation: None,
+                automation_details: None,
             }],
         };
         let md = report.to_markdown();
@@ -1037,6 +1522,7 @@ mod tests {
             locations: vec![SarifLocation {
                 physical_location: SarifPhysicalLocation {
                     artifact_location: SarifArtifactLocation {
+                        uri_base_id: None,
                         uri: "test.py".to_string(),
                         index: None,
                     },
@@ -1052,6 +1538,7 @@ mod tests {
                 },
             }],
             fingerprints: None,
+            partial_fingerprints: None,
             baseline_state: None,
             suppressions: None,
             properties: None,
@@ -1060,6 +1547,7 @@ mod tests {
             schema: "".to_string(),
             version: "2.1.0".to_string(),
             runs: vec![SarifRun {
+                original_uri_base_ids: None,
                 tool: SarifTool {
                     driver: SarifDriver {
                         name: "Parsentry".to_string(),
@@ -1071,6 +1559,7 @@ mod tests {
                 results: vec![result],
                 artifacts: None,
                 invocation: None,
+                automation_details: None,
             }],
         };
         let md = report.to_markdown();
@@ -1086,6 +1575,7 @@ mod tests {
             schema: "".to_string(),
             version: "2.1.0".to_string(),
             runs: vec![SarifRun {
+                original_uri_base_ids: None,
                 tool: SarifTool {
                     driver: SarifDriver {
                         name: "Parsentry".to_string(),
@@ -1103,6 +1593,7 @@ mod tests {
                 ],
                 artifacts: None,
                 invocation: None,
+                automation_details: None,
             }],
         };
         let md = report.to_summary_markdown();
@@ -1118,6 +1609,7 @@ mod tests {
             schema: "".to_string(),
             version: "2.1.0".to_string(),
             runs: vec![SarifRun {
+                original_uri_base_ids: None,
                 tool: SarifTool {
                     driver: SarifDriver {
                         name: "Parsentry".to_string(),
@@ -1129,6 +1621,7 @@ mod tests {
                 results: vec![],
                 artifacts: None,
                 invocation: None,
+                automation_details: None,
             }],
         };
         let md = report.to_summary_markdown();
@@ -1142,6 +1635,7 @@ mod tests {
             schema: "".to_string(),
             version: "2.1.0".to_string(),
             runs: vec![SarifRun {
+                original_uri_base_ids: None,
                 tool: SarifTool {
                     driver: SarifDriver {
                         name: "Parsentry".to_string(),
@@ -1153,6 +1647,7 @@ mod tests {
                 results: vec![make_sarif_result("error", "SQLI")],
                 artifacts: None,
                 invocation: None,
+                automation_details: None,
             }],
         };
         let md = report.to_summary_markdown();
@@ -1167,6 +1662,7 @@ mod tests {
             schema: "".to_string(),
             version: "2.1.0".to_string(),
             runs: vec![SarifRun {
+                original_uri_base_ids: None,
                 tool: SarifTool {
                     driver: SarifDriver {
                         name: "Parsentry".to_string(),
@@ -1178,6 +1674,7 @@ mod tests {
                 results: vec![make_sarif_result("error", "SQLI")],
                 artifacts: None,
                 invocation: None,
+                automation_details: None,
             }],
         };
         let md = report.to_summary_markdown();
@@ -1185,37 +1682,42 @@ mod tests {
         assert!(md.contains("85%"));
     }
 
-    // --- generate_fingerprints tests ---
+    // --- generate_fingerprint tests ---
 
     #[test]
-    fn test_generate_fingerprints_non_empty() {
-        let response = Response {
-            scratchpad: "".to_string(),
-            analysis: "test".to_string(),
-            poc: "".to_string(),
-            confidence_score: 50,
-            vulnerability_types: vec![],
+    fn test_generate_fingerprint_ignores_analysis_and_line_shifts() {
+        let source = "def load(name):\n    return open(name)\n";
+        let response = |analysis: &str, source: &str| Response {
+            analysis: analysis.to_string(),
+            matched_source_code: Some("return open(name)".to_string()),
+            full_source_code: Some(source.to_string()),
             ..Default::default()
         };
-        let fps = generate_fingerprints(Path::new("test.py"), &response);
-        assert!(!fps.is_empty());
-        assert!(fps.contains_key("parsentry/v1"));
-        assert!(!fps["parsentry/v1"].is_empty());
+        let fp = generate_fingerprint(Path::new("a.py"), "LFI", &response("first run", source));
+        let shifted = format!("import os\n\n{source}");
+        assert_eq!(
+            fp,
+            generate_fingerprint(Path::new("a.py"), "LFI", &response("second run", &shifted))
+        );
+        assert_ne!(
+            fp,
+            generate_fingerprint(Path::new("a.py"), "RCE", &response("first run", source))
+        );
     }
 
     #[test]
-    fn test_generate_fingerprints_different_files() {
+    fn test_generate_fingerprint_different_files() {
         let response = Response {
-            scratchpad: "".to_string(),
             analysis: "test".to_string(),
-            poc: "".to_string(),
             confidence_score: 50,
-            vulnerability_types: vec![],
             ..Default::default()
         };
-        let fps1 = generate_fingerprints(Path::new("a.py"), &response);
-        let fps2 = generate_fingerprints(Path::new("b.py"), &response);
-        assert_ne!(fps1["parsentry/v1"], fps2["parsentry/v1"]);
+        let fp1 = generate_fingerprint(Path::new("a.py"), "SQLI", &response);
+        let fp2 = generate_fingerprint(Path::new("b.py"), "SQLI", &response);
+        assert_ne!(fp1, fp2);
+        let fp3 = generate_fingerprint(Path::new("routes/index.js"), "SQLI", &response);
+        let fp4 = generate_fingerprint(Path::new("lib/index.js"), "SQLI", &response);
+        assert_ne!(fp3, fp4);
     }
 
     // --- guess_mime_type tests ---
@@ -1496,6 +1998,7 @@ mod tests {
             },
             locations: vec![],
             fingerprints: None,
+            partial_fingerprints: None,
             baseline_state: None,
             suppressions: None,
             properties: Some(SarifResultProperties {
@@ -1507,12 +2010,25 @@ mod tests {
                 action: None,
                 resource: None,
                 data_flow: None,
+                consensus: None,
+                raw_confidence: None,
+                initial_confidence: None,
+                verification: None,
+                transcript: None,
+                fix_patch: None,
+                introduced_by: None,
+                blame: None,
+                poc_run: None,
+                model: None,
+                owners: None,
+                risk_score: None,
             }),
         };
         let report = SarifReport {
             schema: "".to_string(),
             version: "2.1.0".to_string(),
             runs: vec![SarifRun {
+                original_uri_base_ids: None,
                 tool: SarifTool {
                     driver: SarifDriver {
                         name: "P".to_string(),
@@ -1524,6 +2040,7 @@ mod tests {
                 results: vec![result],
                 artifacts: None,
                 invocation: None,
+                automation_details: None,
             }],
         };
         let md = report.to_markdown();
@@ -1567,6 +2084,7 @@ mod tests {
             },
             locations: vec![],
             fingerprints: None,
+            partial_fingerprints: None,
             baseline_state: None,
             suppressions: None,
             properties: None,
@@ -1575,6 +2093,7 @@ mod tests {
             schema: "".to_string(),
             version: "2.1.0".to_string(),
             runs: vec![SarifRun {
+                original_uri_base_ids: None,
                 tool: SarifTool {
                     driver: SarifDriver {
                         name: "P".to_string(),
@@ -1586,6 +2105,7 @@ mod tests {
                 results: vec![result],
                 artifacts: None,
                 invocation: None,
+                automation_details: None,
             }],
         };
         let md = report.to_markdown();
@@ -1601,6 +2121,7 @@ mod tests {
             schema: "".to_string(),
             version: "2.1.0".to_string(),
             runs: vec![SarifRun {
+                original_uri_base_ids: None,
                 tool: SarifTool {
                     driver: SarifDriver {
                         name: "P".to_string(),
@@ -1612,6 +2133,7 @@ mod tests {
                 results: vec![make_sarif_result("warning", "XSS")],
                 artifacts: None,
                 invocation: None,
+                automation_details: None,
             }],
         };
         let md = report.to_summary_markdown();
@@ -0,0 +1,308 @@
+//! Strict SARIF 2.1.0 checks run before a report is written.
+//!
+//! Serde only checks that a log has the right shape; GitHub code scanning
+//! also rejects logs that break constraints of the SARIF schema, such as a
+//! level outside `none`/`note`/`warning`/`error`, a `ruleIndex` that does not
+//! point at the result's rule, or a line number below 1. [`fix`] repairs
+//! those common defects, and [`errors`] lists what cannot be repaired
+//! without guessing. Both worker output ([`crate::repair::check_sarif`]) and
+//! the reports parsentry writes pass through here.
+
+use anyhow::{Result, bail};
+use std::collections::HashSet;
+
+use crate::sarif::{SARIF_SCHEMA, SARIF_VERSION, SarifReport, SarifResult, SarifRule, SarifRun};
+
+/// Levels allowed by SARIF §3.27.10.
+const LEVELS: &[&str] = &["none", "note", "warning", "error"];
+/// Suppression kinds allowed by SARIF §3.35.2.
+const SUPPRESSION_KINDS: &[&str] = &["inSource", "external"];
+/// Suppression statuses allowed by SARIF §3.35.3.
+const SUPPRESSION_STATUSES: &[&str] = &["accepted", "underReview", "rejected"];
+/// Baseline states allowed by SARIF §3.27.24.
+const BASELINE_STATES: &[&str] = &["new", "unchanged", "updated", "absent"];
+
+/// Repair common schema violations in place; returns one note per repair.
+pub fn fix(report: &mut SarifReport) -> Vec<String> {
+    let mut repairs = Vec::new();
+    if report.version != SARIF_VERSION {
+        repairs.push(format!(
+            "version: replaced `{}` with `{SARIF_VERSION}`",
+            report.version
+        ));
+        report.version = SARIF_VERSION.to_string();
+    }
+    if report.schema.is_empty() {
+        report.schema = SARIF_SCHEMA.to_string();
+        repairs.push("$schema: set missing value".to_string());
+    }
+    for (i, run) in report.runs.iter_mut().enumerate() {
+        fix_run(run, &format!("runs[{i}]"), &mut repairs);
+    }
+    repairs
+}
+
+fn fix_run(run: &mut SarifRun, at: &str, repairs: &mut Vec<String>) {
+    let mut rules = run.tool.driver.rules.take().unwrap_or_default();
+    let mut ids = HashSet::new();
+    let before = rules.len();
+    rules.retain(|rule| ids.insert(rule.id.clone()));
+    if rules.len() < before {
+        repairs.push(format!(
+            "{at}.tool.driver.rules: removed {} duplicate rule(s)",
+            before - rules.len()
+        ));
+    }
+    let artifacts = run.artifacts.as_ref().map_or(0, Vec::len);
+    for (j, result) in run.results.iter_mut().enumerate() {
+        let at = format!("{at}.results[{j}]");
+        fix_rule_index(result, &mut rules, &at, repairs);
+        fix_result(result, artifacts, &at, repairs);
+    }
+    run.tool.driver.rules = (!rules.is_empty()).then_some(rules);
+}
+
+/// Point `ruleIndex` at the rule named by `ruleId`. A result with an index
+/// but no such rule in the driver gets the rule added; one with neither is
+/// valid as it is.
+fn fix_rule_index(
+    result: &mut SarifResult,
+    rules: &mut Vec<SarifRule>,
+    at: &str,
+    repairs: &mut Vec<String>,
+) {
+    if result.rule_id.is_empty() {
+        return;
+    }
+    let index = match rules.iter().position(|r| r.id == result.rule_id) {
+        Some(index) => index,
+        None if result.rule_index.is_none() => return,
+        None => {
+            rules.push(SarifRule {
+                id: result.rule_id.clone(),
+                name: None,
+                short_description: None,
+                full_description: None,
+                help: None,
+                properties: None,
+                default_configuration: None,
+            });
+            repairs.push(format!(
+                "{at}: added missing rule `{}` to the driver",
+                result.rule_id
+            ));
+            rules.len() - 1
+        }
+    };
+    if result.rule_index != Some(index) {
+        let was = result
+            .rule_index
+            .map_or("missing".to_string(), |i| i.to_string());
+        repairs.push(format!("{at}.ruleIndex: set to {index} (was {was})"));
+        result.rule_index = Some(index);
+    }
+}
+
+fn fix_result(result: &mut SarifResult, artifacts: usize, at: &str, repairs: &mut Vec<String>) {
+    if !LEVELS.contains(&result.level.as_str()) {
+        let level = level_alias(&result.level);
+        repairs.push(format!(
+            "{at}.level: mapped `{}` to `{level}`",
+            result.level
+        ));
+        result.level = level.to_string();
+    }
+    for (k, location) in result.locations.iter_mut().enumerate() {
+        let physical = &mut location.physical_location;
+        let loc = format!("{at}.locations[{k}].physicalLocation");
+        if physical
+            .artifact_location
+            .index
+            .is_some_and(|i| i >= artifacts)
+        {
+            physical.artifact_location.index = None;
+            repairs.push(format!(
+                "{loc}.artifactLocation.index: removed, no such artifact"
+            ));
+        }
+        let Some(region) = &mut physical.region else {
+            continue;
+        };
+        if region.start_line < 1 {
+            repairs.push(format!(
+                "{loc}.region.startLine: raised {} to 1",
+                region.start_line
+            ));
+            region.start_line = 1;
+        }
+        if region.end_line.is_some_and(|end| end < region.start_line) {
+            region.end_line = Some(region.start_line);
+            repairs.push(format!("{loc}.region.endLine: raised to startLine"));
+        }
+        if region.start_column.is_some_and(|c| c < 1) || region.end_column.is_some_and(|c| c < 1) {
+            region.start_column = None;
+            region.end_column = None;
+            repairs.push(format!("{loc}.region: removed columns below 1"));
+        }
+    }
+    for (k, suppression) in result.suppressions.iter_mut().flatten().enumerate() {
+        let at = format!("{at}.suppressions[{k}]");
+        if !SUPPRESSION_KINDS.contains(&suppression.kind.as_str()) {
+            repairs.push(format!(
+                "{at}.kind: replaced `{}` with `external`",
+                suppression.kind
+            ));
+            suppression.kind = "external".to_string();
+        }
+        if let Some(status) = &suppression.status
+            && !SUPPRESSION_STATUSES.contains(&status.as_str())
+        {
+            repairs.push(format!("{at}.status: removed unknown `{status}`"));
+            suppression.status = None;
+        }
+    }
+    if let Some(state) = &result.baseline_state
+        && !BASELINE_STATES.contains(&state.as_str())
+    {
+        repairs.push(format!("{at}.baselineState: removed unknown `{state}`"));
+        result.baseline_state = None;
+    }
+}
+
+/// SARIF level for a severity workers commonly write instead.
+fn level_alias(level: &str) -> &'static str {
+    match level.trim().to_lowercase().as_str() {
+        "error" | "critical" | "high" | "severe" => "error",
+        "note" | "low" | "info" | "information" | "informational" => "note",
+        "none" | "off" => "none",
+        _ => "warning",
+    }
+}
+
+/// Violations [`fix`] cannot repair, one per problem.
+pub fn errors(report: &SarifReport) -> Vec<String> {
+    let mut errors = Vec::new();
+    for (i, run) in report.runs.iter().enumerate() {
+        if run.tool.driver.name.trim().is_empty() {
+            errors.push(format!("runs[{i}].tool.driver.name must not be empty"));
+        }
+        for (r, rule) in run.tool.driver.rules.iter().flatten().enumerate() {
+            if rule.id.trim().is_empty() {
+                errors.push(format!(
+                    "runs[{i}].tool.driver.rules[{r}].id must not be empty"
+                ));
+            }
+        }
+        for (j, result) in run.results.iter().enumerate() {
+            let at = format!("runs[{i}].results[{j}]");
+            if result.rule_id.trim().is_empty() {
+                errors.push(format!("{at}.ruleId must not be empty"));
+            }
+            if result.message.text.trim().is_empty() {
+                errors.push(format!("{at}.message.text must not be empty"));
+            }
+            for (k, location) in result.locations.iter().enumerate() {
+                if location
+                    .physical_location
+                    .artifact_location
+                    .uri
+                    .trim()
+                    .is_empty()
+                {
+                    errors.push(format!(
+                        "{at}.locations[{k}].physicalLocation.artifactLocation.uri must not be empty"
+                    ));
+                }
+            }
+        }
+    }
+    errors
+}
+
+/// [`fix`] `report` and fail with every remaining violation, so that no
+/// report GitHub would reject is written.
+pub fn enforce(report: &mut SarifReport) -> Result<Vec<String>> {
+    let repairs = fix(report);
+    let errors = errors(report);
+    if !errors.is_empty() {
+        bail!(
+            "SARIF does not match the 2.1.0 schema:\n  {}",
+            errors.join("\n  ")
+        );
+    }
+    Ok(repairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sarif::SarifSuppression;
+    use crate::test_support::{ResultFixture, report, result, rule};
+
+    #[test]
+    fn fixes_rule_indices_levels_and_lines() {
+        let mut sqli = result("SQLI", "a.py").level("High").message("a").line(0);
+        sqli.rule_index = Some(0);
+        let location = &mut sqli.locations[0].physical_location;
+        location.artifact_location.index = Some(3);
+        location.region.as_mut().unwrap().end_line = Some(-1);
+        let mut rce = result("RCE", "")
+            .level("medium")
+            .message("b")
+            .unlocated()
+            .baseline_state("old");
+        rce.rule_index = Some(7);
+        rce.suppressions = Some(vec![SarifSuppression {
+            kind: "manual".to_string(),
+            status: Some("done".to_string()),
+            justification: None,
+        }]);
+        let csrf = result("CSRF", "").level("note").message("c").unlocated();
+        let mut sarif = report([sqli, rce, csrf]);
+        sarif.version = "2.0.0".to_string();
+        sarif.runs[0].tool.driver.rules = Some(vec![rule("XSS"), rule("SQLI"), rule("XSS")]);
+        let repairs = fix(&mut sarif);
+        assert_eq!(sarif.version, SARIF_VERSION);
+        assert_eq!(sarif.schema, SARIF_SCHEMA);
+        let run = &sarif.runs[0];
+        let ids: Vec<&str> = run
+            .tool
+            .driver
+            .rules
+            .iter()
+            .flatten()
+            .map(|r| r.id.as_str())
+            .collect();
+        assert_eq!(ids, ["XSS", "SQLI", "RCE"]);
+        let (sqli, rce) = (&run.results[0], &run.results[1]);
+        assert_eq!((sqli.rule_index, sqli.level.as_str()), (Some(1), "error"));
+        assert_eq!((rce.rule_index, rce.level.as_str()), (Some(2), "warning"));
+        assert_eq!(run.results[2].rule_index, None);
+        let location = &sqli.locations[0].physical_location;
+        assert_eq!(location.artifact_location.index, None);
+        let region = location.region.as_ref().unwrap();
+        assert_eq!((region.start_line, region.end_line), (1, Some(1)));
+        let suppression = &rce.suppressions.as_ref().unwrap()[0];
+        assert_eq!(
+            (suppression.kind.as_str(), suppression.status.as_deref()),
+            ("external", None)
+        );
+        assert_eq!(rce.baseline_state, None);
+        assert!(repairs.contains(&"runs[0].results[0].ruleIndex: set to 1 (was 0)".to_string()));
+        assert!(
+            repairs.contains(&"runs[0].results[0].level: mapped `High` to `error`".to_string())
+        );
+
+        assert!(fix(&mut sarif).is_empty());
+        assert!(enforce(&mut sarif).is_ok());
+    }
+
+    #[test]
+    fn reports_what_cannot_be_fixed() {
+        let mut sarif = report([result("", "").message(" ")]);
+        let error = enforce(&mut sarif).unwrap_err().to_string();
+        assert!(error.contains("runs[0].results[0].ruleId must not be empty"));
+        assert!(error.contains("runs[0].results[0].message.text must not be empty"));
+        assert!(error.contains("artifactLocation.uri must not be empty"));
+    }
+}
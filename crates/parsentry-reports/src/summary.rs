@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
+use crate::filename::generate_output_filename;
+use crate::sarif::SarifReport;
 use parsentry_core::{Response, VulnType};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +31,81 @@ impl AnalysisSummary {
         });
     }
 
+    /// Summary of the unsuppressed results of agent-written SARIF, so that
+    /// summary filters and exporters apply to it as to any other analysis.
+    pub fn from_sarif(report: &SarifReport) -> Self {
+        let mut summary = Self::new();
+        summary.extend_from_sarif(report);
+        summary
+    }
+
+    /// Add the unsuppressed results of `report`. Results are grouped by
+    /// file like analyses are: each file's response lists every rule id
+    /// reported for it, with the highest confidence and all messages.
+    pub fn extend_from_sarif(&mut self, report: &SarifReport) {
+        let results = report
+            .runs
+            .iter()
+            .flat_map(|run| &run.results)
+            .filter(|r| r.suppressions.as_ref().is_none_or(|s| s.is_empty()));
+        let mut by_file: HashMap<PathBuf, usize> = self
+            .results
+            .iter()
+            .enumerate()
+            .map(|(i, r)| (r.file_path.clone(), i))
+            .collect();
+        for result in results {
+            let Some(location) = result.locations.first() else {
+                continue;
+            };
+            let file_path = PathBuf::from(&location.physical_location.artifact_location.uri);
+            let vuln_type: VulnType = result.rule_id.parse().unwrap_or_else(|e| match e {});
+            let confidence = result
+                .properties
+                .as_ref()
+                .and_then(|p| p.confidence)
+                .map_or(0, |c| (c.clamp(0.0, 1.0) * 100.0).round() as i32);
+            let analysis = result
+                .message
+                .markdown
+                .clone()
+                .unwrap_or_else(|| result.message.text.clone());
+            let snippet = location
+                .physical_location
+                .region
+                .as_ref()
+                .and_then(|r| r.snippet.as_ref())
+                .map(|s| s.text.clone());
+
+            if let Some(&index) = by_file.get(&file_path) {
+                let response = &mut self.results[index].response;
+                if !response.vulnerability_types.contains(&vuln_type) {
+                    response.vulnerability_types.push(vuln_type);
+                }
+                response.confidence_score = response.confidence_score.max(confidence);
+                response.analysis = format!("{}\n\n{analysis}", response.analysis);
+                if response.matched_source_code.is_none() {
+                    response.matched_source_code = snippet;
+                }
+                continue;
+            }
+            let output_filename = generate_output_filename(&file_path, Path::new(""));
+            by_file.insert(file_path.clone(), self.results.len());
+            self.add_result(
+                file_path.clone(),
+                Response {
+                    analysis,
+                    confidence_score: confidence,
+                    vulnerability_types: vec![vuln_type],
+                    file_path: Some(file_path.to_string_lossy().to_string()),
+                    matched_source_code: snippet,
+                    ..Default::default()
+                },
+                output_filename,
+            );
+        }
+    }
+
     pub fn sort_by_confidence(&mut self) {
         self.results.sort_by(|a, b| {
             b.response
@@ -554,4 +632,38 @@ mod tests {
         );
         assert_eq!(summary.results.len(), 2);
     }
+
+    // --- from_sarif ---
+
+    #[test]
+    fn test_from_sarif_groups_results_by_file() {
+        let report = SarifReport::from_json(
+            r#"{"$schema":"","version":"2.1.0","runs":[{"tool":{"driver":{"name":"parsentry","version":"1"}},"results":[
+                {"ruleId":"SQLI","level":"error","message":{"text":"raw query"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"app/db.py"},"region":{"startLine":3,"snippet":{"text":"cursor.execute(q)"}}}}],"properties":{"confidence":0.9}},
+                {"ruleId":"PATH_TRAVERSAL","level":"warning","message":{"text":"open(name)"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"app/db.py"}}}],"properties":{"confidence":0.6}},
+                {"ruleId":"XSS","level":"warning","message":{"text":"reflected"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"web.js"}}}],"properties":{"confidence":0.4}},
+                {"ruleId":"RCE","level":"error","message":{"text":"triaged"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"run.py"}}}],"suppressions":[{"kind":"external"}]}
+            ]}]}"#,
+        )
+        .unwrap();
+        let summary = AnalysisSummary::from_sarif(&report);
+        assert_eq!(summary.results.len(), 2);
+        let db = &summary.results[0].response;
+        assert_eq!(
+            db.vulnerability_types,
+            vec![
+                VulnType::SQLI,
+                VulnType::Other("PATH_TRAVERSAL".to_string())
+            ]
+        );
+        assert_eq!(db.confidence_score, 90);
+        assert_eq!(db.analysis, "raw query\n\nopen(name)");
+        assert_eq!(db.matched_source_code.as_deref(), Some("cursor.execute(q)"));
+
+        let filtered = summary
+            .filter_by_min_confidence(50)
+            .filter_by_vuln_types(&[VulnType::SQLI]);
+        assert_eq!(filtered.results.len(), 1);
+        assert_eq!(filtered.results[0].file_path, PathBuf::from("app/db.py"));
+    }
 }
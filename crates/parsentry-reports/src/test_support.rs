@@ -0,0 +1,158 @@
+//! SARIF fixtures for tests.
+//!
+//! [`report`] wraps [`result`]s in a single parsentry run; the
+//! [`ResultFixture`] methods adjust a result where a test needs more than a
+//! rule id and a file.
+
+use crate::sarif::{
+    SarifArtifactContent, SarifArtifactLocation, SarifDriver, SarifLocation, SarifMessage,
+    SarifPhysicalLocation, SarifRegion, SarifReport, SarifResult, SarifResultProperties, SarifRule,
+    SarifRun, SarifSuppression, SarifTool,
+};
+
+/// Report with one parsentry run holding `results`.
+pub fn report(results: impl IntoIterator<Item = SarifResult>) -> SarifReport {
+    SarifReport {
+        schema: String::new(),
+        version: "2.1.0".to_string(),
+        runs: vec![SarifRun {
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: "parsentry".to_string(),
+                    version: "1.0".to_string(),
+                    information_uri: None,
+                    rules: None,
+                },
+            },
+            results: results.into_iter().collect(),
+            artifacts: None,
+            invocation: None,
+            automation_details: None,
+            original_uri_base_ids: None,
+        }],
+    }
+}
+
+/// Driver rule `id` without metadata.
+pub fn rule(id: &str) -> SarifRule {
+    SarifRule {
+        id: id.to_string(),
+        name: None,
+        short_description: None,
+        full_description: None,
+        help: None,
+        properties: None,
+        default_configuration: None,
+    }
+}
+
+/// Error-level `rule_id` finding in `uri`, without a region.
+pub fn result(rule_id: &str, uri: &str) -> SarifResult {
+    SarifResult {
+        rule_id: rule_id.to_string(),
+        rule_index: None,
+        level: "error".to_string(),
+        message: SarifMessage {
+            text: "m".to_string(),
+            markdown: None,
+        },
+        locations: vec![SarifLocation {
+            physical_location: SarifPhysicalLocation {
+                artifact_location: SarifArtifactLocation {
+                    uri_base_id: None,
+                    uri: uri.to_string(),
+                    index: None,
+                },
+                region: None,
+            },
+        }],
+        fingerprints: None,
+        partial_fingerprints: None,
+        baseline_state: None,
+        suppressions: None,
+        properties: None,
+    }
+}
+
+/// Builder-style adjustments of a [`result`].
+pub trait ResultFixture: Sized {
+    fn level(self, level: &str) -> Self;
+    fn message(self, text: &str) -> Self;
+    /// Start line of the first location.
+    fn line(self, line: i32) -> Self;
+    /// Snippet of the first location, on line 1 unless a line was set.
+    fn snippet(self, text: &str) -> Self;
+    /// Drop the locations.
+    fn unlocated(self) -> Self;
+    fn confidence(self, confidence: f64) -> Self;
+    /// Set other result properties.
+    fn properties(self, set: impl FnOnce(&mut SarifResultProperties)) -> Self;
+    /// Suppress externally with `status`.
+    fn suppressed(self, status: Option<&str>) -> Self;
+    fn baseline_state(self, state: &str) -> Self;
+}
+
+impl ResultFixture for SarifResult {
+    fn level(mut self, level: &str) -> Self {
+        self.level = level.to_string();
+        self
+    }
+
+    fn message(mut self, text: &str) -> Self {
+        self.message.text = text.to_string();
+        self
+    }
+
+    fn line(mut self, line: i32) -> Self {
+        region(&mut self).start_line = line;
+        self
+    }
+
+    fn snippet(mut self, text: &str) -> Self {
+        region(&mut self).snippet = Some(SarifArtifactContent {
+            text: text.to_string(),
+        });
+        self
+    }
+
+    fn unlocated(mut self) -> Self {
+        self.locations.clear();
+        self
+    }
+
+    fn confidence(self, confidence: f64) -> Self {
+        self.properties(|p| p.confidence = Some(confidence))
+    }
+
+    fn properties(mut self, set: impl FnOnce(&mut SarifResultProperties)) -> Self {
+        set(self.properties.get_or_insert_with(Default::default));
+        self
+    }
+
+    fn suppressed(mut self, status: Option<&str>) -> Self {
+        self.suppressions = Some(vec![SarifSuppression {
+            kind: "external".to_string(),
+            status: status.map(str::to_string),
+            justification: None,
+        }]);
+        self
+    }
+
+    fn baseline_state(mut self, state: &str) -> Self {
+        self.baseline_state = Some(state.to_string());
+        self
+    }
+}
+
+fn region(result: &mut SarifResult) -> &mut SarifRegion {
+    result.locations[0]
+        .physical_location
+        .region
+        .get_or_insert(SarifRegion {
+            start_line: 1,
+            start_column: None,
+            end_line: None,
+            end_column: None,
+            snippet: None,
+        })
+}
@@ -0,0 +1,84 @@
+//! Per-worker transcripts archived by `scan --save-transcripts`.
+//!
+//! Each analysis worker gets `reports/transcripts/<id>/` with the exact
+//! prompt, the raw output (the agent session when it can be found, else the
+//! worker's unmodified result file), the final `result.sarif.json` and a
+//! [`TranscriptMeta`] with token counts and timing. Findings reference the
+//! id in `properties.transcript`.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path};
+
+/// Directory under `reports` holding one subdirectory per transcript.
+pub const TRANSCRIPTS_DIR: &str = "transcripts";
+/// Metadata file inside a transcript directory.
+pub const TRANSCRIPT_META_FILE: &str = "meta.json";
+
+/// How one worker's result was derived.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TranscriptMeta {
+    pub id: String,
+    /// Worker directory relative to `reports`, e.g. `S-1/models/sample-2`.
+    pub worker: String,
+    /// Cache key of the result the transcript belongs to.
+    pub cache_key: String,
+    /// File holding the raw output, relative to the transcript directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Agent session file the output was copied from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+}
+
+/// Transcript id of the worker in `relative_dir`: its path components
+/// joined by `--`, without the `models` level.
+pub fn transcript_id(relative_dir: &Path) -> String {
+    relative_dir
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(name) if name != crate::compare::MODELS_DIR => {
+                Some(name.to_string_lossy().to_string())
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("--")
+}
+
+/// Seconds between two RFC 3339 timestamps.
+pub fn duration_secs(started_at: &str, ended_at: &str) -> Option<f64> {
+    let start = chrono::DateTime::parse_from_rfc3339(started_at).ok()?;
+    let end = chrono::DateTime::parse_from_rfc3339(ended_at).ok()?;
+    Some((end - start).num_milliseconds() as f64 / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcript_id_and_duration() {
+        assert_eq!(transcript_id(Path::new("S-1")), "S-1");
+        assert_eq!(
+            transcript_id(&Path::new("S-1").join("models").join("sample-2")),
+            "S-1--sample-2"
+        );
+        assert_eq!(
+            duration_secs("2026-01-01T00:00:00Z", "2026-01-01T00:01:30.5Z"),
+            Some(90.5)
+        );
+        assert_eq!(duration_secs("yesterday", "2026-01-01T00:00:00Z"), None);
+    }
+}
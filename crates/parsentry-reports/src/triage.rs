@@ -0,0 +1,180 @@
+//! Triage decisions on findings.
+//!
+//! `parsentry review` records one [`Decision`] per finding, keyed by
+//! fingerprint, in [`TRIAGE_FILE`] in the project cache next to
+//! `merged.sarif.json`. `merge` turns false positives and accepted risks
+//! into SARIF suppressions, so later scans carry them as suppressed findings
+//! instead of reporting them again.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::merge::fingerprint;
+use crate::output::write_atomic;
+use crate::sarif::{SarifResult, SarifSuppression};
+
+/// Triage file in the project cache directory.
+pub const TRIAGE_FILE: &str = "triage.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Decision {
+    TruePositive,
+    FalsePositive,
+    AcceptedRisk,
+}
+
+impl Decision {
+    pub fn label(self) -> &'static str {
+        match self {
+            Decision::TruePositive => "true positive",
+            Decision::FalsePositive => "false positive",
+            Decision::AcceptedRisk => "accepted risk",
+        }
+    }
+
+    /// Whether later reports suppress findings with this decision.
+    pub fn suppresses(self) -> bool {
+        !matches!(self, Decision::TruePositive)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriageEntry {
+    pub fingerprint: String,
+    /// Rule and file of the finding, for readers of the file.
+    pub rule_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    pub decision: Decision,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub decided_at: String,
+}
+
+/// All triage decisions of a project.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Triage {
+    pub entries: Vec<TriageEntry>,
+}
+
+impl Triage {
+    /// Load `path`, or an empty triage when it does not exist yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("cannot read triage file: {}", path.display()))?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("invalid triage file: {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        write_atomic(path, serde_json::to_string_pretty(self)? + "\n")
+    }
+
+    pub fn get(&self, result: &SarifResult) -> Option<&TriageEntry> {
+        let fp = fingerprint(result);
+        self.entries.iter().find(|e| e.fingerprint == fp)
+    }
+
+    /// Record `decision` for `result`, replacing an earlier one.
+    pub fn decide(&mut self, result: &SarifResult, decision: Decision, note: Option<String>) {
+        self.clear(result);
+        self.entries.push(TriageEntry {
+            fingerprint: fingerprint(result),
+            rule_id: result.rule_id.clone(),
+            uri: result
+                .locations
+                .first()
+                .map(|l| l.physical_location.artifact_location.uri.clone()),
+            decision,
+            note,
+            decided_at: chrono::Utc::now().to_rfc3339(),
+        });
+    }
+
+    pub fn clear(&mut self, result: &SarifResult) {
+        let fp = fingerprint(result);
+        self.entries.retain(|e| e.fingerprint != fp);
+    }
+
+    /// Suppress results triaged as false positive or accepted risk, unless
+    /// they already carry a suppression. Returns the number suppressed.
+    pub fn apply(&self, results: &mut [SarifResult]) -> usize {
+        let mut suppressed = 0;
+        for result in results.iter_mut() {
+            let Some(entry) = self.get(result).filter(|e| e.decision.suppresses()) else {
+                continue;
+            };
+            if result.suppressions.as_ref().is_some_and(|s| !s.is_empty()) {
+                continue;
+            }
+            let justification = match &entry.note {
+                Some(note) => format!("{}: {}", entry.decision.label(), note),
+                None => entry.decision.label().to_string(),
+            };
+            result.suppressions = Some(vec![SarifSuppression {
+                kind: "external".to_string(),
+                status: Some("accepted".to_string()),
+                justification: Some(justification),
+            }]);
+            suppressed += 1;
+        }
+        suppressed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sarif::SarifReport;
+
+    fn results() -> Vec<SarifResult> {
+        SarifReport::from_json(
+            r#"{"$schema":"","version":"2.1.0","runs":[{"tool":{"driver":{"name":"p","version":"0"}},"results":[
+                {"ruleId":"SQLI","level":"error","message":{"text":"m"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"a.py"}}}]},
+                {"ruleId":"XSS","level":"warning","message":{"text":"m"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"b.js"}}}]},
+                {"ruleId":"SSRF","level":"warning","message":{"text":"m"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"c.go"}}}]}
+            ]}]}"#,
+        )
+        .unwrap()
+        .runs
+        .remove(0)
+        .results
+    }
+
+    #[test]
+    fn test_apply_suppresses_false_positives_and_accepted_risks() {
+        let mut results = results();
+        let mut triage = Triage::default();
+        triage.decide(&results[0], Decision::TruePositive, None);
+        triage.decide(&results[1], Decision::AcceptedRisk, None);
+        triage.decide(&results[1], Decision::FalsePositive, Some("escaped".into()));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(TRIAGE_FILE);
+        triage.save(&path).unwrap();
+        let triage = Triage::load(&path).unwrap();
+        assert_eq!(triage.entries.len(), 2);
+
+        assert_eq!(triage.apply(&mut results), 1);
+        assert!(results[0].suppressions.is_none());
+        let suppression = &results[1].suppressions.as_ref().unwrap()[0];
+        assert_eq!(suppression.status.as_deref(), Some("accepted"));
+        assert_eq!(
+            suppression.justification.as_deref(),
+            Some("false positive: escaped")
+        );
+        assert!(results[2].suppressions.is_none());
+    }
+
+    #[test]
+    fn test_load_missing_file_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let triage = Triage::load(&dir.path().join(TRIAGE_FILE)).unwrap();
+        assert!(triage.entries.is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+//! Second-pass verification of high-confidence findings.
+//!
+//! `parsentry verify` writes one prompt per finding at or above a confidence
+//! threshold to `<surface>/verify/<fingerprint>/prompt.md`. A verification
+//! worker re-examines the finding and writes a [`Verdict`] next to it;
+//! `merge` then replaces the finding's confidence with the verified one and
+//! keeps the original in `properties.initial_confidence`.
+//!
+//! Verdicts are tied to the surface's `.cache_key`, so a re-scan of changed
+//! sources discards them.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::merge::fingerprint;
+use crate::output::write_atomic;
+use crate::sarif::SarifResult;
+
+/// Per-surface directory holding verification prompts and verdicts.
+pub const VERIFY_DIR: &str = "verify";
+/// File a verification worker writes its verdict to.
+pub const VERDICT_FILE: &str = "verdict.json";
+
+/// Outcome of re-examining one finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerdictKind {
+    Confirmed,
+    Rejected,
+    Uncertain,
+}
+
+impl VerdictKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VerdictKind::Confirmed => "confirmed",
+            VerdictKind::Rejected => "rejected",
+            VerdictKind::Uncertain => "uncertain",
+        }
+    }
+}
+
+/// Verdict written by a verification worker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Verdict {
+    pub verdict: VerdictKind,
+    /// Verified confidence, 0.0-1.0.
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rationale: Option<String>,
+}
+
+/// Directory of the verification prompt and verdict for `result`.
+pub fn verification_dir(surface_dir: &Path, result: &SarifResult) -> PathBuf {
+    let fp = fingerprint(result);
+    let name: String = fp
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .take(16)
+        .collect();
+    surface_dir.join(VERIFY_DIR).join(name)
+}
+
+/// Whether `result` qualifies for verification at `threshold`.
+pub fn needs_verification(result: &SarifResult, threshold: f64) -> bool {
+    result
+        .properties
+        .as_ref()
+        .and_then(|p| p.confidence)
+        .is_some_and(|c| c >= threshold)
+}
+
+fn surface_cache_key(surface_dir: &Path) -> Option<String> {
+    std::fs::read_to_string(surface_dir.join(".cache_key"))
+        .ok()
+        .map(|k| k.trim().to_string())
+}
+
+/// Record that the prompt in `dir` belongs to the surface's current scan.
+pub fn stamp(dir: &Path, surface_dir: &Path) -> Result<()> {
+    let key = surface_cache_key(surface_dir).unwrap_or_default();
+    write_atomic(dir.join(".cache_key"), key)
+}
+
+/// The current verdict in `dir`, ignoring verdicts from an earlier scan.
+pub fn load_verdict(dir: &Path, surface_dir: &Path) -> Option<Verdict> {
+    let stamped = std::fs::read_to_string(dir.join(".cache_key")).ok()?;
+    if stamped.trim() != surface_cache_key(surface_dir).unwrap_or_default() {
+        return None;
+    }
+    let content = std::fs::read_to_string(dir.join(VERDICT_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Apply the verdicts under `surface_dir` to its results.
+pub fn apply_verdicts(surface_dir: &Path, results: &mut [SarifResult]) {
+    for result in results {
+        let Some(verdict) = load_verdict(&verification_dir(surface_dir, result), surface_dir)
+        else {
+            continue;
+        };
+        let props = result.properties.get_or_insert_with(Default::default);
+        if props.initial_confidence.is_none() {
+            props.initial_confidence = props.confidence;
+        }
+        props.confidence = Some(verdict.confidence.clamp(0.0, 1.0));
+        props.verification = Some(verdict.verdict.as_str().to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sarif::SarifReport;
+    use crate::test_support::{ResultFixture, result};
+
+    fn report() -> SarifReport {
+        crate::test_support::report([
+            result("SQLI", "a.py").confidence(0.9),
+            result("XSS", "b.js").level("warning").confidence(0.4),
+        ])
+    }
+
+    #[test]
+    fn test_apply_verdicts_records_initial_confidence() {
+        let surface = tempfile::tempdir().unwrap();
+        std::fs::write(surface.path().join(".cache_key"), "k1").unwrap();
+        let mut results = report().runs.remove(0).results;
+        assert!(needs_verification(&results[0], 0.8));
+        assert!(!needs_verification(&results[1], 0.8));
+
+        let dir = verification_dir(surface.path(), &results[0]);
+        std::fs::create_dir_all(&dir).unwrap();
+        stamp(&dir, surface.path()).unwrap();
+        std::fs::write(
+            dir.join(VERDICT_FILE),
+            r#"{"verdict":"rejected","confidence":0.2,"rationale":"input is parameterized"}"#,
+        )
+        .unwrap();
+
+        apply_verdicts(surface.path(), &mut results);
+        let props = results[0].properties.as_ref().unwrap();
+        assert_eq!(props.initial_confidence, Some(0.9));
+        assert_eq!(props.confidence, Some(0.2));
+        assert_eq!(props.verification.as_deref(), Some("rejected"));
+        assert!(
+            results[1]
+                .properties
+                .as_ref()
+                .unwrap()
+                .verification
+                .is_none()
+        );
+
+        // A re-scan changes the surface key and invalidates the verdict.
+        std::fs::write(surface.path().join(".cache_key"), "k2").unwrap();
+        let mut results = report().runs.remove(0).results;
+        apply_verdicts(surface.path(), &mut results);
+        assert!(
+            results[0]
+                .properties
+                .as_ref()
+                .unwrap()
+                .verification
+                .is_none()
+        );
+    }
+}
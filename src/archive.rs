@@ -0,0 +1,273 @@
+//! Source archives (`.zip`, `.tar`, `.tar.gz`, `.tgz`) as scan targets.
+//!
+//! Archives are unpacked with the system `tar`/`unzip`. Every entry name is
+//! checked before extraction, and the extracted tree is checked for symlinks
+//! leading outside of it, so a crafted archive cannot write or expose files
+//! beyond its workspace (zip-slip).
+
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::{Component, Path};
+use std::process::Command;
+
+use parsentry_core::offline::ensure_online;
+
+/// Maximum size of a downloaded archive in bytes (1 GiB).
+const MAX_ARCHIVE_SIZE: u64 = 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+}
+
+impl ArchiveKind {
+    /// Kind of archive named by a path or URL, judged by its extension.
+    fn of(target: &str) -> Option<Self> {
+        let path = target.split(['?', '#']).next().unwrap_or(target);
+        let lower = path.to_ascii_lowercase();
+        if lower.ends_with(".zip") {
+            Some(Self::Zip)
+        } else if [".tar", ".tar.gz", ".tgz"]
+            .iter()
+            .any(|ext| lower.ends_with(ext))
+        {
+            Some(Self::Tar)
+        } else {
+            None
+        }
+    }
+
+    /// File extension for a downloaded archive of this kind; `tar` detects
+    /// gzip compression by itself.
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Zip => "zip",
+            Self::Tar => "tar",
+        }
+    }
+}
+
+/// Whether `target` names a source archive, locally or by URL.
+pub fn is_archive(target: &str) -> bool {
+    ArchiveKind::of(target).is_some()
+}
+
+/// File name of an archive target without its archive extension.
+pub fn archive_stem(target: &str) -> String {
+    let path = target.split(['?', '#']).next().unwrap_or(target);
+    let name = path
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(path);
+    let lower = name.to_ascii_lowercase();
+    [".tar.gz", ".tgz", ".tar", ".zip"]
+        .iter()
+        .find(|ext| lower.ends_with(*ext))
+        .map_or(name, |ext| &name[..name.len() - ext.len()])
+        .to_string()
+}
+
+/// Whether an archive entry name stays inside the extraction directory.
+fn is_safe_entry(name: &str) -> bool {
+    let name = name.trim_end_matches('/');
+    if name.is_empty() {
+        return true;
+    }
+    if name.contains('\\') || name.contains(':') {
+        return false;
+    }
+    Path::new(name)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+fn run(program: &str, args: &[&str], archive: &Path, dest: Option<&Path>) -> Result<String> {
+    let mut command = Command::new(program);
+    command.args(args).arg(archive);
+    if let Some(dest) = dest {
+        command
+            .arg(if program == "unzip" { "-d" } else { "-C" })
+            .arg(dest);
+    }
+    let output = command
+        .output()
+        .with_context(|| format!("cannot run {program} to unpack {}", archive.display()))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{program} failed on {}: {}",
+            archive.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Extract `archive` into the new directory `dest`.
+///
+/// Fails without extracting anything if an entry is absolute or climbs out
+/// with `..`, and removes `dest` again if the extracted tree contains a
+/// symlink that resolves outside of it.
+pub fn extract_archive(archive: &Path, dest: &Path) -> Result<()> {
+    let kind = ArchiveKind::of(&archive.to_string_lossy())
+        .with_context(|| format!("not a .zip or .tar(.gz) archive: {}", archive.display()))?;
+    if dest.exists() {
+        anyhow::bail!("Destination directory already exists");
+    }
+
+    let (program, list, extract): (_, &[&str], &[&str]) = match kind {
+        ArchiveKind::Zip => ("unzip", &["-Z1"], &["-q"]),
+        ArchiveKind::Tar => ("tar", &["-tf"], &["-xf"]),
+    };
+    let entries = run(program, list, archive, None)?;
+    if let Some(entry) = entries.lines().find(|e| !is_safe_entry(e)) {
+        anyhow::bail!(
+            "refusing to extract {}: entry {entry:?} escapes the extraction directory",
+            archive.display()
+        );
+    }
+
+    std::fs::create_dir_all(dest)?;
+    let result = run(program, extract, archive, Some(dest)).and_then(|_| check_symlinks(dest));
+    if result.is_err() {
+        let _ = std::fs::remove_dir_all(dest);
+    }
+    result
+}
+
+/// Reject symlinks under `root` that resolve outside of it.
+fn check_symlinks(root: &Path) -> Result<()> {
+    let root = root.canonicalize()?;
+    let mut pending = vec![root.clone()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_symlink() {
+                let inside = entry
+                    .path()
+                    .canonicalize()
+                    .is_ok_and(|target| target.starts_with(&root));
+                if !inside {
+                    anyhow::bail!(
+                        "archive symlink {} points outside the extraction directory",
+                        entry
+                            .path()
+                            .strip_prefix(&root)
+                            .unwrap_or(&entry.path())
+                            .display()
+                    );
+                }
+            } else if file_type.is_dir() {
+                pending.push(entry.path());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Download the archive at `url` into `dir`, returning the file's path.
+pub async fn download_archive(url: &str, dir: &Path) -> Result<std::path::PathBuf> {
+    let kind = ArchiveKind::of(url).with_context(|| format!("not an archive URL: {url}"))?;
+    ensure_online(&format!("download archive {url}"))?;
+    let mut response = crate::code_host::http_client()?
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("cannot download {url}"))?;
+    if !response.status().is_success() {
+        anyhow::bail!("HTTP {} downloading {}", response.status(), url);
+    }
+
+    let path = dir.join(format!("source.{}", kind.extension()));
+    let mut file = std::fs::File::create(&path)?;
+    let mut size = 0u64;
+    while let Some(chunk) = response.chunk().await? {
+        size += chunk.len() as u64;
+        if size > MAX_ARCHIVE_SIZE {
+            anyhow::bail!(
+                "{url} exceeds the {} MiB archive limit",
+                MAX_ARCHIVE_SIZE >> 20
+            );
+        }
+        file.write_all(&chunk)?;
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn recognizes_archive_targets() {
+        assert!(is_archive("drop/app.zip"));
+        assert!(is_archive("app.tar.gz"));
+        assert!(is_archive("https://vendor.example.com/src.TGZ?token=x"));
+        assert!(!is_archive("owner/repo"));
+        assert!(!is_archive("https://example.com/app"));
+        assert_eq!(archive_stem("drops/app-1.2.tar.gz"), "app-1.2");
+        assert_eq!(archive_stem("https://x.example.com/src.ZIP?sig=1"), "src");
+    }
+
+    #[test]
+    fn rejects_escaping_entries() {
+        assert!(is_safe_entry("src/app.py"));
+        assert!(is_safe_entry("./src/"));
+        assert!(!is_safe_entry("../etc/passwd"));
+        assert!(!is_safe_entry("src/../../x"));
+        assert!(!is_safe_entry("/etc/passwd"));
+        assert!(!is_safe_entry("..\\evil.dll"));
+        assert!(!is_safe_entry("C:/evil.dll"));
+    }
+
+    #[test]
+    fn extracts_tarball() {
+        let tmp = TempDir::new().unwrap();
+        let src = tmp.path().join("src");
+        std::fs::create_dir_all(src.join("app")).unwrap();
+        std::fs::write(src.join("app/main.py"), "print(1)").unwrap();
+        let archive = tmp.path().join("drop.tar.gz");
+        let status = Command::new("tar")
+            .arg("-czf")
+            .arg(&archive)
+            .arg("-C")
+            .arg(&src)
+            .arg("app")
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let dest = tmp.path().join("out");
+        extract_archive(&archive, &dest).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dest.join("app/main.py")).unwrap(),
+            "print(1)"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_out_of_the_archive() {
+        let tmp = TempDir::new().unwrap();
+        let src = tmp.path().join("src");
+        std::fs::create_dir_all(&src).unwrap();
+        std::os::unix::fs::symlink("/etc", src.join("escape")).unwrap();
+        let archive = tmp.path().join("drop.tar");
+        let status = Command::new("tar")
+            .arg("-cf")
+            .arg(&archive)
+            .arg("-C")
+            .arg(&src)
+            .arg("escape")
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let dest = tmp.path().join("out");
+        assert!(extract_archive(&archive, &dest).is_err());
+        assert!(!dest.exists());
+    }
+}
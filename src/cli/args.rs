@@ -1,4 +1,12 @@
-use clap::{Parser, Subcommand};
+use clap::{Args as ClapArgs, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+use crate::i18n::ResponseLanguage;
+use crate::profile::ScanProfile;
+use crate::prompt::VERIFY_CONFIDENCE_THRESHOLD;
+use crate::residency::SourceUpload;
+use crate::telemetry::LogFormat;
+use parsentry_reports::filename::Layout;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -23,6 +31,18 @@ use clap::{Parser, Subcommand};
 pub struct Args {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Diagnostic log format on stderr (json also structures status lines)
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+
+    /// Use only cached data; fail instead of cloning, fetching or dispatching workers
+    #[arg(long, global = true)]
+    pub offline: bool,
+
+    /// Analyze minified bundles and generated code instead of skipping them
+    #[arg(long, global = true)]
+    pub include_generated: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -32,28 +52,271 @@ pub enum Commands {
         /// Target to analyze: local path, GitHub repo (owner/repo), URL, IP, or domain
         #[arg(default_value = ".")]
         target: String,
+
+        #[command(flatten)]
+        scope: ScopeArgs,
     },
-    /// Generate per-surface analysis prompts from a threat model
-    Scan {
-        /// Target to analyze: local path, GitHub repo (owner/repo), URL, IP, or domain
+    /// Render the threat model as a graph of surfaces and source files
+    #[command(args_conflicts_with_subcommands = true)]
+    Graph {
+        #[command(subcommand)]
+        action: Option<GraphCommand>,
+
+        /// Target whose threat model to render: local path, owner/repo, URL, IP, or domain
         #[arg(default_value = ".")]
         target: String,
 
+        /// Output format
+        #[arg(long, value_enum, default_value_t = GraphFormat::Json)]
+        format: GraphFormat,
+
+        /// Write the graph to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Generate per-surface analysis prompts from a threat model
+    Scan {
+        /// Targets to analyze: local paths, repos (owner/repo[@ref]), archives (.zip/.tar.gz), URLs, IPs, or domains [default: .]
+        targets: Vec<String>,
+
+        /// Additional target (repeatable); combined with the positional targets
+        #[arg(long = "target", value_name = "TARGET")]
+        extra_targets: Vec<String>,
+
+        #[command(flatten)]
+        sweep: SweepArgs,
+
         /// Git ref to diff against (only changed files)
         #[arg(long)]
         diff_base: Option<String>,
 
+        /// Only analyze lines introduced by the commits in RANGE (A..B), attributing findings to them
+        #[arg(long, value_name = "RANGE")]
+        commits: Option<String>,
+
         /// Filter by language (comma-separated)
         #[arg(long)]
         filter_lang: Option<String>,
+
+        /// Ask workers to write a .patch file for each high-confidence finding
+        #[arg(long)]
+        suggest_fixes: bool,
+
+        /// Scan profile (default: PARSENTRY_PROFILE, else standard)
+        #[arg(long, value_enum)]
+        profile: Option<ScanProfile>,
+
+        /// Worker model, then fallbacks (comma-separated) for workers that keep failing on it
+        #[arg(
+            long,
+            value_delimiter = ',',
+            value_name = "MODELS",
+            conflicts_with = "compare_models"
+        )]
+        model: Vec<String>,
+
+        /// Run every surface on each of these models (comma-separated) and compare them
+        #[arg(long, value_delimiter = ',', value_name = "MODELS")]
+        compare_models: Vec<String>,
+
+        /// Analyze every surface N times independently and keep findings a quorum agrees on
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        samples: u32,
+
+        /// Members that must report a finding to keep it [default: strict majority]
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+        quorum: Option<u32>,
+
+        /// Re-examine high-confidence findings in a second verification pass
+        #[arg(long)]
+        verify: bool,
+
+        /// Minimum confidence (0.0-1.0) of findings to verify
+        #[arg(long, requires = "verify", default_value_t = VERIFY_CONFIDENCE_THRESHOLD)]
+        verify_threshold: f64,
+
+        /// Model for verification workers (e.g. a stronger model than the first pass)
+        #[arg(long, requires = "verify")]
+        verify_model: Option<String>,
+
+        /// Only analyze surfaces where a resource pattern match is reachable from a principal match via the call graph
+        #[arg(long)]
+        reachability: bool,
+
+        /// Report executables and libraries committed to the repository as informational findings
+        #[arg(long)]
+        report_binaries: bool,
+
+        /// Archive each worker's prompt, raw output, token counts and timing under reports/transcripts/
+        #[arg(long)]
+        save_transcripts: bool,
+
+        /// Open report.md with a one-page executive summary: risk themes, hot spots and a prioritized remediation plan
+        #[arg(long)]
+        executive_summary: bool,
+
+        /// Restore results from a transcripts, reports or project cache directory instead of dispatching workers
+        #[arg(long, value_name = "DIR")]
+        replay: Option<PathBuf>,
+
+        /// Surfaces without a stored result: fail the scan or skip them
+        #[arg(long, value_enum, requires = "replay", default_value_t = ReplayMissing::Fail)]
+        replay_missing: ReplayMissing,
+
+        /// Targets to clone and prepare at the same time (worker concurrency is set by the profile)
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        jobs: u32,
+
+        /// Analyze every surface again instead of reusing results complete for the current commit
+        #[arg(long, conflicts_with = "replay")]
+        refresh: bool,
+
+        /// Workers may read the repository but write nothing except their own output files
+        #[arg(long)]
+        read_only: bool,
+
+        /// Workers may not run shell commands (this also disables sandboxed PoC runs)
+        #[arg(long)]
+        no_shell: bool,
+
+        /// Language workers write findings in (default: PARSENTRY_LANGUAGE, else en)
+        #[arg(long, value_enum, value_name = "LANG")]
+        language: Option<ResponseLanguage>,
+
+        /// Keep source code out of prompts: paths (workers read files locally) or hashed (workers read no files, reduced precision)
+        #[arg(
+            long,
+            value_enum,
+            value_name = "MODE",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "paths"
+        )]
+        no_source_upload: Option<SourceUpload>,
+
+        #[command(flatten)]
+        scope: ScopeArgs,
     },
-    /// Merge per-surface SARIF files into a single report
+    /// Search other repositories for variants of a known vulnerability
+    Variants {
+        /// Source file of the seed vulnerability (with --line or --text)
+        #[arg(long, required_unless_present = "sarif", conflicts_with = "sarif")]
+        file: Option<PathBuf>,
+
+        /// Line of --file the vulnerability is on
+        #[arg(long, required_unless_present_any = ["text", "sarif"], value_parser = clap::value_parser!(u32).range(1..))]
+        line: Option<u32>,
+
+        /// Text on the vulnerable line of --file
+        #[arg(long, requires = "file", conflicts_with = "line")]
+        text: Option<String>,
+
+        /// SARIF report whose finding seeds the search
+        #[arg(long)]
+        sarif: Option<PathBuf>,
+
+        /// Fingerprint (parsentry/v1) of the seed finding in --sarif [default: the first finding]
+        #[arg(long, requires = "sarif")]
+        fingerprint: Option<String>,
+
+        /// Repository the seed paths are relative to
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+
+        /// GitHub code search query to use instead of the derived one
+        #[arg(long)]
+        query: Option<String>,
+
+        /// Maximum number of candidate repositories to scan
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+
+        /// Minimum similarity (0.0-1.0) of occurrences to the code around the seed
+        #[arg(long, default_value_t = 0.0)]
+        min_similarity: f64,
+
+        /// Print the derived pattern and query as JSON without searching
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Validate (and optionally apply) patches suggested by `scan --suggest-fixes`
+    Fix {
+        /// Target whose suggested patches to check: local path or owner/repo
+        #[arg(default_value = ".")]
+        target: String,
+
+        /// Apply every patch that passes validation
+        #[arg(long)]
+        apply: bool,
+
+        /// Skip the confirmation prompt when applying
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Serve findings to editors as diagnostics over the Language Server Protocol (stdio)
+    Lsp,
+    /// Expose scanning, pattern matching and reports to AI agents over the Model Context Protocol (stdio)
+    Mcp,
+    /// Triage findings of a report in the terminal (true/false positive, accepted risk)
+    Review {
+        /// SARIF report to review, e.g. <cache_dir>/merged.sarif.json
+        report: PathBuf,
+
+        /// Repository root the report's file paths are relative to
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+
+        /// Triage file to write (default: triage.json next to the report)
+        #[arg(long)]
+        triage: Option<PathBuf>,
+    },
+    /// Archive worker transcripts (used by `scan --save-transcripts`)
+    #[command(hide = true)]
+    Transcripts {
+        /// Target to resolve report directory: local path, owner/repo, URL, IP, or domain
+        #[arg(default_value = ".")]
+        target: String,
+    },
+    /// Repair malformed worker SARIF and write repair prompts for the rest
     #[command(hide = true)]
+    Validate {
+        /// Target to resolve report directory: local path, owner/repo, URL, IP, or domain
+        #[arg(default_value = ".")]
+        target: String,
+    },
+    /// Write verification prompts for high-confidence findings (used by `scan --verify`)
+    #[command(hide = true)]
+    Verify {
+        /// Target to resolve report directory: local path, owner/repo, URL, IP, or domain
+        #[arg(default_value = ".")]
+        target: String,
+
+        /// Minimum confidence (0.0-1.0) of findings to verify
+        #[arg(long, default_value_t = VERIFY_CONFIDENCE_THRESHOLD)]
+        threshold: f64,
+
+        /// Apply `merge --quorum K` consensus before selecting findings
+        #[arg(long, value_name = "K", value_parser = clap::value_parser!(u32).range(1..))]
+        quorum: Option<u32>,
+
+        /// Model the verification workers must run on
+        #[arg(long)]
+        model: Option<String>,
+    },
+    /// Merge per-surface SARIF files, or SARIF files from sharded scans, into a single report
     Merge {
         /// Target to resolve report directory: local path, owner/repo, URL, IP, or domain
         #[arg(default_value = ".")]
         target: String,
 
+        /// Merge these SARIF files (e.g. from sharded CI jobs) instead of the target's results
+        #[arg(long, value_name = "FILE", num_args = 1.., conflicts_with_all = ["quorum", "gh_issue", "jira", "linear", "notion", "defectdojo"])]
+        sarif: Vec<PathBuf>,
+
+        /// Replace each surface's result with findings at least K of its members agree on
+        #[arg(long, value_name = "K", value_parser = clap::value_parser!(u32).range(1..))]
+        quorum: Option<u32>,
+
         /// Create GitHub issues for findings (owner/repo format)
         #[arg(long)]
         gh_issue: Option<String>,
@@ -70,6 +333,14 @@ pub enum Commands {
         #[arg(long)]
         notion: Option<String>,
 
+        /// Import findings into DefectDojo (engagement ID)
+        #[arg(long)]
+        defectdojo: Option<String>,
+
+        /// Post a scan summary to a Slack, Teams, or generic webhook URL (repeatable)
+        #[arg(long)]
+        notify: Vec<String>,
+
         /// Minimum severity level to report: error, warning, note (default: warning)
         #[arg(long, default_value = "warning")]
         min_level: String,
@@ -78,15 +349,183 @@ pub enum Commands {
         #[arg(long)]
         dry_run: bool,
     },
-    /// Generate PDF report from scan results
+    /// Post findings as review comments on a GitHub pull request
+    Comment {
+        /// Target to resolve report directory: local path or owner/repo
+        #[arg(default_value = ".")]
+        target: String,
+
+        /// Pull request number
+        #[arg(long)]
+        pr: u64,
+
+        /// Repository hosting the pull request (owner/repo, default: TARGET)
+        #[arg(long)]
+        repo: Option<String>,
+
+        /// Minimum severity level to comment on: error, warning, note (default: warning)
+        #[arg(long, default_value = "warning")]
+        min_level: String,
+
+        /// Show what would be posted without making changes
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Generate a report (PDF by default) from scan results
     Generate {
+        /// Targets to resolve report directories: local path, owner/repo, URL, IP, or domain.
+        /// Several targets are combined with --format sarif, html, markdown, json or junit
+        #[arg(default_value = ".")]
+        targets: Vec<String>,
+
+        /// Output path, or - for stdout (default: <cache_dir>/report.pdf, gl-sast-report.json,
+        /// asff.json, ocsf.json, report.sarif.json, report.html, report.md, summary.json or
+        /// junit.xml; combined reports default to the working directory)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Report format
+        #[arg(long, value_enum, default_value_t = ReportFormat::Pdf)]
+        format: ReportFormat,
+
+        /// Minimum severity level that fails a JUnit test case: error, warning, note (default: warning)
+        #[arg(long, default_value = "warning")]
+        min_level: String,
+
+        /// Post a scan summary to a Slack, Teams, or generic webhook URL (repeatable)
+        #[arg(long)]
+        notify: Vec<String>,
+
+        /// Also write one SARIF and Markdown report per CODEOWNERS owner to by-owner/ next to
+        /// the report
+        #[arg(long)]
+        split_by_owner: bool,
+
+        /// Also write one Markdown report per file and vulnerability type to findings/ next to the
+        /// report
+        #[arg(long)]
+        split_by_file: bool,
+
+        /// Names of --split-by-file reports, with {relpath}, {filename}, {pattern_slug} and
+        /// {rule_id} (default: {relpath}-{pattern_slug}.md, or {relpath}/{pattern_slug}.md
+        /// mirrored; env: PARSENTRY_FILENAME_TEMPLATE)
+        #[arg(long, value_name = "TEMPLATE")]
+        filename_template: Option<String>,
+
+        /// Layout of --split-by-file reports: flat, or mirrored for directories following the
+        /// source tree (env: PARSENTRY_REPORT_LAYOUT)
+        #[arg(long, value_name = "LAYOUT")]
+        layout: Option<Layout>,
+
+        /// Post a summary of only OWNER's findings to a webhook, as OWNER=URL (repeatable)
+        #[arg(long, value_name = "OWNER=URL")]
+        notify_owner: Vec<String>,
+
+        /// Sign every written report with a P-256 private key in PEM (e.g. from
+        /// `cosign generate-key-pair`) into <file>.sig and write an in-toto attestation
+        #[arg(long, value_name = "KEY")]
+        sign_key: Option<PathBuf>,
+
+        /// Sign every written report with an OIDC identity through `cosign` (Sigstore keyless)
+        #[arg(long, conflicts_with = "sign_key")]
+        sign_keyless: bool,
+
+        /// Policy file (YAML/JSON rules, or .rego); exits non-zero when the policy fails
+        #[arg(long)]
+        policy: Option<PathBuf>,
+    },
+    /// Check git, agent CLIs, credentials, configuration, cache and grammars before scanning
+    Doctor,
+    /// Run a proof-of-concept script without network under resource limits and print the outcome as JSON
+    Poc {
+        /// Script to run; it needs a shebang line and is copied into an empty directory first
+        script: PathBuf,
+
+        /// Seconds before the script is killed
+        #[arg(long, default_value_t = 60)]
+        timeout: u64,
+    },
+    /// Inspect and validate configuration (environment variables / .env)
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+    /// List, lint and try out PAR patterns (built-in and vuln-patterns.yml)
+    Patterns {
+        #[command(subcommand)]
+        action: PatternsCommand,
+    },
+    /// Compare two reports (SARIF or summary JSON) by fingerprint
+    Diff {
+        /// Baseline report
+        old: PathBuf,
+
+        /// Report to compare against the baseline
+        new: PathBuf,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = DiffFormat::Markdown)]
+        format: DiffFormat,
+
+        /// Exit with an error when the new report has findings not in the old one
+        #[arg(long)]
+        fail_on_new: bool,
+    },
+    /// Compare per-model results from `scan --compare-models`
+    Compare {
         /// Target to resolve report directory: local path, owner/repo, URL, IP, or domain
         #[arg(default_value = ".")]
         target: String,
 
-        /// Output PDF path (default: <reports_dir>/report.pdf)
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Markdown)]
+        format: OutputFormat,
+    },
+    /// Score scan results against a labeled dataset (precision/recall/F1)
+    Eval {
+        /// Dataset: local path or owner/repo with labels, or owasp-benchmark
+        #[arg(long)]
+        dataset: String,
+
+        /// Results to score: SARIF, summary JSON or reports dir, as [NAME=]PATH (repeatable)
+        #[arg(long = "results", value_name = "[NAME=]PATH")]
+        results: Vec<String>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Markdown)]
+        format: OutputFormat,
+
+        /// Write the report to a file instead of stdout
         #[arg(short, long)]
-        output: Option<String>,
+        output: Option<PathBuf>,
+
+        /// Fit per-model confidence calibration from the scored results and write it to FILE
+        #[arg(long, value_name = "FILE")]
+        calibration: Option<PathBuf>,
+    },
+    /// List recorded scan summaries for a target
+    History {
+        /// Target whose history to show: local path or owner/repo
+        #[arg(default_value = ".")]
+        target: String,
+
+        /// Number of most recent scans to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+
+        /// Print entries as JSON lines
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show finding count trend (regression/improvement) across scans
+    Trend {
+        /// Target whose history to show: local path or owner/repo
+        #[arg(default_value = ".")]
+        target: String,
+
+        /// Number of most recent scans to include
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
     },
     /// Monitor scan progress (docker compose logs compatible)
     #[command(alias = "logs")]
@@ -119,3 +558,211 @@ pub enum Commands {
         no_color: bool,
     },
 }
+
+/// Output format for `parsentry generate`.
+/// Which files of the target are considered during discovery.
+#[derive(ClapArgs, Debug, Clone, Default)]
+pub struct ScopeArgs {
+    /// Only consider paths matching this glob (repeatable, relative to the target root)
+    #[arg(long = "include", value_name = "GLOB")]
+    pub include: Vec<String>,
+
+    /// Skip paths matching this glob (repeatable), e.g. `vendor/**`
+    #[arg(long = "exclude", value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Do not honor .gitignore and .parsentryignore
+    #[arg(long)]
+    pub no_ignore: bool,
+
+    /// Only consider the files listed (one per line) in FILE, or stdin with `-`
+    #[arg(long, value_name = "FILE")]
+    pub files_from: Option<String>,
+
+    /// Clone remote targets with only the --include paths checked out
+    #[arg(long)]
+    pub sparse: bool,
+}
+
+/// GitHub repositories to add as scan targets.
+#[derive(ClapArgs, Debug, Clone, Default)]
+pub struct SweepArgs {
+    /// Scan the repositories of this GitHub organization
+    #[arg(long, value_name = "ORG")]
+    pub org: Option<String>,
+
+    /// Scan the repositories with this GitHub topic (within --org if given)
+    #[arg(long, value_name = "TOPIC")]
+    pub topic: Option<String>,
+
+    /// Include forks in --org/--topic sweeps
+    #[arg(long)]
+    pub include_forks: bool,
+
+    /// Include archived repositories in --org/--topic sweeps
+    #[arg(long)]
+    pub include_archived: bool,
+
+    /// Maximum number of repositories an --org/--topic sweep adds
+    #[arg(long, default_value_t = 1000)]
+    pub max_repos: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// report.md rendered to PDF via tools/pdf-report
+    Pdf,
+    /// GitLab SAST report (gl-sast-report.json)
+    Gitlab,
+    /// AWS Security Hub findings in ASFF (asff.json), for batch-import-findings
+    Asff,
+    /// OCSF Vulnerability Finding events (ocsf.json) for security data lakes
+    Ocsf,
+    /// SARIF with one run per target (report.sarif.json)
+    Sarif,
+    /// Self-contained HTML dashboard with a section per target (report.html)
+    Html,
+    /// One report.md with a table of contents, findings grouped by severity then file
+    Markdown,
+    /// Summary JSON (summary.json) of the unsuppressed findings per file, as read by `diff`
+    Json,
+    /// JUnit XML (junit.xml) with a failing test case per finding, for CI test report views
+    Junit,
+}
+
+/// Output format for `parsentry diff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DiffFormat {
+    Markdown,
+    Json,
+}
+
+/// What `scan --replay` does with surfaces that have no stored result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReplayMissing {
+    Fail,
+    Skip,
+}
+
+/// Output format for `parsentry graph`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    Json,
+    /// Graphviz DOT, e.g. `dot -Tsvg`
+    Dot,
+    /// Mermaid flowchart, e.g. for a fenced block in Markdown
+    Mermaid,
+}
+
+/// Output format for `parsentry eval` and `parsentry compare`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Markdown,
+    Json,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum GraphCommand {
+    /// Print the call paths from one function to another
+    Path {
+        /// Function the paths start at (e.g. a request handler)
+        #[arg(long)]
+        from: String,
+
+        /// Function the paths end at (e.g. a query executor)
+        #[arg(long)]
+        to: String,
+
+        /// Repository to build the call graph of: local path or owner/repo
+        #[arg(default_value = ".")]
+        target: String,
+
+        /// Maximum number of calls in a path
+        #[arg(long, default_value_t = 8)]
+        max_depth: usize,
+
+        /// Print the paths as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PatternsCommand {
+    /// List built-in and custom patterns per language
+    List {
+        /// Only list patterns for this language
+        #[arg(long)]
+        lang: Option<String>,
+
+        /// Directory containing vuln-patterns.yml
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+    },
+    /// Check that every pattern query compiles for its language
+    Lint {
+        /// Pattern file to check (default: built-ins and ROOT/vuln-patterns.yml)
+        file: Option<PathBuf>,
+
+        /// Repair or drop invalid queries in the custom pattern file
+        #[arg(long)]
+        fix: bool,
+
+        /// Directory containing vuln-patterns.yml
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+    },
+    /// Merge a pattern file into ROOT/vuln-patterns.yml (dedup by query)
+    Merge {
+        /// Pattern file to merge
+        file: PathBuf,
+
+        /// Directory containing vuln-patterns.yml
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+    },
+    /// Install a pattern pack into the user-level pack directory
+    Install {
+        /// Pack YAML path or URL, git repository (git+URL or *.git), or registry name
+        source: String,
+    },
+    /// Re-fetch installed packs from their source and install newer versions
+    Update {
+        /// Only report outdated packs (fails if any)
+        #[arg(long)]
+        check: bool,
+    },
+    /// Run patterns against a sample file and show the matches
+    Test {
+        /// Sample source file
+        sample: PathBuf,
+
+        /// Language of the sample (default: inferred from the extension)
+        #[arg(long)]
+        lang: Option<String>,
+
+        /// Only show patterns whose description contains this text
+        #[arg(long)]
+        pattern: Option<String>,
+
+        /// Directory containing vuln-patterns.yml
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Report unknown PARSENTRY_* variables and invalid policy files
+    Validate {
+        /// Policy file to validate
+        #[arg(long)]
+        policy: Option<PathBuf>,
+    },
+    /// Print configured settings and where each value comes from
+    Show {
+        /// Include settings left at their default value
+        #[arg(long)]
+        effective: bool,
+    },
+}
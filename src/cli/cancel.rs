@@ -0,0 +1,41 @@
+//! Cooperative Ctrl+C handling.
+//!
+//! Commands that can stop at a safe point call [`CancellationToken::observe`]
+//! and poll [`CancellationToken::is_cancelled`]; the first Ctrl+C then lets
+//! them finish in-flight work and keep what is done. Everything else, and a
+//! second Ctrl+C, exits immediately with status 130.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    cancelled: AtomicBool,
+    observed: AtomicBool,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare that the running command checks this token.
+    pub fn observe(&self) {
+        self.inner.observed.store(true, Ordering::Relaxed);
+    }
+
+    /// Request cancellation. Returns whether a command will stop gracefully.
+    pub fn cancel(&self) -> bool {
+        self.inner.cancelled.store(true, Ordering::Relaxed);
+        self.inner.observed.load(Ordering::Relaxed)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Relaxed)
+    }
+}
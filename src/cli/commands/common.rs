@@ -1,14 +1,22 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::collections::HashSet;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 
+use crate::archive::{archive_stem, download_archive, extract_archive, is_archive};
+use crate::cli::args::ScopeArgs;
 use crate::cli::ui::StatusPrinter;
-use crate::github::clone_repo;
+use crate::code_host::{CloneOptions, clone_hosted_repo, resolve_hosted_target};
+use crate::prompt::scope_section;
+
+use parsentry_reports::compare::MODELS_DIR;
+use parsentry_reports::output::OutputLock;
 
 use parsentry_core::{
-    RepoMetadata, THREAT_MODEL_SYSTEM_PROMPT, build_threat_model_prompt, threat_model_schema,
+    PathFilter, RepoMetadata, THREAT_MODEL_SYSTEM_PROMPT, build_threat_model_prompt,
+    threat_model_schema,
 };
+use parsentry_parser::{EntryPoint, detect_entry_points};
 
 /// Check if the target string is an HTTP(S) URL.
 pub fn is_url(target: &str) -> bool {
@@ -99,6 +107,20 @@ pub fn cache_dir_for(target: &str) -> PathBuf {
     }
 }
 
+/// Lock the cache directory of `target` against other parsentry
+/// processes writing to it, waiting for them to finish.
+pub fn lock_cache_dir(target: &str, printer: &StatusPrinter) -> Result<OutputLock> {
+    let dir = cache_dir_for(target);
+    if let Some(lock) = OutputLock::try_acquire(&dir)? {
+        return Ok(lock);
+    }
+    printer.status(
+        "Waiting",
+        &format!("another parsentry process is writing to {}", dir.display()),
+    );
+    OutputLock::acquire(&dir)
+}
+
 /// Extract short repository name from a target string.
 /// e.g. "HikaruEgashira/parsentry" → "parsentry", "/local/path/repo" → "repo"
 /// For network targets: `https://example.com/app` → "example.com", "192.168.1.1" → "192.168.1.1"
@@ -132,6 +154,20 @@ pub async fn locate_repository(
     target: &str,
     printer: &StatusPrinter,
 ) -> Result<(PathBuf, Option<String>)> {
+    locate_repository_with(target, printer, &CloneOptions::default()).await
+}
+
+/// [`locate_repository`] with remote targets cloned per `clone`, checking out
+/// the ref pinned in the target (`owner/repo@ref`) if any.
+pub async fn locate_repository_with(
+    target: &str,
+    printer: &StatusPrinter,
+    clone: &CloneOptions,
+) -> Result<(PathBuf, Option<String>)> {
+    if is_archive(target) {
+        return locate_archive(target, printer).await;
+    }
+
     if is_url(target) {
         return locate_url_assets(target, printer).await;
     }
@@ -141,10 +177,13 @@ pub async fn locate_repository(
         return locate_url_assets(&url, printer).await;
     }
 
-    if target.contains('/') && !Path::new(target).exists() {
+    if let Some(hosted) = resolve_hosted_target(target)
+        && !Path::new(target).exists()
+    {
         let project_cache = cache_dir_for(target);
-        let dest = project_cache.join("repo");
-        let repo_name = target
+        let dest = checkout_dir(&project_cache, &clone.sparse_paths);
+        let repo_name = hosted
+            .slug
             .split('/')
             .next_back()
             .unwrap_or("unknown-repo")
@@ -158,7 +197,30 @@ pub async fn locate_repository(
             }
             std::fs::create_dir_all(&project_cache)?;
             printer.status("Cloning", &format!("{} → {}", target, dest.display()));
-            clone_repo(target, &dest)?;
+            // Clone into a private staging directory and move it into place,
+            // so concurrent scans of the same target never see a partial
+            // checkout or remove each other's.
+            let staging = tempfile::Builder::new()
+                .prefix(".clone-")
+                .tempdir_in(&project_cache)?;
+            let checkout = staging.path().join("repo");
+            let options = CloneOptions {
+                reference: hosted.reference,
+                ..clone.clone()
+            };
+            let token = hosted.host.clone_token().await?;
+            clone_hosted_repo(
+                hosted.host.as_ref(),
+                &hosted.slug,
+                &checkout,
+                token,
+                &options,
+            )?;
+            if let Err(e) = std::fs::rename(&checkout, &dest)
+                && !dest.join(".git").exists()
+            {
+                return Err(e).with_context(|| format!("cannot move clone to {}", dest.display()));
+            }
         }
 
         Ok((dest, Some(repo_name)))
@@ -167,6 +229,89 @@ pub async fn locate_repository(
     }
 }
 
+/// Checkout directory of a remote target inside its cache: `repo` for a full
+/// checkout, and one directory per distinct set of sparse paths.
+fn checkout_dir(project_cache: &Path, sparse_paths: &[String]) -> PathBuf {
+    if sparse_paths.is_empty() {
+        return project_cache.join("repo");
+    }
+    use sha2::{Digest, Sha256};
+    let digest = format!("{:x}", Sha256::digest(sparse_paths.join("\n").as_bytes()));
+    project_cache.join(format!("repo-sparse-{}", &digest[..12]))
+}
+
+/// What [`locate_repository_with`] would fetch over the network for
+/// `target`, or `None` when the target is local or already cached.
+pub fn network_requirement(target: &str, clone: &CloneOptions) -> Option<String> {
+    let local = Path::new(target).exists();
+    let cache = cache_dir_for(target);
+    if is_archive(target) {
+        let extracted = is_url(target) && cache.join("source").is_dir();
+        return (is_url(target) && !extracted).then(|| format!("download {target}"));
+    }
+    if is_url(target) || (!local && (is_ip_address(target) || is_domain(target))) {
+        let assets = cache.join("assets");
+        let cached = std::fs::read_dir(&assets).is_ok_and(|mut d| d.next().is_some());
+        return (!cached).then(|| format!("fetch assets from {target}"));
+    }
+    if !local
+        && resolve_hosted_target(target).is_some()
+        && !checkout_dir(&cache, &clone.sparse_paths)
+            .join(".git")
+            .exists()
+    {
+        return Some(format!("clone {target}"));
+    }
+    None
+}
+
+/// Extract a local or downloaded source archive into the cache directory.
+///
+/// Local archives are extracted per content hash, so a new drop under the
+/// same name is never scanned from a stale extraction. The download and any
+/// partial extraction live in a staging directory removed afterwards.
+async fn locate_archive(
+    target: &str,
+    printer: &StatusPrinter,
+) -> Result<(PathBuf, Option<String>)> {
+    let project_cache = cache_dir_for(target);
+    let name = archive_stem(target);
+    let dest = if is_url(target) {
+        project_cache.join("source")
+    } else {
+        use sha2::{Digest, Sha256};
+        let bytes = std::fs::read(target).with_context(|| format!("cannot read {target}"))?;
+        let digest = format!("{:x}", Sha256::digest(&bytes));
+        project_cache.join(format!("source-{}", &digest[..12]))
+    };
+    if dest.is_dir() {
+        printer.status("Cached", &format!("{} → {}", target, dest.display()));
+        return Ok((dest, Some(name)));
+    }
+
+    std::fs::create_dir_all(&project_cache)?;
+    let staging = tempfile::Builder::new()
+        .prefix(".archive-")
+        .tempdir_in(&project_cache)?;
+    let archive = if is_url(target) {
+        printer.status("Fetching", &format!("archive {}", target));
+        std::fs::write(project_cache.join("source_url.txt"), target)?;
+        download_archive(target, staging.path()).await?
+    } else {
+        PathBuf::from(target)
+    };
+    printer.status("Extracting", &format!("{} → {}", target, dest.display()));
+    let extracted = staging.path().join("source");
+    extract_archive(&archive, &extracted)?;
+    if let Err(e) = std::fs::rename(&extracted, &dest)
+        && !dest.is_dir()
+    {
+        return Err(e).with_context(|| format!("cannot move extraction to {}", dest.display()));
+    }
+
+    Ok((dest, Some(name)))
+}
+
 /// Fetch frontend assets from a URL target into the cache directory.
 async fn locate_url_assets(
     target: &str,
@@ -237,6 +382,23 @@ pub fn get_diff_files(root_dir: &Path, diff_base: &str) -> Result<HashSet<PathBu
         .collect())
 }
 
+/// Worker directories under `reports_dir`: every directory with a
+/// `prompt.md`, including per-model and per-sample members.
+pub fn worker_dirs(reports_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    let surfaces = std::fs::read_dir(reports_dir)
+        .with_context(|| format!("cannot read directory: {}", reports_dir.display()))?;
+    for surface in surfaces.filter_map(|e| e.ok()).map(|e| e.path()) {
+        dirs.push(surface.clone());
+        if let Ok(members) = std::fs::read_dir(surface.join(MODELS_DIR)) {
+            dirs.extend(members.filter_map(|e| e.ok()).map(|e| e.path()));
+        }
+    }
+    dirs.retain(|d| d.join("prompt.md").exists());
+    dirs.sort();
+    Ok(dirs)
+}
+
 /// Write content to stdout with an explicit flush.
 ///
 /// When stdout is piped (not a TTY), Rust uses full block-buffering by default.
@@ -251,9 +413,99 @@ pub fn write_stdout(content: &str) -> Result<()> {
     Ok(())
 }
 
+/// Split a comma-separated list of globs from the environment.
+fn env_globs(name: &str) -> Vec<String> {
+    std::env::var(name)
+        .map(|v| {
+            v.split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Build the discovery filter from `--include/--exclude`, falling back to
+/// `PARSENTRY_INCLUDE` / `PARSENTRY_EXCLUDE` when a flag is not given.
+/// Ignore files are honored unless `--no-ignore` is set, and `--files-from`
+/// replaces directory discovery with an explicit list.
+pub fn path_filter(scope: &ScopeArgs) -> Result<PathFilter> {
+    let include = if scope.include.is_empty() {
+        env_globs("PARSENTRY_INCLUDE")
+    } else {
+        scope.include.clone()
+    };
+    let exclude = if scope.exclude.is_empty() {
+        env_globs("PARSENTRY_EXCLUDE")
+    } else {
+        scope.exclude.clone()
+    };
+    let filter = PathFilter::new(&include, &exclude)?.with_ignore_files(!scope.no_ignore);
+    match &scope.files_from {
+        Some(source) => Ok(filter.with_files(read_file_list(source)?)),
+        None => Ok(filter),
+    }
+}
+
+/// How remote targets are cloned for `scope`: `--sparse` checks out only the
+/// `--include` paths.
+pub fn clone_options(scope: &ScopeArgs) -> Result<CloneOptions> {
+    if !scope.sparse {
+        return Ok(CloneOptions::default());
+    }
+    let include = if scope.include.is_empty() {
+        env_globs("PARSENTRY_INCLUDE")
+    } else {
+        scope.include.clone()
+    };
+    if include.is_empty() {
+        anyhow::bail!("--sparse needs --include paths to check out");
+    }
+    Ok(CloneOptions {
+        reference: None,
+        sparse_paths: include,
+    })
+}
+
+/// Read a newline-separated file list from a path, or stdin for `-`.
+fn read_file_list(source: &str) -> Result<Vec<String>> {
+    let content = if source == "-" {
+        io::read_to_string(io::stdin())?
+    } else {
+        std::fs::read_to_string(source)
+            .map_err(|e| anyhow::anyhow!("cannot read file list {}: {}", source, e))?
+    };
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Detect the route handlers, CLI commands and message consumers in
+/// `files` and record them as entry points of `metadata`.
+pub(crate) fn add_framework_entry_points(
+    metadata: &mut RepoMetadata,
+    files: &[PathBuf],
+) -> Vec<EntryPoint> {
+    let entry_points = tracing::info_span!("entry_points")
+        .in_scope(|| detect_entry_points(&metadata.root_dir, files));
+    for entry_point in &entry_points {
+        metadata.add_framework_entry_point(&entry_point.file, entry_point.describe());
+    }
+    entry_points
+}
+
 /// Build threat model prompt for Claude Code CLI.
-pub fn build_threat_model_cli_prompt(metadata: &RepoMetadata, output: &Path) -> String {
-    let repo_context = metadata.to_prompt_context();
+pub fn build_threat_model_cli_prompt(
+    metadata: &RepoMetadata,
+    output: &Path,
+    filter: &PathFilter,
+) -> String {
+    let mut repo_context = metadata.to_prompt_context();
+    repo_context.push_str(&scope_section(filter));
     let languages: Vec<String> = metadata
         .languages
         .keys()
@@ -0,0 +1,17 @@
+use anyhow::Result;
+
+use super::common::write_stdout;
+use super::generate::resolve_reports_dir;
+use crate::cli::args::OutputFormat;
+use parsentry_reports::compare::{ModelComparison, load_model_runs};
+
+/// Print agreement, unique findings, confidence deltas, cost and latency of
+/// the models used in `scan --compare-models`.
+pub fn run_compare_command(target: &str, format: OutputFormat) -> Result<()> {
+    let runs = load_model_runs(&resolve_reports_dir(target))?;
+    let comparison = ModelComparison::compute(&runs);
+    match format {
+        OutputFormat::Markdown => write_stdout(&comparison.to_markdown()),
+        OutputFormat::Json => write_stdout(&format!("{}\n", comparison.to_json()?)),
+    }
+}
@@ -0,0 +1,72 @@
+use anyhow::{Result, bail};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::common::write_stdout;
+use crate::cli::ui::StatusPrinter;
+use crate::config::{self, SETTINGS, Source};
+use parsentry_reports::policy::PolicyEngine;
+
+/// Entries from `.env` in the current directory (or a parent), if any.
+fn dotenv_entries() -> HashMap<String, String> {
+    dotenvy::dotenv_iter()
+        .map(|iter| iter.filter_map(|item| item.ok()).collect())
+        .unwrap_or_default()
+}
+
+/// Check environment variables (and optionally a policy file) for mistakes.
+pub fn run_config_validate_command(policy: Option<&Path>) -> Result<()> {
+    let printer = StatusPrinter::new();
+    let env: Vec<String> = std::env::vars().map(|(k, _)| k).collect();
+    let unknown = config::unknown_variables(env.iter().map(String::as_str));
+
+    for name in &unknown {
+        let hint = config::suggest(name)
+            .map(|s| format!(" (did you mean `{s}`?)"))
+            .unwrap_or_default();
+        printer.error("Unknown", &format!("{name}{hint}"));
+    }
+
+    let mut errors = unknown.len();
+    if let Some(path) = policy {
+        match PolicyEngine::from_file(path) {
+            Ok(_) => printer.success("Valid", &format!("policy {}", path.display())),
+            Err(e) => {
+                printer.error("Invalid", &format!("{e:#}"));
+                errors += 1;
+            }
+        }
+    }
+
+    if errors > 0 {
+        bail!("configuration has {errors} problem(s)");
+    }
+    printer.success("Valid", "configuration");
+    Ok(())
+}
+
+/// Print configuration settings with the source of each value.
+pub fn run_config_show_command(effective: bool) -> Result<()> {
+    let env: HashMap<String, String> = std::env::vars().collect();
+    let dotenv = dotenv_entries();
+
+    let mut out = String::new();
+    for setting in SETTINGS {
+        let Some((value, source)) = config::resolve(setting, &env, &dotenv) else {
+            continue;
+        };
+        if !effective && source == Source::Default {
+            continue;
+        }
+        let value = if setting.secret {
+            config::mask(&value)
+        } else {
+            value
+        };
+        out.push_str(&format!(
+            "{}={}  # {} ({})\n",
+            setting.name, value, source, setting.description
+        ));
+    }
+    write_stdout(&out)
+}
@@ -0,0 +1,26 @@
+use anyhow::{Result, bail};
+use std::path::Path;
+
+use super::common::write_stdout;
+use crate::cli::args::DiffFormat;
+use parsentry_reports::diff::{ReportDiff, load_report};
+
+/// Print new/fixed/unchanged findings between two reports.
+pub fn run_diff_command(
+    old: &Path,
+    new: &Path,
+    format: DiffFormat,
+    fail_on_new: bool,
+) -> Result<()> {
+    let diff = ReportDiff::compute(&load_report(old)?, &load_report(new)?);
+
+    match format {
+        DiffFormat::Markdown => write_stdout(&diff.to_markdown())?,
+        DiffFormat::Json => write_stdout(&format!("{}\n", diff.to_json()?))?,
+    }
+
+    if fail_on_new && !diff.new.is_empty() {
+        bail!("{} new finding(s)", diff.new.len());
+    }
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use anyhow::{Result, bail};
+use std::path::{Path, PathBuf};
+
+use super::common::{locate_repository, write_stdout};
+use super::generate::resolve_reports_dir;
+use crate::cli::args::OutputFormat;
+use crate::cli::ui::StatusPrinter;
+use parsentry_reports::SarifReport;
+use parsentry_reports::calibration::{self, Calibration};
+use parsentry_reports::diff::load_report;
+use parsentry_reports::eval::{Dataset, EvalReport, score};
+use parsentry_reports::merge_sarif_dir;
+use parsentry_reports::output::write_atomic;
+
+/// Well-known datasets and the repositories they are cloned from.
+const KNOWN_DATASETS: &[(&str, &str)] = &[("owasp-benchmark", "OWASP-Benchmark/BenchmarkJava")];
+
+fn dataset_target(dataset: &str) -> &str {
+    KNOWN_DATASETS
+        .iter()
+        .find(|(name, _)| *name == dataset)
+        .map_or(dataset, |(_, repo)| repo)
+}
+
+/// Split `NAME=PATH`; a bare path is named after its file stem.
+fn parse_run(spec: &str) -> (String, PathBuf) {
+    if let Some((name, path)) = spec.split_once('=')
+        && !name.contains('/')
+    {
+        return (name.to_string(), PathBuf::from(path));
+    }
+    let path = PathBuf::from(spec);
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.trim_end_matches(".json").trim_end_matches(".sarif"))
+        .unwrap_or(spec)
+        .to_string();
+    (name, path)
+}
+
+fn load_run(path: &Path) -> Result<SarifReport> {
+    if path.is_dir() {
+        merge_sarif_dir(path, None)
+    } else {
+        load_report(path)
+    }
+}
+
+/// Score one or more scan results against a labeled dataset and print a
+/// precision/recall/F1 comparison.
+///
+/// Each `--results` entry is a SARIF file, summary JSON or reports directory,
+/// optionally named (`sonnet=merged.sarif.json`) to compare models or prompt
+/// revisions. Without `--results`, the dataset's own reports directory is used.
+pub async fn run_eval_command(
+    dataset: &str,
+    results: &[String],
+    format: OutputFormat,
+    output: Option<&Path>,
+    calibration: Option<&Path>,
+) -> Result<()> {
+    let printer = StatusPrinter::new();
+    let target = dataset_target(dataset);
+    let (root, _) = locate_repository(target, &printer).await?;
+    let labels = Dataset::load(dataset, &root)?;
+    printer.status(
+        "Dataset",
+        &format!(
+            "{} ({} labels, {} vulnerable)",
+            dataset,
+            labels.labels.len(),
+            labels.positives()
+        ),
+    );
+
+    let runs: Vec<(String, PathBuf)> = if results.is_empty() {
+        let reports = resolve_reports_dir(target);
+        if !reports.is_dir() {
+            bail!(
+                "no results for {dataset}: run `parsentry model {target}` and `parsentry scan {target}` first, or pass --results"
+            );
+        }
+        vec![("parsentry".to_string(), reports)]
+    } else {
+        results.iter().map(|r| parse_run(r)).collect()
+    };
+
+    let mut scores = Vec::new();
+    let mut samples = Vec::new();
+    for (name, path) in runs {
+        let report = load_run(&path)?;
+        samples.extend(calibration::samples(&labels, &name, &report));
+        let run = score(&labels, &name, &report);
+        printer.status(
+            "Scored",
+            &format!(
+                "{name}: {} TP, {} FP, {} FN",
+                run.overall.tp, run.overall.fp, run.overall.fn_
+            ),
+        );
+        scores.push(run);
+    }
+
+    if let Some(path) = calibration {
+        let fitted = Calibration::fit(&samples);
+        if fitted.models.is_empty() {
+            bail!(
+                "{} labeled detections with a confidence; calibration needs at least {}",
+                samples.len(),
+                calibration::MIN_SAMPLES
+            );
+        }
+        fitted.save(path)?;
+        printer.success(
+            "Calibration",
+            &format!(
+                "{} curves from {} detections → {}",
+                fitted
+                    .models
+                    .values()
+                    .map(|types| types.len())
+                    .sum::<usize>(),
+                samples.len(),
+                path.display()
+            ),
+        );
+    }
+
+    let report = EvalReport::new(&labels, scores);
+    let rendered = match format {
+        OutputFormat::Markdown => report.to_markdown(),
+        OutputFormat::Json => format!("{}\n", report.to_json()?),
+    };
+    match output {
+        Some(path) => {
+            write_atomic(path, rendered)?;
+            printer.success("Report", &path.display().to_string());
+            Ok(())
+        }
+        None => write_stdout(&rendered),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_run() {
+        assert_eq!(
+            parse_run("opus=out/merged.sarif.json"),
+            ("opus".to_string(), PathBuf::from("out/merged.sarif.json"))
+        );
+        assert_eq!(parse_run("out/sonnet.sarif.json").0, "sonnet");
+        assert_eq!(
+            dataset_target("owasp-benchmark"),
+            "OWASP-Benchmark/BenchmarkJava"
+        );
+        assert_eq!(dataset_target("./corpus"), "./corpus");
+    }
+}
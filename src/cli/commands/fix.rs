@@ -0,0 +1,140 @@
+use anyhow::{Result, bail};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use crate::cli::ui::StatusPrinter;
+use crate::repo::RepoOps;
+
+use super::common::{cache_dir_for, locate_repository, repo_name_from_target};
+
+/// Collect `<surface>/fixes/*.patch` files written by `scan --suggest-fixes`.
+fn collect_patches(reports_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut patches = Vec::new();
+    if !reports_dir.is_dir() {
+        return Ok(patches);
+    }
+
+    for entry in std::fs::read_dir(reports_dir)?.filter_map(|e| e.ok()) {
+        let fixes_dir = entry.path().join("fixes");
+        if !fixes_dir.is_dir() {
+            continue;
+        }
+        for patch in std::fs::read_dir(&fixes_dir)?.filter_map(|e| e.ok()) {
+            let path = patch.path();
+            if path.extension().is_some_and(|ext| ext == "patch") && path.is_file() {
+                patches.push(path);
+            }
+        }
+    }
+
+    patches.sort();
+    Ok(patches)
+}
+
+/// Ask for confirmation on stderr/stdin. Returns false when stdin is not a TTY.
+fn confirm(question: &str) -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        return Ok(false);
+    }
+    eprint!("{} [y/N] ", question);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// Validate suggested patches against the target tree and optionally apply them.
+pub async fn run_fix_command(target: &str, apply: bool, yes: bool) -> Result<()> {
+    let printer = StatusPrinter::with_service(repo_name_from_target(target));
+
+    let (root_dir, _repo_name) = locate_repository(target, &printer).await?;
+    let reports_dir = cache_dir_for(target).join("reports");
+    let patches = collect_patches(&reports_dir)?;
+
+    if patches.is_empty() {
+        printer.warning(
+            "Fix",
+            &format!(
+                "no suggested patches in {}. Run `parsentry scan --suggest-fixes` first.",
+                reports_dir.display()
+            ),
+        );
+        return Ok(());
+    }
+
+    let repo = RepoOps::new(root_dir.clone());
+    let mut valid = Vec::new();
+    for patch in &patches {
+        let name = patch.strip_prefix(&reports_dir).unwrap_or(patch).display();
+        match repo.check_patch(patch) {
+            Ok(()) => {
+                printer.status("Valid", &name.to_string());
+                valid.push(patch);
+            }
+            Err(e) => printer.error("Invalid", &format!("{}: {}", name, e)),
+        }
+    }
+
+    printer.success(
+        "Checked",
+        &format!(
+            "{} of {} patches apply cleanly to {}",
+            valid.len(),
+            patches.len(),
+            root_dir.display()
+        ),
+    );
+
+    if !apply || valid.is_empty() {
+        return Ok(());
+    }
+
+    let question = format!("Apply {} patches to {}?", valid.len(), root_dir.display());
+    if !yes && !confirm(&question)? {
+        bail!("aborted: patches not applied (pass --yes to skip confirmation)");
+    }
+
+    let mut applied = 0usize;
+    for patch in valid {
+        let name = patch.strip_prefix(&reports_dir).unwrap_or(patch).display();
+        match repo.apply_patch(patch) {
+            Ok(()) => {
+                printer.status("Applied", &name.to_string());
+                applied += 1;
+            }
+            Err(e) => printer.error("Failed", &format!("{}: {}", name, e)),
+        }
+    }
+
+    printer.success("Complete", &format!("{} patches applied", applied));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn collects_patches_from_surface_fix_dirs() {
+        let tmp = TempDir::new().unwrap();
+        let fixes = tmp.path().join("SURFACE-001").join("fixes");
+        std::fs::create_dir_all(&fixes).unwrap();
+        std::fs::write(fixes.join("SQLI-1.patch"), "diff").unwrap();
+        std::fs::write(fixes.join("notes.txt"), "ignored").unwrap();
+        std::fs::create_dir_all(tmp.path().join("SURFACE-002")).unwrap();
+
+        let patches = collect_patches(tmp.path()).unwrap();
+        assert_eq!(patches, vec![fixes.join("SQLI-1.patch")]);
+    }
+
+    #[test]
+    fn missing_reports_dir_yields_no_patches() {
+        let tmp = TempDir::new().unwrap();
+        assert!(
+            collect_patches(&tmp.path().join("missing"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -1,14 +1,36 @@
 use anyhow::{Context, Result, bail};
+use std::collections::{BTreeMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use super::common::cache_dir_for;
+use super::common::{cache_dir_for, lock_cache_dir, write_stdout};
+use crate::cli::args::ReportFormat;
 use crate::cli::ui::StatusPrinter;
-use parsentry_reports::merge_sarif_dir;
+use crate::prompt::EXECUTIVE_SUMMARY_FILE;
+use crate::repo::RepoOps;
+use parsentry_core::offline::{ensure_online, require_network};
+use parsentry_reports::filename::FilenameTemplate;
+use parsentry_reports::output::{prune_stale, write_atomic};
+use parsentry_reports::{
+    AnalysisSummary, AsffAccount, AsffReport, GitLabSastReport, OcsfReport, SarifReport,
+    ScanNotification, UriBase, blame,
+    calibration::Calibration,
+    combine_reports,
+    feedback::{FALSE_POSITIVES_FILE, FalsePositives},
+    grouping::{CodeOwners, owner_file_stem, split_by_owner},
+    merge::merge_sarif_dir_with,
+    policy::{PolicyDecision, PolicyEngine},
+    report_template::ReportTemplate,
+    risk::{self, REACHABLE_FILE},
+    schema, send_notifications,
+    signing::{ReportSigner, statement},
+    to_html_dashboard, to_junit_xml,
+    triage::{TRIAGE_FILE, Triage},
+};
 
 /// Resolve the reports directory for a given target.
 /// Accepts: local directory path (containing *.sarif.json) or owner/repo cache key.
-fn resolve_reports_dir(target: &str) -> PathBuf {
+pub(crate) fn resolve_reports_dir(target: &str) -> PathBuf {
     let local = PathBuf::from(target);
     // If target is a local directory containing SARIF files, use it directly
     if local.is_dir() {
@@ -34,6 +56,17 @@ fn resolve_reports_dir(target: &str) -> PathBuf {
     cache_dir_for(target).join("reports")
 }
 
+/// Reports directory of the workspace at `root`, for servers that start in
+/// a project rather than taking a scan target.
+pub(crate) fn workspace_reports_dir(root: &Path) -> PathBuf {
+    let reports_dir = resolve_reports_dir(&root.to_string_lossy());
+    // `parsentry scan` without a target caches the working directory as `.`.
+    if !reports_dir.is_dir() && std::env::current_dir().is_ok_and(|cwd| cwd == root) {
+        return resolve_reports_dir(".");
+    }
+    reports_dir
+}
+
 /// Locate the pdf-report tool.
 fn pdf_tool_dir() -> Result<PathBuf> {
     // 1. PARSENTRY_PDF_TOOL env var (explicit override)
@@ -66,6 +99,9 @@ fn pdf_tool_dir() -> Result<PathBuf> {
     bail!("pdf-report tool not found. Set PARSENTRY_PDF_TOOL or run from the repository root.")
 }
 
+/// `--output` value that writes the report to stdout.
+const STDOUT: &str = "-";
+
 /// Ensure npm dependencies are installed.
 fn ensure_deps(tool_dir: &Path, printer: &StatusPrinter) -> Result<()> {
     let node_modules = tool_dir.join("node_modules");
@@ -73,11 +109,13 @@ fn ensure_deps(tool_dir: &Path, printer: &StatusPrinter) -> Result<()> {
         return Ok(());
     }
 
+    ensure_online("install pdf-report dependencies with npm")?;
     printer.status("Install", "pdf-report dependencies...");
     let status = Command::new("npm")
         .arg("install")
         .arg("--silent")
         .current_dir(tool_dir)
+        .stdout(std::io::stderr())
         .status()
         .context("failed to run `npm install` — is Node.js installed?")?;
 
@@ -87,10 +125,533 @@ fn ensure_deps(tool_dir: &Path, printer: &StatusPrinter) -> Result<()> {
     Ok(())
 }
 
-/// Merge SARIF + generate report.md + render PDF.
-pub async fn run_generate_command(target: &str, output: Option<&str>) -> Result<()> {
-    let printer = StatusPrinter::with_service(super::common::repo_name_from_target(target));
+/// Resolve a user-supplied output path, defaulting to `default_name` in the cache dir.
+fn resolve_output_path(target: &str, output: Option<&str>, default_name: &str) -> PathBuf {
+    match output {
+        Some(p) => std::fs::canonicalize(PathBuf::from(p).parent().unwrap_or(Path::new(".")))
+            .unwrap_or_else(|_| PathBuf::from("."))
+            .join(PathBuf::from(p).file_name().unwrap_or_default()),
+        None => cache_dir_for(target).join(default_name),
+    }
+}
+
+/// Where findings go per CODEOWNERS owner (`--split-by-owner`, `--notify-owner`).
+#[derive(Debug, Default)]
+pub struct OwnerRouting {
+    /// Write a report per owner to `by-owner/` next to the report.
+    pub split: bool,
+    /// Owner and webhook URL pairs.
+    pub webhooks: Vec<(String, String)>,
+}
+
+impl OwnerRouting {
+    /// Parse `OWNER=URL` webhook arguments.
+    pub fn new(split: bool, webhooks: &[String]) -> Result<Self> {
+        let webhooks = webhooks
+            .iter()
+            .map(|arg| match arg.split_once('=') {
+                Some((owner, url)) if !owner.is_empty() && !url.is_empty() => {
+                    Ok((owner.to_string(), url.to_string()))
+                }
+                _ => bail!("--notify-owner expects OWNER=URL, got `{arg}`"),
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { split, webhooks })
+    }
+}
+
+/// How `generate` signs what it writes.
+#[derive(Debug)]
+pub enum Signing {
+    /// ECDSA P-256 private key in PEM (`--sign-key`).
+    Key(PathBuf),
+    /// OIDC identity through `cosign` (`--sign-keyless`).
+    Keyless,
+}
+
+/// What `generate` does with the reports beyond writing them.
+#[derive(Debug, Default)]
+pub struct Delivery {
+    pub owners: OwnerRouting,
+    pub signing: Option<Signing>,
+    /// Names of the per-file reports in `findings/` (`--split-by-file`).
+    pub files: Option<FilenameTemplate>,
+}
+
+/// Merge SARIF, then either write a GitLab SAST, ASFF or OCSF report or generate
+/// report.md + render PDF.
+/// `sarif`, `html`, `markdown`, `json` and `junit` combine several targets into one report
+/// with a run (or dashboard section) per target.
+/// Posts a summary to each `notify` webhook on completion or failure, and
+/// each owner's findings to their webhook. Signs every written file when
+/// `delivery.signing` is set; a failed signature fails the run.
+#[tracing::instrument(name = "generate", skip_all, fields(?targets, ?format))]
+pub async fn run_generate_command(
+    targets: &[String],
+    output: Option<&str>,
+    format: ReportFormat,
+    min_level: &str,
+    notify: &[String],
+    delivery: &Delivery,
+    policy: Option<&Path>,
+) -> Result<()> {
+    let owners = &delivery.owners;
+    let combined = matches!(
+        format,
+        ReportFormat::Sarif
+            | ReportFormat::Html
+            | ReportFormat::Markdown
+            | ReportFormat::Json
+            | ReportFormat::Junit
+    );
+    if targets.len() > 1 && !combined {
+        bail!("only --format sarif, html, markdown, json and junit combine several targets");
+    }
+    if output == Some(STDOUT) {
+        if format == ReportFormat::Pdf {
+            bail!("--output - needs a text --format, e.g. json, sarif or markdown");
+        }
+        if delivery.signing.is_some() {
+            bail!("a report written to stdout cannot be signed; pass an --output path");
+        }
+    }
+    let webhooks = notify.len() + owners.webhooks.len();
+    let mut fetches = Vec::new();
+    if webhooks > 0 {
+        fetches.push(format!("{webhooks} webhook notification(s)"));
+    }
+    if matches!(delivery.signing, Some(Signing::Keyless)) {
+        fetches.push("keyless signing with Sigstore".to_string());
+    }
+    require_network(&fetches)?;
+    let label = targets.join(", ");
+    let printer = match targets {
+        [target] => StatusPrinter::with_service(super::common::repo_name_from_target(target)),
+        _ => StatusPrinter::new(),
+    };
+    // Per-directory overrides only apply to a single checkout.
+    let policy = match targets {
+        [target] => load_policy(target, policy, &printer)?,
+        _ => policy.map(PolicyEngine::from_file).transpose()?,
+    };
+    // Sorted, so generating overlapping targets cannot deadlock.
+    let mut cache_dirs: Vec<&String> = targets.iter().collect();
+    cache_dirs.sort_by_key(|t| cache_dir_for(t));
+    cache_dirs.dedup_by_key(|t| cache_dir_for(t));
+    let _locks = cache_dirs
+        .into_iter()
+        .map(|t| lock_cache_dir(t, &printer))
+        .collect::<Result<Vec<_>>>()?;
+    let mut result = match targets {
+        [target] if !combined => generate_report(target, output, format, policy.as_ref(), &printer),
+        _ => generate_combined_report(
+            targets,
+            output,
+            format,
+            min_level,
+            policy.as_ref(),
+            &printer,
+        ),
+    };
+
+    if let Ok(generated) = &result {
+        for (target, merged) in &generated.targets {
+            match super::history::record_scan(target, merged) {
+                Ok(Some(path)) => printer.dim(&format!("history → {}", path.display())),
+                Ok(None) => printer.dim("history unchanged since the last run"),
+                Err(e) => printer.warning("History", &format!("{e:#}")),
+            }
+        }
+    }
+
+    let owner_artifacts = match &result {
+        Ok(generated) if owners.split => {
+            write_owner_reports(&generated.merged, &generated.artifacts, &printer).unwrap_or_else(
+                |e| {
+                    printer.warning("Split", &format!("{e:#}"));
+                    BTreeMap::new()
+                },
+            )
+        }
+        _ => BTreeMap::new(),
+    };
 
+    let file_artifacts = match (&result, &delivery.files) {
+        (Ok(generated), Some(template)) => {
+            write_file_reports(&generated.merged, &generated.artifacts, template, &printer)
+                .unwrap_or_else(|e| {
+                    printer.warning("Split", &format!("{e:#}"));
+                    Vec::new()
+                })
+        }
+        _ => Vec::new(),
+    };
+
+    let signed = match (&result, &delivery.signing) {
+        (Ok(generated), Some(signing)) => {
+            // The final report comes last; split reports are in subdirectories.
+            let report = generated.artifacts.last().cloned().unwrap_or_default();
+            let artifacts: Vec<PathBuf> = generated
+                .artifacts
+                .iter()
+                .chain(owner_artifacts.values().flatten())
+                .chain(&file_artifacts)
+                .cloned()
+                .collect();
+            sign_reports(signing, &artifacts, &report, &generated.merged, &printer)
+        }
+        _ => Ok(()),
+    };
+    if let Err(e) = signed {
+        result = Err(e);
+    }
+
+    if !notify.is_empty() {
+        let notification = match &result {
+            Ok(generated) => ScanNotification::completed(
+                &label,
+                &generated.merged,
+                generated
+                    .artifacts
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect(),
+            ),
+            Err(e) => ScanNotification::failed(&label, &format!("{e:#}")),
+        };
+        match send_notifications(notify, &notification).await {
+            Ok(()) => printer.status("Notified", &format!("{} webhook(s)", notify.len())),
+            Err(e) => printer.warning("Notify", &format!("{e:#}")),
+        }
+    }
+
+    if let Ok(generated) = &result
+        && !owners.webhooks.is_empty()
+    {
+        notify_owners(&label, generated, owners, &owner_artifacts, &printer).await;
+    }
+
+    if let Some(decision) = result?.policy
+        && !decision.passed
+    {
+        bail!("policy failed: {}", decision.reasons().join("; "));
+    }
+    Ok(())
+}
+
+/// Write each owner's findings as `by-owner/<owner>.sarif.json` and
+/// `.md` next to the last artifact, returning the files per owner.
+fn write_owner_reports(
+    merged: &SarifReport,
+    artifacts: &[PathBuf],
+    printer: &StatusPrinter,
+) -> Result<BTreeMap<String, Vec<PathBuf>>> {
+    let dir = artifacts
+        .last()
+        .and_then(|p| p.parent())
+        .unwrap_or(Path::new("."))
+        .join("by-owner");
+    std::fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let mut written = BTreeMap::new();
+    let mut names = Vec::new();
+    for (owner, report) in split_by_owner(merged) {
+        let stem = owner_file_stem(&owner);
+        let sarif = PathBuf::from(format!("{stem}.sarif.json"));
+        let markdown = PathBuf::from(format!("{stem}.md"));
+        write_atomic(dir.join(&sarif), serde_json::to_string_pretty(&report)?)?;
+        write_atomic(dir.join(&markdown), report.to_single_markdown())?;
+        written.insert(owner, vec![dir.join(&sarif), dir.join(&markdown)]);
+        names.extend([sarif, markdown]);
+    }
+    // Owners who no longer have findings must not keep a stale report
+    prune_stale(&dir, &names)?;
+    printer.success(
+        "Split",
+        &format!("{} owner report(s) → {}", written.len(), dir.display()),
+    );
+    Ok(written)
+}
+
+/// Write a Markdown report per name of `template` to `findings/` next to
+/// the last artifact, returning the files written.
+fn write_file_reports(
+    merged: &SarifReport,
+    artifacts: &[PathBuf],
+    template: &FilenameTemplate,
+    printer: &StatusPrinter,
+) -> Result<Vec<PathBuf>> {
+    let dir = artifacts
+        .last()
+        .and_then(|p| p.parent())
+        .unwrap_or(Path::new("."))
+        .join("findings");
+    let mut written = Vec::new();
+    let mut names = Vec::new();
+    for (name, report) in template.split(merged) {
+        let path = dir.join(&name);
+        write_atomic(&path, report.to_single_markdown())?;
+        written.push(path);
+        names.push(name);
+    }
+    // Findings that were fixed must not keep a stale report
+    std::fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    prune_stale(&dir, &names)?;
+    printer.success(
+        "Split",
+        &format!("{} file report(s) → {}", written.len(), dir.display()),
+    );
+    Ok(written)
+}
+
+/// Sign `artifacts` and attest to them next to `report`.
+fn sign_reports(
+    signing: &Signing,
+    artifacts: &[PathBuf],
+    report: &Path,
+    merged: &SarifReport,
+    printer: &StatusPrinter,
+) -> Result<()> {
+    let dir = report
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    match signing {
+        Signing::Key(key) => {
+            let signer = ReportSigner::load(key)?;
+            let written = signer.sign_artifacts(artifacts, merged, dir)?;
+            printer.success(
+                "Signed",
+                &format!(
+                    "{} file(s) with key {} → {}",
+                    artifacts.len(),
+                    &signer.keyid()[..12],
+                    written.last().map_or(dir, |p| p.as_path()).display()
+                ),
+            );
+            printer.dim(
+                "verify with `cosign verify-blob --key <public key> --signature <file>.sig <file>`",
+            );
+        }
+        Signing::Keyless => {
+            for artifact in artifacts {
+                cosign(
+                    &["sign-blob", "--yes", "--bundle"],
+                    &bundle_path(artifact, "sigstore"),
+                    artifact,
+                )?;
+            }
+            // cosign attests to one blob: the report itself
+            let predicate = dir.join("parsentry.provenance.json");
+            let statement = statement(&[report.to_path_buf()], dir, merged)?;
+            write_atomic(
+                &predicate,
+                serde_json::to_string_pretty(&statement["predicate"])?,
+            )?;
+            cosign(
+                &[
+                    "attest-blob",
+                    "--yes",
+                    "--type",
+                    "slsaprovenance1",
+                    "--predicate",
+                    &predicate.to_string_lossy(),
+                    "--bundle",
+                ],
+                &bundle_path(report, "intoto.sigstore"),
+                report,
+            )?;
+            printer.success(
+                "Signed",
+                &format!(
+                    "{} file(s) with Sigstore bundles (<file>.sigstore.json)",
+                    artifacts.len()
+                ),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `<artifact>.<suffix>.json`.
+fn bundle_path(artifact: &Path, suffix: &str) -> PathBuf {
+    let mut name = artifact.as_os_str().to_owned();
+    name.push(format!(".{suffix}.json"));
+    PathBuf::from(name)
+}
+
+/// Run `cosign <args> <bundle> <blob>`.
+fn cosign(args: &[&str], bundle: &Path, blob: &Path) -> Result<()> {
+    let status = Command::new("cosign")
+        .args(args)
+        .arg(bundle)
+        .arg(blob)
+        .stdout(std::io::stderr())
+        .status()
+        .context("failed to run cosign — is it installed? (needed for --sign-keyless)")?;
+    if !status.success() {
+        bail!(
+            "cosign {} failed for {} ({status})",
+            args[0],
+            blob.display()
+        );
+    }
+    Ok(())
+}
+
+/// Post each owner's findings to their webhook; owners without findings
+/// are not notified.
+async fn notify_owners(
+    label: &str,
+    generated: &GeneratedReport,
+    owners: &OwnerRouting,
+    artifacts: &BTreeMap<String, Vec<PathBuf>>,
+    printer: &StatusPrinter,
+) {
+    let reports: BTreeMap<String, SarifReport> =
+        split_by_owner(&generated.merged).into_iter().collect();
+    for (owner, url) in &owners.webhooks {
+        let Some(report) = reports.get(owner) else {
+            printer.dim(&format!("no findings for {owner}, not notified"));
+            continue;
+        };
+        let links = artifacts
+            .get(owner)
+            .into_iter()
+            .flatten()
+            .map(|p| p.display().to_string())
+            .collect();
+        let notification =
+            ScanNotification::completed(&format!("{label} ({owner})"), report, links);
+        match send_notifications(std::slice::from_ref(url), &notification).await {
+            Ok(()) => printer.status(
+                "Notified",
+                &format!("{owner} ({} finding(s))", notification.total()),
+            ),
+            Err(e) => printer.warning("Notify", &format!("{owner}: {e:#}")),
+        }
+    }
+}
+
+/// Checkout scanned for `target`: the directory itself, or the clone in
+/// the target's cache.
+pub(crate) fn checkout_dir(target: &str) -> PathBuf {
+    let local = PathBuf::from(target);
+    if local.is_dir() {
+        local
+    } else {
+        cache_dir_for(target).join("repo")
+    }
+}
+
+/// The base artifact URIs of `checkout` are made relative to.
+pub(crate) fn uri_base(checkout: &Path) -> UriBase {
+    let base = UriBase::new(checkout);
+    match std::env::var("PARSENTRY_SARIF_URI_PREFIX") {
+        Ok(prefix) => base.with_prefix(&prefix),
+        Err(_) => base,
+    }
+}
+
+/// Make artifact URIs in `report` relative to `checkout`, prefixed with
+/// `PARSENTRY_SARIF_URI_PREFIX` when only part of a repository was scanned.
+pub(crate) fn relativize_uris(checkout: &Path, report: &mut SarifReport) {
+    let rewritten = uri_base(checkout).apply(report);
+    if rewritten > 0 {
+        tracing::debug!(
+            "made {rewritten} artifact URIs relative to {}",
+            checkout.display()
+        );
+    }
+}
+
+/// Keep findings triaged as false positives as exemplars for later scans.
+fn record_false_positives(
+    cache_dir: &Path,
+    merged: &SarifReport,
+    printer: &StatusPrinter,
+) -> Result<()> {
+    let triage = Triage::load(&cache_dir.join(TRIAGE_FILE))?;
+    let path = cache_dir.join(FALSE_POSITIVES_FILE);
+    let mut store = FalsePositives::load(&path)?;
+    let recorded = store.collect(merged, &triage);
+    if recorded > 0 {
+        store.save(&path)?;
+        printer.dim(&format!(
+            "{recorded} false positive(s) → {}",
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Calibrate confidences with the `PARSENTRY_CALIBRATION` file, if set.
+pub(crate) fn calibrate(report: &mut SarifReport) -> Result<()> {
+    let Some(path) = std::env::var_os("PARSENTRY_CALIBRATION").filter(|p| !p.is_empty()) else {
+        return Ok(());
+    };
+    let calibrated = Calibration::load(Path::new(&path))?.apply(report);
+    tracing::debug!("calibrated {calibrated} confidences");
+    Ok(())
+}
+
+/// Load the `--policy` file and merge per-directory `parsentry-policy.yml`
+/// overrides from the scanned checkout. Rego policies are used as-is.
+fn load_policy(
+    target: &str,
+    path: Option<&Path>,
+    printer: &StatusPrinter,
+) -> Result<Option<PolicyEngine>> {
+    let base = match path.map(PolicyEngine::from_file).transpose()? {
+        Some(PolicyEngine::Rules(policy)) => Some(policy),
+        other => return Ok(other),
+    };
+
+    let repo_root = checkout_dir(target);
+    if !repo_root.is_dir() {
+        return Ok(base.map(PolicyEngine::Rules));
+    }
+
+    let had_base = base.is_some();
+    let (policy, dirs) = base.unwrap_or_default().with_overrides(&repo_root)?;
+    for dir in &dirs {
+        printer.dim(&format!("policy override → {dir}/"));
+    }
+    Ok((had_base || !dirs.is_empty()).then_some(PolicyEngine::Rules(policy)))
+}
+
+/// Output of a successful `generate` run.
+struct GeneratedReport {
+    /// The report the policy and notifications see: the target's merged
+    /// SARIF, or all targets combined for `sarif` and `html`.
+    merged: SarifReport,
+    /// Merged SARIF of each target.
+    targets: Vec<(String, SarifReport)>,
+    /// Paths of the written artifacts (merged SARIF, final report, ...).
+    artifacts: Vec<PathBuf>,
+    policy: Option<PolicyDecision>,
+}
+
+/// Marks the start of the generated policy section in report.md so re-runs replace it.
+const POLICY_SECTION_MARKER: &str = "<!-- parsentry-policy -->";
+
+/// Replace (or append) the policy section at the end of `markdown`.
+fn with_policy_section(markdown: &str, decision: &PolicyDecision) -> String {
+    let base = markdown
+        .split(POLICY_SECTION_MARKER)
+        .next()
+        .unwrap_or(markdown)
+        .trim_end();
+    format!(
+        "{base}
+
+{POLICY_SECTION_MARKER}
+{}",
+        decision.to_markdown()
+    )
+}
+
+/// The target's reports directory, which `scan` must have created.
+fn existing_reports_dir(target: &str) -> Result<PathBuf> {
     let reports_dir = std::fs::canonicalize(resolve_reports_dir(target))
         .unwrap_or_else(|_| resolve_reports_dir(target));
     if !reports_dir.exists() {
@@ -99,15 +660,53 @@ pub async fn run_generate_command(target: &str, output: Option<&str>) -> Result<
             reports_dir.display()
         );
     }
+    Ok(reports_dir)
+}
 
-    // Phase 1: Merge SARIF
-    printer.status("Merge", "merging per-surface SARIF files...");
-    let merged = merge_sarif_dir(&reports_dir, None)?;
+/// Score every finding's risk, weighing in the files `scan` found reachable
+/// from entry points when `cache_dir` has them.
+pub(crate) fn assign_risk(cache_dir: &Path, report: &mut SarifReport) {
+    let reachable: Option<HashSet<String>> =
+        std::fs::read_to_string(cache_dir.join(REACHABLE_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok());
+    risk::assign(report, reachable.as_ref());
+}
+
+/// Merge the target's per-surface SARIF into `<cache_dir>/merged.sarif.json`.
+fn merge_target(
+    target: &str,
+    reports_dir: &Path,
+    printer: &StatusPrinter,
+) -> Result<(SarifReport, PathBuf)> {
+    printer.status(
+        "Merge",
+        &format!("merging per-surface SARIF files of {target}..."),
+    );
+    let base = uri_base(&checkout_dir(target));
+    let mut merged = tracing::info_span!("merge")
+        .in_scope(|| merge_sarif_dir_with(reports_dir, None, Some(&base)))?;
+    relativize_uris(&checkout_dir(target), &mut merged);
+    calibrate(&mut merged)?;
+    if let Some(owners) = CodeOwners::load(&checkout_dir(target)) {
+        owners.assign(&mut merged);
+    }
+    let repo = RepoOps::new(checkout_dir(target));
+    blame::assign(&mut merged, |path, lines| {
+        repo.blame(path, lines).unwrap_or_else(|e| {
+            tracing::debug!("no blame for {path}: {e}");
+            Default::default()
+        })
+    });
+    for repair in schema::enforce(&mut merged)? {
+        tracing::debug!("merged SARIF: {repair}");
+    }
     let cache_dir = cache_dir_for(target);
     std::fs::create_dir_all(&cache_dir).ok();
+    assign_risk(&cache_dir, &mut merged);
+    record_false_positives(&cache_dir, &merged, printer)?;
     let merged_path = cache_dir.join("merged.sarif.json");
-    std::fs::write(&merged_path, serde_json::to_string_pretty(&merged)?)
-        .context("failed to write merged.sarif.json")?;
+    write_atomic(&merged_path, serde_json::to_string_pretty(&merged)?)?;
     printer.success(
         "Merged",
         &format!(
@@ -116,6 +715,202 @@ pub async fn run_generate_command(target: &str, output: Option<&str>) -> Result<
             merged_path.display()
         ),
     );
+    for props in merged
+        .runs
+        .iter()
+        .filter_map(|r| r.invocation.as_ref()?.properties.as_ref())
+    {
+        if let Some(coverage) = &props.coverage {
+            printer.status(
+                "Coverage",
+                &format!(
+                    "{} discovered, {} matched, {} analyzed, {} skipped",
+                    coverage.discovered,
+                    coverage.matched,
+                    coverage.analyzed,
+                    coverage.skipped.len()
+                ),
+            );
+        }
+        if !props.timed_out.is_empty() {
+            printer.warning(
+                "Timeout",
+                &format!("not analyzed: {}", props.timed_out.join(", ")),
+            );
+        }
+        if !props.missing_results.is_empty() {
+            printer.warning(
+                "Partial",
+                &format!(
+                    "no result for {} surfaces (interrupted or failed): {}",
+                    props.missing_results.len(),
+                    props.missing_results.join(", ")
+                ),
+            );
+        }
+    }
+    Ok((merged, merged_path))
+}
+
+/// Write `rendered` to stdout for `--output -`, else to `path`. Returns
+/// where it went, for the status line.
+fn write_report(output: Option<&str>, path: &Path, rendered: &str) -> Result<String> {
+    if output == Some(STDOUT) {
+        write_stdout(rendered)?;
+        return Ok("stdout".to_string());
+    }
+    write_atomic(path, rendered)?;
+    Ok(path.display().to_string())
+}
+
+/// Evaluate the policy against `merged`, saving the decision to `policy_path`.
+fn evaluate_policy(
+    policy: Option<&PolicyEngine>,
+    merged: &SarifReport,
+    policy_path: &Path,
+    printer: &StatusPrinter,
+) -> Result<Option<PolicyDecision>> {
+    let decision = policy.map(|p| p.evaluate(merged)).transpose()?;
+    if let Some(decision) = &decision {
+        write_atomic(policy_path, serde_json::to_string_pretty(decision)?)?;
+        if decision.passed {
+            printer.success("Policy", &format!("pass ({} rule(s))", decision.evaluated));
+        } else {
+            printer.error("Policy", "fail");
+            for reason in decision.reasons() {
+                printer.bullet(&reason);
+            }
+        }
+    }
+    Ok(decision)
+}
+
+/// Combine the targets' merged SARIF into one report with a run per target,
+/// written as SARIF, an HTML dashboard or a single Markdown document.
+fn generate_combined_report(
+    targets: &[String],
+    output: Option<&str>,
+    format: ReportFormat,
+    min_level: &str,
+    policy: Option<&PolicyEngine>,
+    printer: &StatusPrinter,
+) -> Result<GeneratedReport> {
+    let mut merged_targets = Vec::new();
+    let mut artifacts = Vec::new();
+    for target in targets {
+        let (merged, merged_path) = merge_target(target, &existing_reports_dir(target)?, printer)?;
+        merged_targets.push((target.clone(), merged));
+        artifacts.push(merged_path);
+    }
+    let mut merged = combine_reports(merged_targets.clone());
+    schema::enforce(&mut merged)?;
+
+    let default_name = match format {
+        ReportFormat::Html => "report.html",
+        ReportFormat::Markdown => "report.md",
+        ReportFormat::Json => "summary.json",
+        ReportFormat::Junit => "junit.xml",
+        _ => "report.sarif.json",
+    };
+    // A single target keeps its artifacts in its cache directory; combined
+    // reports default to the working directory.
+    let output_path = match targets {
+        [target] => resolve_output_path(target, output, default_name),
+        _ => PathBuf::from(output.unwrap_or(default_name)),
+    };
+    let policy_path = match targets {
+        [target] => cache_dir_for(target).join("policy.json"),
+        _ => output_path.with_file_name("policy.json"),
+    };
+    let decision = evaluate_policy(policy, &merged, &policy_path, printer)?;
+
+    let rendered = match format {
+        ReportFormat::Html => {
+            let trends: Vec<_> = merged_targets
+                .iter()
+                .map(|(target, report)| {
+                    super::history::trend(target, report).unwrap_or_else(|e| {
+                        printer.warning("History", &format!("{e:#}"));
+                        Vec::new()
+                    })
+                })
+                .collect();
+            to_html_dashboard(&merged, &trends)
+        }
+        ReportFormat::Markdown => merged.to_single_markdown(),
+        ReportFormat::Json => {
+            serde_json::to_string_pretty(&AnalysisSummary::from_sarif(&merged))? + "\n"
+        }
+        ReportFormat::Junit => to_junit_xml(&merged, min_level),
+        _ => serde_json::to_string_pretty(&merged)?,
+    };
+    let destination = write_report(output, &output_path, &rendered)?;
+    printer.success(
+        "Saved",
+        &format!(
+            "{} results from {} target(s) → {destination}",
+            merged.runs.iter().map(|r| r.results.len()).sum::<usize>(),
+            targets.len(),
+        ),
+    );
+    if output != Some(STDOUT) {
+        artifacts.push(output_path);
+    }
+    Ok(GeneratedReport {
+        merged,
+        targets: merged_targets,
+        artifacts,
+        policy: decision,
+    })
+}
+
+fn generate_report(
+    target: &str,
+    output: Option<&str>,
+    format: ReportFormat,
+    policy: Option<&PolicyEngine>,
+    printer: &StatusPrinter,
+) -> Result<GeneratedReport> {
+    let reports_dir = existing_reports_dir(target)?;
+    let (merged, merged_path) = merge_target(target, &reports_dir, printer)?;
+    let cache_dir = cache_dir_for(target);
+    let decision = evaluate_policy(policy, &merged, &cache_dir.join("policy.json"), printer)?;
+    let targets = vec![(target.to_string(), merged.clone())];
+
+    let security_report = match format {
+        ReportFormat::Gitlab => {
+            let report = GitLabSastReport::from_sarif(&merged);
+            Some((
+                "gl-sast-report.json",
+                report.vulnerabilities.len(),
+                report.to_json()?,
+            ))
+        }
+        ReportFormat::Asff => {
+            let report = AsffReport::from_sarif(&merged, &AsffAccount::from_env()?);
+            Some(("asff.json", report.findings.len(), report.to_json()?))
+        }
+        ReportFormat::Ocsf => {
+            let report = OcsfReport::from_sarif(&merged);
+            Some(("ocsf.json", report.findings.len(), report.to_json()?))
+        }
+        _ => None,
+    };
+    if let Some((default_name, count, json)) = security_report {
+        let output_path = resolve_output_path(target, output, default_name);
+        let destination = write_report(output, &output_path, &json)?;
+        printer.success("Saved", &format!("{count} vulnerabilities → {destination}"));
+        let mut artifacts = vec![merged_path];
+        if output != Some(STDOUT) {
+            artifacts.push(output_path);
+        }
+        return Ok(GeneratedReport {
+            merged,
+            targets,
+            artifacts,
+            policy: decision,
+        });
+    }
 
     // Phase 2: Generate report.md
     // Check both source reports_dir and cache_dir; prefer existing one
@@ -128,32 +923,41 @@ pub async fn run_generate_command(target: &str, output: Option<&str>) -> Result<
         printer.status("Report", &format!("using existing {}", report_md.display()));
     } else {
         printer.status("Report", "report.md not found, generating from SARIF...");
-        let md = merged.to_markdown();
-        std::fs::write(&report_md, &md).context("failed to write report.md")?;
+        let mut md = match ReportTemplate::load()? {
+            Some(template) => template.render(&merged),
+            None => merged.to_markdown(),
+        };
+        // `scan --executive-summary` leaves the summary even if the
+        // orchestrator stopped before writing report.md
+        if let Ok(summary) = std::fs::read_to_string(reports_dir.join(EXECUTIVE_SUMMARY_FILE)) {
+            md = format!("{}\n\n{md}", summary.trim_end());
+        }
+        write_atomic(&report_md, &md)?;
         printer.success("Report", &format!("generated {}", report_md.display()));
     }
+    if let Some(decision) = &decision {
+        let md = std::fs::read_to_string(&report_md).context("failed to read report.md")?;
+        write_atomic(&report_md, with_policy_section(&md, decision))?;
+    }
 
     // Phase 3: Render PDF
     let tool_dir = pdf_tool_dir()?;
-    ensure_deps(&tool_dir, &printer)?;
+    ensure_deps(&tool_dir, printer)?;
 
-    let output_path = match output {
-        Some(p) => std::fs::canonicalize(PathBuf::from(p).parent().unwrap_or(Path::new(".")))
-            .unwrap_or_else(|_| PathBuf::from("."))
-            .join(PathBuf::from(p).file_name().unwrap_or_default()),
-        None => cache_dir_for(target).join("report.pdf"),
-    };
+    let output_path = resolve_output_path(target, output, "report.pdf");
     // Ensure parent directory exists
     if let Some(parent) = output_path.parent() {
         std::fs::create_dir_all(parent).ok();
     }
 
     printer.status("Render", "generating PDF report...");
+    let _render = tracing::info_span!("render").entered();
     let status = Command::new("npx")
         .args(["tsx", "src/index.tsx"])
         .arg(cache_dir.to_str().unwrap())
         .arg(output_path.to_str().unwrap())
         .current_dir(&tool_dir)
+        .stdout(std::io::stderr())
         .status()
         .context("failed to run pdf-report tool — is Node.js installed?")?;
 
@@ -162,5 +966,10 @@ pub async fn run_generate_command(target: &str, output: Option<&str>) -> Result<
     }
 
     printer.success("Saved", &format!("{}", output_path.display()));
-    Ok(())
+    Ok(GeneratedReport {
+        merged,
+        targets,
+        artifacts: vec![merged_path, output_path],
+        policy: decision,
+    })
 }
@@ -0,0 +1,211 @@
+use anyhow::{Context, Result};
+use parsentry_reports::output::write_atomic;
+use std::path::Path;
+
+use super::common::{cache_dir_for, locate_repository, repo_name_from_target, write_stdout};
+use crate::cli::args::GraphFormat;
+use crate::cli::ui::StatusPrinter;
+use crate::prompt::scan_coverage;
+use parsentry_core::{FileDiscovery, GraphNodeKind, Language, ThreatModel, ThreatModelGraph};
+use parsentry_parser::{CallGraph, PathStep, SecurityRiskPatterns};
+
+/// Most call paths `graph path` prints.
+const MAX_PATHS: usize = 100;
+
+/// Render the target's threat model as a graph of surfaces and the source
+/// files they cover, highlighting files with PAR pattern hits.
+pub async fn run_graph_command(
+    target: &str,
+    format: GraphFormat,
+    output: Option<&Path>,
+) -> Result<()> {
+    let printer = StatusPrinter::with_service(repo_name_from_target(target));
+    let model_path = cache_dir_for(target).join("model.json");
+    let json = std::fs::read_to_string(&model_path).with_context(|| {
+        format!(
+            "cannot read threat model {}; run `parsentry model {target}` first",
+            model_path.display()
+        )
+    })?;
+    let model: ThreatModel = serde_json::from_str(&json)
+        .with_context(|| format!("invalid threat model JSON in {}", model_path.display()))?;
+    let (root_dir, _) = locate_repository(target, &printer).await?;
+
+    let graph = model_graph(&model, &root_dir);
+    let hits = graph
+        .nodes
+        .iter()
+        .filter(|n| n.kind == GraphNodeKind::File && n.pattern_hits > 0)
+        .count();
+    printer.status(
+        "Graph",
+        &format!(
+            "{} surfaces, {} files ({hits} with pattern hits)",
+            model.surfaces.len(),
+            graph.nodes.len() - model.surfaces.len()
+        ),
+    );
+
+    let rendered = match format {
+        GraphFormat::Json => format!("{}\n", serde_json::to_string_pretty(&graph)?),
+        GraphFormat::Dot => graph.to_dot(),
+        GraphFormat::Mermaid => graph.to_mermaid(),
+    };
+    match output {
+        Some(path) => {
+            write_atomic(path, rendered)?;
+            printer.success("Graph", &path.display().to_string());
+            Ok(())
+        }
+        None => write_stdout(&rendered),
+    }
+}
+
+/// Print the call paths from `from` to `to` in the target's call graph,
+/// shortest first.
+pub async fn run_graph_path_command(
+    target: &str,
+    from: &str,
+    to: &str,
+    max_depth: usize,
+    json: bool,
+) -> Result<()> {
+    let printer = StatusPrinter::with_service(repo_name_from_target(target));
+    let (root_dir, _) = locate_repository(target, &printer).await?;
+    let files = FileDiscovery::new(root_dir.clone()).get_files()?;
+    let graph = CallGraph::build_indexed(&root_dir, &files, &cache_dir_for(target).join("graph"));
+
+    let paths = graph.paths(from, to, max_depth, MAX_PATHS);
+    let truncated = if paths.len() == MAX_PATHS {
+        format!(" (first {MAX_PATHS})")
+    } else {
+        String::new()
+    };
+    printer.status(
+        "Paths",
+        &format!(
+            "{} from `{from}` to `{to}` within {max_depth} calls{truncated}",
+            paths.len()
+        ),
+    );
+    if json {
+        return write_stdout(&format!("{}\n", serde_json::to_string_pretty(&paths)?));
+    }
+    write_stdout(&render_paths(&paths))
+}
+
+fn render_paths(paths: &[Vec<PathStep>]) -> String {
+    let mut out = String::new();
+    for (n, path) in paths.iter().enumerate() {
+        if n > 0 {
+            out.push('\n');
+        }
+        for (i, step) in path.iter().enumerate() {
+            let prefix = if i == 0 {
+                format!("{}. ", n + 1)
+            } else {
+                "   → ".to_string()
+            };
+            out.push_str(&format!(
+                "{prefix}{} ({}:{})\n",
+                step.name, step.file, step.line
+            ));
+        }
+    }
+    out
+}
+
+/// Graph of `model` with the files each surface hands to its worker.
+fn model_graph(model: &ThreatModel, root_dir: &Path) -> ThreatModelGraph {
+    let coverage = scan_coverage(model, root_dir, 0);
+    let mut graph = ThreatModelGraph::default();
+    for surface in &model.surfaces {
+        let files: Vec<(String, usize)> = coverage
+            .surfaces
+            .get(&surface.id)
+            .into_iter()
+            .flatten()
+            .map(|path| (path.clone(), pattern_hits(root_dir, path)))
+            .collect();
+        graph.add_surface(surface, &files);
+    }
+    graph
+}
+
+fn pattern_hits(root_dir: &Path, path: &str) -> usize {
+    let language = Language::from_filename(path);
+    if language == Language::Other {
+        return 0;
+    }
+    std::fs::read_to_string(root_dir.join(path))
+        .map(|text| {
+            SecurityRiskPatterns::new_with_root(language, Some(root_dir))
+                .get_pattern_matches(&text)
+                .len()
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parsentry_core::AttackSurface;
+
+    #[test]
+    fn render_paths_numbers_each_path() {
+        let step = |name: &str, line| PathStep {
+            name: name.to_string(),
+            file: "app.py".to_string(),
+            line,
+        };
+        let paths = vec![
+            vec![step("handler", 1), step("exec_sql", 9)],
+            vec![step("handler", 1), step("find", 4), step("exec_sql", 9)],
+        ];
+        assert_eq!(
+            render_paths(&paths),
+            "1. handler (app.py:1)\n   → exec_sql (app.py:9)\n\n\
+             2. handler (app.py:1)\n   → find (app.py:4)\n   → exec_sql (app.py:9)\n"
+        );
+    }
+
+    #[test]
+    fn model_graph_links_surfaces_to_resolved_files() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(
+            root.path().join("app.py"),
+            "import os\nos.system(input())\n",
+        )
+        .unwrap();
+        std::fs::write(root.path().join("util.py"), "X = 1\n").unwrap();
+        let model = ThreatModel {
+            repository: "r".to_string(),
+            generated_at: String::new(),
+            app_type: "cli".to_string(),
+            summary: String::new(),
+            surfaces: vec![AttackSurface {
+                id: "S-1".to_string(),
+                kind: "cli".to_string(),
+                identifier: "main".to_string(),
+                locations: vec![
+                    "app.py".to_string(),
+                    "util.py".to_string(),
+                    "GET /".to_string(),
+                ],
+                description: String::new(),
+            }],
+        };
+
+        let graph = model_graph(&model, root.path());
+        let files: Vec<(&str, usize)> = graph
+            .nodes
+            .iter()
+            .skip(1)
+            .map(|n| (n.label.as_str(), n.pattern_hits))
+            .collect();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[1], ("util.py", 0));
+        assert!(files[0].0 == "app.py" && files[0].1 > 0);
+        assert_eq!(graph.nodes[0].pattern_hits, files[0].1);
+    }
+}
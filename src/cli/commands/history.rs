@@ -0,0 +1,584 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::common::{cache_dir_for, write_stdout};
+use parsentry_reports::{SarifReport, TrendPoint};
+
+/// Scan history of every target, relative to the working directory.
+const HISTORY_DB: &str = ".parsentry/history.db";
+/// Per-target scan log of earlier versions, imported into the database.
+const LEGACY_HISTORY_FILE: &str = "history.jsonl";
+/// Scans shown in the HTML report's trend chart.
+const TREND_SCANS: usize = 30;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS scans (
+    id INTEGER PRIMARY KEY,
+    target TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    commit_sha TEXT,
+    total INTEGER NOT NULL,
+    findings TEXT,
+    duration_secs REAL,
+    cost_usd REAL
+);
+CREATE INDEX IF NOT EXISTS scans_by_target ON scans (target, id);
+-- Finding counts per level ('level') and per rule ('rule').
+CREATE TABLE IF NOT EXISTS scan_counts (
+    scan_id INTEGER NOT NULL REFERENCES scans (id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    key TEXT NOT NULL,
+    count INTEGER NOT NULL,
+    PRIMARY KEY (scan_id, kind, key)
+);
+";
+
+/// One `generate` run of a target, recorded in [`HISTORY_DB`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    pub total: usize,
+    #[serde(default)]
+    pub by_level: BTreeMap<String, usize>,
+    #[serde(default)]
+    pub by_rule: BTreeMap<String, usize>,
+    /// Hash of the findings' sorted fingerprints, to tell unchanged runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub findings: Option<String>,
+    /// Wall-clock time of the scan, from its manifest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+    /// What the scan's workers reported spending.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+impl HistoryEntry {
+    pub fn from_sarif(report: &SarifReport, commit: Option<String>) -> Self {
+        let mut by_level = BTreeMap::new();
+        let mut by_rule = BTreeMap::new();
+        let mut total = 0;
+        let mut fingerprints = Vec::new();
+        for result in report
+            .runs
+            .iter()
+            .flat_map(|r| r.results.iter())
+            .filter(|r| r.baseline_state.as_deref() != Some("absent"))
+        {
+            total += 1;
+            *by_level.entry(result.level.clone()).or_insert(0) += 1;
+            *by_rule.entry(result.rule_id.clone()).or_insert(0) += 1;
+            fingerprints.push(
+                result
+                    .fingerprints
+                    .as_ref()
+                    .and_then(|f| f.get("parsentry/v1"))
+                    .map_or(result.rule_id.as_str(), String::as_str),
+            );
+        }
+        fingerprints.sort_unstable();
+        let mut hasher = Sha256::new();
+        for fingerprint in fingerprints {
+            hasher.update(fingerprint);
+            hasher.update([0]);
+        }
+        let findings = hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+
+        let invocations = report.runs.iter().filter_map(|r| r.invocation.as_ref());
+        let duration_secs = invocations
+            .clone()
+            .filter_map(|i| {
+                let start = DateTime::parse_from_rfc3339(i.start_time_utc.as_deref()?).ok()?;
+                let end = DateTime::parse_from_rfc3339(i.end_time_utc.as_deref()?).ok()?;
+                Some((end - start).num_milliseconds() as f64 / 1000.0)
+            })
+            .reduce(|a, b| a + b);
+        let cost_usd = invocations
+            .filter_map(|i| i.properties.as_ref()?.cost_usd)
+            .reduce(|a, b| a + b);
+        Self {
+            timestamp: Utc::now(),
+            commit,
+            total,
+            by_level,
+            by_rule,
+            findings: Some(findings),
+            duration_secs,
+            cost_usd,
+        }
+    }
+
+    /// Whether `self` recorded the same findings on the same commit.
+    fn same_scan(&self, other: &Self) -> bool {
+        self.findings.is_some() && self.findings == other.findings && self.commit == other.commit
+    }
+
+    fn level(&self, level: &str) -> usize {
+        self.by_level.get(level).copied().unwrap_or(0)
+    }
+
+    fn trend_point(&self) -> TrendPoint {
+        TrendPoint {
+            date: self.timestamp.format("%Y-%m-%d %H:%M").to_string(),
+            commit: self.commit.clone(),
+            total: self.total,
+            duration_secs: self.duration_secs,
+            cost_usd: self.cost_usd,
+        }
+    }
+}
+
+/// SQLite store of the scans of every target.
+pub struct History {
+    conn: Connection,
+}
+
+impl History {
+    /// Open (creating if needed) the database at `path`.
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn =
+            Connection::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+        conn.execute_batch(SCHEMA)
+            .with_context(|| format!("failed to initialize {}", path.display()))?;
+        Ok(Self { conn })
+    }
+
+    /// The last `limit` (or all) scans of `target`, oldest first.
+    pub fn entries(&self, target: &str, limit: Option<usize>) -> Result<Vec<HistoryEntry>> {
+        let mut scans = self.conn.prepare(
+            "SELECT id, timestamp, commit_sha, total, findings, duration_secs, cost_usd
+             FROM scans WHERE target = ?1 ORDER BY id DESC LIMIT ?2",
+        )?;
+        let limit = limit.map_or(-1, |n| i64::try_from(n).unwrap_or(i64::MAX));
+        let mut entries = scans
+            .query_map(params![target, limit], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    HistoryEntry {
+                        timestamp: row.get(1)?,
+                        commit: row.get(2)?,
+                        total: row.get(3)?,
+                        by_level: BTreeMap::new(),
+                        by_rule: BTreeMap::new(),
+                        findings: row.get(4)?,
+                        duration_secs: row.get(5)?,
+                        cost_usd: row.get(6)?,
+                    },
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        entries.reverse();
+
+        let mut counts = self
+            .conn
+            .prepare("SELECT kind, key, count FROM scan_counts WHERE scan_id = ?1")?;
+        for (id, entry) in &mut entries {
+            let rows = counts.query_map([*id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, usize>(2)?,
+                ))
+            })?;
+            for row in rows {
+                let (kind, key, count) = row?;
+                match kind.as_str() {
+                    "level" => entry.by_level.insert(key, count),
+                    _ => entry.by_rule.insert(key, count),
+                };
+            }
+        }
+        Ok(entries.into_iter().map(|(_, entry)| entry).collect())
+    }
+
+    /// Record `entry` as the latest scan of `target`.
+    pub fn append(&mut self, target: &str, entry: &HistoryEntry) -> Result<()> {
+        self.append_all(target, std::slice::from_ref(entry))
+    }
+
+    fn append_all(&mut self, target: &str, entries: &[HistoryEntry]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        for entry in entries {
+            tx.execute(
+                "INSERT INTO scans (target, timestamp, commit_sha, total, findings, duration_secs, cost_usd)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    target,
+                    entry.timestamp,
+                    entry.commit,
+                    entry.total,
+                    entry.findings,
+                    entry.duration_secs,
+                    entry.cost_usd
+                ],
+            )?;
+            let id = tx.last_insert_rowid();
+            let counts = (entry.by_level.iter().map(|c| ("level", c)))
+                .chain(entry.by_rule.iter().map(|c| ("rule", c)));
+            for (kind, (key, count)) in counts {
+                tx.execute(
+                    "INSERT INTO scan_counts (scan_id, kind, key, count) VALUES (?1, ?2, ?3, ?4)",
+                    params![id, kind, key, count],
+                )?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Import a `history.jsonl` of earlier versions, unless `target`
+    /// already has scans here. Unparseable lines are skipped.
+    fn import_legacy(&mut self, target: &str, path: &Path) -> Result<()> {
+        if !path.exists() || !self.entries(target, Some(1))?.is_empty() {
+            return Ok(());
+        }
+        let content = std::fs::read_to_string(path)?;
+        let entries: Vec<HistoryEntry> = content
+            .lines()
+            .filter_map(|l| serde_json::from_str(l).ok())
+            .collect();
+        self.append_all(target, &entries)
+    }
+}
+
+/// The history database, with the target's legacy log imported.
+fn open_history(target: &str) -> Result<History> {
+    let mut history = History::open(Path::new(HISTORY_DB))?;
+    history.import_legacy(target, &cache_dir_for(target).join(LEGACY_HISTORY_FILE))?;
+    Ok(history)
+}
+
+/// The last `limit` scans of `target`, without creating a database just to
+/// find it empty.
+fn load_history(target: &str, limit: usize) -> Result<Vec<HistoryEntry>> {
+    if !Path::new(HISTORY_DB).exists() && !cache_dir_for(target).join(LEGACY_HISTORY_FILE).exists()
+    {
+        return Ok(Vec::new());
+    }
+    open_history(target)?.entries(target, Some(limit))
+}
+
+/// HEAD commit of the scanned checkout, if it is a git repository.
+pub(crate) fn head_commit(target: &str) -> Option<String> {
+    let local = Path::new(target);
+    let repo_dir = if local.exists() {
+        local.to_path_buf()
+    } else {
+        cache_dir_for(target).join("repo")
+    };
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(repo_dir)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Record the merged report's summary in the scan history, unless the
+/// target's latest scan recorded the same findings on the same commit.
+/// Returns the history database when a scan was recorded.
+pub fn record_scan(target: &str, report: &SarifReport) -> Result<Option<PathBuf>> {
+    let mut history = open_history(target)?;
+    let entry = HistoryEntry::from_sarif(report, head_commit(target));
+    if history
+        .entries(target, Some(1))?
+        .last()
+        .is_some_and(|last| last.same_scan(&entry))
+    {
+        return Ok(None);
+    }
+    history.append(target, &entry)?;
+    Ok(Some(PathBuf::from(HISTORY_DB)))
+}
+
+/// Recent scans of `target` for the HTML report's trend chart, ending with
+/// `report` itself, which is recorded only after the report is written.
+pub fn trend(target: &str, report: &SarifReport) -> Result<Vec<TrendPoint>> {
+    let mut entries = load_history(target, TREND_SCANS)?;
+    let current = HistoryEntry::from_sarif(report, head_commit(target));
+    if !entries.last().is_some_and(|last| last.same_scan(&current)) {
+        entries.push(current);
+    }
+    let skip = entries.len().saturating_sub(TREND_SCANS);
+    Ok(entries[skip..]
+        .iter()
+        .map(HistoryEntry::trend_point)
+        .collect())
+}
+
+fn short_commit(entry: &HistoryEntry) -> &str {
+    entry
+        .commit
+        .as_deref()
+        .map(|c| &c[..c.len().min(8)])
+        .unwrap_or("-")
+}
+
+fn signed(delta: i64) -> String {
+    if delta > 0 {
+        format!("+{delta}")
+    } else {
+        delta.to_string()
+    }
+}
+
+/// Print recorded scans for a target.
+pub fn run_history_command(target: &str, limit: usize, json: bool) -> Result<()> {
+    let entries = load_history(target, limit)?;
+
+    if json {
+        let mut out = String::new();
+        for e in &entries {
+            out.push_str(&serde_json::to_string(e)?);
+            out.push('\n');
+        }
+        return write_stdout(&out);
+    }
+
+    if entries.is_empty() {
+        eprintln!("No scan history for {target}. Run `parsentry generate` first.");
+        return Ok(());
+    }
+
+    let mut out = format!(
+        "{:<20}  {:<8}  {:>5}  {:>5}  {:>7}  {:>4}  {:>8}  {:>7}\n",
+        "DATE", "COMMIT", "TOTAL", "ERROR", "WARNING", "NOTE", "DURATION", "COST"
+    );
+    for e in &entries {
+        out.push_str(&format!(
+            "{:<20}  {:<8}  {:>5}  {:>5}  {:>7}  {:>4}  {:>8}  {:>7}\n",
+            e.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            short_commit(e),
+            e.total,
+            e.level("error"),
+            e.level("warning"),
+            e.level("note"),
+            e.duration_secs
+                .map_or("-".to_string(), |s| format!("{s:.0}s")),
+            e.cost_usd.map_or("-".to_string(), |c| format!("${c:.2}")),
+        ));
+    }
+    write_stdout(&out)
+}
+
+/// Render a trend table with per-scan deltas and a per-rule comparison
+/// between the first and last scan in the window.
+fn render_trend(entries: &[HistoryEntry]) -> String {
+    let max_total = entries.iter().map(|e| e.total).max().unwrap_or(0).max(1);
+    let mut out = String::new();
+    let mut prev: Option<&HistoryEntry> = None;
+
+    for e in entries {
+        let delta = prev
+            .map(|p| signed(e.total as i64 - p.total as i64))
+            .unwrap_or_default();
+        let bar = "█".repeat((e.total * 30).div_ceil(max_total));
+        out.push_str(&format!(
+            "{}  {:<8}  {:>4} {:>5}  {}\n",
+            e.timestamp.format("%Y-%m-%d"),
+            short_commit(e),
+            e.total,
+            delta,
+            bar
+        ));
+        prev = Some(e);
+    }
+
+    if let (Some(first), Some(last)) = (entries.first(), entries.last())
+        && entries.len() > 1
+    {
+        let delta = last.total as i64 - first.total as i64;
+        let verdict = match delta.cmp(&0) {
+            std::cmp::Ordering::Greater => "regression",
+            std::cmp::Ordering::Less => "improvement",
+            std::cmp::Ordering::Equal => "no change",
+        };
+        out.push_str(&format!(
+            "\n{verdict}: {} → {} findings ({})\n",
+            first.total,
+            last.total,
+            signed(delta)
+        ));
+
+        let mut rules: Vec<&String> = first.by_rule.keys().chain(last.by_rule.keys()).collect();
+        rules.sort();
+        rules.dedup();
+        for rule in rules {
+            let before = first.by_rule.get(rule).copied().unwrap_or(0) as i64;
+            let after = last.by_rule.get(rule).copied().unwrap_or(0) as i64;
+            if before != after {
+                out.push_str(&format!(
+                    "  {rule:<10} {before} → {after} ({})\n",
+                    signed(after - before)
+                ));
+            }
+        }
+    }
+    out
+}
+
+/// Show how finding counts changed over the last `limit` scans.
+pub fn run_trend_command(target: &str, limit: usize) -> Result<()> {
+    let entries = load_history(target, limit)?;
+    if entries.is_empty() {
+        eprintln!("No scan history for {target}. Run `parsentry generate` first.");
+        return Ok(());
+    }
+    write_stdout(&render_trend(&entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parsentry_reports::sarif::{SarifInvocation, SarifInvocationProperties};
+    use parsentry_reports::test_support::{ResultFixture, report, result};
+    use std::collections::HashMap;
+
+    fn entry(total: usize, rules: &[(&str, usize)]) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: Utc::now(),
+            commit: Some("0123456789abcdef".to_string()),
+            total,
+            by_level: BTreeMap::from([("error".to_string(), total)]),
+            by_rule: rules.iter().map(|(r, n)| (r.to_string(), *n)).collect(),
+            findings: None,
+            duration_secs: None,
+            cost_usd: None,
+        }
+    }
+
+    #[test]
+    fn test_history_keeps_scans_per_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut history = History::open(&dir.path().join(".parsentry/history.db")).unwrap();
+        let mut last = entry(3, &[("SQLI", 1), ("XSS", 2)]);
+        last.duration_secs = Some(12.5);
+        last.cost_usd = Some(0.4);
+        history.append("acme/api", &entry(1, &[])).unwrap();
+        history.append("acme/web", &entry(5, &[])).unwrap();
+        history.append("acme/api", &entry(2, &[])).unwrap();
+        history.append("acme/api", &last).unwrap();
+
+        let entries = history.entries("acme/api", Some(2)).unwrap();
+        assert_eq!(
+            entries.iter().map(|e| e.total).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert_eq!(entries[1].by_rule, last.by_rule);
+        assert_eq!(entries[1].level("error"), 3);
+        assert_eq!(entries[1].timestamp, last.timestamp);
+        assert_eq!(entries[1].duration_secs, Some(12.5));
+        assert_eq!(entries[1].cost_usd, Some(0.4));
+        assert_eq!(history.entries("acme/api", None).unwrap().len(), 3);
+        assert!(history.entries("acme/cli", None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_import_legacy_skips_bad_lines_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let legacy = dir.path().join(LEGACY_HISTORY_FILE);
+        let line = serde_json::to_string(&entry(2, &[("SQLI", 2)])).unwrap();
+        std::fs::write(&legacy, format!("{line}\nnot json\n{line}\n")).unwrap();
+
+        let mut history = History::open(&dir.path().join("history.db")).unwrap();
+        history.import_legacy("acme/api", &legacy).unwrap();
+        history.import_legacy("acme/api", &legacy).unwrap();
+        history
+            .import_legacy("acme/web", &dir.path().join("missing"))
+            .unwrap();
+        let entries = history.entries("acme/api", None).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].by_rule["SQLI"], 2);
+        assert!(history.entries("acme/web", None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_render_trend_reports_regression_per_rule() {
+        let out = render_trend(&[
+            entry(1, &[("SQLI", 1)]),
+            entry(3, &[("SQLI", 1), ("XSS", 2)]),
+        ]);
+        assert!(out.contains("regression: 1 → 3 findings (+2)"));
+        assert!(out.contains("XSS"));
+        assert!(!out.contains("  SQLI"));
+        assert!(out.contains("01234567"));
+    }
+
+    #[test]
+    fn test_from_sarif_ignores_absent_results() {
+        let sarif = report([
+            result("SQLI", "a.py"),
+            result("XSS", "b.py")
+                .level("warning")
+                .baseline_state("absent"),
+        ]);
+        let e = HistoryEntry::from_sarif(&sarif, None);
+        assert_eq!(e.total, 1);
+        assert_eq!(e.level("error"), 1);
+        assert_eq!(e.level("warning"), 0);
+        assert_eq!(e.duration_secs, None);
+        assert_eq!(e.cost_usd, None);
+    }
+
+    #[test]
+    fn test_from_sarif_reads_duration_and_cost() {
+        let mut sarif = report([]);
+        sarif.runs[0].invocation = Some(SarifInvocation {
+            execution_successful: true,
+            start_time_utc: Some("2026-01-01T00:00:00Z".to_string()),
+            end_time_utc: Some("2026-01-01T00:01:30Z".to_string()),
+            arguments: None,
+            properties: Some(SarifInvocationProperties {
+                cost_usd: Some(1.25),
+                ..Default::default()
+            }),
+        });
+        let e = HistoryEntry::from_sarif(&sarif, None);
+        assert_eq!(e.duration_secs, Some(90.0));
+        assert_eq!(e.cost_usd, Some(1.25));
+        let point = e.trend_point();
+        assert_eq!(
+            (point.duration_secs, point.cost_usd),
+            (Some(90.0), Some(1.25))
+        );
+    }
+
+    #[test]
+    fn test_same_scan_needs_same_commit_and_findings() {
+        let scan = |rule: &str| {
+            let mut finding = result(rule, "a.py");
+            finding.fingerprints = Some(HashMap::from([(
+                "parsentry/v1".to_string(),
+                format!("{rule}-1"),
+            )]));
+            report([finding])
+        };
+        let commit = || Some("abc".to_string());
+        let first = HistoryEntry::from_sarif(&scan("SQLI"), commit());
+        assert!(first.same_scan(&HistoryEntry::from_sarif(&scan("SQLI"), commit())));
+        assert!(!first.same_scan(&HistoryEntry::from_sarif(&scan("XSS"), commit())));
+        assert!(!first.same_scan(&HistoryEntry::from_sarif(
+            &scan("SQLI"),
+            Some("def".to_string())
+        )));
+        assert!(!entry(1, &[]).same_scan(&entry(1, &[])));
+    }
+}
@@ -5,7 +5,8 @@ use std::path::{Path, PathBuf};
 use std::sync::mpsc;
 use std::time::{Duration, Instant};
 
-use crate::cli::ui::{colors, colors_enabled};
+use crate::cli::ui::progress::{self, ScanDashboard};
+use crate::cli::ui::{colors, colors_enabled, interactive};
 
 const SURFACE_COLORS: &[&str] = &[
     colors::CYAN,
@@ -155,6 +156,8 @@ pub async fn run_log_command(
     let mut session_jsonls: Vec<(String, PathBuf)> = Vec::new();
     let mut last_session_count: Option<usize> = None;
     let mut dir_existed = output_dir.exists();
+    // Following on a terminal draws a live view instead of per-event lines.
+    let mut dashboard = (follow && interactive()).then(ScanDashboard::new);
 
     // Initial discovery
     if dir_existed {
@@ -164,6 +167,7 @@ pub async fn run_log_command(
             &mut surface_colors_map,
             use_colors,
             timestamps,
+            dashboard.as_mut(),
         );
         check_completed_surfaces(
             &output_dir,
@@ -172,8 +176,12 @@ pub async fn run_log_command(
             &surface_colors_map,
             use_colors,
             timestamps,
+            dashboard.as_mut(),
         );
     }
+    if let Some(dashboard) = dashboard.as_mut() {
+        dashboard.stats.completed_before = dashboard.stats.completed;
+    }
 
     if !follow {
         if known_surfaces.is_empty() {
@@ -196,6 +204,7 @@ pub async fn run_log_command(
     }
 
     if !known_surfaces.is_empty() && completed.len() == known_surfaces.len() {
+        drop(dashboard);
         print_summary(
             &known_surfaces,
             &completed,
@@ -235,7 +244,12 @@ pub async fn run_log_command(
         &surface_colors_map,
         use_colors,
         timestamps,
+        dashboard.as_mut(),
     );
+    if let Some(dashboard) = dashboard.as_mut() {
+        dashboard.stats.tokens = session_tokens(&session_jsonls);
+        dashboard.refresh();
+    }
 
     loop {
         // Check timeout
@@ -249,6 +263,7 @@ pub async fn run_log_command(
                 timestamps,
                 colors::BRIGHT_RED,
             );
+            drop(dashboard);
             print_summary(
                 &known_surfaces,
                 &completed,
@@ -311,7 +326,14 @@ pub async fn run_log_command(
                                         .get(&surface_id)
                                         .unwrap_or(&colors::RESET);
                                     for ev in &events {
-                                        print_event(&surface_id, ev, use_colors, timestamps, color);
+                                        print_event(
+                                            &surface_id,
+                                            ev,
+                                            use_colors,
+                                            timestamps,
+                                            color,
+                                            dashboard.as_mut(),
+                                        );
                                     }
                                 }
                             }
@@ -327,6 +349,7 @@ pub async fn run_log_command(
                                 &mut surface_colors_map,
                                 use_colors,
                                 timestamps,
+                                dashboard.as_mut(),
                             );
                             check_completed_surfaces(
                                 &output_dir,
@@ -335,6 +358,7 @@ pub async fn run_log_command(
                                 &surface_colors_map,
                                 use_colors,
                                 timestamps,
+                                dashboard.as_mut(),
                             );
                         }
                     }
@@ -372,6 +396,7 @@ pub async fn run_log_command(
                     &mut surface_colors_map,
                     use_colors,
                     timestamps,
+                    dashboard.as_mut(),
                 );
             }
 
@@ -390,6 +415,7 @@ pub async fn run_log_command(
                 &surface_colors_map,
                 use_colors,
                 timestamps,
+                dashboard.as_mut(),
             );
 
             check_completed_surfaces(
@@ -399,11 +425,21 @@ pub async fn run_log_command(
                 &surface_colors_map,
                 use_colors,
                 timestamps,
+                dashboard.as_mut(),
             );
+
+            if let Some(dashboard) = dashboard.as_mut() {
+                dashboard.stats.tokens = session_tokens(&session_jsonls);
+            }
+        }
+
+        if let Some(dashboard) = &dashboard {
+            dashboard.refresh();
         }
 
         // All done?
         if !known_surfaces.is_empty() && completed.len() == known_surfaces.len() {
+            drop(dashboard);
             print_summary(
                 &known_surfaces,
                 &completed,
@@ -465,6 +501,7 @@ fn flush_jsonl_events(
     surface_colors_map: &HashMap<String, &str>,
     use_colors: bool,
     timestamps: bool,
+    mut dashboard: Option<&mut ScanDashboard>,
 ) {
     for (surface_id, jsonl_path) in session_jsonls {
         let offset = offsets.get(jsonl_path).copied().unwrap_or(0);
@@ -472,19 +509,43 @@ fn flush_jsonl_events(
             offsets.insert(jsonl_path.clone(), new_offset);
             let color = surface_colors_map.get(surface_id).unwrap_or(&colors::RESET);
             for ev in &events {
-                print_event(surface_id, ev, use_colors, timestamps, color);
+                print_event(
+                    surface_id,
+                    ev,
+                    use_colors,
+                    timestamps,
+                    color,
+                    dashboard.as_deref_mut(),
+                );
             }
         }
     }
 }
 
+/// Tokens used so far by the tracked worker sessions.
+fn session_tokens(session_jsonls: &[(String, PathBuf)]) -> u64 {
+    session_jsonls
+        .iter()
+        .filter_map(|(_, path)| parsentry_claude::transcript_stats(path).ok())
+        .map(|stats| stats.input_tokens + stats.output_tokens)
+        .sum()
+}
+
 fn print_event(
     surface_id: &str,
     event: &parsentry_claude::SessionEvent,
     use_colors: bool,
     timestamps: bool,
     color: &str,
+    dashboard: Option<&mut ScanDashboard>,
 ) {
+    // The dashboard shows each worker's latest activity on its own line.
+    if let Some(dashboard) = dashboard {
+        if let parsentry_claude::SessionEvent::ToolUse { name, summary, .. } = event {
+            dashboard.activity(surface_id, &format!("{} {}", name, summary));
+        }
+        return;
+    }
     match event {
         parsentry_claude::SessionEvent::ToolUse { name, summary, .. } => {
             print_log(
@@ -508,6 +569,7 @@ fn discover_new_surfaces(
     color_map: &mut HashMap<String, &'static str>,
     use_colors: bool,
     timestamps: bool,
+    dashboard: Option<&mut ScanDashboard>,
 ) {
     let entries = match std::fs::read_dir(output_dir) {
         Ok(e) => e,
@@ -538,6 +600,9 @@ fn discover_new_surfaces(
         print_log(&s, "waiting", use_colors, timestamps, color);
         known.push(s);
     }
+    if let Some(dashboard) = dashboard {
+        dashboard.stats.total = known.len();
+    }
 }
 
 fn check_completed_surfaces(
@@ -547,11 +612,22 @@ fn check_completed_surfaces(
     color_map: &HashMap<String, &str>,
     use_colors: bool,
     timestamps: bool,
+    mut dashboard: Option<&mut ScanDashboard>,
 ) {
     for surface in surfaces {
         if !completed.contains(surface) && sarif_exists(output_dir, surface) {
             let findings = count_sarif_findings(output_dir, surface);
             completed.insert(surface.clone());
+            if let Some(dashboard) = dashboard.as_deref_mut() {
+                dashboard.finish_worker(surface);
+                dashboard.stats.completed += 1;
+                if is_cached(output_dir, surface) {
+                    dashboard.stats.cached += 1;
+                }
+                if let Some(cost) = reported_cost(output_dir, surface) {
+                    *dashboard.stats.cost_usd.get_or_insert(0.0) += cost;
+                }
+            }
             let color = color_map.get(surface).unwrap_or(&colors::RESET);
             print_log(
                 surface,
@@ -571,6 +647,33 @@ fn sarif_exists(output_dir: &Path, surface_id: &str) -> bool {
         .exists()
 }
 
+/// Whether `scan` reused the surface's result from its cache: the result is
+/// older than the orchestrator prompt of the current scan.
+fn is_cached(output_dir: &Path, surface_id: &str) -> bool {
+    let modified = |path: PathBuf| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    match (
+        modified(output_dir.join(surface_id).join("result.sarif.json")),
+        modified(output_dir.join("orchestrator.prompt.md")),
+    ) {
+        (Some(result), Some(scan)) => result < scan,
+        _ => false,
+    }
+}
+
+/// Cost the surface's worker reported in its invocation, if any.
+fn reported_cost(output_dir: &Path, surface_id: &str) -> Option<f64> {
+    let path = output_dir.join(surface_id).join("result.sarif.json");
+    parsentry_reports::SarifReport::from_file(&path)
+        .ok()?
+        .runs
+        .first()?
+        .invocation
+        .as_ref()?
+        .properties
+        .as_ref()?
+        .cost_usd
+}
+
 fn count_sarif_findings(output_dir: &Path, surface_id: &str) -> usize {
     let path = output_dir.join(surface_id).join("result.sarif.json");
     let data = match std::fs::read_to_string(&path) {
@@ -599,6 +702,10 @@ fn print_log(service: &str, message: &str, use_colors: bool, show_timestamps: bo
         String::new()
     };
 
+    progress::suspend(|| print_lines(service, message, use_colors, &ts, color));
+}
+
+fn print_lines(service: &str, message: &str, use_colors: bool, ts: &str, color: &str) {
     for line in message.lines() {
         if use_colors {
             eprintln!(
@@ -1,10 +1,43 @@
 pub mod common;
+pub mod compare;
+pub mod config;
+pub mod diff;
+pub mod doctor;
+pub mod eval;
+pub mod fix;
 pub mod generate;
+pub mod graph;
+pub mod history;
 pub mod log;
 pub mod model;
+pub mod patterns;
+pub mod poc;
+pub mod review;
 pub mod scan;
+pub mod transcripts;
+pub mod validate;
+pub mod variants;
+pub mod verify;
 
-pub use generate::run_generate_command;
+pub use compare::run_compare_command;
+pub use config::{run_config_show_command, run_config_validate_command};
+pub use diff::run_diff_command;
+pub use doctor::run_doctor_command;
+pub use eval::run_eval_command;
+pub use fix::run_fix_command;
+pub use generate::{Delivery, OwnerRouting, Signing, run_generate_command};
+pub use graph::{run_graph_command, run_graph_path_command};
+pub use history::{run_history_command, run_trend_command};
 pub use log::run_log_command;
 pub use model::run_model_command;
-pub use scan::run_scan_command;
+pub use patterns::{
+    run_patterns_install_command, run_patterns_lint_command, run_patterns_list_command,
+    run_patterns_merge_command, run_patterns_test_command, run_patterns_update_command,
+};
+pub use poc::run_poc_command;
+pub use review::run_review_command;
+pub use scan::{AnalysisPlan, Replay, run_scan_command, sweep_targets};
+pub use transcripts::run_transcripts_command;
+pub use validate::run_validate_command;
+pub use variants::{VariantSeed, run_variants_command};
+pub use verify::run_verify_command;
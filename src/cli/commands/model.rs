@@ -1,24 +1,35 @@
 use anyhow::Result;
 
+use crate::cli::args::ScopeArgs;
 use crate::cli::ui::StatusPrinter;
 
 use super::common::{
-    build_threat_model_cli_prompt, cache_dir_for, is_network_target, locate_repository,
-    repo_name_from_target, write_stdout,
+    add_framework_entry_points, build_threat_model_cli_prompt, cache_dir_for, clone_options,
+    is_network_target, locate_repository_with, path_filter, repo_name_from_target, write_stdout,
 };
 
-use parsentry_core::RepoMetadata;
+use parsentry_core::offline::{is_offline, require_network};
+use parsentry_core::{FileDiscovery, RepoMetadata};
 
-pub async fn run_model_command(target: &str) -> Result<()> {
+#[tracing::instrument(name = "model", skip_all, fields(target = %target))]
+pub async fn run_model_command(target: &str, scope: &ScopeArgs) -> Result<()> {
     let printer = StatusPrinter::with_service(repo_name_from_target(target));
+    let filter = path_filter(scope)?;
 
-    let (root_dir, _repo_name) = locate_repository(target, &printer).await?;
+    let (root_dir, _repo_name) =
+        locate_repository_with(target, &printer, &clone_options(scope)?).await?;
 
-    let mut repo_metadata = RepoMetadata::collect(&root_dir)?;
+    let mut repo_metadata = tracing::info_span!("discovery")
+        .in_scope(|| RepoMetadata::collect_filtered(&root_dir, &filter))?;
 
     if is_network_target(target) {
         repo_metadata.source_url = Some(target.to_string());
     }
+    let files = FileDiscovery::new(root_dir.clone())
+        .with_filter(filter.clone())
+        .get_files()
+        .unwrap_or_default();
+    let entry_points = add_framework_entry_points(&mut repo_metadata, &files);
 
     printer.status(
         "Collected",
@@ -28,9 +39,26 @@ pub async fn run_model_command(target: &str) -> Result<()> {
             repo_metadata.languages.len()
         ),
     );
+    if !entry_points.is_empty() {
+        printer.status(
+            "Entry points",
+            &format!(
+                "{} route handlers, commands and consumers",
+                entry_points.len()
+            ),
+        );
+    }
 
     let output = cache_dir_for(target).join("model.json");
-    let prompt = build_threat_model_cli_prompt(&repo_metadata, &output);
+    if is_offline() {
+        // Cache-only: an existing threat model is reused as is.
+        if !output.exists() {
+            require_network(&[format!("threat model generation for {target}")])?;
+        }
+        printer.status("Cached", &format!("threat model → {}", output.display()));
+        return Ok(());
+    }
+    let prompt = build_threat_model_cli_prompt(&repo_metadata, &output, &filter);
     write_stdout(&prompt)?;
 
     printer.success(
@@ -0,0 +1,298 @@
+use anyhow::{Context, Result, bail};
+use std::path::Path;
+
+use super::common::write_stdout;
+use crate::cli::ui::StatusPrinter;
+use crate::pattern_packs::fetch_pack;
+use parsentry_core::Language;
+use parsentry_parser::{
+    PatternEntry, PatternFile, PatternOrigin, SecurityRiskPatterns, default_packs_dir,
+    disabled_patterns, install_pack, installed_packs,
+};
+
+fn parse_language(lang: &str) -> Result<Language> {
+    lang.parse::<Language>()
+        .map_err(|e| anyhow::anyhow!("unknown language `{lang}`: {e}"))
+}
+
+/// First line of a (possibly multi-line) string, for one-line listings.
+fn first_line(text: &str) -> &str {
+    text.trim().lines().next().unwrap_or_default()
+}
+
+/// List built-in and custom patterns, optionally for one language.
+pub fn run_patterns_list_command(root: &Path, lang: Option<&str>) -> Result<()> {
+    let language = lang.map(parse_language).transpose()?;
+    let entries: Vec<PatternEntry> = SecurityRiskPatterns::list_patterns(Some(root))?
+        .into_iter()
+        .filter(|e| language.is_none_or(|l| e.language == l))
+        .collect();
+
+    let disabled = disabled_patterns();
+    let mut out = String::new();
+    let mut current = None;
+    for entry in &entries {
+        if current != Some(entry.language) {
+            out.push_str(&format!("{}\n", entry.language));
+            current = Some(entry.language);
+        }
+        let config = &entry.config;
+        let mut line = format!(
+            "  [{}] {:<10} {:<10} {}",
+            entry.origin,
+            entry.category,
+            config.pattern_type.kind(),
+            config.description
+        );
+        if let Some(severity) = config.severity {
+            line.push_str(&format!(" ({severity})"));
+        }
+        if !config.tags.is_empty() {
+            line.push_str(&format!(" #{}", config.tags.join(" #")));
+        }
+        if !config.is_active(&disabled) {
+            line.push_str(" [disabled]");
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    write_stdout(&out)
+}
+
+/// Compile every pattern query and report the ones that are invalid.
+///
+/// With `file`, only that pattern file is checked; otherwise the built-in
+/// patterns and `<root>/vuln-patterns.yml`. With `fix`, the custom pattern
+/// file is rewritten with repairable queries fixed and the rest dropped.
+pub fn run_patterns_lint_command(root: &Path, file: Option<&Path>, fix: bool) -> Result<()> {
+    let printer = StatusPrinter::new();
+    if fix {
+        let path = file
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| SecurityRiskPatterns::custom_patterns_path(Some(root)));
+        let report = SecurityRiskPatterns::sanitize_pattern_file(&path)?;
+        for description in &report.repaired {
+            printer.status("Repaired", description);
+        }
+        for issue in &report.rejected {
+            printer.warning(
+                "Dropped",
+                &format!(
+                    "{} {} `{}`: {}",
+                    issue.entry.language,
+                    issue.entry.category,
+                    issue.entry.config.description,
+                    first_line(&issue.error)
+                ),
+            );
+        }
+        printer.success(
+            "Fixed",
+            &format!(
+                "{}: {} kept, {} repaired, {} rejected",
+                path.display(),
+                report.kept,
+                report.repaired.len(),
+                report.rejected.len()
+            ),
+        );
+        return Ok(());
+    }
+    let entries = match file {
+        Some(path) => SecurityRiskPatterns::read_pattern_file(path)?
+            .into_iter()
+            .flat_map(|(language, patterns)| {
+                patterns
+                    .iter()
+                    .map(|(category, config)| PatternEntry {
+                        language,
+                        category,
+                        origin: PatternOrigin::Custom,
+                        config: config.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect(),
+        None => SecurityRiskPatterns::list_patterns(Some(root))?,
+    };
+
+    let issues = SecurityRiskPatterns::lint_patterns(&entries);
+    for issue in &issues {
+        printer.error(
+            "Invalid",
+            &format!(
+                "{} {} `{}` ({}): {}",
+                issue.entry.language,
+                issue.entry.category,
+                issue.entry.config.description,
+                issue.entry.origin,
+                first_line(&issue.error)
+            ),
+        );
+    }
+
+    if !issues.is_empty() {
+        bail!(
+            "{} of {} pattern(s) have invalid queries",
+            issues.len(),
+            entries.len()
+        );
+    }
+    printer.success("Valid", &format!("{} pattern(s)", entries.len()));
+    Ok(())
+}
+
+/// Merge a pattern file into `<root>/vuln-patterns.yml`.
+///
+/// Patterns with the same query replace the existing entry; the merged file
+/// is validated afterwards so broken incoming queries are not kept.
+pub fn run_patterns_merge_command(root: &Path, file: &Path) -> Result<()> {
+    let printer = StatusPrinter::new();
+    let target = SecurityRiskPatterns::custom_patterns_path(Some(root));
+    let mut merged = PatternFile::load_or_default(&target)?;
+    let stats = merged.merge(PatternFile::load(file)?);
+    merged.save(&target)?;
+
+    let report = SecurityRiskPatterns::sanitize_pattern_file(&target)?;
+    for issue in &report.rejected {
+        printer.warning(
+            "Dropped",
+            &format!(
+                "{} `{}`: {}",
+                issue.entry.language,
+                issue.entry.config.description,
+                first_line(&issue.error)
+            ),
+        );
+    }
+    printer.success(
+        "Merged",
+        &format!(
+            "{} added, {} updated, {} rejected → {}",
+            stats.added,
+            stats.updated,
+            report.rejected.len(),
+            target.display()
+        ),
+    );
+    Ok(())
+}
+
+fn packs_dir() -> Result<std::path::PathBuf> {
+    default_packs_dir().ok_or_else(|| {
+        anyhow::anyhow!("cannot determine the pattern pack directory; set PARSENTRY_PATTERNS_DIR")
+    })
+}
+
+/// Install a pattern pack from a path, URL, git repository or registry name.
+pub async fn run_patterns_install_command(source: &str) -> Result<()> {
+    let printer = StatusPrinter::new();
+    let (pack, provenance) = fetch_pack(source).await?;
+    let dir = install_pack(&packs_dir()?, &pack, &provenance)?;
+    printer.success(
+        "Installed",
+        &format!(
+            "{} {} ({} patterns) → {}",
+            pack.name,
+            pack.version,
+            pack.patterns.len(),
+            dir.display()
+        ),
+    );
+    Ok(())
+}
+
+/// Re-fetch every installed pack from its recorded source and install newer
+/// versions; with `check`, only report what would change.
+pub async fn run_patterns_update_command(check: bool) -> Result<()> {
+    let printer = StatusPrinter::new();
+    let dir = packs_dir()?;
+    let installed = installed_packs(&dir);
+    if installed.is_empty() {
+        printer.status("Packs", "none installed");
+        return Ok(());
+    }
+
+    let mut outdated = 0;
+    for current in installed {
+        let name = &current.pack.name;
+        let (pack, provenance) = match fetch_pack(&current.provenance.source).await {
+            Ok(fetched) => fetched,
+            Err(e) => {
+                printer.warning("Unreachable", &format!("{name}: {e:#}"));
+                continue;
+            }
+        };
+        if provenance.sha256 == current.provenance.sha256 {
+            printer.status("Current", &format!("{name} {}", current.pack.version));
+            continue;
+        }
+        outdated += 1;
+        let change = format!("{name} {} → {}", current.pack.version, pack.version);
+        if check {
+            printer.warning("Outdated", &change);
+        } else {
+            install_pack(&dir, &pack, &provenance)?;
+            printer.success("Updated", &change);
+        }
+    }
+
+    if check && outdated > 0 {
+        bail!("{outdated} pack(s) can be updated");
+    }
+    Ok(())
+}
+
+/// Run the patterns for a sample file's language and print each match.
+pub fn run_patterns_test_command(
+    sample: &Path,
+    root: &Path,
+    lang: Option<&str>,
+    pattern: Option<&str>,
+) -> Result<()> {
+    let content = std::fs::read_to_string(sample)
+        .with_context(|| format!("cannot read {}", sample.display()))?;
+    let language = match lang {
+        Some(lang) => parse_language(lang)?,
+        None => Language::from_filename(&sample.to_string_lossy()),
+    };
+    if language == Language::Other {
+        bail!(
+            "cannot infer the language of {}; pass --lang",
+            sample.display()
+        );
+    }
+
+    let matcher = SecurityRiskPatterns::new_with_root(language, Some(root));
+    let needle = pattern.map(str::to_lowercase);
+    let mut matches: Vec<_> = matcher
+        .get_pattern_matches(&content)
+        .into_iter()
+        .filter(|m| {
+            needle
+                .as_deref()
+                .is_none_or(|n| m.pattern_config.description.to_lowercase().contains(n))
+        })
+        .collect();
+    matches.sort_by_key(|m| m.start_byte);
+
+    let mut out = String::new();
+    for m in &matches {
+        let line = content[..m.start_byte].matches('\n').count() + 1;
+        out.push_str(&format!(
+            "{}:{}: {} ({}): {}\n",
+            sample.display(),
+            line,
+            m.pattern_config.description,
+            m.pattern_config.pattern_type.kind(),
+            first_line(&m.matched_text)
+        ));
+    }
+    write_stdout(&out)?;
+
+    StatusPrinter::new().status(
+        "Matched",
+        &format!("{} match(es) for {}", matches.len(), language),
+    );
+    Ok(())
+}
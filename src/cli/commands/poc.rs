@@ -0,0 +1,14 @@
+use anyhow::Result;
+use std::path::Path;
+use std::time::Duration;
+
+use super::common::write_stdout;
+use crate::sandbox::Sandbox;
+
+/// Run a proof-of-concept script in a sandbox and print its outcome as JSON.
+///
+/// Fails without running anything when no sandbox is available.
+pub fn run_poc_command(script: &Path, timeout_secs: u64) -> Result<()> {
+    let run = Sandbox::detect()?.run(script, Duration::from_secs(timeout_secs))?;
+    write_stdout(&format!("{}\n", serde_json::to_string_pretty(&run)?))
+}
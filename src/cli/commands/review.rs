@@ -0,0 +1,274 @@
+use anyhow::{Result, bail};
+use console::{Key, Term, style};
+use std::io::IsTerminal;
+use std::path::Path;
+
+use crate::cli::ui::StatusPrinter;
+use parsentry_reports::SarifReport;
+use parsentry_reports::sarif::SarifResult;
+use parsentry_reports::triage::{Decision, TRIAGE_FILE, Triage, TriageEntry};
+
+/// Lines of source shown above and below a finding.
+const CONTEXT_LINES: usize = 5;
+
+const KEYS: &str = "[t] true positive  [f] false positive  [a] accepted risk  [u] undo  \
+                    [←/→] previous/next  [q] quit";
+
+/// Step through the findings of `report`, record a triage decision for each
+/// and save them to the triage file that `merge` applies to later scans.
+pub fn run_review_command(report: &Path, root: &Path, triage_path: Option<&Path>) -> Result<()> {
+    let printer = StatusPrinter::new();
+    let term = Term::stderr();
+    if !term.is_term() || !std::io::stdin().is_terminal() {
+        bail!("`parsentry review` needs an interactive terminal");
+    }
+
+    let sarif = SarifReport::from_file(report)?;
+    // Findings fixed since the baseline need no decision.
+    let results: Vec<&SarifResult> = sarif
+        .runs
+        .iter()
+        .flat_map(|run| &run.results)
+        .filter(|r| r.baseline_state.as_deref() != Some("absent"))
+        .collect();
+    if results.is_empty() {
+        printer.info("Review", &format!("no findings in {}", report.display()));
+        return Ok(());
+    }
+
+    let triage_path = match triage_path {
+        Some(path) => path.to_path_buf(),
+        None => report.parent().unwrap_or(Path::new(".")).join(TRIAGE_FILE),
+    };
+    let mut triage = Triage::load(&triage_path)?;
+
+    term.hide_cursor()?;
+    let outcome = review_loop(&term, &results, root, &mut triage, &triage_path);
+    term.clear_screen()?;
+    term.show_cursor()?;
+    outcome?;
+
+    let decided = results.iter().filter(|r| triage.get(r).is_some()).count();
+    printer.success(
+        "Triaged",
+        &format!(
+            "{decided}/{} findings → {}",
+            results.len(),
+            triage_path.display()
+        ),
+    );
+    if decided < results.len() {
+        printer.dim(&format!(
+            "{} findings left untriaged; run `parsentry review` again to continue",
+            results.len() - decided
+        ));
+    }
+    Ok(())
+}
+
+fn review_loop(
+    term: &Term,
+    results: &[&SarifResult],
+    root: &Path,
+    triage: &mut Triage,
+    triage_path: &Path,
+) -> Result<()> {
+    // Resume at the first finding without a decision.
+    let mut index = results
+        .iter()
+        .position(|r| triage.get(r).is_none())
+        .unwrap_or(0);
+    loop {
+        let result = results[index];
+        let context = code_context(root, result);
+        term.clear_screen()?;
+        for line in render_finding(result, index, results.len(), triage.get(result), &context) {
+            term.write_line(&line)?;
+        }
+
+        let decision = match term.read_key()? {
+            Key::Char('t') => Some(Decision::TruePositive),
+            Key::Char('f') => Some(Decision::FalsePositive),
+            Key::Char('a') => Some(Decision::AcceptedRisk),
+            Key::Char('u') => {
+                triage.clear(result);
+                triage.save(triage_path)?;
+                None
+            }
+            Key::Char('n' | 'j' | ' ') | Key::ArrowRight | Key::Enter => {
+                index = (index + 1).min(results.len() - 1);
+                None
+            }
+            Key::Char('p' | 'k') | Key::ArrowLeft => {
+                index = index.saturating_sub(1);
+                None
+            }
+            Key::Char('q') | Key::Escape | Key::CtrlC => return Ok(()),
+            _ => None,
+        };
+
+        if let Some(decision) = decision {
+            let note = if decision.suppresses() {
+                term.show_cursor()?;
+                term.write_str("Justification (optional): ")?;
+                let note = term.read_line()?;
+                term.hide_cursor()?;
+                Some(note.trim().to_string()).filter(|n| !n.is_empty())
+            } else {
+                None
+            };
+            triage.decide(result, decision, note);
+            // Save as we go so an interrupted review keeps its decisions.
+            triage.save(triage_path)?;
+            if index + 1 < results.len() {
+                index += 1;
+            }
+        }
+    }
+}
+
+/// Screen lines for one finding: header, message, PAR analysis, source.
+fn render_finding(
+    result: &SarifResult,
+    index: usize,
+    total: usize,
+    entry: Option<&TriageEntry>,
+    context: &[String],
+) -> Vec<String> {
+    let props = result.properties.clone().unwrap_or_default();
+    let location = result.locations.first().map(|l| &l.physical_location);
+    let uri = location.map_or("-", |l| l.artifact_location.uri.as_str());
+    let line = location
+        .and_then(|l| l.region.as_ref())
+        .map(|r| format!(":{}", r.start_line))
+        .unwrap_or_default();
+    let status = match entry {
+        Some(entry) => style(entry.decision.label()).green().to_string(),
+        None => style("untriaged").yellow().to_string(),
+    };
+
+    let mut lines = vec![
+        format!(
+            "{} {} {} {}",
+            style(format!("[{}/{}]", index + 1, total)).dim(),
+            style(&result.rule_id).bold(),
+            result.level,
+            status
+        ),
+        format!("{}{}", style(uri).cyan(), line),
+        String::new(),
+        result.message.text.clone(),
+        String::new(),
+    ];
+
+    let mut field = |name: &str, value: Option<String>| {
+        if let Some(value) = value {
+            lines.push(format!("{:<12} {}", style(name).dim(), value));
+        }
+    };
+    field(
+        "confidence",
+        props.confidence.map(|c| format!("{:.0}%", c * 100.0)),
+    );
+    field("principal", props.principal);
+    field("action", props.action);
+    field("resource", props.resource);
+    field("data flow", props.data_flow);
+    field("cwe", props.cwe.map(|c| c.join(", ")));
+    field("verification", props.verification);
+    if let Some(note) = entry.and_then(|e| e.note.as_ref()) {
+        lines.push(format!("{:<12} {}", style("note").dim(), note));
+    }
+
+    if !context.is_empty() {
+        lines.push(String::new());
+        lines.extend(context.iter().cloned());
+    }
+    lines.push(String::new());
+    lines.push(style(KEYS).dim().to_string());
+    lines
+}
+
+/// Source lines around the finding, read from `root`, falling back to the
+/// snippet the report carries.
+fn code_context(root: &Path, result: &SarifResult) -> Vec<String> {
+    let Some(location) = result.locations.first().map(|l| &l.physical_location) else {
+        return Vec::new();
+    };
+    let region = location.region.as_ref();
+    let start = region.map_or(1, |r| r.start_line.max(1) as usize);
+    let end = region
+        .and_then(|r| r.end_line)
+        .map_or(start, |e| (e.max(1) as usize).max(start));
+
+    let path = root.join(&location.artifact_location.uri);
+    match std::fs::read_to_string(&path) {
+        Ok(source) => source
+            .lines()
+            .enumerate()
+            .map(|(i, text)| (i + 1, text))
+            .skip(start.saturating_sub(CONTEXT_LINES + 1))
+            .take_while(|(n, _)| *n <= end + CONTEXT_LINES)
+            .map(|(n, text)| {
+                let marker = if (start..=end).contains(&n) { ">" } else { " " };
+                format!("{marker} {n:>5} | {text}")
+            })
+            .collect(),
+        Err(_) => region
+            .and_then(|r| r.snippet.as_ref())
+            .map(|s| s.text.lines().map(|l| format!("        | {l}")).collect())
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parsentry_reports::test_support::ResultFixture;
+
+    fn result(uri: &str, start: i32) -> SarifResult {
+        parsentry_reports::test_support::result("SQLI", uri)
+            .message("query built from input")
+            .line(start)
+            .snippet("db.execute(q)")
+            .confidence(0.9)
+            .properties(|p| {
+                p.principal = Some("request.args".to_string());
+                p.resource = Some("db".to_string());
+            })
+    }
+
+    #[test]
+    fn code_context_marks_finding_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let source: String = (1..=20).map(|n| format!("line {n}\n")).collect();
+        std::fs::write(dir.path().join("app.py"), source).unwrap();
+
+        let context = code_context(dir.path(), &result("app.py", 10));
+        assert_eq!(context.len(), 2 * CONTEXT_LINES + 1);
+        assert_eq!(context[0], "      5 | line 5");
+        assert_eq!(context[CONTEXT_LINES], ">    10 | line 10");
+
+        let fallback = code_context(dir.path(), &result("missing.py", 3));
+        assert_eq!(fallback, vec!["        | db.execute(q)"]);
+    }
+
+    #[test]
+    fn render_finding_shows_par_analysis_and_decision() {
+        let result = result("app.py", 3);
+        let mut triage = Triage::default();
+        let lines = render_finding(&result, 0, 2, None, &[]);
+        assert!(lines.iter().any(|l| l.contains("untriaged")));
+        assert!(lines.iter().any(|l| l.contains("request.args")));
+        assert!(lines.iter().any(|l| l.contains("90%")));
+
+        triage.decide(
+            &result,
+            Decision::AcceptedRisk,
+            Some("internal only".into()),
+        );
+        let lines = render_finding(&result, 0, 2, triage.get(&result), &[]);
+        assert!(lines.iter().any(|l| l.contains("accepted risk")));
+        assert!(lines.iter().any(|l| l.contains("internal only")));
+    }
+}
@@ -1,16 +1,214 @@
 use anyhow::Result;
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
+use crate::cli::args::{ReplayMissing, ScopeArgs, SweepArgs};
+use crate::cli::cancel::CancellationToken;
 use crate::cli::ui::StatusPrinter;
-use crate::prompt::{SurfacePrompt, build_all_surface_prompts, build_orchestrator_prompt};
+use crate::code_host::CloneOptions;
+use crate::commit_range::introduced_changes;
+use crate::config::config_hash;
+use crate::examples::{examples_dir, load_examples, select_examples};
+use crate::github::{GitHubSearchClient, RepositoryFilter};
+use crate::i18n::ResponseLanguage;
+use crate::knowledge::{detect_frameworks, packs_for};
+use crate::profile::{ProfileSettings, ScanProfile, WorkerTools};
+use crate::prompt::{
+    MAX_RELATED_FUNCTIONS, OrchestratorBatch, PromptTemplate, SurfacePrompt, Verification,
+    build_all_surface_prompts, build_multi_target_orchestrator_prompt, hex_sha256, scan_coverage,
+    surface_in_scope,
+};
+use crate::residency::SourceUpload;
+use parsentry_reports::feedback::FalsePositives;
+use parsentry_reports::output::write_atomic;
+use parsentry_reports::risk::REACHABLE_FILE;
 
-use parsentry_core::{RepoMetadata, ThreatModel};
+use parsentry_core::content_type::{ContentType, includes_generated};
+use parsentry_core::graphql::{self, RootField};
+use parsentry_core::offline::{is_offline, require_network};
+use parsentry_core::{
+    ConfigKind, DEPENDENCIES_FILE, Dependency, FileClassifier, FileDiscovery, Language, PathFilter,
+    RepoMetadata, ThreatModel,
+};
+use parsentry_parser::{
+    CallGraph, EntryPoint, ReachableSink, SecurityRiskPatterns, default_packs_dir, installed_packs,
+    reachable_sinks,
+};
+use parsentry_reports::binaries::{BINARIES_SARIF_FILE, BINARY_EXTENSIONS, scan_binaries};
+use parsentry_reports::consensus::majority;
+use parsentry_reports::introduced::INTRODUCED_FILE;
+use parsentry_reports::manifest::{PhaseTimer, SOURCE_UPLOAD_PARAMETER, ScanManifest};
+use parsentry_reports::merge::TIMED_OUT_FILE;
+use parsentry_reports::mobile::{MOBILE_CONFIG_EXTENSIONS, MOBILE_SARIF_FILE, scan_mobile_configs};
+use parsentry_reports::replay::ReplaySource;
 
-use super::common::{cache_dir_for, locate_repository, repo_name_from_target, write_stdout};
+use super::common::{
+    add_framework_entry_points, cache_dir_for, locate_repository_with, lock_cache_dir,
+    network_requirement, path_filter, repo_name_from_target, write_stdout,
+};
+
+/// Records the commit a target's results are complete for, so re-runs of a
+/// multi-target scan skip it without preparing it again.
+const SCAN_STATE_FILE: &str = "scan-state.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ScanState {
+    commit: String,
+    /// Hash of the threat model and options the results were produced with.
+    key: String,
+}
+
+/// Key of what a target's prompts depend on besides its code.
+fn scan_state_key(
+    project_cache: &Path,
+    suggest_fixes: bool,
+    profile: ScanProfile,
+    plan: &AnalysisPlan<'_>,
+    filter: &PathFilter,
+) -> String {
+    let model = std::fs::read_to_string(project_cache.join("model.json")).unwrap_or_default();
+    hex_sha256(&format!(
+        "{}\0{model}\0{}\0{:?}\0{}\0{}\0{}\0{}\0{suggest_fixes}\0{:?}\0{:?}\0{}\0{:?}\0{:?}",
+        env!("CARGO_PKG_VERSION"),
+        profile.name(),
+        plan.models,
+        plan.samples,
+        plan.reachability,
+        plan.report_binaries,
+        includes_generated(),
+        filter.include_patterns(),
+        filter.exclude_patterns(),
+        filter.respects_ignore_files(),
+        filter.listed_files(),
+        plan.commits,
+    ))
+}
+
+/// HEAD commit of the checkout at `root_dir`, unless it has uncommitted
+/// changes the commit does not describe.
+fn clean_commit(target: &str, root_dir: &Path) -> Option<String> {
+    let status = std::process::Command::new("git")
+        .args(["status", "--porcelain"])
+        .current_dir(root_dir)
+        .output()
+        .ok()?;
+    if !status.status.success() || !status.stdout.is_empty() {
+        return None;
+    }
+    super::history::head_commit(target)
+}
+
+/// Scan options and the worker settings they resolve to, by name, for the
+/// scan manifest.
+fn scan_parameters(
+    suggest_fixes: bool,
+    profile: ScanProfile,
+    settings: &ProfileSettings,
+    plan: &AnalysisPlan<'_>,
+    filter: &PathFilter,
+) -> BTreeMap<String, String> {
+    let optional = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    [
+        ("profile", profile.name().to_string()),
+        ("models", plan.models.join(",")),
+        ("fallback_models", settings.fallback_models.join(",")),
+        ("samples", plan.samples.to_string()),
+        ("quorum", optional(plan.quorum.map(|q| q.to_string()))),
+        ("verify", plan.verify.is_some().to_string()),
+        ("reachability", plan.reachability.to_string()),
+        ("report_binaries", plan.report_binaries.to_string()),
+        ("include_generated", includes_generated().to_string()),
+        ("suggest_fixes", suggest_fixes.to_string()),
+        ("deep_context", settings.deep_context.to_string()),
+        ("poc", settings.poc.to_string()),
+        (
+            "min_confidence",
+            optional(settings.min_confidence.map(|c| c.to_string())),
+        ),
+        ("read_only", settings.tools.read_only.to_string()),
+        ("shell", settings.tools.shell.to_string()),
+        ("language", plan.language.code().to_string()),
+        (SOURCE_UPLOAD_PARAMETER, plan.source.name().to_string()),
+        ("include", filter.include_patterns().join(",")),
+        ("exclude", filter.exclude_patterns().join(",")),
+        ("commits", optional(plan.commits.map(str::to_string))),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value))
+    .collect()
+}
+
+/// Manifest of a scan of `target`, without its timing.
+fn scan_manifest(
+    target: &str,
+    root_dir: &Path,
+    threat_model: &str,
+    settings: &ProfileSettings,
+    parameters: BTreeMap<String, String>,
+) -> ScanManifest {
+    let commit = super::history::head_commit(target);
+    let dirty = commit.is_some() && clean_commit(target, root_dir).is_none();
+    let mut patterns = BTreeMap::new();
+    let custom = SecurityRiskPatterns::custom_patterns_path(Some(root_dir));
+    if let Ok(content) = std::fs::read_to_string(&custom) {
+        patterns.insert(
+            custom
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            hex_sha256(&content),
+        );
+    }
+    for installed in default_packs_dir().map_or_else(Vec::new, |dir| installed_packs(&dir)) {
+        patterns.insert(
+            format!("pack:{}@{}", installed.pack.name, installed.pack.version),
+            installed.provenance.sha256,
+        );
+    }
+    let ci = [
+        ("GITHUB_ACTIONS", "github-actions"),
+        ("GITLAB_CI", "gitlab-ci"),
+        ("CI", "ci"),
+    ]
+    .into_iter()
+    .find(|(var, _)| std::env::var_os(var).is_some())
+    .map(|(_, name)| name);
+    let mut environment = BTreeMap::from([
+        ("os".to_string(), std::env::consts::OS.to_string()),
+        ("arch".to_string(), std::env::consts::ARCH.to_string()),
+    ]);
+    if let Some(ci) = ci {
+        environment.insert("ci".to_string(), ci.to_string());
+    }
+    ScanManifest {
+        tool_version: env!("CARGO_PKG_VERSION").to_string(),
+        commit,
+        dirty,
+        config_hash: config_hash(&std::env::vars().collect()),
+        threat_model_hash: Some(hex_sha256(threat_model)),
+        model: settings.model.clone(),
+        parameters,
+        patterns,
+        environment,
+        ..Default::default()
+    }
+}
+
+/// Whether the target's results are complete for `commit` and `key`.
+fn scan_complete(project_cache: &Path, commit: &str, key: &str) -> bool {
+    std::fs::read_to_string(project_cache.join(SCAN_STATE_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str::<ScanState>(&json).ok())
+        .is_some_and(|state| state.commit == commit && state.key == key)
+}
 
 /// Check if a surface has a cached SARIF result with a matching cache key.
 fn is_cached(output_dir: &Path, sp: &SurfacePrompt) -> bool {
-    let surface_dir = output_dir.join(&sp.surface_id);
+    let surface_dir = output_dir.join(sp.relative_dir());
     let sarif_path = surface_dir.join("result.sarif.json");
     let cache_key_path = surface_dir.join(".cache_key");
 
@@ -26,22 +224,427 @@ fn is_cached(output_dir: &Path, sp: &SurfacePrompt) -> bool {
 
 /// Write the cache key sidecar file for a surface.
 fn write_cache_key(output_dir: &Path, sp: &SurfacePrompt) -> Result<()> {
-    let cache_key_path = output_dir.join(&sp.surface_id).join(".cache_key");
-    std::fs::write(&cache_key_path, &sp.cache_key)?;
+    let cache_key_path = output_dir.join(sp.relative_dir()).join(".cache_key");
+    write_atomic(&cache_key_path, &sp.cache_key)?;
+    Ok(())
+}
+
+/// Write the worker prompt of `sp`, telling it where to put its SARIF, and
+/// its cache key. Returns the prompt path.
+pub(crate) fn write_surface_prompt(output_dir: &Path, sp: &SurfacePrompt) -> Result<PathBuf> {
+    let surface_dir = output_dir.join(sp.relative_dir());
+    std::fs::create_dir_all(&surface_dir)?;
+
+    let prompt_path = surface_dir.join("prompt.md");
+    let sarif_path = surface_dir.join("result.sarif.json");
+
+    let full_prompt = format!(
+        "{}\n\nWrite the SARIF JSON output to: {}\n\
+         Write ONLY valid JSON. No markdown, no code fences, no explanation.\n",
+        sp.prompt,
+        sarif_path.display()
+    );
+
+    write_atomic(&prompt_path, &full_prompt)?;
+    write_cache_key(output_dir, sp)?;
+    let _ = std::fs::remove_file(surface_dir.join(TIMED_OUT_FILE));
+    Ok(prompt_path)
+}
+
+/// Restore the stored result of every pending worker from `replay`.
+///
+/// Results are matched by cache key, so only identical prompts replay.
+/// Misses fail the scan before anything is written, or are skipped and
+/// have any stale result removed so it cannot leak into the report.
+fn replay_pending(
+    output_dir: &Path,
+    pending: &[&SurfacePrompt],
+    replay: &Replay,
+    printer: &StatusPrinter,
+) -> Result<()> {
+    let (found, missing): (Vec<_>, Vec<_>) = pending
+        .iter()
+        .map(|sp| (sp, replay.source.lookup(&sp.relative_dir(), &sp.cache_key)))
+        .partition(|(_, stored)| stored.is_some());
+    let missing: Vec<&SurfacePrompt> = missing.into_iter().map(|(sp, _)| *sp).collect();
+    if !missing.is_empty() && replay.missing == ReplayMissing::Fail {
+        let ids: Vec<String> = missing
+            .iter()
+            .map(|sp| sp.relative_dir().display().to_string())
+            .collect();
+        anyhow::bail!(
+            "no stored result for {} surfaces: {} (use --replay-missing skip to continue without them)",
+            ids.len(),
+            ids.join(", ")
+        );
+    }
+
+    for (sp, stored) in &found {
+        let surface_dir = output_dir.join(sp.relative_dir());
+        std::fs::create_dir_all(&surface_dir)?;
+        std::fs::copy(
+            stored.as_ref().expect("partitioned on Some"),
+            surface_dir.join("result.sarif.json"),
+        )?;
+        write_cache_key(output_dir, sp)?;
+    }
+    for sp in &missing {
+        let _ = std::fs::remove_file(output_dir.join(sp.relative_dir()).join("result.sarif.json"));
+        printer.warning(
+            "Skipped",
+            &format!("{}: no stored result", sp.relative_dir().display()),
+        );
+    }
+    printer.success(
+        "Replayed",
+        &format!(
+            "{} surfaces from stored results ({} skipped)",
+            found.len(),
+            missing.len()
+        ),
+    );
     Ok(())
 }
 
+/// Surfaces of one target that still need analysis.
+struct PreparedTarget {
+    target: String,
+    output_dir: PathBuf,
+    pending: Vec<SurfacePrompt>,
+}
+
+/// How each surface is analyzed: one analysis per model and sample,
+/// optionally followed by second-pass verification.
+#[derive(Debug, Clone, Copy)]
+pub struct AnalysisPlan<'a> {
+    /// Models to compare (`--compare-models`); empty for the default model.
+    pub models: &'a [String],
+    /// Worker model and its fallbacks (`--model`); empty for the profile's.
+    pub model_chain: &'a [String],
+    /// Independent samples per model (`--samples`).
+    pub samples: usize,
+    /// Members that must agree on a finding (`--quorum`).
+    pub quorum: Option<usize>,
+    /// Re-examine high-confidence findings (`--verify`).
+    pub verify: Option<&'a Verification>,
+    /// Only analyze surfaces with a sink reachable from a principal
+    /// (`--reachability`).
+    pub reachability: bool,
+    /// Report committed executables and libraries (`--report-binaries`).
+    pub report_binaries: bool,
+    /// Archive worker transcripts (`--save-transcripts`).
+    pub save_transcripts: bool,
+    /// Open report.md with a one-page executive summary
+    /// (`--executive-summary`).
+    pub executive_summary: bool,
+    /// Restore stored results instead of dispatching workers (`--replay`).
+    pub replay: Option<&'a Replay>,
+    /// Targets prepared at the same time (`--jobs`), independent of how
+    /// many workers the orchestrator runs.
+    pub jobs: usize,
+    /// Ctrl+C stops the scan after the targets in flight.
+    pub cancel: &'a CancellationToken,
+    /// Analyze every surface again, even targets whose results are
+    /// complete for their commit (`--refresh`).
+    pub refresh: bool,
+    /// How remote targets are cloned (`--sparse`).
+    pub clone: &'a CloneOptions,
+    /// Only analyze lines introduced by this commit range (`--commits`).
+    pub commits: Option<&'a str>,
+    /// Tools workers may use (`--read-only`, `--no-shell`).
+    pub tools: WorkerTools,
+    /// Language workers write findings in (`--language`).
+    pub language: ResponseLanguage,
+    /// How much source code prompts may carry (`--no-source-upload`).
+    pub source: SourceUpload,
+}
+
+/// Stored results for a deterministic re-run without any model calls.
+#[derive(Debug)]
+pub struct Replay {
+    pub source: ReplaySource,
+    pub missing: ReplayMissing,
+}
+
+impl AnalysisPlan<'_> {
+    /// Profile settings with the plan's worker tools and model chain.
+    fn settings(&self, profile: ScanProfile) -> ProfileSettings {
+        profile
+            .settings_with(self.tools)
+            .with_model_chain(self.model_chain)
+    }
+
+    fn members(&self) -> usize {
+        self.models.len().max(1) * self.samples
+    }
+
+    /// Validate `quorum`, defaulting to a strict majority when sampling.
+    fn resolve_quorum(&self) -> Result<Option<usize>> {
+        let members = self.members();
+        Ok(match self.quorum {
+            Some(k) if k > members => {
+                anyhow::bail!("--quorum {k} exceeds the number of analyses per surface ({members})")
+            }
+            Some(k) if members > 1 => Some(k),
+            Some(_) => anyhow::bail!("--quorum needs --samples or --compare-models"),
+            None if self.samples > 1 => Some(majority(members)),
+            None => None,
+        })
+    }
+}
+
+/// Repositories of the `--org`/`--topic` sweep, as `owner/repo` targets.
+pub async fn sweep_targets(sweep: &SweepArgs) -> Result<Vec<String>> {
+    let scope = match (&sweep.org, &sweep.topic) {
+        (Some(org), Some(topic)) => format!("{org} with topic {topic}"),
+        (Some(org), None) => org.clone(),
+        (None, Some(topic)) => format!("topic {topic}"),
+        (None, None) => return Ok(Vec::new()),
+    };
+    let filter = RepositoryFilter {
+        include_forks: sweep.include_forks,
+        include_archived: sweep.include_archived,
+    };
+    let repos = GitHubSearchClient::new()?
+        .list_repositories(
+            sweep.org.as_deref(),
+            sweep.topic.as_deref(),
+            filter,
+            sweep.max_repos,
+        )
+        .await?;
+    StatusPrinter::new().status("Sweep", &format!("{} repositories in {scope}", repos.len()));
+    Ok(repos.into_iter().map(|r| r.full_name).collect())
+}
+
+#[tracing::instrument(name = "scan", skip_all, fields(targets = targets.len(), profile = profile.name()))]
 pub async fn run_scan_command(
-    target: &str,
+    targets: &[String],
     _diff_base: Option<&str>,
     _filter_lang: Option<&str>,
+    suggest_fixes: bool,
+    profile: ScanProfile,
+    plan: AnalysisPlan<'_>,
+    scope: &ScopeArgs,
 ) -> Result<()> {
+    let filter = path_filter(scope)?;
+    let plan = AnalysisPlan {
+        quorum: plan.resolve_quorum()?,
+        ..plan
+    };
+    if plan.verify.is_some() && plan.quorum.is_none() && !plan.models.is_empty() {
+        // Without a quorum the models are only compared, never merged.
+        anyhow::bail!("--verify with --compare-models needs --quorum");
+    }
+    if is_offline() {
+        let fetches: Vec<String> = targets
+            .iter()
+            .filter_map(|t| network_requirement(t, plan.clone))
+            .collect();
+        require_network(&fetches)?;
+    }
+    let parsentry_bin = std::env::current_exe()?;
+
+    plan.cancel.observe();
+    let mut prepared = Vec::new();
+    let mut skipped = Vec::new();
+    let results = prepare_targets(
+        targets,
+        suggest_fixes,
+        profile,
+        plan,
+        &filter,
+        &parsentry_bin,
+    )
+    .await;
+    for (target, result) in targets.iter().zip(results) {
+        match result {
+            Some(result) => prepared.extend(result?),
+            None => skipped.push(target.as_str()),
+        }
+    }
+    if plan.cancel.is_cancelled() {
+        // Prompts of finished targets are kept; their orchestrators are in
+        // each reports directory.
+        StatusPrinter::new().warning(
+            "Interrupted",
+            &if skipped.is_empty() {
+                "all targets prepared, orchestrator not started".to_string()
+            } else {
+                format!("not started: {}", skipped.join(", "))
+            },
+        );
+        return Ok(());
+    }
+    if prepared.is_empty() {
+        return Ok(());
+    }
+
+    let batches: Vec<OrchestratorBatch<'_>> = prepared
+        .iter()
+        .map(|p| OrchestratorBatch {
+            target: &p.target,
+            output_dir: &p.output_dir,
+            prompts: &p.pending,
+            quorum: plan.quorum,
+            verify: plan.verify,
+            save_transcripts: plan.save_transcripts,
+            executive_summary: plan.executive_summary,
+        })
+        .collect();
+    let orchestrator_content =
+        build_multi_target_orchestrator_prompt(&batches, &parsentry_bin, &plan.settings(profile));
+    write_stdout(&format!("{}\n", orchestrator_content))?;
+
+    Ok(())
+}
+
+/// Prepare each of `targets`, up to `plan.jobs` at the same time, each on
+/// its own thread. Results are in target order; `None` for targets not
+/// started because of Ctrl+C or an earlier failure.
+async fn prepare_targets(
+    targets: &[String],
+    suggest_fixes: bool,
+    profile: ScanProfile,
+    plan: AnalysisPlan<'_>,
+    filter: &PathFilter,
+    parsentry_bin: &Path,
+) -> Vec<Option<Result<Option<PreparedTarget>>>> {
+    let mut results: Vec<Option<Result<Option<PreparedTarget>>>> =
+        targets.iter().map(|_| None).collect();
+    let jobs = plan.jobs.clamp(1, targets.len().max(1));
+    if jobs == 1 {
+        for (target, slot) in targets.iter().zip(results.iter_mut()) {
+            if plan.cancel.is_cancelled() {
+                break;
+            }
+            let result =
+                prepare_target(target, suggest_fixes, profile, plan, filter, parsentry_bin).await;
+            let failed = result.is_err();
+            *slot = Some(result);
+            if failed {
+                break;
+            }
+        }
+        return results;
+    }
+
+    let runtime = tokio::runtime::Handle::current();
+    let next = AtomicUsize::new(0);
+    let finished = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let results = Mutex::new(results);
+    let printer = StatusPrinter::new();
+    printer.status(
+        "Parallel",
+        &format!("preparing {} targets, {jobs} at a time", targets.len()),
+    );
+    tokio::task::block_in_place(|| {
+        std::thread::scope(|scope| {
+            for _ in 0..jobs {
+                scope.spawn(|| {
+                    while !plan.cancel.is_cancelled() && !failed.load(Ordering::Relaxed) {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(target) = targets.get(i) else {
+                            break;
+                        };
+                        let result = runtime.block_on(prepare_target(
+                            target,
+                            suggest_fixes,
+                            profile,
+                            plan,
+                            filter,
+                            parsentry_bin,
+                        ));
+                        failed.fetch_or(result.is_err(), Ordering::Relaxed);
+                        results.lock().expect("results lock poisoned")[i] = Some(result);
+                        let done = finished.fetch_add(1, Ordering::Relaxed) + 1;
+                        printer.status("Progress", &format!("{done}/{} targets", targets.len()));
+                    }
+                });
+            }
+        })
+    });
+    results.into_inner().expect("results lock poisoned")
+}
+
+/// Write prompts for one target's uncached surfaces and its own
+/// orchestrator prompt. Returns `None` when nothing needs analysis.
+#[tracing::instrument(name = "target", skip_all, fields(target = %target))]
+async fn prepare_target(
+    target: &str,
+    suggest_fixes: bool,
+    profile: ScanProfile,
+    plan: AnalysisPlan<'_>,
+    filter: &PathFilter,
+    parsentry_bin: &Path,
+) -> Result<Option<PreparedTarget>> {
     let printer = StatusPrinter::with_service(repo_name_from_target(target));
+    let mut timer = PhaseTimer::start();
+    let _lock = lock_cache_dir(target, &printer)?;
 
-    let (root_dir, _repo_name) = locate_repository(target, &printer).await?;
+    let (root_dir, _repo_name) = locate_repository_with(target, &printer, plan.clone).await?;
+    timer.lap("checkout");
+    let project_cache = cache_dir_for(target);
+    let introduced = plan
+        .commits
+        .map(|range| introduced_changes(&root_dir, range))
+        .transpose()?;
+    let range_filter;
+    let filter = match &introduced {
+        Some(changes) if changes.hunks.is_empty() => {
+            printer.warning(
+                "Commits",
+                &format!("no lines introduced by {}", changes.range),
+            );
+            return Ok(None);
+        }
+        Some(changes) => {
+            let files = changes.files();
+            let lines: usize = changes
+                .hunks
+                .iter()
+                .map(|h| h.end_line - h.start_line + 1)
+                .sum();
+            printer.status(
+                "Commits",
+                &format!(
+                    "{lines} lines in {} files introduced by {}",
+                    files.len(),
+                    changes.range
+                ),
+            );
+            let listed = filter.listed_files();
+            range_filter = filter.clone().with_files(
+                files
+                    .into_iter()
+                    .map(PathBuf::from)
+                    .filter(|f| listed.is_none_or(|l| l.contains(f))),
+            );
+            &range_filter
+        }
+        None => filter,
+    };
+    let commit = clean_commit(target, &root_dir);
+    let state_key = scan_state_key(&project_cache, suggest_fixes, profile, &plan, filter);
+    if let Some(commit) = &commit
+        && !plan.refresh
+        && scan_complete(&project_cache, commit, &state_key)
+    {
+        printer.success(
+            "Unchanged",
+            &format!(
+                "results complete for commit {} (--refresh to analyze again)",
+                &commit[..commit.len().min(12)]
+            ),
+        );
+        return Ok(None);
+    }
 
     // Phase 1: Collect repository metadata
-    let repo_metadata = RepoMetadata::collect(&root_dir)?;
+    let mut repo_metadata = tracing::info_span!("discovery")
+        .in_scope(|| RepoMetadata::collect_filtered(&root_dir, filter))?;
+    timer.lap("discovery");
     printer.status(
         "Collected",
         &format!(
@@ -52,7 +655,6 @@ pub async fn run_scan_command(
     );
 
     // Phase 2: Load threat model from per-repo cache
-    let project_cache = cache_dir_for(target);
     let threat_model_path = project_cache.join("model.json");
     let json = std::fs::read_to_string(&threat_model_path).map_err(|e| {
         anyhow::anyhow!(
@@ -62,7 +664,7 @@ pub async fn run_scan_command(
             target
         )
     })?;
-    let threat_model: ThreatModel = serde_json::from_str(&json).map_err(|e| {
+    let mut threat_model: ThreatModel = serde_json::from_str(&json).map_err(|e| {
         anyhow::anyhow!(
             "Invalid threat model JSON in {}: {}",
             threat_model_path.display(),
@@ -77,22 +679,377 @@ pub async fn run_scan_command(
         ),
     );
 
+    let before = threat_model.surfaces.len();
+    threat_model
+        .surfaces
+        .retain(|s| surface_in_scope(s, &root_dir, filter));
+    let skipped = before - threat_model.surfaces.len();
+    if skipped > 0 {
+        printer.status(
+            "Filtered",
+            &format!("{skipped} surfaces excluded by globs or ignore files"),
+        );
+    }
+
     // Phase 3: Generate per-surface prompts
     let output_dir = project_cache.join("reports");
     std::fs::create_dir_all(&output_dir)?;
 
-    let surface_prompts = build_all_surface_prompts(&threat_model, &root_dir);
+    let coverage = scan_coverage(&threat_model, &root_dir, repo_metadata.total_files);
+    coverage.save(&output_dir)?;
+    match &introduced {
+        Some(changes) => changes.save(&output_dir)?,
+        None => {
+            // A full scan must not be attributed to an earlier range.
+            let _ = std::fs::remove_file(output_dir.join(INTRODUCED_FILE));
+        }
+    }
+    // `merge` records them in the report.
+    write_atomic(
+        output_dir.join(DEPENDENCIES_FILE),
+        serde_json::to_string_pretty(&repo_metadata.dependencies)?,
+    )?;
+    let planned = coverage.resolve(&[]);
+    printer.status(
+        "Coverage",
+        &format!(
+            "{} files discovered, {} matched by surfaces",
+            planned.discovered, planned.matched
+        ),
+    );
+    if !planned.skipped.is_empty() {
+        let mut reasons = std::collections::BTreeMap::new();
+        for skipped in &planned.skipped {
+            *reasons.entry(skipped.reason.to_string()).or_insert(0) += 1;
+        }
+        let reasons: Vec<String> = reasons.iter().map(|(r, n)| format!("{n} {r}")).collect();
+        printer.warning(
+            "Skipped",
+            &format!(
+                "{} files left out of prompts ({})",
+                planned.skipped.len(),
+                reasons.join(", ")
+            ),
+        );
+    }
+
+    let settings = plan.settings(profile);
+    if profile != ScanProfile::Standard {
+        printer.status("Profile", profile.name());
+    }
+    let discovered = FileDiscovery::new(root_dir.clone())
+        .with_filter(filter.clone())
+        .discover()
+        .unwrap_or_default();
+    let (binary, minified, generated) = (
+        discovered.skipped_count(ContentType::Binary),
+        discovered.skipped_count(ContentType::Minified),
+        discovered.skipped_count(ContentType::Generated),
+    );
+    if binary + minified + generated > 0 {
+        printer.status(
+            "Skipped",
+            &format!(
+                "{binary} binary, {minified} minified and {generated} generated files with source \
+                 extensions"
+            ),
+        );
+    }
+    let files = discovered.files;
+    if plan.report_binaries {
+        let candidates = FileDiscovery::with_extensions(
+            root_dir.clone(),
+            BINARY_EXTENSIONS.iter().map(|e| e.to_string()).collect(),
+        )
+        .with_filter(filter.clone())
+        .discover()
+        .unwrap_or_default();
+        let candidates: Vec<PathBuf> = candidates
+            .skipped
+            .into_iter()
+            .chain(discovered.skipped)
+            .filter(|(_, content)| *content == ContentType::Binary)
+            .map(|(path, _)| path)
+            .collect();
+        let binaries = scan_binaries(&root_dir, &candidates);
+        let count = binaries.runs.iter().map(|r| r.results.len()).sum::<usize>();
+        binaries.save_to_file(output_dir.join(BINARIES_SARIF_FILE))?;
+        printer.status(
+            "Binaries",
+            &format!("{count} committed executables or libraries"),
+        );
+    } else {
+        let _ = std::fs::remove_file(output_dir.join(BINARIES_SARIF_FILE));
+    }
+    let frameworks = detect_frameworks(&repo_metadata.dependency_manifests);
+    if !frameworks.is_empty() {
+        let names: Vec<&str> = frameworks.iter().map(|p| p.framework).collect();
+        printer.status("Frameworks", &names.join(", "));
+    }
+    let mobile_configs = FileDiscovery::with_extensions(
+        root_dir.clone(),
+        MOBILE_CONFIG_EXTENSIONS
+            .iter()
+            .map(|e| e.to_string())
+            .collect(),
+    )
+    .with_filter(filter.clone())
+    .get_files()
+    .unwrap_or_default();
+    let mobile = scan_mobile_configs(&root_dir, &mobile_configs);
+    let mobile_findings = mobile.runs.iter().map(|r| r.results.len()).sum::<usize>();
+    if mobile_findings > 0 {
+        // `merge` folds it in with the workers' results.
+        mobile.save_to_file(output_dir.join(MOBILE_SARIF_FILE))?;
+        printer.status(
+            "Mobile",
+            &format!("{mobile_findings} findings in app manifests and entitlements"),
+        );
+    } else {
+        let _ = std::fs::remove_file(output_dir.join(MOBILE_SARIF_FILE));
+    }
+    let entry_points = add_framework_entry_points(&mut repo_metadata, &files);
+    if !entry_points.is_empty() {
+        printer.status(
+            "Entry points",
+            &format!(
+                "{} route handlers, commands and consumers",
+                entry_points.len()
+            ),
+        );
+    }
+    let call_graph = tracing::info_span!("call_graph")
+        .in_scope(|| CallGraph::build_indexed(&root_dir, &files, &project_cache.join("graph")));
+    timer.lap("call_graph");
+    let surface_files = |sp: &SurfacePrompt| {
+        coverage
+            .surfaces
+            .get(&sp.surface_id)
+            .cloned()
+            .unwrap_or_default()
+    };
+    let template = PromptTemplate::load()?;
+    let examples = examples_dir()
+        .map(|dir| load_examples(&dir))
+        .transpose()?
+        .unwrap_or_default();
+    let mut false_positives = FalsePositives::for_repository(&project_cache, &root_dir)?;
+    for exemplar in &mut false_positives.exemplars {
+        exemplar.code = exemplar
+            .code
+            .as_deref()
+            .and_then(|code| plan.source.quote(code));
+    }
+    if !false_positives.exemplars.is_empty() {
+        printer.status(
+            "Feedback",
+            &format!(
+                "{} known false positives shown to workers",
+                false_positives.exemplars.len()
+            ),
+        );
+    }
+    if plan.source.reduced_precision() {
+        printer.warning(
+            "Precision",
+            "workers analyze without reading source code (--no-source-upload=hashed)",
+        );
+    }
+    let mut surface_prompts =
+        build_all_surface_prompts(&threat_model, &root_dir, template.as_ref());
+    if plan.reachability {
+        let mut sinks = tracing::info_span!("reachability")
+            .in_scope(|| reachable_sinks(&root_dir, &files, &call_graph));
+        for sink in &mut sinks {
+            sink.text = plan.source.quote(&sink.text).unwrap_or_default();
+        }
+        // A surface keeps the sinks whose call path passes through its files.
+        let surface_sinks = |sp: &SurfacePrompt| -> Vec<ReachableSink> {
+            let files = surface_files(sp);
+            sinks
+                .iter()
+                .filter(|s| {
+                    files.contains(&s.file)
+                        || files.contains(&s.principal_file)
+                        || s.path.iter().any(|step| files.contains(&step.file))
+                })
+                .cloned()
+                .collect()
+        };
+        let before = surface_prompts.len();
+        surface_prompts = surface_prompts
+            .into_iter()
+            .filter_map(|sp| {
+                let sinks = surface_sinks(&sp);
+                (!sinks.is_empty()).then(|| sp.with_reachable_sinks(&sinks))
+            })
+            .collect();
+        printer.status(
+            "Reachability",
+            &format!(
+                "{} sinks reachable from principals; {}/{before} surfaces kept",
+                sinks.len(),
+                surface_prompts.len()
+            ),
+        );
+        if surface_prompts.is_empty() {
+            printer.warning("Scan", "no surface reaches a sink from a principal");
+            return Ok(None);
+        }
+    }
+    let mut surface_prompts: Vec<SurfacePrompt> = surface_prompts
+        .into_iter()
+        .map(|sp| {
+            let files = surface_files(&sp);
+            let mut related = call_graph.related(&files, MAX_RELATED_FUNCTIONS);
+            for function in &mut related {
+                function.snippet = plan.source.quote(&function.snippet).unwrap_or_default();
+            }
+            let languages: Vec<Language> = files
+                .iter()
+                .map(|f| match Language::from_filename(f) {
+                    Language::Other => FileClassifier::classify_file(&root_dir.join(f)),
+                    language => language,
+                })
+                .collect();
+            let shown = threat_model
+                .surfaces
+                .iter()
+                .find(|s| s.id == sp.surface_id)
+                .map(|surface| select_examples(&examples, surface, &languages))
+                .unwrap_or_default();
+            let extensions: Vec<String> = files
+                .iter()
+                .filter_map(|f| Some(Path::new(f).extension()?.to_string_lossy().to_lowercase()))
+                .collect();
+            let handlers: Vec<&EntryPoint> = entry_points
+                .iter()
+                .filter(|e| files.contains(&e.file))
+                .collect();
+            let mut config_kinds: Vec<ConfigKind> = files
+                .iter()
+                .filter_map(|f| FileClassifier::config_kind(&root_dir.join(f)))
+                .collect();
+            config_kinds.sort();
+            config_kinds.dedup();
+            let schema_fields: Vec<RootField> = files
+                .iter()
+                .filter(|f| graphql::is_graphql_file(f))
+                .filter_map(|f| {
+                    let sdl = std::fs::read_to_string(root_dir.join(f)).ok()?;
+                    Some(graphql::root_fields(f, &sdl))
+                })
+                .flatten()
+                .collect();
+            let deps: Vec<&Dependency> = repo_metadata
+                .dependencies
+                .iter()
+                .filter(|d| d.languages().iter().any(|l| languages.contains(l)))
+                .collect();
+            let sp = sp
+                .with_dependencies(&deps)
+                .with_framework_guidance(&packs_for(&frameworks, &languages))
+                .with_config_guidance(&config_kinds)
+                .with_graphql_schema(&schema_fields.iter().collect::<Vec<_>>())
+                .with_entry_points(&handlers)
+                .with_related_functions(&related)
+                .with_examples(&shown)
+                .with_false_positives(&false_positives.for_languages(&extensions))
+                .with_response_language(plan.language)
+                .with_source_upload(plan.source)
+                .with_profile(profile, &settings)
+                .with_tool_restrictions(settings.tools)
+                .with_scope(filter);
+            let sp = if settings.fallback_models.is_empty() || !plan.models.is_empty() {
+                sp
+            } else {
+                sp.with_model_tag()
+            };
+            let sp = if settings.poc && settings.tools.shell {
+                sp.with_poc_sandbox(parsentry_bin)
+            } else {
+                sp
+            };
+            match &introduced {
+                Some(changes) => {
+                    sp.with_introduced_changes(&changes.range, &changes.hunks_in(&files))
+                }
+                None => sp,
+            }
+        })
+        .collect();
+    // Surfaces reachable from entry points are dispatched first.
+    let reachable_path = project_cache.join(REACHABLE_FILE);
+    let _ = std::fs::remove_file(&reachable_path);
+    if !repo_metadata.entry_points.is_empty() {
+        let reachable = call_graph.reachable_from(&repo_metadata.entry_points);
+        // `generate` weighs findings' risk by it.
+        let mut files: Vec<&String> = reachable.iter().collect();
+        files.sort();
+        write_atomic(&reachable_path, serde_json::to_string(&files)?)?;
+        let is_reachable =
+            |sp: &SurfacePrompt| surface_files(sp).iter().any(|f| reachable.contains(f));
+        surface_prompts.sort_by_key(|sp| !is_reachable(sp));
+        let first = surface_prompts.iter().filter(|sp| is_reachable(sp)).count();
+        if first > 0 {
+            printer.status(
+                "Prioritized",
+                &format!(
+                    "{first}/{} surfaces reachable from entry points",
+                    surface_prompts.len()
+                ),
+            );
+        }
+    }
+    if !plan.models.is_empty() {
+        printer.status("Compare", &plan.models.join(", "));
+        surface_prompts = surface_prompts
+            .iter()
+            .flat_map(|sp| plan.models.iter().map(|m| sp.for_model(m)))
+            .collect();
+    }
+    let samples = plan.samples;
+    if samples > 1 {
+        printer.status("Samples", &format!("{samples} per surface"));
+        surface_prompts = surface_prompts
+            .iter()
+            .flat_map(|sp| (1..=samples).map(|n| sp.for_sample(n, samples)))
+            .collect();
+    }
+    if suggest_fixes {
+        surface_prompts = surface_prompts
+            .into_iter()
+            .map(|sp| {
+                let fixes_dir = output_dir.join(sp.relative_dir()).join("fixes");
+                sp.with_fix_suggestions(&fixes_dir)
+            })
+            .collect();
+    }
 
     if surface_prompts.is_empty() {
         printer.warning("Scan", "no surfaces had readable source files");
-        return Ok(());
+        return Ok(None);
     }
+    timer.lap("prompts");
+
+    let mut manifest = scan_manifest(
+        target,
+        &root_dir,
+        &json,
+        &settings,
+        scan_parameters(suggest_fixes, profile, &settings, &plan, filter),
+    );
+    timer.finish(&mut manifest);
+    // Read before the target's own manifest is replaced, in case the
+    // replay restores this cache's results.
+    let replayed_manifest = plan.replay.and_then(|replay| replay.source.manifest());
+    manifest.save(&output_dir)?;
 
     // Partition into cached and new surfaces
     let mut cached: Vec<&SurfacePrompt> = Vec::new();
     let mut pending: Vec<&SurfacePrompt> = Vec::new();
     for sp in &surface_prompts {
-        if is_cached(&output_dir, sp) {
+        if !plan.refresh && is_cached(&output_dir, sp) {
             cached.push(sp);
         } else {
             pending.push(sp);
@@ -106,7 +1063,42 @@ pub async fn run_scan_command(
         );
     }
 
+    if let Some(replay) = plan.replay {
+        for difference in replayed_manifest
+            .map(|stored| manifest.differences(&stored))
+            .unwrap_or_default()
+        {
+            printer.warning(
+                "Manifest",
+                &format!("differs from the replayed scan: {difference}"),
+            );
+        }
+        replay_pending(&output_dir, &pending, replay, &printer)?;
+        printer.status(
+            "Next",
+            &format!("run `parsentry generate {target}` to build reports from the replay"),
+        );
+        return Ok(None);
+    }
+
+    // Offline runs are cache-only: pending surfaces would need a worker.
+    let analyses: Vec<String> = pending
+        .iter()
+        .map(|sp| format!("{target}: worker analysis of {}", sp.worker_id()))
+        .collect();
+    require_network(&analyses)?;
+
     if pending.is_empty() {
+        if let Some(commit) = commit {
+            let state = ScanState {
+                commit,
+                key: state_key,
+            };
+            write_atomic(
+                project_cache.join(SCAN_STATE_FILE),
+                serde_json::to_string_pretty(&state)?,
+            )?;
+        }
         printer.success(
             "Complete",
             &format!(
@@ -115,42 +1107,49 @@ pub async fn run_scan_command(
                 output_dir.display()
             ),
         );
-        return Ok(());
+        return Ok(None);
     }
 
     // Write prompts only for pending (non-cached) surfaces
+    let _ = std::fs::remove_file(project_cache.join(SCAN_STATE_FILE));
     printer.section("Prompts");
     for sp in &pending {
-        let surface_dir = output_dir.join(&sp.surface_id);
-        std::fs::create_dir_all(&surface_dir)?;
-
-        let prompt_path = surface_dir.join("prompt.md");
-        let sarif_path = surface_dir.join("result.sarif.json");
-
-        let full_prompt = format!(
-            "{}\n\nWrite the SARIF JSON output to: {}\n\
-             Write ONLY valid JSON. No markdown, no code fences, no explanation.\n",
-            sp.prompt,
-            sarif_path.display()
-        );
-
-        std::fs::write(&prompt_path, &full_prompt)?;
-        write_cache_key(&output_dir, sp)?;
+        if suggest_fixes {
+            std::fs::create_dir_all(output_dir.join(sp.relative_dir()).join("fixes"))?;
+        }
+        if plan.refresh {
+            // The prompt is unchanged, so a stale result would count as cached.
+            let _ =
+                std::fs::remove_file(output_dir.join(sp.relative_dir()).join("result.sarif.json"));
+        }
+        let prompt_path = write_surface_prompt(&output_dir, sp)?;
 
-        printer.bullet(&format!("{} → {}", sp.surface_id, prompt_path.display()));
+        let label = match &sp.model {
+            Some(model) => format!("{}@{model}", sp.surface_id),
+            None => sp.surface_id.clone(),
+        };
+        printer.bullet(&format!("{label} → {}", prompt_path.display()));
     }
 
     // Phase 4: Generate orchestrator prompt only for pending surfaces
     let pending_owned: Vec<SurfacePrompt> = pending.iter().map(|s| (*s).clone()).collect();
-    let parsentry_bin = std::env::current_exe()?;
-    let orchestrator_content =
-        build_orchestrator_prompt(&pending_owned, &output_dir, target, &parsentry_bin);
+    let orchestrator_content = build_multi_target_orchestrator_prompt(
+        &[OrchestratorBatch {
+            target,
+            output_dir: &output_dir,
+            prompts: &pending_owned,
+            quorum: plan.quorum,
+            verify: plan.verify,
+            save_transcripts: plan.save_transcripts,
+            executive_summary: plan.executive_summary,
+        }],
+        parsentry_bin,
+        &settings,
+    );
     let orchestrator_path = output_dir.join("orchestrator.prompt.md");
-    std::fs::write(&orchestrator_path, &orchestrator_content)?;
+    write_atomic(&orchestrator_path, &orchestrator_content)?;
     printer.bullet(&format!("orchestrator → {}", orchestrator_path.display()));
 
-    write_stdout(&format!("{}\n", orchestrator_content))?;
-
     printer.success(
         "Complete",
         &format!(
@@ -161,5 +1160,9 @@ pub async fn run_scan_command(
         ),
     );
 
-    Ok(())
+    Ok(Some(PreparedTarget {
+        target: target.to_string(),
+        output_dir,
+        pending: pending_owned,
+    }))
 }
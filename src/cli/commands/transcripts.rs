@@ -0,0 +1,133 @@
+use anyhow::Result;
+use std::path::Path;
+
+use super::common::{cache_dir_for, repo_name_from_target, worker_dirs};
+use crate::cli::ui::StatusPrinter;
+use parsentry_reports::SarifReport;
+use parsentry_reports::output::write_atomic;
+use parsentry_reports::redact::Redactor;
+use parsentry_reports::repair::RAW_RESULT_FILE;
+use parsentry_reports::transcript::{
+    TRANSCRIPT_META_FILE, TRANSCRIPTS_DIR, TranscriptMeta, duration_secs, transcript_id,
+};
+
+/// Archive prompt, raw output, token counts and timing of every analysis
+/// worker under `reports/transcripts/`, and tag its findings with the
+/// transcript id. With `PARSENTRY_REDACT`, secrets in the archived files
+/// are redacted.
+pub fn run_transcripts_command(target: &str) -> Result<()> {
+    let printer = StatusPrinter::with_service(repo_name_from_target(target));
+    let reports_dir = cache_dir_for(target).join("reports");
+    let redactor = Redactor::from_env()?;
+    // Agent sessions are stored per working directory of the orchestrator.
+    let sessions_dir = parsentry_claude::project_sessions_dir(&std::env::current_dir()?)?;
+
+    let (mut archived, mut with_session) = (0, 0);
+    for dir in worker_dirs(&reports_dir)? {
+        let result_path = dir.join("result.sarif.json");
+        if !result_path.exists() {
+            continue;
+        }
+        let relative = dir.strip_prefix(&reports_dir).unwrap_or(&dir);
+        let id = transcript_id(relative);
+        let out = reports_dir.join(TRANSCRIPTS_DIR).join(&id);
+        let cache_key = std::fs::read_to_string(dir.join(".cache_key"))
+            .map(|k| k.trim().to_string())
+            .unwrap_or_default();
+        if is_current(&out, &cache_key) {
+            continue;
+        }
+        std::fs::create_dir_all(&out)?;
+        std::fs::copy(dir.join("prompt.md"), out.join("prompt.md"))?;
+
+        let mut meta = TranscriptMeta {
+            id: id.clone(),
+            worker: relative.display().to_string(),
+            cache_key,
+            ..Default::default()
+        };
+        let prompt_path = dir.join("prompt.md").display().to_string();
+        if let Some(session) =
+            parsentry_claude::find_subagent_transcript(&sessions_dir, &prompt_path)?
+        {
+            std::fs::copy(&session, out.join("session.jsonl"))?;
+            let stats = parsentry_claude::transcript_stats(&session)?;
+            meta.output = Some("session.jsonl".to_string());
+            meta.session = Some(session.display().to_string());
+            meta.input_tokens = Some(stats.input_tokens);
+            meta.output_tokens = Some(stats.output_tokens);
+            meta.started_at = stats.started_at;
+            meta.ended_at = stats.ended_at;
+            with_session += 1;
+        } else {
+            // Without a session, the worker's own output is the closest
+            // thing to the raw response.
+            if dir.join(RAW_RESULT_FILE).exists() {
+                std::fs::copy(dir.join(RAW_RESULT_FILE), out.join(RAW_RESULT_FILE))?;
+                meta.output = Some(RAW_RESULT_FILE.to_string());
+            } else {
+                meta.output = Some("result.sarif.json".to_string());
+            }
+        }
+
+        let mut report = SarifReport::from_file(&result_path).ok();
+        if let Some(invocation) = report
+            .as_ref()
+            .and_then(|r| r.runs.first())
+            .and_then(|r| r.invocation.as_ref())
+        {
+            meta.model = invocation.properties.as_ref().and_then(|p| p.model.clone());
+            meta.started_at = meta.started_at.or(invocation.start_time_utc.clone());
+            meta.ended_at = meta.ended_at.or(invocation.end_time_utc.clone());
+        }
+        if let (Some(start), Some(end)) = (&meta.started_at, &meta.ended_at) {
+            meta.duration_secs = duration_secs(start, end);
+        }
+        write_atomic(
+            out.join(TRANSCRIPT_META_FILE),
+            serde_json::to_string_pretty(&meta)?,
+        )?;
+
+        if let Some(report) = report.as_mut() {
+            for result in report.runs.iter_mut().flat_map(|r| &mut r.results) {
+                result
+                    .properties
+                    .get_or_insert_with(Default::default)
+                    .transcript = Some(id.clone());
+            }
+            report.save_to_file(&result_path)?;
+        }
+        // The final result makes the transcript usable by `scan --replay`.
+        std::fs::copy(&result_path, out.join("result.sarif.json"))?;
+        if let Some(redactor) = &redactor {
+            for name in [
+                "prompt.md",
+                "session.jsonl",
+                RAW_RESULT_FILE,
+                "result.sarif.json",
+            ] {
+                if out.join(name).exists() {
+                    redactor.redact_file(&out.join(name))?;
+                }
+            }
+        }
+        archived += 1;
+    }
+
+    printer.status(
+        "Archived",
+        &format!(
+            "{archived} transcripts ({with_session} with agent sessions) in {}",
+            reports_dir.join(TRANSCRIPTS_DIR).display()
+        ),
+    );
+    Ok(())
+}
+
+/// Whether `out` already holds the transcript of the result keyed `cache_key`.
+fn is_current(out: &Path, cache_key: &str) -> bool {
+    std::fs::read_to_string(out.join(TRANSCRIPT_META_FILE))
+        .ok()
+        .and_then(|s| serde_json::from_str::<TranscriptMeta>(&s).ok())
+        .is_some_and(|m| !cache_key.is_empty() && m.cache_key == cache_key)
+}
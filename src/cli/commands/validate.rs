@@ -0,0 +1,87 @@
+use anyhow::Result;
+
+use super::common::{cache_dir_for, repo_name_from_target, worker_dirs, write_stdout};
+use crate::cli::ui::StatusPrinter;
+use crate::prompt::build_repair_prompt;
+use parsentry_reports::merge::TIMED_OUT_FILE;
+use parsentry_reports::output::write_atomic;
+use parsentry_reports::redact::Redactor;
+use parsentry_reports::repair::{
+    DIAGNOSTICS_FILE, RAW_RESULT_FILE, Validation, validate_result_file,
+};
+
+/// Prompt written next to an output that needs a repair worker.
+const REPAIR_PROMPT_FILE: &str = "repair.prompt.md";
+
+/// Check every worker's `result.sarif.json`, repair what can be fixed in
+/// place and print a repair worker for each remaining failure. With
+/// `PARSENTRY_REDACT`, secrets in the cached outputs are redacted.
+pub fn run_validate_command(target: &str) -> Result<()> {
+    let printer = StatusPrinter::with_service(repo_name_from_target(target));
+    let reports_dir = cache_dir_for(target).join("reports");
+    let redactor = Redactor::from_env()?;
+    let mut redacted = 0;
+
+    let mut assignments = String::new();
+    for dir in worker_dirs(&reports_dir)? {
+        let id = dir
+            .strip_prefix(&reports_dir)
+            .unwrap_or(&dir)
+            .display()
+            .to_string();
+        let result_path = dir.join("result.sarif.json");
+        if !result_path.exists() && dir.join(TIMED_OUT_FILE).exists() {
+            // Given up on after its deadline: reported as not analyzed.
+            printer.warning("Timeout", &id);
+            continue;
+        }
+        let errors = if result_path.exists() {
+            match validate_result_file(&result_path)? {
+                Validation::Valid(_) => None,
+                Validation::Repaired { repairs, .. } => {
+                    printer.status("Repaired", &format!("{id}: {}", repairs.join(", ")));
+                    None
+                }
+                Validation::Invalid(errors) => Some(errors),
+            }
+        } else {
+            let errors = vec!["no result.sarif.json was written".to_string()];
+            write_atomic(
+                dir.join(DIAGNOSTICS_FILE),
+                format!("{}\n- {}\n", result_path.display(), errors[0]),
+            )?;
+            Some(errors)
+        };
+        if let Some(redactor) = &redactor {
+            for path in [result_path.clone(), dir.join(RAW_RESULT_FILE)] {
+                if path.exists() {
+                    redacted += redactor.redact_file(&path)?;
+                }
+            }
+        }
+        let repair_path = dir.join(REPAIR_PROMPT_FILE);
+        let Some(errors) = errors else {
+            let _ = std::fs::remove_file(&repair_path);
+            continue;
+        };
+
+        printer.warning("Invalid", &format!("{id}: {}", errors.join("; ")));
+        let prompt = build_repair_prompt(&dir.join("prompt.md"), &result_path, &errors);
+        write_atomic(&repair_path, prompt)?;
+        assignments.push_str(&format!(
+            "- Worker `{id}/repair`: read `{}` and execute the instructions in it.\n",
+            repair_path.display()
+        ));
+    }
+
+    if redacted > 0 {
+        printer.status(
+            "Redacted",
+            &format!("{redacted} secrets or personal data in worker outputs"),
+        );
+    }
+    if assignments.is_empty() {
+        return write_stdout("All worker outputs are valid.\n");
+    }
+    write_stdout(&format!("Repair Workers\n\n{assignments}"))
+}
@@ -0,0 +1,521 @@
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::common::{cache_dir_for, locate_repository, write_stdout};
+use super::scan::write_surface_prompt;
+use crate::cli::ui::StatusPrinter;
+use crate::github::GitHubSearchClient;
+use crate::profile::ScanProfile;
+use crate::prompt::{
+    OrchestratorBatch, SurfacePrompt, build_multi_target_orchestrator_prompt, build_surface_prompt,
+};
+use parsentry_core::offline::require_network;
+use parsentry_core::{AttackSurface, FileDiscovery, Language};
+use parsentry_parser::{SecurityRiskPatterns, Shingles, cluster_snippets};
+use parsentry_reports::sarif::SarifReport;
+
+/// Code search results fetched per candidate repository requested.
+const SEARCH_RESULTS_PER_REPO: usize = 5;
+
+/// Longest seed text quoted in a code search query.
+const MAX_QUERY_TEXT: usize = 80;
+
+/// Lines around an occurrence that are compared with the seed.
+const SNIPPET_CONTEXT: usize = 1;
+
+/// Similarity at which occurrences are grouped as the same code.
+const CLUSTER_SIMILARITY: f64 = 0.6;
+
+/// Known vulnerability whose variants to look for.
+#[derive(Debug, Clone)]
+pub enum VariantSeed {
+    /// A line of a file.
+    Line { file: PathBuf, line: usize },
+    /// The first line of a file containing some text.
+    Text { file: PathBuf, text: String },
+    /// A finding of a SARIF report: the one with this fingerprint, or the first.
+    Finding {
+        sarif: PathBuf,
+        fingerprint: Option<String>,
+    },
+}
+
+/// What a seed is searched for by: a GitHub code search query, and the PAR
+/// pattern or text that confirms a candidate file.
+#[derive(Debug, Clone, Serialize)]
+pub struct Variant {
+    /// Seed location, `file:line`.
+    pub seed: String,
+    /// What the seed finding is about.
+    pub message: String,
+    pub language: Language,
+    /// PAR pattern matching the seed line, if any.
+    pub pattern: Option<String>,
+    /// Code the variant contains: the pattern's match at the seed, or the
+    /// seed line itself.
+    pub text: String,
+    /// The seed line with the lines around it, which occurrences in
+    /// candidate repositories are compared with.
+    pub snippet: String,
+    pub query: String,
+}
+
+/// Where a candidate repository contains the variant.
+struct Occurrence {
+    repo: String,
+    file: String,
+    line: usize,
+    /// The line, trimmed.
+    text: String,
+    /// Shingles of the line and the lines around it.
+    shingles: Shingles,
+    /// Similarity to the seed's snippet.
+    similarity: f64,
+}
+
+/// Derive a variant search from `seed`, search GitHub for repositories with
+/// similar code, and write an analysis prompt for every file there that
+/// matches the variant. Occurrences less than `min_similarity` like the
+/// seed are left out; the rest are reported grouped by similarity. Prints
+/// the orchestrator prompt for them.
+pub async fn run_variants_command(
+    seed: VariantSeed,
+    root: &Path,
+    query: Option<String>,
+    limit: usize,
+    min_similarity: f64,
+    dry_run: bool,
+) -> Result<()> {
+    let printer = StatusPrinter::new();
+    let mut variant = derive_variant(&seed, root)?;
+    if let Some(query) = query {
+        variant.query = query;
+    }
+    printer.status("Seed", &format!("{} ({})", variant.seed, variant.message));
+    printer.status(
+        "Pattern",
+        variant
+            .pattern
+            .as_deref()
+            .unwrap_or("none; matching the seed text"),
+    );
+    printer.status("Query", &variant.query);
+    if dry_run {
+        return write_stdout(&format!("{}\n", serde_json::to_string_pretty(&variant)?));
+    }
+
+    require_network(&[format!("search GitHub code for `{}`", variant.query)])?;
+    let results = GitHubSearchClient::new()?
+        .search_code(&variant.query, limit * SEARCH_RESULTS_PER_REPO)
+        .await?;
+    let mut repos: Vec<String> = Vec::new();
+    for result in results {
+        if repos.len() < limit && !repos.contains(&result.repository.full_name) {
+            repos.push(result.repository.full_name);
+        }
+    }
+    printer.status("Candidates", &format!("{} repositories", repos.len()));
+
+    let matcher = SecurityRiskPatterns::new_with_root(variant.language, Some(root));
+    let seed_shingles = Shingles::of(&variant.snippet);
+    let mut occurrences: Vec<Occurrence> = Vec::new();
+    let mut prepared: Vec<(String, PathBuf, SurfacePrompt)> = Vec::new();
+    for repo in &repos {
+        let repo_printer = StatusPrinter::with_service(repo.clone());
+        let (repo_root, _) = match locate_repository(repo, &repo_printer).await {
+            Ok(located) => located,
+            Err(e) => {
+                repo_printer.warning("Skipped", &format!("{e:#}"));
+                continue;
+            }
+        };
+        let hits = variant_hits(&variant, &matcher, &repo_root);
+        let found: Vec<Occurrence> = variant_occurrences(repo, &repo_root, &hits, &seed_shingles)
+            .into_iter()
+            .filter(|o| o.similarity >= min_similarity)
+            .collect();
+        if found.is_empty() {
+            repo_printer.status("Clean", "no file matches the variant");
+            continue;
+        }
+        let mut hits: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for occurrence in &found {
+            hits.entry(occurrence.file.clone())
+                .or_default()
+                .push(occurrence.line);
+        }
+        occurrences.extend(found);
+        let Some(sp) = build_surface_prompt(&variant_surface(&variant, &hits), &repo_root) else {
+            continue;
+        };
+        let output_dir = cache_dir_for(repo).join("reports");
+        let prompt_path = write_surface_prompt(&output_dir, &sp)?;
+        repo_printer.status(
+            "Variant",
+            &format!("{} files → {}", hits.len(), prompt_path.display()),
+        );
+        prepared.push((repo.clone(), output_dir, sp));
+    }
+    if prepared.is_empty() {
+        printer.success("Complete", "no candidate repository contains the variant");
+        return Ok(());
+    }
+    report_clusters(&occurrences, &printer);
+
+    let prompts: Vec<[SurfacePrompt; 1]> = prepared.iter().map(|(_, _, sp)| [sp.clone()]).collect();
+    let batches: Vec<OrchestratorBatch<'_>> = prepared
+        .iter()
+        .zip(&prompts)
+        .map(|((repo, output_dir, _), prompts)| OrchestratorBatch {
+            target: repo,
+            output_dir,
+            prompts,
+            quorum: None,
+            verify: None,
+            save_transcripts: false,
+            executive_summary: false,
+        })
+        .collect();
+    let orchestrator = build_multi_target_orchestrator_prompt(
+        &batches,
+        &std::env::current_exe()?,
+        &ScanProfile::default().settings(),
+    );
+    write_stdout(&format!("{orchestrator}\n"))
+}
+
+/// Group the occurrences by similarity and print each group, closest to
+/// the seed first, with its most representative occurrence.
+fn report_clusters(occurrences: &[Occurrence], printer: &StatusPrinter) {
+    let shingles: Vec<Shingles> = occurrences.iter().map(|o| o.shingles.clone()).collect();
+    let mut clusters = cluster_snippets(&shingles, CLUSTER_SIMILARITY);
+    clusters.sort_by(|a, b| {
+        occurrences[b.exemplar]
+            .similarity
+            .total_cmp(&occurrences[a.exemplar].similarity)
+    });
+    printer.status(
+        "Clusters",
+        &format!(
+            "{} occurrences in {} groups of similar code",
+            occurrences.len(),
+            clusters.len()
+        ),
+    );
+    for cluster in &clusters {
+        let exemplar = &occurrences[cluster.exemplar];
+        let mut repos: Vec<&str> = cluster
+            .members
+            .iter()
+            .map(|&i| occurrences[i].repo.as_str())
+            .collect();
+        repos.sort_unstable();
+        repos.dedup();
+        printer.bullet(&format!(
+            "{} occurrences in {} repositories, {:.0}% like the seed; e.g. {}:{}:{} `{}`",
+            cluster.members.len(),
+            repos.len(),
+            exemplar.similarity * 100.0,
+            exemplar.repo,
+            exemplar.file,
+            exemplar.line,
+            truncate(&exemplar.text, MAX_QUERY_TEXT)
+        ));
+    }
+}
+
+/// Derive the variant search for `seed`, whose paths are relative to `root`.
+fn derive_variant(seed: &VariantSeed, root: &Path) -> Result<Variant> {
+    let (file, line, message) = match seed {
+        VariantSeed::Line { file, line } => (file.clone(), *line, None),
+        VariantSeed::Text { file, text } => {
+            let source = std::fs::read_to_string(root.join(file))
+                .with_context(|| format!("cannot read {}", file.display()))?;
+            let line = source
+                .lines()
+                .position(|l| l.contains(text.as_str()))
+                .with_context(|| format!("`{text}` does not occur in {}", file.display()))?;
+            (file.clone(), line + 1, None)
+        }
+        VariantSeed::Finding { sarif, fingerprint } => {
+            let report = SarifReport::from_file(sarif)?;
+            let result = report
+                .runs
+                .iter()
+                .flat_map(|run| &run.results)
+                .find(|r| match fingerprint {
+                    Some(fp) => r
+                        .fingerprints
+                        .as_ref()
+                        .and_then(|f| f.get("parsentry/v1"))
+                        .is_some_and(|v| v == fp),
+                    None => true,
+                })
+                .with_context(|| format!("no matching finding in {}", sarif.display()))?;
+            let location = result
+                .locations
+                .first()
+                .context("the finding has no location")?;
+            let line = location
+                .physical_location
+                .region
+                .as_ref()
+                .map_or(1, |r| r.start_line.max(1) as usize);
+            (
+                PathBuf::from(&location.physical_location.artifact_location.uri),
+                line,
+                Some(format!("{}: {}", result.rule_id, result.message.text)),
+            )
+        }
+    };
+
+    let source = std::fs::read_to_string(root.join(&file))
+        .with_context(|| format!("cannot read {}", file.display()))?;
+    let language = Language::from_filename(&file.to_string_lossy());
+    if language == Language::Other {
+        bail!("{} is not in a supported language", file.display());
+    }
+    let Some(seed_line) = source.lines().nth(line - 1) else {
+        bail!("{} has no line {line}", file.display());
+    };
+
+    // The narrowest resource, else action, else principal match on the line.
+    let line_start: usize = source.lines().take(line - 1).map(|l| l.len() + 1).sum();
+    let line_end = line_start + seed_line.len();
+    let pattern = SecurityRiskPatterns::new_with_root(language, Some(root))
+        .get_pattern_matches(&source)
+        .into_iter()
+        .filter(|m| m.start_byte <= line_end && line_start < m.end_byte)
+        .min_by_key(|m| {
+            let rank = ["resources", "actions", "principals"]
+                .iter()
+                .position(|c| *c == m.category);
+            (rank, m.end_byte - m.start_byte)
+        });
+    let text = match &pattern {
+        Some(m) => m.matched_text.lines().next().unwrap_or_default(),
+        None => seed_line,
+    }
+    .trim()
+    .to_string();
+    let query = format!(
+        "\"{}\" language:{}",
+        truncate(&text.replace('"', " "), MAX_QUERY_TEXT),
+        search_language(language)
+    );
+
+    Ok(Variant {
+        seed: format!("{}:{line}", file.display()),
+        snippet: snippet(&source, line),
+        message: message.unwrap_or_else(|| match &pattern {
+            Some(m) => m.pattern_config.description.clone(),
+            None => format!("code like `{text}`"),
+        }),
+        language,
+        pattern: pattern.map(|m| m.pattern_config.description),
+        text,
+        query,
+    })
+}
+
+/// Files under `repo_root` containing the variant, with the lines it is on.
+fn variant_hits(
+    variant: &Variant,
+    matcher: &SecurityRiskPatterns,
+    repo_root: &Path,
+) -> BTreeMap<String, Vec<usize>> {
+    let files = FileDiscovery::new(repo_root.to_path_buf())
+        .get_files()
+        .unwrap_or_default();
+    let mut hits = BTreeMap::new();
+    for path in files {
+        let relative = path
+            .strip_prefix(repo_root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        if Language::from_filename(&relative) != variant.language {
+            continue;
+        }
+        let Ok(source) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let line_of = |byte: usize| source[..byte.min(source.len())].matches('\n').count() + 1;
+        let mut lines: Vec<usize> = match &variant.pattern {
+            Some(pattern) => matcher
+                .get_pattern_matches(&source)
+                .iter()
+                .filter(|m| m.pattern_config.description == *pattern)
+                .map(|m| line_of(m.start_byte))
+                .collect(),
+            None => source
+                .match_indices(variant.text.as_str())
+                .map(|(byte, _)| line_of(byte))
+                .collect(),
+        };
+        lines.dedup();
+        if !lines.is_empty() {
+            hits.insert(relative, lines);
+        }
+    }
+    hits
+}
+
+/// Occurrences of the variant at `hits` in `repo`, with their similarity
+/// to the seed.
+fn variant_occurrences(
+    repo: &str,
+    repo_root: &Path,
+    hits: &BTreeMap<String, Vec<usize>>,
+    seed: &Shingles,
+) -> Vec<Occurrence> {
+    let mut occurrences = Vec::new();
+    for (file, lines) in hits {
+        let Ok(source) = std::fs::read_to_string(repo_root.join(file)) else {
+            continue;
+        };
+        for &line in lines {
+            let shingles = Shingles::of(&snippet(&source, line));
+            occurrences.push(Occurrence {
+                repo: repo.to_string(),
+                file: file.clone(),
+                line,
+                text: source
+                    .lines()
+                    .nth(line - 1)
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+                similarity: seed.similarity(&shingles),
+                shingles,
+            });
+        }
+    }
+    occurrences
+}
+
+/// Line `line` of `source` with `SNIPPET_CONTEXT` lines either side.
+fn snippet(source: &str, line: usize) -> String {
+    let first = line.saturating_sub(SNIPPET_CONTEXT + 1);
+    source
+        .lines()
+        .skip(first)
+        .take(line - first + SNIPPET_CONTEXT)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Surface covering the files of a candidate repository that contain the
+/// variant.
+fn variant_surface(variant: &Variant, hits: &BTreeMap<String, Vec<usize>>) -> AttackSurface {
+    let lines: Vec<String> = hits
+        .iter()
+        .map(|(file, lines)| {
+            let lines: Vec<String> = lines.iter().map(usize::to_string).collect();
+            format!("{file} (lines {})", lines.join(", "))
+        })
+        .collect();
+    AttackSurface {
+        id: "VARIANT-001".to_string(),
+        kind: "variant".to_string(),
+        identifier: variant.text.clone(),
+        locations: hits.keys().cloned().collect(),
+        description: format!(
+            "Possible variant of a known vulnerability ({}; seed {}). Check whether each \
+             occurrence of `{}` is exploitable the same way: {}",
+            variant.message,
+            variant.seed,
+            variant.text,
+            lines.join("; ")
+        ),
+    }
+}
+
+/// GitHub code search name of `language`.
+fn search_language(language: Language) -> &'static str {
+    match language {
+        Language::Cpp => "c++",
+        Language::Terraform => "hcl",
+        Language::Bash | Language::Shell => "shell",
+        Language::CloudFormation | Language::Kubernetes => "yaml",
+        _ => language.display_name(),
+    }
+}
+
+fn truncate(text: &str, max: usize) -> &str {
+    match text.char_indices().nth(max) {
+        Some((i, _)) => &text[..i],
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_pattern_and_query_from_seeds() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(
+            root.path().join("db.py"),
+            "def find(name):\n    return cursor.execute(\"SELECT \" + name)\n",
+        )
+        .unwrap();
+        let file = PathBuf::from("db.py");
+
+        let variant = derive_variant(
+            &VariantSeed::Text {
+                file: file.clone(),
+                text: "SELECT".to_string(),
+            },
+            root.path(),
+        )
+        .unwrap();
+        assert_eq!(variant.seed, "db.py:2");
+        assert_eq!(variant.pattern.as_deref(), Some("SQL execution resource"));
+        assert_eq!(variant.query, "\"cursor.execute\" language:Python");
+
+        let sarif = root.path().join("result.sarif.json");
+        std::fs::write(
+            &sarif,
+            r#"{"$schema":"","version":"2.1.0","runs":[{"tool":{"driver":{"name":"p","version":"0"}},"results":[
+                {"ruleId":"SQLI","level":"error","message":{"text":"query from input"},
+                 "locations":[{"physicalLocation":{"artifactLocation":{"uri":"db.py"},"region":{"startLine":2}}}]}
+            ]}]}"#,
+        )
+        .unwrap();
+        let from_finding = derive_variant(
+            &VariantSeed::Finding {
+                sarif,
+                fingerprint: None,
+            },
+            root.path(),
+        )
+        .unwrap();
+        assert_eq!(from_finding.message, "SQLI: query from input");
+        assert_eq!(from_finding.query, variant.query);
+
+        let hits = variant_hits(
+            &variant,
+            &SecurityRiskPatterns::new(Language::Python),
+            root.path(),
+        );
+        assert_eq!(hits["db.py"], [2]);
+        let occurrences = variant_occurrences(
+            "acme/db",
+            root.path(),
+            &hits,
+            &Shingles::of(&variant.snippet),
+        );
+        assert_eq!(
+            occurrences[0].text,
+            "return cursor.execute(\"SELECT \" + name)"
+        );
+        assert_eq!(occurrences[0].similarity, 1.0);
+        let surface = variant_surface(&variant, &hits);
+        assert_eq!(surface.locations, ["db.py"]);
+        assert!(surface.description.contains("db.py (lines 2)"));
+    }
+}
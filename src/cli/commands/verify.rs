@@ -0,0 +1,82 @@
+use anyhow::{Context, Result};
+
+use super::common::{cache_dir_for, locate_repository, repo_name_from_target, write_stdout};
+use crate::cli::ui::StatusPrinter;
+use crate::prompt::build_verification_prompt;
+use parsentry_reports::SarifReport;
+use parsentry_reports::consensus::apply_consensus;
+use parsentry_reports::output::write_atomic;
+use parsentry_reports::verify::{
+    VERDICT_FILE, load_verdict, needs_verification, stamp, verification_dir,
+};
+
+/// Write a verification prompt for every finding at or above `threshold`
+/// that has no current verdict, and print the worker assignments.
+pub async fn run_verify_command(
+    target: &str,
+    threshold: f64,
+    quorum: Option<usize>,
+    model: Option<&str>,
+) -> Result<()> {
+    let printer = StatusPrinter::with_service(repo_name_from_target(target));
+    let reports_dir = cache_dir_for(target).join("reports");
+    if let Some(quorum) = quorum {
+        apply_consensus(&reports_dir, Some(quorum))?;
+    }
+    let (root_dir, _) = locate_repository(target, &printer).await?;
+
+    let mut surfaces: Vec<_> = std::fs::read_dir(&reports_dir)
+        .with_context(|| format!("cannot read directory: {}", reports_dir.display()))?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.join("result.sarif.json").exists())
+        .collect();
+    surfaces.sort();
+
+    let on_model = model
+        .map(|m| format!(" Run it on model `{m}`."))
+        .unwrap_or_default();
+    let mut assignments = String::new();
+    let mut reused = 0;
+    for surface_dir in &surfaces {
+        let surface_id = surface_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let report = SarifReport::from_file(surface_dir.join("result.sarif.json"))?;
+        for result in report.runs.iter().flat_map(|r| &r.results) {
+            if !needs_verification(result, threshold) {
+                continue;
+            }
+            let dir = verification_dir(surface_dir, result);
+            if load_verdict(&dir, surface_dir).is_some() {
+                reused += 1;
+                continue;
+            }
+            std::fs::create_dir_all(&dir)?;
+            let _ = std::fs::remove_file(dir.join(VERDICT_FILE));
+            let prompt_path = dir.join("prompt.md");
+            let prompt =
+                build_verification_prompt(result, &surface_id, &root_dir, &dir.join(VERDICT_FILE));
+            write_atomic(&prompt_path, prompt)?;
+            stamp(&dir, surface_dir)?;
+
+            let id = dir
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            assignments.push_str(&format!(
+                "- Worker `{surface_id}/verify-{id}`: read `{}` and execute the instructions in it.{on_model}\n",
+                prompt_path.display(),
+            ));
+        }
+    }
+
+    if reused > 0 {
+        printer.status("Cached", &format!("{reused} findings already verified"));
+    }
+    if assignments.is_empty() {
+        return write_stdout("No findings need verification.\n");
+    }
+    write_stdout(&format!("Verification Workers\n\n{assignments}"))
+}
@@ -1,4 +1,5 @@
 pub mod args;
+pub mod cancel;
 pub mod commands;
 pub mod root;
 pub mod ui;
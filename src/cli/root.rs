@@ -1,44 +1,287 @@
 use anyhow::Result;
 use clap::Parser;
 
-use crate::cli::args::{Args, Commands};
-use crate::cli::commands::common::write_stdout;
+use crate::cli::args::{Args, Commands, ConfigCommand, GraphCommand, PatternsCommand};
+use crate::cli::cancel::CancellationToken;
+use crate::cli::commands::common::{clone_options, write_stdout};
 use crate::cli::commands::{
-    run_generate_command, run_log_command, run_model_command, run_scan_command,
+    AnalysisPlan, Delivery, OwnerRouting, Replay, Signing, VariantSeed, run_compare_command,
+    run_config_show_command, run_config_validate_command, run_diff_command, run_doctor_command,
+    run_eval_command, run_fix_command, run_generate_command, run_graph_command,
+    run_graph_path_command, run_history_command, run_log_command, run_model_command,
+    run_patterns_install_command, run_patterns_lint_command, run_patterns_list_command,
+    run_patterns_merge_command, run_patterns_test_command, run_patterns_update_command,
+    run_poc_command, run_review_command, run_scan_command, run_transcripts_command,
+    run_trend_command, run_validate_command, run_variants_command, run_verify_command,
+    sweep_targets,
 };
+use crate::i18n::ResponseLanguage;
+use crate::profile::{ScanProfile, WorkerTools};
+use crate::prompt::Verification;
+use crate::residency::SourceUpload;
+use parsentry_core::offline::require_network;
+use parsentry_reports::filename::FilenameTemplate;
+use parsentry_reports::replay::ReplaySource;
 
 pub struct RootCommand;
 
 impl RootCommand {
-    pub async fn execute() -> Result<()> {
+    pub async fn execute(cancel: &CancellationToken) -> Result<()> {
         let args = Args::parse();
+        parsentry_core::offline::set_offline(args.offline);
+        let _telemetry = crate::telemetry::init(args.log_format)?;
+        parsentry_core::content_type::set_include_generated(args.include_generated);
 
         match args.command {
-            Commands::Model { target } => run_model_command(&target).await,
-            Commands::Scan {
+            Commands::Model { target, scope } => run_model_command(&target, &scope).await,
+            Commands::Graph {
+                action: None,
                 target,
+                format,
+                output,
+            } => run_graph_command(&target, format, output.as_deref()).await,
+            Commands::Graph {
+                action:
+                    Some(GraphCommand::Path {
+                        from,
+                        to,
+                        target,
+                        max_depth,
+                        json,
+                    }),
+                ..
+            } => run_graph_path_command(&target, &from, &to, max_depth, json).await,
+            Commands::Scan {
+                mut targets,
+                extra_targets,
+                sweep,
                 diff_base,
+                commits,
                 filter_lang,
-            } => run_scan_command(&target, diff_base.as_deref(), filter_lang.as_deref()).await,
-            Commands::Generate { target, output } => {
-                run_generate_command(&target, output.as_deref()).await
+                suggest_fixes,
+                profile,
+                model,
+                compare_models,
+                samples,
+                quorum,
+                verify,
+                verify_threshold,
+                verify_model,
+                reachability,
+                report_binaries,
+                save_transcripts,
+                executive_summary,
+                replay,
+                replay_missing,
+                jobs,
+                refresh,
+                read_only,
+                no_shell,
+                language,
+                no_source_upload,
+                scope,
+            } => {
+                let replay = replay
+                    .map(|dir| -> Result<Replay> {
+                        Ok(Replay {
+                            source: ReplaySource::open(&dir)?,
+                            missing: replay_missing,
+                        })
+                    })
+                    .transpose()?;
+                let verification = verify.then_some(Verification {
+                    threshold: verify_threshold,
+                    model: verify_model,
+                });
+                targets.extend(extra_targets);
+                targets.extend(sweep_targets(&sweep).await?);
+                let clone = clone_options(&scope)?;
+                if targets.is_empty() {
+                    targets.push(".".to_string());
+                }
+                run_scan_command(
+                    &targets,
+                    diff_base.as_deref(),
+                    filter_lang.as_deref(),
+                    suggest_fixes,
+                    profile.or_else(ScanProfile::from_env).unwrap_or_default(),
+                    AnalysisPlan {
+                        models: &compare_models,
+                        model_chain: &model,
+                        samples: samples as usize,
+                        quorum: quorum.map(|k| k as usize),
+                        verify: verification.as_ref(),
+                        reachability,
+                        report_binaries,
+                        save_transcripts,
+                        executive_summary,
+                        replay: replay.as_ref(),
+                        jobs: jobs as usize,
+                        cancel,
+                        refresh,
+                        clone: &clone,
+                        commits: commits.as_deref(),
+                        tools: WorkerTools {
+                            read_only,
+                            shell: !no_shell,
+                        },
+                        language: language
+                            .or_else(ResponseLanguage::from_env)
+                            .unwrap_or_default(),
+                        source: no_source_upload
+                            .or_else(SourceUpload::from_env)
+                            .unwrap_or_default(),
+                    },
+                    &scope,
+                )
+                .await
+            }
+            Commands::Variants {
+                file,
+                line,
+                text,
+                sarif,
+                fingerprint,
+                root,
+                query,
+                limit,
+                min_similarity,
+                dry_run,
+            } => {
+                let seed = match (sarif, file, text) {
+                    (Some(sarif), _, _) => VariantSeed::Finding { sarif, fingerprint },
+                    (None, Some(file), Some(text)) => VariantSeed::Text { file, text },
+                    (None, Some(file), None) => VariantSeed::Line {
+                        file,
+                        line: line.unwrap_or(1) as usize,
+                    },
+                    (None, None, _) => unreachable!("clap requires --file or --sarif"),
+                };
+                run_variants_command(seed, &root, query, limit, min_similarity, dry_run).await
+            }
+            Commands::Fix { target, apply, yes } => run_fix_command(&target, apply, yes).await,
+            Commands::Lsp => crate::lsp::serve(),
+            Commands::Mcp => crate::mcp::serve(),
+            Commands::Review {
+                report,
+                root,
+                triage,
+            } => run_review_command(&report, &root, triage.as_deref()),
+            Commands::Generate {
+                targets,
+                output,
+                format,
+                min_level,
+                notify,
+                split_by_owner,
+                split_by_file,
+                filename_template,
+                layout,
+                notify_owner,
+                sign_key,
+                sign_keyless,
+                policy,
+            } => {
+                let delivery = Delivery {
+                    owners: OwnerRouting::new(split_by_owner, &notify_owner)?,
+                    signing: match sign_key {
+                        Some(key) => Some(Signing::Key(key)),
+                        None => sign_keyless.then_some(Signing::Keyless),
+                    },
+                    files: split_by_file
+                        .then(|| FilenameTemplate::configured(filename_template.as_deref(), layout))
+                        .transpose()?,
+                };
+                run_generate_command(
+                    &targets,
+                    output.as_deref(),
+                    format,
+                    &min_level,
+                    &notify,
+                    &delivery,
+                    policy.as_deref(),
+                )
+                .await
+            }
+            Commands::Transcripts { target } => run_transcripts_command(&target),
+            Commands::Validate { target } => run_validate_command(&target),
+            Commands::Verify {
+                target,
+                threshold,
+                quorum,
+                model,
+            } => {
+                run_verify_command(
+                    &target,
+                    threshold,
+                    quorum.map(|k| k as usize),
+                    model.as_deref(),
+                )
+                .await
             }
             Commands::Merge {
                 target,
+                sarif,
+                quorum,
                 gh_issue,
                 jira,
                 linear,
                 notion,
+                defectdojo,
+                notify,
                 min_level,
                 dry_run,
             } => {
                 use crate::cli::commands::common::cache_dir_for;
+                use crate::cli::commands::generate::{
+                    assign_risk, calibrate, checkout_dir, relativize_uris, uri_base,
+                };
                 use crate::github::run_gh_issue_command;
+                use parsentry_reports::consensus::apply_consensus;
                 use parsentry_reports::{
-                    merge_sarif_dir, run_jira_command, run_linear_command, run_notion_command,
+                    ScanNotification,
+                    merge::{merge_sarif_dir_with, merge_sarif_files},
+                    run_defectdojo_command, run_jira_command, run_linear_command,
+                    run_notion_command, send_notifications,
                 };
+                let exports = [
+                    gh_issue.as_ref().map(|r| format!("GitHub issues in {r}")),
+                    jira.as_ref().map(|p| format!("Jira export to {p}")),
+                    linear.as_ref().map(|t| format!("Linear export to {t}")),
+                    notion.as_ref().map(|_| "Notion export".to_string()),
+                    defectdojo
+                        .as_ref()
+                        .map(|e| format!("DefectDojo import to {e}")),
+                    (!notify.is_empty() && !dry_run)
+                        .then(|| format!("{} webhook notification(s)", notify.len())),
+                ];
+                require_network(&exports.into_iter().flatten().collect::<Vec<_>>())?;
                 let reports_dir = cache_dir_for(&target).join("reports");
-                let merged = merge_sarif_dir(&reports_dir, None)?;
+                if let Some(quorum) = quorum {
+                    let stats = apply_consensus(&reports_dir, Some(quorum as usize))?;
+                    crate::cli::ui::StatusPrinter::new().status(
+                        "Consensus",
+                        &format!(
+                            "kept {} and dropped {} findings across {} surfaces",
+                            stats.kept, stats.dropped, stats.surfaces
+                        ),
+                    );
+                }
+                let (mut merged, checkout) = if sarif.is_empty() {
+                    let checkout = checkout_dir(&target);
+                    let base = uri_base(&checkout);
+                    (
+                        merge_sarif_dir_with(&reports_dir, None, Some(&base))?,
+                        checkout,
+                    )
+                } else {
+                    // Shards are merged where they were scanned.
+                    (merge_sarif_files(&sarif)?, std::path::PathBuf::from("."))
+                };
+                relativize_uris(&checkout, &mut merged);
+                calibrate(&mut merged)?;
+                parsentry_reports::schema::enforce(&mut merged)?;
+                assign_risk(&cache_dir_for(&target), &mut merged);
                 write_stdout(&format!("{}\n", serde_json::to_string_pretty(&merged)?))?;
                 if let Some(repo) = gh_issue {
                     run_gh_issue_command(&reports_dir, &repo, dry_run, &min_level).await?;
@@ -52,8 +295,82 @@ impl RootCommand {
                 if let Some(db_id) = notion {
                     run_notion_command(&reports_dir, &db_id, dry_run, &min_level).await?;
                 }
+                if let Some(engagement) = defectdojo {
+                    run_defectdojo_command(&reports_dir, &engagement, dry_run, &min_level).await?;
+                }
+                if !notify.is_empty() && !dry_run {
+                    let notification = ScanNotification::completed(&target, &merged, vec![]);
+                    send_notifications(&notify, &notification).await?;
+                }
                 Ok(())
             }
+            Commands::Comment {
+                target,
+                pr,
+                repo,
+                min_level,
+                dry_run,
+            } => {
+                use crate::cli::commands::common::cache_dir_for;
+                use crate::github::run_pr_comment_command;
+                let repo = repo.unwrap_or_else(|| target.clone());
+                let reports_dir = cache_dir_for(&target).join("reports");
+                run_pr_comment_command(&reports_dir, &repo, pr, dry_run, &min_level).await
+            }
+            Commands::Doctor => run_doctor_command(),
+            Commands::Poc { script, timeout } => run_poc_command(&script, timeout),
+            Commands::Config { action } => match action {
+                ConfigCommand::Validate { policy } => {
+                    run_config_validate_command(policy.as_deref())
+                }
+                ConfigCommand::Show { effective } => run_config_show_command(effective),
+            },
+            Commands::Patterns { action } => match action {
+                PatternsCommand::List { lang, root } => {
+                    run_patterns_list_command(&root, lang.as_deref())
+                }
+                PatternsCommand::Lint { file, root, fix } => {
+                    run_patterns_lint_command(&root, file.as_deref(), fix)
+                }
+                PatternsCommand::Merge { file, root } => run_patterns_merge_command(&root, &file),
+                PatternsCommand::Install { source } => run_patterns_install_command(&source).await,
+                PatternsCommand::Update { check } => run_patterns_update_command(check).await,
+                PatternsCommand::Test {
+                    sample,
+                    lang,
+                    pattern,
+                    root,
+                } => run_patterns_test_command(&sample, &root, lang.as_deref(), pattern.as_deref()),
+            },
+            Commands::Diff {
+                old,
+                new,
+                format,
+                fail_on_new,
+            } => run_diff_command(&old, &new, format, fail_on_new),
+            Commands::Compare { target, format } => run_compare_command(&target, format),
+            Commands::Eval {
+                dataset,
+                results,
+                format,
+                output,
+                calibration,
+            } => {
+                run_eval_command(
+                    &dataset,
+                    &results,
+                    format,
+                    output.as_deref(),
+                    calibration.as_deref(),
+                )
+                .await
+            }
+            Commands::History {
+                target,
+                limit,
+                json,
+            } => run_history_command(&target, limit, json),
+            Commands::Trend { target, limit } => run_trend_command(&target, limit),
             Commands::Log {
                 target,
                 follow,
@@ -3,6 +3,19 @@
 //! Output style inspired by docker compose: `parsentry  | keyword message`
 
 use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// When set, status lines are emitted as tracing events instead of text.
+static STRUCTURED_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Route `StatusPrinter` output through `tracing` (used by `--log-format json`).
+pub fn set_structured_output(enabled: bool) {
+    STRUCTURED_OUTPUT.store(enabled, Ordering::Relaxed);
+}
+
+fn structured_output() -> bool {
+    STRUCTURED_OUTPUT.load(Ordering::Relaxed)
+}
 
 /// ANSI color codes for terminal styling
 pub mod colors {
@@ -98,6 +111,10 @@ impl StatusPrinter {
 
     /// Print a status line: `parsentry    | Scanning target/path`
     pub fn status(&self, keyword: &str, message: &str) {
+        if structured_output() {
+            tracing::info!(service = %self.service, keyword = keyword, "{}", message);
+            return;
+        }
         let prefix = self.prefix(colors::BRIGHT_GREEN);
         let kw = self.styled(colors::BRIGHT_GREEN, true, keyword);
         eprintln!("{} {} {}", prefix, kw, message);
@@ -105,6 +122,10 @@ impl StatusPrinter {
 
     /// Print an info line: `parsentry    | Info some information`
     pub fn info(&self, keyword: &str, message: &str) {
+        if structured_output() {
+            tracing::info!(service = %self.service, keyword = keyword, "{}", message);
+            return;
+        }
         let prefix = self.prefix(colors::BRIGHT_CYAN);
         let kw = self.styled(colors::BRIGHT_CYAN, true, keyword);
         eprintln!("{} {} {}", prefix, kw, message);
@@ -112,6 +133,10 @@ impl StatusPrinter {
 
     /// Print a warning line: `parsentry    | Warning message`
     pub fn warning(&self, keyword: &str, message: &str) {
+        if structured_output() {
+            tracing::warn!(service = %self.service, keyword = keyword, "{}", message);
+            return;
+        }
         let prefix = self.prefix(colors::BRIGHT_YELLOW);
         let kw = self.styled(colors::BRIGHT_YELLOW, true, keyword);
         eprintln!("{} {} {}", prefix, kw, message);
@@ -119,6 +144,10 @@ impl StatusPrinter {
 
     /// Print an error line: `parsentry    | Error message`
     pub fn error(&self, keyword: &str, message: &str) {
+        if structured_output() {
+            tracing::error!(service = %self.service, keyword = keyword, "{}", message);
+            return;
+        }
         let prefix = self.prefix(colors::BRIGHT_RED);
         let kw = self.styled(colors::BRIGHT_RED, true, keyword);
         eprintln!("{} {} {}", prefix, kw, message);
@@ -126,6 +155,10 @@ impl StatusPrinter {
 
     /// Print a success line: `parsentry    | Done message`
     pub fn success(&self, keyword: &str, message: &str) {
+        if structured_output() {
+            tracing::info!(service = %self.service, keyword = keyword, "{}", message);
+            return;
+        }
         let prefix = self.prefix(colors::GREEN);
         let kw = self.styled(colors::GREEN, true, keyword);
         eprintln!("{} {} {}", prefix, kw, message);
@@ -133,6 +166,10 @@ impl StatusPrinter {
 
     /// Print a dim/secondary info line
     pub fn dim(&self, message: &str) {
+        if structured_output() {
+            tracing::debug!(service = %self.service, keyword = "", "{}", message);
+            return;
+        }
         let prefix = self.prefix(colors::DIM);
         let msg = if self.use_colors {
             format!("{}{}{}", colors::DIM, message, colors::RESET)
@@ -157,6 +194,10 @@ impl StatusPrinter {
 
     /// Print a bullet point
     pub fn bullet(&self, text: &str) {
+        if structured_output() {
+            tracing::info!(service = %self.service, keyword = "", "{}", text);
+            return;
+        }
         let prefix = self.prefix(colors::DIM);
         eprintln!("{} {}", prefix, text);
     }
@@ -288,9 +329,193 @@ impl Default for SummaryTable {
     }
 }
 
+/// Whether live progress can be drawn: stderr is a terminal that is not dumb
+/// and status lines are plain text.
+pub fn interactive() -> bool {
+    std::io::stderr().is_terminal()
+        && !std::env::var("TERM").is_ok_and(|t| t == "dumb")
+        && !structured_output()
+}
+
 /// Progress indicator styles
 pub mod progress {
-    use indicatif::{ProgressBar, ProgressStyle};
+    use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    /// The dashboard currently drawing, so plain lines can be printed above it.
+    static ACTIVE: Mutex<Option<MultiProgress>> = Mutex::new(None);
+
+    /// Run `f` (which writes to stderr) with the active dashboard hidden.
+    pub fn suspend<R>(f: impl FnOnce() -> R) -> R {
+        let active = ACTIVE.lock().ok().and_then(|a| a.clone());
+        match active {
+            Some(multi) => multi.suspend(f),
+            None => f(),
+        }
+    }
+
+    /// Counters shown on the overall line of a [`ScanDashboard`].
+    #[derive(Debug, Clone, Default, PartialEq)]
+    pub struct ScanStats {
+        /// Surfaces with a prompt.
+        pub total: usize,
+        /// Surfaces with a result, including cache hits.
+        pub completed: usize,
+        /// Surfaces already complete when the dashboard started.
+        pub completed_before: usize,
+        /// Results reused from the cache by `scan`.
+        pub cached: usize,
+        /// Tokens used by the workers' sessions so far.
+        pub tokens: u64,
+        /// Sum of the `cost_usd` workers reported.
+        pub cost_usd: Option<f64>,
+    }
+
+    impl ScanStats {
+        /// Remaining time, extrapolated from the surfaces completed in the
+        /// `elapsed` time since the dashboard started.
+        pub fn eta(&self, elapsed: Duration) -> Option<Duration> {
+            let analyzed = self.completed.saturating_sub(self.completed_before) as u32;
+            if analyzed == 0 {
+                return None;
+            }
+            let remaining = self.total.saturating_sub(self.completed) as u32;
+            Some(elapsed / analyzed * remaining)
+        }
+
+        /// One-line summary: counts, ETA, cache hit rate, tokens and cost.
+        pub fn summary(&self, elapsed: Duration) -> String {
+            let remaining = self.total.saturating_sub(self.completed);
+            let eta = match self.eta(elapsed) {
+                _ if remaining == 0 => "done".to_string(),
+                Some(eta) => format!("ETA {}", format_duration(eta)),
+                None => "ETA --".to_string(),
+            };
+            let hit_rate = match self.total {
+                0 => 0,
+                total => self.cached * 100 / total,
+            };
+            let mut line = format!(
+                "{}/{} · {} left · {} · cache {}% · {} tokens",
+                self.completed,
+                self.total,
+                remaining,
+                eta,
+                hit_rate,
+                format_tokens(self.tokens)
+            );
+            if let Some(cost) = self.cost_usd {
+                line.push_str(&format!(" · ${cost:.2}"));
+            }
+            line
+        }
+    }
+
+    fn format_duration(d: Duration) -> String {
+        let secs = d.as_secs();
+        if secs >= 3600 {
+            format!("{}h {}m", secs / 3600, secs % 3600 / 60)
+        } else if secs >= 60 {
+            format!("{}m {}s", secs / 60, secs % 60)
+        } else {
+            format!("{}s", secs)
+        }
+    }
+
+    fn format_tokens(tokens: u64) -> String {
+        match tokens {
+            0..1_000 => tokens.to_string(),
+            1_000..1_000_000 => format!("{:.1}k", tokens as f64 / 1e3),
+            _ => format!("{:.1}M", tokens as f64 / 1e6),
+        }
+    }
+
+    /// Live multi-line view of a running scan: an overall line with
+    /// [`ScanStats`] above one line per active worker showing what it is
+    /// doing. Plain lines go through [`suspend`] while it is drawn.
+    pub struct ScanDashboard {
+        multi: MultiProgress,
+        overall: ProgressBar,
+        workers: HashMap<String, ProgressBar>,
+        started: Instant,
+        pub stats: ScanStats,
+    }
+
+    impl ScanDashboard {
+        pub fn new() -> Self {
+            let multi = MultiProgress::new();
+            let overall = multi.add(ProgressBar::new(0));
+            overall.set_style(
+                ProgressStyle::default_bar()
+                    .template("{spinner:.cyan} [{elapsed_precise}] {bar:20.cyan/blue} {wide_msg}")
+                    .unwrap()
+                    .progress_chars("━━╸")
+                    .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏"),
+            );
+            overall.enable_steady_tick(Duration::from_millis(100));
+            if let Ok(mut active) = ACTIVE.lock() {
+                *active = Some(multi.clone());
+            }
+            Self {
+                multi,
+                overall,
+                workers: HashMap::new(),
+                started: Instant::now(),
+                stats: ScanStats::default(),
+            }
+        }
+
+        /// Show `activity` (e.g. the file a tool call reads) on the line of
+        /// `worker`, adding the line if the worker is new.
+        pub fn activity(&mut self, worker: &str, activity: &str) {
+            let bar = self.workers.entry(worker.to_string()).or_insert_with(|| {
+                let bar = self.multi.add(ProgressBar::new_spinner());
+                bar.set_style(
+                    ProgressStyle::default_spinner()
+                        .template("  {spinner:.cyan} {prefix:14.bold} {wide_msg:.dim}")
+                        .unwrap()
+                        .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏"),
+                );
+                bar.set_prefix(worker.to_string());
+                bar.enable_steady_tick(Duration::from_millis(100));
+                bar
+            });
+            bar.set_message(activity.lines().next().unwrap_or_default().to_string());
+        }
+
+        /// Remove the line of a worker that finished.
+        pub fn finish_worker(&mut self, worker: &str) {
+            if let Some(bar) = self.workers.remove(worker) {
+                bar.finish_and_clear();
+                self.multi.remove(&bar);
+            }
+        }
+
+        /// Redraw the overall line from [`ScanDashboard::stats`].
+        pub fn refresh(&self) {
+            self.overall.set_length(self.stats.total as u64);
+            self.overall.set_position(self.stats.completed as u64);
+            self.overall
+                .set_message(self.stats.summary(self.started.elapsed()));
+        }
+    }
+
+    impl Default for ScanDashboard {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Drop for ScanDashboard {
+        fn drop(&mut self) {
+            if let Ok(mut active) = ACTIVE.lock() {
+                *active = None;
+            }
+            let _ = self.multi.clear();
+        }
+    }
 
     /// Create a styled progress bar
     pub fn create_bar(total: u64) -> ProgressBar {
@@ -333,6 +558,39 @@ mod tests {
         assert_eq!(truncate_path("abcd", 3), "..."); // too short for ellipsis
     }
 
+    #[test]
+    fn test_scan_stats_summary() {
+        use progress::ScanStats;
+        use std::time::Duration;
+
+        let mut stats = ScanStats {
+            total: 10,
+            completed: 4,
+            completed_before: 2,
+            cached: 2,
+            tokens: 12_345,
+            cost_usd: None,
+        };
+        // 2 completed in 60s, 6 remaining
+        assert_eq!(
+            stats.eta(Duration::from_secs(60)),
+            Some(Duration::from_secs(180))
+        );
+        assert_eq!(
+            stats.summary(Duration::from_secs(60)),
+            "4/10 · 6 left · ETA 3m 0s · cache 20% · 12.3k tokens"
+        );
+
+        stats.completed = 2;
+        stats.cost_usd = Some(0.5);
+        assert_eq!(stats.eta(Duration::from_secs(60)), None);
+        assert!(
+            stats
+                .summary(Duration::from_secs(60))
+                .ends_with("ETA -- · cache 20% · 12.3k tokens · $0.50")
+        );
+    }
+
     #[test]
     fn test_terminal_width() {
         let width = terminal_width();
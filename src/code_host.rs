@@ -0,0 +1,527 @@
+//! Code hosting abstraction over GitHub, GitLab and Bitbucket.
+//!
+//! Targets may carry a host prefix (`gitlab:group/project`,
+//! `bitbucket:workspace/repo`, `github:owner/repo`); bare `owner/repo` slugs
+//! default to GitHub. Self-managed instances are selected through
+//! `PARSENTRY_GITHUB_URL`, `PARSENTRY_GITLAB_URL` and `PARSENTRY_BITBUCKET_URL`,
+//! and clone/API credentials are read from `GITHUB_TOKEN` (or a GitHub App
+//! configured through `GITHUB_APP_*`), `GITLAB_TOKEN` and `BITBUCKET_TOKEN`.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use base64::Engine;
+use std::env;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use parsentry_core::offline::ensure_online;
+
+const GITHUB_DEFAULT_URL: &str = "https://github.com";
+const GITLAB_DEFAULT_URL: &str = "https://gitlab.com";
+const BITBUCKET_DEFAULT_URL: &str = "https://bitbucket.org";
+
+/// A git hosting service that repositories can be cloned from.
+#[async_trait]
+pub trait CodeHost: Send + Sync {
+    /// Short host label, also used as the target prefix (e.g. `gitlab`).
+    fn kind(&self) -> &'static str;
+
+    /// Web base URL of the instance, without trailing slash.
+    fn base_url(&self) -> &str;
+
+    /// Access token used for HTTPS clone and API calls.
+    fn token(&self) -> Option<String>;
+
+    /// Token for HTTPS clone, minting a short-lived one where the host needs it.
+    async fn clone_token(&self) -> Result<Option<String>> {
+        Ok(self.token())
+    }
+
+    /// User name paired with [`CodeHost::token`] for HTTPS basic auth.
+    fn auth_user(&self) -> &'static str;
+
+    /// Whether slugs may contain nested namespaces (GitLab subgroups).
+    fn allows_nested_slug(&self) -> bool {
+        false
+    }
+
+    /// HTTPS clone URL for a repository slug.
+    fn clone_url(&self, slug: &str) -> String {
+        format!("{}/{}.git", self.base_url(), slug)
+    }
+}
+
+fn base_url_from_env(var: &str, default: &str) -> String {
+    env::var(var)
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| default.to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+fn token_from_env(var: &str) -> Option<String> {
+    env::var(var).ok().filter(|t| !t.is_empty())
+}
+
+pub(crate) fn http_client() -> Result<reqwest::Client> {
+    Ok(parsentry_core::http::client_builder()?
+        .user_agent(concat!("parsentry/", env!("CARGO_PKG_VERSION")))
+        .build()?)
+}
+
+// ── GitHub ────────────────────────────────────────────────────────────────────
+
+pub struct GitHubHost {
+    base_url: String,
+}
+
+impl GitHubHost {
+    pub fn from_env() -> Self {
+        Self {
+            base_url: base_url_from_env("PARSENTRY_GITHUB_URL", GITHUB_DEFAULT_URL),
+        }
+    }
+}
+
+#[async_trait]
+impl CodeHost for GitHubHost {
+    fn kind(&self) -> &'static str {
+        "github"
+    }
+
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn token(&self) -> Option<String> {
+        token_from_env("GITHUB_TOKEN")
+    }
+
+    async fn clone_token(&self) -> Result<Option<String>> {
+        crate::github::clone_token().await
+    }
+
+    fn auth_user(&self) -> &'static str {
+        "x-access-token"
+    }
+}
+
+// ── GitLab ────────────────────────────────────────────────────────────────────
+
+pub struct GitLabHost {
+    base_url: String,
+}
+
+impl GitLabHost {
+    pub fn from_env() -> Self {
+        Self {
+            base_url: base_url_from_env("PARSENTRY_GITLAB_URL", GITLAB_DEFAULT_URL),
+        }
+    }
+}
+
+#[async_trait]
+impl CodeHost for GitLabHost {
+    fn kind(&self) -> &'static str {
+        "gitlab"
+    }
+
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn token(&self) -> Option<String> {
+        token_from_env("GITLAB_TOKEN")
+    }
+
+    fn auth_user(&self) -> &'static str {
+        "oauth2"
+    }
+
+    fn allows_nested_slug(&self) -> bool {
+        true
+    }
+}
+
+// ── Bitbucket ─────────────────────────────────────────────────────────────────
+
+/// Bitbucket Cloud (`bitbucket.org`) or a self-managed Bitbucket Data Center.
+pub struct BitbucketHost {
+    base_url: String,
+}
+
+impl BitbucketHost {
+    pub fn from_env() -> Self {
+        Self {
+            base_url: base_url_from_env("PARSENTRY_BITBUCKET_URL", BITBUCKET_DEFAULT_URL),
+        }
+    }
+
+    fn is_cloud(&self) -> bool {
+        self.base_url == BITBUCKET_DEFAULT_URL
+    }
+}
+
+#[async_trait]
+impl CodeHost for BitbucketHost {
+    fn kind(&self) -> &'static str {
+        "bitbucket"
+    }
+
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn token(&self) -> Option<String> {
+        token_from_env("BITBUCKET_TOKEN")
+    }
+
+    fn auth_user(&self) -> &'static str {
+        "x-token-auth"
+    }
+
+    fn clone_url(&self, slug: &str) -> String {
+        if self.is_cloud() {
+            format!("{}/{}.git", self.base_url, slug)
+        } else {
+            // Data Center serves git under /scm/<PROJECT>/<repo>.git
+            format!("{}/scm/{}.git", self.base_url, slug)
+        }
+    }
+}
+
+// ── Target resolution and cloning ─────────────────────────────────────────────
+
+/// A remote repository target: `[host:]slug[@ref]`.
+pub struct HostedTarget {
+    pub host: Box<dyn CodeHost>,
+    pub slug: String,
+    /// Branch, tag or commit SHA pinned with `@ref`.
+    pub reference: Option<String>,
+}
+
+/// Resolve a remote repository target into its host, slug and pinned ref.
+///
+/// Returns `None` for targets that are not repository slugs. Callers are
+/// expected to rule out existing local paths first.
+pub fn resolve_hosted_target(target: &str) -> Option<HostedTarget> {
+    let (host, slug): (Box<dyn CodeHost>, &str) = if let Some(slug) = target.strip_prefix("gitlab:")
+    {
+        (Box::new(GitLabHost::from_env()), slug)
+    } else if let Some(slug) = target.strip_prefix("bitbucket:") {
+        (Box::new(BitbucketHost::from_env()), slug)
+    } else if let Some(slug) = target.strip_prefix("github:") {
+        (Box::new(GitHubHost::from_env()), slug)
+    } else {
+        (Box::new(GitHubHost::from_env()), target)
+    };
+    let (slug, reference) = match slug.split_once('@') {
+        Some((slug, reference)) => (slug, Some(reference.to_string())),
+        None => (slug, None),
+    };
+
+    if !slug.contains('/') {
+        return None;
+    }
+    Some(HostedTarget {
+        host,
+        slug: slug.to_string(),
+        reference,
+    })
+}
+
+/// Validate a repository slug (`owner/repo`, or `group/sub/project` when nested).
+fn is_valid_slug(slug: &str, allow_nested: bool) -> bool {
+    let parts: Vec<&str> = slug.split('/').collect();
+    if parts.len() < 2 || (!allow_nested && parts.len() != 2) {
+        return false;
+    }
+    let valid_part = |p: &str| {
+        !p.is_empty()
+            && !p.starts_with('-')
+            && !p.starts_with('.')
+            && p.chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.')
+    };
+    parts.iter().all(|p| valid_part(p)) && !slug.ends_with(".git")
+}
+
+/// Environment passing `user:token` to git as a basic auth header, so the
+/// token appears neither on the command line nor in `.git/config`.
+///
+/// The header is scoped to `base_url` and appended after any inherited
+/// `GIT_CONFIG_*` entries rather than replacing them.
+fn credential_env(base_url: &str, user: &str, token: &str) -> [(String, String); 3] {
+    let inherited = env::var("GIT_CONFIG_COUNT")
+        .ok()
+        .and_then(|count| count.trim().parse().ok())
+        .unwrap_or(0);
+    credential_config(inherited, base_url, user, token)
+}
+
+fn credential_config(
+    index: usize,
+    base_url: &str,
+    user: &str,
+    token: &str,
+) -> [(String, String); 3] {
+    let basic = base64::engine::general_purpose::STANDARD.encode(format!("{user}:{token}"));
+    [
+        ("GIT_CONFIG_COUNT".to_string(), (index + 1).to_string()),
+        (
+            format!("GIT_CONFIG_KEY_{index}"),
+            format!("http.{base_url}/.extraHeader"),
+        ),
+        (
+            format!("GIT_CONFIG_VALUE_{index}"),
+            format!("Authorization: Basic {basic}"),
+        ),
+    ]
+}
+
+/// How [`clone_hosted_repo`] checks out a repository.
+#[derive(Debug, Clone, Default)]
+pub struct CloneOptions {
+    /// Branch, tag or commit SHA to check out instead of the default branch.
+    pub reference: Option<String>,
+    /// Sparse-checkout patterns (gitignore syntax); empty checks out everything.
+    pub sparse_paths: Vec<String>,
+}
+
+/// Shallow-clone (depth 1) a repository from `host` into `dest`,
+/// authenticating with `token` (see [`CodeHost::clone_token`]) when given.
+///
+/// With sparse paths only matching files are checked out, and blobs of the
+/// others are never downloaded. Credentials reach git through its
+/// environment only, so tokens never persist in `.git/config`.
+pub fn clone_hosted_repo(
+    host: &dyn CodeHost,
+    slug: &str,
+    dest: &Path,
+    token: Option<String>,
+    options: &CloneOptions,
+) -> Result<()> {
+    if dest.exists() {
+        anyhow::bail!("Destination directory already exists");
+    }
+    if !is_valid_slug(slug, host.allows_nested_slug()) {
+        anyhow::bail!(
+            "Invalid {} repository format: expected 'owner/repo', got: {}",
+            host.kind(),
+            slug
+        );
+    }
+    // Reject flag-like refs to prevent git argument injection
+    if let Some(reference) = &options.reference
+        && (reference.is_empty() || reference.starts_with('-'))
+    {
+        anyhow::bail!("Invalid git ref: {:?}", reference);
+    }
+
+    ensure_online(&format!("clone {} repository {slug}", host.kind()))?;
+    std::fs::create_dir_all(dest)?;
+    let result = fetch_checkout(host, slug, dest, token.as_deref(), options);
+    if result.is_err() {
+        let _ = std::fs::remove_dir_all(dest);
+    }
+    result
+}
+
+fn fetch_checkout(
+    host: &dyn CodeHost,
+    slug: &str,
+    dest: &Path,
+    token: Option<&str>,
+    options: &CloneOptions,
+) -> Result<()> {
+    let url = host.clone_url(slug);
+    let credentials = token
+        .map(|t| credential_env(host.base_url(), host.auth_user(), t))
+        .into_iter()
+        .flatten();
+    let git_cmd = crate::github::get_verified_git_path().unwrap_or_else(|| "git".to_string());
+    let git = |args: &[&str], stdin: Option<String>| -> Result<()> {
+        let mut child = Command::new(&git_cmd)
+            .args(args)
+            .envs(parsentry_core::http::git_env())
+            .envs(credentials.clone())
+            .current_dir(dest)
+            .stdin(if stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            pipe.write_all(input.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            let mut stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            if let Some(t) = token {
+                stderr = stderr.replace(t, "***");
+            }
+            anyhow::bail!("git {} failed: {}", args[0], stderr.trim());
+        }
+        Ok(())
+    };
+
+    git(&["init", "-q"], None)?;
+    git(&["remote", "add", "origin", &url], None)?;
+    let sparse = !options.sparse_paths.is_empty();
+    if sparse {
+        // Patterns go through stdin so none can be taken for an option
+        git(
+            &["sparse-checkout", "set", "--no-cone", "--stdin"],
+            Some(options.sparse_paths.join("\n")),
+        )?;
+    }
+    let reference = options.reference.as_deref().unwrap_or("HEAD");
+    let mut fetch = vec!["fetch", "-q", "--depth", "1"];
+    if sparse {
+        fetch.push("--filter=blob:none");
+    }
+    fetch.extend(["origin", reference]);
+    git(&fetch, None)?;
+    git(&["checkout", "-q", "--detach", "FETCH_HEAD"], None)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_host_prefixes() {
+        let target = resolve_hosted_target("gitlab:group/sub/project").unwrap();
+        assert_eq!(target.host.kind(), "gitlab");
+        assert_eq!(target.slug, "group/sub/project");
+
+        let target = resolve_hosted_target("bitbucket:team/repo").unwrap();
+        assert_eq!(target.host.kind(), "bitbucket");
+        assert_eq!(target.slug, "team/repo");
+
+        let target = resolve_hosted_target("owner/repo").unwrap();
+        assert_eq!(target.host.kind(), "github");
+        assert_eq!(target.slug, "owner/repo");
+        assert_eq!(target.reference, None);
+
+        let target = resolve_hosted_target("owner/repo@release/1.2").unwrap();
+        assert_eq!(target.slug, "owner/repo");
+        assert_eq!(target.reference.as_deref(), Some("release/1.2"));
+
+        assert!(resolve_hosted_target("gitlab:project").is_none());
+        assert!(resolve_hosted_target("repo").is_none());
+    }
+
+    #[test]
+    fn validates_slugs() {
+        assert!(is_valid_slug("owner/repo", false));
+        assert!(!is_valid_slug("group/sub/project", false));
+        assert!(is_valid_slug("group/sub/project", true));
+        assert!(!is_valid_slug("owner/repo.git", false));
+        assert!(!is_valid_slug("owner/-repo", false));
+        assert!(!is_valid_slug("owner//repo", true));
+        assert!(!is_valid_slug("owner", true));
+    }
+
+    #[test]
+    fn bitbucket_clone_urls_differ_between_cloud_and_data_center() {
+        let cloud = BitbucketHost {
+            base_url: BITBUCKET_DEFAULT_URL.to_string(),
+        };
+        assert_eq!(
+            cloud.clone_url("team/repo"),
+            "https://bitbucket.org/team/repo.git"
+        );
+
+        let dc = BitbucketHost {
+            base_url: "https://git.example.com".to_string(),
+        };
+        assert_eq!(
+            dc.clone_url("PROJ/repo"),
+            "https://git.example.com/scm/PROJ/repo.git"
+        );
+    }
+
+    #[test]
+    fn clones_pinned_sparse_checkout() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let origin = tmp.path().join("owner/repo.git");
+        std::fs::create_dir_all(origin.join("src")).unwrap();
+        std::fs::create_dir_all(origin.join("docs")).unwrap();
+        let git = |args: &[&str]| {
+            let output = Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+                .args(args)
+                .current_dir(&origin)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {:?} failed", args);
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        git(&["init", "-q"]);
+        git(&["config", "uploadpack.allowFilter", "true"]);
+        git(&["config", "uploadpack.allowAnySHA1InWant", "true"]);
+        std::fs::write(origin.join("src/app.py"), "v1").unwrap();
+        std::fs::write(origin.join("docs/guide.md"), "docs").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-qm", "first"]);
+        let pinned = git(&["rev-parse", "HEAD"]);
+        std::fs::write(origin.join("src/app.py"), "v2").unwrap();
+        git(&["commit", "-qam", "second"]);
+
+        let host = GitHubHost {
+            base_url: format!("file://{}", tmp.path().display()),
+        };
+        let dest = tmp.path().join("checkout");
+        let options = CloneOptions {
+            reference: Some(pinned.clone()),
+            sparse_paths: vec!["src/".to_string()],
+        };
+        clone_hosted_repo(&host, "owner/repo", &dest, None, &options).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(dest.join("src/app.py")).unwrap(),
+            "v1"
+        );
+        assert!(!dest.join("docs/guide.md").exists());
+        let head = Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .current_dir(&dest)
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&head.stdout).trim(), pinned);
+    }
+
+    #[test]
+    fn rejects_flag_like_refs() {
+        let host = GitHubHost::from_env();
+        let tmp = tempfile::TempDir::new().unwrap();
+        let options = CloneOptions {
+            reference: Some("--upload-pack=evil".to_string()),
+            ..Default::default()
+        };
+        let err = clone_hosted_repo(&host, "owner/repo", &tmp.path().join("d"), None, &options)
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid git ref"));
+    }
+
+    #[test]
+    fn credentials_go_in_an_auth_header() {
+        let env = credential_config(0, "https://gitlab.example.com", "oauth2", "t@k");
+        assert_eq!(env[0].1, "1");
+        assert_eq!(env[1].1, "http.https://gitlab.example.com/.extraHeader");
+        assert_eq!(env[2].1, "Authorization: Basic b2F1dGgyOnRAaw==");
+
+        // Appended after the caller's own GIT_CONFIG_* entries.
+        let env = credential_config(2, "https://gitlab.example.com", "oauth2", "t@k");
+        assert_eq!(env[0], ("GIT_CONFIG_COUNT".to_string(), "3".to_string()));
+        assert_eq!(env[1].0, "GIT_CONFIG_KEY_2");
+        assert_eq!(env[2].0, "GIT_CONFIG_VALUE_2");
+    }
+}
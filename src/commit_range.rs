@@ -0,0 +1,301 @@
+//! Lines introduced by a git commit range (`scan --commits A..B`).
+//!
+//! The range's net diff (`A...B`) gives the added lines; `git blame A..B`
+//! attributes each of them to the commit in the range that wrote it. Lines
+//! blamed on a boundary commit predate the range and are left out.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+use parsentry_reports::introduced::{IntroducedChanges, IntroducedHunk, Introduction};
+
+/// `base..head` of a range, with `head` defaulting to `HEAD`.
+fn split_range(range: &str) -> Result<(&str, &str)> {
+    let (base, head) = range
+        .split_once("..")
+        .filter(|(_, head)| !head.starts_with('.'))
+        .with_context(|| format!("--commits must be a range like A..B, got: {range}"))?;
+    let head = if head.is_empty() { "HEAD" } else { head };
+    // Reject flag-like values to prevent git argument injection
+    if base.is_empty() || base.starts_with('-') || head.starts_with('-') {
+        anyhow::bail!("Invalid commit range: {range}");
+    }
+    Ok((base, head))
+}
+
+fn git(root_dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(root_dir)
+        .output()
+        .with_context(|| format!("git {} failed to start", args[0]))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn resolve_commit(root_dir: &Path, rev: &str) -> Option<String> {
+    git(
+        root_dir,
+        &["rev-parse", "--verify", "-q", &format!("{rev}^{{commit}}")],
+    )
+    .ok()
+    .map(|sha| sha.trim().to_string())
+}
+
+/// Lines introduced by the commits of `range` in the checkout at `root_dir`,
+/// whose `HEAD` must be the end of the range. Shallow clones are deepened
+/// when the start of the range is not part of them.
+pub fn introduced_changes(root_dir: &Path, range: &str) -> Result<IntroducedChanges> {
+    let (base, head) = split_range(range)?;
+    if resolve_commit(root_dir, base).is_none()
+        && git(root_dir, &["rev-parse", "--is-shallow-repository"])?.trim() == "true"
+    {
+        git(root_dir, &["fetch", "-q", "--unshallow", "origin"])?;
+    }
+    let base_sha = resolve_commit(root_dir, base)
+        .with_context(|| format!("unknown revision {base} in {}", root_dir.display()))?;
+    let head_sha = resolve_commit(root_dir, head)
+        .with_context(|| format!("unknown revision {head} in {}", root_dir.display()))?;
+    if resolve_commit(root_dir, "HEAD").as_deref() != Some(head_sha.as_str()) {
+        anyhow::bail!(
+            "{head} is not checked out in {}; check it out (or pin owner/repo@{head}) to scan {range}",
+            root_dir.display()
+        );
+    }
+
+    let diff = git(
+        root_dir,
+        &[
+            "-c",
+            "core.quotePath=false",
+            "diff",
+            "-U0",
+            "--no-color",
+            "--no-ext-diff",
+            &format!("{base_sha}...{head_sha}"),
+        ],
+    )?;
+    let mut by_file: Vec<(String, Vec<(usize, usize)>)> = Vec::new();
+    for (path, start, count) in parse_added_hunks(&diff) {
+        match by_file.last_mut() {
+            Some((last, ranges)) if *last == path => ranges.push((start, count)),
+            _ => by_file.push((path, vec![(start, count)])),
+        }
+    }
+
+    let mut hunks = Vec::new();
+    for (path, ranges) in by_file {
+        let mut args = vec!["blame".to_string(), "--porcelain".to_string()];
+        for (start, count) in ranges {
+            args.push(format!("-L{start},+{count}"));
+        }
+        args.extend([
+            format!("{base_sha}..{head_sha}"),
+            "--".to_string(),
+            path.clone(),
+        ]);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        hunks.extend(group_blame(&path, &git(root_dir, &args)?));
+    }
+
+    Ok(IntroducedChanges {
+        range: range.to_string(),
+        hunks,
+    })
+}
+
+/// `(path, first line, line count)` of every added range in a `-U0` diff.
+fn parse_added_hunks(diff: &str) -> Vec<(String, usize, usize)> {
+    let mut hunks = Vec::new();
+    let mut path: Option<String> = None;
+    for line in diff.lines() {
+        if let Some(target) = line.strip_prefix("+++ ") {
+            path = target.strip_prefix("b/").map(str::to_string);
+        } else if let (Some(header), Some(path)) = (line.strip_prefix("@@ "), &path) {
+            let Some(new) = header.split(' ').find_map(|part| part.strip_prefix('+')) else {
+                continue;
+            };
+            let (start, count) = match new.split_once(',') {
+                Some((start, count)) => (start.parse().ok(), count.parse().ok()),
+                None => (new.parse().ok(), Some(1)),
+            };
+            if let (Some(start), Some(count)) = (start, count)
+                && count > 0
+            {
+                hunks.push((path.clone(), start, count));
+            }
+        }
+    }
+    hunks
+}
+
+/// Group `git blame --porcelain` output into runs of lines by the same
+/// commit, leaving out lines blamed on a boundary commit.
+fn group_blame(path: &str, porcelain: &str) -> Vec<IntroducedHunk> {
+    #[derive(Default)]
+    struct Commit {
+        author: String,
+        mail: String,
+        summary: String,
+        boundary: bool,
+    }
+    let mut commits: HashMap<String, Commit> = HashMap::new();
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    let mut current = String::new();
+    for line in porcelain.lines() {
+        if line.starts_with('\t') {
+            continue;
+        }
+        let mut parts = line.split(' ');
+        let first = parts.next().unwrap_or_default();
+        if first.len() >= 40 && first.chars().all(|c| c.is_ascii_hexdigit()) {
+            if let Some(final_line) = parts.nth(1).and_then(|n| n.parse().ok()) {
+                current = first.to_string();
+                commits.entry(current.clone()).or_default();
+                lines.push((final_line, current.clone()));
+            }
+            continue;
+        }
+        let Some(commit) = commits.get_mut(&current) else {
+            continue;
+        };
+        let value = line.split_once(' ').map_or("", |(_, v)| v).to_string();
+        match first {
+            "author" => commit.author = value,
+            "author-mail" => commit.mail = value,
+            "summary" => commit.summary = value,
+            "boundary" => commit.boundary = true,
+            _ => {}
+        }
+    }
+
+    lines.sort();
+    let mut hunks: Vec<IntroducedHunk> = Vec::new();
+    for (line, sha) in lines {
+        let commit = &commits[&sha];
+        if commit.boundary {
+            continue;
+        }
+        if let Some(last) = hunks.last_mut()
+            && last.introduction.commit == sha
+            && last.end_line + 1 == line
+        {
+            last.end_line = line;
+            continue;
+        }
+        hunks.push(IntroducedHunk {
+            path: path.to_string(),
+            start_line: line,
+            end_line: line,
+            introduction: Introduction {
+                commit: sha,
+                author: format!("{} {}", commit.author, commit.mail),
+                summary: commit.summary.clone(),
+            },
+        });
+    }
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_ranges() {
+        assert_eq!(split_range("v1.2..v1.3").unwrap(), ("v1.2", "v1.3"));
+        assert_eq!(split_range("main..").unwrap(), ("main", "HEAD"));
+        assert!(split_range("main...topic").is_err());
+        assert!(split_range("abc123").is_err());
+        assert!(split_range("--output=x..HEAD").is_err());
+    }
+
+    #[test]
+    fn parses_added_hunks() {
+        let diff = "\
+diff --git a/app.py b/app.py
+--- a/app.py
++++ b/app.py
+@@ -3,0 +4,2 @@ def main():
++    run(input())
++    log()
+@@ -10 +12 @@
+-old
++new
+@@ -20,3 +21,0 @@
+-gone
+diff --git a/old.py b/old.py
+--- a/old.py
++++ /dev/null
+@@ -1 +0,0 @@
+-removed
+";
+        assert_eq!(
+            parse_added_hunks(diff),
+            vec![("app.py".to_string(), 4, 2), ("app.py".to_string(), 12, 1)]
+        );
+    }
+
+    #[test]
+    fn groups_blame_by_commit_without_boundary() {
+        let a = "a".repeat(40);
+        let b = "b".repeat(40);
+        let porcelain = format!(
+            "{a} 4 4 2\nauthor Alice\nauthor-mail <alice@example.com>\nsummary Add upload\nfilename app.py\n\tline\n\
+             {a} 5 5\n\tline\n\
+             {b} 6 6 1\nauthor Bob\nauthor-mail <bob@example.com>\nsummary Initial\nboundary\nfilename app.py\n\tline\n\
+             {a} 9 7 1\n\tline\n"
+        );
+        let hunks = group_blame("app.py", &porcelain);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!((hunks[0].start_line, hunks[0].end_line), (4, 5));
+        assert_eq!(hunks[0].introduction.author, "Alice <alice@example.com>");
+        assert_eq!(hunks[0].introduction.summary, "Add upload");
+        assert_eq!((hunks[1].start_line, hunks[1].end_line), (7, 7));
+    }
+
+    #[test]
+    fn attributes_lines_introduced_in_range() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = tmp.path();
+        let commit = |message: &str, author: &str| {
+            let output = Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+                .args(["commit", "-qam", message, "--author", author])
+                .current_dir(root)
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+        };
+        git(root, &["init", "-q"]).unwrap();
+        std::fs::write(root.join("app.py"), "a\nb\n").unwrap();
+        git(root, &["add", "."]).unwrap();
+        commit("base", "Base <base@example.com>");
+        git(root, &["tag", "v1"]).unwrap();
+        std::fs::write(root.join("app.py"), "a\nnew1\nb\n").unwrap();
+        commit("first", "Alice <alice@example.com>");
+        std::fs::write(root.join("app.py"), "a\nnew1\nb\nnew2\n").unwrap();
+        commit("second", "Bob <bob@example.com>");
+
+        let changes = introduced_changes(root, "v1..").unwrap();
+        let attributed: Vec<(usize, &str)> = changes
+            .hunks
+            .iter()
+            .map(|h| (h.start_line, h.introduction.summary.as_str()))
+            .collect();
+        assert_eq!(attributed, vec![(2, "first"), (4, "second")]);
+        assert_eq!(
+            changes.hunks[1].introduction.author,
+            "Bob <bob@example.com>"
+        );
+        assert!(introduced_changes(root, "v1..HEAD~1").is_err());
+    }
+}
@@ -0,0 +1,359 @@
+//! Known configuration settings.
+//!
+//! Parsentry is configured through environment variables (optionally loaded
+//! from `.env`). This module is the single list of recognised variables so
+//! that typos such as `PARSENTRY_CACHEDIR` can be reported instead of being
+//! silently ignored.
+
+use std::collections::HashMap;
+
+/// Prefix reserved for Parsentry's own variables; unknown names under it are typos.
+pub const ENV_PREFIX: &str = "PARSENTRY_";
+
+pub struct Setting {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Value used when the variable is unset, if there is a fixed one.
+    pub default: Option<&'static str>,
+    /// Mask the value in `config show`.
+    pub secret: bool,
+}
+
+const fn setting(name: &'static str, description: &'static str) -> Setting {
+    Setting {
+        name,
+        description,
+        default: None,
+        secret: false,
+    }
+}
+
+const fn with_default(
+    name: &'static str,
+    description: &'static str,
+    default: &'static str,
+) -> Setting {
+    Setting {
+        name,
+        description,
+        default: Some(default),
+        secret: false,
+    }
+}
+
+const fn secret(name: &'static str, description: &'static str) -> Setting {
+    Setting {
+        name,
+        description,
+        default: None,
+        secret: true,
+    }
+}
+
+pub const SETTINGS: &[Setting] = &[
+    setting(
+        "PARSENTRY_CACHE_DIR",
+        "cache root (default: XDG cache dir/parsentry)",
+    ),
+    setting("PARSENTRY_PDF_TOOL", "path to tools/pdf-report"),
+    with_default("PARSENTRY_LOG", "log filter (EnvFilter syntax)", "warn"),
+    setting("PARSENTRY_NOTIFY_TEMPLATE", "webhook message template"),
+    setting(
+        "PARSENTRY_CA_BUNDLE",
+        "PEM bundle of extra CA certificates trusted by HTTP clients and git (TLS-intercepting proxies)",
+    ),
+    setting(
+        "PARSENTRY_REDACT",
+        "1 redacts secrets and personal data in reports, worker results and transcripts",
+    ),
+    setting(
+        "PARSENTRY_REDACT_PATTERNS",
+        "file of extra redaction patterns, one `kind: regex` per line",
+    ),
+    with_default(
+        "PARSENTRY_PROFILE",
+        "scan profile: quick, standard or deep",
+        "standard",
+    ),
+    setting(
+        "PARSENTRY_INCLUDE",
+        "comma-separated globs to scan (--include)",
+    ),
+    setting(
+        "PARSENTRY_EXCLUDE",
+        "comma-separated globs to skip (--exclude)",
+    ),
+    setting(
+        "PARSENTRY_LANGUAGE_OVERRIDES",
+        "comma-separated GLOB=LANGUAGE pairs forcing a file's language (e.g. bin/*=bash)",
+    ),
+    setting(
+        "PARSENTRY_PATTERNS_DIR",
+        "installed pattern packs (default: data dir/parsentry/patterns)",
+    ),
+    setting(
+        "PARSENTRY_PATTERN_REGISTRY",
+        "base URL for `patterns install <name>`",
+    ),
+    setting(
+        "PARSENTRY_DISABLED_PATTERNS",
+        "comma-separated pattern descriptions or tags to skip",
+    ),
+    setting(
+        "PARSENTRY_MODEL",
+        "worker model hint, or comma-separated fallback chain (overrides the profile's model)",
+    ),
+    setting(
+        "PARSENTRY_NO_SOURCE_UPLOAD",
+        "keep source code out of prompts: paths or hashed (--no-source-upload)",
+    ),
+    with_default(
+        "PARSENTRY_LANGUAGE",
+        "language workers write findings in: en, ja, zh, ko, es, de, fr or pt",
+        "en",
+    ),
+    setting(
+        "PARSENTRY_EXAMPLES_DIR",
+        "directory of few-shot examples (code file plus <name>.sarif.json) for surface prompts",
+    ),
+    setting(
+        "PARSENTRY_PROMPT_TEMPLATE",
+        "file whose text is appended to surface prompts, or replaces them if it uses {{default}}",
+    ),
+    setting(
+        "PARSENTRY_REPORT_TEMPLATE",
+        "Markdown template for report.md with {{summary}}, {{findings}}, {{report}}, ... placeholders",
+    ),
+    setting(
+        "PARSENTRY_FILENAME_TEMPLATE",
+        "names of `generate --split-by-file` reports, e.g. {relpath}/{pattern_slug}.md",
+    ),
+    with_default(
+        "PARSENTRY_REPORT_LAYOUT",
+        "layout of `generate --split-by-file` reports: flat or mirrored",
+        "flat",
+    ),
+    with_default(
+        "PARSENTRY_MAX_PROMPT_TOKENS",
+        "estimated tokens a surface prompt may use before related functions are shortened",
+        "12000",
+    ),
+    setting(
+        "PARSENTRY_WORKER_TIMEOUT_MINS",
+        "minutes before a single worker is given up on",
+    ),
+    setting(
+        "PARSENTRY_SCAN_TIMEOUT_MINS",
+        "minutes before all remaining workers are given up on",
+    ),
+    setting(
+        "PARSENTRY_SANDBOX",
+        "PoC sandbox: bwrap, podman or docker (default: first available)",
+    ),
+    with_default(
+        "PARSENTRY_POC_IMAGE",
+        "container image for PoCs run with podman or docker",
+        "python:3-slim",
+    ),
+    with_default(
+        "PARSENTRY_GITHUB_URL",
+        "GitHub base URL",
+        "https://github.com",
+    ),
+    with_default(
+        "PARSENTRY_GITLAB_URL",
+        "GitLab base URL",
+        "https://gitlab.com",
+    ),
+    with_default(
+        "PARSENTRY_BITBUCKET_URL",
+        "Bitbucket base URL",
+        "https://bitbucket.org",
+    ),
+    secret(
+        "GITHUB_TOKEN",
+        "GitHub token for clone, issues and PR comments",
+    ),
+    setting("GITHUB_APP_ID", "GitHub App ID (instead of GITHUB_TOKEN)"),
+    setting("GITHUB_APP_INSTALLATION_ID", "GitHub App installation ID"),
+    secret(
+        "GITHUB_APP_PRIVATE_KEY",
+        "GitHub App private key (PEM or path to it)",
+    ),
+    secret("GITLAB_TOKEN", "GitLab token for clone and search"),
+    secret("BITBUCKET_TOKEN", "Bitbucket token for clone and search"),
+    setting("JIRA_URL", "Jira site URL"),
+    setting("JIRA_EMAIL", "Jira account email"),
+    secret("JIRA_API_TOKEN", "Jira API token"),
+    secret("LINEAR_API_KEY", "Linear API key"),
+    secret("NOTION_TOKEN", "Notion integration token"),
+    setting("NOTION_DATABASE_ID", "Notion database ID"),
+    setting("DEFECTDOJO_URL", "DefectDojo URL"),
+    setting(
+        "PARSENTRY_CALIBRATION",
+        "Confidence calibration file from `parsentry eval --calibration`, applied by generate and merge",
+    ),
+    setting(
+        "PARSENTRY_SARIF_URI_PREFIX",
+        "Path of the scanned directory within its repository, prepended to SARIF artifact URIs",
+    ),
+    setting(
+        "PARSENTRY_ASFF_ACCOUNT_ID",
+        "AWS account id Security Hub findings are imported into (generate --format asff)",
+    ),
+    setting(
+        "PARSENTRY_ASFF_REGION",
+        "AWS region of those findings (default: AWS_REGION or AWS_DEFAULT_REGION)",
+    ),
+    secret("DEFECTDOJO_API_KEY", "DefectDojo API v2 key"),
+    setting(
+        "OTEL_EXPORTER_OTLP_ENDPOINT",
+        "OTLP endpoint (otel feature)",
+    ),
+];
+
+pub fn lookup(name: &str) -> Option<&'static Setting> {
+    SETTINGS.iter().find(|s| s.name == name)
+}
+
+/// Where an effective value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Env,
+    DotEnv,
+    Default,
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Env => write!(f, "env"),
+            Source::DotEnv => write!(f, ".env"),
+            Source::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// Resolve a setting against the process environment and the `.env` entries.
+///
+/// `.env` never overrides the real environment, so a value only counts as
+/// coming from `.env` when it matches what the file specifies.
+pub fn resolve(
+    setting: &Setting,
+    env: &HashMap<String, String>,
+    dotenv: &HashMap<String, String>,
+) -> Option<(String, Source)> {
+    match env.get(setting.name) {
+        Some(value) if dotenv.get(setting.name) == Some(value) => {
+            Some((value.clone(), Source::DotEnv))
+        }
+        Some(value) => Some((value.clone(), Source::Env)),
+        None => setting.default.map(|d| (d.to_string(), Source::Default)),
+    }
+}
+
+pub fn mask(value: &str) -> String {
+    if value.len() <= 4 {
+        "****".to_string()
+    } else {
+        format!("{}****", value.chars().take(4).collect::<String>())
+    }
+}
+
+/// Levenshtein distance, used for "did you mean" suggestions.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            cur.push((prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1));
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+/// Closest known setting name, if it is plausibly a typo.
+pub fn suggest(name: &str) -> Option<&'static str> {
+    SETTINGS
+        .iter()
+        .map(|s| (edit_distance(name, s.name), s.name))
+        .filter(|(d, _)| *d <= 3)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, n)| n)
+}
+
+/// SHA-256 of the non-secret settings set in `env`, telling scans apart by
+/// configuration without recording credentials.
+pub fn config_hash(env: &HashMap<String, String>) -> String {
+    let settings: String = SETTINGS
+        .iter()
+        .filter(|s| !s.secret)
+        .filter_map(|s| Some(format!("{}={}\n", s.name, env.get(s.name)?)))
+        .collect();
+    crate::prompt::hex_sha256(&settings)
+}
+
+/// `PARSENTRY_*` variables that are not recognised settings.
+pub fn unknown_variables<'a>(names: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut unknown: Vec<&str> = names
+        .filter(|n| n.starts_with(ENV_PREFIX) && lookup(n).is_none())
+        .collect();
+    unknown.sort();
+    unknown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest_typo() {
+        assert_eq!(suggest("PARSENTRY_CACHEDIR"), Some("PARSENTRY_CACHE_DIR"));
+        assert_eq!(suggest("PARSENTRY_GITLAB_UR"), Some("PARSENTRY_GITLAB_URL"));
+        assert_eq!(suggest("PARSENTRY_SOMETHING_ELSE"), None);
+    }
+
+    #[test]
+    fn test_unknown_variables_only_checks_prefix() {
+        let names = ["PARSENTRY_CACHE_DIR", "PARSENTRY_CAHCE_DIR", "HOME"];
+        assert_eq!(
+            unknown_variables(names.into_iter()),
+            vec!["PARSENTRY_CAHCE_DIR"]
+        );
+    }
+
+    #[test]
+    fn test_config_hash_ignores_secrets_and_unknown_names() {
+        let secret = SETTINGS.iter().find(|s| s.secret).unwrap();
+        let mut env =
+            HashMap::from([("PARSENTRY_CACHE_DIR".to_string(), "/tmp/cache".to_string())]);
+        let hash = config_hash(&env);
+        env.insert(secret.name.to_string(), "token".to_string());
+        env.insert("HOME".to_string(), "/root".to_string());
+        assert_eq!(config_hash(&env), hash);
+        env.insert("PARSENTRY_CACHE_DIR".to_string(), "/var/cache".to_string());
+        assert_ne!(config_hash(&env), hash);
+    }
+
+    #[test]
+    fn test_resolve_attributes_source() {
+        let s = lookup("PARSENTRY_GITLAB_URL").unwrap();
+        let mut env = HashMap::new();
+        let mut dotenv = HashMap::new();
+        assert_eq!(resolve(s, &env, &dotenv).unwrap().1, Source::Default);
+        env.insert(s.name.to_string(), "https://a".to_string());
+        assert_eq!(resolve(s, &env, &dotenv).unwrap().1, Source::Env);
+        dotenv.insert(s.name.to_string(), "https://a".to_string());
+        assert_eq!(resolve(s, &env, &dotenv).unwrap().1, Source::DotEnv);
+        assert!(resolve(lookup("JIRA_URL").unwrap(), &HashMap::new(), &dotenv).is_none());
+    }
+
+    #[test]
+    fn test_mask() {
+        assert_eq!(mask("ghp_abcdef"), "ghp_****");
+        assert_eq!(mask("abc"), "****");
+    }
+}
@@ -0,0 +1,162 @@
+//! Few-shot examples for surface prompts (`PARSENTRY_EXAMPLES_DIR`).
+//!
+//! The directory holds pairs of a code sample and the SARIF expected for it,
+//! e.g. `sqli.py` with `sqli.sarif.json`. A surface is shown the examples in
+//! its own languages, those whose rule ids its description mentions first.
+//! An example whose SARIF has no results shows code that must not be
+//! reported, which helps smaller models keep false positives down.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+use parsentry_core::{AttackSurface, Language};
+
+/// Suffix of the expected-output file paired with a code sample.
+const EXPECTED_SUFFIX: &str = ".sarif.json";
+
+/// Maximum number of examples added to one surface prompt.
+pub const MAX_EXAMPLES: usize = 2;
+
+/// A code sample and the SARIF a worker should produce for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FewShotExample {
+    /// File name of the code sample.
+    pub name: String,
+    pub language: Language,
+    pub code: String,
+    pub expected: String,
+    /// Rule ids of the expected results; empty for a negative example.
+    pub rule_ids: Vec<String>,
+}
+
+/// The directory named by `PARSENTRY_EXAMPLES_DIR`, if set.
+pub fn examples_dir() -> Option<PathBuf> {
+    std::env::var_os("PARSENTRY_EXAMPLES_DIR")
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Load every example pair in `dir`, sorted by name. Code samples without
+/// an expected-output file, or in an unsupported language, are ignored.
+pub fn load_examples(dir: &Path) -> Result<Vec<FewShotExample>> {
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("cannot read examples directory {}", dir.display()))?;
+    let mut examples = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if name.ends_with(EXPECTED_SUFFIX) || !path.is_file() {
+            continue;
+        }
+        let language = Language::from_filename(name);
+        if language == Language::Other {
+            continue;
+        }
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or(name);
+        let expected_path = dir.join(format!("{stem}{EXPECTED_SUFFIX}"));
+        let Ok(expected) = std::fs::read_to_string(&expected_path) else {
+            continue;
+        };
+        let report: serde_json::Value = serde_json::from_str(&expected)
+            .with_context(|| format!("invalid JSON in {}", expected_path.display()))?;
+        let results = |run: &serde_json::Value| run["results"].as_array().cloned();
+        let mut rule_ids: Vec<String> = report["runs"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(results)
+            .flatten()
+            .filter_map(|r| r["ruleId"].as_str().map(str::to_string))
+            .collect();
+        rule_ids.sort();
+        rule_ids.dedup();
+        examples.push(FewShotExample {
+            name: name.to_string(),
+            language,
+            code: std::fs::read_to_string(&path)
+                .with_context(|| format!("cannot read example {}", path.display()))?,
+            expected: expected.trim().to_string(),
+            rule_ids,
+        });
+    }
+    examples.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(examples)
+}
+
+/// Up to [`MAX_EXAMPLES`] examples in one of `languages`, preferring those
+/// whose rule ids are mentioned by the surface.
+pub fn select_examples<'a>(
+    examples: &'a [FewShotExample],
+    surface: &AttackSurface,
+    languages: &[Language],
+) -> Vec<&'a FewShotExample> {
+    let hint = format!(
+        "{} {} {}",
+        surface.kind, surface.identifier, surface.description
+    )
+    .to_lowercase();
+    let mentioned = |example: &FewShotExample| {
+        example
+            .rule_ids
+            .iter()
+            .any(|id| hint.contains(&id.to_lowercase()))
+    };
+    let mut selected: Vec<&FewShotExample> = examples
+        .iter()
+        .filter(|e| languages.contains(&e.language))
+        .collect();
+    selected.sort_by_key(|e| !mentioned(e));
+    selected.truncate(MAX_EXAMPLES);
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parsentry_reports::test_support::{ResultFixture, report, result};
+
+    fn surface(description: &str) -> AttackSurface {
+        AttackSurface {
+            id: "S-1".to_string(),
+            kind: "endpoint".to_string(),
+            identifier: "GET /search".to_string(),
+            locations: vec!["app.py".to_string()],
+            description: description.to_string(),
+        }
+    }
+
+    fn sarif(rule_ids: &[&str]) -> String {
+        let results = rule_ids.iter().map(|id| result(id, "").unlocated());
+        serde_json::to_string(&report(results)).unwrap()
+    }
+
+    #[test]
+    fn selects_examples_by_language_and_rule() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, contents: &str| std::fs::write(dir.path().join(name), contents);
+        write("a_safe.py", "cursor.execute(q, (name,))").unwrap();
+        write("a_safe.sarif.json", &sarif(&[])).unwrap();
+        write("b_xss.py", "return f'<p>{name}</p>'").unwrap();
+        write("b_xss.sarif.json", &sarif(&["XSS"])).unwrap();
+        write("c_sqli.py", "cursor.execute(f'... {name}')").unwrap();
+        write("c_sqli.sarif.json", &sarif(&["SQLI"])).unwrap();
+        write("d_sqli.go", "db.Query(q + name)").unwrap();
+        write("d_sqli.sarif.json", &sarif(&["SQLI"])).unwrap();
+        write("unpaired.py", "pass").unwrap();
+
+        let examples = load_examples(dir.path()).unwrap();
+        assert_eq!(examples.len(), 4);
+        assert_eq!(examples[2].rule_ids, vec!["SQLI"]);
+
+        let selected = select_examples(
+            &examples,
+            &surface("search query built into SQLi-prone SQL"),
+            &[Language::Python],
+        );
+        let names: Vec<&str> = selected.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["c_sqli.py", "a_safe.py"]);
+        assert!(select_examples(&examples, &surface(""), &[Language::Rust]).is_empty());
+    }
+}
@@ -1,11 +1,27 @@
 use anyhow::{Result, anyhow};
-use octocrab::Octocrab;
+use http::Uri;
+use http::header::{HeaderValue, USER_AGENT};
+use jsonwebtoken::EncodingKey;
+use octocrab::auth::AppAuth;
+use octocrab::models::{AppId, InstallationId};
+use octocrab::service::middleware::auth_header::AuthHeaderLayer;
+use octocrab::service::middleware::base_uri::BaseUriLayer;
+use octocrab::service::middleware::extra_headers::ExtraHeadersLayer;
+use octocrab::{AuthState, Octocrab, OctocrabBuilder};
+use secrecy::ExposeSecret;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::env;
 use std::io::Write;
 use std::path::Path;
+use std::pin::Pin;
 use std::process::{Command, Stdio};
-use tracing::debug;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+use parsentry_core::offline::ensure_online;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
@@ -20,10 +36,276 @@ pub struct SearchResult {
 
 pub struct GitHubSearchClient {
     client: Octocrab,
+    authenticated: bool,
+}
+
+/// Repositories an organization or topic sweep leaves out unless asked.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RepositoryFilter {
+    pub include_forks: bool,
+    pub include_archived: bool,
+}
+
+impl RepositoryFilter {
+    fn allows(&self, repo: &RepositoryItem) -> bool {
+        (self.include_forks || !repo.fork) && (self.include_archived || !repo.archived)
+    }
+}
+
+/// Search query for the repositories of `org` with `topic`, or `None` when
+/// there is no topic and the organization is listed directly.
+fn sweep_query(org: Option<&str>, topic: Option<&str>, filter: RepositoryFilter) -> Option<String> {
+    let mut query = format!("topic:{}", topic?);
+    if let Some(org) = org {
+        query.push_str(&format!(" org:{org}"));
+    }
+    if filter.include_forks {
+        query.push_str(" fork:true");
+    }
+    if !filter.include_archived {
+        query.push_str(" archived:false");
+    }
+    Some(query)
+}
+
+const GITHUB_API_URL: &str = "https://api.github.com";
+const GITHUB_UPLOAD_URL: &str = "https://uploads.github.com";
+
+/// Rate-limited requests retried before the error is surfaced.
+const MAX_RATE_LIMIT_RETRIES: usize = 3;
+/// Longest rate-limit reset worth sleeping through.
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(15 * 60);
+/// Wait GitHub asks for after hitting a secondary rate limit.
+const SECONDARY_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+/// Installation tokens expire after an hour; mint a new one well before.
+const INSTALLATION_TOKEN_TTL: Duration = Duration::from_secs(50 * 60);
+
+static INSTALLATION_TOKEN: Mutex<Option<(String, Instant)>> = Mutex::new(None);
+
+/// Credentials for the GitHub API and HTTPS clones.
+enum GitHubAuth {
+    /// GitHub App installation, from `GITHUB_APP_ID`,
+    /// `GITHUB_APP_INSTALLATION_ID` and `GITHUB_APP_PRIVATE_KEY`.
+    App {
+        app_id: u64,
+        installation_id: u64,
+        private_key: String,
+    },
+    /// Personal access token from git credential helper or `GITHUB_TOKEN`.
+    Token(String),
+    Anonymous,
+}
+
+impl GitHubAuth {
+    fn from_env() -> Result<Self> {
+        let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+        let Some(app_id) = var("GITHUB_APP_ID") else {
+            return Ok(GitHubSearchClient::get_token_from_credential_helper()
+                .or_else(|| var("GITHUB_TOKEN"))
+                .filter(|t| !t.is_empty())
+                .map_or(Self::Anonymous, Self::Token));
+        };
+        let required =
+            |name: &str| var(name).ok_or_else(|| anyhow!("GITHUB_APP_ID is set but {name} is not"));
+        let installation_id = required("GITHUB_APP_INSTALLATION_ID")?;
+        let key = required("GITHUB_APP_PRIVATE_KEY")?;
+        let private_key = if key.contains("-----BEGIN") {
+            key
+        } else {
+            std::fs::read_to_string(&key)
+                .map_err(|e| anyhow!("Failed to read GITHUB_APP_PRIVATE_KEY file {key}: {e}"))?
+        };
+        Ok(Self::App {
+            app_id: app_id
+                .parse()
+                .map_err(|_| anyhow!("GITHUB_APP_ID must be numeric, got: {app_id}"))?,
+            installation_id: installation_id.parse().map_err(|_| {
+                anyhow!("GITHUB_APP_INSTALLATION_ID must be numeric, got: {installation_id}")
+            })?,
+            private_key,
+        })
+    }
+
+    fn is_authenticated(&self) -> bool {
+        !matches!(self, Self::Anonymous)
+    }
+
+    /// Client authenticated as the app itself, for minting installation tokens.
+    fn app_client(app_id: u64, private_key: &str) -> Result<Octocrab> {
+        let key = EncodingKey::from_rsa_pem(private_key.as_bytes())
+            .map_err(|e| anyhow!("Invalid GITHUB_APP_PRIVATE_KEY: {e}"))?;
+        octocrab_client(
+            None,
+            AuthState::App(AppAuth {
+                app_id: AppId(app_id),
+                key,
+            }),
+        )
+    }
+
+    fn client(&self) -> Result<Octocrab> {
+        match self {
+            Self::App {
+                app_id,
+                installation_id,
+                private_key,
+            } => Self::app_client(*app_id, private_key)?
+                .installation(InstallationId(*installation_id))
+                .map_err(|e| anyhow!("Failed to create GitHub client: {}", e)),
+            Self::Token(token) => octocrab_client(
+                Some(
+                    HeaderValue::from_str(&format!("Bearer {token}"))
+                        .map_err(|_| anyhow!("GitHub token contains invalid characters"))?,
+                ),
+                AuthState::None,
+            ),
+            Self::Anonymous => octocrab_client(None, AuthState::None),
+        }
+    }
+}
+
+/// GitHub API client sending its requests through [`ReqwestTransport`].
+fn octocrab_client(auth_header: Option<HeaderValue>, auth: AuthState) -> Result<Octocrab> {
+    let base_uri = Uri::from_static(GITHUB_API_URL);
+    let upload_uri = Uri::from_static(GITHUB_UPLOAD_URL);
+    let user_agent = HeaderValue::from_static(concat!("parsentry/", env!("CARGO_PKG_VERSION")));
+    OctocrabBuilder::new_empty()
+        .with_service(ReqwestTransport(parsentry_core::http::client()?))
+        .with_layer(&ExtraHeadersLayer::new(Arc::new(vec![(
+            USER_AGENT, user_agent,
+        )])))
+        .with_layer(&BaseUriLayer::new(base_uri.clone()))
+        .with_layer(&AuthHeaderLayer::new(auth_header, base_uri, upload_uri))
+        .with_auth(auth)
+        .build()
+        .map_err(|e| anyhow!("Failed to create GitHub client: {}", e))
+}
+
+/// Octocrab's own transport ignores `HTTPS_PROXY` and trusts only the
+/// system roots; GitHub API requests go through the shared reqwest client
+/// instead, with its proxy and CA settings.
+#[derive(Clone)]
+struct ReqwestTransport(reqwest::Client);
+
+impl<B> tower_service::Service<http::Request<B>> for ReqwestTransport
+where
+    B: http_body::Body + Send + Sync + 'static,
+    B::Data: Into<bytes::Bytes>,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    type Response = http::Response<reqwest::Body>;
+    type Error = reqwest::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let client = self.0.clone();
+        Box::pin(async move {
+            let request = reqwest::Request::try_from(request.map(reqwest::Body::wrap))?;
+            Ok(client.execute(request).await?.into())
+        })
+    }
+}
+
+/// The configured GitHub credentials, for `parsentry doctor`; `None` when
+/// requests are anonymous. Fails on an incomplete or unreadable App setup.
+pub fn describe_credentials() -> Result<Option<String>> {
+    Ok(match GitHubAuth::from_env()? {
+        GitHubAuth::App {
+            app_id,
+            installation_id,
+            private_key,
+        } => {
+            EncodingKey::from_rsa_pem(private_key.as_bytes())
+                .map_err(|e| anyhow!("Invalid GITHUB_APP_PRIVATE_KEY: {e}"))?;
+            Some(format!(
+                "GitHub App {app_id} (installation {installation_id})"
+            ))
+        }
+        GitHubAuth::Token(_) => Some("personal access token".to_string()),
+        GitHubAuth::Anonymous => None,
+    })
+}
+
+/// Token for HTTPS git access to GitHub: the personal access token, or a
+/// GitHub App installation token (minted once and reused until near expiry).
+pub async fn clone_token() -> Result<Option<String>> {
+    let (app_id, installation_id, private_key) = match GitHubAuth::from_env()? {
+        GitHubAuth::Token(token) => return Ok(Some(token)),
+        GitHubAuth::Anonymous => return Ok(None),
+        GitHubAuth::App {
+            app_id,
+            installation_id,
+            private_key,
+        } => (app_id, installation_id, private_key),
+    };
+    if let Some((token, minted)) = INSTALLATION_TOKEN.lock().unwrap().clone()
+        && minted.elapsed() < INSTALLATION_TOKEN_TTL
+    {
+        return Ok(Some(token));
+    }
+    ensure_online("GitHub App installation token")?;
+    let (_, token) = GitHubAuth::app_client(app_id, &private_key)?
+        .installation_and_token(InstallationId(installation_id))
+        .await
+        .map_err(|e| github_error("GitHub App installation token request", e, true))?;
+    let token = token.expose_secret().to_string();
+    *INSTALLATION_TOKEN.lock().unwrap() = Some((token.clone(), Instant::now()));
+    Ok(Some(token))
+}
+
+/// Whether a GitHub API error is a primary or secondary rate limit.
+fn is_rate_limited(status: u16, message: &str) -> bool {
+    status == 429 || (status == 403 && message.to_lowercase().contains("rate limit"))
+}
+
+/// Time until a rate limit that resets at the `reset` epoch second.
+fn rate_limit_reset_wait(reset: u64, now: u64) -> Duration {
+    Duration::from_secs(reset.saturating_sub(now) + 1)
+}
+
+/// Explain a failed GitHub API request, pointing at the credential or scope
+/// that is likely missing.
+fn describe_github_error(action: &str, status: u16, message: &str, authenticated: bool) -> String {
+    let hint = match status {
+        401 => {
+            "the GitHub credentials were rejected; check that GITHUB_TOKEN or the GitHub App key is valid and not expired"
+        }
+        403 | 429 if is_rate_limited(status, message) && !authenticated => {
+            "unauthenticated requests have a low rate limit; set GITHUB_TOKEN or configure a GitHub App"
+        }
+        403 | 429 if is_rate_limited(status, message) => {
+            "the rate limit does not reset soon enough; retry later"
+        }
+        403 | 404 if !authenticated => {
+            "private repositories and organizations require GITHUB_TOKEN or a GitHub App"
+        }
+        403 => {
+            "the token lacks a required scope: classic tokens need `repo` (and `read:org` for organization sweeps); fine-grained tokens and GitHub Apps need read access to Metadata and Contents"
+        }
+        404 => "not found, or not visible to this token; check its scopes and repository access",
+        _ => return format!("{action} failed: {message}"),
+    };
+    format!("{action} failed: {message} ({hint})")
+}
+
+fn github_error(action: &str, err: octocrab::Error, authenticated: bool) -> anyhow::Error {
+    match &err {
+        octocrab::Error::GitHub { source, .. } => anyhow!(describe_github_error(
+            action,
+            source.status_code.as_u16(),
+            &source.message,
+            authenticated
+        )),
+        _ => anyhow!("{action} failed: {err}"),
+    }
 }
 
 /// Get verified git binary path from trusted locations
-fn get_verified_git_path() -> Option<String> {
+pub(crate) fn get_verified_git_path() -> Option<String> {
     let git_path = Command::new("which")
         .arg("git")
         .output()
@@ -88,6 +370,7 @@ pub fn clone_repo(repo: &str, dest: &Path) -> Result<()> {
         );
     }
 
+    ensure_online(&format!("clone GitHub repository {repo}"))?;
     let url = format!("https://github.com/{}.git", repo);
 
     // Use verified git path if available, otherwise fall back to "git"
@@ -96,6 +379,7 @@ pub fn clone_repo(repo: &str, dest: &Path) -> Result<()> {
     let output = Command::new(&git_cmd)
         .args(["clone", "--depth", "1", &url])
         .arg(dest)
+        .envs(parsentry_core::http::git_env())
         .output()?;
 
     if !output.status.success() {
@@ -108,23 +392,15 @@ pub fn clone_repo(repo: &str, dest: &Path) -> Result<()> {
 
 impl GitHubSearchClient {
     /// Create a new GitHub search client
-    /// Uses git credential helper for authentication, falling back to GITHUB_TOKEN env var
+    /// Authenticates as a GitHub App installation when `GITHUB_APP_ID` is set,
+    /// otherwise via git credential helper, falling back to GITHUB_TOKEN env var
     pub fn new() -> Result<Self> {
-        let mut builder = Octocrab::builder();
-
-        // Try to get token from git credential helper first, then fall back to env var
-        if let Some(token) =
-            Self::get_token_from_credential_helper().or_else(|| env::var("GITHUB_TOKEN").ok())
-            && !token.is_empty()
-        {
-            builder = builder.personal_token(token);
-        }
-
-        let client = builder
-            .build()
-            .map_err(|e| anyhow!("Failed to create GitHub client: {}", e))?;
-
-        Ok(Self { client })
+        ensure_online("GitHub search")?;
+        let auth = GitHubAuth::from_env()?;
+        Ok(Self {
+            client: auth.client()?,
+            authenticated: auth.is_authenticated(),
+        })
     }
 
     /// Get GitHub token from git credential helper
@@ -161,6 +437,44 @@ impl GitHubSearchClient {
         None
     }
 
+    /// GET a GitHub API path, sleeping through rate limits that reset soon.
+    async fn get_json<R: DeserializeOwned>(&self, url: &str, action: &str) -> Result<R> {
+        let mut retries = 0;
+        loop {
+            let err = match self.client.get::<R, _, _>(url, None::<&()>).await {
+                Ok(response) => return Ok(response),
+                Err(err) => err,
+            };
+            if let octocrab::Error::GitHub { source, .. } = &err
+                && retries < MAX_RATE_LIMIT_RETRIES
+                && is_rate_limited(source.status_code.as_u16(), &source.message)
+                && let Some(wait) = self.rate_limit_wait(url, &source.message).await
+            {
+                warn!("GitHub rate limit hit; retrying in {}s", wait.as_secs());
+                tokio::time::sleep(wait).await;
+                retries += 1;
+                continue;
+            }
+            return Err(github_error(action, err, self.authenticated));
+        }
+    }
+
+    /// How long to wait out a rate limit, or `None` if it resets too late.
+    async fn rate_limit_wait(&self, url: &str, message: &str) -> Option<Duration> {
+        if message.to_lowercase().contains("secondary") {
+            return Some(SECONDARY_RATE_LIMIT_WAIT);
+        }
+        let limits = self.client.ratelimit().get().await.ok()?;
+        let rate = if url.starts_with("/search/") {
+            limits.resources.search
+        } else {
+            limits.resources.core
+        };
+        let now = chrono::Utc::now().timestamp().max(0) as u64;
+        let wait = rate_limit_reset_wait(rate.reset, now);
+        (wait <= MAX_RATE_LIMIT_WAIT).then_some(wait)
+    }
+
     /// Search for repositories using GitHub Search API
     ///
     /// # Arguments
@@ -180,18 +494,16 @@ impl GitHubSearchClient {
 
         while results.len() < max_results {
             let response: RepositorySearchResponse = self
-                .client
-                .get(
-                    format!(
+                .get_json(
+                    &format!(
                         "/search/repositories?q={}&per_page={}&page={}",
                         urlencoding::encode(query),
                         per_page,
                         page
                     ),
-                    None::<&()>,
+                    "GitHub API search",
                 )
-                .await
-                .map_err(|e| anyhow!("GitHub API search failed: {}", e))?;
+                .await?;
 
             if response.items.is_empty() {
                 break;
@@ -201,20 +513,66 @@ impl GitHubSearchClient {
                 if results.len() >= max_results {
                     break;
                 }
+                results.push(SearchResult::from(repo));
+            }
 
-                let owner = repo.owner.login.clone();
+            page += 1;
+        }
 
-                results.push(SearchResult {
-                    owner: owner.clone(),
-                    repo: repo.name.clone(),
-                    full_name: repo.full_name.clone(),
-                    clone_url: repo.clone_url.clone(),
-                    html_url: repo.html_url.clone(),
-                    stars: repo.stargazers_count,
-                    description: repo.description.clone(),
-                });
-            }
+        Ok(results)
+    }
 
+    /// List the repositories of an organization, the repositories with a
+    /// topic, or an organization's repositories with a topic, following
+    /// pagination up to `max_results`. Forks and archived repositories are
+    /// left out unless `filter` includes them.
+    pub async fn list_repositories(
+        &self,
+        org: Option<&str>,
+        topic: Option<&str>,
+        filter: RepositoryFilter,
+        max_results: usize,
+    ) -> Result<Vec<SearchResult>> {
+        if let Some(org) = org
+            && !is_valid_repo_slug(&format!("{org}/x"))
+        {
+            anyhow::bail!("Invalid organization name: {org}");
+        }
+        let query = sweep_query(org, topic, filter);
+        let Some(path) = (match (&query, org) {
+            (Some(query), _) => Some(format!(
+                "/search/repositories?q={}",
+                urlencoding::encode(query)
+            )),
+            (None, org) => org.map(|org| format!("/orgs/{org}/repos?type=all")),
+        }) else {
+            anyhow::bail!("an organization or a topic is required");
+        };
+
+        let mut results = Vec::new();
+        let mut page = 1u32;
+        let per_page = 100usize;
+        while results.len() < max_results {
+            let url = format!("{path}&per_page={per_page}&page={page}");
+            let items: Vec<RepositoryItem> = if query.is_some() {
+                self.get_json::<RepositorySearchResponse>(&url, "GitHub API search")
+                    .await?
+                    .items
+            } else {
+                self.get_json(&url, "GitHub API listing").await?
+            };
+            let last_page = items.len() < per_page;
+            let remaining = max_results - results.len();
+            results.extend(
+                items
+                    .into_iter()
+                    .filter(|r| filter.allows(r))
+                    .take(remaining)
+                    .map(SearchResult::from),
+            );
+            if last_page {
+                break;
+            }
             page += 1;
         }
 
@@ -239,19 +597,17 @@ impl GitHubSearchClient {
         let per_page = 100u8;
 
         while results.len() < max_results {
-            let response = self
-                .client
-                .get::<CodeSearchResponse, _, _>(
-                    format!(
+            let response: CodeSearchResponse = self
+                .get_json(
+                    &format!(
                         "/search/code?q={}&per_page={}&page={}",
                         urlencoding::encode(query),
                         per_page,
                         page
                     ),
-                    None::<&()>,
+                    "GitHub code search",
                 )
-                .await
-                .map_err(|e| anyhow!("GitHub code search failed: {}", e))?;
+                .await?;
 
             if response.items.is_empty() {
                 break;
@@ -329,6 +685,24 @@ struct RepositoryItem {
     clone_url: String,
     description: Option<String>,
     stargazers_count: u32,
+    #[serde(default)]
+    fork: bool,
+    #[serde(default)]
+    archived: bool,
+}
+
+impl From<RepositoryItem> for SearchResult {
+    fn from(repo: RepositoryItem) -> Self {
+        Self {
+            owner: repo.owner.login,
+            repo: repo.name,
+            full_name: repo.full_name,
+            clone_url: repo.clone_url,
+            html_url: repo.html_url,
+            stars: repo.stargazers_count,
+            description: repo.description,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -340,6 +714,21 @@ struct RepoOwner {
 
 const ISSUE_LABEL: &str = "parsentry";
 
+/// Build an Octocrab client authenticated as a GitHub App installation, or
+/// via git credential helper or `GITHUB_TOKEN`.
+fn authenticated_client() -> Result<Octocrab> {
+    ensure_online("GitHub API")?;
+    GitHubAuth::from_env()?.client()
+}
+
+/// Split an `owner/repo` slug, validating both parts are non-empty.
+fn split_repo_slug<'a>(repo: &'a str, flag: &str) -> Result<(&'a str, &'a str)> {
+    match repo.split_once('/') {
+        Some((owner, name)) if !owner.is_empty() && !name.is_empty() => Ok((owner, name)),
+        _ => anyhow::bail!("{flag} must be in 'owner/repo' format, got: {repo}"),
+    }
+}
+
 /// Create GitHub issues from per-surface SARIF reports.
 ///
 /// For each surface a parent issue is created with title `[Parsentry] {surface_name}`.
@@ -362,22 +751,9 @@ pub async fn run_gh_issue_command(
         load_surface_reports, parse_fingerprint_from_body, parse_surface_from_body,
     };
 
-    let parts: Vec<&str> = repo.splitn(2, '/').collect();
-    if parts.len() != 2 || parts[0].is_empty() || parts[1].is_empty() {
-        anyhow::bail!("--gh-issue must be in 'owner/repo' format, got: {}", repo);
-    }
-    let (owner, repo_name) = (parts[0], parts[1]);
+    let (owner, repo_name) = split_repo_slug(repo, "--gh-issue")?;
 
-    let mut builder = Octocrab::builder();
-    if let Some(token) = GitHubSearchClient::get_token_from_credential_helper()
-        .or_else(|| env::var("GITHUB_TOKEN").ok())
-        && !token.is_empty()
-    {
-        builder = builder.personal_token(token);
-    }
-    let client = builder
-        .build()
-        .map_err(|e| anyhow!("Failed to create GitHub client: {}", e))?;
+    let client = authenticated_client()?;
 
     let surfaces = load_surface_reports(reports_dir, min_level)?;
     if surfaces.is_empty() {
@@ -602,10 +978,311 @@ pub async fn run_gh_issue_command(
     Ok(())
 }
 
+// ── Pull request review comments ──────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+struct PullRequestHead {
+    sha: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PullRequestInfo {
+    head: PullRequestHead,
+}
+
+#[derive(Debug, Deserialize)]
+struct PullRequestFile {
+    filename: String,
+    #[serde(default)]
+    patch: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReviewComment {
+    id: u64,
+    #[serde(default)]
+    body: String,
+}
+
+/// Lines on the new (RIGHT) side of a unified diff that GitHub accepts review
+/// comments on: added lines and context lines inside a hunk.
+fn commentable_lines(patch: &str) -> std::collections::HashSet<u32> {
+    let mut lines = std::collections::HashSet::new();
+    let mut new_line: Option<u32> = None;
+
+    for line in patch.lines() {
+        if let Some(header) = line.strip_prefix("@@ ") {
+            // @@ -a,b +c,d @@
+            new_line = header
+                .split_whitespace()
+                .find_map(|part| part.strip_prefix('+'))
+                .and_then(|range| range.split(',').next())
+                .and_then(|start| start.parse().ok());
+            continue;
+        }
+        let Some(current) = new_line else { continue };
+        if line.starts_with('-') {
+            continue;
+        }
+        if line.starts_with('\\') {
+            continue;
+        }
+        lines.insert(current);
+        new_line = Some(current + 1);
+    }
+
+    lines
+}
+
+/// Find the PR file a SARIF artifact URI refers to.
+///
+/// Agents may emit absolute paths or paths relative to the repository root,
+/// so a suffix match on a path boundary is accepted.
+fn match_pr_file<'a>(uri: &str, files: &'a [PullRequestFile]) -> Option<&'a PullRequestFile> {
+    let uri = uri.trim_start_matches("file://");
+    files.iter().find(|f| {
+        uri == f.filename
+            || uri
+                .strip_suffix(f.filename.as_str())
+                .is_some_and(|prefix| prefix.ends_with('/'))
+    })
+}
+
+/// Markdown body for a PR review comment.
+fn build_review_comment_body(
+    r: &parsentry_reports::SarifResult,
+    remediation: Option<&str>,
+    fingerprint: Option<&str>,
+) -> String {
+    use parsentry_reports::report_common::FINGERPRINT_MARKER;
+
+    let mut body = format!("**[{}] {}**", r.level.to_uppercase(), r.rule_id);
+    if let Some(c) = r.properties.as_ref().and_then(|p| p.confidence) {
+        body.push_str(&format!(" (confidence {:.0}%)", c * 100.0));
+    }
+    body.push_str("\n\n");
+    body.push_str(r.message.markdown.as_deref().unwrap_or(&r.message.text));
+    body.push_str("\n\n");
+
+    if let Some(help) = remediation {
+        body.push_str("**Remediation**\n\n");
+        body.push_str(help);
+        body.push_str("\n\n");
+    }
+
+    body.push_str("---\n*Generated by [parsentry](https://github.com/HikaruEgashira/parsentry)*\n");
+    if let Some(fp) = fingerprint {
+        let safe_fp = fp.replace("--", "\\-\\-");
+        body.push_str(&format!("\n{FINGERPRINT_MARKER} {safe_fp} -->"));
+    }
+    body
+}
+
+/// Post findings as review comments on a pull request.
+///
+/// Findings are mapped onto the PR diff by file and start line; findings
+/// outside the diff are skipped. Comments carry the finding fingerprint in a
+/// hidden marker so that re-runs update the existing comment instead of
+/// posting a duplicate.
+pub async fn run_pr_comment_command(
+    reports_dir: &Path,
+    repo: &str,
+    pr: u64,
+    dry_run: bool,
+    min_level: &str,
+) -> Result<()> {
+    use parsentry_reports::merge_sarif_dir;
+    use parsentry_reports::report_common::{
+        extract_fingerprint, level_passes, parse_fingerprint_from_body,
+    };
+
+    let (owner, repo_name) = split_repo_slug(repo, "--repo")?;
+    let client = authenticated_client()?;
+
+    let merged = merge_sarif_dir(reports_dir, None)?;
+    let pr_route = format!("/repos/{owner}/{repo_name}/pulls/{pr}");
+
+    let info: PullRequestInfo = client
+        .get(&pr_route, None::<&()>)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch PR #{pr}: {e}"))?;
+
+    let mut files: Vec<PullRequestFile> = Vec::new();
+    let mut page = 1u32;
+    loop {
+        let batch: Vec<PullRequestFile> = client
+            .get(
+                format!("{pr_route}/files?per_page=100&page={page}"),
+                None::<&()>,
+            )
+            .await
+            .map_err(|e| anyhow!("Failed to list files of PR #{pr}: {e}"))?;
+        let done = batch.len() < 100;
+        files.extend(batch);
+        if done {
+            break;
+        }
+        page += 1;
+    }
+
+    // Existing parsentry comments: fingerprint → comment id
+    let mut existing = std::collections::HashMap::<String, u64>::new();
+    let mut page = 1u32;
+    loop {
+        let batch: Vec<ReviewComment> = client
+            .get(
+                format!("{pr_route}/comments?per_page=100&page={page}"),
+                None::<&()>,
+            )
+            .await
+            .map_err(|e| anyhow!("Failed to list review comments of PR #{pr}: {e}"))?;
+        let done = batch.len() < 100;
+        for comment in &batch {
+            if let Some(fp) = parse_fingerprint_from_body(&comment.body) {
+                existing.insert(fp, comment.id);
+            }
+        }
+        if done {
+            break;
+        }
+        page += 1;
+    }
+
+    let (mut created, mut updated, mut outside) = (0usize, 0usize, 0usize);
+
+    for run in &merged.runs {
+        let rules = run.tool.driver.rules.as_deref().unwrap_or_default();
+        for result in run
+            .results
+            .iter()
+            .filter(|r| level_passes(&r.level, min_level))
+        {
+            if result.baseline_state.as_deref() == Some("absent") {
+                continue;
+            }
+
+            let Some(location) = result.locations.first() else {
+                outside += 1;
+                continue;
+            };
+            let uri = &location.physical_location.artifact_location.uri;
+            let line = location
+                .physical_location
+                .region
+                .as_ref()
+                .map(|r| r.start_line)
+                .filter(|l| *l > 0)
+                .map(|l| l as u32);
+
+            let target = match_pr_file(uri, &files).and_then(|f| {
+                let commentable = commentable_lines(f.patch.as_deref().unwrap_or_default());
+                line.filter(|l| commentable.contains(l))
+                    .map(|l| (f.filename.as_str(), l))
+            });
+            let Some((path, line)) = target else {
+                outside += 1;
+                continue;
+            };
+
+            let remediation = rules
+                .iter()
+                .find(|r| r.id == result.rule_id)
+                .and_then(|r| r.help.as_ref())
+                .map(|h| h.markdown.as_deref().unwrap_or(&h.text));
+            let fp = extract_fingerprint(result);
+            let body = build_review_comment_body(result, remediation, fp.as_deref());
+
+            if let Some(&id) = fp.as_ref().and_then(|f| existing.get(f)) {
+                if dry_run {
+                    eprintln!("[dry-run] Would update comment {id} on {path}:{line}");
+                } else {
+                    let _: serde_json::Value = client
+                        .patch(
+                            format!("/repos/{owner}/{repo_name}/pulls/comments/{id}"),
+                            Some(&serde_json::json!({ "body": body })),
+                        )
+                        .await
+                        .map_err(|e| anyhow!("Failed to update comment {id}: {e}"))?;
+                    eprintln!("Updated comment {id} on {path}:{line}");
+                }
+                updated += 1;
+            } else if dry_run {
+                eprintln!(
+                    "[dry-run] Would comment on {path}:{line} ({})",
+                    result.rule_id
+                );
+                created += 1;
+            } else {
+                let comment: ReviewComment = client
+                    .post(
+                        format!("{pr_route}/comments"),
+                        Some(&serde_json::json!({
+                            "body": body,
+                            "commit_id": info.head.sha,
+                            "path": path,
+                            "line": line,
+                            "side": "RIGHT",
+                        })),
+                    )
+                    .await
+                    .map_err(|e| anyhow!("Failed to comment on {path}:{line}: {e}"))?;
+                eprintln!("Commented on {path}:{line} ({})", result.rule_id);
+                // Later findings with the same fingerprint update this comment
+                if let Some(f) = fp {
+                    existing.insert(f, comment.id);
+                }
+                created += 1;
+            }
+        }
+    }
+
+    eprintln!(
+        "Done. created={created}, updated={updated}, outside_diff={outside}{}",
+        if dry_run { " (dry-run)" } else { "" }
+    );
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn transport_sends_octocrab_requests_through_reqwest() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tower_service::Service;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 4096];
+            let n = socket.read(&mut request).await.unwrap();
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\n{}")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&request[..n]).to_string()
+        });
+
+        let client =
+            octocrab_client(Some(HeaderValue::from_static("Bearer t")), AuthState::None).unwrap();
+        let response = client
+            ._get(format!("http://{addr}/repos/o/r"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let received = server.await.unwrap();
+        assert!(received.starts_with("GET /repos/o/r HTTP/1.1"));
+        assert!(received.contains("user-agent: parsentry/"));
+
+        let mut transport = ReqwestTransport(reqwest::Client::new());
+        let request = http::Request::get("http://127.0.0.1:1/")
+            .body(String::new())
+            .unwrap();
+        assert!(transport.call(request).await.unwrap_err().is_connect());
+    }
+
     #[tokio::test]
     #[ignore] // Requires GITHUB_TOKEN and network access
     async fn test_search_repositories() {
@@ -639,4 +1316,122 @@ mod tests {
             assert!(results.len() <= 5);
         }
     }
+
+    #[test]
+    fn test_commentable_lines_tracks_new_side() {
+        let patch = "@@ -1,3 +1,4 @@\n import os\n-old()\n+new()\n+more()\n tail\n@@ -20,2 +21,2 @@ def f():\n ctx\n+added\n\\ No newline at end of file";
+        let lines = commentable_lines(patch);
+        for l in [1, 2, 3, 4, 21, 22] {
+            assert!(lines.contains(&l), "line {l} should be commentable");
+        }
+        assert!(!lines.contains(&5));
+        assert!(!lines.contains(&20));
+        assert_eq!(lines.len(), 6);
+    }
+
+    #[test]
+    fn test_match_pr_file_on_path_boundary() {
+        let files = vec![
+            PullRequestFile {
+                filename: "src/app.py".to_string(),
+                patch: None,
+            },
+            PullRequestFile {
+                filename: "app.py".to_string(),
+                patch: None,
+            },
+        ];
+        assert_eq!(
+            match_pr_file("/tmp/repo/src/app.py", &files).map(|f| f.filename.as_str()),
+            Some("src/app.py")
+        );
+        assert_eq!(
+            match_pr_file("app.py", &files).map(|f| f.filename.as_str()),
+            Some("app.py")
+        );
+        assert!(match_pr_file("src/myapp.pyc", &files).is_none());
+    }
+
+    #[test]
+    fn test_is_rate_limited() {
+        assert!(is_rate_limited(429, "Too Many Requests"));
+        assert!(is_rate_limited(403, "API rate limit exceeded for 1.2.3.4."));
+        assert!(is_rate_limited(
+            403,
+            "You have exceeded a secondary rate limit. Please wait a few minutes."
+        ));
+        assert!(!is_rate_limited(
+            403,
+            "Resource not accessible by integration"
+        ));
+        assert!(!is_rate_limited(404, "Not Found"));
+    }
+
+    #[test]
+    fn test_rate_limit_reset_wait() {
+        assert_eq!(rate_limit_reset_wait(1_000, 970), Duration::from_secs(31));
+        assert_eq!(rate_limit_reset_wait(1_000, 1_200), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_describe_github_error() {
+        let msg = describe_github_error("GitHub API listing", 403, "Forbidden", true);
+        assert!(msg.starts_with("GitHub API listing failed: Forbidden"));
+        assert!(msg.contains("`repo`"));
+        let msg = describe_github_error("GitHub API search", 403, "API rate limit exceeded", false);
+        assert!(msg.contains("set GITHUB_TOKEN"));
+        let msg = describe_github_error("GitHub API listing", 401, "Bad credentials", true);
+        assert!(msg.contains("rejected"));
+        assert_eq!(
+            describe_github_error("GitHub code search", 422, "Validation Failed", true),
+            "GitHub code search failed: Validation Failed"
+        );
+    }
+
+    #[test]
+    fn test_sweep_query() {
+        let filter = RepositoryFilter::default();
+        assert_eq!(sweep_query(Some("acme"), None, filter), None);
+        assert_eq!(
+            sweep_query(Some("acme"), Some("payments"), filter).as_deref(),
+            Some("topic:payments org:acme archived:false")
+        );
+        let all = RepositoryFilter {
+            include_forks: true,
+            include_archived: true,
+        };
+        assert_eq!(
+            sweep_query(None, Some("payments"), all).as_deref(),
+            Some("topic:payments fork:true")
+        );
+    }
+
+    #[test]
+    fn test_split_repo_slug() {
+        assert_eq!(
+            split_repo_slug("owner/repo", "--repo").unwrap(),
+            ("owner", "repo")
+        );
+        assert!(split_repo_slug("owner", "--repo").is_err());
+        assert!(split_repo_slug("/repo", "--repo").is_err());
+    }
+
+    #[test]
+    fn test_review_comment_body_embeds_fingerprint_and_remediation() {
+        use parsentry_reports::report_common::parse_fingerprint_from_body;
+
+        let result: parsentry_reports::SarifResult = serde_json::from_value(serde_json::json!({
+            "ruleId": "SQLI",
+            "level": "error",
+            "message": {"text": "query built from request args"},
+            "properties": {"confidence": 0.9}
+        }))
+        .unwrap();
+
+        let body = build_review_comment_body(&result, Some("Use bound parameters."), Some("abc"));
+        assert!(body.starts_with("**[ERROR] SQLI** (confidence 90%)"));
+        assert!(body.contains("query built from request args"));
+        assert!(body.contains("Use bound parameters."));
+        assert_eq!(parse_fingerprint_from_body(&body).as_deref(), Some("abc"));
+    }
 }
@@ -0,0 +1,77 @@
+//! Languages workers write their findings in (`scan --language`).
+//!
+//! Rule ids, SARIF keys, code and paths stay as they are; only the prose of
+//! a finding (message, description, remediation) is written in the chosen
+//! language. English needs no instruction and is the default.
+//!
+//! The language is the workers' alone: reports keep their English headers,
+//! tables and labels, so findings written in Japanese can be shared in a
+//! report framed for an international team.
+
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, ValueEnum)]
+pub enum ResponseLanguage {
+    #[default]
+    En,
+    Ja,
+    Zh,
+    Ko,
+    Es,
+    De,
+    Fr,
+    Pt,
+}
+
+impl ResponseLanguage {
+    /// ISO 639-1 code, as accepted by `--language`.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::Ja => "ja",
+            Self::Zh => "zh",
+            Self::Ko => "ko",
+            Self::Es => "es",
+            Self::De => "de",
+            Self::Fr => "fr",
+            Self::Pt => "pt",
+        }
+    }
+
+    /// English name of the language, as used in prompts.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::En => "English",
+            Self::Ja => "Japanese",
+            Self::Zh => "Simplified Chinese",
+            Self::Ko => "Korean",
+            Self::Es => "Spanish",
+            Self::De => "German",
+            Self::Fr => "French",
+            Self::Pt => "Portuguese",
+        }
+    }
+
+    /// Language selected by `PARSENTRY_LANGUAGE`, if set to a known code.
+    /// Regional variants such as `pt-BR` or `zh_CN` select their language.
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var("PARSENTRY_LANGUAGE").ok()?;
+        let code = value.trim().split(['-', '_']).next().unwrap_or_default();
+        Self::from_str(code, true).ok()
+    }
+
+    /// Prompt instruction to write findings in this language; `None` for
+    /// English.
+    pub fn instruction(self) -> Option<String> {
+        if self == Self::En {
+            return None;
+        }
+        Some(format!(
+            "\nResponse Language\n\n\
+             Write `message.text`, `message.markdown` and all other prose in the SARIF in {} \
+             ({}). Keep `ruleId`, JSON keys, code, identifiers and file paths unchanged.\n",
+            self.name(),
+            self.code()
+        ))
+    }
+}
@@ -0,0 +1,258 @@
+//! Framework knowledge packs for surface prompts.
+//!
+//! Generic prompts miss sinks that are only dangerous by a framework's
+//! idiom: a Django `extra()` call, a React `dangerouslySetInnerHTML` prop,
+//! a Spring SpEL expression. Each pack names the dependencies that reveal
+//! its framework in the repository's manifests and the languages it applies
+//! to; a surface prompt gets the guidance of the detected frameworks in its
+//! own languages.
+//!
+//! Pipeline and chart configuration gets guidance by [`ConfigKind`]
+//! instead, since its risks lie in what untrusted triggers can run or
+//! deploy rather than in code.
+
+use regex::Regex;
+
+use parsentry_core::{ConfigKind, Language, ManifestInfo};
+
+/// Guidance on one framework's security pitfalls.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnowledgePack {
+    pub framework: &'static str,
+    /// Dependency names, any of which in a manifest reveals the framework.
+    pub dependencies: &'static [&'static str],
+    pub languages: &'static [Language],
+    /// Markdown bullets.
+    pub guidance: &'static str,
+}
+
+pub const PACKS: &[KnowledgePack] = &[
+    KnowledgePack {
+        framework: "Django",
+        dependencies: &["django"],
+        languages: &[Language::Python],
+        guidance: "\
+- `QuerySet.raw()`, `.extra()`, `RawSQL` and `cursor.execute()` with interpolated strings \
+bypass ORM parameterization; `%s` placeholders with a params list are safe.
+- `mark_safe`, `|safe`, `{% autoescape off %}` and `format_html` with pre-built strings \
+disable template escaping.
+- `@csrf_exempt` views and `ALLOWED_HOSTS = ['*']` or `DEBUG = True` in production settings.
+- `Model.objects.filter(**request.GET.dict())` lets callers filter on any field, including \
+relations (`password__startswith`).
+- Views without `@login_required` / permission checks that look up objects by id from the \
+URL (IDOR).",
+    },
+    KnowledgePack {
+        framework: "Flask",
+        dependencies: &["flask"],
+        languages: &[Language::Python],
+        guidance: "\
+- `render_template_string` with user input is server-side template injection; `Markup()` \
+and `|safe` disable escaping.
+- `send_file` / `send_from_directory` with request-controlled paths; `safe_join` is needed \
+for the former.
+- A hard-coded or default `SECRET_KEY` lets anyone forge session cookies.
+- `app.run(debug=True)` exposes the Werkzeug debugger console.",
+    },
+    KnowledgePack {
+        framework: "React",
+        dependencies: &["react"],
+        languages: &[Language::JavaScript, Language::TypeScript],
+        guidance: "\
+- `dangerouslySetInnerHTML={{ __html: ... }}` with data not passed through a sanitizer \
+(DOMPurify) is XSS.
+- `href` / `src` built from user input accept `javascript:` URLs; React only warns.
+- Direct DOM writes through refs (`ref.current.innerHTML`) bypass React's escaping.
+- Server-rendered state embedded with `JSON.stringify` into a `<script>` tag needs `<` \
+escaped.",
+    },
+    KnowledgePack {
+        framework: "Express",
+        dependencies: &["express"],
+        languages: &[Language::JavaScript, Language::TypeScript],
+        guidance: "\
+- `res.sendFile` / `express.static` with request-controlled paths and no `root` option.
+- `res.redirect(req.query.next)` is an open redirect.
+- Object merges of `req.body` (`Object.assign`, lodash `merge`) into shared objects allow \
+prototype pollution.
+- Missing `helmet`, permissive `cors({ origin: true, credentials: true })`, and session \
+cookies without `httpOnly`/`secure`.",
+    },
+    KnowledgePack {
+        framework: "Spring",
+        dependencies: &[
+            "springframework",
+            "spring-boot-starter-web",
+            "spring-webmvc",
+        ],
+        languages: &[Language::Java],
+        guidance: "\
+- `SpelExpressionParser.parseExpression(userInput).getValue()` with a \
+`StandardEvaluationContext` is remote code execution; `SimpleEvaluationContext` limits it.
+- `@Query` with string concatenation, `JdbcTemplate` queries built with `+`, and \
+`EntityManager.createQuery` with interpolated JPQL.
+- Data binding of request parameters onto entities (mass assignment) without \
+`@InitBinder` allow-lists or DTOs.
+- `permitAll()` on sensitive paths, `csrf().disable()`, and actuator endpoints exposed \
+without authentication.",
+    },
+    KnowledgePack {
+        framework: "Rails",
+        dependencies: &["rails"],
+        languages: &[Language::Ruby],
+        guidance: "\
+- `params.permit!` or passing `params` directly to `new` / `update` bypasses strong \
+parameters (mass assignment of `admin`, `role`, `user_id`).
+- `where(\"name = '#{params[:name]}'\")`, `order(params[:sort])` and `find_by_sql` with \
+interpolation are SQL injection.
+- `html_safe`, `raw` and `<%== %>` disable ERB escaping.
+- `redirect_to params[:url]`, `send_file params[:path]` and `constantize` / `send` on user \
+input.",
+    },
+    KnowledgePack {
+        framework: "GraphQL",
+        dependencies: &[
+            "graphql",
+            "@apollo/server",
+            "apollo-server",
+            "graphql-yoga",
+            "type-graphql",
+            "@nestjs/graphql",
+            "graphene",
+            "strawberry-graphql",
+            "ariadne",
+            "spring-boot-starter-graphql",
+            "graphql-java",
+            "graphql-ruby",
+            "async-graphql",
+            "juniper",
+            "gqlgen",
+        ],
+        languages: &[
+            Language::JavaScript,
+            Language::TypeScript,
+            Language::Python,
+            Language::Java,
+            Language::Ruby,
+            Language::Rust,
+            Language::Go,
+            Language::GraphQL,
+        ],
+        guidance: "\
+- Authorization checked only on root fields leaks data through nested resolvers \
+(`User.email`, `Order.owner`); every resolver returning another principal's data needs its \
+own check.
+- Resolver arguments are untrusted input: string-built SQL, Mongo `$where`/operator objects \
+from `JSON` scalars, and input objects passed whole to ORM `update()` (mass assignment).
+- List fields without a `first`/`limit` cap, and servers without query depth, complexity or \
+alias limits, allow denial of service and batched brute force of `login`-style mutations.
+- Introspection and field suggestions enabled in production, and error formatting that \
+returns stack traces.",
+    },
+];
+
+/// Markdown bullets on the pitfalls of `kind` configuration.
+pub fn config_guidance(kind: ConfigKind) -> &'static str {
+    match kind {
+        ConfigKind::GitHubActions => {
+            "\
+- `${{ github.event.issue.title }}`, `pull_request.title/body`, `head_ref`, comment and \
+commit message fields interpolated into `run:` are shell injection; passing them through \
+`env:` and quoting `\"$VAR\"` is safe.
+- `pull_request_target` and `workflow_run` run with secrets and a write token; checking out \
+`github.event.pull_request.head.sha` and then building, testing or running scripts executes \
+the contributor's code with them.
+- `GITHUB_ENV` / `GITHUB_OUTPUT` writes of untrusted values, and artifacts downloaded from \
+untrusted runs, carry injection into later steps.
+- Third-party actions not pinned to a commit SHA, `permissions: write-all` or missing \
+`permissions`, and self-hosted runners on public repositories."
+        }
+        ConfigKind::GitLabCi => {
+            "\
+- `$CI_COMMIT_MESSAGE`, `$CI_COMMIT_BRANCH`, `$CI_COMMIT_REF_NAME` and \
+`$CI_MERGE_REQUEST_TITLE` are contributor-controlled; unquoted use in `script:` is shell \
+injection.
+- Merge request pipelines from forks that reach protected variables, deploy jobs not limited \
+to protected branches, and `CI_JOB_TOKEN` exposed to untrusted jobs.
+- `include: remote:` and images without a pinned digest let a third party change the \
+pipeline."
+        }
+        ConfigKind::Helm => {
+            "\
+- Defaults in `values.yaml` that ship `privileged: true`, `runAsUser: 0`, `hostNetwork`, \
+`hostPath` mounts or added capabilities apply to every install that does not override them.
+- `tpl .Values...` renders user values as templates; unquoted `{{ .Values.x }}` in \
+`command:`/`args:` or YAML structure lets values inject keys.
+- Credentials as chart defaults, `Secret`s rendered from plain values, and RBAC rules with \
+`*` verbs or resources."
+        }
+    }
+}
+
+/// Packs whose dependencies appear in `manifests`, in [`PACKS`] order.
+pub fn detect_frameworks(manifests: &[ManifestInfo]) -> Vec<&'static KnowledgePack> {
+    PACKS
+        .iter()
+        .filter(|pack| {
+            pack.dependencies.iter().any(|dependency| {
+                // Whole names only: `react` matches neither `preact` nor
+                // `react-dom`.
+                let name = Regex::new(&format!(
+                    r"(?i)(^|[^A-Za-z0-9_-]){}($|[^A-Za-z0-9_-])",
+                    regex::escape(dependency)
+                ))
+                .expect("valid regex");
+                manifests.iter().any(|m| name.is_match(&m.content))
+            })
+        })
+        .collect()
+}
+
+/// The packs of `frameworks` that apply to any of `languages`.
+pub fn packs_for<'a>(
+    frameworks: &[&'a KnowledgePack],
+    languages: &[Language],
+) -> Vec<&'a KnowledgePack> {
+    frameworks
+        .iter()
+        .filter(|pack| pack.languages.iter().any(|l| languages.contains(l)))
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(path: &str, content: &str) -> ManifestInfo {
+        ManifestInfo {
+            path: path.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn detects_frameworks_from_manifests() {
+        let manifests = [
+            manifest("requirements.txt", "Django==5.0\npsycopg2==2.9\n"),
+            manifest(
+                "package.json",
+                r#"{"dependencies": {"react-dom": "^18", "preact-render": "1"}}"#,
+            ),
+        ];
+        let names: Vec<&str> = detect_frameworks(&manifests)
+            .iter()
+            .map(|p| p.framework)
+            .collect();
+        assert_eq!(names, vec!["Django"]);
+
+        let manifests = [manifest(
+            "package.json",
+            r#"{"dependencies": {"react": "^18", "express": "^4"}}"#,
+        )];
+        let frameworks = detect_frameworks(&manifests);
+        assert_eq!(frameworks.len(), 2);
+        assert!(packs_for(&frameworks, &[Language::Python]).is_empty());
+        assert_eq!(packs_for(&frameworks, &[Language::TypeScript]).len(), 2);
+    }
+}
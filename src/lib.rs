@@ -1,10 +1,24 @@
 //! Parsentry - PAR-based security scanner.
 
+pub mod archive;
 pub mod cli;
+pub mod code_host;
+pub mod commit_range;
+pub mod config;
+pub mod examples;
 pub mod github;
+pub mod i18n;
+pub mod knowledge;
+pub mod lsp;
+pub mod mcp;
+pub mod pattern_packs;
+pub mod profile;
 pub mod prompt;
 pub mod repo;
+pub mod residency;
 pub mod response;
+pub mod sandbox;
+pub mod telemetry;
 pub mod url_collector;
 
 // Re-export core types for convenience
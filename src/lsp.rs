@@ -0,0 +1,669 @@
+//! Language Server Protocol mode (`parsentry lsp`).
+//!
+//! Publishes the findings of the workspace's latest scan as diagnostics,
+//! together with the PAR pattern matches of each open file, which are
+//! re-checked on save. The merged report stays in memory and is only
+//! re-merged when a worker result or the triage file changes. Hover shows a
+//! finding's analysis and remediation; code actions apply the worker's
+//! suggested patch or triage the finding.
+//!
+//! Speaks JSON-RPC 2.0 over stdio with `Content-Length` framing.
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::cli::commands::generate::workspace_reports_dir;
+use parsentry_core::Language;
+use parsentry_parser::SecurityRiskPatterns;
+use parsentry_reports::merge_sarif_dir;
+use parsentry_reports::sarif::{SarifReport, SarifResult};
+use parsentry_reports::triage::{Decision, TRIAGE_FILE, Triage};
+
+/// Command behind the triage code actions: `[fingerprint, decision]`.
+const TRIAGE_COMMAND: &str = "parsentry.triage";
+
+/// JSON-RPC error code for unknown methods.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Serve LSP requests on stdin/stdout until the client sends `exit`.
+pub fn serve() -> Result<()> {
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock();
+    let mut output = std::io::stdout().lock();
+    let mut server = Server::new(std::env::current_dir()?);
+    while let Some(message) = read_message(&mut input)? {
+        for reply in server.handle(&message) {
+            write_message(&mut output, &reply)?;
+        }
+        if server.exited {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Read one `Content-Length` framed message; `None` at end of input.
+fn read_message(input: &mut impl BufRead) -> Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = Some(value.trim().parse::<usize>()?);
+        }
+    }
+    let length = length.context("LSP message without Content-Length")?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+fn write_message(output: &mut impl Write, message: &Value) -> Result<()> {
+    let body = serde_json::to_string(message)?;
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()?;
+    Ok(())
+}
+
+struct Server {
+    root: PathBuf,
+    /// Merged findings of the workspace and the state they were merged at.
+    report: Option<SarifReport>,
+    signature: Option<SystemTime>,
+    /// Text of open documents by URI.
+    documents: HashMap<String, String>,
+    exited: bool,
+}
+
+impl Server {
+    fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            report: None,
+            signature: None,
+            documents: HashMap::new(),
+            exited: false,
+        }
+    }
+
+    fn reports_dir(&self) -> PathBuf {
+        workspace_reports_dir(&self.root)
+    }
+
+    /// Messages to send in reply to `message`.
+    fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        let id = message.get("id").cloned();
+        let respond = |result: Value| json!({"jsonrpc": "2.0", "id": id, "result": result});
+
+        match method {
+            "initialize" => {
+                if let Some(root) = params["rootUri"]
+                    .as_str()
+                    .and_then(uri_to_path)
+                    .or_else(|| params["rootPath"].as_str().map(PathBuf::from))
+                {
+                    self.root = root;
+                }
+                vec![respond(json!({
+                    "capabilities": {
+                        "textDocumentSync": {"openClose": true, "change": 1, "save": {}},
+                        "hoverProvider": true,
+                        "codeActionProvider": {"codeActionKinds": ["quickfix"]},
+                        "executeCommandProvider": {"commands": [TRIAGE_COMMAND]},
+                    },
+                    "serverInfo": {"name": "parsentry", "version": env!("CARGO_PKG_VERSION")},
+                }))]
+            }
+            "initialized" => {
+                self.reload();
+                // Findings are shown in the problems view before files are opened.
+                let uris: Vec<String> = self
+                    .results()
+                    .filter_map(|r| r.locations.first())
+                    .map(|l| {
+                        path_to_uri(&self.root.join(&l.physical_location.artifact_location.uri))
+                    })
+                    .collect::<std::collections::BTreeSet<_>>()
+                    .into_iter()
+                    .collect();
+                uris.iter().map(|uri| self.publish(uri)).collect()
+            }
+            "textDocument/didOpen" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents.insert(uri.to_string(), text.to_string());
+                self.reload();
+                vec![self.publish(uri)]
+            }
+            "textDocument/didChange" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                if let Some(text) = params["contentChanges"]
+                    .as_array()
+                    .and_then(|c| c.last())
+                    .and_then(|c| c["text"].as_str())
+                {
+                    self.documents.insert(uri.to_string(), text.to_string());
+                }
+                Vec::new()
+            }
+            "textDocument/didSave" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                if let Some(text) = uri_to_path(uri).and_then(|p| std::fs::read_to_string(p).ok()) {
+                    self.documents.insert(uri.to_string(), text);
+                }
+                self.reload();
+                vec![self.publish(uri)]
+            }
+            "textDocument/didClose" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                self.documents.remove(uri);
+                Vec::new()
+            }
+            "textDocument/hover" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let line = params["position"]["line"].as_u64().unwrap_or_default() as usize;
+                let contents: Vec<String> = self
+                    .findings_in(uri, line, line)
+                    .into_iter()
+                    .map(|r| self.hover_markdown(r))
+                    .collect();
+                let result = if contents.is_empty() {
+                    Value::Null
+                } else {
+                    json!({"contents": {"kind": "markdown", "value": contents.join("\n\n---\n\n")}})
+                };
+                vec![respond(result)]
+            }
+            "textDocument/codeAction" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+                let start = params["range"]["start"]["line"]
+                    .as_u64()
+                    .unwrap_or_default() as usize;
+                let end = params["range"]["end"]["line"].as_u64().unwrap_or_default() as usize;
+                let actions: Vec<Value> = self
+                    .findings_in(uri, start, end)
+                    .into_iter()
+                    .flat_map(|r| self.code_actions(uri, r))
+                    .collect();
+                vec![respond(json!(actions))]
+            }
+            "workspace/executeCommand" if params["command"] == TRIAGE_COMMAND => {
+                let mut replies = vec![respond(Value::Null)];
+                if let Some(uri) = self.triage(&params["arguments"]) {
+                    replies.push(self.publish(&uri));
+                }
+                replies
+            }
+            "shutdown" => vec![respond(Value::Null)],
+            "exit" => {
+                self.exited = true;
+                Vec::new()
+            }
+            _ if id.is_some() => vec![json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {"code": METHOD_NOT_FOUND, "message": format!("unsupported method: {method}")},
+            })],
+            // Other notifications need no reply.
+            _ => Vec::new(),
+        }
+    }
+
+    /// Re-merge the workspace's results when a result or triage changed.
+    fn reload(&mut self) {
+        let reports_dir = self.reports_dir();
+        let signature = report_signature(&reports_dir);
+        if signature.is_some() && signature == self.signature {
+            return;
+        }
+        self.signature = signature;
+        self.report = merge_sarif_dir(&reports_dir, None)
+            .inspect_err(|e| tracing::debug!("no findings to publish: {e:#}"))
+            .ok();
+    }
+
+    /// Unsuppressed findings of the workspace.
+    fn results(&self) -> impl Iterator<Item = &SarifResult> {
+        self.report
+            .iter()
+            .flat_map(|r| &r.runs)
+            .flat_map(|run| &run.results)
+            .filter(|r| r.suppressions.as_ref().is_none_or(|s| s.is_empty()))
+            .filter(|r| r.baseline_state.as_deref() != Some("absent"))
+    }
+
+    /// Findings in the file `uri` whose lines overlap `start..=end` (0-based).
+    fn findings_in(&self, uri: &str, start: usize, end: usize) -> Vec<&SarifResult> {
+        let Some(relative) = self.relative_path(uri) else {
+            return Vec::new();
+        };
+        self.results()
+            .filter(|r| {
+                let Some(location) = r.locations.first() else {
+                    return false;
+                };
+                let (first, last) = line_span(r);
+                location.physical_location.artifact_location.uri == relative
+                    && first <= end
+                    && start <= last
+            })
+            .collect()
+    }
+
+    fn relative_path(&self, uri: &str) -> Option<String> {
+        let path = uri_to_path(uri)?;
+        let relative = path.strip_prefix(&self.root).ok()?;
+        Some(relative.to_string_lossy().replace('\\', "/"))
+    }
+
+    /// `textDocument/publishDiagnostics` for `uri`: its findings, then the
+    /// PAR pattern matches of its current text.
+    fn publish(&self, uri: &str) -> Value {
+        let mut diagnostics: Vec<Value> = match self.relative_path(uri) {
+            Some(relative) => self
+                .results()
+                .filter(|r| {
+                    r.locations
+                        .first()
+                        .is_some_and(|l| l.physical_location.artifact_location.uri == relative)
+                })
+                .map(finding_diagnostic)
+                .collect(),
+            None => Vec::new(),
+        };
+        if let Some(text) = self.documents.get(uri) {
+            diagnostics.extend(self.pattern_diagnostics(uri, text));
+        }
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": {"uri": uri, "diagnostics": diagnostics},
+        })
+    }
+
+    fn pattern_diagnostics(&self, uri: &str, text: &str) -> Vec<Value> {
+        let language = Language::from_filename(uri);
+        if language == Language::Other {
+            return Vec::new();
+        }
+        SecurityRiskPatterns::new_with_root(language, Some(&self.root))
+            .get_pattern_matches(text)
+            .into_iter()
+            .map(|m| {
+                json!({
+                    "range": {
+                        "start": position(text, m.start_byte),
+                        "end": position(text, m.end_byte),
+                    },
+                    "severity": 4,
+                    "source": "parsentry-patterns",
+                    "message": format!(
+                        "{} ({})",
+                        m.pattern_config.description,
+                        m.pattern_config.pattern_type.kind()
+                    ),
+                })
+            })
+            .collect()
+    }
+
+    fn hover_markdown(&self, result: &SarifResult) -> String {
+        let mut md = format!("**[{}] {}**", result.level.to_uppercase(), result.rule_id);
+        let props = result.properties.clone().unwrap_or_default();
+        if let Some(c) = props.confidence {
+            md.push_str(&format!(" (confidence {:.0}%)", c * 100.0));
+        }
+        md.push_str("\n\n");
+        md.push_str(
+            result
+                .message
+                .markdown
+                .as_deref()
+                .unwrap_or(&result.message.text),
+        );
+        md.push_str("\n\n");
+        for (name, value) in [
+            ("Principal", props.principal),
+            ("Action", props.action),
+            ("Resource", props.resource),
+            ("Data flow", props.data_flow),
+            ("CWE", props.cwe.map(|c| c.join(", "))),
+        ] {
+            if let Some(value) = value {
+                md.push_str(&format!("- **{name}**: {value}\n"));
+            }
+        }
+        if let Some(help) = self.remediation(result) {
+            md.push_str("\n**Remediation**\n\n");
+            md.push_str(help);
+        }
+        md
+    }
+
+    fn remediation(&self, result: &SarifResult) -> Option<&str> {
+        self.report
+            .iter()
+            .flat_map(|r| &r.runs)
+            .flat_map(|run| run.tool.driver.rules.iter().flatten())
+            .find(|rule| rule.id == result.rule_id)
+            .and_then(|rule| rule.help.as_ref())
+            .map(|h| h.markdown.as_deref().unwrap_or(&h.text))
+    }
+
+    /// Quick fixes for a finding: the worker's suggested patch, if any, and
+    /// triage decisions.
+    fn code_actions(&self, uri: &str, result: &SarifResult) -> Vec<Value> {
+        let diagnostic = finding_diagnostic(result);
+        let mut actions = Vec::new();
+        if let Some(edits) = self.patch_edits(uri, result) {
+            actions.push(json!({
+                "title": format!("Apply suggested fix for {}", result.rule_id),
+                "kind": "quickfix",
+                "diagnostics": [diagnostic],
+                "isPreferred": true,
+                "edit": {"changes": {uri: edits}},
+            }));
+        }
+        if let Some(fp) = fingerprint(result) {
+            for decision in [Decision::FalsePositive, Decision::AcceptedRisk] {
+                actions.push(json!({
+                    "title": format!("Triage {} as {}", result.rule_id, decision.label()),
+                    "kind": "quickfix",
+                    "diagnostics": [diagnostic],
+                    "command": {
+                        "title": "Triage finding",
+                        "command": TRIAGE_COMMAND,
+                        "arguments": [fp, decision],
+                    },
+                }));
+            }
+        }
+        actions
+    }
+
+    /// Edits of the patch a worker suggested for `result` in the file `uri`.
+    fn patch_edits(&self, uri: &str, result: &SarifResult) -> Option<Vec<Value>> {
+        let name = result.properties.as_ref()?.fix_patch.as_ref()?;
+        let name = Path::new(name).file_name()?;
+        let relative = self.relative_path(uri)?;
+        let patch = std::fs::read_dir(self.reports_dir())
+            .ok()?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path().join("fixes").join(name))
+            .find(|p| p.is_file())?;
+        patch_edits(&std::fs::read_to_string(patch).ok()?, &relative)
+    }
+
+    /// Record a triage decision from a code action and re-merge. Returns the
+    /// URI of the finding's file.
+    fn triage(&mut self, arguments: &Value) -> Option<String> {
+        let fp = arguments[0].as_str()?;
+        let decision: Decision = serde_json::from_value(arguments[1].clone()).ok()?;
+        let result = self.results().find(|r| fingerprint(r) == Some(fp))?.clone();
+        let triage_path = self.reports_dir().parent()?.join(TRIAGE_FILE);
+        let mut triage = Triage::load(&triage_path).ok()?;
+        triage.decide(&result, decision, None);
+        triage.save(&triage_path).ok()?;
+        self.reload();
+        let location = result.locations.first()?;
+        Some(path_to_uri(
+            &self
+                .root
+                .join(&location.physical_location.artifact_location.uri),
+        ))
+    }
+}
+
+/// Newest modification of any worker result or the triage file.
+fn report_signature(reports_dir: &Path) -> Option<SystemTime> {
+    let modified = |p: PathBuf| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+    std::fs::read_dir(reports_dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| modified(e.path().join("result.sarif.json")))
+        .chain(
+            reports_dir
+                .parent()
+                .and_then(|c| modified(c.join(TRIAGE_FILE))),
+        )
+        .max()
+}
+
+fn fingerprint(result: &SarifResult) -> Option<&str> {
+    result
+        .fingerprints
+        .as_ref()?
+        .get("parsentry/v1")
+        .map(String::as_str)
+}
+
+/// First and last 0-based line of a finding.
+fn line_span(result: &SarifResult) -> (usize, usize) {
+    let region = result
+        .locations
+        .first()
+        .and_then(|l| l.physical_location.region.as_ref());
+    let start = region.map_or(0, |r| r.start_line.max(1) as usize - 1);
+    let end = region
+        .and_then(|r| r.end_line)
+        .map_or(start, |e| (e.max(1) as usize - 1).max(start));
+    (start, end)
+}
+
+fn finding_diagnostic(result: &SarifResult) -> Value {
+    let (start, end) = line_span(result);
+    let region = result
+        .locations
+        .first()
+        .and_then(|l| l.physical_location.region.as_ref());
+    let start_char = region
+        .and_then(|r| r.start_column)
+        .map_or(0, |c| c.max(1) - 1);
+    // Without an end column the finding covers its whole last line.
+    let end_pos = match region.and_then(|r| r.end_column) {
+        Some(c) => json!({"line": end, "character": c.max(1) - 1}),
+        None => json!({"line": end + 1, "character": 0}),
+    };
+    let severity = match result.level.as_str() {
+        "error" => 1,
+        "warning" => 2,
+        _ => 3,
+    };
+    let mut message = result.message.text.clone();
+    if let Some(c) = result.properties.as_ref().and_then(|p| p.confidence) {
+        message.push_str(&format!(" (confidence {:.0}%)", c * 100.0));
+    }
+    json!({
+        "range": {"start": {"line": start, "character": start_char}, "end": end_pos},
+        "severity": severity,
+        "code": result.rule_id,
+        "source": "parsentry",
+        "message": message,
+    })
+}
+
+/// LSP position (0-based line, UTF-16 column) of a byte offset in `text`.
+fn position(text: &str, byte: usize) -> Value {
+    let before = &text[..byte.min(text.len())];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let character: usize = before[line_start..].chars().map(char::len_utf16).sum();
+    json!({"line": line, "character": character})
+}
+
+/// `TextEdit`s of the hunks in a unified diff that touch `relative`.
+fn patch_edits(patch: &str, relative: &str) -> Option<Vec<Value>> {
+    let mut edits = Vec::new();
+    let mut in_file = false;
+    let mut lines = patch.lines().peekable();
+    while let Some(line) = lines.next() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            in_file = path.trim().trim_start_matches("b/") == relative;
+            continue;
+        }
+        let Some(header) = line.strip_prefix("@@ -").filter(|_| in_file) else {
+            continue;
+        };
+        let old = header.split_whitespace().next()?;
+        let (old_start, old_len) = match old.split_once(',') {
+            Some((start, len)) => (start.parse::<usize>().ok()?, len.parse::<usize>().ok()?),
+            None => (old.parse::<usize>().ok()?, 1),
+        };
+        let mut new_text = String::new();
+        while let Some(hunk_line) = lines.peek() {
+            match hunk_line.chars().next() {
+                Some(' ') | Some('+') => {
+                    new_text.push_str(&hunk_line[1..]);
+                    new_text.push('\n');
+                }
+                Some('-') if !hunk_line.starts_with("---") => {}
+                Some('\\') => {}
+                _ => break,
+            }
+            lines.next();
+        }
+        // A hunk without old lines inserts after `old_start`.
+        let start = if old_len == 0 {
+            old_start
+        } else {
+            old_start - 1
+        };
+        edits.push(json!({
+            "range": {
+                "start": {"line": start, "character": 0},
+                "end": {"line": start + old_len, "character": 0},
+            },
+            "newText": new_text,
+        }));
+    }
+    (!edits.is_empty()).then_some(edits)
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    Some(PathBuf::from(urlencoding::decode(path).ok()?.into_owned()))
+}
+
+fn path_to_uri(path: &Path) -> String {
+    let encoded: Vec<String> = path
+        .to_string_lossy()
+        .split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect();
+    format!("file://{}", encoded.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn frame(message: &Value) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_message(&mut out, message).unwrap();
+        out
+    }
+
+    #[test]
+    fn messages_round_trip_through_framing() {
+        let message = json!({"jsonrpc": "2.0", "id": 1, "method": "shutdown"});
+        let mut input = Cursor::new(frame(&message));
+        assert_eq!(read_message(&mut input).unwrap(), Some(message));
+        assert_eq!(read_message(&mut input).unwrap(), None);
+    }
+
+    #[test]
+    fn patch_edits_replace_hunks_of_the_file() {
+        let patch = "\
+diff --git a/app.py b/app.py
+--- a/app.py
++++ b/app.py
+@@ -2,2 +2,2 @@ def run():
+-    os.system(cmd)
++    subprocess.run([cmd], check=True)
+     return 0
+";
+        let edits = patch_edits(patch, "app.py").unwrap();
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0]["range"]["start"]["line"], 1);
+        assert_eq!(edits[0]["range"]["end"]["line"], 3);
+        assert_eq!(
+            edits[0]["newText"],
+            "    subprocess.run([cmd], check=True)\n    return 0\n"
+        );
+        assert!(patch_edits(patch, "other.py").is_none());
+    }
+
+    #[test]
+    fn publishes_findings_and_answers_hover_for_open_files() {
+        let cache = tempfile::tempdir().unwrap();
+        let reports = cache.path().join("reports");
+        std::fs::create_dir_all(reports.join("S-1")).unwrap();
+        std::fs::write(
+            reports.join("S-1").join("result.sarif.json"),
+            r#"{"$schema":"","version":"2.1.0","runs":[{"tool":{"driver":{"name":"p","version":"0"}},"results":[
+                {"ruleId":"SQLI","level":"error","message":{"text":"query from input"},
+                 "locations":[{"physicalLocation":{"artifactLocation":{"uri":"app.txt"},"region":{"startLine":2}}}],
+                 "properties":{"principal":"request.args"}}
+            ]}]}"#,
+        )
+        .unwrap();
+
+        // A directory with `reports/` is its own reports location.
+        let mut server = Server::new(cache.path().to_path_buf());
+        let uri = path_to_uri(&cache.path().join("app.txt"));
+        let replies = server.handle(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {"textDocument": {"uri": uri, "text": "a\nb\n"}},
+        }));
+        let diagnostics = &replies[0]["params"]["diagnostics"];
+        assert_eq!(diagnostics.as_array().unwrap().len(), 1);
+        assert_eq!(diagnostics[0]["code"], "SQLI");
+        assert_eq!(diagnostics[0]["range"]["start"]["line"], 1);
+
+        let replies = server.handle(&json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "textDocument/hover",
+            "params": {"textDocument": {"uri": uri}, "position": {"line": 1, "character": 0}},
+        }));
+        let hover = replies[0]["result"]["contents"]["value"].as_str().unwrap();
+        assert!(hover.contains("**Principal**: request.args"));
+
+        let replies = server.handle(&json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "textDocument/codeAction",
+            "params": {
+                "textDocument": {"uri": uri},
+                "range": {"start": {"line": 1, "character": 0}, "end": {"line": 1, "character": 0}},
+            },
+        }));
+        let actions = replies[0]["result"].as_array().unwrap();
+        assert_eq!(actions.len(), 2);
+        let arguments = actions[0]["command"]["arguments"].clone();
+
+        let replies = server.handle(&json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "method": "workspace/executeCommand",
+            "params": {"command": TRIAGE_COMMAND, "arguments": arguments},
+        }));
+        // Triaged as a false positive, the finding is suppressed.
+        assert!(cache.path().join(TRIAGE_FILE).exists());
+        assert_eq!(replies[1]["params"]["diagnostics"], json!([]));
+    }
+}
@@ -2,25 +2,37 @@ use anyhow::Result;
 use dotenvy::dotenv;
 
 use parsentry::cli::RootCommand;
+use parsentry::cli::cancel::CancellationToken;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv().ok();
 
-    // Handle Ctrl+C gracefully
-    tokio::select! {
-        result = RootCommand::execute() => result,
-        ctrl_c_result = tokio::signal::ctrl_c() => {
-            match ctrl_c_result {
-                Ok(()) => {
-                    eprintln!("\nInterrupted by user");
-                    std::process::exit(130);
-                }
-                Err(e) => {
-                    eprintln!("Failed to listen for ctrl-c signal: {}", e);
-                    Err(anyhow::anyhow!("Signal handler setup failed: {}", e))
-                }
-            }
+    // Handle Ctrl+C gracefully: observing commands stop at a safe point and
+    // keep finished work; a second Ctrl+C aborts.
+    let cancel = CancellationToken::new();
+    let listener = cancel.clone();
+    tokio::spawn(async move {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            eprintln!("Failed to listen for ctrl-c signal: {}", e);
+            return;
         }
+        if listener.cancel() {
+            eprintln!("\nInterrupted: finishing in-flight work (Ctrl+C again to abort)");
+            let _ = tokio::signal::ctrl_c().await;
+        }
+        eprintln!("\nInterrupted by user");
+        std::process::exit(130);
+    });
+
+    let result = RootCommand::execute(&cancel)
+        .await
+        .map_err(parsentry_core::http::explain_network_error);
+    if cancel.is_cancelled() {
+        if let Err(e) = result {
+            eprintln!("Error: {e:#}");
+        }
+        std::process::exit(130);
     }
+    result
 }
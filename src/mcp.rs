@@ -0,0 +1,523 @@
+//! Model Context Protocol mode (`parsentry mcp`).
+//!
+//! Exposes Parsentry to AI agents as tools: PAR pattern matching of a file
+//! or a diff, the findings of the workspace's latest scan, and the analysis
+//! prompt for a single file, which the calling agent runs itself. Every tool
+//! returns its result as JSON, both as text content and as
+//! `structuredContent`.
+//!
+//! Speaks newline-delimited JSON-RPC 2.0 over stdio.
+
+use anyhow::{Context, Result, bail};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::cli::commands::generate::workspace_reports_dir;
+use crate::prompt::build_surface_prompt;
+use crate::repo::RepoOps;
+use parsentry_core::{AttackSurface, Language};
+use parsentry_parser::SecurityRiskPatterns;
+use parsentry_reports::merge_sarif_dir;
+use parsentry_reports::sarif::SarifResult;
+
+/// Protocol revisions this server speaks, newest first.
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// JSON-RPC error codes.
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const PARSE_ERROR: i64 = -32700;
+
+/// Serve MCP requests on stdin/stdout until the client closes stdin.
+pub fn serve() -> Result<()> {
+    let server = Server::new(std::env::current_dir()?);
+    let mut output = std::io::stdout().lock();
+    for line in std::io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => server.handle(&message),
+            Err(e) => Some(error(Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+        if let Some(reply) = reply {
+            writeln!(output, "{}", serde_json::to_string(&reply)?)?;
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+struct Server {
+    root: PathBuf,
+}
+
+impl Server {
+    fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Reply to `message`; `None` for notifications.
+    fn handle(&self, message: &Value) -> Option<Value> {
+        let id = message.get("id").cloned()?;
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        let result = match method {
+            "initialize" => {
+                let requested = params["protocolVersion"].as_str().unwrap_or_default();
+                let version = PROTOCOL_VERSIONS
+                    .iter()
+                    .find(|v| **v == requested)
+                    .unwrap_or(&PROTOCOL_VERSIONS[0]);
+                json!({
+                    "protocolVersion": version,
+                    "capabilities": {"tools": {}},
+                    "serverInfo": {"name": "parsentry", "version": env!("CARGO_PKG_VERSION")},
+                })
+            }
+            "ping" => json!({}),
+            "tools/list" => json!({"tools": tools()}),
+            "tools/call" => {
+                let name = params["name"].as_str().unwrap_or_default();
+                let arguments = &params["arguments"];
+                let outcome = match name {
+                    "match_patterns" => self.match_patterns(arguments),
+                    "scan_file" => self.scan_file(arguments),
+                    "check_diff" => self.check_diff(arguments),
+                    "get_report" => self.get_report(arguments),
+                    _ => return Some(error(id, INVALID_PARAMS, &format!("unknown tool: {name}"))),
+                };
+                // Tool failures are reported to the agent, not as protocol errors.
+                match outcome {
+                    Ok(value) => json!({
+                        "content": [{"type": "text", "text": value.to_string()}],
+                        "structuredContent": value,
+                        "isError": false,
+                    }),
+                    Err(e) => json!({
+                        "content": [{"type": "text", "text": format!("{e:#}")}],
+                        "isError": true,
+                    }),
+                }
+            }
+            _ => {
+                return Some(error(
+                    id,
+                    METHOD_NOT_FOUND,
+                    &format!("unsupported method: {method}"),
+                ));
+            }
+        };
+        Some(json!({"jsonrpc": "2.0", "id": id, "result": result}))
+    }
+
+    /// `root`-relative form of a path argument, rejecting paths outside it.
+    fn relative_path(&self, path: &str) -> Result<String> {
+        let root = self.root.canonicalize()?;
+        let absolute = root
+            .join(path)
+            .canonicalize()
+            .with_context(|| format!("no such file: {path}"))?;
+        let relative = absolute
+            .strip_prefix(&root)
+            .map_err(|_| anyhow::anyhow!("{path} is outside the workspace"))?;
+        Ok(relative.to_string_lossy().replace('\\', "/"))
+    }
+
+    /// Unsuppressed findings of the workspace's latest scan.
+    fn findings(&self) -> Vec<SarifResult> {
+        merge_sarif_dir(&workspace_reports_dir(&self.root), None)
+            .map(|report| {
+                report
+                    .runs
+                    .into_iter()
+                    .flat_map(|run| run.results)
+                    .filter(|r| r.suppressions.as_ref().is_none_or(|s| s.is_empty()))
+                    .filter(|r| r.baseline_state.as_deref() != Some("absent"))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn match_patterns(&self, arguments: &Value) -> Result<Value> {
+        let path = arguments["path"].as_str().context("`path` is required")?;
+        // Unsaved text is matched as if it were the file at `path`.
+        let (relative, text) = match arguments["content"].as_str() {
+            Some(content) => (path.to_string(), content.to_string()),
+            None => {
+                let relative = self.relative_path(path)?;
+                let text = std::fs::read_to_string(self.root.join(&relative))?;
+                (relative, text)
+            }
+        };
+        Ok(json!({"matches": pattern_matches(&self.root, &relative, &text)}))
+    }
+
+    fn scan_file(&self, arguments: &Value) -> Result<Value> {
+        let path = arguments["path"].as_str().context("`path` is required")?;
+        let relative = self.relative_path(path)?;
+        let text = std::fs::read_to_string(self.root.join(&relative))?;
+        let findings: Vec<Value> = self
+            .findings()
+            .iter()
+            .filter(|r| finding_file(r) == Some(relative.as_str()))
+            .map(finding_json)
+            .collect();
+        let surface = AttackSurface {
+            id: "MCP-001".to_string(),
+            kind: "file".to_string(),
+            identifier: relative.clone(),
+            locations: vec![relative.clone()],
+            description: "File submitted for analysis by an agent".to_string(),
+        };
+        Ok(json!({
+            "file": relative,
+            "findings": findings,
+            "pattern_matches": pattern_matches(&self.root, &relative, &text),
+            // Parsentry runs no model itself; the agent analyzes with this prompt.
+            "analysis_prompt": build_surface_prompt(&surface, &self.root).map(|p| p.prompt),
+        }))
+    }
+
+    fn check_diff(&self, arguments: &Value) -> Result<Value> {
+        let diff = match arguments["diff"].as_str() {
+            Some(diff) => diff.to_string(),
+            None => RepoOps::new(self.root.clone())
+                .diff(arguments["base"].as_str().unwrap_or("HEAD"))?,
+        };
+        let added = added_lines(&diff);
+        if added.is_empty() {
+            bail!("the diff adds no lines");
+        }
+
+        let mut matches = Vec::new();
+        for (file, lines) in &added {
+            // Diffs are client input; files outside the workspace are skipped.
+            let Ok(relative) = self.relative_path(file) else {
+                continue;
+            };
+            let Ok(text) = std::fs::read_to_string(self.root.join(&relative)) else {
+                continue;
+            };
+            matches.extend(
+                pattern_matches(&self.root, &relative, &text)
+                    .into_iter()
+                    .filter(|m| m["line"].as_u64().is_some_and(|l| lines.contains(&l))),
+            );
+        }
+        let findings: Vec<Value> = self
+            .findings()
+            .iter()
+            .filter(|r| {
+                finding_file(r)
+                    .and_then(|f| added.get(f))
+                    .is_some_and(|lines| lines.contains(&finding_line(r)))
+            })
+            .map(finding_json)
+            .collect();
+        Ok(json!({
+            "files": added.keys().collect::<Vec<_>>(),
+            "findings": findings,
+            "pattern_matches": matches,
+        }))
+    }
+
+    fn get_report(&self, arguments: &Value) -> Result<Value> {
+        let min_level = arguments["min_level"].as_str().unwrap_or("none");
+        let findings: Vec<Value> = self
+            .findings()
+            .iter()
+            .filter(|r| level_rank(&r.level) >= level_rank(min_level))
+            .map(finding_json)
+            .collect();
+        Ok(json!({"findings": findings}))
+    }
+}
+
+/// Tool definitions for `tools/list`.
+fn tools() -> Value {
+    let path = json!({"type": "string", "description": "File path relative to the workspace root"});
+    json!([
+        {
+            "name": "match_patterns",
+            "description": "Match a file against Parsentry's PAR (principal/action/resource) security patterns.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": path,
+                    "content": {"type": "string", "description": "Unsaved file text to match instead of the file on disk"},
+                },
+                "required": ["path"],
+            },
+        },
+        {
+            "name": "scan_file",
+            "description": "Security findings and pattern matches for one file, with a prompt to analyze it for new findings.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {"path": path},
+                "required": ["path"],
+            },
+        },
+        {
+            "name": "check_diff",
+            "description": "Findings and pattern matches on the lines a diff adds.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "diff": {"type": "string", "description": "Unified diff; defaults to the working tree's diff against `base`"},
+                    "base": {"type": "string", "description": "Git ref to diff the working tree against (default HEAD)"},
+                },
+            },
+        },
+        {
+            "name": "get_report",
+            "description": "Findings of the workspace's latest Parsentry scan.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "min_level": {"type": "string", "enum": ["error", "warning", "note", "none"]},
+                },
+            },
+        },
+    ])
+}
+
+fn pattern_matches(root: &Path, file: &str, text: &str) -> Vec<Value> {
+    let language = Language::from_filename(file);
+    if language == Language::Other {
+        return Vec::new();
+    }
+    SecurityRiskPatterns::new_with_root(language, Some(root))
+        .get_pattern_matches(text)
+        .into_iter()
+        .map(|m| {
+            let before = &text[..m.start_byte.min(text.len())];
+            let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+            json!({
+                "file": file,
+                "line": before.matches('\n').count() + 1,
+                "column": before[line_start..].chars().count() + 1,
+                "pattern": m.pattern_config.description,
+                "kind": m.pattern_config.pattern_type.kind(),
+                "text": m.matched_text,
+            })
+        })
+        .collect()
+}
+
+fn finding_file(result: &SarifResult) -> Option<&str> {
+    result
+        .locations
+        .first()
+        .map(|l| l.physical_location.artifact_location.uri.as_str())
+}
+
+fn finding_line(result: &SarifResult) -> u64 {
+    result
+        .locations
+        .first()
+        .and_then(|l| l.physical_location.region.as_ref())
+        .map_or(1, |r| r.start_line.max(1) as u64)
+}
+
+fn finding_json(result: &SarifResult) -> Value {
+    let props = result.properties.clone().unwrap_or_default();
+    json!({
+        "rule_id": result.rule_id,
+        "level": result.level,
+        "message": result.message.text,
+        "file": finding_file(result),
+        "line": finding_line(result),
+        "confidence": props.confidence,
+        "principal": props.principal,
+        "action": props.action,
+        "resource": props.resource,
+        "cwe": props.cwe,
+        "fingerprint": result.fingerprints.as_ref().and_then(|f| f.get("parsentry/v1")),
+    })
+}
+
+fn level_rank(level: &str) -> u8 {
+    match level {
+        "error" => 3,
+        "warning" => 2,
+        "note" => 1,
+        _ => 0,
+    }
+}
+
+/// New-side line numbers each file of a unified diff adds, by path.
+fn added_lines(diff: &str) -> BTreeMap<String, BTreeSet<u64>> {
+    let mut added: BTreeMap<String, BTreeSet<u64>> = BTreeMap::new();
+    let mut file = None;
+    let mut line = 0;
+    for text in diff.lines() {
+        if let Some(path) = text.strip_prefix("+++ ") {
+            let path = path.trim();
+            file = (path != "/dev/null").then(|| path.trim_start_matches("b/").to_string());
+        } else if let Some(header) = text.strip_prefix("@@ ") {
+            // `@@ -a,b +c,d @@`: the new side starts at line c.
+            line = header
+                .split_whitespace()
+                .find_map(|h| h.strip_prefix('+'))
+                .and_then(|h| h.split(',').next())
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(0);
+        } else if let Some(file) = &file {
+            match text.chars().next() {
+                Some('+') => {
+                    added.entry(file.clone()).or_default().insert(line);
+                    line += 1;
+                }
+                Some(' ') => line += 1,
+                _ => {}
+            }
+        }
+    }
+    added
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(server: &Server, name: &str, arguments: Value) -> Value {
+        let reply = server
+            .handle(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "tools/call",
+                "params": {"name": name, "arguments": arguments},
+            }))
+            .unwrap();
+        reply["result"].clone()
+    }
+
+    #[test]
+    fn added_lines_follow_hunk_offsets() {
+        let diff = "\
+diff --git a/app.py b/app.py
+--- a/app.py
++++ b/app.py
+@@ -3,2 +3,3 @@ def run():
+     x = 1
++    os.system(cmd)
+     return x
+@@ -10 +11,2 @@
+-old
++new
++newer
+diff --git a/gone.py b/gone.py
+--- a/gone.py
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+";
+        let added = added_lines(diff);
+        assert_eq!(added.len(), 1);
+        assert_eq!(
+            added["app.py"].iter().copied().collect::<Vec<_>>(),
+            vec![4, 11, 12]
+        );
+    }
+
+    #[test]
+    fn lists_tools_and_rejects_unknown_methods() {
+        let server = Server::new(PathBuf::from("."));
+        let init = server
+            .handle(&json!({"jsonrpc": "2.0", "id": 0, "method": "initialize",
+                "params": {"protocolVersion": "2024-11-05"}}))
+            .unwrap();
+        assert_eq!(init["result"]["protocolVersion"], "2024-11-05");
+        assert!(
+            server
+                .handle(&json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+                .is_none()
+        );
+
+        let list = server
+            .handle(&json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}))
+            .unwrap();
+        let names: Vec<&str> = list["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|t| t["name"].as_str())
+            .collect();
+        assert_eq!(
+            names,
+            ["match_patterns", "scan_file", "check_diff", "get_report"]
+        );
+
+        let unknown = server
+            .handle(&json!({"jsonrpc": "2.0", "id": 2, "method": "resources/list"}))
+            .unwrap();
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn tools_return_workspace_findings_as_json() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join("app.txt"), "a\nb\nc\n").unwrap();
+        let reports = root.path().join("reports").join("S-1");
+        std::fs::create_dir_all(&reports).unwrap();
+        std::fs::write(
+            reports.join("result.sarif.json"),
+            r#"{"$schema":"","version":"2.1.0","runs":[{"tool":{"driver":{"name":"p","version":"0"}},"results":[
+                {"ruleId":"SQLI","level":"error","message":{"text":"query from input"},
+                 "locations":[{"physicalLocation":{"artifactLocation":{"uri":"app.txt"},"region":{"startLine":2}}}],
+                 "properties":{"principal":"request.args"}},
+                {"ruleId":"INFO","level":"note","message":{"text":"debug flag"},
+                 "locations":[{"physicalLocation":{"artifactLocation":{"uri":"app.txt"},"region":{"startLine":3}}}]}
+            ]}]}"#,
+        )
+        .unwrap();
+        let server = Server::new(root.path().to_path_buf());
+
+        let report = call(&server, "get_report", json!({"min_level": "warning"}));
+        let findings = report["structuredContent"]["findings"].as_array().unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0]["principal"], "request.args");
+
+        let scan = call(&server, "scan_file", json!({"path": "app.txt"}));
+        assert_eq!(
+            scan["structuredContent"]["findings"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(
+            scan["structuredContent"]["analysis_prompt"]
+                .as_str()
+                .unwrap()
+                .contains("app.txt")
+        );
+
+        let diff = "--- a/app.txt\n+++ b/app.txt\n@@ -1,2 +1,2 @@\n a\n-x\n+b\n";
+        let check = call(&server, "check_diff", json!({"diff": diff}));
+        let findings = check["structuredContent"]["findings"].as_array().unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0]["rule_id"], "SQLI");
+
+        let outside = call(&server, "scan_file", json!({"path": "../../etc/passwd"}));
+        assert_eq!(outside["isError"], true);
+
+        let diff = "--- a/../../etc/passwd\n+++ b/../../etc/passwd\n@@ -1 +1 @@\n-x\n+root\n";
+        let check = call(&server, "check_diff", json!({"diff": diff}));
+        assert!(
+            check["structuredContent"]["pattern_matches"]
+                .as_array()
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Fetching pattern packs for `parsentry patterns install`.
+//!
+//! A pack source is one of:
+//! - a local file path,
+//! - an `http(s)://` URL of the pack YAML,
+//! - a git repository (`git+https://...` or a URL ending in `.git`) with
+//!   `parsentry-pack.yml` at its root,
+//! - a bare pack name, resolved as `$PARSENTRY_PATTERN_REGISTRY/<name>.yml`.
+
+use anyhow::{Context, Result, bail};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::process::Command;
+
+use parsentry_core::offline::ensure_online;
+use parsentry_parser::{PatternPack, Provenance, SecurityRiskPatterns};
+
+/// Pack file expected at the root of a git pack repository.
+pub const REPO_PACK_FILE: &str = "parsentry-pack.yml";
+
+fn is_git_source(source: &str) -> bool {
+    source.starts_with("git+") || source.ends_with(".git")
+}
+
+fn is_pack_name(source: &str) -> bool {
+    !source.is_empty()
+        && source
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Turn a bare pack name into a registry URL and a local path into an
+/// absolute one, so that `patterns update` works from any directory.
+pub fn resolve_source(source: &str) -> Result<String> {
+    let path = Path::new(source);
+    if path.exists() {
+        return Ok(std::fs::canonicalize(path)?.display().to_string());
+    }
+    if !is_pack_name(source) {
+        return Ok(source.to_string());
+    }
+    let registry = std::env::var("PARSENTRY_PATTERN_REGISTRY").map_err(|_| {
+        anyhow::anyhow!(
+            "`{source}` is not a file or URL; set PARSENTRY_PATTERN_REGISTRY to install packs by name"
+        )
+    })?;
+    Ok(format!("{}/{}.yml", registry.trim_end_matches('/'), source))
+}
+
+async fn fetch_bytes(source: &str) -> Result<String> {
+    if is_git_source(source) {
+        let url = source.trim_start_matches("git+");
+        ensure_online(&format!("clone pattern pack {url}"))?;
+        let tmp = tempfile::tempdir()?;
+        let git_cmd = crate::github::get_verified_git_path().unwrap_or_else(|| "git".to_string());
+        let output = Command::new(&git_cmd)
+            .args(["clone", "--depth", "1", url])
+            .arg(tmp.path())
+            .envs(parsentry_core::http::git_env())
+            .output()?;
+        if !output.status.success() {
+            bail!(
+                "git clone {} failed: {}",
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        let path = tmp.path().join(REPO_PACK_FILE);
+        return std::fs::read_to_string(&path)
+            .with_context(|| format!("{url} has no {REPO_PACK_FILE}"));
+    }
+    if source.starts_with("https://") || source.starts_with("http://") {
+        ensure_online(&format!("download pattern pack {source}"))?;
+        let response = crate::code_host::http_client()?
+            .get(source)
+            .send()
+            .await?
+            .error_for_status()?;
+        return Ok(response.text().await?);
+    }
+    std::fs::read_to_string(source).with_context(|| format!("cannot read {source}"))
+}
+
+/// Fetch and validate a pack. Packs with queries that do not compile are
+/// rejected so that a broken pack never reaches the scanner.
+pub async fn fetch_pack(source: &str) -> Result<(PatternPack, Provenance)> {
+    let source = resolve_source(source)?;
+    let content = fetch_bytes(&source).await?;
+    let pack = PatternPack::from_yaml(&content)?;
+
+    let entries = SecurityRiskPatterns::entries_of(&pack.patterns);
+    let issues = SecurityRiskPatterns::lint_patterns(&entries);
+    if let Some(first) = issues.first() {
+        bail!(
+            "pack `{}` has {} invalid quer{} (first: {} `{}`: {})",
+            pack.name,
+            issues.len(),
+            if issues.len() == 1 { "y" } else { "ies" },
+            first.entry.language,
+            first.entry.config.description,
+            first.error.lines().next().unwrap_or_default()
+        );
+    }
+
+    let provenance = Provenance {
+        source,
+        version: pack.version.clone(),
+        sha256: format!("{:x}", Sha256::digest(content.as_bytes())),
+        installed_at: chrono::Utc::now().to_rfc3339(),
+    };
+    Ok((pack, provenance))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_source_passes_urls_through() {
+        let url = "https://example.com/packs/django.yml";
+        assert_eq!(resolve_source(url).unwrap(), url);
+        assert!(is_git_source("git+https://example.com/packs"));
+        assert!(is_git_source("https://example.com/packs.git"));
+        assert!(!is_pack_name(url));
+        assert!(is_pack_name("django-orm"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_pack_rejects_invalid_queries() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("pack.yml");
+        std::fs::write(
+            &path,
+            "name: demo\nversion: 1.0.0\npatterns:\n  Python:\n    resources:\n      - reference: \"(nope)\"\n        description: broken\n        attack_vector: []\n",
+        )
+        .unwrap();
+        let err = fetch_pack(path.to_str().unwrap()).await.unwrap_err();
+        assert!(err.to_string().contains("invalid query"));
+
+        std::fs::write(&path, "name: demo\nversion: 1.0.0\npatterns: {}\n").unwrap();
+        let (pack, provenance) = fetch_pack(path.to_str().unwrap()).await.unwrap();
+        assert_eq!(pack.name, "demo");
+        assert_eq!(provenance.version, "1.0.0");
+        assert_eq!(provenance.sha256.len(), 64);
+    }
+}
@@ -0,0 +1,199 @@
+//! Scan profiles.
+//!
+//! A profile bundles the knobs that usually change together when switching
+//! between a fast PR scan and a nightly audit: which model tier workers
+//! should use, whether they follow data flow beyond the surface's own
+//! locations, whether a proof of concept is required, how many workers run
+//! at once and the minimum confidence worth reporting.
+
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ScanProfile {
+    /// Fast model, listed locations only, high-confidence findings only
+    Quick,
+    /// Agent defaults
+    #[default]
+    Standard,
+    /// Most capable model, cross-module tracing, PoC required
+    Deep,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileSettings {
+    /// Model hint for workers; `None` leaves the choice to the agent.
+    pub model: Option<String>,
+    /// Models a worker is run on again, in order, when it keeps failing on
+    /// `model` (rate limits, overload, context length).
+    pub fallback_models: Vec<String>,
+    /// Follow callers and callees outside the surface's locations.
+    pub deep_context: bool,
+    /// Require a proof of concept for every finding.
+    pub poc: bool,
+    /// Maximum number of workers running at once; `None` means all.
+    pub max_workers: Option<usize>,
+    /// Findings below this confidence are dropped by workers.
+    pub min_confidence: Option<f64>,
+    /// Minutes a single worker may run before it is given up on.
+    pub worker_timeout_mins: Option<u64>,
+    /// Minutes all workers together may run before the rest are given up on.
+    pub scan_timeout_mins: Option<u64>,
+    /// Tools workers may use (`scan --read-only`, `--no-shell`).
+    pub tools: WorkerTools,
+}
+
+/// Restrictions on the tools workers may use.
+///
+/// The default leaves the tool set to the agent; a restricted worker
+/// reports findings without being able to change the scanned repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkerTools {
+    /// Workers may write only the output files named in their prompt.
+    pub read_only: bool,
+    /// Workers may run shell commands.
+    pub shell: bool,
+}
+
+impl Default for WorkerTools {
+    fn default() -> Self {
+        Self {
+            read_only: false,
+            shell: true,
+        }
+    }
+}
+
+impl WorkerTools {
+    /// Whether any tool is restricted.
+    pub fn is_restricted(self) -> bool {
+        self.read_only || !self.shell
+    }
+}
+
+impl ScanProfile {
+    pub fn name(self) -> &'static str {
+        match self {
+            ScanProfile::Quick => "quick",
+            ScanProfile::Standard => "standard",
+            ScanProfile::Deep => "deep",
+        }
+    }
+
+    /// Profile selected by `PARSENTRY_PROFILE`, if set to a known name.
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var("PARSENTRY_PROFILE").ok()?;
+        Self::from_str(value.trim(), true).ok()
+    }
+
+    /// Settings of the profile with worker tools restricted to `tools`.
+    pub fn settings_with(self, tools: WorkerTools) -> ProfileSettings {
+        ProfileSettings {
+            tools,
+            ..self.settings()
+        }
+    }
+
+    pub fn settings(self) -> ProfileSettings {
+        let settings = match self {
+            ScanProfile::Quick => ProfileSettings {
+                model: Some("the fastest available model".to_string()),
+                fallback_models: Vec::new(),
+                deep_context: false,
+                poc: false,
+                max_workers: Some(8),
+                min_confidence: Some(0.7),
+                worker_timeout_mins: None,
+                scan_timeout_mins: None,
+                tools: WorkerTools::default(),
+            },
+            ScanProfile::Standard => ProfileSettings {
+                model: None,
+                fallback_models: Vec::new(),
+                deep_context: false,
+                poc: false,
+                max_workers: None,
+                min_confidence: None,
+                worker_timeout_mins: None,
+                scan_timeout_mins: None,
+                tools: WorkerTools::default(),
+            },
+            ScanProfile::Deep => ProfileSettings {
+                model: Some("the most capable available model".to_string()),
+                fallback_models: Vec::new(),
+                deep_context: true,
+                poc: true,
+                max_workers: Some(4),
+                min_confidence: Some(0.3),
+                worker_timeout_mins: None,
+                scan_timeout_mins: None,
+                tools: WorkerTools::default(),
+            },
+        };
+        let settings = ProfileSettings {
+            worker_timeout_mins: env_minutes("PARSENTRY_WORKER_TIMEOUT_MINS"),
+            scan_timeout_mins: env_minutes("PARSENTRY_SCAN_TIMEOUT_MINS"),
+            ..settings
+        };
+        let chain: Vec<String> = std::env::var("PARSENTRY_MODEL")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .map(str::to_string)
+            .collect();
+        settings.with_model_chain(&chain)
+    }
+}
+
+impl ProfileSettings {
+    /// Run workers on the first model of `chain`, falling back to the
+    /// others in order. An empty chain keeps the profile's model.
+    pub fn with_model_chain(self, chain: &[String]) -> Self {
+        let Some((first, rest)) = chain.split_first() else {
+            return self;
+        };
+        Self {
+            model: Some(format!("`{first}`")),
+            fallback_models: rest.to_vec(),
+            ..self
+        }
+    }
+}
+
+/// A positive number of minutes from the environment.
+fn env_minutes(name: &str) -> Option<u64> {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|&m| m > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_profile_changes_nothing() {
+        let s = ScanProfile::Standard.settings();
+        assert!(!s.deep_context && !s.poc);
+        assert_eq!(s.max_workers, None);
+        assert_eq!(s.min_confidence, None);
+    }
+
+    #[test]
+    fn test_model_chain_overrides_profile_model() {
+        let chain = ["o3".to_string(), "o4-mini".to_string()];
+        let s = ScanProfile::Quick.settings().with_model_chain(&chain);
+        assert_eq!(s.model.as_deref(), Some("`o3`"));
+        assert_eq!(s.fallback_models, ["o4-mini"]);
+        let s = ScanProfile::Quick.settings().with_model_chain(&[]);
+        assert_eq!(s.model.as_deref(), Some("the fastest available model"));
+    }
+
+    #[test]
+    fn test_deep_profile_requires_poc() {
+        let s = ScanProfile::Deep.settings();
+        assert!(s.deep_context && s.poc);
+        assert!(s.min_confidence < ScanProfile::Quick.settings().min_confidence);
+    }
+}
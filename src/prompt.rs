@@ -4,14 +4,91 @@
 //! source code from the surface's locations, so that surfaces can be
 //! independently dispatched to CLI agents and cached by content hash.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use parsentry_core::{AttackSurface, FileDiscovery, ThreatModel};
+use anyhow::{Context, Result};
+use parsentry_core::content_type::{ContentType, is_skipped, sniff_file, source_map_sources};
+use parsentry_core::graphql::RootField;
+use parsentry_core::template::{fill_placeholders, placeholders};
+use parsentry_core::{
+    AttackSurface, ConfigKind, Dependency, FileDiscovery, PathFilter, ThreatModel,
+    render_dependencies,
+};
+use parsentry_parser::{EntryPoint, ReachableSink, RelatedFunction};
+use parsentry_reports::SarifResult;
+use parsentry_reports::compare::{MODELS_DIR, model_dir_name};
+use parsentry_reports::coverage::{ScanCoverage, SkipReason, SkippedFile};
+use parsentry_reports::feedback::FalsePositive;
+use parsentry_reports::introduced::IntroducedHunk;
+use parsentry_reports::merge::TIMED_OUT_FILE;
+use parsentry_reports::sarif::{SARIF_SCHEMA, SARIF_VERSION};
 use sha2::{Digest, Sha256};
 
+use crate::examples::FewShotExample;
+use crate::i18n::ResponseLanguage;
+use crate::knowledge::{KnowledgePack, config_guidance};
+use crate::profile::{ProfileSettings, ScanProfile, WorkerTools};
+use crate::residency::SourceUpload;
+
 /// Maximum file size (in bytes) to include in a prompt.
 const MAX_FILE_SIZE: u64 = 50 * 1024;
 
+/// Maximum number of `--files-from` entries spelled out in a prompt.
+const MAX_LISTED_FILES: usize = 200;
+
+/// Minimum finding confidence for which workers are asked to propose a patch.
+pub const FIX_CONFIDENCE_THRESHOLD: f64 = 0.8;
+
+/// Default minimum confidence for second-pass verification (`scan --verify`).
+pub const VERIFY_CONFIDENCE_THRESHOLD: f64 = 0.7;
+
+/// Maximum number of callers and callees quoted in a surface prompt.
+pub const MAX_RELATED_FUNCTIONS: usize = 8;
+
+/// Maximum number of reachable sinks listed in a surface prompt
+/// (`scan --reachability`).
+pub const MAX_REACHABLE_SINKS: usize = 10;
+
+/// Maximum number of introduced line ranges listed in a surface prompt
+/// (`scan --commits`).
+pub const MAX_INTRODUCED_HUNKS: usize = 200;
+
+/// Maximum number of known false positives listed in a surface prompt.
+pub const MAX_FALSE_POSITIVES: usize = 10;
+
+/// Maximum number of dependencies listed in a surface prompt.
+pub const MAX_DEPENDENCIES: usize = 60;
+
+/// Maximum number of framework entry points listed in a surface prompt.
+pub const MAX_ENTRY_POINTS: usize = 20;
+
+/// Maximum number of GraphQL schema fields listed in a surface prompt.
+pub const MAX_SCHEMA_FIELDS: usize = 20;
+
+/// Default size limit of a surface prompt in estimated tokens; optional
+/// context is shortened, then dropped, to stay below it.
+pub const DEFAULT_MAX_PROMPT_TOKENS: usize = 12_000;
+
+/// Lines kept of each related function's excerpt when the full excerpts
+/// exceed the prompt budget.
+const RELATED_HEAD_LINES: usize = 3;
+
+/// Rough token count of `text`: about four bytes per token for code and
+/// English with current tokenizers, rounded up.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+/// Surface prompt size limit from `PARSENTRY_MAX_PROMPT_TOKENS`, else
+/// [`DEFAULT_MAX_PROMPT_TOKENS`].
+pub fn max_prompt_tokens() -> usize {
+    std::env::var("PARSENTRY_MAX_PROMPT_TOKENS")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_MAX_PROMPT_TOKENS)
+}
+
 /// A prompt scoped to a single attack surface, ready for agent dispatch.
 #[derive(Debug, Clone)]
 pub struct SurfacePrompt {
@@ -21,6 +98,666 @@ pub struct SurfacePrompt {
     pub prompt: String,
     /// SHA-256 hex digest of resolved source contents, used as a cache key.
     pub cache_key: String,
+    /// Model the worker must run on (`scan --compare-models`).
+    pub model: Option<String>,
+    /// Independent sample number (`scan --samples`), starting at 1.
+    pub sample: Option<usize>,
+}
+
+impl SurfacePrompt {
+    /// Directory of this prompt and its result, relative to the reports
+    /// directory: `<surface>`, or `<surface>/models/<member>` for one member
+    /// of a comparison or consensus run.
+    #[must_use]
+    pub fn relative_dir(&self) -> PathBuf {
+        let member = match (&self.model, self.sample) {
+            (None, None) => return PathBuf::from(&self.surface_id),
+            (Some(model), None) => model_dir_name(model),
+            (None, Some(n)) => format!("sample-{n}"),
+            (Some(model), Some(n)) => format!("{}-{n}", model_dir_name(model)),
+        };
+        Path::new(&self.surface_id).join(MODELS_DIR).join(member)
+    }
+
+    /// Worker id in orchestrator assignments, e.g. `S-1@model/sample-2`.
+    pub fn worker_id(&self) -> String {
+        let mut id = self.surface_id.clone();
+        if let Some(model) = &self.model {
+            id = format!("{id}@{model}");
+        }
+        if let Some(n) = self.sample {
+            id = format!("{id}/sample-{n}");
+        }
+        id
+    }
+
+    /// Render the user's prompt template for `surface`, replacing or
+    /// extending the built-in prompt. The rendered text is part of the
+    /// cache key, so editing the template invalidates cached results.
+    #[must_use]
+    pub fn with_template(
+        mut self,
+        template: &PromptTemplate,
+        surface: &AttackSurface,
+        root_dir: &Path,
+    ) -> Self {
+        let repository_root = root_dir
+            .canonicalize()
+            .unwrap_or_else(|_| root_dir.to_path_buf());
+        let rendered = template.render(surface, &repository_root, &self.prompt);
+        self.cache_key = hex_sha256(&format!("{}\0template={rendered}", self.cache_key));
+        if template.replaces_default() {
+            self.prompt = rendered;
+            if !self.prompt.ends_with('\n') {
+                self.prompt.push('\n');
+            }
+        } else {
+            self.prompt.push_str(&format!(
+                "\nAdditional Instructions\n\n{}\n",
+                rendered.trim_end()
+            ));
+        }
+        self
+    }
+
+    /// Copy of the prompt for one model of a comparison run.
+    ///
+    /// Workers record timing, model and cost in the SARIF invocation so that
+    /// `parsentry compare` can report latency and cost per model. Each model
+    /// has its own cache key, so unchanged surfaces are reused per model.
+    #[must_use]
+    pub fn for_model(&self, model: &str) -> Self {
+        let mut sp = self.clone();
+        sp.prompt.push_str(&format!(
+            "\nRun Metadata\n\n\
+             Set `runs[0].invocation` to an object with:\n\
+             - `executionSuccessful`: true\n\
+             - `startTimeUtc` / `endTimeUtc`: RFC 3339 times at which you started and finished\n\
+             - `properties.model`: `{model}`\n\
+             - `properties.cost_usd`: the cost of this analysis in USD, when known\n"
+        ));
+        sp.cache_key = hex_sha256(&format!("{}\0model={model}", self.cache_key));
+        sp.model = Some(model.to_string());
+        sp
+    }
+
+    /// Ask the worker to record the model it ran on, for scans whose workers
+    /// may fall back to another model (`scan --model A,B`).
+    #[must_use]
+    pub fn with_model_tag(mut self) -> Self {
+        self.prompt.push_str(
+            "\nRun Metadata\n\n\
+             Set `runs[0].invocation` to an object with `executionSuccessful`: true and \
+             `properties.model`: the name of the model you run on, as given by the orchestrator.\n",
+        );
+        self
+    }
+
+    /// Copy of the prompt for sample `n` of `samples` independent analyses.
+    ///
+    /// `merge --quorum` keeps only findings that enough samples agree on,
+    /// so every sample has its own cache key and result directory.
+    #[must_use]
+    pub fn for_sample(&self, n: usize, samples: usize) -> Self {
+        let mut sp = self.clone();
+        sp.prompt.push_str(&format!(
+            "\nIndependent Sample\n\n\
+             This is sample {n} of {samples} independent analyses of this surface. Form your \
+             own conclusions from the source code; do not read or reuse other samples' \
+             results. Only report findings you can support with a concrete data flow.\n"
+        ));
+        sp.cache_key = hex_sha256(&format!("{}\0sample={n}", self.cache_key));
+        sp.sample = Some(n);
+        sp
+    }
+
+    /// Extend the prompt with autofix instructions.
+    ///
+    /// Workers are asked to write one minimal unified diff per high-confidence
+    /// finding into `fixes_dir`. The cache key is re-derived so that results
+    /// produced without patches are not reused.
+    #[must_use]
+    pub fn with_fix_suggestions(mut self, fixes_dir: &Path) -> Self {
+        self.prompt.push_str(&format!(
+            "\nSuggested Fixes\n\n\
+             For every finding with `properties.confidence` >= {threshold}, write a minimal \
+             unified diff that remediates it to `{dir}/<ruleId>-<n>.patch` (n starts at 1).\n\
+             - Paths in the diff MUST be relative to the Repository Root (`a/` and `b/` prefixes).\n\
+             - The patch MUST apply cleanly with `git apply` against the current working tree.\n\
+             - Change only what is needed to fix the finding. Do NOT modify files directly.\n\
+             - Record the patch file name in `properties.fix_patch` of the SARIF result.\n",
+            threshold = FIX_CONFIDENCE_THRESHOLD,
+            dir = fixes_dir.display(),
+        ));
+        self.cache_key = hex_sha256(&format!("{}\0suggest-fixes", self.cache_key));
+        self
+    }
+
+    /// Extend the prompt with the analysis depth of a [`ScanProfile`].
+    ///
+    /// The standard profile leaves the prompt and cache key untouched so that
+    /// existing results stay reusable.
+    #[must_use]
+    pub fn with_profile(mut self, profile: ScanProfile, settings: &ProfileSettings) -> Self {
+        if profile == ScanProfile::Standard {
+            return self;
+        }
+        self.prompt
+            .push_str(&format!("\nAnalysis Depth ({})\n\n", profile.name()));
+        if settings.deep_context {
+            self.prompt.push_str(
+                "- Trace data flow beyond the listed locations: follow callers, callees and \
+                 shared helpers across module boundaries until the source and sink are known.\n",
+            );
+        } else {
+            self.prompt.push_str(
+                "- Limit the analysis to the listed locations. Do not explore unrelated files.\n",
+            );
+        }
+        if settings.poc {
+            self.prompt.push_str(
+                "- For every finding, include a minimal proof of concept (request, input or \
+                 call sequence) in `properties.poc`. Drop findings you cannot demonstrate.\n",
+            );
+        }
+        if let Some(min) = settings.min_confidence {
+            self.prompt.push_str(&format!(
+                "- Only report findings with `properties.confidence` >= {min}.\n"
+            ));
+        }
+        self.cache_key = hex_sha256(&format!("{}\0profile={}", self.cache_key, profile.name()));
+        self
+    }
+
+    /// Tell the worker to run proofs of concept only through `parsentry poc`.
+    ///
+    /// The sandbox refuses to run anything when no isolation is available,
+    /// so workers never execute PoC code directly.
+    #[must_use]
+    pub fn with_poc_sandbox(mut self, parsentry_bin: &Path) -> Self {
+        self.prompt.push_str(&format!(
+            "\nProof of Concept Execution\n\n\
+             To confirm a proof of concept by running it, save it as a self-contained script \
+             with a shebang line (e.g. `poc-1.py`) and run it only with:\n\
+             ```bash\n\
+             {bin} poc <script>\n\
+             ```\n\
+             It runs the script without network access under CPU, memory and time limits and \
+             prints a JSON object; copy that object into `properties.poc_run` of the finding.\n\
+             - Never execute PoC code, exploit requests or payloads in any other way.\n\
+             - If the command refuses because no sandbox is available, do not run the PoC; \
+             report the finding without `properties.poc_run`.\n",
+            bin = shell_quote(&parsentry_bin.display().to_string()),
+        ));
+        self.cache_key = hex_sha256(&format!("{}\0poc-sandbox", self.cache_key));
+        self
+    }
+
+    /// Tell the worker which tools it must not use (`scan --read-only`,
+    /// `--no-shell`). Unrestricted workers get no extra instructions.
+    #[must_use]
+    pub fn with_tool_restrictions(mut self, tools: WorkerTools) -> Self {
+        if tools.is_restricted() {
+            self.prompt.push_str("\nTool Restrictions\n\n");
+            for restriction in tool_restrictions(tools) {
+                self.prompt.push_str(&format!("- You {restriction}.\n"));
+            }
+        }
+        self
+    }
+
+    /// Tell the worker which paths are out of scope.
+    #[must_use]
+    pub fn with_scope(mut self, filter: &PathFilter) -> Self {
+        let section = scope_section(filter);
+        if section.is_empty() {
+            return self;
+        }
+        self.prompt.push_str(&section);
+        self.cache_key = hex_sha256(&format!("{}\0{}", self.cache_key, section));
+        self
+    }
+
+    /// Quote the callers and callees of the surface that live outside its
+    /// locations, within what is left of the prompt budget.
+    ///
+    /// The quoted source is part of the cache key, so a changed callee
+    /// invalidates the surface's result.
+    #[must_use]
+    pub fn with_related_functions(mut self, related: &[RelatedFunction]) -> Self {
+        let budget = max_prompt_tokens().saturating_sub(estimate_tokens(&self.prompt));
+        let section = render_related_functions(related, budget);
+        if section.is_empty() {
+            return self;
+        }
+        self.prompt.push_str(&section);
+        self.cache_key = hex_sha256(&format!("{}\0{}", self.cache_key, section));
+        self
+    }
+
+    /// Show the worker example code with the SARIF expected for it, within
+    /// what is left of the prompt budget; examples that do not fit are left
+    /// out.
+    #[must_use]
+    pub fn with_examples(mut self, examples: &[&FewShotExample]) -> Self {
+        let mut budget = max_prompt_tokens().saturating_sub(estimate_tokens(&self.prompt));
+        let mut section = String::new();
+        for example in examples {
+            let entry = format!(
+                "\n`{}`:\n\n```\n{}\n```\n\nExpected output:\n\n```json\n{}\n```\n",
+                example.name,
+                example.code.trim_end(),
+                example.expected
+            );
+            let tokens = estimate_tokens(&entry);
+            if tokens > budget {
+                continue;
+            }
+            budget -= tokens;
+            section.push_str(&entry);
+        }
+        if section.is_empty() {
+            return self;
+        }
+        let section = format!(
+            "\nExamples\n\n\
+             Analyses of similar code and the SARIF expected for them. An example without \
+             results is code that must not be reported. Follow their output format; do not \
+             report findings in the examples themselves.\n{section}"
+        );
+        self.prompt.push_str(&section);
+        self.cache_key = hex_sha256(&format!("{}\0{}", self.cache_key, section));
+        self
+    }
+
+    /// List findings triaged as false positives in this repository, within
+    /// what is left of the prompt budget, so the worker does not report the
+    /// same patterns again.
+    #[must_use]
+    pub fn with_false_positives(mut self, exemplars: &[&FalsePositive]) -> Self {
+        let mut budget = max_prompt_tokens().saturating_sub(estimate_tokens(&self.prompt));
+        let mut section = String::new();
+        for exemplar in exemplars.iter().take(MAX_FALSE_POSITIVES) {
+            let mut entry = format!("\n- `{}`", exemplar.rule_id);
+            if let Some(reason) = &exemplar.reason {
+                entry.push_str(&format!(": {reason}"));
+            }
+            entry.push('\n');
+            if let Some(code) = &exemplar.code {
+                let fence = exemplar.language.as_deref().unwrap_or("");
+                entry.push_str(&format!(
+                    "\n  ```{fence}\n  {}\n  ```\n",
+                    code.replace('\n', "\n  ")
+                ));
+            }
+            let tokens = estimate_tokens(&entry);
+            if tokens > budget {
+                continue;
+            }
+            budget -= tokens;
+            section.push_str(&entry);
+        }
+        if section.is_empty() {
+            return self;
+        }
+        let section = format!(
+            "\nKnown false-positive patterns in this repository\n\n\
+             Findings like these were reviewed and rejected. Do not report code matching them \
+             unless it differs in a way that makes it exploitable.\n{section}"
+        );
+        self.prompt.push_str(&section);
+        self.cache_key = hex_sha256(&format!("{}\0{}", self.cache_key, section));
+        self
+    }
+
+    /// Tell the worker what it may read when source code must not be
+    /// uploaded (`scan --no-source-upload`).
+    #[must_use]
+    pub fn with_source_upload(mut self, mode: SourceUpload) -> Self {
+        if let Some(section) = mode.instruction() {
+            self.prompt.push_str(&section);
+            self.cache_key = hex_sha256(&format!(
+                "{}\0source-upload={}",
+                self.cache_key,
+                mode.name()
+            ));
+        }
+        self
+    }
+
+    /// Ask the worker to write its findings in `language`.
+    #[must_use]
+    pub fn with_response_language(mut self, language: ResponseLanguage) -> Self {
+        if let Some(section) = language.instruction() {
+            self.prompt.push_str(&section);
+            self.cache_key =
+                hex_sha256(&format!("{}\0language={}", self.cache_key, language.code()));
+        }
+        self
+    }
+
+    /// Limit the worker to the lines a commit range introduced in the
+    /// surface's files.
+    pub fn with_introduced_changes(mut self, range: &str, hunks: &[&IntroducedHunk]) -> Self {
+        let section = render_introduced_changes(range, hunks);
+        if section.is_empty() {
+            return self;
+        }
+        self.prompt.push_str(&section);
+        self.cache_key = hex_sha256(&format!("{}\0{}", self.cache_key, section));
+        self
+    }
+
+    /// List the dependencies and versions the surface's code can use, so
+    /// the worker can tell e.g. whether a template engine escapes by
+    /// default.
+    #[must_use]
+    pub fn with_dependencies(mut self, deps: &[&Dependency]) -> Self {
+        if deps.is_empty() {
+            return self;
+        }
+        let section = format!(
+            "\nKnown Dependencies and Versions\n\n{}",
+            render_dependencies(deps, MAX_DEPENDENCIES)
+        );
+        self.prompt.push_str(&section);
+        self.cache_key = hex_sha256(&format!("{}\0{}", self.cache_key, section));
+        self
+    }
+
+    /// Add the pitfalls of the repository's frameworks that apply to the
+    /// surface's languages.
+    #[must_use]
+    pub fn with_framework_guidance(mut self, packs: &[&KnowledgePack]) -> Self {
+        if packs.is_empty() {
+            return self;
+        }
+        let mut section = String::from(
+            "\nFramework Guidance\n\n\
+             The repository depends on these frameworks. Check their idiomatic sinks, which a \
+             generic review misses:\n",
+        );
+        for pack in packs {
+            section.push_str(&format!("\n### {}\n\n{}\n", pack.framework, pack.guidance));
+        }
+        self.prompt.push_str(&section);
+        self.cache_key = hex_sha256(&format!("{}\0{}", self.cache_key, section));
+        self
+    }
+
+    /// Add the checks for the CI pipelines and Helm charts among the
+    /// surface's files.
+    #[must_use]
+    pub fn with_config_guidance(mut self, kinds: &[ConfigKind]) -> Self {
+        if kinds.is_empty() {
+            return self;
+        }
+        let mut section = String::from(
+            "\nCI/CD and Deployment Configuration\n\n\
+             The surface includes pipeline or deployment configuration. Treat who can trigger \
+             it and what they control as the attack surface:\n",
+        );
+        for kind in kinds {
+            section.push_str(&format!(
+                "\n### {}\n\n{}\n",
+                kind.label(),
+                config_guidance(*kind)
+            ));
+        }
+        self.prompt.push_str(&section);
+        self.cache_key = hex_sha256(&format!("{}\0{}", self.cache_key, section));
+        self
+    }
+
+    /// Point the worker at the GraphQL root fields of the surface's schemas
+    /// that are most often left open.
+    #[must_use]
+    pub fn with_graphql_schema(mut self, fields: &[&RootField]) -> Self {
+        let flagged: Vec<(&RootField, &str)> = fields
+            .iter()
+            .filter_map(|field| Some((*field, field.issue()?)))
+            .collect();
+        if flagged.is_empty() {
+            return self;
+        }
+        let mut section = String::from(
+            "\nGraphQL Schema\n\n\
+             Any client of the endpoint can call these root fields. Check that their resolvers \
+             enforce authorization and bound the work a single query can cause:\n\n",
+        );
+        for (field, issue) in flagged.iter().take(MAX_SCHEMA_FIELDS) {
+            section.push_str(&format!("- {}: {issue}\n", field.describe()));
+        }
+        if flagged.len() > MAX_SCHEMA_FIELDS {
+            section.push_str(&format!(
+                "- … and {} more\n",
+                flagged.len() - MAX_SCHEMA_FIELDS
+            ));
+        }
+        self.prompt.push_str(&section);
+        self.cache_key = hex_sha256(&format!("{}\0{}", self.cache_key, section));
+        self
+    }
+
+    /// List the route handlers, CLI commands and message consumers in the
+    /// surface's files, where attacker input enters.
+    #[must_use]
+    pub fn with_entry_points(mut self, entry_points: &[&EntryPoint]) -> Self {
+        if entry_points.is_empty() {
+            return self;
+        }
+        let mut section = String::from(
+            "\nEntry Points\n\n\
+             These handlers are called from outside the repository. Trace untrusted input \
+             from them first; code no entry point reaches is lower priority.\n\n",
+        );
+        for entry_point in entry_points.iter().take(MAX_ENTRY_POINTS) {
+            section.push_str(&format!("- {}\n", entry_point.describe()));
+        }
+        if entry_points.len() > MAX_ENTRY_POINTS {
+            section.push_str(&format!(
+                "- … and {} more\n",
+                entry_points.len() - MAX_ENTRY_POINTS
+            ));
+        }
+        self.prompt.push_str(&section);
+        self.cache_key = hex_sha256(&format!("{}\0{}", self.cache_key, section));
+        self
+    }
+
+    /// Point the worker at the surface's sinks reachable from a principal,
+    /// with the call path to each.
+    pub fn with_reachable_sinks(mut self, sinks: &[ReachableSink]) -> Self {
+        let section = render_reachable_sinks(sinks);
+        if section.is_empty() {
+            return self;
+        }
+        self.prompt.push_str(&section);
+        self.cache_key = hex_sha256(&format!("{}\0{}", self.cache_key, section));
+        self
+    }
+}
+
+/// Markdown section listing the lines of a surface's files introduced by a
+/// commit range (`scan --commits`), with the introducing commit, at most
+/// [`MAX_INTRODUCED_HUNKS`].
+pub fn render_introduced_changes(range: &str, hunks: &[&IntroducedHunk]) -> String {
+    if hunks.is_empty() {
+        return String::new();
+    }
+    let mut out = format!(
+        "\nIntroduced Changes\n\n\
+         This scan audits the commits in `{range}`. Only report vulnerabilities that the \
+         lines below introduce or make exploitable, and locate each result on one of them; \
+         the surrounding code is context.\n\n"
+    );
+    for hunk in hunks.iter().take(MAX_INTRODUCED_HUNKS) {
+        let lines = if hunk.start_line == hunk.end_line {
+            format!("line {}", hunk.start_line)
+        } else {
+            format!("lines {}-{}", hunk.start_line, hunk.end_line)
+        };
+        let commit = &hunk.introduction.commit;
+        out.push_str(&format!(
+            "- `{}` {lines}: `{}` by {} ({})\n",
+            hunk.path,
+            &commit[..commit.len().min(12)],
+            hunk.introduction.author,
+            hunk.introduction.summary
+        ));
+    }
+    if hunks.len() > MAX_INTRODUCED_HUNKS {
+        out.push_str(&format!(
+            "- ... and {} more ranges in these files\n",
+            hunks.len() - MAX_INTRODUCED_HUNKS
+        ));
+    }
+    out
+}
+
+/// Markdown section listing resource pattern matches with a call path from
+/// a principal match, at most [`MAX_REACHABLE_SINKS`].
+pub fn render_reachable_sinks(sinks: &[ReachableSink]) -> String {
+    if sinks.is_empty() {
+        return String::new();
+    }
+    let mut out = String::from(
+        "\nReachable Sinks\n\n\
+         Resource accesses that a principal reaches through this surface's code, following \
+         the call graph. Calls are resolved by name, so confirm each path before reporting \
+         it; start the analysis from these.\n\n",
+    );
+    for sink in sinks.iter().take(MAX_REACHABLE_SINKS) {
+        let path: Vec<String> = sink
+            .path
+            .iter()
+            .map(|step| format!("`{}` (`{}:{}`)", step.name, step.file, step.line))
+            .collect();
+        let sink_text = if sink.text.is_empty() {
+            sink.resource.clone()
+        } else {
+            format!("`{}` ({})", sink.text, sink.resource)
+        };
+        out.push_str(&format!(
+            "- {sink_text} at `{}:{}`, from {} at `{}:{}`\n  - path: {}\n",
+            sink.file,
+            sink.line,
+            sink.principal,
+            sink.principal_file,
+            sink.principal_line,
+            path.join(" → ")
+        ));
+    }
+    if sinks.len() > MAX_REACHABLE_SINKS {
+        out.push_str(&format!(
+            "- … and {} more\n",
+            sinks.len() - MAX_REACHABLE_SINKS
+        ));
+    }
+    out
+}
+
+/// Markdown section quoting functions outside a surface that it calls or
+/// is called by.
+///
+/// A section over `max_tokens` is shortened deterministically: first every
+/// excerpt is cut to its first [`RELATED_HEAD_LINES`] lines, then excerpts
+/// are left out, then functions are dropped from the end of the list.
+pub fn render_related_functions(related: &[RelatedFunction], max_tokens: usize) -> String {
+    for excerpt in [Some(usize::MAX), Some(RELATED_HEAD_LINES), None] {
+        let section = related_functions_section(related, excerpt);
+        if estimate_tokens(&section) <= max_tokens {
+            return section;
+        }
+    }
+    (0..related.len())
+        .rev()
+        .map(|n| related_functions_section(&related[..n], None))
+        .find(|section| estimate_tokens(section) <= max_tokens)
+        .unwrap_or_default()
+}
+
+/// Related Functions section quoting up to `excerpt` lines of each
+/// function, or only its location when `excerpt` is `None`.
+fn related_functions_section(related: &[RelatedFunction], excerpt: Option<usize>) -> String {
+    if related.is_empty() {
+        return String::new();
+    }
+    let mut out = String::from(
+        "\nRelated Functions\n\n\
+         Functions outside the listed locations that this surface calls (callee) or that call \
+         into it (caller). Use them to trace data across the surface boundary; open the file \
+         when the excerpt is not enough.\n",
+    );
+    for function in related {
+        out.push_str(&format!(
+            "\n- `{}` ({}) — `{}:{}`\n",
+            function.name,
+            function.relation.label(),
+            function.file,
+            function.line,
+        ));
+        if let Some(lines) = excerpt.filter(|_| !function.snippet.is_empty()) {
+            let snippet: Vec<&str> = function.snippet.lines().take(lines).collect();
+            out.push_str(&format!("\n```\n{}\n```\n", snippet.join("\n")));
+        }
+    }
+    out
+}
+
+/// Markdown section telling an agent which paths are out of scope.
+pub fn scope_section(filter: &PathFilter) -> String {
+    if filter.is_empty() {
+        return String::new();
+    }
+    let mut out = String::from("\nScope\n\n");
+    for glob in filter.include_patterns() {
+        out.push_str(&format!("- Only consider paths matching `{glob}`\n"));
+    }
+    for glob in filter.exclude_patterns() {
+        out.push_str(&format!("- Ignore paths matching `{glob}`\n"));
+    }
+    if let Some(files) = filter.listed_files() {
+        out.push_str("- Only consider these files:\n");
+        for file in files.iter().take(MAX_LISTED_FILES) {
+            out.push_str(&format!("  - `{}`\n", file.display()));
+        }
+        if files.len() > MAX_LISTED_FILES {
+            out.push_str(&format!(
+                "  - ... and {} more\n",
+                files.len() - MAX_LISTED_FILES
+            ));
+        }
+    }
+    out
+}
+
+/// Whether any on-disk location of `surface` survives `filter`.
+///
+/// Surfaces without on-disk locations (endpoints, services) are always in
+/// scope. Directory locations are walked with ignore files applied;
+/// explicitly named files are only checked against the globs.
+pub fn surface_in_scope(surface: &AttackSurface, root_dir: &Path, filter: &PathFilter) -> bool {
+    let discovery = FileDiscovery::new(root_dir.to_path_buf()).with_filter(filter.clone());
+    let mut on_disk = false;
+    for location in &surface.locations {
+        let full_path = root_dir.join(location);
+        if full_path.is_file() {
+            on_disk = true;
+            if discovery.is_in_scope(&full_path) {
+                return true;
+            }
+        } else if full_path.is_dir() {
+            on_disk = true;
+            if discovery
+                .get_files_in_path(&full_path)
+                .is_ok_and(|files| !files.is_empty())
+            {
+                return true;
+            }
+        }
+    }
+    !on_disk
 }
 
 /// Resolved source file: relative path + contents.
@@ -29,89 +766,114 @@ struct SourceFile {
     contents: String,
 }
 
-/// Resolve all readable source files for a surface's locations.
-fn resolve_source_files(surface: &AttackSurface, root_dir: &Path) -> Vec<SourceFile> {
+/// Resolve all readable source files for a surface's locations, and the
+/// files left out with the reason.
+fn skip_reason(content: ContentType) -> SkipReason {
+    match content {
+        ContentType::Minified => SkipReason::Minified,
+        ContentType::Generated => SkipReason::Generated,
+        _ => SkipReason::Binary,
+    }
+}
+
+fn resolve_source_files(
+    surface: &AttackSurface,
+    root_dir: &Path,
+) -> (Vec<SourceFile>, Vec<SkippedFile>) {
     let discovery = FileDiscovery::new(root_dir.to_path_buf());
     let mut sources: Vec<SourceFile> = Vec::new();
+    let mut skipped: Vec<SkippedFile> = Vec::new();
     let mut seen = std::collections::HashSet::new();
+    let mut skip = |path: String, reason| {
+        skipped.push(SkippedFile {
+            path,
+            reason,
+            surface: surface.id.clone(),
+        });
+    };
 
     let canonical_root = match root_dir.canonicalize() {
         Ok(p) => p,
-        Err(_) => return sources,
+        Err(_) => return (sources, skipped),
     };
 
     for location in &surface.locations {
         // Reject absolute paths and path traversal attempts
         if Path::new(location).is_absolute() || location.contains("..") {
+            skip(location.clone(), SkipReason::OutsideRepository);
             continue;
         }
 
         let full_path = root_dir.join(location);
+        // Verify the resolved path stays within root_dir
+        let inside = full_path
+            .canonicalize()
+            .is_ok_and(|canonical| canonical.starts_with(&canonical_root));
 
-        if full_path.is_file() {
-            // Verify the resolved path stays within root_dir
-            if let Ok(canonical) = full_path.canonicalize() {
-                if !canonical.starts_with(&canonical_root) {
-                    continue;
-                }
-            } else {
-                continue;
-            }
-            // Single file
-            if let Ok(meta) = std::fs::metadata(&full_path)
-                && meta.len() <= MAX_FILE_SIZE
-            {
-                let rel = full_path
-                    .strip_prefix(root_dir)
-                    .unwrap_or(&full_path)
-                    .to_string_lossy()
-                    .to_string();
-                if seen.insert(rel.clone())
-                    && let Ok(contents) = std::fs::read_to_string(&full_path)
-                {
-                    sources.push(SourceFile {
-                        rel_path: rel,
-                        contents,
-                    });
-                }
+        let (mut files, unread) = if full_path.is_file() {
+            match sniff_file(&full_path) {
+                content if is_skipped(content) => (Vec::new(), vec![(full_path, content)]),
+                _ => (vec![full_path], Vec::new()),
             }
         } else if full_path.is_dir() {
-            // Verify the directory stays within root_dir
-            if let Ok(canonical) = full_path.canonicalize() {
-                if !canonical.starts_with(&canonical_root) {
-                    continue;
-                }
+            // Directory — find all source files under it
+            let discovered = discovery.discover_in_path(&full_path).unwrap_or_default();
+            (discovered.files, discovered.skipped)
+        } else {
+            // Not a path (endpoint, service, ...) or missing: nothing to read.
+            continue;
+        };
+        if !inside {
+            skip(location.clone(), SkipReason::OutsideRepository);
+            continue;
+        }
+
+        for (file_path, content) in unread {
+            let rel = file_path
+                .strip_prefix(root_dir)
+                .unwrap_or(&file_path)
+                .to_string_lossy()
+                .to_string();
+            if !seen.insert(rel.clone()) {
+                continue;
+            }
+            // A bundle is read as the files its source map was built from
+            let originals = match content {
+                ContentType::Binary => Vec::new(),
+                _ => source_map_sources(root_dir, &file_path),
+            };
+            if !originals.is_empty() {
+                files.extend(originals);
+            } else if std::fs::metadata(&file_path).is_ok_and(|meta| meta.len() > MAX_FILE_SIZE) {
+                skip(rel, SkipReason::TooLarge);
             } else {
+                skip(rel, skip_reason(content));
+            }
+        }
+        for file_path in files {
+            let rel = file_path
+                .strip_prefix(root_dir)
+                .unwrap_or(&file_path)
+                .to_string_lossy()
+                .to_string();
+            if !seen.insert(rel.clone()) {
                 continue;
             }
-            // Directory — find all source files under it
-            if let Ok(files) = discovery.get_files_in_path(&full_path) {
-                for file_path in files {
-                    if let Ok(meta) = std::fs::metadata(&file_path)
-                        && meta.len() > MAX_FILE_SIZE
-                    {
-                        continue;
-                    }
-                    let rel = file_path
-                        .strip_prefix(root_dir)
-                        .unwrap_or(&file_path)
-                        .to_string_lossy()
-                        .to_string();
-                    if seen.insert(rel.clone())
-                        && let Ok(contents) = std::fs::read_to_string(&file_path)
-                    {
-                        sources.push(SourceFile {
-                            rel_path: rel,
-                            contents,
-                        });
-                    }
-                }
+            if std::fs::metadata(&file_path).is_ok_and(|meta| meta.len() > MAX_FILE_SIZE) {
+                skip(rel, SkipReason::TooLarge);
+                continue;
+            }
+            match std::fs::read_to_string(&file_path) {
+                Ok(contents) => sources.push(SourceFile {
+                    rel_path: rel,
+                    contents,
+                }),
+                Err(_) => skip(rel, SkipReason::Unreadable),
             }
         }
-        // If the path doesn't exist, silently skip it.
     }
 
-    sources
+    (sources, skipped)
 }
 
 /// Generate a prompt for a single [`AttackSurface`].
@@ -120,7 +882,7 @@ fn resolve_source_files(surface: &AttackSurface, root_dir: &Path) -> Vec<SourceF
 /// Otherwise, the prompt instructs the agent to investigate the surface
 /// using whatever methods are appropriate.
 pub fn build_surface_prompt(surface: &AttackSurface, root_dir: &Path) -> Option<SurfacePrompt> {
-    let sources = resolve_source_files(surface, root_dir);
+    let (sources, _) = resolve_source_files(surface, root_dir);
 
     // Cache key: file contents when available, otherwise surface metadata
     let cache_key = if !sources.is_empty() {
@@ -169,10 +931,12 @@ pub fn build_surface_prompt(surface: &AttackSurface, root_dir: &Path) -> Option<
          or other resources — investigate accordingly.\n\n",
     );
 
-    prompt.push_str("Output valid SARIF v2.1.0 JSON compatible with `parsentry merge`.\n");
+    prompt.push_str(&format!(
+        "Output valid SARIF v{SARIF_VERSION} JSON compatible with `parsentry merge`.\n"
+    ));
     prompt.push_str("The SARIF MUST include:\n");
-    prompt.push_str("- top-level `$schema`\n");
-    prompt.push_str("- top-level `version` set to `2.1.0`\n");
+    prompt.push_str(&format!("- top-level `$schema`: `{SARIF_SCHEMA}`\n"));
+    prompt.push_str(&format!("- top-level `version` set to `{SARIF_VERSION}`\n"));
     prompt.push_str("- `runs[0].tool.driver.name`\n");
     prompt.push_str("- `runs[0].tool.driver.version`\n");
     prompt.push_str("For each finding, provide:\n");
@@ -181,27 +945,261 @@ pub fn build_surface_prompt(surface: &AttackSurface, root_dir: &Path) -> Option<
     prompt.push_str("- `message.text`\n");
     prompt.push_str("- `locations[].physicalLocation.artifactLocation.uri`\n");
     prompt.push_str("- `locations[].physicalLocation.region.startLine` when known\n");
+    prompt.push_str(
+        "- `locations[].physicalLocation.region.snippet.text`: the vulnerable lines, copied verbatim (they identify the finding across runs)\n",
+    );
     prompt.push_str("- `properties.confidence`: 0.0-1.0\n");
 
     Some(SurfacePrompt {
         surface_id: surface.id.clone(),
         prompt,
         cache_key,
+        model: None,
+        sample: None,
     })
 }
 
-/// Build prompts for every surface in a [`ThreatModel`].
+/// Build the second-pass prompt that re-examines one finding.
+///
+/// The verifier gets the finding as reported and is asked to refute it
+/// independently, writing a [`parsentry_reports::verify::Verdict`] to
+/// `verdict_path`.
+pub fn build_verification_prompt(
+    result: &SarifResult,
+    surface_id: &str,
+    root_dir: &Path,
+    verdict_path: &Path,
+) -> String {
+    let repository_root = root_dir
+        .canonicalize()
+        .unwrap_or_else(|_| root_dir.to_path_buf());
+    let props = result.properties.clone().unwrap_or_default();
+
+    let mut prompt = String::new();
+    prompt.push_str(
+        "You are a senior security reviewer verifying a finding reported by another \
+         auditor. Assume it may be a false positive until the code proves otherwise.\n\n",
+    );
+    prompt.push_str("Finding Under Review\n\n");
+    prompt.push_str(&format!("- Surface: {surface_id}\n"));
+    prompt.push_str(&format!("- Rule: {}\n", result.rule_id));
+    prompt.push_str(&format!(
+        "- Repository Root: {}\n",
+        repository_root.display()
+    ));
+    for loc in &result.locations {
+        let line = loc
+            .physical_location
+            .region
+            .as_ref()
+            .map(|r| format!(":{}", r.start_line))
+            .unwrap_or_default();
+        prompt.push_str(&format!(
+            "- Location: {}{line}\n",
+            loc.physical_location.artifact_location.uri
+        ));
+    }
+    if let Some(confidence) = props.confidence {
+        prompt.push_str(&format!("- Reported confidence: {confidence}\n"));
+    }
+    for (label, value) in [
+        ("Principal", &props.principal),
+        ("Action", &props.action),
+        ("Resource", &props.resource),
+        ("Data flow", &props.data_flow),
+    ] {
+        if let Some(value) = value {
+            prompt.push_str(&format!("- {label}: {value}\n"));
+        }
+    }
+    prompt.push_str(&format!(
+        "\nReported Analysis\n\n{}\n\n",
+        result
+            .message
+            .markdown
+            .as_ref()
+            .unwrap_or(&result.message.text)
+    ));
+
+    prompt.push_str("Verification Steps\n\n");
+    prompt.push_str("1. Read the code at each location and the code that reaches it.\n");
+    prompt.push_str(
+        "2. Check whether attacker-controlled input actually reaches the sink, and whether \
+         validation, sanitization, framework defaults or configuration neutralize it.\n",
+    );
+    prompt.push_str("3. Decide independently; do not defer to the reported confidence.\n\n");
+
+    prompt.push_str(&format!(
+        "Write JSON to {} with:\n\
+         - `verdict`: `confirmed`, `rejected` or `uncertain`\n\
+         - `confidence`: 0.0-1.0, your confidence that the finding is exploitable\n\
+         - `rationale`: one or two sentences explaining the verdict\n",
+        verdict_path.display()
+    ));
+    prompt
+}
+
+/// Build the prompt that asks a worker to fix output `parsentry validate`
+/// could not repair.
+pub fn build_repair_prompt(prompt_path: &Path, result_path: &Path, errors: &[String]) -> String {
+    let mut prompt = format!(
+        "The SARIF written for the analysis in {} could not be used.\n\nProblems\n\n",
+        prompt_path.display()
+    );
+    for error in errors {
+        prompt.push_str(&format!("- {error}\n"));
+    }
+    prompt.push_str(&format!(
+        "\nRead {result} if it exists and rewrite it as valid SARIF v{SARIF_VERSION} that meets the \
+         output requirements in {prompt}. Keep the existing findings; repeat the analysis \
+         only if the file is missing or empty. Write bare JSON to {result}, without Markdown \
+         fences or commentary.\n",
+        result = result_path.display(),
+        prompt = prompt_path.display(),
+    ));
+    prompt
+}
+
+/// Build prompts for every surface in a [`ThreatModel`], rendered through
+/// the user's `template` when one is configured.
 pub fn build_all_surface_prompts(
     threat_model: &ThreatModel,
     root_dir: &Path,
+    template: Option<&PromptTemplate>,
 ) -> Vec<SurfacePrompt> {
     threat_model
         .surfaces
         .iter()
-        .filter_map(|s| build_surface_prompt(s, root_dir))
+        .filter_map(|s| {
+            let sp = build_surface_prompt(s, root_dir)?;
+            Some(match template {
+                Some(template) => sp.with_template(template, s, root_dir),
+                None => sp,
+            })
+        })
         .collect()
 }
 
+/// A user prompt template (`PARSENTRY_PROMPT_TEMPLATE`).
+///
+/// Placeholders such as `{{identifier}}` are filled in per surface. A
+/// template containing `{{default}}` replaces the built-in prompt (role,
+/// surface and output format), which it can embed there; any other template
+/// is appended to it as additional instructions, e.g. coding standards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTemplate {
+    text: String,
+    replaces_default: bool,
+}
+
+impl PromptTemplate {
+    /// Placeholders a template may use.
+    pub const PLACEHOLDERS: &[&str] = &[
+        "default",
+        "surface_id",
+        "kind",
+        "identifier",
+        "description",
+        "locations",
+        "repository_root",
+    ];
+
+    /// Parse a template, rejecting unknown or unterminated placeholders.
+    pub fn parse(text: &str) -> Result<Self> {
+        let names = placeholders(text, Self::PLACEHOLDERS)?;
+        Ok(Self {
+            text: text.to_string(),
+            replaces_default: names.contains(&"default"),
+        })
+    }
+
+    /// The template at `PARSENTRY_PROMPT_TEMPLATE`, if set.
+    pub fn load() -> Result<Option<Self>> {
+        let Some(path) = std::env::var_os("PARSENTRY_PROMPT_TEMPLATE").filter(|p| !p.is_empty())
+        else {
+            return Ok(None);
+        };
+        let path = PathBuf::from(path);
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("cannot read prompt template {}", path.display()))?;
+        Self::parse(&text)
+            .with_context(|| format!("invalid prompt template {}", path.display()))
+            .map(Some)
+    }
+
+    /// Whether the template replaces the built-in prompt.
+    pub fn replaces_default(&self) -> bool {
+        self.replaces_default
+    }
+
+    /// The template with its placeholders filled in for `surface`.
+    pub fn render(&self, surface: &AttackSurface, repository_root: &Path, default: &str) -> String {
+        fill_placeholders(&self.text, |name| match name {
+            "default" => default.trim_end().to_string(),
+            "surface_id" => surface.id.clone(),
+            "kind" => surface.kind.clone(),
+            "identifier" => surface.identifier.clone(),
+            "description" => surface.description.clone(),
+            "locations" => surface.locations.join(", "),
+            "repository_root" => repository_root.display().to_string(),
+            _ => String::new(),
+        })
+    }
+}
+
+/// Files every surface in a [`ThreatModel`] hands to its worker and the
+/// files left out, with `discovered` files in scope.
+pub fn scan_coverage(
+    threat_model: &ThreatModel,
+    root_dir: &Path,
+    discovered: usize,
+) -> ScanCoverage {
+    let mut coverage = ScanCoverage {
+        discovered,
+        ..Default::default()
+    };
+    for surface in &threat_model.surfaces {
+        let (sources, skipped) = resolve_source_files(surface, root_dir);
+        coverage.surfaces.insert(
+            surface.id.clone(),
+            sources.into_iter().map(|s| s.rel_path).collect(),
+        );
+        coverage.skipped.extend(skipped);
+    }
+    coverage
+}
+
+/// Pending surface prompts of one target, for a multi-target orchestrator.
+pub struct OrchestratorBatch<'a> {
+    pub target: &'a str,
+    /// The target's `reports` directory inside its project cache.
+    pub output_dir: &'a Path,
+    pub prompts: &'a [SurfacePrompt],
+    /// Findings each surface's members must agree on (`scan --quorum`);
+    /// `None` unless the scan runs several members per surface.
+    pub quorum: Option<usize>,
+    /// Second-pass verification after the first analysis (`scan --verify`).
+    pub verify: Option<&'a Verification>,
+    /// Archive worker transcripts before merging (`scan --save-transcripts`).
+    pub save_transcripts: bool,
+    /// Open the report with a one-page executive summary
+    /// (`scan --executive-summary`).
+    pub executive_summary: bool,
+}
+
+/// File in a target's `reports` directory holding the executive summary
+/// written when `scan --executive-summary` is set.
+pub const EXECUTIVE_SUMMARY_FILE: &str = "executive-summary.md";
+
+/// Settings of the second-pass verification stage.
+#[derive(Debug, Clone)]
+pub struct Verification {
+    /// Findings at or above this confidence are re-examined.
+    pub threshold: f64,
+    /// Model for verification workers, typically a stronger one.
+    pub model: Option<String>,
+}
+
 /// Build an orchestrator prompt that dispatches all surface analyses
 /// in an agent-neutral way.
 pub fn build_orchestrator_prompt(
@@ -209,9 +1207,33 @@ pub fn build_orchestrator_prompt(
     output_dir: &Path,
     target: &str,
     parsentry_bin: &Path,
+    profile: &ProfileSettings,
 ) -> String {
-    let mut prompt = String::new();
+    build_multi_target_orchestrator_prompt(
+        &[OrchestratorBatch {
+            target,
+            output_dir,
+            prompts: surface_prompts,
+            quorum: None,
+            verify: None,
+            save_transcripts: false,
+            executive_summary: false,
+        }],
+        parsentry_bin,
+        profile,
+    )
+}
 
+/// Build one orchestrator prompt covering several targets.
+///
+/// Workers of all targets run in a single parallel pool; merge and report
+/// steps stay per target, so each target keeps its own `reports` directory.
+pub fn build_multi_target_orchestrator_prompt(
+    batches: &[OrchestratorBatch<'_>],
+    parsentry_bin: &Path,
+    profile: &ProfileSettings,
+) -> String {
+    let mut prompt = String::new();
     prompt.push_str(
         "You are a security analysis orchestrator. Dispatch one worker per surface in \
          parallel using your environment's built-in subagent or agent capability.\n\n",
@@ -231,17 +1253,146 @@ pub fn build_orchestrator_prompt(
          its assigned prompt file.\n",
     );
     prompt.push_str("5. Wait for every worker to finish before starting post-processing.\n");
-    prompt.push_str("\nWorker Assignments\n\n");
-
-    for sp in surface_prompts {
-        let prompt_path = output_dir.join(&sp.surface_id).join("prompt.md");
+    let mut rule = 6;
+    if profile.tools.is_restricted() {
+        prompt.push_str(&format!(
+            "{rule}. Restrict every worker: it {}. Enforce this through your environment's tool \
+             permissions if it has them (an allowed-tools list, a read-only sandbox mode); \
+             otherwise pass the restriction on in the worker's instructions.\n",
+            tool_restrictions(profile.tools).join(" and ")
+        ));
+        rule += 1;
+    }
+    if let Some(max) = profile.max_workers {
+        if batches.len() > 1 {
+            // One large target must not hold every slot while the others wait.
+            prompt.push_str(&format!(
+                "{rule}. Run at most {max} workers at the same time, shared fairly between \
+                 targets: as soon as one finishes, start the next listed worker of the target \
+                 with the fewest workers running.\n"
+            ));
+        } else {
+            prompt.push_str(&format!(
+                "{rule}. Run at most {max} workers at the same time; start the next one as soon \
+                 as one finishes, in the order the assignments are listed.\n"
+            ));
+        }
+        rule += 1;
+    }
+    if let Some(mins) = profile.worker_timeout_mins {
+        prompt.push_str(&format!(
+            "{rule}. Give each worker at most {mins} minutes. If it has not finished by then, \
+             terminate it together with every process it started (if you cannot, at least stop \
+             waiting for it), create an empty file named `{TIMED_OUT_FILE}` next to its prompt \
+             file and treat it as finished. Do not retry it or analyze its surface yourself.\n"
+        ));
+        rule += 1;
+    }
+    if let Some(mins) = profile.scan_timeout_mins {
+        prompt.push_str(&format!(
+            "{rule}. All workers together have {mins} minutes from when the first one starts. \
+             When that deadline passes, do not start any more workers; terminate every worker \
+             that has not finished together with the processes it started, create an empty file \
+             named `{TIMED_OUT_FILE}` next to its prompt file and continue with post-processing.\n"
+        ));
+        rule += 1;
+    }
+    let comparing = batches
+        .iter()
+        .any(|b| b.prompts.iter().any(|sp| sp.model.is_some()));
+    if comparing {
+        prompt.push_str(&format!(
+            "{rule}. Run each worker on the model named in its assignment. If your environment \
+             cannot select that model, report it instead of substituting another model.\n"
+        ));
+        rule += 1;
+    } else if let Some(model) = &profile.model {
+        prompt.push_str(&format!(
+            "{rule}. If your environment lets you choose the worker model, use {model}.\n"
+        ));
+        rule += 1;
+        if !profile.fallback_models.is_empty() {
+            let fallbacks: Vec<String> = profile
+                .fallback_models
+                .iter()
+                .map(|m| format!("`{m}`"))
+                .collect();
+            prompt.push_str(&format!(
+                "{rule}. If a worker fails twice on {model} because of rate limits, overload or a \
+                 prompt too long for the model, run it again on the next model of: {}. Tell every \
+                 worker the name of the model it runs on.\n",
+                fallbacks.join(", ")
+            ));
+            rule += 1;
+        }
+    }
+    if batches
+        .iter()
+        .any(|b| b.prompts.iter().any(|sp| sp.sample.is_some()))
+    {
         prompt.push_str(&format!(
-            "- Worker `{id}`: read `{path}` and execute the instructions in it.\n",
-            id = sp.surface_id,
-            path = prompt_path.display(),
+            "{rule}. Workers of the same surface are independent samples: never share one \
+             worker's findings with another.\n"
         ));
     }
+    prompt.push_str("\nWorker Assignments\n\n");
+
+    let multi = batches.len() > 1;
+    for batch in batches {
+        if multi {
+            prompt.push_str(&format!("\nTarget `{}`\n\n", batch.target));
+        }
+        for sp in batch.prompts {
+            let prompt_path = batch.output_dir.join(sp.relative_dir()).join("prompt.md");
+            let mut id = sp.worker_id();
+            // Surface ids are only unique within one threat model.
+            if multi {
+                id = format!("{}#{id}", batch.target);
+            }
+            let on_model = sp
+                .model
+                .as_ref()
+                .map(|m| format!(" Run it on model `{m}`."))
+                .unwrap_or_default();
+            prompt.push_str(&format!(
+                "- Worker `{id}`: read `{path}` and execute the instructions in it.{on_model}\n",
+                path = prompt_path.display(),
+            ));
+        }
+    }
+
+    if multi {
+        prompt.push_str("\nAfter ALL workers complete, post-process every target below in turn.\n");
+    }
+    for batch in batches {
+        if multi {
+            prompt.push_str(&format!("\nTarget `{}`:\n", batch.target));
+        }
+        prompt.push_str(&post_processing(batch, parsentry_bin));
+    }
+
+    prompt
+}
+
+/// What a worker restricted to `tools` must not do, completing "it ...".
+fn tool_restrictions(tools: WorkerTools) -> Vec<&'static str> {
+    let mut restrictions = Vec::new();
+    if tools.read_only {
+        restrictions.push(
+            "must not create, modify or delete any file except the output files named in the \
+             worker prompt",
+        );
+    }
+    if !tools.shell {
+        restrictions.push("must not run shell commands");
+    }
+    restrictions
+}
 
+/// Merge and report instructions for one target.
+fn post_processing(batch: &OrchestratorBatch<'_>, parsentry_bin: &Path) -> String {
+    let output_dir = batch.output_dir;
+    let target = batch.target;
     let target_q = shell_quote(target);
     let parsentry_bin_q = shell_quote(&parsentry_bin.display().to_string());
     let project_cache = output_dir
@@ -250,21 +1401,100 @@ pub fn build_orchestrator_prompt(
     let cache_base = project_cache
         .parent()
         .expect("project cache dir must have a cache base parent");
+    let cache_base_q = shell_quote(&cache_base.display().to_string());
+    let quorum = batch
+        .quorum
+        .map(|k| format!(" --quorum {k}"))
+        .unwrap_or_default();
+    let mut steps = format!(
+        "\nAfter ALL workers complete, run exactly:\n\
+         ```bash\n\
+         PARSENTRY_CACHE_DIR={cache_base_q} {parsentry_bin_q} validate {target_q}\n\
+         ```\n\
+         It repairs malformed worker output and lists one repair worker for every output it \
+         cannot fix. Dispatch them in parallel and wait for all of them. This is the only \
+         retry: do not run validate again. If it lists none, continue.\n"
+    );
+    let mut after = "After ALL repair workers complete";
+    if batch.save_transcripts {
+        steps.push_str(&format!(
+            "\n{after}, run exactly:\n\
+             ```bash\n\
+             PARSENTRY_CACHE_DIR={cache_base_q} {parsentry_bin_q} transcripts {target_q}\n\
+             ```\n"
+        ));
+        after = "Next";
+    }
+    if quorum.is_empty() && batch.prompts.iter().any(|sp| sp.model.is_some()) {
+        let comparison = output_dir.join("model-comparison.md");
+        let comparison_q = shell_quote(&comparison.display().to_string());
+        steps.push_str(&format!(
+            "\n{after}, run exactly:\n\
+             ```bash\n\
+             PARSENTRY_CACHE_DIR={cache_base_q} {parsentry_bin_q} compare {target_q} > {comparison_q}\n\
+             test -s {comparison_q}\n\
+             ```\n\
+             Then read {comparison} and summarize where the models agree and differ.\n",
+            comparison = comparison.display(),
+        ));
+        return steps;
+    }
     let merged_sarif = output_dir.join("merged.sarif.json");
     let report_md = output_dir.join("report.md");
-    let cache_base_q = shell_quote(&cache_base.display().to_string());
     let merged_q = shell_quote(&merged_sarif.display().to_string());
     let report_q = shell_quote(&report_md.display().to_string());
-    prompt.push_str(&format!(
-        "\nAfter ALL workers complete, run exactly:\n\
+    if let Some(verify) = batch.verify {
+        let model = verify
+            .model
+            .as_ref()
+            .map(|m| format!(" --model {}", shell_quote(m)))
+            .unwrap_or_default();
+        steps.push_str(&format!(
+            "\n{after}, run exactly:\n\
+             ```bash\n\
+             PARSENTRY_CACHE_DIR={cache_base_q} {parsentry_bin_q} verify {target_q} --threshold {threshold}{quorum}{model}\n\
+             ```\n\
+             It lists one verification worker per high-confidence finding. Dispatch them in \
+             parallel exactly like the analysis workers and wait for all of them; do not \
+             verify findings yourself. If it lists none, continue.\n",
+            threshold = verify.threshold,
+        ));
+        after = "After ALL verification workers complete";
+    }
+    let summary_md = output_dir.join(EXECUTIVE_SUMMARY_FILE);
+    let opening = if batch.executive_summary {
+        format!(
+            "Then read {merged} once more and write a one-page executive summary (at most 500 \
+             words) to {summary} for readers who will not read the findings. Use only the \
+             findings in {merged}:\n\
+             - Risk themes: the few kinds of weakness that recur and what they put at risk\n\
+             - Hot spots: the files, directories or components with the most severe findings\n\
+             - Remediation plan: a prioritized list of fixes, most urgent first\n\
+             Verify it with `test -s {summary_q}`.\n\
+             Then write a security report to {report} that opens with the contents of \
+             {summary}, followed by:\n",
+            merged = merged_q,
+            summary = summary_md.display(),
+            summary_q = shell_quote(&summary_md.display().to_string()),
+            report = report_md.display(),
+        )
+    } else {
+        format!(
+            "Then read {merged} and write a security report to {report} with:\n\
+             - Executive summary (finding counts by severity)\n",
+            merged = merged_q,
+            report = report_md.display(),
+        )
+    };
+    steps.push_str(&format!(
+        "\n{after}, run exactly:\n\
          ```bash\n\
          tmp_merged=$(mktemp /tmp/parsentry-merged.XXXXXX.json)\n\
-         PARSENTRY_CACHE_DIR={cache_base} {parsentry_bin} merge {target} > \"$tmp_merged\"\n\
+         PARSENTRY_CACHE_DIR={cache_base} {parsentry_bin} merge {target}{quorum} > \"$tmp_merged\"\n\
          test -s \"$tmp_merged\"\n\
          mv \"$tmp_merged\" {merged}\n\
          ```\n\
-         Then read {merged} and write a security report to {report} with:\n\
-         - Executive summary (finding counts by severity)\n\
+         {opening}\
          - Per-finding details (rule ID, severity, confidence, location, description)\n\
          - Remediation recommendations\n\
          The task is not complete until `{report}` exists and is non-empty. \
@@ -279,15 +1509,14 @@ pub fn build_orchestrator_prompt(
         report = report_md.display(),
         report_q = report_q,
     ));
-
-    prompt
+    steps
 }
 
 fn shell_quote(input: &str) -> String {
     format!("'{}'", input.replace('\'', "'\"'\"'"))
 }
 
-fn hex_sha256(data: &str) -> String {
+pub(crate) fn hex_sha256(data: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(data.as_bytes());
     format!("{:x}", hasher.finalize())
@@ -309,6 +1538,217 @@ mod tests {
         }
     }
 
+    #[test]
+    fn requests_the_sarif_parsentry_writes() {
+        let temp = TempDir::new().unwrap();
+        let sp = build_surface_prompt(&make_surface("S-1", vec![]), temp.path()).unwrap();
+        assert!(sp.prompt.contains(SARIF_SCHEMA));
+        assert!(
+            sp.prompt
+                .contains(&format!("`version` set to `{SARIF_VERSION}`"))
+        );
+    }
+
+    #[test]
+    fn asks_for_response_language() {
+        let temp = TempDir::new().unwrap();
+        let base = build_surface_prompt(&make_surface("S-1", vec![]), temp.path()).unwrap();
+        let english = base.clone().with_response_language(ResponseLanguage::En);
+        assert_eq!(english.prompt, base.prompt);
+        assert_eq!(english.cache_key, base.cache_key);
+
+        let korean = base.clone().with_response_language(ResponseLanguage::Ko);
+        assert!(
+            korean
+                .prompt
+                .contains("all other prose in the SARIF in Korean (ko)")
+        );
+        assert_ne!(korean.cache_key, base.cache_key);
+    }
+
+    #[test]
+    fn adds_examples_within_budget() {
+        let temp = TempDir::new().unwrap();
+        let base = build_surface_prompt(&make_surface("S-1", vec!["app.py"]), temp.path()).unwrap();
+        let example = |name: &str, code: String| FewShotExample {
+            name: name.to_string(),
+            language: parsentry_core::Language::Python,
+            code,
+            expected: r#"{"version":"2.1.0","runs":[{"results":[]}]}"#.to_string(),
+            rule_ids: Vec::new(),
+        };
+        let small = example("safe.py", "cursor.execute(q, (name,))".to_string());
+        let huge = example("huge.py", "x = 1\n".repeat(DEFAULT_MAX_PROMPT_TOKENS));
+
+        let sp = base.clone().with_examples(&[&huge, &small]);
+        assert!(sp.prompt.contains("Examples"));
+        assert!(sp.prompt.contains("`safe.py`"));
+        assert!(!sp.prompt.contains("`huge.py`"));
+        assert_ne!(sp.cache_key, base.cache_key);
+        assert_eq!(base.clone().with_examples(&[]).cache_key, base.cache_key);
+    }
+
+    #[test]
+    fn lists_known_false_positives() {
+        let temp = TempDir::new().unwrap();
+        let base = build_surface_prompt(&make_surface("S-1", vec!["app.py"]), temp.path()).unwrap();
+        let exemplar = FalsePositive {
+            rule_id: "SQLI".to_string(),
+            language: Some("py".to_string()),
+            code: Some("cursor.execute(\"…\", (name,))".to_string()),
+            reason: Some("parameterized".to_string()),
+            fingerprint: None,
+        };
+
+        let sp = base.clone().with_false_positives(&[&exemplar]);
+        assert!(
+            sp.prompt
+                .contains("Known false-positive patterns in this repository")
+        );
+        assert!(sp.prompt.contains("- `SQLI`: parameterized\n"));
+        assert!(sp.prompt.contains("  ```py\n  cursor.execute("));
+        assert_ne!(sp.cache_key, base.cache_key);
+        assert_eq!(
+            base.clone().with_false_positives(&[]).cache_key,
+            base.cache_key
+        );
+    }
+
+    #[test]
+    fn lists_dependencies() {
+        let temp = TempDir::new().unwrap();
+        let base = build_surface_prompt(&make_surface("S-1", vec!["app.py"]), temp.path()).unwrap();
+        let jinja = Dependency {
+            name: "Jinja2".to_string(),
+            version: Some("==2.10".to_string()),
+            manifest: "requirements.txt".to_string(),
+            dev: false,
+        };
+
+        let sp = base.clone().with_dependencies(&[&jinja]);
+        assert!(
+            sp.prompt.contains(
+                "\nKnown Dependencies and Versions\n\n- requirements.txt: Jinja2 ==2.10\n"
+            )
+        );
+        assert_ne!(sp.cache_key, base.cache_key);
+        assert_eq!(
+            base.clone().with_dependencies(&[]).cache_key,
+            base.cache_key
+        );
+    }
+
+    #[test]
+    fn adds_framework_guidance() {
+        let temp = TempDir::new().unwrap();
+        let base = build_surface_prompt(&make_surface("S-1", vec!["app.py"]), temp.path()).unwrap();
+        let django = &crate::knowledge::PACKS[0];
+
+        let sp = base.clone().with_framework_guidance(&[django]);
+        assert!(sp.prompt.contains("\nFramework Guidance\n"));
+        assert!(sp.prompt.contains("### Django\n\n- `QuerySet.raw()`"));
+        assert_ne!(sp.cache_key, base.cache_key);
+        assert_eq!(
+            base.clone().with_framework_guidance(&[]).cache_key,
+            base.cache_key
+        );
+    }
+
+    #[test]
+    fn adds_config_guidance() {
+        let temp = TempDir::new().unwrap();
+        let base = build_surface_prompt(
+            &make_surface("S-1", vec![".github/workflows/ci.yml"]),
+            temp.path(),
+        )
+        .unwrap();
+
+        let sp = base
+            .clone()
+            .with_config_guidance(&[ConfigKind::GitHubActions]);
+        assert!(sp.prompt.contains("\nCI/CD and Deployment Configuration\n"));
+        assert!(sp.prompt.contains("### GitHub Actions workflow\n\n- `${{"));
+        assert_ne!(sp.cache_key, base.cache_key);
+        assert_eq!(
+            base.clone().with_config_guidance(&[]).cache_key,
+            base.cache_key
+        );
+    }
+
+    #[test]
+    fn lists_open_graphql_fields() {
+        let temp = TempDir::new().unwrap();
+        let base = build_surface_prompt(&make_surface("S-1", vec!["schema.graphql"]), temp.path())
+            .unwrap();
+        let fields = parsentry_core::graphql::root_fields(
+            "schema.graphql",
+            "type Query {\n  users: [User]\n  me: User\n}\ntype Mutation {\n  login(pw: String): Token\n}\n",
+        );
+        let fields: Vec<&RootField> = fields.iter().collect();
+
+        let sp = base.clone().with_graphql_schema(&fields);
+        assert!(sp.prompt.contains("\nGraphQL Schema\n"));
+        assert!(sp.prompt.contains(
+            "- `Query.users` at `schema.graphql:2`: list query without pagination arguments\n\
+             - `Mutation.login` at `schema.graphql:6`: mutation without an auth directive\n"
+        ));
+        assert!(!sp.prompt.contains("Query.me"));
+        assert_ne!(sp.cache_key, base.cache_key);
+        assert_eq!(
+            base.clone().with_graphql_schema(&fields[1..2]).cache_key,
+            base.cache_key
+        );
+    }
+
+    #[test]
+    fn lists_entry_points_of_the_surface() {
+        let temp = TempDir::new().unwrap();
+        let base = build_surface_prompt(&make_surface("S-1", vec!["app.py"]), temp.path()).unwrap();
+        let handler = EntryPoint {
+            kind: parsentry_parser::EntryKind::Route,
+            framework: "Flask/FastAPI",
+            name: "list_users".to_string(),
+            file: "app.py".to_string(),
+            line: 3,
+        };
+
+        let sp = base.clone().with_entry_points(&[&handler]);
+        assert!(sp.prompt.contains("\nEntry Points\n"));
+        assert!(
+            sp.prompt
+                .contains("- `list_users` (Flask/FastAPI HTTP route) at `app.py:3`\n")
+        );
+        assert_ne!(sp.cache_key, base.cache_key);
+        assert_eq!(
+            base.clone().with_entry_points(&[]).cache_key,
+            base.cache_key
+        );
+    }
+
+    #[test]
+    fn renders_prompt_templates() {
+        let temp = TempDir::new().unwrap();
+        let surface = make_surface("S-1", vec!["app.py"]);
+        let base = build_surface_prompt(&surface, temp.path()).unwrap();
+
+        let append = PromptTemplate::parse("Follow ACME rule 7 for {{ identifier }}.").unwrap();
+        let sp = base.clone().with_template(&append, &surface, temp.path());
+        assert!(sp.prompt.starts_with(&base.prompt));
+        assert!(sp.prompt.contains("Follow ACME rule 7 for GET /api/S-1."));
+        assert_ne!(sp.cache_key, base.cache_key);
+
+        let replace = PromptTemplate::parse("ACME audit of {{surface_id}}\n\n{{default}}").unwrap();
+        let sp = base.clone().with_template(&replace, &surface, temp.path());
+        assert!(
+            sp.prompt
+                .starts_with("ACME audit of S-1\n\nYou are a security auditor.")
+        );
+        assert!(sp.prompt.contains("Output valid SARIF"));
+
+        assert!(PromptTemplate::parse("{{secret}}").is_err());
+        assert!(PromptTemplate::parse("{{default").is_err());
+    }
+
     #[test]
     fn generates_prompt_even_without_files() {
         let temp = TempDir::new().unwrap();
@@ -354,6 +1794,68 @@ mod tests {
         assert!(!sp.prompt.contains("os.system(cmd)"));
     }
 
+    #[test]
+    fn resolve_source_files_reports_skipped_files_with_reason() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let src_dir = root.join("src");
+        fs::create_dir_all(&src_dir).unwrap();
+        fs::write(src_dir.join("app.py"), "print('hi')\n").unwrap();
+        fs::write(src_dir.join("bundle.js"), "x".repeat(60 * 1024)).unwrap();
+        fs::write(src_dir.join("vendor.min.js"), "var a=1;".repeat(200)).unwrap();
+        fs::write(src_dir.join("blob.py"), b"\x7fELF\x02\x01\x01\0").unwrap();
+
+        let surface = make_surface("S-1", vec!["src", "../etc/passwd", "POST /login"]);
+        let (sources, skipped) = resolve_source_files(&surface, root);
+
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].rel_path, "src/app.py");
+        let reasons: Vec<_> = skipped
+            .iter()
+            .map(|s| (s.path.as_str(), s.reason))
+            .collect();
+        assert!(reasons.contains(&("src/bundle.js", SkipReason::TooLarge)));
+        assert!(reasons.contains(&("../etc/passwd", SkipReason::OutsideRepository)));
+        assert!(reasons.contains(&("src/vendor.min.js", SkipReason::Minified)));
+        assert!(reasons.contains(&("src/blob.py", SkipReason::Binary)));
+        assert_eq!(skipped.len(), 4);
+    }
+
+    #[test]
+    fn resolve_source_files_reads_bundles_as_their_sources() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("dist")).unwrap();
+        fs::write(root.join("src/app.ts"), "export const a = 1;\n").unwrap();
+        fs::write(
+            root.join("dist/app.js"),
+            "exports.a = 1;\n//# sourceMappingURL=app.js.map\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("dist/app.js.map"),
+            r#"{"version":3,"sources":["../src/app.ts"]}"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("dist/api.js"),
+            "// Code generated by openapi-generator. DO NOT EDIT.\n",
+        )
+        .unwrap();
+
+        let surface = make_surface("S-1", vec!["dist"]);
+        let (sources, skipped) = resolve_source_files(&surface, root);
+
+        let paths: Vec<&str> = sources.iter().map(|s| s.rel_path.as_str()).collect();
+        assert_eq!(paths, vec!["src/app.ts"]);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(
+            (skipped[0].path.as_str(), skipped[0].reason),
+            ("dist/api.js", SkipReason::Generated)
+        );
+    }
+
     #[test]
     fn surface_prompt_mentions_repository_root_and_merge_compatible_sarif() {
         let temp = TempDir::new().unwrap();
@@ -377,6 +1879,8 @@ mod tests {
             surface_id: "SURFACE-001".to_string(),
             prompt: "irrelevant".to_string(),
             cache_key: "abc".to_string(),
+            model: None,
+            sample: None,
         }];
         let temp = TempDir::new().unwrap();
 
@@ -385,6 +1889,7 @@ mod tests {
             temp.path(),
             "/tmp/repo with spaces",
             Path::new("/tmp/bin/parsentry"),
+            &ScanProfile::Standard.settings(),
         );
 
         assert!(prompt.contains("subagent or agent capability"));
@@ -398,6 +1903,544 @@ mod tests {
         assert!(prompt.contains("test -s '"));
     }
 
+    #[test]
+    fn compare_models_assigns_workers_per_model() {
+        let temp = TempDir::new().unwrap();
+        let reports = temp.path().join("proj").join("reports");
+        let base = SurfacePrompt {
+            surface_id: "S-1".to_string(),
+            prompt: "analyze".to_string(),
+            cache_key: "abc".to_string(),
+            model: None,
+            sample: None,
+        };
+        let prompts = [
+            base.for_model("gpt-5.1-codex"),
+            base.for_model("claude-sonnet"),
+        ];
+        assert_ne!(prompts[0].cache_key, prompts[1].cache_key);
+        assert_eq!(
+            prompts[0].relative_dir(),
+            Path::new("S-1").join("models").join("gpt-5.1-codex")
+        );
+        assert!(
+            prompts[0]
+                .prompt
+                .contains("`properties.model`: `gpt-5.1-codex`")
+        );
+
+        let prompt = build_orchestrator_prompt(
+            &prompts,
+            &reports,
+            "repo",
+            Path::new("parsentry"),
+            &ScanProfile::Deep.settings(),
+        );
+        assert!(prompt.contains("Worker `S-1@claude-sonnet`"));
+        assert!(prompt.contains("Run it on model `claude-sonnet`."));
+        assert!(prompt.contains("compare 'repo' > "));
+        assert!(!prompt.contains(" merge "));
+    }
+
+    #[test]
+    fn samples_merge_with_quorum() {
+        let temp = TempDir::new().unwrap();
+        let reports = temp.path().join("proj").join("reports");
+        let base = SurfacePrompt {
+            surface_id: "S-1".to_string(),
+            prompt: "analyze".to_string(),
+            cache_key: "abc".to_string(),
+            model: None,
+            sample: None,
+        };
+        let prompts: Vec<_> = (1..=3).map(|n| base.for_sample(n, 3)).collect();
+        assert_ne!(prompts[0].cache_key, prompts[1].cache_key);
+        assert_eq!(
+            prompts[1].relative_dir(),
+            Path::new("S-1").join("models").join("sample-2")
+        );
+        assert_eq!(
+            base.for_model("gpt-5.1-codex")
+                .for_sample(1, 2)
+                .relative_dir(),
+            Path::new("S-1").join("models").join("gpt-5.1-codex-1")
+        );
+
+        let prompt = build_multi_target_orchestrator_prompt(
+            &[OrchestratorBatch {
+                target: "repo",
+                output_dir: &reports,
+                prompts: &prompts,
+                quorum: Some(2),
+                verify: None,
+                save_transcripts: false,
+                executive_summary: false,
+            }],
+            Path::new("parsentry"),
+            &ScanProfile::Standard.settings(),
+        );
+        assert!(prompt.contains("Worker `S-1/sample-3`"));
+        assert!(prompt.contains("independent samples"));
+        assert!(prompt.contains("merge 'repo' --quorum 2 > "));
+    }
+
+    #[test]
+    fn verification_runs_before_merge() {
+        let temp = TempDir::new().unwrap();
+        let reports = temp.path().join("proj").join("reports");
+        let prompts = [SurfacePrompt {
+            surface_id: "S-1".to_string(),
+            prompt: "analyze".to_string(),
+            cache_key: "abc".to_string(),
+            model: None,
+            sample: None,
+        }];
+        let verification = Verification {
+            threshold: 0.75,
+            model: Some("opus".to_string()),
+        };
+        let prompt = build_multi_target_orchestrator_prompt(
+            &[OrchestratorBatch {
+                target: "repo",
+                output_dir: &reports,
+                prompts: &prompts,
+                quorum: None,
+                verify: Some(&verification),
+                save_transcripts: true,
+                executive_summary: false,
+            }],
+            Path::new("parsentry"),
+            &ScanProfile::Standard.settings(),
+        );
+        let validate = prompt.find("validate 'repo'").unwrap();
+        let transcripts = prompt.find("transcripts 'repo'").unwrap();
+        let verify = prompt
+            .find("verify 'repo' --threshold 0.75 --model 'opus'")
+            .unwrap();
+        let merge = prompt.find("merge 'repo' > ").unwrap();
+        assert!(validate < transcripts && transcripts < verify && verify < merge);
+        assert!(prompt.contains("After ALL verification workers complete"));
+
+        let result = parsentry_reports::SarifReport::from_json(
+            r#"{"$schema":"","version":"2.1.0","runs":[{"tool":{"driver":{"name":"p","version":"0"}},"results":[{"ruleId":"SQLI","level":"error","message":{"text":"query built from input"},"locations":[{"physicalLocation":{"artifactLocation":{"uri":"app.py"},"region":{"startLine":12}}}],"properties":{"confidence":0.9,"data_flow":"request.args -> cursor.execute"}}]}]}"#,
+        )
+        .unwrap()
+        .runs
+        .remove(0)
+        .results
+        .remove(0);
+        let verdict = temp.path().join("verdict.json");
+        let prompt = build_verification_prompt(&result, "S-1", temp.path(), &verdict);
+        assert!(prompt.contains("- Location: app.py:12"));
+        assert!(prompt.contains("- Data flow: request.args -> cursor.execute"));
+        assert!(prompt.contains("query built from input"));
+        assert!(prompt.contains(&verdict.display().to_string()));
+    }
+
+    #[test]
+    fn executive_summary_opens_the_report() {
+        let temp = TempDir::new().unwrap();
+        let reports = temp.path().join("proj").join("reports");
+        let prompts = [SurfacePrompt {
+            surface_id: "S-1".to_string(),
+            prompt: "analyze".to_string(),
+            cache_key: "abc".to_string(),
+            model: None,
+            sample: None,
+        }];
+        let build = |executive_summary| {
+            build_multi_target_orchestrator_prompt(
+                &[OrchestratorBatch {
+                    target: "repo",
+                    output_dir: &reports,
+                    prompts: &prompts,
+                    quorum: None,
+                    verify: None,
+                    save_transcripts: false,
+                    executive_summary,
+                }],
+                Path::new("parsentry"),
+                &ScanProfile::Standard.settings(),
+            )
+        };
+        let summary = reports.join(EXECUTIVE_SUMMARY_FILE).display().to_string();
+        let prompt = build(true);
+        let merge = prompt.find("merge 'repo' > ").unwrap();
+        let written = prompt.find(&format!("to {summary} for readers")).unwrap();
+        let report = prompt.find("report.md that opens with").unwrap();
+        assert!(merge < written && written < report);
+        assert!(prompt.contains("Remediation plan: a prioritized list"));
+        assert!(!build(false).contains(EXECUTIVE_SUMMARY_FILE));
+    }
+
+    #[test]
+    fn multi_target_orchestrator_merges_each_target() {
+        let temp = TempDir::new().unwrap();
+        let dir_a = temp.path().join("a").join("reports");
+        let dir_b = temp.path().join("b").join("reports");
+        let sp = |id: &str| SurfacePrompt {
+            surface_id: id.to_string(),
+            prompt: String::new(),
+            cache_key: String::new(),
+            model: None,
+            sample: None,
+        };
+        let (a, b) = ([sp("SURFACE-A")], [sp("SURFACE-B")]);
+
+        let batches = [
+            OrchestratorBatch {
+                target: "svc-a",
+                output_dir: &dir_a,
+                prompts: &a,
+                quorum: None,
+                verify: None,
+                save_transcripts: false,
+                executive_summary: false,
+            },
+            OrchestratorBatch {
+                target: "svc-b",
+                output_dir: &dir_b,
+                prompts: &b,
+                quorum: None,
+                verify: None,
+                save_transcripts: false,
+                executive_summary: false,
+            },
+        ];
+        let prompt = build_multi_target_orchestrator_prompt(
+            &batches,
+            Path::new("parsentry"),
+            &ScanProfile::Standard.settings(),
+        );
+        let capped = build_multi_target_orchestrator_prompt(
+            &batches,
+            Path::new("parsentry"),
+            &ScanProfile::Quick.settings(),
+        );
+
+        assert!(prompt.contains("Target `svc-a`"));
+        assert!(!prompt.contains("shared fairly"));
+        assert!(
+            capped.contains("at most 8 workers at the same time, shared fairly between targets")
+        );
+        assert!(prompt.contains("Worker `svc-b#SURFACE-B`"));
+        assert!(prompt.contains("merge 'svc-a'"));
+        assert!(prompt.contains("merge 'svc-b'"));
+        assert!(prompt.contains(&dir_b.join("report.md").display().to_string()));
+    }
+
+    #[test]
+    fn fix_suggestions_extend_prompt_and_change_cache_key() {
+        let temp = TempDir::new().unwrap();
+        let surface = make_surface("S-1", vec!["src/app.py"]);
+        let sp = build_surface_prompt(&surface, temp.path()).unwrap();
+        let base_key = sp.cache_key.clone();
+
+        let fixes_dir = temp.path().join("S-1").join("fixes");
+        let sp = sp.with_fix_suggestions(&fixes_dir);
+
+        assert!(sp.prompt.contains("Suggested Fixes"));
+        assert!(sp.prompt.contains(&fixes_dir.display().to_string()));
+        assert!(sp.prompt.contains("git apply"));
+        assert_ne!(sp.cache_key, base_key);
+        assert_eq!(sp.cache_key.len(), 64);
+    }
+
+    #[test]
+    fn profiles_adjust_prompts_and_cache_key() {
+        let temp = TempDir::new().unwrap();
+        let surface = make_surface("S-1", vec!["src/app.py"]);
+        let sp = build_surface_prompt(&surface, temp.path()).unwrap();
+
+        let standard = sp
+            .clone()
+            .with_profile(ScanProfile::Standard, &ScanProfile::Standard.settings());
+        assert_eq!(standard.cache_key, sp.cache_key);
+        assert_eq!(standard.prompt, sp.prompt);
+
+        let deep_settings = ScanProfile::Deep.settings();
+        let deep = sp.clone().with_profile(ScanProfile::Deep, &deep_settings);
+        assert!(deep.prompt.contains("Analysis Depth (deep)"));
+        assert!(deep.prompt.contains("properties.poc"));
+        let sandboxed = deep.clone().with_poc_sandbox(Path::new("/opt/parsentry"));
+        assert!(sandboxed.prompt.contains("'/opt/parsentry' poc <script>"));
+        assert_ne!(sandboxed.cache_key, deep.cache_key);
+        assert_ne!(deep.cache_key, sp.cache_key);
+
+        let orchestrator = build_orchestrator_prompt(
+            &[deep],
+            temp.path().join("cache").join("reports").as_path(),
+            "t",
+            Path::new("parsentry"),
+            &deep_settings,
+        );
+        assert!(orchestrator.contains("6. Run at most 4 workers"));
+
+        let timed = ProfileSettings {
+            worker_timeout_mins: Some(15),
+            scan_timeout_mins: Some(60),
+            ..deep_settings
+        };
+        let orchestrator = build_orchestrator_prompt(
+            &[sp],
+            temp.path().join("cache").join("reports").as_path(),
+            "t",
+            Path::new("parsentry"),
+            &timed,
+        );
+        assert!(orchestrator.contains("7. Give each worker at most 15 minutes"));
+        assert!(orchestrator.contains("8. All workers together have 60 minutes"));
+        assert!(orchestrator.contains(&format!("`{TIMED_OUT_FILE}`")));
+        assert!(orchestrator.contains("terminate it together with every process it started"));
+    }
+
+    #[test]
+    fn model_fallbacks_reach_orchestrator_and_workers() {
+        let temp = TempDir::new().unwrap();
+        let surface = make_surface("S-1", vec!["src/app.py"]);
+        let sp = build_surface_prompt(&surface, temp.path()).unwrap();
+        let key = sp.cache_key.clone();
+        let sp = sp.with_model_tag();
+        assert!(
+            sp.prompt
+                .contains("`properties.model`: the name of the model you run on")
+        );
+        assert_eq!(sp.cache_key, key);
+
+        let chain = ["o3".to_string(), "o4-mini".to_string()];
+        let orchestrator = build_orchestrator_prompt(
+            &[sp],
+            temp.path().join("cache").join("reports").as_path(),
+            "t",
+            Path::new("parsentry"),
+            &ScanProfile::Standard.settings().with_model_chain(&chain),
+        );
+        assert!(
+            orchestrator
+                .contains("6. If your environment lets you choose the worker model, use `o3`.")
+        );
+        assert!(orchestrator.contains("7. If a worker fails twice on `o3`"));
+        assert!(orchestrator.contains("the next model of: `o4-mini`."));
+    }
+
+    #[test]
+    fn tool_restrictions_reach_workers_and_orchestrator() {
+        let temp = TempDir::new().unwrap();
+        let surface = make_surface("S-1", vec!["src/app.py"]);
+        let sp = build_surface_prompt(&surface, temp.path()).unwrap();
+        let tools = WorkerTools {
+            read_only: true,
+            shell: false,
+        };
+        assert_eq!(
+            sp.clone()
+                .with_tool_restrictions(WorkerTools::default())
+                .prompt,
+            sp.prompt
+        );
+        let restricted = sp.with_tool_restrictions(tools);
+        assert!(
+            restricted
+                .prompt
+                .contains("- You must not run shell commands.")
+        );
+
+        let orchestrator = build_orchestrator_prompt(
+            &[restricted],
+            temp.path().join("cache").join("reports").as_path(),
+            "t",
+            Path::new("parsentry"),
+            &ScanProfile::Standard.settings_with(tools),
+        );
+        assert!(
+            orchestrator.contains(
+                "6. Restrict every worker: it must not create, modify or delete any file"
+            )
+        );
+        assert!(orchestrator.contains("and must not run shell commands."));
+    }
+
+    #[test]
+    fn related_functions_extend_prompt_and_cache_key() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::write(
+            root.join("views.py"),
+            "def login(request):\n    return find_user(request.args['name'])\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("db.py"),
+            "def find_user(name):\n    return db.execute('SELECT ' + name)\n",
+        )
+        .unwrap();
+        let graph =
+            parsentry_parser::CallGraph::build(root, &[root.join("views.py"), root.join("db.py")]);
+
+        let sp = build_surface_prompt(&make_surface("S-1", vec!["views.py"]), root).unwrap();
+        let unchanged = sp.clone().with_related_functions(&[]);
+        assert_eq!(unchanged.cache_key, sp.cache_key);
+
+        let related = graph.related(&["views.py".to_string()], MAX_RELATED_FUNCTIONS);
+        let enriched = sp.clone().with_related_functions(&related);
+        assert_ne!(enriched.cache_key, sp.cache_key);
+        assert!(enriched.prompt.contains("Related Functions"));
+        assert!(
+            enriched
+                .prompt
+                .contains("- `find_user` (callee) — `db.py:1`")
+        );
+        assert!(enriched.prompt.contains("db.execute('SELECT ' + name)"));
+    }
+
+    #[test]
+    fn source_upload_keeps_code_out_of_prompts() {
+        let temp = TempDir::new().unwrap();
+        let sp = build_surface_prompt(&make_surface("S-1", vec!["views.py"]), temp.path()).unwrap();
+        assert_eq!(
+            sp.clone().with_source_upload(SourceUpload::Full).cache_key,
+            sp.cache_key
+        );
+
+        let code = "return db.execute('SELECT ' + name)";
+        let related = |mode: SourceUpload| {
+            vec![RelatedFunction {
+                name: "find_user".to_string(),
+                file: "db.py".to_string(),
+                line: 1,
+                relation: parsentry_parser::Relation::Callee,
+                snippet: mode.quote(code).unwrap_or_default(),
+            }]
+        };
+        let paths = sp
+            .clone()
+            .with_related_functions(&related(SourceUpload::Paths))
+            .with_source_upload(SourceUpload::Paths);
+        assert!(
+            paths
+                .prompt
+                .contains("- `find_user` (callee) — `db.py:1`\n")
+        );
+        assert!(!paths.prompt.contains("```"));
+        assert!(paths.prompt.contains("Source Code Residency"));
+        assert_ne!(paths.cache_key, sp.cache_key);
+
+        let hashed = sp
+            .clone()
+            .with_related_functions(&related(SourceUpload::Hashed))
+            .with_source_upload(SourceUpload::Hashed);
+        assert!(!hashed.prompt.contains(code));
+        assert!(hashed.prompt.contains("```\nsha256:"));
+        assert!(hashed.prompt.contains("must not be read"));
+    }
+
+    #[test]
+    fn related_functions_shrink_to_the_prompt_budget() {
+        let related: Vec<RelatedFunction> = (0..3)
+            .map(|i| RelatedFunction {
+                name: format!("helper_{i}"),
+                file: "db.py".to_string(),
+                line: i * 20 + 1,
+                relation: parsentry_parser::Relation::Callee,
+                snippet: (0..15).map(|l| format!("    line_{l} = {l}\n")).collect(),
+            })
+            .collect();
+        let full = render_related_functions(&related, usize::MAX);
+        assert!(full.contains("line_14"));
+
+        let budget = estimate_tokens(&full) - 1;
+        let head = render_related_functions(&related, budget);
+        assert!(head.contains("line_2 = 2") && !head.contains("line_3"));
+        assert_eq!(render_related_functions(&related, budget), head);
+
+        let listed = related_functions_section(&related, None);
+        assert_eq!(
+            render_related_functions(&related, estimate_tokens(&listed)),
+            listed
+        );
+        let fewer = render_related_functions(&related, estimate_tokens(&listed) - 1);
+        assert!(fewer.contains("helper_1") && !fewer.contains("helper_2"));
+        assert!(render_related_functions(&related, 10).is_empty());
+    }
+
+    #[test]
+    fn reachable_sinks_extend_prompt_and_cache_key() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let files = [root.join("views.py"), root.join("db.py")];
+        fs::write(
+            &files[0],
+            "def login(request):\n    return find_user(request.args['name'])\n",
+        )
+        .unwrap();
+        fs::write(
+            &files[1],
+            "def find_user(name):\n    return cursor.execute('SELECT ' + name)\n",
+        )
+        .unwrap();
+        let graph = parsentry_parser::CallGraph::build(root, &files);
+        let sinks = parsentry_parser::reachable_sinks(root, &files, &graph);
+
+        let sp = build_surface_prompt(&make_surface("S-1", vec!["db.py"]), root).unwrap();
+        assert_eq!(sp.clone().with_reachable_sinks(&[]).cache_key, sp.cache_key);
+        let enriched = sp.clone().with_reachable_sinks(&sinks);
+        assert_ne!(enriched.cache_key, sp.cache_key);
+        assert!(enriched.prompt.contains("Reachable Sinks"));
+        assert!(enriched.prompt.contains(
+            "(SQL execution resource) at `db.py:2`, from HTTP request handlers at `views.py:1`"
+        ));
+        assert!(
+            enriched
+                .prompt
+                .contains("path: `login` (`views.py:1`) → `find_user` (`db.py:1`)")
+        );
+    }
+
+    #[test]
+    fn scope_drops_excluded_surfaces_and_extends_prompt() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("vendor")).unwrap();
+        fs::write(root.join("vendor/lib.py"), "eval(x)\n").unwrap();
+        fs::write(root.join("app.py"), "eval(x)\n").unwrap();
+        let filter = PathFilter::new(&[], &["vendor/**".to_string()]).unwrap();
+
+        assert!(!surface_in_scope(
+            &make_surface("S-1", vec!["vendor/lib.py"]),
+            root,
+            &filter
+        ));
+        assert!(!surface_in_scope(
+            &make_surface("S-2", vec!["vendor"]),
+            root,
+            &filter
+        ));
+        assert!(surface_in_scope(
+            &make_surface("S-3", vec!["vendor/lib.py", "app.py"]),
+            root,
+            &filter
+        ));
+        assert!(surface_in_scope(
+            &make_surface("S-4", vec!["GET /login"]),
+            root,
+            &filter
+        ));
+
+        let listed = filter.clone().with_files(["app.py"]);
+        assert!(!surface_in_scope(
+            &make_surface("S-5", vec!["vendor/lib.py"]),
+            root,
+            &listed
+        ));
+        assert!(scope_section(&listed).contains("- Only consider these files:\n  - `app.py`"));
+
+        let sp = build_surface_prompt(&make_surface("S-3", vec!["app.py"]), root).unwrap();
+        let key = sp.cache_key.clone();
+        let sp = sp.with_scope(&filter);
+        assert!(sp.prompt.contains("Ignore paths matching `vendor/**`"));
+        assert_ne!(sp.cache_key, key);
+    }
+
     #[test]
     fn generates_prompt_with_metadata_cache_key_for_large_files() {
         let temp = TempDir::new().unwrap();
@@ -1,10 +1,13 @@
 use std::{
+    collections::HashMap,
     fs::File,
     io::{BufRead, BufReader, Result as IoResult},
     path::{Path, PathBuf},
+    process::Command,
 };
 
-use parsentry_core::FileDiscovery;
+use parsentry_core::{FileDiscovery, PathFilter};
+use parsentry_reports::blame::{Blame, parse_porcelain};
 
 #[derive(Default)]
 pub struct LanguageExclusions {
@@ -34,6 +37,12 @@ impl RepoOps {
         }
     }
 
+    /// Restrict discovery to paths allowed by `filter`.
+    pub fn with_filter(mut self, filter: PathFilter) -> Self {
+        self.file_discovery = self.file_discovery.with_filter(filter);
+        self
+    }
+
     pub fn repo_path(&self) -> &Path {
         self.file_discovery.root_path()
     }
@@ -127,4 +136,97 @@ impl RepoOps {
         let path_to_analyze = analyze_path.unwrap_or_else(|| self.repo_path().to_path_buf());
         self.file_discovery.get_files_in_path(&path_to_analyze)
     }
+
+    /// Verify that a unified diff applies cleanly to the working tree.
+    pub fn check_patch(&self, patch_path: &Path) -> anyhow::Result<()> {
+        self.git_apply(patch_path, true)
+    }
+
+    /// Apply a unified diff to the working tree.
+    ///
+    /// The patch is checked first so that a failing patch never leaves the
+    /// tree partially modified.
+    pub fn apply_patch(&self, patch_path: &Path) -> anyhow::Result<()> {
+        self.git_apply(patch_path, true)?;
+        self.git_apply(patch_path, false)
+    }
+
+    /// Unified diff of the working tree against `base`.
+    pub fn diff(&self, base: &str) -> anyhow::Result<String> {
+        // Reject flag-like values to prevent git argument injection
+        if base.starts_with('-') {
+            anyhow::bail!("Invalid diff base ref: must not start with '-'");
+        }
+        let output = Command::new("git")
+            .args(["diff", "--no-color", "--no-ext-diff", base, "--"])
+            .current_dir(self.repo_path())
+            .output()
+            .map_err(|e| anyhow::anyhow!("git diff failed: {}", e))?;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "git diff {} failed: {}",
+                base,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Blame of `lines` of the repository-relative file `path` at `HEAD`.
+    pub fn blame(&self, path: &str, lines: &[usize]) -> anyhow::Result<HashMap<usize, Blame>> {
+        if path.starts_with('-') || lines.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let mut args = vec![
+            "blame".to_string(),
+            "--porcelain".to_string(),
+            "--root".to_string(),
+        ];
+        args.extend(lines.iter().map(|line| format!("-L{line},{line}")));
+        args.extend(["HEAD".to_string(), "--".to_string(), path.to_string()]);
+        let output = Command::new("git")
+            .args(&args)
+            .current_dir(self.repo_path())
+            .output()
+            .map_err(|e| anyhow::anyhow!("git blame failed: {}", e))?;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "git blame {} failed: {}",
+                path,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(parse_porcelain(
+            &String::from_utf8_lossy(&output.stdout),
+            chrono::Utc::now(),
+        ))
+    }
+
+    fn git_apply(&self, patch_path: &Path, check_only: bool) -> anyhow::Result<()> {
+        let patch_path = patch_path
+            .canonicalize()
+            .map_err(|e| anyhow::anyhow!("cannot read patch {}: {}", patch_path.display(), e))?;
+
+        let mut cmd = Command::new("git");
+        cmd.arg("apply");
+        if check_only {
+            cmd.arg("--check");
+        }
+        let output = cmd
+            .arg(&patch_path)
+            .current_dir(self.repo_path())
+            .output()
+            .map_err(|e| anyhow::anyhow!("git apply failed: {}", e))?;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "patch {} does not apply: {}",
+                patch_path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,109 @@
+//! Data-residency controls (`scan --no-source-upload`).
+//!
+//! By default surface prompts quote code from the repository: excerpts of
+//! related functions, reachable sinks and known false positives. Without
+//! source upload, prompts carry only paths, symbol names and structure:
+//!
+//! - `paths`: quoted code is left out; workers read the files themselves,
+//!   for agents whose model runs where the source may go.
+//! - `hashed`: quoted code is replaced by a hash of it and workers must not
+//!   read the repository, so no source reaches the model at all. Findings
+//!   rest on names and structure alone, and reports flag the scan's reduced
+//!   precision.
+
+use clap::ValueEnum;
+
+use parsentry_reports::manifest::HASHED_SOURCE_UPLOAD;
+
+use crate::prompt::hex_sha256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum SourceUpload {
+    #[default]
+    #[value(skip)]
+    Full,
+    /// Paths, symbols and structure only; workers read files locally
+    Paths,
+    /// Code is replaced by its hash and workers do not read files
+    Hashed,
+}
+
+impl SourceUpload {
+    /// Name recorded in the scan manifest (`source_upload`).
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::Paths => "paths",
+            Self::Hashed => HASHED_SOURCE_UPLOAD,
+        }
+    }
+
+    /// Mode selected by `PARSENTRY_NO_SOURCE_UPLOAD`: `paths` (or `1`,
+    /// `true`) or `hashed`.
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var("PARSENTRY_NO_SOURCE_UPLOAD").ok()?;
+        match value.trim().to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" => Some(Self::Paths),
+            value => Self::from_str(value, true).ok(),
+        }
+    }
+
+    /// Whether findings are made without reading the code.
+    pub fn reduced_precision(self) -> bool {
+        self == Self::Hashed
+    }
+
+    /// `code` as it may be quoted in a prompt: unchanged, left out, or
+    /// replaced by the first 16 hex digits of its SHA-256.
+    pub fn quote(self, code: &str) -> Option<String> {
+        match self {
+            Self::Full => Some(code.to_string()),
+            Self::Paths => None,
+            Self::Hashed => Some(format!("sha256:{}", &hex_sha256(code.trim())[..16])),
+        }
+    }
+
+    /// Prompt section telling the worker what it may read; `None` when
+    /// source upload is allowed.
+    pub fn instruction(self) -> Option<String> {
+        match self {
+            Self::Full => None,
+            Self::Paths => Some(
+                "\nSource Code Residency\n\n\
+                 Source code is not quoted in this prompt. Read the files at the listed \
+                 locations from the Repository Root when you need their code.\n"
+                    .to_string(),
+            ),
+            Self::Hashed => Some(
+                "\nSource Code Residency\n\n\
+                 The source code of this repository must not be read. Do not open, read, search \
+                 or run the repository's files; analyze the surface from the paths, symbol \
+                 names, call paths and structure given here. Quoted code is replaced by \
+                 `sha256:` hashes of its text, which only identify it.\n\
+                 - Report a finding only when names and structure make it likely, with \
+                 `properties.confidence` at most 0.5.\n\
+                 - Leave `region.snippet` out and state in `message.text` that the finding was \
+                 made without reading the code.\n"
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_code_by_mode() {
+        let code = "cursor.execute(query)\n";
+        assert_eq!(SourceUpload::Full.quote(code).as_deref(), Some(code));
+        assert_eq!(SourceUpload::Paths.quote(code), None);
+        let hashed = SourceUpload::Hashed.quote(code).unwrap();
+        assert_eq!(hashed.len(), "sha256:".len() + 16);
+        assert_eq!(SourceUpload::Hashed.quote(code.trim()), Some(hashed));
+        assert!(SourceUpload::Full.instruction().is_none());
+        assert!(SourceUpload::Hashed.reduced_precision());
+        assert!(!SourceUpload::Paths.reduced_precision());
+    }
+}
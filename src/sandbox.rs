@@ -0,0 +1,332 @@
+//! Sandboxed proof-of-concept execution (`parsentry poc`).
+//!
+//! A worker that wants to demonstrate a finding writes its PoC as a script
+//! and runs it through `parsentry poc` instead of executing it directly. The
+//! script is copied into an empty temporary directory and run there with no
+//! network, a read-only view of the system directories (bubblewrap) or a
+//! locked-down container (podman/docker), CPU, memory and file size limits,
+//! and a wall-clock timeout. Without any of these runtimes the PoC is not run.
+
+use anyhow::{Context, Result, bail};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use parsentry_reports::sarif::PocRun;
+
+/// Bytes of stdout/stderr kept per run.
+const MAX_OUTPUT: usize = 64 * 1024;
+/// Memory available to a PoC (address space or container memory).
+const MEMORY_LIMIT: u64 = 2 * 1024 * 1024 * 1024;
+/// Largest file a PoC may write.
+const FILE_SIZE_LIMIT: u64 = 64 * 1024 * 1024;
+/// Container image for `podman`/`docker` when `PARSENTRY_POC_IMAGE` is unset.
+const DEFAULT_IMAGE: &str = "python:3-slim";
+
+/// Directory the script runs in, inside the sandbox.
+const WORKDIR: &str = "/poc";
+
+/// Isolation used to run a PoC.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sandbox {
+    /// bubblewrap: fresh namespaces (including network) on Linux.
+    Bubblewrap,
+    /// A container runtime (`podman` or `docker`) with networking disabled.
+    Container { runtime: String, image: String },
+}
+
+/// Whether `program arg` runs successfully, e.g. `docker info` to see that
+/// the daemon is reachable and not just the client installed.
+fn available(program: &str, arg: &str) -> bool {
+    Command::new(program)
+        .arg(arg)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+impl Sandbox {
+    /// The sandbox named by `PARSENTRY_SANDBOX`, else the first available of
+    /// bubblewrap (Linux only), podman and docker.
+    pub fn detect() -> Result<Self> {
+        let image = std::env::var("PARSENTRY_POC_IMAGE")
+            .ok()
+            .filter(|i| !i.is_empty())
+            .unwrap_or_else(|| DEFAULT_IMAGE.to_string());
+        let container = |runtime: &str| Self::Container {
+            runtime: runtime.to_string(),
+            image: image.clone(),
+        };
+        match std::env::var("PARSENTRY_SANDBOX").ok().as_deref() {
+            Some("bwrap") if cfg!(target_os = "linux") => return Ok(Self::Bubblewrap),
+            Some(runtime @ ("podman" | "docker")) => return Ok(container(runtime)),
+            Some(other) if !other.is_empty() => {
+                bail!("PARSENTRY_SANDBOX must be bwrap (Linux), podman or docker, got: {other}")
+            }
+            _ => {}
+        }
+        if cfg!(target_os = "linux") && available("bwrap", "--version") {
+            return Ok(Self::Bubblewrap);
+        }
+        for runtime in ["podman", "docker"] {
+            if available(runtime, "info") {
+                return Ok(container(runtime));
+            }
+        }
+        bail!(
+            "no sandbox available to run the PoC; install bubblewrap (Linux), podman or docker. \
+             Refusing to run it unsandboxed"
+        )
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Bubblewrap => "bwrap",
+            Self::Container { runtime, .. } => runtime,
+        }
+    }
+
+    /// Command running `./<script>` in `workdir` inside the sandbox.
+    fn command(&self, workdir: &Path, script: &str, timeout: Duration, name: &str) -> Command {
+        let workdir = workdir.display().to_string();
+        match self {
+            Self::Bubblewrap => {
+                let mut command = Command::new("bwrap");
+                command.args(["--unshare-all", "--die-with-parent", "--new-session"]);
+                for dir in ["/usr", "/bin", "/sbin", "/lib", "/lib64", "/etc"] {
+                    command.args(["--ro-bind-try", dir, dir]);
+                }
+                command
+                    .args(["--proc", "/proc", "--dev", "/dev", "--tmpfs", "/tmp"])
+                    .args(["--bind", &workdir, WORKDIR, "--chdir", WORKDIR])
+                    .args([
+                        "--clearenv",
+                        "--setenv",
+                        "PATH",
+                        "/usr/local/bin:/usr/bin:/bin",
+                    ])
+                    .args(["--setenv", "HOME", WORKDIR, "--", &format!("./{script}")]);
+                limit_resources(&mut command, timeout);
+                command
+            }
+            Self::Container { runtime, image } => {
+                let mut command = Command::new(runtime);
+                command
+                    .args(["run", "--rm", "--name", name, "--network", "none"])
+                    .args(["--memory", &MEMORY_LIMIT.to_string(), "--pids-limit", "128"])
+                    .args(["--cpus", "1", "--read-only", "--tmpfs", "/tmp"])
+                    .args(["--cap-drop", "ALL", "--security-opt", "no-new-privileges"])
+                    .args(["--ulimit", &format!("fsize={FILE_SIZE_LIMIT}")])
+                    .args(["-v", &format!("{workdir}:{WORKDIR}"), "-w", WORKDIR])
+                    .args([image.as_str(), &format!("./{script}")]);
+                // The runtime client writes the container's output to the
+                // capture files, so cap what it may write like under bwrap
+                limit_file_size(&mut command);
+                command
+            }
+        }
+    }
+
+    /// Stop container `name`, if this sandbox runs one.
+    fn kill_container(&self, name: &str) {
+        if let Self::Container { runtime, .. } = self {
+            let _ = Command::new(runtime)
+                .args(["kill", name])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+        }
+    }
+
+    /// Run `script` in the sandbox, killing it after `timeout`.
+    pub fn run(&self, script: &Path, timeout: Duration) -> Result<PocRun> {
+        let name = script
+            .file_name()
+            .and_then(|n| n.to_str())
+            .filter(|n| !n.starts_with('-'))
+            .with_context(|| format!("invalid PoC script name: {}", script.display()))?
+            .to_string();
+        let workdir = tempfile::Builder::new()
+            .prefix("parsentry-poc-")
+            .tempdir()?;
+        let output = tempfile::tempdir()?;
+        let copy = workdir.path().join(&name);
+        std::fs::copy(script, &copy)
+            .with_context(|| format!("cannot read PoC script {}", script.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&copy, std::fs::Permissions::from_mode(0o755))?;
+            // Containers may run as another user than the directory's owner
+            std::fs::set_permissions(workdir.path(), std::fs::Permissions::from_mode(0o777))?;
+        }
+
+        let container = format!(
+            "parsentry-poc-{}",
+            workdir
+                .path()
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .trim_start_matches("parsentry-poc-")
+                .to_ascii_lowercase()
+        );
+        let stdout_path = output.path().join("stdout");
+        let stderr_path = output.path().join("stderr");
+        let mut command = self.command(workdir.path(), &name, timeout, &container);
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(std::fs::File::create(&stdout_path)?)
+            .stderr(std::fs::File::create(&stderr_path)?)
+            .spawn()
+            .with_context(|| format!("cannot start {}", self.name()))?;
+
+        let deadline = Instant::now() + timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break Some(status);
+            }
+            if Instant::now() >= deadline {
+                self.kill_container(&container);
+                kill(&mut child);
+                break None;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        };
+        if status.is_some_and(|s| s.code().is_none()) {
+            // The client died on a signal (e.g. SIGXFSZ at the output limit)
+            // and may have left the container running
+            self.kill_container(&container);
+        }
+
+        Ok(PocRun {
+            sandbox: self.name().to_string(),
+            exit_code: status.and_then(|s| s.code()),
+            timed_out: status.is_none(),
+            stdout: read_capped(&stdout_path),
+            stderr: read_capped(&stderr_path),
+        })
+    }
+}
+
+/// Kill the sandbox process and everything in its process group.
+fn kill(child: &mut Child) {
+    #[cfg(unix)]
+    // SAFETY: the child leads its own process group (see `Sandbox::run`)
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Cap the CPU time, memory, file sizes and open files of the sandbox.
+#[cfg(unix)]
+fn limit_resources(command: &mut Command, timeout: Duration) {
+    use std::os::unix::process::CommandExt;
+    let cpu = timeout.as_secs() + 1;
+    // SAFETY: setrlimit is async-signal-safe and touches only the child
+    unsafe {
+        command.pre_exec(move || {
+            let limits = [
+                (libc::RLIMIT_CPU, cpu),
+                (libc::RLIMIT_AS, MEMORY_LIMIT),
+                (libc::RLIMIT_FSIZE, FILE_SIZE_LIMIT),
+                (libc::RLIMIT_NOFILE, 256),
+            ];
+            for (resource, value) in limits {
+                let limit = libc::rlimit {
+                    rlim_cur: value as libc::rlim_t,
+                    rlim_max: value as libc::rlim_t,
+                };
+                if libc::setrlimit(resource, &limit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+fn limit_resources(_command: &mut Command, _timeout: Duration) {}
+
+/// Cap only the file sizes of a container runtime client; its memory and
+/// CPU are limited on the container itself.
+#[cfg(unix)]
+fn limit_file_size(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+    // SAFETY: setrlimit is async-signal-safe and touches only the child
+    unsafe {
+        command.pre_exec(|| {
+            let limit = libc::rlimit {
+                rlim_cur: FILE_SIZE_LIMIT as libc::rlim_t,
+                rlim_max: FILE_SIZE_LIMIT as libc::rlim_t,
+            };
+            if libc::setrlimit(libc::RLIMIT_FSIZE, &limit) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+fn limit_file_size(_command: &mut Command) {}
+
+/// Up to [`MAX_OUTPUT`] bytes of a captured stream.
+fn read_capped(path: &Path) -> String {
+    use std::io::Read;
+    let mut bytes = Vec::new();
+    if let Ok(file) = std::fs::File::open(path) {
+        // One byte past the cap tells whether anything was cut off
+        let _ = file.take(MAX_OUTPUT as u64 + 1).read_to_end(&mut bytes);
+    }
+    let mut text = String::from_utf8_lossy(&bytes[..bytes.len().min(MAX_OUTPUT)]).into_owned();
+    if bytes.len() > MAX_OUTPUT {
+        text.push_str("\n[output truncated]");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_unknown_sandbox() {
+        // SAFETY: no other test reads PARSENTRY_SANDBOX
+        unsafe { std::env::set_var("PARSENTRY_SANDBOX", "chroot") };
+        let err = Sandbox::detect().unwrap_err();
+        unsafe { std::env::remove_var("PARSENTRY_SANDBOX") };
+        assert!(err.to_string().contains("PARSENTRY_SANDBOX"));
+    }
+
+    #[test]
+    fn caps_captured_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out");
+        std::fs::write(&path, vec![b'a'; MAX_OUTPUT + 10]).unwrap();
+        let text = read_capped(&path);
+        assert!(text.ends_with("[output truncated]"));
+        assert_eq!(text.len(), MAX_OUTPUT + "\n[output truncated]".len());
+    }
+
+    #[test]
+    fn limits_container_file_sizes() {
+        let sandbox = Sandbox::Container {
+            runtime: "podman".to_string(),
+            image: DEFAULT_IMAGE.to_string(),
+        };
+        let command = sandbox.command(Path::new("/tmp/w"), "poc.sh", Duration::from_secs(5), "c");
+        let args: Vec<_> = command.get_args().map(|a| a.to_string_lossy()).collect();
+        assert!(
+            args.windows(2)
+                .any(|w| w[0] == "--ulimit" && w[1] == format!("fsize={FILE_SIZE_LIMIT}")),
+            "{args:?}"
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! Tracing setup shared by all commands.
+//!
+//! Log records from `tracing` and `log` go to stderr as human-readable text
+//! or JSON (`--log-format json`); verbosity follows `PARSENTRY_LOG`
+//! (`EnvFilter` syntax, default `warn` for text and `info` for JSON).
+//!
+//! With the `otel` feature, spans are additionally exported over OTLP/HTTP
+//! when `OTEL_EXPORTER_OTLP_ENDPOINT` is set.
+
+use anyhow::Result;
+use clap::ValueEnum;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable status lines
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// Flushes pending spans when dropped.
+pub struct TelemetryGuard {
+    #[cfg(feature = "otel")]
+    provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otel")]
+        if let Some(provider) = self.provider.take() {
+            let _ = provider.shutdown();
+        }
+    }
+}
+
+/// Install the global subscriber. Call once, before any command runs.
+pub fn init(format: LogFormat) -> Result<TelemetryGuard> {
+    let default_level = match format {
+        LogFormat::Text => "warn",
+        LogFormat::Json => "info",
+    };
+    let filter =
+        EnvFilter::try_from_env("PARSENTRY_LOG").unwrap_or_else(|_| EnvFilter::new(default_level));
+
+    let (text, json) = match format {
+        LogFormat::Text => (
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(std::io::stderr)
+                    .with_target(false),
+            ),
+            None,
+        ),
+        LogFormat::Json => (
+            None,
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_current_span(true)
+                    .with_span_list(false)
+                    .with_writer(std::io::stderr),
+            ),
+        ),
+    };
+
+    #[cfg(feature = "otel")]
+    let (otel_layer, provider) = match otlp_provider()? {
+        Some(provider) => {
+            use opentelemetry::trace::TracerProvider;
+            let tracer = provider.tracer("parsentry");
+            (
+                Some(tracing_opentelemetry::layer().with_tracer(tracer)),
+                Some(provider),
+            )
+        }
+        None => (None, None),
+    };
+
+    let registry = tracing_subscriber::registry()
+        .with(filter)
+        .with(text)
+        .with(json);
+    #[cfg(feature = "otel")]
+    let registry = registry.with(otel_layer);
+    registry.try_init()?;
+
+    crate::cli::ui::set_structured_output(format == LogFormat::Json);
+
+    Ok(TelemetryGuard {
+        #[cfg(feature = "otel")]
+        provider,
+    })
+}
+
+#[cfg(feature = "otel")]
+fn otlp_provider() -> Result<Option<opentelemetry_sdk::trace::SdkTracerProvider>> {
+    if std::env::var_os("OTEL_EXPORTER_OTLP_ENDPOINT").is_none() {
+        return Ok(None);
+    }
+    parsentry_core::offline::ensure_online("export traces to OTEL_EXPORTER_OTLP_ENDPOINT")?;
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .build()?;
+    let resource = opentelemetry_sdk::Resource::builder()
+        .with_service_name("parsentry")
+        .build();
+    Ok(Some(
+        opentelemetry_sdk::trace::SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(resource)
+            .build(),
+    ))
+}
@@ -2,6 +2,8 @@ use anyhow::Result;
 use regex::Regex;
 use std::path::{Path, PathBuf};
 
+use parsentry_core::offline::ensure_online;
+
 /// Maximum number of assets to collect from a single URL.
 const MAX_ASSET_COUNT: usize = 50;
 
@@ -23,7 +25,8 @@ pub struct CollectedAsset {
 
 impl UrlAssetCollector {
     pub fn new(base_url: &str) -> Result<Self> {
-        let client = reqwest::Client::builder()
+        ensure_online(&format!("fetch assets from {base_url}"))?;
+        let client = parsentry_core::http::client_builder()?
             .user_agent("parsentry/0.1 (security scanner)")
             .build()?;
         Ok(Self {
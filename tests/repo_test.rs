@@ -63,3 +63,51 @@ fn test_matches_gitignore_nested_directory() {
         "node_modules"
     ));
 }
+
+const FIX_PATCH: &str = "\
+--- a/app.py
++++ b/app.py
+@@ -1 +1 @@
+-os.system(cmd)
++subprocess.run([cmd], check=True)
+";
+
+#[test]
+fn test_check_patch_accepts_clean_patch() -> anyhow::Result<()> {
+    let dir = tempdir()?;
+    std::fs::write(dir.path().join("app.py"), "os.system(cmd)\n")?;
+    let patch = dir.path().join("fix.patch");
+    std::fs::write(&patch, FIX_PATCH)?;
+
+    let repo = RepoOps::new(dir.path().to_path_buf());
+    repo.check_patch(&patch)?;
+    // --check must not touch the working tree
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("app.py"))?,
+        "os.system(cmd)\n"
+    );
+
+    repo.apply_patch(&patch)?;
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("app.py"))?,
+        "subprocess.run([cmd], check=True)\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_check_patch_rejects_stale_patch() -> anyhow::Result<()> {
+    let dir = tempdir()?;
+    std::fs::write(dir.path().join("app.py"), "print('changed')\n")?;
+    let patch = dir.path().join("fix.patch");
+    std::fs::write(&patch, FIX_PATCH)?;
+
+    let repo = RepoOps::new(dir.path().to_path_buf());
+    assert!(repo.check_patch(&patch).is_err());
+    assert!(repo.apply_patch(&patch).is_err());
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("app.py"))?,
+        "print('changed')\n"
+    );
+    Ok(())
+}